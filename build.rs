@@ -20,7 +20,7 @@ fn main() {
             let file_name = path.file_name()?.to_str()?.to_string();
             let stem = path.file_stem()?.to_str()?.to_string();
 
-            let excluded = matches!(file_name.as_str(), "mod.rs" | "types.rs" | "callsign.rs");
+            let excluded = matches!(file_name.as_str(), "mod.rs" | "types.rs" | "callsign.rs" | "custom.rs" | "sections.rs");
             if excluded {
                 return None;
             }
@@ -45,7 +45,7 @@ fn main() {
     output.push_str("    vec![\n");
     for module in &contest_modules {
         output.push_str(&format!(
-            "        ContestDescriptor {{ id: {0}::CONTEST_ID, display_name: {0}::DISPLAY_NAME, factory: {0}::make_contest }},\n",
+            "        ContestDescriptor {{ id: {0}::CONTEST_ID, display_name: {0}::DISPLAY_NAME, factory: std::sync::Arc::new({0}::make_contest) }},\n",
             module
         ));
     }
@@ -0,0 +1,183 @@
+// Achievements earned over all sessions, kept next to the settings and shown
+// in the Trophies window
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::config::AppSettings;
+use crate::contest::sections;
+use crate::i18n::{tr, tr_args};
+use crate::stats::QsoRecord;
+
+/// QSOs for the first milestone
+const HUNDRED_QSOS: u64 = 100;
+/// Caller speed to copy for the speed achievement
+const FAST_WPM: u8 = 40;
+/// Stations calling at once for the pileup achievement
+const DEEP_PILEUP: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Log 100 QSOs
+    HundredQsos,
+    /// Log every ARRL and RAC section correctly
+    CleanSweep,
+    /// Log a caller at 40 WPM or faster correctly
+    FortyWpm,
+    /// Log a QSO correctly with ten or more stations calling
+    DeepPileup,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::HundredQsos,
+        Achievement::CleanSweep,
+        Achievement::FortyWpm,
+        Achievement::DeepPileup,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Achievement::HundredQsos => tr("First 100 QSOs"),
+            Achievement::CleanSweep => tr("Clean Sweep"),
+            Achievement::FortyWpm => tr("Speed Copy"),
+            Achievement::DeepPileup => tr("Pileup Survivor"),
+        }
+        .to_string()
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Achievement::HundredQsos => tr("Log 100 QSOs"),
+            Achievement::CleanSweep => tr("Log every ARRL and RAC section correctly"),
+            Achievement::FortyWpm => tr("Copy a caller at 40 WPM or faster"),
+            Achievement::DeepPileup => tr("Log a QSO right with 10 or more stations calling"),
+        }
+        .to_string()
+    }
+}
+
+/// What has been earned, and the running totals the rest are counted from
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    /// Each achievement earned, with the local date it came
+    pub unlocked: BTreeMap<Achievement, String>,
+    /// QSOs logged over all sessions
+    pub total_qsos: u64,
+    /// Sections logged correctly over all sessions
+    pub sections: BTreeSet<String>,
+}
+
+impl Achievements {
+    /// Count a logged QSO, with the section it was logged with when that
+    /// was copied right; returns what it unlocked
+    pub fn on_qso(&mut self, qso: &QsoRecord, section: Option<&str>) -> Vec<Achievement> {
+        self.total_qsos += 1;
+        if let Some(section) = section.filter(|s| sections::is_section(s)) {
+            self.sections.insert(section.trim().to_uppercase());
+        }
+        let correct = qso.is_correct();
+        let earned: Vec<Achievement> = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| !self.unlocked.contains_key(achievement))
+            .filter(|achievement| match achievement {
+                Achievement::HundredQsos => self.total_qsos >= HUNDRED_QSOS,
+                Achievement::CleanSweep => self.sections.len() >= sections::all_sections().count(),
+                Achievement::FortyWpm => correct && qso.station_wpm >= FAST_WPM,
+                Achievement::DeepPileup => correct && qso.pileup_size.unwrap_or(0) >= DEEP_PILEUP,
+            })
+            .collect();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        for achievement in &earned {
+            self.unlocked.insert(*achievement, today.clone());
+        }
+        earned
+    }
+
+    /// How far along a locked achievement is, for those counted up over time
+    pub fn progress(&self, achievement: Achievement) -> Option<String> {
+        match achievement {
+            Achievement::HundredQsos => Some(tr_args(
+                "{}/{} QSOs",
+                &[&self.total_qsos.to_string(), &HUNDRED_QSOS.to_string()],
+            )),
+            Achievement::CleanSweep => Some(tr_args(
+                "{}/{} sections",
+                &[
+                    &self.sections.len().to_string(),
+                    &sections::all_sections().count().to_string(),
+                ],
+            )),
+            Achievement::FortyWpm | Achievement::DeepPileup => None,
+        }
+    }
+}
+
+/// The achievements file sits next to the settings file
+fn achievements_path() -> PathBuf {
+    AppSettings::config_path().with_file_name("achievements.json")
+}
+
+/// Saved achievements, or none yet
+pub fn load() -> Achievements {
+    std::fs::read_to_string(achievements_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(achievements: &Achievements) -> Result<(), String> {
+    let path = achievements_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(achievements).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qso(station_wpm: u8, pileup_size: usize, correct: bool) -> QsoRecord {
+        QsoRecord {
+            callsign_correct: correct,
+            exchange_correct: true,
+            station_wpm,
+            pileup_size: Some(pileup_size),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn achievements_unlock_once() {
+        let mut achievements = Achievements::default();
+        assert!(achievements
+            .on_qso(&qso(38, 3, true), Some("ct"))
+            .is_empty());
+        assert_eq!(
+            achievements.on_qso(&qso(42, 12, true), None),
+            [Achievement::FortyWpm, Achievement::DeepPileup]
+        );
+        // Busted, or already earned: nothing new
+        assert!(achievements.on_qso(&qso(45, 15, false), None).is_empty());
+        assert!(achievements.on_qso(&qso(45, 15, true), None).is_empty());
+
+        for _ in 5..HUNDRED_QSOS {
+            achievements.on_qso(&qso(25, 1, true), Some("DX"));
+        }
+        assert_eq!(
+            achievements.on_qso(&qso(25, 1, true), None),
+            [Achievement::HundredQsos]
+        );
+        assert_eq!(achievements.sections.len(), 1);
+        assert!(achievements.sections.contains("CT"));
+
+        for section in sections::all_sections() {
+            achievements.on_qso(&qso(25, 1, true), Some(section));
+        }
+        assert!(achievements.unlocked.contains_key(&Achievement::CleanSweep));
+    }
+}
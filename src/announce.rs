@@ -0,0 +1,118 @@
+// Status announcements for screen readers and speech
+
+use std::process::{Command, Stdio};
+
+/// One spoken/announced status change
+#[derive(Clone, Debug)]
+pub struct Announcement {
+    pub text: String,
+    /// Counts up with each announcement, so repeating the same text still
+    /// reads as a new message
+    pub seq: u64,
+}
+
+/// Collects status changes (station calling, exchange received, QSO logged)
+/// so the UI can expose them to a screen reader, and optionally hands each
+/// one to a user-chosen speech or notification command.
+#[derive(Default)]
+pub struct Announcer {
+    latest: Option<Announcement>,
+}
+
+impl Announcer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Announce a status change. `speech_command` is run with the text as its
+    /// last argument; leave it empty to only update the screen reader text.
+    pub fn announce(&mut self, text: impl Into<String>, speech_command: &str) {
+        let text = text.into();
+        if !speech_command.trim().is_empty() {
+            speak(speech_command, &text);
+        }
+        let seq = self.latest.as_ref().map(|last| last.seq + 1).unwrap_or(0);
+        self.latest = Some(Announcement { text, seq });
+    }
+
+    pub fn latest(&self) -> Option<&Announcement> {
+        self.latest.as_ref()
+    }
+}
+
+/// Split a command line into program and arguments, keeping quoted
+/// arguments together ("say -v Alex" or "notify-send 'Contest Trainer'")
+fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for ch in command.chars() {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            (None, c) => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Run the speech command in the background; failures are ignored so a
+/// missing program never gets in the way of operating
+fn speak(command: &str, text: &str) {
+    let parts = split_command(command);
+    let Some((program, args)) = parts.split_first() else {
+        return;
+    };
+    let child = Command::new(program)
+        .args(args)
+        .arg(text)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        // Reap the process once it finishes
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_keeps_quoted_arguments() {
+        assert_eq!(
+            split_command("espeak-ng -s 220"),
+            ["espeak-ng", "-s", "220"]
+        );
+        assert_eq!(
+            split_command("notify-send 'Contest Trainer'"),
+            ["notify-send", "Contest Trainer"]
+        );
+        assert!(split_command("   ").is_empty());
+    }
+
+    #[test]
+    fn latest_announcement_replaces_previous() {
+        let mut announcer = Announcer::new();
+        assert!(announcer.latest().is_none());
+        announcer.announce("Station calling", "");
+        announcer.announce("Exchange received", "");
+        announcer.announce("Exchange received", "");
+        let latest = announcer.latest().unwrap();
+        assert_eq!(latest.text, "Exchange received");
+        assert_eq!(latest.seq, 2);
+    }
+}
@@ -0,0 +1,129 @@
+// Receiver AGC: rides the gain on loud signals and static crashes
+
+use super::meter::smoothing_coefficient;
+use crate::config::AgcMode;
+
+/// Envelope level the AGC tries to hold the receiver output at
+const AGC_TARGET: f32 = 0.5;
+/// Most gain the AGC adds when the band is quiet
+const MAX_GAIN: f32 = 2.0;
+/// Most the AGC can turn the gain down
+const MIN_GAIN: f32 = 0.1;
+/// Attack time (ms): how quickly gain drops when a strong signal arrives
+const ATTACK_MS: f32 = 2.0;
+
+impl AgcMode {
+    /// Time (ms) for the gain to recover after a strong signal goes away
+    fn decay_ms(&self) -> Option<f32> {
+        match self {
+            AgcMode::Off => None,
+            AgcMode::Fast => Some(100.0),
+            AgcMode::Slow => Some(800.0),
+        }
+    }
+}
+
+pub struct Agc {
+    envelope: f32,
+    attack: f32,
+    /// None when the AGC is off
+    decay: Option<f32>,
+    sample_rate: u32,
+}
+
+impl Agc {
+    pub fn new(sample_rate: u32, mode: AgcMode) -> Self {
+        let mut agc = Self {
+            envelope: AGC_TARGET,
+            attack: smoothing_coefficient(sample_rate, ATTACK_MS),
+            decay: None,
+            sample_rate,
+        };
+        agc.set_mode(mode);
+        agc
+    }
+
+    pub fn set_mode(&mut self, mode: AgcMode) {
+        self.decay = mode
+            .decay_ms()
+            .map(|ms| smoothing_coefficient(self.sample_rate, ms));
+    }
+
+    /// Apply gain control in place to a stereo pair. Both channels get the
+    /// same gain, taken from the louder side, so panned signals stay put.
+    /// Signals arrive already faded by QSB, so slow fades are partly evened
+    /// out while quick crashes still pump.
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let Some(decay) = self.decay else {
+            return;
+        };
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let magnitude = l.abs().max(r.abs());
+            let coef = if magnitude > self.envelope {
+                self.attack
+            } else {
+                decay
+            };
+            self.envelope += (magnitude - self.envelope) * coef;
+            let gain = (AGC_TARGET / self.envelope.max(1e-6)).clamp(MIN_GAIN, MAX_GAIN);
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 2.0 * std::f32::consts::PI * 600.0 / 8000.0).sin())
+            .collect()
+    }
+
+    /// Run mono audio through the AGC (same signal on both channels)
+    fn process_mono(agc: &mut Agc, samples: &mut [f32]) {
+        let mut right = samples.to_vec();
+        agc.process(samples, &mut right);
+        assert_eq!(samples, &right[..]);
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |max, s| max.max(s.abs()))
+    }
+
+    #[test]
+    fn off_leaves_audio_untouched() {
+        let mut agc = Agc::new(8000, AgcMode::Off);
+        let mut samples = tone(1.0, 800);
+        process_mono(&mut agc, &mut samples);
+        assert_eq!(samples, tone(1.0, 800));
+    }
+
+    #[test]
+    fn loud_and_weak_signals_are_evened_out() {
+        let mut agc = Agc::new(8000, AgcMode::Fast);
+        let mut loud = tone(1.0, 4000);
+        process_mono(&mut agc, &mut loud);
+        assert!(peak(&loud[3000..]) < 0.7);
+
+        let mut agc = Agc::new(8000, AgcMode::Fast);
+        let mut weak = tone(0.2, 4000);
+        process_mono(&mut agc, &mut weak);
+        assert!(peak(&weak[3000..]) > 0.3);
+    }
+
+    #[test]
+    fn slow_decay_recovers_later_than_fast() {
+        let crash_then_weak = |mode| {
+            let mut agc = Agc::new(8000, mode);
+            let mut crash = tone(1.5, 400);
+            process_mono(&mut agc, &mut crash);
+            let mut after = tone(0.2, 800);
+            process_mono(&mut agc, &mut after);
+            peak(&after[600..])
+        };
+        assert!(crash_then_weak(AgcMode::Slow) < crash_then_weak(AgcMode::Fast));
+    }
+}
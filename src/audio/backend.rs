@@ -0,0 +1,302 @@
+// Where the mixer's audio goes: the sound card, nowhere (for running
+// without one), or back to the caller block by block for headless runs
+// and tests. The app talks to whichever is open through `AudioBackend`.
+
+use crossbeam_channel::{Receiver, Sender};
+use std::time::Instant;
+
+use super::engine::AudioEngine;
+use super::mixer::Mixer;
+use super::spectrum::spectrum_db;
+use crate::config::{AudioBackendKind, AudioSettings};
+use crate::messages::{AudioCommand, AudioEvent};
+
+/// Samples the silent output renders at a time
+const SILENT_BLOCK_FRAMES: usize = 512;
+/// The silent output doesn't catch up on more than this much missed time
+/// (ms), as after the machine sleeps
+const SILENT_MAX_CATCH_UP_MS: u64 = 1000;
+
+pub trait AudioBackend {
+    /// Hand queued commands to the mixer, and render whatever is due on
+    /// backends without a device clock (call this from the main thread
+    /// periodically)
+    fn process_commands(&mut self);
+
+    /// Current receiver envelope level for the S-meter
+    fn rx_level(&self) -> f32;
+
+    /// Spectrum of the recent receiver audio in dB, `bins` columns from
+    /// `low_hz` to `high_hz`
+    fn rx_spectrum(&self, low_hz: f32, high_hz: f32, bins: usize) -> Vec<f32>;
+
+    /// Frames per callback last asked for (0 = backend default)
+    fn buffer_frames(&self) -> u32 {
+        0
+    }
+
+    /// Reopen the output with a new buffer size; backends without a device
+    /// have nothing to reopen
+    fn set_buffer_frames(&mut self, _frames: u32) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// How the output callback is keeping up; None without a device
+    fn telemetry(&self) -> Option<AudioTelemetry> {
+        None
+    }
+}
+
+/// How the sound card's callback is keeping up, counted since the output
+/// was opened
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AudioTelemetry {
+    pub callbacks: u64,
+    /// Callbacks late enough for the device to run dry, plus stream errors
+    pub underruns: u32,
+    /// Time the last callback took as a share of the audio it produced
+    pub load: f32,
+    /// Highest load seen
+    pub peak_load: f32,
+    /// Callbacks that had to hold completion events back for a later one
+    /// because the app's queue was full
+    pub delayed_events: u32,
+}
+
+/// Whether this build can play through JACK
+pub const JACK_AVAILABLE: bool = cfg!(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+));
+
+/// Open the chosen backend
+pub fn open(
+    kind: AudioBackendKind,
+    cmd_rx: Receiver<AudioCommand>,
+    event_tx: Sender<AudioEvent>,
+    settings: AudioSettings,
+) -> Result<Box<dyn AudioBackend>, String> {
+    match kind {
+        AudioBackendKind::SoundCard => {
+            AudioEngine::new(cpal::default_host(), cmd_rx, event_tx, settings)
+                .map(|engine| Box::new(engine) as Box<dyn AudioBackend>)
+                .map_err(|e| e.to_string())
+        }
+        AudioBackendKind::Silent => Ok(Box::new(SilentOutput::new(cmd_rx, event_tx, settings))),
+        AudioBackendKind::Jack => {
+            let host = jack_host()?;
+            AudioEngine::new(host, cmd_rx, event_tx, settings)
+                .map(|engine| Box::new(engine) as Box<dyn AudioBackend>)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// The JACK host, if the server is running
+#[cfg(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+))]
+fn jack_host() -> Result<cpal::Host, String> {
+    cpal::host_from_id(cpal::HostId::Jack).map_err(|e| format!("JACK is not running: {}", e))
+}
+
+#[cfg(not(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+)))]
+fn jack_host() -> Result<cpal::Host, String> {
+    Err("this build has no JACK support (build with --features jack)".to_string())
+}
+
+/// A mixer rendered on demand: each `render` call applies the queued
+/// commands, fills one block and reports what finished in it
+pub struct OfflineRenderer {
+    mixer: Mixer,
+    cmd_rx: Receiver<AudioCommand>,
+    event_tx: Sender<AudioEvent>,
+}
+
+impl OfflineRenderer {
+    pub fn new(
+        cmd_rx: Receiver<AudioCommand>,
+        event_tx: Sender<AudioEvent>,
+        settings: AudioSettings,
+    ) -> Self {
+        Self {
+            mixer: Mixer::new(settings.sample_rate, settings),
+            cmd_rx,
+            event_tx,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.mixer.settings.sample_rate
+    }
+
+    /// Render the next `left.len()` frames
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        self.process_commands();
+        self.mixer.fill_buffer(left, right);
+        let event_tx = &self.event_tx;
+        self.mixer.completions.report(|event| {
+            // Nobody left to tell once the app has gone
+            let _ = event_tx.send(event);
+            true
+        });
+    }
+}
+
+impl AudioBackend for OfflineRenderer {
+    fn process_commands(&mut self) {
+        while let Ok(cmd) = self.cmd_rx.try_recv() {
+            self.mixer.handle_command(cmd);
+        }
+    }
+
+    fn rx_level(&self) -> f32 {
+        self.mixer.rx_meter.level()
+    }
+
+    fn rx_spectrum(&self, low_hz: f32, high_hz: f32, bins: usize) -> Vec<f32> {
+        spectrum_db(
+            &self.mixer.rx_tap.snapshot(),
+            self.sample_rate(),
+            low_hz,
+            high_hz,
+            bins,
+        )
+    }
+}
+
+/// Renders in step with the wall clock and discards the audio, so
+/// messages still take as long as they would on air
+pub struct SilentOutput {
+    renderer: OfflineRenderer,
+    started: Instant,
+    frames_rendered: u64,
+}
+
+impl SilentOutput {
+    pub fn new(
+        cmd_rx: Receiver<AudioCommand>,
+        event_tx: Sender<AudioEvent>,
+        settings: AudioSettings,
+    ) -> Self {
+        Self {
+            renderer: OfflineRenderer::new(cmd_rx, event_tx, settings),
+            started: Instant::now(),
+            frames_rendered: 0,
+        }
+    }
+
+    /// Render blocks until the audio has caught up with the clock
+    fn catch_up(&mut self) {
+        let sample_rate = u64::from(self.renderer.sample_rate());
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        let due = elapsed_ms * sample_rate / 1000;
+        let max_behind = SILENT_MAX_CATCH_UP_MS * sample_rate / 1000;
+        if due > self.frames_rendered + max_behind {
+            self.frames_rendered = due - max_behind;
+        }
+        let mut left = [0.0f32; SILENT_BLOCK_FRAMES];
+        let mut right = [0.0f32; SILENT_BLOCK_FRAMES];
+        while self.frames_rendered + SILENT_BLOCK_FRAMES as u64 <= due {
+            self.renderer.render(&mut left, &mut right);
+            self.frames_rendered += SILENT_BLOCK_FRAMES as u64;
+        }
+    }
+}
+
+impl AudioBackend for SilentOutput {
+    fn process_commands(&mut self) {
+        self.renderer.process_commands();
+        self.catch_up();
+    }
+
+    fn rx_level(&self) -> f32 {
+        self.renderer.rx_level()
+    }
+
+    fn rx_spectrum(&self, low_hz: f32, high_hz: f32, bins: usize) -> Vec<f32> {
+        self.renderer.rx_spectrum(low_hz, high_hz, bins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+
+    use crate::messages::{MessageSegment, MessageSegmentType};
+
+    #[test]
+    fn offline_renderer_reports_the_end_of_a_message() {
+        let (cmd_tx, cmd_rx) = unbounded();
+        let (event_tx, event_rx) = unbounded();
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            ..AudioSettings::default()
+        };
+        let mut renderer = OfflineRenderer::new(cmd_rx, event_tx, settings);
+        cmd_tx
+            .send(AudioCommand::PlayUserMessageSegmented {
+                segments: vec![MessageSegment {
+                    content: "E".to_string(),
+                    segment_type: MessageSegmentType::TheirCallsign,
+                }],
+                wpm: 30,
+            })
+            .unwrap();
+
+        // One dit at 30 WPM is 40 ms; half a second is plenty
+        let mut left = [0.0f32; 400];
+        let mut right = [0.0f32; 400];
+        let mut events = Vec::new();
+        for _ in 0..10 {
+            renderer.render(&mut left, &mut right);
+            events.extend(event_rx.try_iter());
+        }
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AudioEvent::UserSegmentComplete(MessageSegmentType::TheirCallsign),
+                AudioEvent::UserMessageComplete
+            ]
+        ));
+    }
+
+    #[test]
+    fn jack_needs_a_build_with_the_feature() {
+        if JACK_AVAILABLE {
+            return;
+        }
+        let (_cmd_tx, cmd_rx) = unbounded();
+        let (event_tx, _event_rx) = unbounded();
+        match open(
+            AudioBackendKind::Jack,
+            cmd_rx,
+            event_tx,
+            AudioSettings::default(),
+        ) {
+            Ok(_) => panic!("opened JACK without the feature"),
+            Err(e) => assert!(e.contains("--features jack")),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// Alert chimes for the UI sounds bus: two short notes, well above any CW
+// pitch, with soft edges so they don't click
+
+use crate::messages::UiSound;
+
+/// Length of each note (ms)
+const NOTE_MS: f32 = 110.0;
+/// Peak level before the UI sounds volume
+const LEVEL: f32 = 0.5;
+
+pub struct Chime {
+    notes: [f32; 2],
+    sample_rate: f32,
+    note_samples: usize,
+    position: usize,
+}
+
+impl Chime {
+    pub fn new(sound: UiSound, sample_rate: u32) -> Self {
+        let notes = match sound {
+            UiSound::Milestone => [1319.0, 1760.0],
+            UiSound::Warning => [1175.0, 880.0],
+        };
+        let sample_rate = sample_rate as f32;
+        Self {
+            notes,
+            sample_rate,
+            note_samples: (sample_rate * NOTE_MS / 1000.0) as usize,
+            position: 0,
+        }
+    }
+
+    /// The next sample, or None once both notes have played
+    pub fn next_sample(&mut self) -> Option<f32> {
+        let note = self.position / self.note_samples.max(1);
+        let frequency = *self.notes.get(note)?;
+        let within = (self.position % self.note_samples.max(1)) as f32;
+        // A half-sine swell over each note
+        let envelope = (std::f32::consts::PI * within / self.note_samples as f32).sin();
+        let phase = std::f32::consts::TAU * frequency * self.position as f32 / self.sample_rate;
+        self.position += 1;
+        Some(phase.sin() * envelope * LEVEL)
+    }
+}
@@ -1,150 +1,488 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use crossbeam_channel::{Receiver, Sender, TryRecvError};
-use std::sync::{Arc, Mutex};
+use crossbeam_channel::{Receiver, Sender};
+use rtrb::{Consumer, Producer, RingBuffer};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::mixer::Mixer;
+use super::backend::{AudioBackend, AudioTelemetry};
+use super::meter::LevelMeter;
+use super::mixer::{prepare_command, Mixer, MixerCommand, Retired, MAX_BLOCK_FRAMES};
+use super::spectrum::{spectrum_db, SampleTap};
 use crate::config::AudioSettings;
 use crate::messages::{AudioCommand, AudioEvent};
 
+/// A stream that couldn't be opened, and the callback state if it came back
+type OpenError = (
+    Box<dyn std::error::Error + Send + Sync>,
+    Option<Box<CallbackState>>,
+);
+
+/// A gap between callbacks longer than this many buffer lengths means the
+/// device ran dry before we refilled it
+const UNDERRUN_GAP_FACTOR: f64 = 2.0;
+/// Built commands the callback can have waiting; more wait on our side
+const MIXER_QUEUE_LEN: usize = 64;
+/// Receiver audio (seconds) the callback can pass back between frames
+const RX_FEED_SECS: f32 = 0.5;
+/// Completion events the callback can pass back between frames; more wait
+/// in the mixer
+const EVENT_QUEUE_LEN: usize = 256;
+/// Finished stations and messages the callback can pass back to be freed
+const RETIRED_QUEUE_LEN: usize = 256;
+
+/// Counters the output callback keeps without taking a lock
+#[derive(Default)]
+struct CallbackCounters {
+    callbacks: AtomicU64,
+    underruns: AtomicU32,
+    /// Last and highest callback load, as f32 bits
+    load: AtomicU32,
+    peak_load: AtomicU32,
+    delayed_events: AtomicU32,
+}
+
+impl CallbackCounters {
+    fn record_callback(&self, load: f32) {
+        self.callbacks.fetch_add(1, Ordering::Relaxed);
+        self.load.store(load.to_bits(), Ordering::Relaxed);
+        // Non-negative floats order the same as their bits
+        self.peak_load.fetch_max(load.to_bits(), Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.callbacks.store(0, Ordering::Relaxed);
+        self.underruns.store(0, Ordering::Relaxed);
+        self.load.store(0, Ordering::Relaxed);
+        self.peak_load.store(0, Ordering::Relaxed);
+        self.delayed_events.store(0, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> AudioTelemetry {
+        AudioTelemetry {
+            callbacks: self.callbacks.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            load: f32::from_bits(self.load.load(Ordering::Relaxed)),
+            peak_load: f32::from_bits(self.peak_load.load(Ordering::Relaxed)),
+            delayed_events: self.delayed_events.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The mixer and its ends of the queues, owned outright by whichever
+/// stream is open. Commands come in built, and events, receiver audio and
+/// what the mixer is done with go out through lock-free queues, so the
+/// callback never waits on the app.
+struct CallbackState {
+    mixer: Mixer,
+    commands: Consumer<MixerCommand>,
+    events: Producer<AudioEvent>,
+    /// Mixing buffers, `MAX_BLOCK_FRAMES` long
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+/// The callback's hold on the state. When the stream closes it goes back
+/// over `home`, for the next stream to take over.
+struct CallbackSlot {
+    state: Option<CallbackState>,
+    home: Producer<CallbackState>,
+}
+
+impl Drop for CallbackSlot {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            let _ = self.home.push(state);
+        }
+    }
+}
+
+/// Output to a cpal host's default device: the sound card, or a JACK server
 pub struct AudioEngine {
-    mixer: Arc<Mutex<Mixer>>,
+    counters: Arc<CallbackCounters>,
+    /// Where the callback state comes back when a stream closes
+    home: Consumer<CallbackState>,
+    /// The callback state after a stream failed to open, for the next try
+    parked: Option<CallbackState>,
     cmd_rx: Receiver<AudioCommand>,
-    _stream: cpal::Stream,
+    event_tx: Sender<AudioEvent>,
+    events: Consumer<AudioEvent>,
+    retired: Consumer<Retired>,
+    /// The mixer's settings as of the last command built, with the
+    /// device's sample rate
+    settings: AudioSettings,
+    commands: Producer<MixerCommand>,
+    /// A built command waiting for room in the callback's queue; nothing
+    /// more is taken off the channel until it goes
+    pending: Option<MixerCommand>,
+    rx_samples: Consumer<f32>,
+    rx_tap: SampleTap,
+    rx_meter: LevelMeter,
+    device: cpal::Device,
+    sample_format: cpal::SampleFormat,
+    config: cpal::StreamConfig,
+    /// Requested frames per callback (0 = backend default)
+    buffer_frames: u32,
+    /// Kept alive for playback; None only while being reopened
+    _stream: Option<cpal::Stream>,
 }
 
 impl AudioEngine {
     pub fn new(
+        host: cpal::Host,
         cmd_rx: Receiver<AudioCommand>,
         event_tx: Sender<AudioEvent>,
         settings: AudioSettings,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let host = cpal::default_host();
         let device = host
             .default_output_device()
             .ok_or("No audio output device found")?;
 
         let supported_config = device.default_output_config()?;
         let sample_rate = supported_config.sample_rate().0;
+        let sample_format = supported_config.sample_format();
+        let mut config: cpal::StreamConfig = supported_config.into();
+        config.buffer_size = buffer_size(settings.buffer_frames);
 
         // Update settings with actual sample rate
         let mut settings = settings;
         settings.sample_rate = sample_rate;
 
-        let mixer = Arc::new(Mutex::new(Mixer::new(sample_rate, settings.clone())));
-        let mixer_for_callback = Arc::clone(&mixer);
-        let event_tx_for_callback = event_tx.clone();
-
-        let stream = match supported_config.sample_format() {
-            cpal::SampleFormat::F32 => Self::build_stream::<f32>(
-                &device,
-                &supported_config.into(),
-                mixer_for_callback,
-                event_tx_for_callback,
-            )?,
-            cpal::SampleFormat::I16 => Self::build_stream::<i16>(
-                &device,
-                &supported_config.into(),
-                mixer_for_callback,
-                event_tx_for_callback,
-            )?,
-            cpal::SampleFormat::U16 => Self::build_stream::<u16>(
-                &device,
-                &supported_config.into(),
-                mixer_for_callback,
-                event_tx_for_callback,
-            )?,
-            _ => return Err("Unsupported sample format".into()),
+        let (commands, command_rx) = RingBuffer::new(MIXER_QUEUE_LEN);
+        let (rx_feed, rx_samples) = RingBuffer::new((sample_rate as f32 * RX_FEED_SECS) as usize);
+        let (events_tx, events) = RingBuffer::new(EVENT_QUEUE_LEN);
+        let (retired_tx, retired) = RingBuffer::new(RETIRED_QUEUE_LEN);
+        let mut mixer = Mixer::new(sample_rate, settings.clone());
+        mixer.rx_feed = Some(rx_feed);
+        mixer.retired = Some(retired_tx);
+        let state = CallbackState {
+            mixer,
+            commands: command_rx,
+            events: events_tx,
+            left: vec![0.0; MAX_BLOCK_FRAMES],
+            right: vec![0.0; MAX_BLOCK_FRAMES],
         };
+        let counters = Arc::new(CallbackCounters::default());
 
-        stream.play()?;
+        let (stream, home) =
+            match Self::open_stream(&device, sample_format, &config, state, &counters) {
+                Ok(opened) => opened,
+                // Not every backend accepts every buffer size; fall back to the default
+                Err((_, Some(state))) if settings.buffer_frames > 0 => {
+                    config.buffer_size = cpal::BufferSize::Default;
+                    Self::open_stream(&device, sample_format, &config, *state, &counters)
+                        .map_err(|(e, _)| e)?
+                }
+                Err((e, _)) => return Err(e),
+            };
 
         Ok(Self {
-            mixer,
+            counters,
+            home,
+            parked: None,
             cmd_rx,
-            _stream: stream,
+            event_tx,
+            events,
+            retired,
+            commands,
+            pending: None,
+            rx_samples,
+            rx_tap: SampleTap::new(),
+            rx_meter: LevelMeter::new(sample_rate),
+            device,
+            sample_format,
+            config,
+            buffer_frames: settings.buffer_frames,
+            settings,
+            _stream: Some(stream),
         })
     }
 
+    /// Open a stream on the callback state: parked after a failed open,
+    /// or back from the stream that just closed
+    fn reopen(&mut self, config: &cpal::StreamConfig) -> Result<(), String> {
+        let state = self
+            .parked
+            .take()
+            .or_else(|| self.home.pop().ok())
+            .ok_or("The previous audio stream is still closing")?;
+        match Self::open_stream(
+            &self.device,
+            self.sample_format,
+            config,
+            state,
+            &self.counters,
+        ) {
+            Ok((stream, home)) => {
+                self._stream = Some(stream);
+                self.home = home;
+                Ok(())
+            }
+            Err((e, state)) => {
+                self.parked = state.map(|state| *state);
+                Err(format!("Failed to open audio stream: {}", e))
+            }
+        }
+    }
+
+    /// Open a stream that takes over `state`. Returns the stream and where
+    /// the state comes back once it closes; if it can't be opened, the
+    /// state comes back with the error.
+    fn open_stream(
+        device: &cpal::Device,
+        sample_format: cpal::SampleFormat,
+        config: &cpal::StreamConfig,
+        state: CallbackState,
+        counters: &Arc<CallbackCounters>,
+    ) -> Result<(cpal::Stream, Consumer<CallbackState>), OpenError> {
+        let (home_tx, mut home) = RingBuffer::new(1);
+        let slot = CallbackSlot {
+            state: Some(state),
+            home: home_tx,
+        };
+        let counters = Arc::clone(counters);
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => Self::build_stream::<f32>(device, config, slot, counters),
+            cpal::SampleFormat::I16 => Self::build_stream::<i16>(device, config, slot, counters),
+            cpal::SampleFormat::U16 => Self::build_stream::<u16>(device, config, slot, counters),
+            _ => {
+                drop(slot);
+                return Err((
+                    "Unsupported sample format".into(),
+                    home.pop().ok().map(Box::new),
+                ));
+            }
+        };
+        let opened = stream.map_err(Into::into).and_then(|stream| {
+            stream.play()?;
+            Ok(stream)
+        });
+        match opened {
+            Ok(stream) => Ok((stream, home)),
+            // The stream has been dropped, and the state with it sent home
+            Err(e) => Err((e, home.pop().ok().map(Box::new))),
+        }
+    }
+
     fn build_stream<T>(
         device: &cpal::Device,
         config: &cpal::StreamConfig,
-        mixer: Arc<Mutex<Mixer>>,
-        event_tx: Sender<AudioEvent>,
+        mut slot: CallbackSlot,
+        counters: Arc<CallbackCounters>,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: cpal::SizedSample + cpal::FromSample<f32>,
     {
         let channels = config.channels as usize;
+        let sample_rate = config.sample_rate.0;
+        let error_counters = Arc::clone(&counters);
+        let mut last_callback: Option<(Instant, usize)> = None;
 
         device.build_output_stream(
             config,
             move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
-                // Create a mono buffer
                 let num_frames = data.len() / channels;
-                let mut mono_buffer = vec![0.0f32; num_frames];
-
-                // Fill the mono buffer
-                let (completed_stations, user_completed, completed_segments) = {
-                    let mut mixer = mixer.lock().unwrap();
-                    mixer.fill_buffer(&mut mono_buffer)
-                };
 
-                // Convert to output format (duplicate mono to all channels)
-                for (frame_idx, frame) in data.chunks_mut(channels).enumerate() {
-                    let sample = mono_buffer.get(frame_idx).copied().unwrap_or(0.0);
-                    let converted: T = T::from_sample(sample);
-                    for channel_sample in frame.iter_mut() {
-                        *channel_sample = converted;
+                let now = Instant::now();
+                if let Some((last, last_frames)) = last_callback {
+                    if is_underrun(now - last, last_frames, sample_rate) {
+                        counters.underruns.fetch_add(1, Ordering::Relaxed);
                     }
                 }
+                last_callback = Some((now, num_frames));
 
-                // Send completion events
-                for station_id in completed_stations {
-                    let _ = event_tx.try_send(AudioEvent::StationComplete(station_id));
+                let Some(state) = &mut slot.state else {
+                    data.fill(T::from_sample(0.0));
+                    return;
+                };
+                while let Ok(cmd) = state.commands.pop() {
+                    state.mixer.apply(cmd);
                 }
-                // Send segment completion events before user message complete
-                for segment_type in completed_segments {
-                    let _ = event_tx.try_send(AudioEvent::UserSegmentComplete(segment_type));
+
+                // Mix into the buffers set aside for it, a block at a time
+                for frames in data.chunks_mut(MAX_BLOCK_FRAMES * channels) {
+                    let block = frames.len() / channels;
+                    let (left, right) = (&mut state.left[..block], &mut state.right[..block]);
+                    state.mixer.fill_buffer(left, right);
+
+                    // Convert to output format: left/right on the first two
+                    // channels, a mono mix on a single-channel device or any
+                    // extra channels
+                    for (frame, (&l, &r)) in frames
+                        .chunks_mut(channels)
+                        .zip(left.iter().zip(right.iter()))
+                    {
+                        for (channel, channel_sample) in frame.iter_mut().enumerate() {
+                            let sample = match (channels, channel) {
+                                (1, _) => (l + r) * 0.5,
+                                (_, 0) => l,
+                                (_, 1) => r,
+                                _ => (l + r) * 0.5,
+                            };
+                            *channel_sample = T::from_sample(sample);
+                        }
+                    }
                 }
-                if user_completed {
-                    let _ = event_tx.try_send(AudioEvent::UserMessageComplete);
+
+                let events = &mut state.events;
+                state
+                    .mixer
+                    .completions
+                    .report(|event| events.push(event).is_ok());
+                if !state.mixer.completions.is_empty() {
+                    counters.delayed_events.fetch_add(1, Ordering::Relaxed);
                 }
+                counters.record_callback(callback_load(now.elapsed(), num_frames, sample_rate));
             },
-            |err| {
+            move |err| {
                 #[cfg(debug_assertions)]
                 eprintln!("Audio stream error: {}", err);
                 let _ = err;
+                error_counters.underruns.fetch_add(1, Ordering::Relaxed);
             },
             None,
         )
     }
+}
 
-    /// Process pending commands (call this from the main thread periodically)
-    pub fn process_commands(&self) {
+impl AudioBackend for AudioEngine {
+    /// Build queued commands here, off the output callback, and hand them
+    /// over in order; then pass on what finished, free what the callback is
+    /// done with, and take the receiver audio it has played for the meter
+    /// and the waterfall
+    fn process_commands(&mut self) {
         loop {
-            match self.cmd_rx.try_recv() {
-                Ok(cmd) => {
-                    let mut mixer = self.mixer.lock().unwrap();
-                    match cmd {
-                        AudioCommand::StartStation(params) => {
-                            // Generate the message the station will send (their callsign)
-                            let message = params.callsign.clone();
-                            mixer.add_station(&params, &message);
-                        }
-                        AudioCommand::PlayUserMessageSegmented { segments, wpm } => {
-                            mixer.play_user_message_segmented(&segments, wpm);
-                        }
-                        AudioCommand::UpdateSettings(settings) => {
-                            mixer.update_settings(settings);
-                        }
-                        AudioCommand::StopAll => {
-                            mixer.clear_all();
+            let cmd = match self.pending.take() {
+                Some(cmd) => cmd,
+                None => match self.cmd_rx.try_recv() {
+                    Ok(cmd) => {
+                        let cmd = prepare_command(cmd, &self.settings);
+                        if let MixerCommand::UpdateSettings(settings) = &cmd {
+                            self.settings = settings.clone();
                         }
+                        cmd
                     }
-                }
-                Err(TryRecvError::Empty) => break,
-                Err(TryRecvError::Disconnected) => break,
+                    Err(_) => break,
+                },
+            };
+            if let Err(rtrb::PushError::Full(cmd)) = self.commands.push(cmd) {
+                self.pending = Some(cmd);
+                break;
+            }
+        }
+
+        while let Ok(event) = self.events.pop() {
+            let _ = self.event_tx.send(event);
+        }
+        while let Ok(retired) = self.retired.pop() {
+            drop(retired);
+        }
+
+        let available = self.rx_samples.slots();
+        if let Ok(chunk) = self.rx_samples.read_chunk(available) {
+            let (first, second) = chunk.as_slices();
+            for samples in [first, second] {
+                self.rx_tap.push(samples);
+                self.rx_meter.process(samples);
             }
+            chunk.commit_all();
         }
     }
+
+    fn rx_level(&self) -> f32 {
+        self.rx_meter.level()
+    }
+
+    fn rx_spectrum(&self, low_hz: f32, high_hz: f32, bins: usize) -> Vec<f32> {
+        spectrum_db(
+            &self.rx_tap.snapshot(),
+            self.settings.sample_rate,
+            low_hz,
+            high_hz,
+            bins,
+        )
+    }
+
+    fn buffer_frames(&self) -> u32 {
+        self.buffer_frames
+    }
+
+    /// If the device rejects the size, the previous stream is restored
+    fn set_buffer_frames(&mut self, frames: u32) -> Result<(), String> {
+        self.buffer_frames = frames;
+        let mut config = self.config.clone();
+        config.buffer_size = buffer_size(frames);
+        // Close the old stream first so the device is free to reopen
+        self._stream = None;
+        let result = self.reopen(&config);
+        if result.is_ok() {
+            self.config = config;
+            self.counters.reset();
+        } else {
+            let previous = self.config.clone();
+            let _ = self.reopen(&previous);
+        }
+        result
+    }
+
+    fn telemetry(&self) -> Option<AudioTelemetry> {
+        Some(self.counters.snapshot())
+    }
+}
+
+fn buffer_size(frames: u32) -> cpal::BufferSize {
+    if frames == 0 {
+        cpal::BufferSize::Default
+    } else {
+        cpal::BufferSize::Fixed(frames)
+    }
+}
+
+/// Whether the time since the previous callback was long enough for the
+/// device to have played out that callback's buffer and gone silent
+fn is_underrun(gap: Duration, previous_frames: usize, sample_rate: u32) -> bool {
+    if previous_frames == 0 || sample_rate == 0 {
+        return false;
+    }
+    let buffer_secs = previous_frames as f64 / sample_rate as f64;
+    gap.as_secs_f64() > buffer_secs * UNDERRUN_GAP_FACTOR
+}
+
+/// Time a callback took as a share of the audio it produced; above 1.0
+/// the device plays out faster than we fill it
+fn callback_load(took: Duration, frames: usize, sample_rate: u32) -> f32 {
+    if frames == 0 || sample_rate == 0 {
+        return 0.0;
+    }
+    let buffer_secs = frames as f64 / sample_rate as f64;
+    (took.as_secs_f64() / buffer_secs) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_callbacks_count_as_underruns() {
+        // 480 frames at 48 kHz is a 10 ms buffer
+        assert!(!is_underrun(Duration::from_millis(10), 480, 48000));
+        assert!(!is_underrun(Duration::from_millis(15), 480, 48000));
+        assert!(is_underrun(Duration::from_millis(25), 480, 48000));
+        assert!(!is_underrun(Duration::from_millis(25), 0, 48000));
+    }
+
+    #[test]
+    fn load_is_callback_time_over_buffer_time() {
+        // 480 frames at 48 kHz is a 10 ms buffer
+        assert_eq!(callback_load(Duration::from_millis(1), 480, 48000), 0.1);
+        assert_eq!(callback_load(Duration::from_millis(1), 0, 48000), 0.0);
+
+        let counters = CallbackCounters::default();
+        counters.record_callback(0.4);
+        counters.record_callback(0.1);
+        let telemetry = counters.snapshot();
+        assert_eq!(telemetry.callbacks, 2);
+        assert_eq!(telemetry.load, 0.1);
+        assert_eq!(telemetry.peak_load, 0.4);
+    }
 }
@@ -0,0 +1,163 @@
+// Receiver level metering for the S-meter, and signal-to-noise estimates
+
+use crate::config::QsbSettings;
+
+/// Envelope level treated as S9 (roughly a caller at half amplitude)
+const S9_LEVEL: f32 = 0.5;
+/// dB per S unit below S9
+const DB_PER_S_UNIT: f32 = 6.0;
+
+/// RMS of the band noise at level 1.0 through a 500 Hz filter (measured)
+const NOISE_RMS_500HZ: f32 = 0.029;
+/// SNR reported when there is no noise to speak of
+pub const MAX_SNR_DB: f32 = 40.0;
+
+/// Peak envelope follower with S-meter style ballistics:
+/// quick to rise, slow to fall back
+pub struct LevelMeter {
+    level: f32,
+    attack: f32,
+    release: f32,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            level: 0.0,
+            attack: smoothing_coefficient(sample_rate, 5.0),
+            release: smoothing_coefficient(sample_rate, 300.0),
+        }
+    }
+
+    /// Feed received audio through the meter
+    pub fn process(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let magnitude = sample.abs();
+            let coef = if magnitude > self.level {
+                self.attack
+            } else {
+                self.release
+            };
+            self.level += (magnitude - self.level) * coef;
+        }
+    }
+
+    /// Current envelope level (linear, 0.0 and up)
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+/// Per-sample smoothing factor for a given time constant
+pub fn smoothing_coefficient(sample_rate: u32, time_ms: f32) -> f32 {
+    1.0 - (-1000.0 / (time_ms * sample_rate as f32)).exp()
+}
+
+/// Reading on the S-meter scale: S0-S9, then dB over S9
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SReading {
+    /// S units (0.0 to 9.0)
+    pub s_units: f32,
+    /// dB above S9 (0.0 when at or below S9)
+    pub db_over_s9: f32,
+}
+
+impl SReading {
+    pub fn from_level(level: f32) -> Self {
+        let db = 20.0 * (level.max(1e-6) / S9_LEVEL).log10();
+        if db > 0.0 {
+            Self {
+                s_units: 9.0,
+                db_over_s9: db,
+            }
+        } else {
+            Self {
+                s_units: (9.0 + db / DB_PER_S_UNIT).max(0.0),
+                db_over_s9: 0.0,
+            }
+        }
+    }
+
+    /// Text like "S7" or "S9+10"
+    pub fn label(&self) -> String {
+        if self.db_over_s9 >= 1.0 {
+            format!("S9+{:.0}", self.db_over_s9)
+        } else {
+            format!("S{:.0}", self.s_units.round())
+        }
+    }
+}
+
+/// Approximate SNR (dB) of a caller at `amplitude` in a receive filter
+/// `bandwidth_hz` wide: the caller's RMS over the band noise's. QSB counts
+/// at its average fade; crashes and QRN are left out.
+pub fn approx_snr_db(
+    amplitude: f32,
+    noise_level: f32,
+    bandwidth_hz: f32,
+    qsb: &QsbSettings,
+) -> f32 {
+    let fade = if qsb.enabled {
+        1.0 - qsb.depth / 2.0
+    } else {
+        1.0
+    };
+    let signal_rms = amplitude * fade / std::f32::consts::SQRT_2;
+    // Noise power grows with the filter width
+    let noise_rms = noise_level * NOISE_RMS_500HZ * (bandwidth_hz.max(1.0) / 500.0).sqrt();
+    if noise_rms <= 0.0 || signal_rms <= 0.0 {
+        return if signal_rms > 0.0 {
+            MAX_SNR_DB
+        } else {
+            -MAX_SNR_DB
+        };
+    }
+    (20.0 * (signal_rms / noise_rms).log10()).clamp(-MAX_SNR_DB, MAX_SNR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s_meter_scale() {
+        assert_eq!(SReading::from_level(S9_LEVEL).label(), "S9");
+        // Each halving of amplitude is one S unit (6 dB)
+        assert_eq!(SReading::from_level(S9_LEVEL / 4.0).label(), "S7");
+        assert_eq!(SReading::from_level(S9_LEVEL * 2.0).label(), "S9+6");
+        assert_eq!(SReading::from_level(0.0).label(), "S0");
+    }
+
+    #[test]
+    fn meter_rises_fast_and_falls_slowly() {
+        let sample_rate = 8000;
+        let mut meter = LevelMeter::new(sample_rate);
+        // 50 ms of full-scale signal
+        meter.process(&vec![1.0; 400]);
+        assert!(meter.level() > 0.9);
+        // 50 ms of silence only brings it part of the way down
+        meter.process(&vec![0.0; 400]);
+        assert!(meter.level() > 0.5);
+    }
+
+    #[test]
+    fn snr_follows_signal_noise_and_filter() {
+        let steady = QsbSettings {
+            enabled: false,
+            ..QsbSettings::default()
+        };
+        let snr = approx_snr_db(0.05, 0.25, 500.0, &steady);
+        assert!((snr - 13.8).abs() < 0.1, "{}", snr);
+        // Half the signal is 6 dB down; a filter four times as wide lets in
+        // four times the noise power
+        assert!((approx_snr_db(0.025, 0.25, 500.0, &steady) - (snr - 6.02)).abs() < 0.01);
+        assert!((approx_snr_db(0.05, 0.25, 2000.0, &steady) - (snr - 6.02)).abs() < 0.01);
+        let fading = QsbSettings {
+            enabled: true,
+            depth: 1.0,
+            ..QsbSettings::default()
+        };
+        assert!(approx_snr_db(0.05, 0.25, 500.0, &fading) < snr);
+        assert_eq!(approx_snr_db(0.05, 0.0, 500.0, &steady), MAX_SNR_DB);
+    }
+}
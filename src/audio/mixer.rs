@@ -1,8 +1,26 @@
+use super::agc::Agc;
+use super::chime::Chime;
+use super::meter::{smoothing_coefficient, LevelMeter};
 use super::morse::{text_to_morse, MorseElement, MorseTimer, ToneGenerator};
 use super::noise::NoiseGenerator;
-use crate::config::{AudioSettings, QsbSettings};
-use crate::messages::{MessageSegment, MessageSegmentType, StationId, StationParams};
-use rand::Rng;
+use super::spectrum::SampleTap;
+use crate::config::{AudioSettings, QsbSettings, SidetoneSettings, SidetoneTimbre};
+use crate::messages::{
+    AudioCommand, AudioEvent, MessageSegment, MessageSegmentType, StationId, StationParams,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rtrb::Producer;
+
+/// Samples between QSB gain updates when fading a block; the fade changes
+/// over seconds, so it is ramped in between
+const QSB_STEP: usize = 32;
+/// Frames mixed at a time; longer buffers are filled in blocks this size,
+/// so the scratch space is set aside once
+pub const MAX_BLOCK_FRAMES: usize = 4096;
+/// Stations room is set aside for, along with their completions, so a
+/// device callback doesn't allocate in any ordinary pileup
+const STATION_CAPACITY: usize = 64;
 
 /// QSB (fading) oscillator that produces natural-sounding signal fading
 /// Uses multiple layered sine waves with different periods for a non-repetitive pattern
@@ -50,12 +68,8 @@ impl QsbOscillator {
         }
     }
 
-    /// Get the current QSB amplitude factor (0.0 to 1.0) and advance the oscillator
-    pub fn next_factor(&mut self) -> f32 {
-        if !self.enabled {
-            return 1.0;
-        }
-
+    /// The QSB amplitude factor (0.0 to 1.0) at the current phase
+    fn factor(&self) -> f32 {
         // Combine three sine waves with different weights
         // This creates a complex, non-repeating pattern
         let combined =
@@ -65,18 +79,30 @@ impl QsbOscillator {
         let normalized = (combined + 1.0) / 2.0;
 
         // Apply depth: at depth=0, always return 1.0; at depth=1, return full range
-        let factor = 1.0 - self.depth + self.depth * normalized;
+        1.0 - self.depth + self.depth * normalized
+    }
 
-        // Advance all phases
+    fn advance(&mut self, samples: usize) {
         for i in 0..3 {
-            self.phases[i] += self.velocities[i];
-            // Keep phases in reasonable range to avoid floating point issues
-            if self.phases[i] > 2.0 * std::f32::consts::PI {
-                self.phases[i] -= 2.0 * std::f32::consts::PI;
-            }
+            // Kept within one turn to avoid floating point issues
+            self.phases[i] = (self.phases[i] + self.velocities[i] * samples as f32)
+                .rem_euclid(2.0 * std::f32::consts::PI);
         }
+    }
 
-        factor
+    /// Fade `samples` and advance the oscillator past them
+    pub fn apply(&mut self, samples: &mut [f32]) {
+        if !self.enabled {
+            return;
+        }
+        for chunk in samples.chunks_mut(QSB_STEP) {
+            let start = self.factor();
+            self.advance(chunk.len());
+            let step = (self.factor() - start) / chunk.len() as f32;
+            for (i, sample) in chunk.iter_mut().enumerate() {
+                *sample *= start + step * i as f32;
+            }
+        }
     }
 
     /// Update settings (called when user changes QSB settings)
@@ -101,6 +127,11 @@ pub struct ActiveStation {
     pub qsb: QsbOscillator,
     /// Samples remaining before this station starts transmitting (reaction delay)
     pub delay_samples_remaining: usize,
+    /// Offset from the receive pitch in Hz (sets the pan in spatial audio mode)
+    pub frequency_offset_hz: f32,
+    /// Keying envelope for each tone length sent, worked out the first
+    /// time that length comes up
+    envelopes: Vec<(usize, Vec<f32>)>,
 }
 
 impl ActiveStation {
@@ -138,56 +169,98 @@ impl ActiveStation {
             completed: false,
             qsb: QsbOscillator::new(sample_rate, qsb_settings),
             delay_samples_remaining: delay_samples,
+            frequency_offset_hz: params.frequency_offset_hz,
+            envelopes: Vec::new(),
         }
     }
 
-    /// Generate the next sample for this station
-    /// Returns None if the station is done sending
-    pub fn next_sample(&mut self) -> Option<f32> {
-        if self.completed || self.current_element_idx >= self.elements.len() {
-            self.completed = true;
-            return None;
+    /// Send with other timing, such as Farnsworth spacing
+    pub fn with_timer(mut self, timer: MorseTimer) -> Self {
+        if let Some(first) = self.elements.first() {
+            self.samples_in_element = timer.element_samples(*first);
         }
+        self.timer = timer;
+        self
+    }
 
-        // If still in delay period, decrement and return silence
-        if self.delay_samples_remaining > 0 {
-            self.delay_samples_remaining -= 1;
-            return Some(0.0);
+    /// Work out the keying envelope of every tone length in the message
+    /// now, rather than the first time each comes up while rendering
+    pub fn with_envelopes(mut self) -> Self {
+        for index in 0..self.elements.len() {
+            let element = self.elements[index];
+            if element.is_tone() {
+                self.envelope_index(self.timer.element_samples(element));
+            }
         }
+        self
+    }
 
-        let element = self.elements[self.current_element_idx];
-
-        // Get QSB factor (always advances the oscillator to keep fading continuous)
-        let qsb_factor = self.qsb.next_factor();
-
-        let sample = if element.is_tone() {
-            // Generate tone with envelope and QSB
-            let raw = self.tone_generator.next_sample();
-            let envelope = self
-                .tone_generator
-                .envelope(self.samples_elapsed, self.samples_in_element);
-            raw * envelope * self.amplitude * qsb_factor
-        } else {
-            // Silence for gaps - but still advance the tone generator phase
-            // to maintain phase continuity
-            0.0
-        };
+    /// Render the next samples into `out` a run at a time: each element's
+    /// share of the block is a tone times its keying envelope, or silence.
+    /// Returns how many samples were written, fewer than `out.len()` once
+    /// the station has finished.
+    pub fn fill(&mut self, out: &mut [f32]) -> usize {
+        let mut written = 0;
+        while written < out.len() {
+            if self.completed || self.current_element_idx >= self.elements.len() {
+                self.completed = true;
+                break;
+            }
+            let rest = &mut out[written..];
 
-        self.samples_elapsed += 1;
+            // Still in the reaction delay
+            if self.delay_samples_remaining > 0 {
+                let run = self.delay_samples_remaining.min(rest.len());
+                rest[..run].fill(0.0);
+                self.delay_samples_remaining -= run;
+                written += run;
+                continue;
+            }
 
-        // Check if we need to move to next element
-        if self.samples_elapsed >= self.samples_in_element {
-            self.current_element_idx += 1;
-            self.samples_elapsed = 0;
+            let element = self.elements[self.current_element_idx];
+            let run = (self.samples_in_element - self.samples_elapsed).min(rest.len());
+            let samples = &mut rest[..run];
+            if element.is_tone() {
+                self.tone_generator.fill(samples);
+                let index = self.envelope_index(self.samples_in_element);
+                let envelope = &self.envelopes[index].1[self.samples_elapsed..];
+                for (sample, envelope) in samples.iter_mut().zip(envelope) {
+                    *sample *= envelope * self.amplitude;
+                }
+            } else {
+                samples.fill(0.0);
+            }
+            // QSB always advances to keep fading continuous
+            self.qsb.apply(samples);
+            written += run;
 
-            if self.current_element_idx < self.elements.len() {
-                self.samples_in_element = self
-                    .timer
-                    .element_samples(self.elements[self.current_element_idx]);
+            self.samples_elapsed += run;
+            if self.samples_elapsed >= self.samples_in_element {
+                self.current_element_idx += 1;
+                self.samples_elapsed = 0;
+                if self.current_element_idx < self.elements.len() {
+                    self.samples_in_element = self
+                        .timer
+                        .element_samples(self.elements[self.current_element_idx]);
+                }
             }
         }
+        written
+    }
 
-        Some(sample)
+    /// Where in `envelopes` the keying envelope for a tone `total` samples
+    /// long is
+    fn envelope_index(&mut self, total: usize) -> usize {
+        match self.envelopes.iter().position(|(len, _)| *len == total) {
+            Some(index) => index,
+            None => {
+                let shape = (0..total)
+                    .map(|position| self.tone_generator.envelope(position, total))
+                    .collect();
+                self.envelopes.push((total, shape));
+                self.envelopes.len() - 1
+            }
+        }
     }
 
     pub fn is_completed(&self) -> bool {
@@ -205,6 +278,8 @@ pub struct SegmentedUserStation {
     pub tone_generator: ToneGenerator,
     pub timer: MorseTimer,
     pub completed: bool,
+    /// Sidetone level
+    pub level: f32,
     /// Segment boundaries: (element_index_end, segment_type)
     /// Each entry marks where a segment ends (exclusive)
     pub segment_boundaries: Vec<(usize, MessageSegmentType)>,
@@ -213,7 +288,13 @@ pub struct SegmentedUserStation {
 }
 
 impl SegmentedUserStation {
-    pub fn new(segments: &[MessageSegment], wpm: u8, sample_rate: u32, frequency_hz: f32) -> Self {
+    pub fn new(
+        segments: &[MessageSegment],
+        wpm: u8,
+        sample_rate: u32,
+        rx_frequency_hz: f32,
+        sidetone: &SidetoneSettings,
+    ) -> Self {
         let mut all_elements = Vec::new();
         let mut segment_boundaries = Vec::new();
 
@@ -229,7 +310,18 @@ impl SegmentedUserStation {
         }
 
         let timer = MorseTimer::new(sample_rate, wpm);
-        let mut tone_generator = ToneGenerator::new(frequency_hz, sample_rate);
+        let frequency_hz = if sidetone.match_rx_pitch {
+            rx_frequency_hz
+        } else {
+            sidetone.pitch_hz
+        };
+        let harmonics = match sidetone.timbre {
+            SidetoneTimbre::Sine => 0.0,
+            SidetoneTimbre::Rich => 0.4,
+        };
+        let mut tone_generator = ToneGenerator::new(frequency_hz, sample_rate)
+            .with_ramp_ms(sidetone.rise_time_ms)
+            .with_harmonics(harmonics);
         tone_generator.reset_phase();
 
         let samples_in_element = if all_elements.is_empty() {
@@ -246,6 +338,7 @@ impl SegmentedUserStation {
             tone_generator,
             timer,
             completed: false,
+            level: sidetone.level,
             segment_boundaries,
             current_segment_idx: 0,
         }
@@ -266,7 +359,7 @@ impl SegmentedUserStation {
             let envelope = self
                 .tone_generator
                 .envelope(self.samples_elapsed, self.samples_in_element);
-            raw * envelope * 0.8 // User's own signal at consistent level
+            raw * envelope * self.level
         } else {
             0.0
         };
@@ -287,6 +380,13 @@ impl SegmentedUserStation {
         Some(sample)
     }
 
+    /// Whether the next sample is part of a dit or dah
+    pub fn key_down(&self) -> bool {
+        self.elements
+            .get(self.current_element_idx)
+            .is_some_and(|element| element.is_tone())
+    }
+
     /// Check if a segment just completed
     /// Returns the segment type if a segment boundary was just crossed
     pub fn check_segment_completion(&mut self) -> Option<MessageSegmentType> {
@@ -310,21 +410,288 @@ impl SegmentedUserStation {
     }
 }
 
+/// Time for the receiver to fade back in after being muted for our
+/// transmission, so break-in switching doesn't click (ms)
+const RX_GATE_RAMP_MS: f32 = 1.0;
+
+/// Receiver muting while we transmit. With QSK it follows the key, staying
+/// closed for the delay after each element; otherwise it is closed for
+/// the whole message.
+pub struct RxGate {
+    gain: f32,
+    /// Samples left before the receiver opens after key-up
+    hang: usize,
+    ramp: f32,
+}
+
+impl RxGate {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            gain: 1.0,
+            hang: 0,
+            ramp: smoothing_coefficient(sample_rate, RX_GATE_RAMP_MS),
+        }
+    }
+
+    /// Receiver gain for the next sample. `muted` is whether we are keying
+    /// (or transmitting at all, without QSK); the receiver stays muted for
+    /// `delay_samples` after that ends.
+    pub fn next_gain(&mut self, muted: bool, delay_samples: usize) -> f32 {
+        if muted {
+            self.hang = delay_samples;
+        } else {
+            self.hang = self.hang.saturating_sub(1);
+        }
+        if muted || self.hang > 0 {
+            // Like a rig's T/R sequencing, the receiver is cut before the key closes
+            self.gain = 0.0;
+        } else {
+            self.gain += (1.0 - self.gain) * self.ramp;
+        }
+        self.gain
+    }
+}
+
+/// Recorded receiver audio played back alongside the simulated stations
+pub struct RecordingPlayer {
+    pub id: StationId,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    position: usize,
+}
+
+impl RecordingPlayer {
+    pub fn new(id: StationId, left: Vec<f32>, right: Vec<f32>) -> Self {
+        Self {
+            id,
+            left,
+            right,
+            position: 0,
+        }
+    }
+
+    pub fn next_frame(&mut self) -> Option<(f32, f32)> {
+        let frame = (
+            *self.left.get(self.position)?,
+            *self.right.get(self.position)?,
+        );
+        self.position += 1;
+        Some(frame)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.position >= self.left.len().min(self.right.len())
+    }
+}
+
+/// An audio command with everything it plays already built, so the mixer
+/// only has to swap it in (see `prepare_command`)
+pub enum MixerCommand {
+    AddStation(ActiveStation),
+    PlayUserMessage(SegmentedUserStation),
+    PlayRecording(RecordingPlayer),
+    PlayUiSound(Chime),
+    UpdateSettings(AudioSettings),
+    Tune { rit_hz: f32, filter_hz: Option<f32> },
+    StopUserMessage,
+    StopAll,
+}
+
+/// Build what a command plays, with the settings the mixer will be using
+/// when it gets there: a station's morse, timing and keying envelopes, or
+/// the elements of our message. This is the slow part of a command, so
+/// with a sound card it is done away from the output callback.
+pub fn prepare_command(cmd: AudioCommand, settings: &AudioSettings) -> MixerCommand {
+    let station = |params: &StationParams| {
+        ActiveStation::new(
+            params,
+            &params.callsign,
+            settings.sample_rate,
+            settings.tone_frequency_hz,
+            &settings.qsb,
+        )
+    };
+    match cmd {
+        AudioCommand::StartStation(params) => {
+            MixerCommand::AddStation(station(&params).with_envelopes())
+        }
+        AudioCommand::StartFarnsworth {
+            params,
+            effective_wpm,
+        } => MixerCommand::AddStation(
+            station(&params)
+                .with_timer(MorseTimer::farnsworth(
+                    settings.sample_rate,
+                    params.wpm,
+                    effective_wpm,
+                ))
+                .with_envelopes(),
+        ),
+        AudioCommand::PlayUserMessageSegmented { segments, wpm } => {
+            MixerCommand::PlayUserMessage(SegmentedUserStation::new(
+                &segments,
+                wpm,
+                settings.sample_rate,
+                settings.tone_frequency_hz,
+                &settings.sidetone,
+            ))
+        }
+        AudioCommand::PlayRecording { id, left, right } => {
+            MixerCommand::PlayRecording(RecordingPlayer::new(id, left, right))
+        }
+        AudioCommand::PlayUiSound(sound) => {
+            MixerCommand::PlayUiSound(Chime::new(sound, settings.sample_rate))
+        }
+        AudioCommand::UpdateSettings(new_settings) => {
+            MixerCommand::UpdateSettings(AudioSettings {
+                // The output's sample rate doesn't change while it's open
+                sample_rate: settings.sample_rate,
+                ..new_settings
+            })
+        }
+        AudioCommand::Tune { rit_hz, filter_hz } => MixerCommand::Tune { rit_hz, filter_hz },
+        AudioCommand::StopUserMessage => MixerCommand::StopUserMessage,
+        AudioCommand::StopAll => MixerCommand::StopAll,
+    }
+}
+
+/// What the mixer lets go of while it plays: passed back over a queue to
+/// be freed on the app's side, away from the output callback. Nothing is
+/// read back out; each is only dropped.
+#[allow(dead_code)]
+pub enum Retired {
+    Station(ActiveStation),
+    Recording(RecordingPlayer),
+    UserMessage(SegmentedUserStation),
+}
+
+/// What the mixer has finished and not yet reported. Room is set aside
+/// up front, and what the app can't take yet waits here rather than
+/// being lost.
+#[derive(Debug, Default)]
+pub struct Completions {
+    pub stations: Vec<StationId>,
+    /// Segments of our messages, reported ahead of the message they end
+    pub segments: Vec<MessageSegmentType>,
+    /// Our messages that finished
+    pub user_messages: u32,
+}
+
+impl Completions {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            stations: Vec::with_capacity(capacity),
+            segments: Vec::with_capacity(capacity),
+            user_messages: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stations.is_empty() && self.segments.is_empty() && self.user_messages == 0
+    }
+
+    /// Hand the completions over in order for as long as `send` takes
+    /// them: stations, then segments, then our messages. What it turns
+    /// down stays for next time.
+    pub fn report(&mut self, mut send: impl FnMut(AudioEvent) -> bool) {
+        let sent = self
+            .stations
+            .iter()
+            .take_while(|&&id| send(AudioEvent::StationComplete(id)))
+            .count();
+        self.stations.drain(..sent);
+        if !self.stations.is_empty() {
+            return;
+        }
+        let sent = self
+            .segments
+            .iter()
+            .take_while(|&&segment| send(AudioEvent::UserSegmentComplete(segment)))
+            .count();
+        self.segments.drain(..sent);
+        if !self.segments.is_empty() {
+            return;
+        }
+        while self.user_messages > 0 && send(AudioEvent::UserMessageComplete) {
+            self.user_messages -= 1;
+        }
+    }
+}
+
+/// Per-sample working space for one block, set aside with the mixer
+struct Scratch {
+    sidetone: Vec<f32>,
+    key_down: Vec<bool>,
+    rx_gain: Vec<f32>,
+    station: Vec<f32>,
+    mid: Vec<f32>,
+}
+
+impl Scratch {
+    fn new(frames: usize) -> Self {
+        Self {
+            sidetone: vec![0.0; frames],
+            key_down: vec![false; frames],
+            rx_gain: vec![0.0; frames],
+            station: vec![0.0; frames],
+            mid: vec![0.0; frames],
+        }
+    }
+}
+
 /// Mixes multiple audio sources together
 pub struct Mixer {
     pub stations: Vec<ActiveStation>,
+    pub recording: Option<RecordingPlayer>,
     pub segmented_user_station: Option<SegmentedUserStation>,
     pub noise: NoiseGenerator,
     pub settings: AudioSettings,
+    /// Recent receiver audio (noise and stations, no sidetone) for the waterfall
+    pub rx_tap: SampleTap,
+    /// Receiver level for the S-meter
+    pub rx_meter: LevelMeter,
+    pub agc: Agc,
+    pub rx_gate: RxGate,
+    /// Receiver offset from the run frequency (RIT)
+    pub rit_hz: f32,
+    /// Receive filter that callers are heard through, when one is set
+    pub filter_hz: Option<f32>,
+    /// Alert chime on the UI sounds bus
+    pub ui_sound: Option<Chime>,
+    /// Receiver audio passed on to another thread for its meter and
+    /// waterfall, when the mixer runs in a device callback. What doesn't
+    /// fit is left out.
+    pub rx_feed: Option<Producer<f32>>,
+    /// Where finished stations and replaced messages go to be freed, when
+    /// the mixer runs in a device callback
+    pub retired: Option<Producer<Retired>>,
+    /// What finished since it was last reported
+    pub completions: Completions,
+    scratch: Scratch,
+    dither_rng: SmallRng,
 }
 
 impl Mixer {
     pub fn new(sample_rate: u32, settings: AudioSettings) -> Self {
         Self {
-            stations: Vec::new(),
+            stations: Vec::with_capacity(STATION_CAPACITY),
+            recording: None,
             segmented_user_station: None,
             noise: NoiseGenerator::new(sample_rate),
+            agc: Agc::new(sample_rate, settings.agc),
+            rx_gate: RxGate::new(sample_rate),
             settings,
+            rx_tap: SampleTap::new(),
+            rx_meter: LevelMeter::new(sample_rate),
+            rit_hz: 0.0,
+            filter_hz: None,
+            ui_sound: None,
+            rx_feed: None,
+            retired: None,
+            // Our message's segments and the recording come on top
+            completions: Completions::with_capacity(STATION_CAPACITY + 1),
+            scratch: Scratch::new(MAX_BLOCK_FRAMES),
+            dither_rng: SmallRng::from_entropy(),
         }
     }
 
@@ -340,16 +707,6 @@ impl Mixer {
         self.stations.push(station);
     }
 
-    /// Start playing a segmented user message with element-level tracking
-    pub fn play_user_message_segmented(&mut self, segments: &[MessageSegment], wpm: u8) {
-        self.segmented_user_station = Some(SegmentedUserStation::new(
-            segments,
-            wpm,
-            self.settings.sample_rate,
-            self.settings.tone_frequency_hz,
-        ));
-    }
-
     /// Update audio settings
     pub fn update_settings(&mut self, settings: AudioSettings) {
         // Update QSB settings on all active stations
@@ -357,99 +714,625 @@ impl Mixer {
             station.qsb.update_settings(&settings.qsb);
         }
         // Update noise filter to match tone frequency and bandwidth
-        self.noise
-            .update_filter(settings.tone_frequency_hz, settings.noise_bandwidth);
+        self.noise.update_filter(
+            settings.tone_frequency_hz,
+            self.filter_hz.unwrap_or(settings.noise_bandwidth),
+        );
+        self.agc.set_mode(settings.agc);
         self.settings = settings;
     }
 
+    /// Build and apply a command from the app
+    pub fn handle_command(&mut self, cmd: AudioCommand) {
+        let cmd = prepare_command(cmd, &self.settings);
+        self.apply(cmd);
+    }
+
+    /// Apply a command already built by `prepare_command`
+    pub fn apply(&mut self, cmd: MixerCommand) {
+        match cmd {
+            MixerCommand::AddStation(station) => self.stations.push(station),
+            MixerCommand::PlayUserMessage(station) => {
+                if let Some(previous) = self.segmented_user_station.replace(station) {
+                    retire(&mut self.retired, Retired::UserMessage(previous));
+                }
+            }
+            MixerCommand::PlayRecording(recording) => {
+                if let Some(previous) = self.recording.replace(recording) {
+                    retire(&mut self.retired, Retired::Recording(previous));
+                }
+            }
+            MixerCommand::PlayUiSound(chime) => self.ui_sound = Some(chime),
+            MixerCommand::UpdateSettings(settings) => {
+                self.update_settings(settings);
+            }
+            MixerCommand::Tune { rit_hz, filter_hz } => {
+                self.rit_hz = rit_hz;
+                self.filter_hz = filter_hz;
+                self.noise.update_filter(
+                    self.settings.tone_frequency_hz,
+                    filter_hz.unwrap_or(self.settings.noise_bandwidth),
+                );
+            }
+            MixerCommand::StopUserMessage => {
+                self.stop_user_message();
+            }
+            MixerCommand::StopAll => {
+                self.clear_all();
+            }
+        }
+    }
+
+    /// Abort the user's message; segments already sent stay reported
+    pub fn stop_user_message(&mut self) {
+        if let Some(user) = self.segmented_user_station.take() {
+            retire(&mut self.retired, Retired::UserMessage(user));
+        }
+    }
+
     /// Clear all stations
     pub fn clear_all(&mut self) {
-        self.stations.clear();
-        self.segmented_user_station = None;
-    }
-
-    /// Fill a buffer with mixed audio
-    /// Returns: (completed_station_ids, user_completed, completed_segments)
-    pub fn fill_buffer(
-        &mut self,
-        buffer: &mut [f32],
-    ) -> (Vec<StationId>, bool, Vec<MessageSegmentType>) {
-        let mut completed_stations = Vec::new();
-        let mut user_completed = false;
-        let mut completed_segments = Vec::new();
-
-        // Clear buffer
-        for sample in buffer.iter_mut() {
+        for station in self.stations.drain(..) {
+            retire(&mut self.retired, Retired::Station(station));
+        }
+        if let Some(recording) = self.recording.take() {
+            retire(&mut self.retired, Retired::Recording(recording));
+        }
+        self.stop_user_message();
+    }
+
+    /// Fill a stereo pair of buffers with mixed audio. Both channels are
+    /// identical unless spatial audio is on, which pans each caller.
+    /// What finished is added to `completions`.
+    pub fn fill_buffer(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (left, right) in left
+            .chunks_mut(MAX_BLOCK_FRAMES)
+            .zip(right.chunks_mut(MAX_BLOCK_FRAMES))
+        {
+            self.fill_block(left, right);
+        }
+    }
+
+    /// Fill one block of at most `MAX_BLOCK_FRAMES`, working in the
+    /// scratch buffers so nothing is allocated
+    fn fill_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let frames = left.len();
+        let Scratch {
+            sidetone,
+            key_down,
+            rx_gain,
+            station: station_buffer,
+            mid,
+        } = &mut self.scratch;
+        let (sidetone, key_down) = (&mut sidetone[..frames], &mut key_down[..frames]);
+        let (rx_gain, station_buffer) = (&mut rx_gain[..frames], &mut station_buffer[..frames]);
+        let mid = &mut mid[..frames];
+
+        // Clear buffers
+        for sample in left.iter_mut().chain(right.iter_mut()) {
             *sample = 0.0;
         }
 
         let user_tx_active = self.segmented_user_station.is_some();
-        let mute_rx = self.settings.mute_rx_during_tx && user_tx_active;
         let mute_sidetone = self.settings.mute_sidetone_during_tx && user_tx_active;
 
-        // Add noise (optionally muted while user is transmitting)
-        if !mute_rx {
+        // Our own signal first: the receiver gating below follows the key
+        sidetone.fill(0.0);
+        key_down.fill(false);
+        if let Some(user) = &mut self.segmented_user_station {
+            for (sample, down) in sidetone.iter_mut().zip(key_down.iter_mut()) {
+                *down = user.key_down();
+                match user.next_sample() {
+                    Some(user_sample) => *sample = user_sample,
+                    None => break,
+                }
+                // Check for segment completion after each sample
+                if let Some(segment_type) = user.check_segment_completion() {
+                    self.completions.segments.push(segment_type);
+                }
+            }
+            // Final check for any remaining segment completions
+            while let Some(segment_type) = user.check_segment_completion() {
+                self.completions.segments.push(segment_type);
+            }
+            if user.is_completed() {
+                self.completions.user_messages += 1;
+                if let Some(user) = self.segmented_user_station.take() {
+                    retire(&mut self.retired, Retired::UserMessage(user));
+                }
+            }
+        }
+
+        // Receiver gain per sample: muted for the whole message, or with
+        // QSK only while the key is down plus the delay
+        let qsk = &self.settings.qsk;
+        let delay_samples = if qsk.enabled {
+            (qsk.delay_ms.max(0.0) * self.settings.sample_rate as f32 / 1000.0) as usize
+        } else {
+            0
+        };
+        let message_muted = self.settings.mute_rx_during_tx && user_tx_active;
+        for (gain, &down) in rx_gain.iter_mut().zip(key_down.iter()) {
+            let muted = if qsk.enabled { down } else { message_muted };
+            *gain = self.rx_gate.next_gain(muted, delay_samples);
+        }
+
+        // Add noise (muted along with the receiver), centered. A recording
+        // brings its own band noise, so ours stays out while one plays.
+        if self.recording.is_none() {
             self.noise
-                .fill_buffer(buffer, self.settings.noise_level, &self.settings.noise);
+                .fill_buffer(left, self.settings.noise_level, &self.settings.noise);
+            for (sample, gain) in left.iter_mut().zip(rx_gain.iter()) {
+                *sample *= gain;
+            }
+            right.copy_from_slice(left);
         }
 
-        // Mix each calling station
-        for station in &mut self.stations {
-            for sample in buffer.iter_mut() {
-                if let Some(station_sample) = station.next_sample() {
-                    if !mute_rx {
-                        *sample += station_sample;
-                    }
-                } else {
+        if let Some(recording) = &mut self.recording {
+            for ((l, r), gain) in left.iter_mut().zip(right.iter_mut()).zip(rx_gain.iter()) {
+                let Some((rec_l, rec_r)) = recording.next_frame() else {
                     break;
+                };
+                *l += rec_l * gain;
+                *r += rec_r * gain;
+            }
+            if recording.is_completed() {
+                self.completions.stations.push(recording.id);
+                if let Some(recording) = self.recording.take() {
+                    retire(&mut self.retired, Retired::Recording(recording));
                 }
             }
+        }
+
+        // Mix each calling station. Stations keep advancing while RX is muted,
+        // so a caller who starts before our message ends loses the overlap.
+        for station in &mut self.stations {
+            // Where the caller lands in the receiver, as tuned
+            let detune_hz = station.frequency_offset_hz - self.rit_hz;
+            station
+                .tone_generator
+                .set_frequency(self.settings.tone_frequency_hz + detune_hz);
+            let filter_gain = passband_gain(detune_hz, self.filter_hz);
+            let (left_gain, right_gain) = pan_gains(detune_hz, self.settings.spatial_audio);
+            let (left_gain, right_gain) = (left_gain * filter_gain, right_gain * filter_gain);
+            let written = station.fill(station_buffer);
+            for (((l, r), gain), sample) in left
+                .iter_mut()
+                .zip(right.iter_mut())
+                .zip(rx_gain.iter())
+                .zip(&station_buffer[..written])
+            {
+                *l += sample * left_gain * gain;
+                *r += sample * right_gain * gain;
+            }
             if station.is_completed() {
-                completed_stations.push(station.id);
+                self.completions.stations.push(station.id);
             }
         }
 
-        // Remove completed stations
-        self.stations.retain(|s| !s.is_completed());
+        // Remove completed stations, in order
+        let mut index = 0;
+        while index < self.stations.len() {
+            if self.stations[index].is_completed() {
+                let station = self.stations.remove(index);
+                retire(&mut self.retired, Retired::Station(station));
+            } else {
+                index += 1;
+            }
+        }
 
-        // Mix segmented user station if active
-        if let Some(ref mut user) = self.segmented_user_station {
-            for sample in buffer.iter_mut() {
-                if let Some(user_sample) = user.next_sample() {
-                    if !mute_sidetone {
-                        *sample += user_sample;
-                    }
-                } else {
-                    break;
-                }
-                // Check for segment completion after each sample
-                if let Some(segment_type) = user.check_segment_completion() {
-                    completed_segments.push(segment_type);
-                }
+        // Waterfall and S-meter see the signal ahead of the AGC, as on a real rig
+        for ((sample, l), r) in mid.iter_mut().zip(left.iter()).zip(right.iter()) {
+            *sample = (l + r) * 0.5;
+        }
+        self.rx_tap.push(mid);
+        self.rx_meter.process(mid);
+        if let Some(feed) = &mut self.rx_feed {
+            let room = feed.slots().min(mid.len());
+            if let Ok(chunk) = feed.write_chunk_uninit(room) {
+                chunk.fill_from_iter(mid.iter().copied());
             }
-            // Final check for any remaining segment completions
-            while let Some(segment_type) = user.check_segment_completion() {
-                completed_segments.push(segment_type);
+        }
+        self.agc.process(left, right);
+
+        // Sidetone is centered
+        if !mute_sidetone {
+            for ((l, r), sample) in left.iter_mut().zip(right.iter_mut()).zip(sidetone.iter()) {
+                *l += sample;
+                *r += sample;
             }
-            if user.is_completed() {
-                user_completed = true;
-                self.segmented_user_station = None;
+        }
+
+        // Alert chimes on their own bus: after the AGC so they don't pull
+        // the receiver gain down, and only once we've finished sending
+        if !user_tx_active {
+            if let Some(chime) = &mut self.ui_sound {
+                let volume = self.settings.ui_sound_volume;
+                let mut finished = false;
+                for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                    let Some(sample) = chime.next_sample() else {
+                        finished = true;
+                        break;
+                    };
+                    *l += sample * volume;
+                    *r += sample * volume;
+                }
+                if finished {
+                    self.ui_sound = None;
+                }
             }
         }
 
         // Apply master volume, dither, and soft clipping
-        let mut rng = rand::thread_rng();
-        for sample in buffer.iter_mut() {
-            *sample *= self.settings.master_volume;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
             // Add very small triangular dither to prevent audio artifacts
-            let dither = (rng.gen::<f32>() - 0.5) * 0.001;
-            *sample += dither;
-            // Soft clipping using tanh
-            if sample.abs() > 0.8 {
-                *sample = sample.signum() * (0.8 + 0.2 * ((*sample).abs() - 0.8).tanh());
+            let dither = (self.dither_rng.gen::<f32>() - 0.5) * 0.001;
+            for sample in [l, r] {
+                *sample *= self.settings.master_volume;
+                *sample += dither;
+                // Soft clipping using tanh
+                if sample.abs() > 0.8 {
+                    *sample = sample.signum() * (0.8 + 0.2 * ((*sample).abs() - 0.8).tanh());
+                }
+            }
+        }
+    }
+}
+
+/// Hand something the mixer is done with to `retired`, to be freed on the
+/// app's side; with no queue, or no room in it, it is freed here
+fn retire(retired: &mut Option<Producer<Retired>>, item: Retired) {
+    if let Some(retired) = retired {
+        let _ = retired.push(item);
+    }
+}
+
+/// How steeply the receive filter's skirt falls beyond its edge: this far
+/// outside costs 20 dB
+const FILTER_SKIRT_HZ: f32 = 150.0;
+
+/// Level of a caller `detune_hz` from the receive pitch through a filter
+/// `filter_hz` wide: full inside it, falling off steeply outside. No filter
+/// passes every caller.
+pub fn passband_gain(detune_hz: f32, filter_hz: Option<f32>) -> f32 {
+    let Some(filter_hz) = filter_hz else {
+        return 1.0;
+    };
+    let outside_hz = detune_hz.abs() - filter_hz / 2.0;
+    if outside_hz <= 0.0 {
+        1.0
+    } else {
+        10f32.powf(-outside_hz / FILTER_SKIRT_HZ)
+    }
+}
+
+/// Frequency offset (Hz) at which a caller reaches the widest pan position
+const PAN_FULL_OFFSET_HZ: f32 = 300.0;
+/// How far toward one side the widest position is (1.0 would be hard left/right)
+const PAN_WIDTH: f32 = 0.6;
+
+/// Left/right gains for a caller at `offset_hz` from the receive pitch.
+/// Lower callers lean left and higher ones right, with a constant-power pan
+/// law normalized so a centered caller keeps unity gain on both channels.
+pub fn pan_gains(offset_hz: f32, spatial_audio: bool) -> (f32, f32) {
+    if !spatial_audio {
+        return (1.0, 1.0);
+    }
+    let pan = (offset_hz / PAN_FULL_OFFSET_HZ).clamp(-1.0, 1.0) * PAN_WIDTH;
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+    let (sin, cos) = angle.sin_cos();
+    (
+        cos * std::f32::consts::SQRT_2,
+        sin * std::f32::consts::SQRT_2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::Exchange;
+    use crate::messages::UiSound;
+
+    #[test]
+    fn rx_gate_holds_for_delay_after_key_up() {
+        let mut gate = RxGate::new(8000);
+        assert_eq!(gate.next_gain(true, 40), 0.0);
+        // Still muted just after key-up, open once the delay has passed
+        for _ in 0..30 {
+            assert!(gate.next_gain(false, 40) < 0.01);
+        }
+        for _ in 0..80 {
+            gate.next_gain(false, 40);
+        }
+        assert!(gate.next_gain(false, 40) > 0.99);
+    }
+
+    #[test]
+    fn station_renders_its_delay_and_message_in_blocks() {
+        let params = StationParams {
+            id: StationId(1),
+            callsign: "TE".to_string(),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 0.0,
+            wpm: 20,
+            amplitude: 0.5,
+            reaction_delay_ms: 100,
+        };
+        let qsb = QsbSettings {
+            enabled: true,
+            depth: 0.5,
+            rate: 60.0,
+        };
+        let mut station = ActiveStation::new(&params, "TE", 8000, 600.0, &qsb);
+        let timer = MorseTimer::new(8000, 20);
+        let expected = 800
+            + text_to_morse("TE")
+                .iter()
+                .map(|e| timer.element_samples(*e))
+                .sum::<usize>();
+
+        let mut rendered = Vec::new();
+        let mut block = vec![0.0; 300];
+        loop {
+            let written = station.fill(&mut block);
+            rendered.extend_from_slice(&block[..written]);
+            if written < block.len() {
+                break;
             }
         }
+        assert!(station.is_completed());
+        assert_eq!(rendered.len(), expected);
+        assert!(rendered[..800].iter().all(|s| *s == 0.0));
+        assert!(rendered.iter().all(|s| s.abs() <= 0.5));
+        assert!(rendered[800..].iter().any(|s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn commands_are_built_ahead_for_the_mixer_to_swap_in() {
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            ..AudioSettings::default()
+        };
+        let params = StationParams {
+            id: StationId(1),
+            callsign: "TE".to_string(),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 0.0,
+            wpm: 20,
+            amplitude: 0.5,
+            reaction_delay_ms: 0,
+        };
+
+        // A dit and a dah: both envelopes are ready before it plays
+        let MixerCommand::AddStation(station) =
+            prepare_command(AudioCommand::StartStation(params), &settings)
+        else {
+            panic!("expected a station");
+        };
+        assert_eq!(station.envelopes.len(), 2);
+
+        // Settings from the app don't change the output's sample rate
+        let MixerCommand::UpdateSettings(updated) = prepare_command(
+            AudioCommand::UpdateSettings(AudioSettings::default()),
+            &settings,
+        ) else {
+            panic!("expected settings");
+        };
+        assert_eq!(updated.sample_rate, 8000);
+
+        // The receiver audio is passed on as it plays
+        let (feed, mut heard) = rtrb::RingBuffer::new(100);
+        let mut mixer = Mixer::new(8000, settings);
+        mixer.rx_feed = Some(feed);
+        mixer.apply(MixerCommand::AddStation(station));
+        let mut left = vec![0.0; 256];
+        let mut right = vec![0.0; 256];
+        mixer.fill_buffer(&mut left, &mut right);
+        assert_eq!(heard.slots(), 100);
+        assert!(std::iter::from_fn(|| heard.pop().ok()).any(|s| s != 0.0));
+    }
+
+    /// Peak receiver level heard while sending a message over a caller
+    fn rx_peak_while_sending(qsk: bool) -> f32 {
+        let mut settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            master_volume: 1.0,
+            mute_rx_during_tx: true,
+            mute_sidetone_during_tx: true,
+            ..AudioSettings::default()
+        };
+        settings.qsk.enabled = qsk;
+        let mut mixer = Mixer::new(8000, settings);
+        mixer.add_station(
+            &StationParams {
+                id: StationId(1),
+                callsign: "TTTT".to_string(),
+                exchange: Exchange::new(Vec::new()),
+                frequency_offset_hz: 0.0,
+                wpm: 20,
+                amplitude: 0.5,
+                reaction_delay_ms: 0,
+            },
+            "TTTT",
+        );
+        mixer.handle_command(AudioCommand::PlayUserMessageSegmented {
+            segments: vec![MessageSegment {
+                content: "EEEE".to_string(),
+                segment_type: MessageSegmentType::Cq,
+            }],
+            wpm: 20,
+        });
+
+        let mut peak = 0.0f32;
+        let mut left = vec![0.0; 256];
+        let mut right = vec![0.0; 256];
+        while mixer.segmented_user_station.is_some() {
+            mixer.fill_buffer(&mut left, &mut right);
+            if mixer.segmented_user_station.is_some() {
+                peak = left.iter().fold(peak, |max, s| max.max(s.abs()));
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn qsk_hears_caller_between_elements() {
+        assert!(rx_peak_while_sending(false) < 0.01);
+        assert!(rx_peak_while_sending(true) > 0.2);
+    }
+
+    #[test]
+    fn chimes_wait_for_our_message_to_end() {
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            master_volume: 1.0,
+            ui_sound_volume: 1.0,
+            mute_sidetone_during_tx: true,
+            ..AudioSettings::default()
+        };
+        let mut mixer = Mixer::new(8000, settings);
+        mixer.handle_command(AudioCommand::PlayUserMessageSegmented {
+            segments: vec![MessageSegment {
+                content: "E".to_string(),
+                segment_type: MessageSegmentType::Tu,
+            }],
+            wpm: 20,
+        });
+        mixer.handle_command(AudioCommand::PlayUiSound(UiSound::Milestone));
+
+        let mut left = vec![0.0; 256];
+        let mut right = vec![0.0; 256];
+        while mixer.segmented_user_station.is_some() {
+            mixer.fill_buffer(&mut left, &mut right);
+            assert!(left.iter().all(|s| s.abs() < 0.01));
+        }
+        let mut peak = 0.0f32;
+        while mixer.ui_sound.is_some() {
+            mixer.fill_buffer(&mut left, &mut right);
+            peak = left.iter().fold(peak, |max, s| max.max(s.abs()));
+        }
+        assert!(peak > 0.3);
+    }
+
+    #[test]
+    fn recording_plays_through_and_completes() {
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.5,
+            ..AudioSettings::default()
+        };
+        let mut mixer = Mixer::new(8000, settings);
+        mixer.handle_command(AudioCommand::PlayRecording {
+            id: StationId(7),
+            left: vec![0.0; 300],
+            right: vec![0.0; 300],
+        });
+
+        // Silent recording: no synthesized noise underneath it
+        let mut left = vec![0.0; 256];
+        let mut right = vec![0.0; 256];
+        mixer.fill_buffer(&mut left, &mut right);
+        assert!(mixer.completions.is_empty());
+        assert!(left.iter().all(|s| s.abs() < 0.01));
+
+        mixer.fill_buffer(&mut left, &mut right);
+        assert_eq!(mixer.completions.stations, vec![StationId(7)]);
+        assert!(mixer.recording.is_none());
+    }
+
+    #[test]
+    fn finished_audio_goes_back_and_completions_wait_for_room() {
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            ..AudioSettings::default()
+        };
+        let (retired_tx, retired) = rtrb::RingBuffer::new(8);
+        let mut mixer = Mixer::new(8000, settings);
+        mixer.retired = Some(retired_tx);
+        for id in 1..=2 {
+            let params = StationParams {
+                id: StationId(id),
+                callsign: "E".to_string(),
+                exchange: Exchange::new(Vec::new()),
+                frequency_offset_hz: 0.0,
+                wpm: 30,
+                amplitude: 0.5,
+                reaction_delay_ms: 0,
+            };
+            mixer.add_station(&params, "E");
+        }
+        mixer.handle_command(AudioCommand::PlayUserMessageSegmented {
+            segments: vec![MessageSegment {
+                content: "E".to_string(),
+                segment_type: MessageSegmentType::TheirCallsign,
+            }],
+            wpm: 30,
+        });
+
+        // Longer than a block: filled a block at a time
+        let mut left = vec![0.0; MAX_BLOCK_FRAMES + 100];
+        let mut right = vec![0.0; MAX_BLOCK_FRAMES + 100];
+        mixer.fill_buffer(&mut left, &mut right);
+        assert!(mixer.stations.is_empty());
+        assert_eq!(retired.slots(), 3);
+
+        // The app has room for one event, then for the rest
+        let mut events = Vec::new();
+        let mut room = 1;
+        mixer.completions.report(|event| {
+            if room == 0 {
+                return false;
+            }
+            room -= 1;
+            events.push(event);
+            true
+        });
+        assert_eq!(events.len(), 1);
+        mixer.completions.report(|event| {
+            events.push(event);
+            true
+        });
+        assert!(mixer.completions.is_empty());
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AudioEvent::StationComplete(StationId(1)),
+                AudioEvent::StationComplete(StationId(2)),
+                AudioEvent::UserSegmentComplete(MessageSegmentType::TheirCallsign),
+                AudioEvent::UserMessageComplete
+            ]
+        ));
+    }
+
+    #[test]
+    fn filter_skirt_fades_callers_outside_it() {
+        assert_eq!(passband_gain(900.0, None), 1.0);
+        assert_eq!(passband_gain(-125.0, Some(250.0)), 1.0);
+        assert!((passband_gain(275.0, Some(250.0)) - 0.1).abs() < 1e-6);
+        assert!(passband_gain(-900.0, Some(250.0)) < 1e-4);
+    }
+
+    #[test]
+    fn pan_follows_frequency_offset() {
+        assert_eq!(pan_gains(-200.0, false), (1.0, 1.0));
+
+        let (left, right) = pan_gains(0.0, true);
+        assert!((left - 1.0).abs() < 1e-6 && (right - 1.0).abs() < 1e-6);
+
+        // Lower callers lean left, higher callers lean right, by equal amounts
+        let (low_left, low_right) = pan_gains(-150.0, true);
+        let (high_left, high_right) = pan_gains(150.0, true);
+        assert!(low_left > low_right);
+        assert!(high_right > high_left);
+        assert!((low_left - high_right).abs() < 1e-6);
 
-        (completed_stations, user_completed, completed_segments)
+        // Never panned hard to one side, and capped beyond the full offset
+        let (far_left, far_right) = pan_gains(-1000.0, true);
+        assert!(far_right > 0.1);
+        assert_eq!((far_left, far_right), pan_gains(-PAN_FULL_OFFSET_HZ, true));
     }
 }
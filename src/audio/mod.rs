@@ -1,6 +1,13 @@
+pub mod agc;
+pub mod backend;
+pub mod chime;
 pub mod engine;
+pub mod meter;
 pub mod mixer;
 pub mod morse;
 pub mod noise;
+pub mod render;
+pub mod spectrum;
+pub mod wav;
 
-pub use engine::AudioEngine;
+pub use backend::AudioBackend;
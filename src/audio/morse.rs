@@ -6,6 +6,7 @@ pub enum MorseElement {
     ElementGap, // 1 unit silence (between dit/dah in same character)
     CharGap,    // 3 units silence (between characters)
     WordGap,    // 7 units silence (between words)
+    Carrier,    // 40 units tone (a tune-up)
 }
 
 impl MorseElement {
@@ -17,18 +18,25 @@ impl MorseElement {
             MorseElement::ElementGap => 1,
             MorseElement::CharGap => 3,
             MorseElement::WordGap => 7,
+            MorseElement::Carrier => 40,
         }
     }
 
     /// Returns true if this element produces a tone
     pub fn is_tone(&self) -> bool {
-        matches!(self, MorseElement::Dit | MorseElement::Dah)
+        matches!(
+            self,
+            MorseElement::Dit | MorseElement::Dah | MorseElement::Carrier
+        )
     }
 }
 
 /// Calculates Morse timing based on WPM
 pub struct MorseTimer {
     samples_per_unit: usize,
+    /// Unit for the gaps between characters and words; longer than
+    /// `samples_per_unit` with Farnsworth spacing
+    samples_per_gap_unit: usize,
 }
 
 impl MorseTimer {
@@ -39,12 +47,36 @@ impl MorseTimer {
         let units_per_second = (wpm as f64 * 50.0) / 60.0;
         let samples_per_unit = (sample_rate as f64 / units_per_second) as usize;
 
-        Self { samples_per_unit }
+        Self {
+            samples_per_unit,
+            samples_per_gap_unit: samples_per_unit,
+        }
+    }
+
+    /// Characters at `wpm` with the gaps between them stretched so the
+    /// overall speed is `effective_wpm` (Farnsworth spacing)
+    pub fn farnsworth(sample_rate: u32, wpm: u8, effective_wpm: u8) -> Self {
+        let timer = Self::new(sample_rate, wpm);
+        if effective_wpm == 0 || effective_wpm >= wpm {
+            return timer;
+        }
+        // PARIS has 31 units of characters and 19 of gaps; the gaps take up
+        // whatever time is left of a minute at the effective speed (ARRL)
+        let (c, s) = (wpm as f64, effective_wpm as f64);
+        let gap_seconds = (60.0 * c - 37.2 * s) / (s * c);
+        Self {
+            samples_per_gap_unit: (sample_rate as f64 * gap_seconds / 19.0) as usize,
+            ..timer
+        }
     }
 
     /// Get samples for a given element
     pub fn element_samples(&self, element: MorseElement) -> usize {
-        self.samples_per_unit * element.units() as usize
+        let unit = match element {
+            MorseElement::CharGap | MorseElement::WordGap => self.samples_per_gap_unit,
+            _ => self.samples_per_unit,
+        };
+        unit * element.units() as usize
     }
 }
 
@@ -55,6 +87,8 @@ pub struct ToneGenerator {
     phase: f64,
     // Envelope for click-free keying (in samples)
     ramp_samples: usize,
+    // Amount of 2nd/3rd harmonic added to the sine (0.0 = pure tone)
+    harmonics: f32,
 }
 
 impl ToneGenerator {
@@ -67,12 +101,36 @@ impl ToneGenerator {
             sample_rate: sample_rate as f32,
             phase: 0.0,
             ramp_samples,
+            harmonics: 0.0,
         }
     }
 
+    /// Use a different rise/fall time for the keying envelope
+    pub fn with_ramp_ms(mut self, ramp_ms: f32) -> Self {
+        self.ramp_samples = (self.sample_rate * ramp_ms.max(0.0) / 1000.0) as usize;
+        self
+    }
+
+    /// Mix in harmonics for a buzzier, less pure tone
+    pub fn with_harmonics(mut self, harmonics: f32) -> Self {
+        self.harmonics = harmonics.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Change the pitch without a jump in phase, as when the receiver is
+    /// retuned mid-character
+    pub fn set_frequency(&mut self, frequency_hz: f32) {
+        self.frequency_hz = frequency_hz;
+    }
+
     /// Generate a sample at the current phase
     pub fn next_sample(&mut self) -> f32 {
-        let sample = (self.phase * 2.0 * std::f64::consts::PI).sin() as f32;
+        let angle = self.phase * 2.0 * std::f64::consts::PI;
+        let mut sample = angle.sin() as f32;
+        if self.harmonics > 0.0 {
+            let overtones = 0.5 * (2.0 * angle).sin() as f32 + 0.25 * (3.0 * angle).sin() as f32;
+            sample = (sample + self.harmonics * overtones) / (1.0 + 0.75 * self.harmonics);
+        }
         self.phase += self.frequency_hz as f64 / self.sample_rate as f64;
         if self.phase >= 1.0 {
             self.phase -= 1.0;
@@ -80,9 +138,28 @@ impl ToneGenerator {
         sample
     }
 
+    /// Fill `out` with the tone (without harmonics), carrying on from the
+    /// current phase. Cheaper than `next_sample` in a loop: the phase is
+    /// turned by a fixed step instead of taking a sine for every sample.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        let step = self.frequency_hz as f64 / self.sample_rate as f64;
+        let (mut sin, mut cos) = (self.phase * std::f64::consts::TAU).sin_cos();
+        let (step_sin, step_cos) = (step * std::f64::consts::TAU).sin_cos();
+        for sample in out.iter_mut() {
+            *sample = sin as f32;
+            (sin, cos) = (
+                sin * step_cos + cos * step_sin,
+                cos * step_cos - sin * step_sin,
+            );
+        }
+        self.phase = (self.phase + step * out.len() as f64).rem_euclid(1.0);
+    }
+
     /// Apply raised cosine envelope to avoid clicks
     pub fn envelope(&self, position: usize, total: usize) -> f32 {
-        if position < self.ramp_samples {
+        if self.ramp_samples == 0 {
+            1.0
+        } else if position < self.ramp_samples {
             // Attack: raised cosine ramp up
             0.5 * (1.0 - (std::f32::consts::PI * position as f32 / self.ramp_samples as f32).cos())
         } else if position >= total.saturating_sub(self.ramp_samples) {
@@ -101,9 +178,10 @@ impl ToneGenerator {
     }
 }
 
-/// Convert a character to Morse elements
+/// Convert a character to Morse elements. `~` is not a character but a
+/// steady carrier, the sound of a station tuning up.
 pub fn char_to_morse(ch: char) -> Option<Vec<MorseElement>> {
-    use MorseElement::{Dah, Dit};
+    use MorseElement::{Carrier, Dah, Dit};
 
     let code = match ch.to_ascii_uppercase() {
         'A' => vec![Dit, Dah],
@@ -147,30 +225,80 @@ pub fn char_to_morse(ch: char) -> Option<Vec<MorseElement>> {
         '.' => vec![Dit, Dah, Dit, Dah, Dit, Dah],
         ',' => vec![Dah, Dah, Dit, Dit, Dah, Dah],
         '=' => vec![Dah, Dit, Dit, Dit, Dah], // BT
+        '~' => vec![Carrier],
         _ => return None,
     };
 
     Some(code)
 }
 
-/// Convert text to a sequence of Morse elements
-pub fn text_to_morse(text: &str) -> Vec<MorseElement> {
-    let mut elements = Vec::new();
-    let words: Vec<&str> = text.split_whitespace().collect();
-
-    for (word_idx, word) in words.iter().enumerate() {
-        for (char_idx, ch) in word.chars().enumerate() {
-            if let Some(code) = char_to_morse(ch) {
-                for (elem_idx, &elem) in code.iter().enumerate() {
-                    elements.push(elem);
-                    // Add element gap after each dit/dah except the last in character
-                    if elem_idx < code.len() - 1 {
-                        elements.push(MorseElement::ElementGap);
+/// Split a word into Morse symbols. Letters inside angle brackets form a
+/// prosign (e.g. `<SK>`) and are sent run together without character gaps.
+fn word_symbols(word: &str) -> Vec<Vec<MorseElement>> {
+    let mut symbols = Vec::new();
+    let mut prosign: Option<Vec<MorseElement>> = None;
+
+    for ch in word.chars() {
+        match ch {
+            '<' => prosign = Some(Vec::new()),
+            '>' => {
+                if let Some(code) = prosign.take().filter(|code| !code.is_empty()) {
+                    symbols.push(code);
+                }
+            }
+            _ => {
+                let Some(code) = char_to_morse(ch) else {
+                    continue;
+                };
+                match prosign.as_mut() {
+                    Some(run) => {
+                        if !run.is_empty() {
+                            run.push(MorseElement::ElementGap);
+                        }
+                        push_character(run, &code);
+                    }
+                    None => {
+                        let mut symbol = Vec::new();
+                        push_character(&mut symbol, &code);
+                        symbols.push(symbol);
                     }
                 }
             }
+        }
+    }
+
+    // An unterminated prosign is still sent run together
+    if let Some(code) = prosign.filter(|code| !code.is_empty()) {
+        symbols.push(code);
+    }
+
+    symbols
+}
+
+/// Append one character's dits and dahs with element gaps between them
+fn push_character(elements: &mut Vec<MorseElement>, code: &[MorseElement]) {
+    for (elem_idx, &elem) in code.iter().enumerate() {
+        elements.push(elem);
+        if elem_idx < code.len() - 1 {
+            elements.push(MorseElement::ElementGap);
+        }
+    }
+}
+
+/// Convert text to a sequence of Morse elements
+pub fn text_to_morse(text: &str) -> Vec<MorseElement> {
+    let mut elements = Vec::new();
+    let words: Vec<Vec<Vec<MorseElement>>> = text
+        .split_whitespace()
+        .map(word_symbols)
+        .filter(|symbols| !symbols.is_empty())
+        .collect();
+
+    for (word_idx, symbols) in words.iter().enumerate() {
+        for (symbol_idx, symbol) in symbols.iter().enumerate() {
+            elements.extend_from_slice(symbol);
             // Add character gap after each character except the last in word
-            if char_idx < word.chars().count() - 1 {
+            if symbol_idx < symbols.len() - 1 {
                 elements.push(MorseElement::CharGap);
             }
         }
@@ -183,10 +311,43 @@ pub fn text_to_morse(text: &str) -> Vec<MorseElement> {
     elements
 }
 
+/// How long `text` takes to send at `wpm`, in milliseconds
+pub fn message_duration_ms(text: &str, wpm: u8) -> u64 {
+    let units: u64 = text_to_morse(text).iter().map(|e| e.units() as u64).sum();
+    // One unit (dit) lasts 1200 / wpm milliseconds
+    units * 1200 / wpm.max(1) as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn farnsworth_stretches_only_the_gaps() {
+        let plain = MorseTimer::new(6000, 20);
+        let spaced = MorseTimer::farnsworth(6000, 20, 10);
+        assert_eq!(
+            spaced.element_samples(MorseElement::Dah),
+            plain.element_samples(MorseElement::Dah)
+        );
+        assert!(
+            spaced.element_samples(MorseElement::CharGap)
+                > plain.element_samples(MorseElement::CharGap) * 2
+        );
+        // PARIS plus its word gap still takes one minute / effective WPM
+        let paris: usize = text_to_morse("PARIS")
+            .iter()
+            .chain([&MorseElement::WordGap])
+            .map(|e| spaced.element_samples(*e))
+            .sum();
+        assert!((paris as f64 - 6000.0 * 6.0).abs() < 6000.0 * 0.01);
+        // No slower than the character speed means no stretching
+        assert_eq!(
+            MorseTimer::farnsworth(6000, 20, 25).element_samples(MorseElement::WordGap),
+            plain.element_samples(MorseElement::WordGap)
+        );
+    }
+
     #[test]
     fn test_char_to_morse() {
         use MorseElement::{Dah, Dit};
@@ -203,6 +364,23 @@ mod tests {
         assert!(!elements.is_empty());
     }
 
+    #[test]
+    fn test_prosign_runs_together() {
+        use MorseElement::{Dah, Dit, ElementGap};
+        // <SK> = ...-.- sent as one character
+        assert_eq!(
+            text_to_morse("<SK>"),
+            vec![
+                Dit, ElementGap, Dit, ElementGap, Dit, ElementGap, Dah, ElementGap, Dit,
+                ElementGap, Dah
+            ]
+        );
+        assert!(text_to_morse("SK").contains(&MorseElement::CharGap));
+        // Prosign inside a message keeps normal word spacing around it
+        let tu = text_to_morse("TU <SK>");
+        assert_eq!(tu.len(), text_to_morse("TU").len() + 1 + 11);
+    }
+
     #[test]
     fn test_morse_timer() {
         let timer = MorseTimer::new(44100, 20);
@@ -212,4 +390,46 @@ mod tests {
         assert!(dit_samples > 2000);
         assert!(dit_samples < 3000);
     }
+
+    #[test]
+    fn test_tone_generator_shaping() {
+        // A longer ramp is still rising where the default one is already full
+        let default = ToneGenerator::new(600.0, 8000);
+        let slow = ToneGenerator::new(600.0, 8000).with_ramp_ms(10.0);
+        assert_eq!(default.envelope(40, 800), 1.0);
+        assert!(slow.envelope(40, 800) < 1.0);
+
+        // Harmonics change the waveform but keep it within full scale
+        let mut pure = ToneGenerator::new(600.0, 8000);
+        let mut rich = ToneGenerator::new(600.0, 8000).with_harmonics(1.0);
+        let pure: Vec<f32> = (0..100).map(|_| pure.next_sample()).collect();
+        let rich: Vec<f32> = (0..100).map(|_| rich.next_sample()).collect();
+        assert_ne!(pure, rich);
+        assert!(rich.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn block_tone_matches_sample_by_sample() {
+        let mut single = ToneGenerator::new(650.0, 8000);
+        let mut block = ToneGenerator::new(650.0, 8000);
+        let expected: Vec<f32> = (0..5000).map(|_| single.next_sample()).collect();
+        let mut filled = vec![0.0; 5000];
+        for chunk in filled.chunks_mut(333) {
+            block.fill(chunk);
+        }
+        assert!(expected
+            .iter()
+            .zip(&filled)
+            .all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_message_duration() {
+        // PARIS is 50 units including the trailing word gap, 43 without
+        assert_eq!(message_duration_ms("PARIS", 20), 43 * 60);
+        assert_eq!(message_duration_ms("", 20), 0);
+        // A tune-up carrier is one long tone
+        assert_eq!(text_to_morse("~"), vec![MorseElement::Carrier]);
+        assert_eq!(message_duration_ms("~", 20), 40 * 60);
+    }
 }
@@ -0,0 +1,91 @@
+// Offline rendering of station audio for recordings
+
+use std::path::Path;
+
+use super::mixer::Mixer;
+use super::wav::WavWriter;
+use crate::config::AudioSettings;
+use crate::messages::StationParams;
+
+/// Samples rendered per block (same order of size as a sound card callback)
+const BLOCK_FRAMES: usize = 512;
+/// Longest recording rendered, in case a station never finishes (seconds)
+const MAX_RENDER_SECS: usize = 120;
+
+/// Render stations sending their messages over the band noise, from the
+/// start until `tail_ms` after the last one finishes. Returns left/right audio.
+pub fn render_stations(
+    settings: &AudioSettings,
+    stations: &[(StationParams, String)],
+    tail_ms: u32,
+) -> (Vec<f32>, Vec<f32>) {
+    let sample_rate = settings.sample_rate;
+    let mut mixer = Mixer::new(sample_rate, settings.clone());
+    for (params, message) in stations {
+        mixer.add_station(params, message);
+    }
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut block_left = vec![0.0f32; BLOCK_FRAMES];
+    let mut block_right = vec![0.0f32; BLOCK_FRAMES];
+    let mut remaining = stations.len();
+    let max_frames = sample_rate as usize * MAX_RENDER_SECS;
+    let tail_frames = sample_rate as usize * tail_ms as usize / 1000;
+    let mut finished_at = None;
+
+    while left.len() < max_frames {
+        mixer.fill_buffer(&mut block_left, &mut block_right);
+        left.extend_from_slice(&block_left);
+        right.extend_from_slice(&block_right);
+        remaining = remaining.saturating_sub(mixer.completions.stations.len());
+        mixer.completions.stations.clear();
+        if remaining == 0 {
+            let end = *finished_at.get_or_insert(left.len()) + tail_frames;
+            if left.len() >= end {
+                left.truncate(end);
+                right.truncate(end);
+                break;
+            }
+        }
+    }
+    (left, right)
+}
+
+/// Write rendered audio to a 16-bit stereo WAV file
+pub fn write_wav(path: &Path, sample_rate: u32, left: &[f32], right: &[f32]) -> Result<(), String> {
+    let mut writer = WavWriter::create(path, sample_rate)?;
+    writer.write_frames(left, right)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::Exchange;
+    use crate::messages::StationId;
+
+    #[test]
+    fn renders_until_the_last_station_finishes() {
+        let settings = AudioSettings {
+            sample_rate: 8000,
+            noise_level: 0.0,
+            ..AudioSettings::default()
+        };
+        let params = StationParams {
+            id: StationId(1),
+            callsign: "K1ABC".to_string(),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 0.0,
+            wpm: 30,
+            amplitude: 0.5,
+            reaction_delay_ms: 0,
+        };
+        let (left, right) = render_stations(&settings, &[(params, "E".to_string())], 250);
+        assert_eq!(left.len(), right.len());
+        // One dit at 30 WPM is 40 ms, then the 250 ms tail
+        let secs = left.len() as f32 / 8000.0;
+        assert!(secs > 0.25 && secs < 0.4, "rendered {} s", secs);
+        assert!(left.iter().any(|s| s.abs() > 0.1));
+    }
+}
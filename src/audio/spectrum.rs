@@ -0,0 +1,149 @@
+// Spectrum analysis of the received audio for the waterfall display
+
+use std::f32::consts::PI;
+
+/// Number of samples kept for analysis (power of two for the FFT)
+pub const TAP_SIZE: usize = 4096;
+
+/// Ring buffer holding the most recent received samples
+pub struct SampleTap {
+    samples: Vec<f32>,
+    pos: usize,
+}
+
+impl SampleTap {
+    pub fn new() -> Self {
+        Self {
+            samples: vec![0.0; TAP_SIZE],
+            pos: 0,
+        }
+    }
+
+    /// Append samples, overwriting the oldest
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.samples[self.pos] = sample;
+            self.pos = (self.pos + 1) % TAP_SIZE;
+        }
+    }
+
+    /// Copy of the buffer in time order (oldest first)
+    pub fn snapshot(&self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(TAP_SIZE);
+        out.extend_from_slice(&self.samples[self.pos..]);
+        out.extend_from_slice(&self.samples[..self.pos]);
+        out
+    }
+}
+
+/// Magnitude spectrum in dB between `low_hz` and `high_hz`, resampled to
+/// `bins` evenly spaced columns. `samples.len()` must be a power of two.
+pub fn spectrum_db(
+    samples: &[f32],
+    sample_rate: u32,
+    low_hz: f32,
+    high_hz: f32,
+    bins: usize,
+) -> Vec<f32> {
+    let n = samples.len();
+    if n < 2 || !n.is_power_of_two() || bins == 0 {
+        return vec![f32::NEG_INFINITY; bins];
+    }
+
+    // Hann window keeps leakage from strong signals out of nearby bins
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s * (0.5 - 0.5 * (2.0 * PI * i as f32 / (n - 1) as f32).cos()))
+        .collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    let hz_per_bin = sample_rate as f32 / n as f32;
+    (0..bins)
+        .map(|col| {
+            // Take the strongest FFT bin that falls in this column
+            let col_low = low_hz + (high_hz - low_hz) * col as f32 / bins as f32;
+            let col_high = low_hz + (high_hz - low_hz) * (col + 1) as f32 / bins as f32;
+            let first = (col_low / hz_per_bin).floor() as usize;
+            let last = ((col_high / hz_per_bin).ceil() as usize).max(first + 1);
+            let power = (first..last.min(n / 2))
+                .map(|k| (re[k] * re[k] + im[k] * im[k]) / (n * n) as f32)
+                .fold(0.0f32, f32::max);
+            10.0 * power.max(1e-12).log10()
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tone_peaks_in_its_column() {
+        let sample_rate = 8000;
+        let samples: Vec<f32> = (0..1024)
+            .map(|i| (2.0 * PI * 600.0 * i as f32 / sample_rate as f32).sin())
+            .collect();
+        // 400-800 Hz in 8 columns of 50 Hz: 600 Hz lands in column 4
+        let spectrum = spectrum_db(&samples, sample_rate, 400.0, 800.0, 8);
+        let peak = spectrum
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(peak, 4);
+        assert!(spectrum[4] - spectrum[0] > 30.0);
+    }
+
+    #[test]
+    fn tap_snapshot_is_time_ordered() {
+        let mut tap = SampleTap::new();
+        let ramp: Vec<f32> = (0..TAP_SIZE + 10).map(|i| i as f32).collect();
+        tap.push(&ramp);
+        let snapshot = tap.snapshot();
+        assert_eq!(snapshot.len(), TAP_SIZE);
+        assert_eq!(snapshot[0], 10.0);
+        assert_eq!(snapshot[TAP_SIZE - 1], (TAP_SIZE + 9) as f32);
+    }
+}
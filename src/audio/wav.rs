@@ -0,0 +1,248 @@
+// WAV files: 16-bit PCM output for rendering audio, and input for playing
+// back recordings
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const HEADER_LEN: u32 = 44;
+
+/// Streams stereo frames to a WAV file; the header sizes are filled in by
+/// `finish`
+pub struct WavWriter {
+    out: BufWriter<File>,
+    data_bytes: u32,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = Self {
+            out: BufWriter::new(file),
+            data_bytes: 0,
+        };
+        writer
+            .write_header(sample_rate)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(writer)
+    }
+
+    fn write_header(&mut self, sample_rate: u32) -> std::io::Result<()> {
+        let channels: u16 = 2;
+        let block_align = channels * 2;
+        self.out.write_all(b"RIFF")?;
+        self.out.write_all(&(HEADER_LEN - 8).to_le_bytes())?;
+        self.out.write_all(b"WAVEfmt ")?;
+        self.out.write_all(&16u32.to_le_bytes())?;
+        self.out.write_all(&1u16.to_le_bytes())?; // PCM
+        self.out.write_all(&channels.to_le_bytes())?;
+        self.out.write_all(&sample_rate.to_le_bytes())?;
+        self.out
+            .write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        self.out.write_all(&block_align.to_le_bytes())?;
+        self.out.write_all(&16u16.to_le_bytes())?; // bits per sample
+        self.out.write_all(b"data")?;
+        self.out.write_all(&0u32.to_le_bytes())
+    }
+
+    /// Append left/right frames (samples in -1.0..1.0)
+    pub fn write_frames(&mut self, left: &[f32], right: &[f32]) -> Result<(), String> {
+        for (l, r) in left.iter().zip(right.iter()) {
+            for sample in [l, r] {
+                let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                self.out
+                    .write_all(&value.to_le_bytes())
+                    .map_err(|e| format!("Failed to write audio: {}", e))?;
+            }
+            self.data_bytes += 4;
+        }
+        Ok(())
+    }
+
+    /// Fill in the chunk sizes and flush to disk
+    pub fn finish(mut self) -> Result<(), String> {
+        let result = (|| {
+            self.out.seek(SeekFrom::Start(4))?;
+            self.out
+                .write_all(&(HEADER_LEN - 8 + self.data_bytes).to_le_bytes())?;
+            self.out.seek(SeekFrom::Start(HEADER_LEN as u64 - 4))?;
+            self.out.write_all(&self.data_bytes.to_le_bytes())?;
+            self.out.flush()
+        })();
+        result.map_err(|e| format!("Failed to finish WAV file: {}", e))
+    }
+}
+
+/// Audio read from a WAV file, as left/right samples in -1.0..1.0.
+/// Mono files give the same samples on both channels.
+pub struct WavAudio {
+    pub sample_rate: u32,
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Read a PCM (8, 16, 24 or 32-bit) or 32-bit float WAV file. Channels
+/// past the second are ignored.
+pub fn read_wav(path: &Path) -> Result<WavAudio, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_wav(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse_wav(bytes: &[u8]) -> Result<WavAudio, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a WAV file".to_string());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+
+    // (format, channels, sample rate, bits per sample)
+    let mut format = None;
+    let mut data = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let id = &bytes[at..at + 4];
+        let size = read_u32(at + 4) as usize;
+        let body = at + 8;
+        let end = body.saturating_add(size).min(bytes.len());
+        match id {
+            b"fmt " if end - body >= 16 => {
+                let mut tag = read_u16(body);
+                // Extensible files carry the real format in the sub-format GUID
+                if tag == WAVE_FORMAT_EXTENSIBLE && end - body >= 26 {
+                    tag = read_u16(body + 24);
+                }
+                format = Some((
+                    tag,
+                    read_u16(body + 2),
+                    read_u32(body + 4),
+                    read_u16(body + 14),
+                ));
+            }
+            b"data" => data = Some(&bytes[body..end]),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        at = body.saturating_add(size).saturating_add(size & 1);
+    }
+
+    let (tag, channels, sample_rate, bits) = format.ok_or("no format chunk")?;
+    let data = data.ok_or("no audio data")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("no channels".to_string());
+    }
+    let sample: fn(&[u8]) -> f32 = match (tag, bits) {
+        (WAVE_FORMAT_PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (WAVE_FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (WAVE_FORMAT_PCM, 24) => {
+            |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0
+        }
+        (WAVE_FORMAT_PCM, 32) => {
+            |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+        }
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(format!("unsupported format ({} {}-bit)", tag, bits)),
+    };
+
+    let sample_bytes = bits as usize / 8;
+    let frame_bytes = sample_bytes * channels as usize;
+    let frames = data.len() / frame_bytes;
+    let mut left = Vec::with_capacity(frames);
+    let mut right = Vec::with_capacity(frames);
+    for frame in data.chunks_exact(frame_bytes) {
+        let l = sample(&frame[..sample_bytes]);
+        let r = if channels > 1 {
+            sample(&frame[sample_bytes..2 * sample_bytes])
+        } else {
+            l
+        };
+        left.push(l);
+        right.push(r);
+    }
+    Ok(WavAudio {
+        sample_rate,
+        left,
+        right,
+    })
+}
+
+/// Linear resampling to `to_rate`; good enough for CW, which sits well
+/// below either Nyquist frequency
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let frac = (position - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_sizes_match_the_audio_written() {
+        let path = std::env::temp_dir().join(format!("wav_test_{}.wav", std::process::id()));
+        let mut writer = WavWriter::create(&path, 8000).unwrap();
+        writer
+            .write_frames(&[0.0, 0.5, -0.5], &[0.0, 1.0, -1.0])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        assert_eq!(bytes.len(), 44 + 3 * 4);
+        assert_eq!(read_u32(4), 36 + 12);
+        assert_eq!(read_u32(40), 12);
+        assert_eq!(read_u32(24), 8000);
+        assert_eq!(i16::from_le_bytes([bytes[50], bytes[51]]), i16::MAX);
+    }
+
+    #[test]
+    fn written_files_read_back() {
+        let path = std::env::temp_dir().join(format!("wav_read_test_{}.wav", std::process::id()));
+        let mut writer = WavWriter::create(&path, 8000).unwrap();
+        writer
+            .write_frames(&[0.0, 0.5, -0.5], &[0.25, 1.0, -1.0])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let audio = read_wav(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(audio.sample_rate, 8000);
+        assert_eq!(audio.left.len(), 3);
+        for (read, written) in audio
+            .left
+            .iter()
+            .chain(&audio.right)
+            .zip([0.0, 0.5, -0.5, 0.25, 1.0, -1.0])
+        {
+            assert!((read - written).abs() < 0.001, "{} vs {}", read, written);
+        }
+    }
+
+    #[test]
+    fn resampling_keeps_duration() {
+        let samples: Vec<f32> = (0..8000).map(|i| i as f32 / 8000.0).collect();
+        let resampled = resample(&samples, 8000, 48000);
+        assert_eq!(resampled.len(), 48000);
+        assert!((resampled[24000] - 0.5).abs() < 0.001);
+        assert_eq!(resample(&samples, 8000, 8000).len(), 8000);
+    }
+}
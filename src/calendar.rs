@@ -0,0 +1,276 @@
+// Embedded contest calendar used to suggest practice for upcoming events
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+
+/// How far ahead to look when suggesting an upcoming contest
+pub const SUGGESTION_WINDOW_HOURS: i64 = 24;
+
+#[derive(Clone, Copy, Debug)]
+enum Schedule {
+    /// Weekly session starting on `weekday` at `hour`:00 UTC
+    Weekly {
+        weekday: Weekday,
+        hour: u32,
+        duration_hours: i64,
+    },
+    /// Annual event starting on the Saturday of the `nth` full weekend of `month`
+    /// at `hour`:00 UTC. Negative `nth` counts back from the end of the month.
+    Weekend {
+        month: u32,
+        nth: i32,
+        hour: u32,
+        duration_hours: i64,
+    },
+}
+
+struct CalendarEntry {
+    contest_id: &'static str,
+    name: &'static str,
+    schedule: Schedule,
+}
+
+const CALENDAR: &[CalendarEntry] = &[
+    CalendarEntry {
+        contest_id: "cwt",
+        name: "CWT",
+        schedule: Schedule::Weekly {
+            weekday: Weekday::Wed,
+            hour: 13,
+            duration_hours: 1,
+        },
+    },
+    CalendarEntry {
+        contest_id: "cwt",
+        name: "CWT",
+        schedule: Schedule::Weekly {
+            weekday: Weekday::Wed,
+            hour: 19,
+            duration_hours: 1,
+        },
+    },
+    CalendarEntry {
+        contest_id: "cwt",
+        name: "CWT",
+        schedule: Schedule::Weekly {
+            weekday: Weekday::Thu,
+            hour: 3,
+            duration_hours: 1,
+        },
+    },
+    CalendarEntry {
+        contest_id: "cwt",
+        name: "CWT",
+        schedule: Schedule::Weekly {
+            weekday: Weekday::Thu,
+            hour: 7,
+            duration_hours: 1,
+        },
+    },
+    CalendarEntry {
+        contest_id: "arrldx",
+        name: "ARRL DX CW",
+        schedule: Schedule::Weekend {
+            month: 2,
+            nth: 3,
+            hour: 0,
+            duration_hours: 48,
+        },
+    },
+    CalendarEntry {
+        contest_id: "cqwpx",
+        name: "CQ WPX CW",
+        schedule: Schedule::Weekend {
+            month: 5,
+            nth: -1,
+            hour: 0,
+            duration_hours: 48,
+        },
+    },
+    CalendarEntry {
+        contest_id: "allasian",
+        name: "All Asian DX CW",
+        schedule: Schedule::Weekend {
+            month: 6,
+            nth: 3,
+            hour: 0,
+            duration_hours: 48,
+        },
+    },
+    CalendarEntry {
+        contest_id: "sweepstakes",
+        name: "ARRL Sweepstakes CW",
+        schedule: Schedule::Weekend {
+            month: 11,
+            nth: 1,
+            hour: 21,
+            duration_hours: 30,
+        },
+    },
+    CalendarEntry {
+        contest_id: "cqww",
+        name: "CQ World Wide CW",
+        schedule: Schedule::Weekend {
+            month: 11,
+            nth: -1,
+            hour: 0,
+            duration_hours: 48,
+        },
+    },
+];
+
+/// A calendar event that is running now or starts soon
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpcomingContest {
+    pub contest_id: &'static str,
+    pub name: &'static str,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl UpcomingContest {
+    /// Short prompt text, e.g. "CWT starts in 2 hours"
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        if self.start <= now {
+            return format!("{} is on now", self.name);
+        }
+        let minutes = (self.start - now).num_minutes().max(1);
+        if minutes < 60 {
+            let unit = if minutes == 1 { "minute" } else { "minutes" };
+            format!("{} starts in {} {}", self.name, minutes, unit)
+        } else {
+            let hours = (minutes + 30) / 60;
+            let unit = if hours == 1 { "hour" } else { "hours" };
+            format!("{} starts in {} {}", self.name, hours, unit)
+        }
+    }
+}
+
+/// Find the contest that is running at `now` or starts within `window`.
+/// When several qualify, the one starting first wins.
+pub fn next_contest(now: DateTime<Utc>, window: Duration) -> Option<UpcomingContest> {
+    CALENDAR
+        .iter()
+        .flat_map(|entry| {
+            candidate_starts(entry.schedule, now)
+                .into_iter()
+                .map(move |start| UpcomingContest {
+                    contest_id: entry.contest_id,
+                    name: entry.name,
+                    start,
+                    end: start + Duration::hours(duration_hours(entry.schedule)),
+                })
+        })
+        .filter(|event| event.end > now && event.start <= now + window)
+        .min_by_key(|event| event.start)
+}
+
+fn duration_hours(schedule: Schedule) -> i64 {
+    match schedule {
+        Schedule::Weekly { duration_hours, .. } | Schedule::Weekend { duration_hours, .. } => {
+            duration_hours
+        }
+    }
+}
+
+/// Start times of the occurrences nearest to `now` (previous, current and next)
+fn candidate_starts(schedule: Schedule, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    match schedule {
+        Schedule::Weekly { weekday, hour, .. } => {
+            let today = now.date_naive();
+            let days_back =
+                (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+            let this_week = today - Duration::days(days_back as i64);
+            [-7, 0, 7]
+                .iter()
+                .filter_map(|offset| at_hour(this_week + Duration::days(*offset), hour))
+                .collect()
+        }
+        Schedule::Weekend {
+            month, nth, hour, ..
+        } => [now.year() - 1, now.year(), now.year() + 1]
+            .iter()
+            .filter_map(|year| full_weekend_saturday(*year, month, nth))
+            .filter_map(|date| at_hour(date, hour))
+            .collect(),
+    }
+}
+
+fn at_hour(date: NaiveDate, hour: u32) -> Option<DateTime<Utc>> {
+    date.and_hms_opt(hour, 0, 0)
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// Saturday of the nth full (Saturday and Sunday) weekend of the month
+fn full_weekend_saturday(year: i32, month: u32, nth: i32) -> Option<NaiveDate> {
+    let mut saturdays = Vec::new();
+    let mut day = NaiveDate::from_ymd_opt(year, month, 1)?;
+    while day.month() == month {
+        let sunday = day + Duration::days(1);
+        if day.weekday() == Weekday::Sat && sunday.month() == month {
+            saturdays.push(day);
+        }
+        day += Duration::days(1);
+    }
+
+    let index = if nth > 0 {
+        (nth - 1) as usize
+    } else {
+        saturdays.len().checked_sub(nth.unsigned_abs() as usize)?
+    };
+    saturdays.get(index).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn full_weekend_skips_split_weekends() {
+        // November 2025 starts on a Saturday, so the first full weekend is the 1st-2nd
+        assert_eq!(
+            full_weekend_saturday(2025, 11, 1),
+            NaiveDate::from_ymd_opt(2025, 11, 1)
+        );
+        // May 2026 ends on a Sunday (30th-31st is a full weekend)
+        assert_eq!(
+            full_weekend_saturday(2026, 5, -1),
+            NaiveDate::from_ymd_opt(2026, 5, 30)
+        );
+        // February 2026: Feb 28 is a Saturday but Mar 1 is not in February
+        assert_eq!(
+            full_weekend_saturday(2026, 2, -1),
+            NaiveDate::from_ymd_opt(2026, 2, 21)
+        );
+    }
+
+    #[test]
+    fn suggests_cwt_before_session() {
+        // Wednesday 2025-01-08 at 11:00 UTC, two hours before the 13Z session
+        let now = utc(2025, 1, 8, 11, 0);
+        let event = next_contest(now, Duration::hours(SUGGESTION_WINDOW_HOURS)).unwrap();
+        assert_eq!(event.contest_id, "cwt");
+        assert_eq!(event.start, utc(2025, 1, 8, 13, 0));
+        assert_eq!(event.describe(now), "CWT starts in 2 hours");
+    }
+
+    #[test]
+    fn reports_running_contest() {
+        // CQ WW CW 2025 runs Nov 29-30
+        let now = utc(2025, 11, 29, 15, 0);
+        let event = next_contest(now, Duration::hours(SUGGESTION_WINDOW_HOURS)).unwrap();
+        assert_eq!(event.contest_id, "cqww");
+        assert_eq!(event.describe(now), "CQ World Wide CW is on now");
+    }
+
+    #[test]
+    fn nothing_outside_window() {
+        // Monday morning in March: no CWT until Wednesday, no major contest
+        let now = utc(2025, 3, 10, 8, 0);
+        assert!(next_contest(now, Duration::hours(SUGGESTION_WINDOW_HOURS)).is_none());
+    }
+}
@@ -1,6 +1,7 @@
-use crate::contest::{self, Contest};
+use crate::contest::{self, Contest, SettingFieldKind};
+use crate::cty::Continent;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -8,6 +9,10 @@ pub struct AppSettings {
     pub contest: ContestConfig,
     pub audio: AudioSettings,
     pub simulation: SimulationSettings,
+    #[serde(default)]
+    pub drill: DrillSettings,
+    #[serde(default)]
+    pub data_updates: DataUpdateSettings,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -15,13 +20,79 @@ pub struct UserSettings {
     pub callsign: String,
     pub wpm: u8,
     pub font_size: f32,
+    /// Font for labels, menus and windows
+    #[serde(default)]
+    pub ui_font: FontChoice,
+    /// Font for the call and exchange entry boxes and the status line
+    #[serde(default = "default_entry_font")]
+    pub entry_font: FontChoice,
     pub agn_message: String,
+    /// Message sent with F3; {MYCALL} is replaced with the user's callsign.
+    /// Alternatives separated by `|` are picked at random.
+    #[serde(default = "default_tu_message")]
+    pub tu_message: String,
     #[serde(default)]
     pub show_main_hints: bool,
     #[serde(default = "default_true")]
     pub show_status_line: bool,
+    /// Show the receiver waterfall on the main panel
+    #[serde(default)]
+    pub show_waterfall: bool,
+    /// Show the S-meter on the main panel
+    #[serde(default)]
+    pub show_s_meter: bool,
+    /// Show how many stations are waiting to call (a learning aid)
+    #[serde(default)]
+    pub show_pileup_depth: bool,
+    /// When to show the worked caller's speed and pitch offset
+    #[serde(default)]
+    pub caller_speed_readout: CallerSpeedReadout,
+    /// Fill exchange fields from the call-history file when a known call is entered
+    #[serde(default)]
+    pub exchange_prefill: bool,
+    /// Warn when a QSO is logged before the caller finished the exchange
+    #[serde(default = "default_true")]
+    pub warn_early_log: bool,
+    /// Strict mode: refuse to log until the caller has finished the
+    /// exchange and every exchange box is filled
+    #[serde(default)]
+    pub strict_logging: bool,
+    /// Language for the user interface
+    #[serde(default)]
+    pub language: Language,
+    /// Program run with each status announcement as its last argument
+    /// (e.g. a text-to-speech or desktop notification tool); empty = off
+    #[serde(default)]
+    pub speech_command: String,
+    /// Save the session for crash recovery after this many QSOs (0 = off)
+    #[serde(default = "default_autosave_every")]
+    pub autosave_every: u32,
+    /// Pause the simulation after this many minutes without input (0 = off)
+    #[serde(default = "default_idle_pause_minutes")]
+    pub idle_pause_minutes: u32,
+    /// Banners and chimes for milestones during a session
+    #[serde(default)]
+    pub alerts: AlertSettings,
+    #[serde(default)]
+    pub theme: ColorTheme,
+    /// Color for selections and highlights
+    #[serde(default)]
+    pub accent: AccentColor,
     #[serde(default)]
     pub export_directory: String,
+    /// How much the main window shows
+    #[serde(default)]
+    pub layout: UiLayout,
+    /// Keep the main window above other windows
+    #[serde(default)]
+    pub always_on_top: bool,
+    /// Listen for `contest_trainer quick-drill`, run from a desktop shortcut
+    #[serde(default)]
+    pub quick_drill_hotkey: bool,
+    /// The guided first session has been finished or skipped. Settings
+    /// saved before the tutorial existed count as finished.
+    #[serde(default = "default_true")]
+    pub tutorial_done: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -29,6 +100,16 @@ pub struct ContestConfig {
     pub active_contest_id: String,
     #[serde(default)]
     pub contests: HashMap<String, toml::Value>,
+    /// Offer to switch to a contest from the built-in calendar on launch
+    #[serde(default = "default_true")]
+    pub suggest_upcoming: bool,
+    /// User message macros per contest, keyed by contest id then function key name
+    #[serde(default)]
+    pub macros: HashMap<String, BTreeMap<String, String>>,
+    /// Contests whose rules summary has been read and put away on the main
+    /// screen
+    #[serde(default)]
+    pub help_seen: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -48,6 +129,154 @@ pub struct AudioSettings {
     pub noise: NoiseSettings,
     #[serde(default)]
     pub qsb: QsbSettings,
+    /// Receiver AGC response
+    #[serde(default)]
+    pub agc: AgcMode,
+    /// Sound of our own transmitted signal
+    #[serde(default)]
+    pub sidetone: SidetoneSettings,
+    /// Break-in: hear the band between our own elements
+    #[serde(default)]
+    pub qsk: QskSettings,
+    /// Pan each caller left or right by its frequency offset
+    #[serde(default)]
+    pub spatial_audio: bool,
+    /// Frames per audio callback (0 = let the audio backend choose)
+    #[serde(default)]
+    pub buffer_frames: u32,
+    /// Where the audio goes
+    #[serde(default)]
+    pub backend: AudioBackendKind,
+    /// Level of the alert chimes, which are mixed apart from the CW
+    /// (0.0 - 1.0)
+    #[serde(default = "default_ui_sound_volume")]
+    pub ui_sound_volume: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SidetoneSettings {
+    /// Use the receive tone frequency for the sidetone
+    pub match_rx_pitch: bool,
+    /// Sidetone pitch when not matching the receive pitch
+    pub pitch_hz: f32,
+    /// Sidetone level (0.0 - 1.0)
+    pub level: f32,
+    /// Keying envelope rise/fall time in milliseconds
+    pub rise_time_ms: f32,
+    pub timbre: SidetoneTimbre,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QskSettings {
+    /// Mute the receiver only while the key is down (and for the delay
+    /// after), instead of for the whole message
+    pub enabled: bool,
+    /// Time the receiver stays muted after each element (ms)
+    pub delay_ms: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FontChoice {
+    /// egui's built-in proportional font
+    #[default]
+    Ubuntu,
+    /// egui's built-in monospace font
+    Hack,
+    /// Bundled monospace font with a slashed zero
+    DejaVuSansMono,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorTheme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    /// Blue/orange instead of green/red for right and wrong
+    Colorblind,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccentColor {
+    #[default]
+    Blue,
+    Green,
+    Orange,
+    Purple,
+    Teal,
+}
+
+/// When the worked caller's speed and pitch offset are shown, for
+/// calibrating your sense of speed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallerSpeedReadout {
+    #[default]
+    Off,
+    /// On the Last QSO line, once the QSO is logged
+    AfterQso,
+    /// While working the caller as well (a learning aid)
+    Live,
+}
+
+/// How much the main window shows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiLayout {
+    #[default]
+    Normal,
+    /// A single row of entry boxes, score and status, for a small screen or
+    /// running beside a real logger
+    Compact,
+    /// Meters, the last QSO and TX history in a side panel
+    Expanded,
+}
+
+impl UiLayout {
+    pub const ALL: [UiLayout; 3] = [UiLayout::Normal, UiLayout::Compact, UiLayout::Expanded];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UiLayout::Normal => "Normal",
+            UiLayout::Compact => "Compact",
+            UiLayout::Expanded => "Expanded",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SidetoneTimbre {
+    /// Pure sine, same as the callers
+    #[default]
+    Sine,
+    /// Sine with a little 2nd/3rd harmonic, like many rig sidetones
+    Rich,
+}
+
+/// Which audio backend plays the audio
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioBackendKind {
+    /// The default output device
+    #[default]
+    SoundCard,
+    /// No output: the audio is rendered on time and thrown away
+    Silent,
+    /// A JACK server, for routing the audio to other programs; only builds
+    /// with the `jack` feature can open it
+    Jack,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgcMode {
+    #[default]
+    Off,
+    Fast,
+    Slow,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -64,6 +293,54 @@ fn default_true() -> bool {
     true
 }
 
+fn default_double_probability() -> f32 {
+    0.1
+}
+
+fn default_call_twice_probability() -> f32 {
+    0.2
+}
+
+fn default_nil_probability() -> f32 {
+    0.5
+}
+
+fn default_fill_request_probability() -> f32 {
+    0.1
+}
+
+fn default_courtesy_probability() -> f32 {
+    0.2
+}
+
+fn default_call_match_threshold() -> f32 {
+    0.4
+}
+
+fn default_entry_font() -> FontChoice {
+    FontChoice::DejaVuSansMono
+}
+
+fn default_autosave_every() -> u32 {
+    5
+}
+
+fn default_idle_pause_minutes() -> u32 {
+    5
+}
+
+fn default_ui_sound_volume() -> f32 {
+    0.3
+}
+
+fn default_tu_message() -> String {
+    "TU {MYCALL}".to_string()
+}
+
+fn default_cut_number_probability() -> f32 {
+    0.6
+}
+
 fn default_noise_bandwidth() -> f32 {
     400.0
 }
@@ -93,18 +370,246 @@ pub struct SimulationSettings {
     pub amplitude_max: f32,
     #[serde(default)]
     pub agn_request_probability: f32,
+    /// Probability a caller asks for one element of our exchange ("NR?")
+    /// instead of sending theirs
+    #[serde(default = "default_fill_request_probability")]
+    pub fill_request_probability: f32,
+    /// Probability a caller sends their call twice when answering a CQ
+    #[serde(default = "default_call_twice_probability")]
+    pub call_twice_probability: f32,
+    /// Probability two responding callers double: same pitch, same time
+    #[serde(default = "default_double_probability")]
+    pub double_probability: f32,
+    /// Probability a caller we sent our exchange to, then dropped without
+    /// logging, logged us anyway: a NIL against us
+    #[serde(default = "default_nil_probability")]
+    pub nil_probability: f32,
+    /// A QSO scores only if the caller heard their call from us right at
+    /// least once
+    #[serde(default)]
+    pub require_sent_call: bool,
     /// Whether to filter callers based on country
     #[serde(default)]
     pub same_country_filter_enabled: bool,
     /// Probability of a caller being from the same country as the user (0.0 - 1.0)
     #[serde(default)]
     pub same_country_probability: f32,
+    /// Probability a caller sends numeric exchange fields with cut numbers
+    /// (contests may override this in their own settings)
+    #[serde(default)]
+    pub cut_number_probability: f32,
+    /// Probability a caller adds a courtesy word (TU, EE, 73, GL) to
+    /// their exchange
+    #[serde(default = "default_courtesy_probability")]
+    pub courtesy_probability: f32,
+    /// How strongly callers are picked for the characters the user
+    /// miscopies most (0.0 = off, 1.0 = strongest)
+    #[serde(default)]
+    pub weak_character_focus: f32,
+    /// Least similarity between the entered call and a caller's call for
+    /// that caller to be selected (0.0 - 1.0)
+    #[serde(default = "default_call_match_threshold")]
+    pub call_match_threshold: f32,
     /// Pileup persistence settings
     #[serde(default)]
     pub pileup: PileupSettings,
     /// Call correction settings
     #[serde(default)]
     pub call_correction: CallCorrectionSettings,
+    /// When callers start sending relative to the end of our CQ
+    #[serde(default)]
+    pub reaction_time: ReactionTimeSettings,
+    /// Gaps between turns once a QSO is under way
+    #[serde(default)]
+    pub timing: TimingSettings,
+    /// Nuisance behavior from badly behaved callers
+    #[serde(default)]
+    pub lids: LidSettings,
+    /// Signals on frequency that have nothing to do with our run
+    #[serde(default)]
+    pub ambience: AmbienceSettings,
+    /// Simulated CW skimmer spotting the callers
+    #[serde(default)]
+    pub skimmer: SkimmerSettings,
+    /// Relative share of callers from each continent
+    #[serde(default)]
+    pub continent_weights: ContinentWeights,
+    /// Generated callsigns in place of the callsign file
+    #[serde(default)]
+    pub synthetic_calls: SyntheticCallSettings,
+    /// Callers spread well beyond the receive filter, found with RIT
+    #[serde(default)]
+    pub wide_pileup: WidePileupSettings,
+    /// Someone taking over our run frequency, to fight or QSY
+    #[serde(default)]
+    pub frequency_fight: FrequencyFightSettings,
+    /// Station probability that follows the run: up with fast clean QSOs,
+    /// down with slow or busted ones
+    #[serde(default)]
+    pub dynamic_density: DynamicDensitySettings,
+}
+
+/// Relative caller weights by continent (looked up in cty.dat). Only the
+/// ratios matter; equal weights leave the callsign file's own mix alone.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContinentWeights {
+    pub north_america: f32,
+    pub south_america: f32,
+    pub europe: f32,
+    pub africa: f32,
+    pub asia: f32,
+    pub oceania: f32,
+}
+
+impl ContinentWeights {
+    pub fn weight(&self, continent: Continent) -> f32 {
+        match continent {
+            Continent::NorthAmerica => self.north_america,
+            Continent::SouthAmerica => self.south_america,
+            Continent::Europe => self.europe,
+            Continent::Africa => self.africa,
+            Continent::Asia => self.asia,
+            Continent::Oceania => self.oceania,
+        }
+    }
+
+    pub fn weight_mut(&mut self, continent: Continent) -> &mut f32 {
+        match continent {
+            Continent::NorthAmerica => &mut self.north_america,
+            Continent::SouthAmerica => &mut self.south_america,
+            Continent::Europe => &mut self.europe,
+            Continent::Africa => &mut self.africa,
+            Continent::Asia => &mut self.asia,
+            Continent::Oceania => &mut self.oceania,
+        }
+    }
+
+    /// Chance of keeping a caller from `continent`: its weight relative to
+    /// the largest. Returns 1.0 when the weights don't favor anyone.
+    pub fn keep_probability(&self, continent: Continent) -> f32 {
+        let max = Continent::ALL
+            .iter()
+            .map(|c| self.weight(*c))
+            .fold(0.0f32, f32::max);
+        if max <= 0.0 {
+            return 1.0;
+        }
+        (self.weight(continent) / max).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for ContinentWeights {
+    fn default() -> Self {
+        Self {
+            north_america: 1.0,
+            south_america: 1.0,
+            europe: 1.0,
+            africa: 1.0,
+            asia: 1.0,
+            oceania: 1.0,
+        }
+    }
+}
+
+/// Milestones worth a word during a session, and how to give it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// Flash a banner over the main window
+    pub banner: bool,
+    /// Play a chime, held back while we're sending
+    pub chime: bool,
+    /// Every this many QSOs (0 = off)
+    pub qso_step: u32,
+    /// When the 10-minute rate passes the personal best
+    pub best_rate: bool,
+    /// When the last 10 QSOs fall below this accuracy (%, 0 = off)
+    pub accuracy_floor_pct: u32,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            banner: false,
+            chime: false,
+            qso_step: 25,
+            best_rate: true,
+            accuracy_floor_pct: 80,
+        }
+    }
+}
+
+/// Where File > Check for Data Updates downloads from
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataUpdateSettings {
+    pub cty_url: String,
+    /// CWT roster in the CWT callsign file format (blank to skip it)
+    pub cwt_roster_url: String,
+}
+
+pub const DEFAULT_CTY_URL: &str = "https://www.country-files.com/cty/cty.dat";
+
+impl Default for DataUpdateSettings {
+    fn default() -> Self {
+        Self {
+            cty_url: DEFAULT_CTY_URL.to_string(),
+            cwt_roster_url: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DrillSettings {
+    /// Minimum sending speed for drill items
+    pub wpm_min: u8,
+    /// Maximum sending speed for drill items
+    pub wpm_max: u8,
+    /// Probability each 0/9/1 in the serial drill is sent as T/N/A
+    #[serde(default = "default_cut_number_probability")]
+    pub cut_number_probability: f32,
+    /// Recordings generated by "Export Audio Drill"
+    #[serde(default)]
+    pub audio_export: AudioDrillSettings,
+    #[serde(default)]
+    pub koch: KochSettings,
+    /// Abbreviations drill asks which of four was sent instead of a typed copy
+    #[serde(default)]
+    pub abbreviation_choices: bool,
+}
+
+/// Speeds for the Koch character course
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KochSettings {
+    /// Speed each character is sent at (at least 20 WPM)
+    pub char_wpm: u8,
+    /// Overall speed, set by the spacing between characters
+    pub effective_wpm: u8,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioDrillSettings {
+    pub kind: AudioDrillKind,
+    /// Number of recordings to generate
+    pub count: u32,
+    pub wpm_min: u8,
+    pub wpm_max: u8,
+    /// Most callers in one pileup recording
+    pub max_callers: u8,
+    /// Background noise level for the recordings
+    pub noise_level: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioDrillKind {
+    /// Several callers sending their callsigns at once
+    #[default]
+    Pileup,
+    /// One station sending its callsign and exchange
+    Exchange,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -117,6 +622,170 @@ pub struct CallCorrectionSettings {
     pub max_correction_attempts: u8,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReactionTimeSettings {
+    /// Fastest normal reaction after our CQ ends (ms)
+    pub min_ms: u32,
+    /// Slowest normal reaction after our CQ ends (ms)
+    pub max_ms: u32,
+    /// Probability a caller jumps in before our CQ has finished
+    pub tailgate_probability: f32,
+    /// How far before the end of our CQ a tailgater may start (ms)
+    pub tailgate_max_ms: u32,
+    /// Probability a caller is seconds late responding
+    pub late_probability: f32,
+    /// Longest extra delay for a late caller (ms)
+    pub late_max_ms: u32,
+}
+
+/// How quickly the other station takes its turn. Each gap is picked at
+/// random within its range, so the rhythm never settles into a fixed beat.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimingSettings {
+    /// Shortest gap between the end of our exchange, AGN or partial query
+    /// and the station's reply (ms)
+    pub reply_min_ms: u32,
+    /// Longest gap before the station's reply (ms)
+    pub reply_max_ms: u32,
+    /// Gap after our CQ ends before the callers are picked (ms); their own
+    /// reaction time comes on top of it
+    pub post_cq_gap_ms: u32,
+}
+
+impl TimingSettings {
+    /// A gap before the station's reply, picked within the range
+    pub fn reply_gap_ms(&self, rng: &mut dyn rand::RngCore) -> u64 {
+        use rand::Rng;
+
+        let (min, max) = (
+            self.reply_min_ms.min(self.reply_max_ms),
+            self.reply_max_ms.max(self.reply_min_ms),
+        );
+        rng.gen_range(min..=max) as u64
+    }
+}
+
+/// Overall pace of the other operators: how fast callers react to our CQ
+/// and come back to our transmissions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperatorTempo {
+    /// Rapid-fire, like the CWTs
+    Fast,
+    /// The default timing
+    Normal,
+    /// Unhurried, like a DX station working a pileup
+    Relaxed,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct LidSettings {
+    /// Probability a station outside the pileup calls while the caller
+    /// we're working is sending their exchange
+    pub out_of_turn_probability: f32,
+    /// Probability a pileup caller we didn't pick calls again after we've
+    /// sent to someone else
+    pub call_over_probability: f32,
+    /// Probability a new caller never listens and calls after every one
+    /// of our transmissions until worked or out of patience
+    pub continuous_caller_probability: f32,
+    /// Probability a caller sends our call wrong ahead of their exchange
+    pub wrong_call_probability: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmbienceSettings {
+    /// Average number of background events per minute (0.0 to disable)
+    pub events_per_minute: f32,
+    /// Someone asks "QRL?" before using the frequency
+    pub qrl_query: bool,
+    /// Someone sends a brief carrier while tuning up
+    pub tune_up: bool,
+    /// Someone calls CQ on our frequency while we're not running
+    pub cq_on_frequency: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyntheticCallSettings {
+    /// Generate calls from cty.dat prefixes instead of reading the
+    /// callsign file (contests whose exchange comes from the call)
+    pub enabled: bool,
+    /// Relative share of one-, two- and three-letter suffixes
+    pub suffix_length_weights: [f32; 3],
+    /// Probability a call carries a portable suffix (/P, /QRP, /M or /digit)
+    pub portable_probability: f32,
+}
+
+/// What a caller sends when they run out of patience
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GiveUpBehavior {
+    /// Just stops calling
+    #[default]
+    Silent,
+    /// One last call, then silence
+    FinalCall,
+    /// A quick "E E" on the way out
+    Dits,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkimmerSettings {
+    /// Share of spots with the call decoded right (0.0 - 1.0)
+    pub accuracy: f32,
+    /// Decoding delay after the call ends (ms)
+    pub delay_ms: u32,
+}
+
+/// A pileup spread across a couple of kHz, wider than the receive filter:
+/// callers outside the filter are heard only faintly until RIT brings them in
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidePileupSettings {
+    pub enabled: bool,
+    /// Total spread of the callers; offsets are ± half of it
+    pub spread_hz: f32,
+    /// Receive filter width while the mode is on
+    pub filter_hz: f32,
+}
+
+/// A loud station that starts calling CQ on our run frequency after we've
+/// held it a while, leaving us to keep CQing through them or QSY
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrequencyFightSettings {
+    pub enabled: bool,
+    /// Minutes of running on one frequency before anyone takes it
+    pub min_run_minutes: f32,
+    /// Chance each minute after that someone does (0.0 - 1.0)
+    pub chance_per_minute: f32,
+    /// Share of callers still heard through them (0.0 - 1.0)
+    pub callers_heard: f32,
+    /// Seconds a QSY takes to find a clear frequency
+    pub qsy_secs: u32,
+}
+
+/// Pileup that builds while the run goes well and thins when it doesn't.
+/// Starts from `station_probability` and stays within the range.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DynamicDensitySettings {
+    pub enabled: bool,
+    /// Lowest and highest station probability it moves between
+    pub min_probability: f32,
+    pub max_probability: f32,
+    /// A clean QSO at most this long builds the pileup
+    pub fast_qso_secs: f32,
+    /// A QSO longer than this, or a busted one, thins it
+    pub slow_qso_secs: f32,
+    /// How much each fast QSO adds to the station probability
+    pub build_step: f32,
+    /// How much each slow or busted QSO takes off
+    pub thin_step: f32,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PileupSettings {
     /// Minimum patience (call attempts) for new callers
@@ -127,6 +796,9 @@ pub struct PileupSettings {
     pub retry_delay_min_ms: u32,
     /// Maximum delay before retry (ms)
     pub retry_delay_max_ms: u32,
+    /// Whether a caller who gives up is heard leaving
+    #[serde(default)]
+    pub give_up: GiveUpBehavior,
 }
 
 impl Default for AppSettings {
@@ -136,20 +808,54 @@ impl Default for AppSettings {
             contest: ContestConfig::default(),
             audio: AudioSettings::default(),
             simulation: SimulationSettings::default(),
+            drill: DrillSettings::default(),
+            data_updates: DataUpdateSettings::default(),
         }
     }
 }
 
+impl UserSettings {
+    /// The TU message alternatives, blanks left out
+    pub fn tu_message_variants(&self) -> Vec<&str> {
+        self.tu_message
+            .split('|')
+            .map(str::trim)
+            .filter(|variant| !variant.is_empty())
+            .collect()
+    }
+}
+
 impl Default for UserSettings {
     fn default() -> Self {
         Self {
             callsign: "N9UNX".to_string(),
             wpm: 32,
             font_size: 14.0,
+            ui_font: FontChoice::default(),
+            entry_font: default_entry_font(),
             agn_message: "?".to_string(),
+            tu_message: default_tu_message(),
             show_main_hints: false,
             show_status_line: true,
+            show_waterfall: false,
+            show_s_meter: false,
+            show_pileup_depth: false,
+            caller_speed_readout: CallerSpeedReadout::Off,
+            exchange_prefill: false,
+            warn_early_log: true,
+            strict_logging: false,
+            language: Language::default(),
+            speech_command: String::new(),
+            autosave_every: default_autosave_every(),
+            idle_pause_minutes: default_idle_pause_minutes(),
+            alerts: AlertSettings::default(),
+            theme: ColorTheme::default(),
+            accent: AccentColor::default(),
             export_directory: String::new(),
+            layout: UiLayout::default(),
+            always_on_top: false,
+            quick_drill_hotkey: false,
+            tutorial_done: false,
         }
     }
 }
@@ -166,6 +872,9 @@ impl Default for ContestConfig {
         Self {
             active_contest_id: contest_id.to_string(),
             contests,
+            suggest_upcoming: true,
+            macros: HashMap::new(),
+            help_seen: Vec::new(),
         }
     }
 }
@@ -182,6 +891,13 @@ impl Default for AudioSettings {
             noise_bandwidth: 350.0,
             noise: NoiseSettings::default(),
             qsb: QsbSettings::default(),
+            agc: AgcMode::default(),
+            sidetone: SidetoneSettings::default(),
+            qsk: QskSettings::default(),
+            spatial_audio: false,
+            buffer_frames: 0,
+            backend: AudioBackendKind::default(),
+            ui_sound_volume: default_ui_sound_volume(),
         }
     }
 }
@@ -198,6 +914,27 @@ impl Default for NoiseSettings {
     }
 }
 
+impl Default for SidetoneSettings {
+    fn default() -> Self {
+        Self {
+            match_rx_pitch: true,
+            pitch_hz: 600.0,
+            level: 0.8,
+            rise_time_ms: 5.0,
+            timbre: SidetoneTimbre::Sine,
+        }
+    }
+}
+
+impl Default for QskSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 10.0,
+        }
+    }
+}
+
 impl Default for QsbSettings {
     fn default() -> Self {
         Self {
@@ -219,10 +956,64 @@ impl Default for SimulationSettings {
             amplitude_min: 0.4,
             amplitude_max: 1.0,
             agn_request_probability: 0.1,
+            fill_request_probability: default_fill_request_probability(),
+            call_twice_probability: default_call_twice_probability(),
+            double_probability: default_double_probability(),
+            nil_probability: default_nil_probability(),
+            require_sent_call: false,
             same_country_filter_enabled: false,
             same_country_probability: 0.1,
+            cut_number_probability: 0.0,
+            courtesy_probability: default_courtesy_probability(),
+            weak_character_focus: 0.0,
+            call_match_threshold: default_call_match_threshold(),
             pileup: PileupSettings::default(),
             call_correction: CallCorrectionSettings::default(),
+            reaction_time: ReactionTimeSettings::default(),
+            timing: TimingSettings::default(),
+            lids: LidSettings::default(),
+            ambience: AmbienceSettings::default(),
+            skimmer: SkimmerSettings::default(),
+            continent_weights: ContinentWeights::default(),
+            synthetic_calls: SyntheticCallSettings::default(),
+            wide_pileup: WidePileupSettings::default(),
+            frequency_fight: FrequencyFightSettings::default(),
+            dynamic_density: DynamicDensitySettings::default(),
+        }
+    }
+}
+
+impl Default for DrillSettings {
+    fn default() -> Self {
+        Self {
+            wpm_min: 18,
+            wpm_max: 25,
+            cut_number_probability: default_cut_number_probability(),
+            audio_export: AudioDrillSettings::default(),
+            koch: KochSettings::default(),
+            abbreviation_choices: false,
+        }
+    }
+}
+
+impl Default for AudioDrillSettings {
+    fn default() -> Self {
+        Self {
+            kind: AudioDrillKind::Pileup,
+            count: 10,
+            wpm_min: 24,
+            wpm_max: 32,
+            max_callers: 3,
+            noise_level: 0.2,
+        }
+    }
+}
+
+impl Default for KochSettings {
+    fn default() -> Self {
+        Self {
+            char_wpm: 20,
+            effective_wpm: 12,
         }
     }
 }
@@ -236,6 +1027,95 @@ impl Default for CallCorrectionSettings {
     }
 }
 
+impl Default for ReactionTimeSettings {
+    fn default() -> Self {
+        Self {
+            min_ms: 100,
+            max_ms: 800,
+            tailgate_probability: 0.1,
+            tailgate_max_ms: 600,
+            late_probability: 0.1,
+            late_max_ms: 3000,
+        }
+    }
+}
+
+impl Default for TimingSettings {
+    fn default() -> Self {
+        Self {
+            reply_min_ms: 200,
+            reply_max_ms: 300,
+            post_cq_gap_ms: 200,
+        }
+    }
+}
+
+impl Default for AmbienceSettings {
+    fn default() -> Self {
+        Self {
+            events_per_minute: 0.0,
+            qrl_query: true,
+            tune_up: true,
+            cq_on_frequency: true,
+        }
+    }
+}
+
+impl Default for WidePileupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spread_hz: 2000.0,
+            filter_hz: 250.0,
+        }
+    }
+}
+
+impl Default for FrequencyFightSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_run_minutes: 10.0,
+            chance_per_minute: 0.2,
+            callers_heard: 0.4,
+            qsy_secs: 20,
+        }
+    }
+}
+
+impl Default for DynamicDensitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_probability: 0.2,
+            max_probability: 1.0,
+            fast_qso_secs: 15.0,
+            slow_qso_secs: 40.0,
+            build_step: 0.03,
+            thin_step: 0.08,
+        }
+    }
+}
+
+impl Default for SkimmerSettings {
+    fn default() -> Self {
+        Self {
+            accuracy: 0.9,
+            delay_ms: 2000,
+        }
+    }
+}
+
+impl Default for SyntheticCallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            suffix_length_weights: [1.0, 3.0, 6.0],
+            portable_probability: 0.05,
+        }
+    }
+}
+
 impl Default for PileupSettings {
     fn default() -> Self {
         Self {
@@ -243,6 +1123,7 @@ impl Default for PileupSettings {
             max_patience: 5,
             retry_delay_min_ms: 200,
             retry_delay_max_ms: 1200,
+            give_up: GiveUpBehavior::default(),
         }
     }
 }
@@ -252,7 +1133,74 @@ pub struct SettingsLoadResult {
     pub notice: Option<String>,
 }
 
+/// Function keys available for user message macros (F1-F3, F5, F8 and F12 are built in)
+pub const MACRO_KEYS: &[&str] = &["F4", "F6", "F7", "F9", "F10", "F11"];
+
+/// Per-contest setting key that overrides the global cut number probability
+pub const CUT_NUMBER_PROBABILITY_KEY: &str = "cut_number_probability";
+
+/// Per-contest setting keys for the extra calls a caller sends ahead of
+/// their exchange
+pub const OUR_CALL_PROBABILITY_KEY: &str = "our_call_probability";
+pub const DE_PROBABILITY_KEY: &str = "de_probability";
+pub const SIGN_TWICE_PROBABILITY_KEY: &str = "sign_twice_probability";
+
+/// How often callers put extra calls ahead of their exchange, as in
+/// "W1AW DE K3LR 5NN 05"; all off unless set for the contest
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CallerFormat {
+    /// Probability the caller starts with our call
+    pub our_call: f32,
+    /// Probability the caller signs with "DE" and their own call
+    pub de: f32,
+    /// Probability the caller signs their own call twice
+    pub sign_twice: f32,
+}
+
 impl ContestConfig {
+    /// Cut number probability for a contest, falling back to the global value
+    pub fn cut_number_probability(&self, contest_id: &str, global: f32) -> f32 {
+        self.contests
+            .get(contest_id)
+            .and_then(|settings| settings.get(CUT_NUMBER_PROBABILITY_KEY))
+            .and_then(|value| {
+                value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|v| v as f64))
+            })
+            .map(|value| (value as f32).clamp(0.0, 1.0))
+            .unwrap_or(global)
+    }
+
+    /// Extra calls callers send ahead of their exchange in a contest
+    pub fn caller_format(&self, contest_id: &str) -> CallerFormat {
+        let probability = |key: &str| {
+            self.contests
+                .get(contest_id)
+                .and_then(|settings| settings.get(key))
+                .and_then(|value| {
+                    value
+                        .as_float()
+                        .or_else(|| value.as_integer().map(|v| v as f64))
+                })
+                .map_or(0.0, |value| (value as f32).clamp(0.0, 1.0))
+        };
+        CallerFormat {
+            our_call: probability(OUR_CALL_PROBABILITY_KEY),
+            de: probability(DE_PROBABILITY_KEY),
+            sign_twice: probability(SIGN_TWICE_PROBABILITY_KEY),
+        }
+    }
+
+    /// Macro template assigned to a function key for a contest, if any
+    pub fn macro_for(&self, contest_id: &str, key: &str) -> Option<&str> {
+        self.macros
+            .get(contest_id)
+            .and_then(|macros| macros.get(key))
+            .map(|template| template.trim())
+            .filter(|template| !template.is_empty())
+    }
+
     pub fn settings_for_mut(&mut self, contest: &dyn Contest) -> &mut toml::Value {
         let entry = self
             .contests
@@ -280,8 +1228,15 @@ fn merge_defaults(target: &mut toml::Value, defaults: toml::Value) {
 }
 
 impl AppSettings {
-    /// Get the default config file path
+    /// Get the default config file path. Tests get a folder of their own,
+    /// so they neither read nor write the user's settings, downloads and
+    /// custom contests.
     pub fn config_path() -> std::path::PathBuf {
+        if cfg!(test) {
+            return std::env::temp_dir()
+                .join(format!("contest_trainer_test_{}", std::process::id()))
+                .join("settings.toml");
+        }
         if let Some(config_dir) = dirs::config_dir() {
             config_dir.join("contest_trainer").join("settings.toml")
         } else {
@@ -350,6 +1305,164 @@ impl AppSettings {
         eprintln!("Saved settings to {}", path.display());
         Ok(())
     }
+
+    /// Weak-signal (QRP) practice: callers a few dB over the noise, fading
+    /// in and out. Everything else is left as it was.
+    pub fn apply_weak_signal_preset(&mut self) {
+        self.simulation.amplitude_min = 0.015;
+        self.simulation.amplitude_max = 0.05;
+        self.audio.noise_level = self.audio.noise_level.max(0.25);
+        self.audio.qsb = QsbSettings {
+            enabled: true,
+            depth: 0.6,
+            rate: 6.0,
+        };
+    }
+
+    /// Set the reply gaps and caller reaction times for a tempo. Tailgaters
+    /// and late callers are left as they were.
+    pub fn apply_tempo(&mut self, tempo: OperatorTempo) {
+        let (reply_min_ms, reply_max_ms, post_cq_gap_ms, min_ms, max_ms) = match tempo {
+            OperatorTempo::Fast => (50, 150, 100, 50, 400),
+            OperatorTempo::Normal => {
+                let timing = TimingSettings::default();
+                let reaction = ReactionTimeSettings::default();
+                (
+                    timing.reply_min_ms,
+                    timing.reply_max_ms,
+                    timing.post_cq_gap_ms,
+                    reaction.min_ms,
+                    reaction.max_ms,
+                )
+            }
+            OperatorTempo::Relaxed => (400, 900, 400, 300, 1500),
+        };
+        self.simulation.timing = TimingSettings {
+            reply_min_ms,
+            reply_max_ms,
+            post_cq_gap_ms,
+        };
+        self.simulation.reaction_time.min_ms = min_ms;
+        self.simulation.reaction_time.max_ms = max_ms;
+    }
+}
+
+/// Current version of the shareable settings bundle format
+pub const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+/// A shareable snapshot of the full settings, e.g. a club training configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub format_version: u32,
+    pub settings: AppSettings,
+}
+
+impl AppSettings {
+    /// Export the settings as a versioned bundle.
+    /// File references (each contest's file settings) are stripped unless `include_file_references` is set.
+    pub fn export_bundle(
+        &self,
+        path: &std::path::Path,
+        include_file_references: bool,
+    ) -> Result<(), String> {
+        let mut settings = self.clone();
+        settings.keep_local_commands(&AppSettings::default());
+        if !include_file_references {
+            for (contest_id, value) in &mut settings.contest.contests {
+                if let (Some(contest), toml::Value::Table(table)) =
+                    (contest::create_contest(contest_id), value)
+                {
+                    for field in contest.settings_fields() {
+                        if field.kind == SettingFieldKind::FilePath {
+                            table.remove(field.key);
+                        }
+                    }
+                }
+            }
+        }
+
+        let bundle = SettingsBundle {
+            format_version: SETTINGS_BUNDLE_VERSION,
+            settings,
+        };
+        let content = toml::to_string_pretty(&bundle)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    /// Import a settings bundle and merge it onto these settings.
+    ///
+    /// The bundle replaces the training configuration, but the user's callsign,
+    /// export directory, tutorial and help progress, audio output, speech command and download addresses are kept. Contest settings are merged key by key so that
+    /// values missing from the bundle keep their local value. Every contest table is
+    /// validated before anything is applied.
+    pub fn import_bundle(&self, path: &std::path::Path) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let bundle: SettingsBundle =
+            toml::from_str(&content).map_err(|e| format!("Invalid settings bundle: {}", e))?;
+
+        if bundle.format_version == 0 || bundle.format_version > SETTINGS_BUNDLE_VERSION {
+            return Err(format!(
+                "Unsupported settings bundle version {} (expected {} or lower)",
+                bundle.format_version, SETTINGS_BUNDLE_VERSION
+            ));
+        }
+
+        self.merged_with(bundle.settings)
+    }
+
+    fn merged_with(&self, incoming: AppSettings) -> Result<Self, String> {
+        let mut merged = incoming;
+        merged.user.callsign = self.user.callsign.clone();
+        merged.user.export_directory = self.user.export_directory.clone();
+        // What this user has already been shown
+        merged.user.tutorial_done = self.user.tutorial_done;
+        merged.contest.help_seen = self.contest.help_seen.clone();
+        // The audio output depends on this machine's sound setup and build
+        merged.audio.sample_rate = self.audio.sample_rate;
+        merged.audio.backend = self.audio.backend;
+        merged.audio.buffer_frames = self.audio.buffer_frames;
+        merged.keep_local_commands(self);
+
+        let mut contests = self.contest.contests.clone();
+        for (contest_id, incoming_value) in merged.contest.contests {
+            let entry = contests
+                .entry(contest_id)
+                .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+            match (entry, incoming_value) {
+                (toml::Value::Table(local), toml::Value::Table(incoming)) => {
+                    for (key, value) in incoming {
+                        local.insert(key, value);
+                    }
+                }
+                (entry, incoming_value) => *entry = incoming_value,
+            }
+        }
+        merged.contest.contests = contests;
+
+        for (contest_id, value) in &mut merged.contest.contests {
+            if let Some(contest) = contest::create_contest(contest_id) {
+                merge_defaults(value, contest.default_settings());
+                contest
+                    .validate_settings(value)
+                    .map_err(|e| format!("{}: {}", contest.display_name(), e))?;
+            }
+        }
+
+        if contest::create_contest(&merged.contest.active_contest_id).is_none() {
+            merged.contest.active_contest_id = self.contest.active_contest_id.clone();
+        }
+
+        Ok(merged)
+    }
+
+    /// Settings that run a program or fetch from an address, which a
+    /// bundle must never bring in from someone else
+    fn keep_local_commands(&mut self, local: &AppSettings) {
+        self.user.speech_command = local.user.speech_command.clone();
+        self.data_updates = local.data_updates.clone();
+    }
 }
 
 fn backup_settings_file(path: &std::path::Path) -> Option<std::path::PathBuf> {
@@ -366,3 +1479,230 @@ fn backup_settings_file(path: &std::path::Path) -> Option<std::path::PathBuf> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bundle_{}_{}.toml", name, std::process::id()))
+    }
+
+    fn contest_table(pairs: &[(&str, &str)]) -> toml::Value {
+        toml::Value::Table(
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml::Value::String(value.to_string())))
+                .collect(),
+        )
+    }
+
+    fn contest_value<'a>(settings: &'a AppSettings, contest: &str, key: &str) -> Option<&'a str> {
+        settings.contest.contests.get(contest)?.get(key)?.as_str()
+    }
+
+    #[test]
+    fn bundle_round_trips_keeping_the_local_call() {
+        let mut shared = AppSettings::default();
+        shared.user.callsign = "W1CLUB".to_string();
+        shared.user.wpm = 32;
+        shared.simulation.station_probability = 0.4;
+        shared.user.tutorial_done = false;
+        shared.contest.help_seen = vec!["cqww".to_string(), "sweepstakes".to_string()];
+        let path = bundle_path("round_trip");
+        shared.export_bundle(&path, false).unwrap();
+
+        let mut local = AppSettings::default();
+        local.user.callsign = "K1ABC".to_string();
+        local.user.tutorial_done = true;
+        local.contest.help_seen = vec!["cwt".to_string()];
+        let imported = local.import_bundle(&path);
+        std::fs::remove_file(&path).ok();
+        let imported = imported.unwrap();
+        assert_eq!(imported.user.callsign, "K1ABC");
+        assert_eq!(imported.user.wpm, 32);
+        assert!(imported.user.tutorial_done);
+        assert_eq!(imported.contest.help_seen, vec!["cwt".to_string()]);
+        assert_eq!(imported.simulation.station_probability, 0.4);
+    }
+
+    #[test]
+    fn bundles_never_carry_commands_or_download_addresses() {
+        let mut shared = AppSettings::default();
+        shared.user.speech_command = "/tmp/payload".to_string();
+        shared.data_updates.cty_url = "http://example.com/cty.dat".to_string();
+        shared.data_updates.cwt_roster_url = "http://example.com/roster.txt".to_string();
+        let path = bundle_path("commands");
+        shared.export_bundle(&path, true).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("payload") && !content.contains("example.com"));
+
+        // Even in a bundle edited by hand, the local values win
+        std::fs::write(
+            &path,
+            toml::to_string_pretty(&SettingsBundle {
+                format_version: SETTINGS_BUNDLE_VERSION,
+                settings: shared,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let mut local = AppSettings::default();
+        local.user.speech_command = "say".to_string();
+        local.data_updates.cwt_roster_url = "https://cwops.example/roster".to_string();
+        let imported = local.import_bundle(&path);
+        std::fs::remove_file(&path).ok();
+        let imported = imported.unwrap();
+        assert_eq!(imported.user.speech_command, "say");
+        assert_eq!(imported.data_updates.cty_url, DEFAULT_CTY_URL);
+        assert_eq!(
+            imported.data_updates.cwt_roster_url,
+            "https://cwops.example/roster"
+        );
+    }
+
+    #[test]
+    fn imports_keep_the_local_audio_output() {
+        let mut incoming = AppSettings::default();
+        incoming.audio.backend = AudioBackendKind::Jack;
+        incoming.audio.buffer_frames = 1024;
+        incoming.audio.master_volume = 0.3;
+        let mut local = AppSettings::default();
+        local.audio.backend = AudioBackendKind::SoundCard;
+        local.audio.buffer_frames = 256;
+
+        let merged = local.merged_with(incoming).unwrap();
+        assert_eq!(merged.audio.backend, AudioBackendKind::SoundCard);
+        assert_eq!(merged.audio.buffer_frames, 256);
+        assert_eq!(merged.audio.master_volume, 0.3);
+    }
+
+    #[test]
+    fn bundles_from_a_newer_or_unknown_version_are_rejected() {
+        let content = toml::to_string_pretty(&SettingsBundle {
+            format_version: SETTINGS_BUNDLE_VERSION,
+            settings: AppSettings::default(),
+        })
+        .unwrap();
+        for version in [0, SETTINGS_BUNDLE_VERSION + 1] {
+            let path = bundle_path(&format!("version_{}", version));
+            let changed = content.replacen(
+                &format!("format_version = {}", SETTINGS_BUNDLE_VERSION),
+                &format!("format_version = {}", version),
+                1,
+            );
+            std::fs::write(&path, changed).unwrap();
+            let result = AppSettings::default().import_bundle(&path);
+            std::fs::remove_file(&path).ok();
+            let error = result.err().unwrap();
+            assert!(
+                error.contains("Unsupported settings bundle version"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn contest_settings_merge_key_by_key() {
+        let mut local = AppSettings::default();
+        local.contest.contests.insert(
+            "cwt".to_string(),
+            contest_table(&[("cq_message", "CQ CWT"), ("user_name", "BOB")]),
+        );
+        let mut incoming = AppSettings::default();
+        incoming.contest.contests.clear();
+        incoming.contest.contests.insert(
+            "cwt".to_string(),
+            contest_table(&[("cq_message", "CQ TEST")]),
+        );
+
+        let merged = local.merged_with(incoming).unwrap();
+        // Taken from the bundle, kept locally, and filled from the defaults
+        assert_eq!(contest_value(&merged, "cwt", "cq_message"), Some("CQ TEST"));
+        assert_eq!(contest_value(&merged, "cwt", "user_name"), Some("BOB"));
+        assert_eq!(contest_value(&merged, "cwt", "user_number"), Some("CT"));
+    }
+
+    #[test]
+    fn file_references_are_left_out_unless_asked_for() {
+        let mut settings = AppSettings::default();
+        settings.contest.contests.insert(
+            "cwt".to_string(),
+            contest_table(&[
+                ("callsign_file", "/home/me/calls.txt"),
+                ("activity_file", "/home/me/activity.txt"),
+                ("user_name", "BOB"),
+            ]),
+        );
+        for include in [false, true] {
+            let path = bundle_path(&format!("files_{}", include));
+            settings.export_bundle(&path, include).unwrap();
+            let content = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).ok();
+            let bundle: SettingsBundle = toml::from_str(&content).unwrap();
+            for key in ["callsign_file", "activity_file"] {
+                assert_eq!(
+                    contest_value(&bundle.settings, "cwt", key).is_some(),
+                    include,
+                    "{}",
+                    key
+                );
+            }
+            assert_eq!(
+                contest_value(&bundle.settings, "cwt", "user_name"),
+                Some("BOB")
+            );
+        }
+    }
+
+    #[test]
+    fn cut_number_probability_can_be_set_per_contest() {
+        let mut config = ContestConfig::default();
+        config.contests.clear();
+        // Nothing set for the contest: the global value
+        assert_eq!(config.cut_number_probability("cwt", 0.3), 0.3);
+
+        let mut table = toml::value::Table::new();
+        table.insert(
+            CUT_NUMBER_PROBABILITY_KEY.to_string(),
+            toml::Value::Float(0.75),
+        );
+        config
+            .contests
+            .insert("cwt".to_string(), toml::Value::Table(table));
+        assert_eq!(config.cut_number_probability("cwt", 0.3), 0.75);
+
+        // Whole numbers as TOML writes them, clamped to a probability
+        for (value, expected) in [(1, 1.0), (0, 0.0), (5, 1.0)] {
+            let mut table = toml::value::Table::new();
+            table.insert(
+                CUT_NUMBER_PROBABILITY_KEY.to_string(),
+                toml::Value::Integer(value),
+            );
+            config
+                .contests
+                .insert("cqww".to_string(), toml::Value::Table(table));
+            assert_eq!(config.cut_number_probability("cqww", 0.3), expected);
+        }
+
+        // Anything else falls back to the global value
+        config.contests.insert(
+            "cqwpx".to_string(),
+            contest_table(&[(CUT_NUMBER_PROBABILITY_KEY, "often")]),
+        );
+        assert_eq!(config.cut_number_probability("cqwpx", 0.3), 0.3);
+        assert_eq!(config.cut_number_probability("sweepstakes", 0.3), 0.3);
+    }
+
+    #[test]
+    fn an_invalid_contest_table_rejects_the_whole_bundle() {
+        let mut incoming = AppSettings::default();
+        incoming
+            .contest
+            .contests
+            .insert("cqww".to_string(), contest_table(&[("user_zone", "99")]));
+        let error = AppSettings::default().merged_with(incoming).err().unwrap();
+        assert!(error.contains("Your Zone"), "{}", error);
+    }
+}
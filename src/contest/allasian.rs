@@ -0,0 +1,391 @@
+use rand::{Rng, RngCore};
+use toml::value::Table;
+
+use super::callsign::{check_callsign_file, CallsignFile, CallsignPool, FallbackCallsignSource};
+use super::types::{
+    non_599_setting, pick_rst, CallsignSource, Contest, ContestHelp, Exchange, ExchangeField,
+    FieldKind, FileCheck, RejectedLine, SettingField, SettingFieldGroup, SettingFieldKind,
+    ValidationResult, NON_599_KEY,
+};
+use crate::cty::{base_callsign, Continent};
+use crate::data_update;
+
+pub const CONTEST_ID: &str = "allasian";
+pub const DISPLAY_NAME: &str = "All Asian DX CW";
+
+/// What a YL operator sends in place of her age
+const YL_AGE: &str = "00";
+/// Share of callers (percent) who are YL operators and send 00
+const YL_PERCENT: u32 = 6;
+/// Youngest age the callers send; the spread above it leans towards the
+/// fifties and sixties, as the field does
+const MIN_AGE: u32 = 25;
+
+/// All Asian DX CW (JARL): everyone outside Asia works Asian stations
+/// Exchange: RST + operator's age, or 00 from a YL
+pub struct AllAsianContest;
+
+pub fn make_contest() -> Box<dyn Contest> {
+    Box::new(AllAsianContest::new())
+}
+
+impl AllAsianContest {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string()
+    }
+}
+
+/// The age a station sends, the same every time it's worked: 25 to 85,
+/// most often around 55, or 00 for a YL
+fn age_for_callsign(callsign: &str) -> String {
+    // FNV-1a over the station's own call, so K1ABC/7 is as old as K1ABC
+    let hash = base_callsign(callsign)
+        .bytes()
+        .fold(0x811c_9dc5u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        });
+    if hash % 100 < YL_PERCENT {
+        return YL_AGE.to_string();
+    }
+    let age = MIN_AGE + (hash >> 8) % 31 + (hash >> 16) % 31;
+    age.to_string()
+}
+
+fn normalize_rst(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'E' => '5',
+            'N' => '9',
+            'T' => '0',
+            _ => c,
+        })
+        .collect()
+}
+
+/// An age as copied, with cut numbers; 0 and 00 are both the YL's 00
+fn parse_age(value: &str) -> Option<u32> {
+    let digits: String = value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'T' | 'O' => '0',
+            'N' => '9',
+            'A' => '1',
+            _ => c,
+        })
+        .collect();
+    if digits.is_empty() || digits.len() > 2 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Callers from a callsign file, keeping only the Asian calls since
+/// stations outside Asia work only Asia
+struct AllAsianCallsignSource {
+    pool: CallsignPool,
+}
+
+impl AllAsianCallsignSource {
+    fn default_pool() -> Self {
+        let (pool, _) = CallsignPool::parse(
+            "JA1ZLO\nJH1NBN\nJA3YBK\nJE1CKA\nJR2PMT\n7K4XNS\nJA0DET\nJH4UTP\n\
+             JA7YRR\nJA9XBW\nHL1VAU\nDS4DRE\nHL5FUA\nBV1EK\nBA4TB\nBD7JNA\n\
+             VU2PTT\nHS0ZDX\n9M2TO\n9V1YC\n4X6TT\nA61ZX\nUA9CDC\nRA9AAA\n\
+             UA0SC\nRZ9WU\nEX8AB\nUN9L\nJT1CO\nXV9NPS",
+        );
+        Self { pool }
+    }
+}
+
+impl CallsignFile for AllAsianCallsignSource {
+    const NOTHING_VALID: &'static str = "No Asian callsigns found in file";
+
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (mut pool, rejects) = CallsignPool::parse(content);
+        let cty = data_update::cty();
+        pool.retain(|call| cty.lookup_continent(call) == Some(Continent::Asia));
+        (Self { pool }, rejects)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.pool.call_count()
+    }
+}
+
+impl CallsignSource for AllAsianCallsignSource {
+    fn random(
+        &mut self,
+        contest: &dyn Contest,
+        serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<(String, Exchange)> {
+        let callsign = self.pool.random(rng)?;
+        let exchange = contest.generate_exchange(&callsign, serial, settings, rng);
+        Some((callsign, exchange))
+    }
+}
+
+impl Contest for AllAsianContest {
+    fn id(&self) -> &'static str {
+        CONTEST_ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        DISPLAY_NAME
+    }
+
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "Run by the JARL. Stations outside Asia work only Asian \
+                    stations. The exchange is a signal report and the \
+                    operator's age; YL operators may send 00 instead.",
+            example_exchange: "5NN 56 / 5NN 00",
+            typical_wpm: Some((24, 32)),
+        })
+    }
+
+    fn exchange_fields(&self) -> Vec<ExchangeField> {
+        vec![
+            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN")
+                .cut_numbers(),
+            ExchangeField::new("Age", "56", 2, FieldKind::Number).focus_on_enter(),
+        ]
+    }
+
+    fn settings_fields(&self) -> Vec<SettingField> {
+        vec![
+            SettingField {
+                key: "cq_message",
+                label: "CQ Message",
+                placeholder: "CQ AA",
+                width_chars: 12,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "callsign_file",
+                label: "Callsign File",
+                placeholder: "callsigns.txt",
+                width_chars: 24,
+                kind: SettingFieldKind::FilePath,
+                group: SettingFieldGroup::Contest,
+            },
+            non_599_setting(),
+            SettingField {
+                key: "user_age",
+                label: "Your Age",
+                placeholder: "00 for a YL",
+                width_chars: 2,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::UserExchange,
+            },
+        ]
+    }
+
+    fn default_settings(&self) -> toml::Value {
+        let mut table = Table::new();
+        table.insert(
+            "cq_message".to_string(),
+            toml::Value::String("CQ AA".to_string()),
+        );
+        table.insert(
+            "callsign_file".to_string(),
+            toml::Value::String("callsigns.txt".to_string()),
+        );
+        table.insert(
+            "user_age".to_string(),
+            toml::Value::String("50".to_string()),
+        );
+        table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
+        toml::Value::Table(table)
+    }
+
+    fn validate_settings(&self, settings: &toml::Value) -> Result<(), String> {
+        let age = Self::get_string(settings, "user_age", "50");
+        if parse_age(&age).is_none() || !age.trim().chars().all(|c| c.is_ascii_digit()) {
+            return Err("Your Age must be one or two digits (00 for a YL)".to_string());
+        }
+        Ok(())
+    }
+
+    fn cq_message(&self, settings: &toml::Value) -> String {
+        Self::get_string(settings, "cq_message", "CQ AA")
+    }
+
+    fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
+        let path = Self::get_string(settings, "callsign_file", "callsigns.txt");
+        match AllAsianCallsignSource::load(&path) {
+            Ok(source) => Ok(Box::new(source)),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                AllAsianCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
+        }
+    }
+
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<AllAsianCallsignSource>(path, self, settings)
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => {
+                let rst = normalize_rst(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            1 => parse_age(value).is_some(),
+            _ => true,
+        }
+    }
+
+    fn generate_exchange(&self, callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
+        let mut age = age_for_callsign(callsign);
+        // Now and then a YL's 00 is sent as a lone 0
+        if age == YL_AGE && rng.gen_bool(0.2) {
+            age = "0".to_string();
+        }
+        Exchange::new(vec![pick_rst(settings, rng), age])
+    }
+
+    fn user_exchange_fields(
+        &self,
+        _user_callsign: &str,
+        _serial: u32,
+        settings: &toml::Value,
+    ) -> Vec<String> {
+        let age = parse_age(&Self::get_string(settings, "user_age", "50")).unwrap_or(50);
+        vec!["5NN".to_string(), format!("{:02}", age)]
+    }
+
+    fn validate(
+        &self,
+        expected_call: &str,
+        expected_exchange: &Exchange,
+        received_call: &str,
+        received_fields: &[String],
+        _settings: &toml::Value,
+    ) -> ValidationResult {
+        let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
+
+        let rst_ok = match (expected_exchange.fields.first(), received_fields.first()) {
+            (Some(expected), Some(received)) => normalize_rst(expected) == normalize_rst(received),
+            _ => false,
+        };
+
+        let expected_age = expected_exchange.fields.get(1).and_then(|a| parse_age(a));
+        let received_age = received_fields.get(1).and_then(|a| parse_age(a));
+        let age_ok = expected_age.is_some() && expected_age == received_age;
+
+        // 00 is a YL, not an age; say so when one was taken for the other
+        let remarks = match (expected_age, received_age) {
+            (Some(0), Some(received)) if received != 0 => vec![format!(
+                "{} sent 00: a YL operator, not an age",
+                expected_call
+            )],
+            (Some(expected), Some(0)) if expected != 0 => vec![format!(
+                "{} sent age {}, not a YL's 00",
+                expected_call, expected
+            )],
+            _ => Vec::new(),
+        };
+
+        let exchange_correct = rst_ok && age_ok;
+        ValidationResult {
+            callsign_correct,
+            exchange_correct,
+            field_correct: vec![rst_ok, age_ok],
+            points: if callsign_correct && exchange_correct {
+                1
+            } else {
+                0
+            },
+            remarks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logged(age: &str) -> Vec<String> {
+        vec!["599".to_string(), age.to_string()]
+    }
+
+    #[test]
+    fn each_station_keeps_a_plausible_age() {
+        let contest = AllAsianContest::new();
+        let settings = contest.default_settings();
+        let mut yls = 0;
+        for n in 0..500 {
+            let call = format!("JA{}X{}", n % 10, n);
+            let age = age_for_callsign(&call);
+            assert_eq!(age, age_for_callsign(&format!("{}/1", call)));
+            if age == YL_AGE {
+                yls += 1;
+                continue;
+            }
+            let age: u32 = age.parse().unwrap();
+            assert!((25..=85).contains(&age), "{}", age);
+            let sent = contest.generate_exchange(&call, 1, &settings, &mut rand::thread_rng());
+            assert_eq!(sent.fields[1], age.to_string());
+        }
+        assert!(yls > 0 && yls < 100, "{} YLs", yls);
+    }
+
+    #[test]
+    fn yl_zeros_are_copied_as_such() {
+        let contest = AllAsianContest::new();
+        let settings = contest.default_settings();
+        let yl = Exchange::new(vec!["5NN".to_string(), "00".to_string()]);
+        for copy in ["00", "0", "TT"] {
+            let result = contest.validate("JA1ZLO", &yl, "JA1ZLO", &logged(copy), &settings);
+            assert!(result.exchange_correct, "{}", copy);
+            assert!(result.remarks.is_empty());
+        }
+        let result = contest.validate("JA1ZLO", &yl, "JA1ZLO", &logged("50"), &settings);
+        assert_eq!(result.field_correct, vec![true, false]);
+        assert_eq!(result.remarks, ["JA1ZLO sent 00: a YL operator, not an age"]);
+
+        let om = Exchange::new(vec!["5NN".to_string(), "56".to_string()]);
+        let result = contest.validate("JA1ZLO", &om, "JA1ZLO", &logged("00"), &settings);
+        assert!(!result.exchange_correct);
+        assert_eq!(result.remarks, ["JA1ZLO sent age 56, not a YL's 00"]);
+        let result = contest.validate("JA1ZLO", &om, "JA1ZLO", &logged("56"), &settings);
+        assert_eq!(result.points, 1);
+
+        assert!(contest.field_input_valid(1, "00"));
+        assert!(!contest.field_input_valid(1, "123"));
+        assert_eq!(
+            contest.user_exchange_fields("W1AW", 1, &settings),
+            vec!["5NN", "50"]
+        );
+    }
+
+    #[test]
+    fn only_asian_calls_are_callers() {
+        let (source, _) = AllAsianCallsignSource::parse("JA1ZLO\nK1ABC\nHL1VAU\nDL1ABC\n");
+        assert_eq!(source.entry_count(), 2);
+        assert_eq!(AllAsianCallsignSource::default_pool().entry_count(), 30);
+    }
+}
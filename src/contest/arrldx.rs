@@ -1,13 +1,17 @@
 use std::collections::HashSet;
-use std::path::Path;
 
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, RngCore};
 use toml::value::Table;
 
+use super::callsign::{
+    check_callsign_file, parse_activity_weight, parse_lines, CallsignFile, FallbackCallsignSource,
+    DEFAULT_ACTIVITY_WEIGHT,
+};
 use super::types::{
-    CallsignSource, Contest, Exchange, ExchangeField, FieldKind, SettingField, SettingFieldGroup,
-    SettingFieldKind, ValidationResult,
+    non_599_setting, pick_rst, CallsignSource, Contest, ContestHelp, Exchange, ExchangeField, FieldKind,
+    FileCheck, RejectedLine, SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult,
+    NON_599_KEY,
 };
 
 pub const CONTEST_ID: &str = "arrldx";
@@ -31,12 +35,21 @@ impl ArrlDxContest {
             .unwrap_or(default)
             .to_string()
     }
+
+    /// Whether we send our power (DX) rather than a state or province
+    /// (W/VE)
+    fn sends_power(settings: &toml::Value) -> bool {
+        let exchange = Self::get_string(settings, "user_exchange", "CT");
+        exchange.chars().any(|c| c.is_ascii_digit()) || exchange.eq_ignore_ascii_case("KW")
+    }
 }
 
 #[derive(Clone, Debug)]
 struct ArrlDxStation {
     callsign: String,
     exchange: String,
+    /// Activity weight; higher comes up more often
+    weight: f32,
 }
 
 struct ArrlDxCallsignSource {
@@ -44,71 +57,76 @@ struct ArrlDxCallsignSource {
     used: HashSet<String>,
 }
 
-impl ArrlDxCallsignSource {
-    fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        let content = std::fs::read_to_string(path)?;
-        let stations: Vec<ArrlDxStation> = content
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .filter(|line| !line.starts_with('#') && !line.starts_with('!'))
-            .filter_map(|line| {
-                let fields: Vec<&str> = line.split(',').collect();
-                if fields.len() < 4 {
-                    return None;
-                }
-
-                let callsign = fields.get(0).unwrap_or(&"").trim().to_uppercase();
-                let state = fields.get(2).unwrap_or(&"").trim().to_uppercase();
-                let power = fields.get(3).unwrap_or(&"").trim().to_uppercase();
-
-                let has_state = !state.is_empty();
-                let has_power = !power.is_empty();
-
-                if (has_state && has_power) || (!has_state && !has_power) {
-                    return None;
-                }
-
-                if callsign.is_empty() {
-                    return None;
-                }
-
-                let exchange = if has_state { state } else { power };
-
-                Some(ArrlDxStation { callsign, exchange })
-            })
-            .collect();
+impl CallsignFile for ArrlDxCallsignSource {
+    const NOTHING_VALID: &'static str = "No valid ARRL DX callsigns found in file";
 
-        if stations.is_empty() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "No valid ARRL DX callsigns found in file",
-            ));
-        }
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (stations, rejects) = parse_lines(content, |line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() < 4 {
+                return Err("expected Call,Name,State,Power".to_string());
+            }
+
+            let callsign = fields.get(0).unwrap_or(&"").trim().to_uppercase();
+            let state = fields.get(2).unwrap_or(&"").trim().to_uppercase();
+            let power = fields.get(3).unwrap_or(&"").trim().to_uppercase();
 
-        Ok(Self {
+            let has_state = !state.is_empty();
+            let has_power = !power.is_empty();
+
+            if has_state && has_power {
+                return Err("has both a state and a power".to_string());
+            }
+            if !has_state && !has_power {
+                return Err("needs a state or a power".to_string());
+            }
+
+            if callsign.is_empty() {
+                return Err("missing callsign".to_string());
+            }
+
+            let exchange = if has_state { state } else { power };
+
+            Ok(ArrlDxStation {
+                callsign,
+                exchange,
+                weight: parse_activity_weight(fields.get(5).copied()),
+            })
+        });
+        let source = Self {
             stations,
             used: HashSet::new(),
-        })
+        };
+        (source, rejects)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.stations.len()
     }
+}
 
+impl ArrlDxCallsignSource {
     fn default_pool() -> Self {
         let stations = vec![
             ArrlDxStation {
                 callsign: "VE2FK".to_string(),
                 exchange: "QC".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             ArrlDxStation {
                 callsign: "K3LR".to_string(),
                 exchange: "PA".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             ArrlDxStation {
                 callsign: "DL1ABC".to_string(),
                 exchange: "100".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             ArrlDxStation {
                 callsign: "JA1ABC".to_string(),
                 exchange: "500".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
         ];
 
@@ -118,7 +136,7 @@ impl ArrlDxCallsignSource {
         }
     }
 
-    fn random_station(&mut self) -> Option<ArrlDxStation> {
+    fn random_station(&mut self, rng: &mut dyn RngCore) -> Option<ArrlDxStation> {
         let available: Vec<_> = self
             .stations
             .iter()
@@ -127,9 +145,13 @@ impl ArrlDxCallsignSource {
 
         let station = if available.is_empty() {
             self.used.clear();
-            self.stations.choose(&mut rand::thread_rng())?
+            self.stations
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         } else {
-            *available.choose(&mut rand::thread_rng())?
+            *available
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         };
 
         self.used.insert(station.callsign.clone());
@@ -142,17 +164,23 @@ impl CallsignSource for ArrlDxCallsignSource {
         &mut self,
         _contest: &dyn Contest,
         _serial: u32,
-        _settings: &toml::Value,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
     ) -> Option<(String, Exchange)> {
-        let station = self.random_station()?;
+        let station = self.random_station(rng)?;
         Some((
             station.callsign.clone(),
             Exchange::new(vec![
-                pick_rst().to_string(),
-                maybe_t_substitute_power(&station.exchange),
+                pick_rst(settings, rng),
+                maybe_t_substitute_power(&station.exchange, rng),
             ]),
         ))
     }
+
+    fn history(&self, callsign: &str) -> Option<Vec<String>> {
+        let station = self.stations.iter().find(|s| s.callsign == callsign)?;
+        Some(vec![String::new(), station.exchange.clone()])
+    }
 }
 
 fn normalize_cw_digits(value: &str) -> String {
@@ -182,24 +210,13 @@ fn normalize_rst(value: &str) -> String {
         .collect()
 }
 
-fn pick_rst() -> &'static str {
-    let roll = rand::thread_rng().gen_range(0..100);
-    if roll < 5 {
-        "ENN"
-    } else if roll < 15 {
-        "599"
-    } else {
-        "5NN"
-    }
-}
-
-fn maybe_t_substitute_power(exchange: &str) -> String {
+fn maybe_t_substitute_power(exchange: &str, rng: &mut dyn RngCore) -> String {
     let trimmed = exchange.trim();
     if trimmed.is_empty() || !trimmed.chars().all(|c| c.is_ascii_digit()) {
         return trimmed.to_string();
     }
 
-    let roll = rand::thread_rng().gen_range(0..100);
+    let roll = rng.gen_range(0..100);
     if roll < 80 {
         trimmed
             .chars()
@@ -219,9 +236,21 @@ impl Contest for ArrlDxContest {
         DISPLAY_NAME
     }
 
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "W/VE stations work DX stations, and DX works W/VE only. \
+                    W/VE send a signal report and their state or province; \
+                    DX sends a signal report and power. Each QSO is worth 3 \
+                    points.",
+            example_exchange: "5NN MA / 5NN 100",
+            typical_wpm: Some((28, 36)),
+        })
+    }
+
     fn exchange_fields(&self) -> Vec<ExchangeField> {
         vec![
-            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN"),
+            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN")
+                .cut_numbers(),
             ExchangeField::new("Exchange", "ST/PWR", 6, FieldKind::Alnum).focus_on_enter(),
         ]
     }
@@ -244,6 +273,7 @@ impl Contest for ArrlDxContest {
                 kind: SettingFieldKind::FilePath,
                 group: SettingFieldGroup::Contest,
             },
+            non_599_setting(),
             SettingField {
                 key: "user_exchange",
                 label: "Your Exchange",
@@ -269,6 +299,7 @@ impl Contest for ArrlDxContest {
             "user_exchange".to_string(),
             toml::Value::String("CT".to_string()),
         );
+        table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
         toml::Value::Table(table)
     }
 
@@ -280,13 +311,55 @@ impl Contest for ArrlDxContest {
         let path = Self::get_string(settings, "callsign_file", "arrldx_callsigns.txt");
         match ArrlDxCallsignSource::load(&path) {
             Ok(source) => Ok(Box::new(source)),
-            Err(_) => Ok(Box::new(ArrlDxCallsignSource::default_pool())),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                ArrlDxCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
         }
     }
 
-    fn generate_exchange(&self, _callsign: &str, _serial: u32, settings: &toml::Value) -> Exchange {
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<ArrlDxCallsignSource>(path, self, settings)
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => {
+                let rst = normalize_rst(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            _ => true,
+        }
+    }
+
+    fn generate_exchange(&self, _callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
         let exchange = Self::get_string(settings, "user_exchange", "CT");
-        Exchange::new(vec![pick_rst().to_string(), exchange])
+        Exchange::new(vec![pick_rst(settings, rng), exchange])
+    }
+
+    fn fill_requests(&self, settings: &toml::Value) -> Vec<Option<String>> {
+        let request = if Self::sends_power(settings) {
+            "PWR?"
+        } else {
+            "ST?"
+        };
+        vec![None, Some(request.to_string())]
+    }
+
+    fn repeat_request(&self, index: usize, settings: &toml::Value) -> String {
+        // The callers are on the other side of the contest from us
+        match index {
+            0 => "RST?",
+            _ if Self::sends_power(settings) => "ST?",
+            _ => "PWR?",
+        }
+        .to_string()
     }
 
     fn user_exchange_fields(
@@ -326,11 +399,13 @@ impl Contest for ArrlDxContest {
         ValidationResult {
             callsign_correct,
             exchange_correct,
+            field_correct: vec![rst_ok, exchange_ok],
             points: if callsign_correct && exchange_correct {
                 1
             } else {
                 0
             },
+            remarks: Vec::new(),
         }
     }
 }
@@ -1,46 +1,142 @@
 use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
 use std::collections::HashSet;
 use std::path::Path;
 
-use super::types::{CallsignSource, Contest, Exchange};
+use super::types::{CallsignSource, Contest, Exchange, FileCheck, RejectedLine};
+use crate::config::SyntheticCallSettings;
+use crate::cty::{base_callsign, CtyDat};
 
-/// Pool of callsigns loaded from file
+/// Weight given to calls without an activity column
+pub const DEFAULT_ACTIVITY_WEIGHT: f32 = 1.0;
+
+/// Parse an optional activity-weight column. Blank, missing, or invalid
+/// values (including zero and negatives) fall back to the default weight.
+pub fn parse_activity_weight(field: Option<&str>) -> f32 {
+    field
+        .and_then(|f| f.trim().parse::<f32>().ok())
+        .filter(|w| w.is_finite() && *w > 0.0)
+        .unwrap_or(DEFAULT_ACTIVITY_WEIGHT)
+}
+
+/// Basic callsign validation. Portable forms such as `F/K1ABC`,
+/// `K1ABC/7` or `VP2E/NT0X/P` are accepted when the station's own call is.
+pub fn is_valid_callsign(call: &str) -> bool {
+    let parts: Vec<&str> = call.split('/').collect();
+    if parts.len() > 3
+        || parts
+            .iter()
+            .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return false;
+    }
+    let base = base_callsign(call);
+    (3..=10).contains(&base.len())
+        && base.chars().any(|c| c.is_ascii_alphabetic())
+        && base.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Parse the entry lines of a callsign file, skipping blank lines and `#`
+/// or `!` comments. Lines `parse` turns down are kept with its reason.
+pub fn parse_lines<T>(
+    content: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> (Vec<T>, Vec<RejectedLine>) {
+    let mut entries = Vec::new();
+    let mut rejects = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+        match parse(line) {
+            Ok(entry) => entries.push(entry),
+            Err(reason) => rejects.push(RejectedLine {
+                line: index + 1,
+                text: line.to_string(),
+                reason,
+            }),
+        }
+    }
+    (entries, rejects)
+}
+
+/// A callsign source read from a file in the contest's own format
+pub trait CallsignFile: CallsignSource + Sized {
+    /// Why loading fails when nothing in the file can be used
+    const NOTHING_VALID: &'static str;
+
+    /// Read a file's contents, with the lines that had to be skipped
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>);
+
+    /// How many entries callers can be made from
+    fn entry_count(&self) -> usize;
+
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let content = std::fs::read_to_string(path)?;
+        let (source, _) = Self::parse(&content);
+        if source.entry_count() == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                Self::NOTHING_VALID,
+            ));
+        }
+        Ok(source)
+    }
+}
+
+/// Read `path` as an `S` file for `contest`, reporting what it holds and
+/// making one caller from it
+pub fn check_callsign_file<S: CallsignFile>(
+    path: &str,
+    contest: &dyn Contest,
+    settings: &toml::Value,
+) -> Result<FileCheck, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (mut source, rejects) = S::parse(&content);
+    let valid = source.entry_count();
+    let sample = if valid > 0 {
+        source.random(contest, 1, settings, &mut rand::thread_rng())
+    } else {
+        None
+    };
+    Ok(FileCheck {
+        valid,
+        rejects,
+        sample,
+    })
+}
+
+/// Pool of callsigns loaded from file, each with an activity weight
 pub struct CallsignPool {
-    callsigns: Vec<String>,
+    callsigns: Vec<(String, f32)>,
     used: HashSet<String>,
 }
 
 impl CallsignPool {
-    /// Load callsigns from a file
+    /// Read a callsign file's contents, with the lines that were skipped
     ///
     /// Supported formats:
     /// - One callsign per line
-    /// - Lines starting with # are comments
+    /// - Lines starting with # or ! are comments
     /// - Empty lines are ignored
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        let content = std::fs::read_to_string(path)?;
-        let callsigns: Vec<String> = content
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty() && !line.starts_with('#'))
-            .map(|line| {
-                // Handle CSV format - take first field
-                line.split(',').next().unwrap_or(line).trim().to_uppercase()
-            })
-            .filter(|call| Self::is_valid_callsign(call))
-            .collect();
-
-        if callsigns.is_empty() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "No valid callsigns found in file",
-            ));
-        }
-
-        Ok(Self {
+    /// - An optional second CSV field sets the activity weight
+    ///   (e.g. `K3LR,25`); active calls come up more often
+    pub fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (callsigns, rejects) = parse_lines(content, |line| {
+            // Handle CSV format - callsign first, optional weight second
+            let mut fields = line.split(',');
+            let call = fields.next().unwrap_or(line).trim().to_uppercase();
+            if !is_valid_callsign(&call) {
+                return Err(format!("{} is not a valid callsign", call));
+            }
+            Ok((call, parse_activity_weight(fields.next())))
+        });
+        let pool = Self {
             callsigns,
             used: HashSet::new(),
-        })
+        };
+        (pool, rejects)
     }
 
     /// Create a pool with default callsigns (for when no file is available)
@@ -53,7 +149,7 @@ impl CallsignPool {
             "JA1ABC", "JH1NBN", "PY2SEX", "LU1FAM", "ZS6EZ", "VK2GR", "ZL1BQD",
         ]
         .into_iter()
-        .map(String::from)
+        .map(|call| (call.to_string(), DEFAULT_ACTIVITY_WEIGHT))
         .collect();
 
         Self {
@@ -62,34 +158,36 @@ impl CallsignPool {
         }
     }
 
-    /// Get a random callsign (avoiding recently used ones)
-    pub fn random(&mut self) -> Option<String> {
+    /// Keep only the calls `keep` accepts
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.callsigns.retain(|(call, _)| keep(call));
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.callsigns.len()
+    }
+
+    /// Get a random callsign, weighted by activity (avoiding recently used ones)
+    pub fn random(&mut self, rng: &mut dyn RngCore) -> Option<String> {
         let available: Vec<_> = self
             .callsigns
             .iter()
-            .filter(|c| !self.used.contains(*c))
+            .filter(|(c, _)| !self.used.contains(c))
             .collect();
 
         if available.is_empty() {
             // Reset if all used
             self.used.clear();
-            return self.callsigns.choose(&mut rand::thread_rng()).cloned();
+            return self
+                .callsigns
+                .choose_weighted(rng, |(_, w)| *w)
+                .ok()
+                .map(|(c, _)| c.clone());
         }
 
-        let call = (*available.choose(&mut rand::thread_rng())?).clone();
+        let (call, _) = *available.choose_weighted(rng, |(_, w)| *w).ok()?;
         self.used.insert(call.clone());
-        Some(call)
-    }
-
-    /// Basic callsign validation
-    fn is_valid_callsign(call: &str) -> bool {
-        if call.len() < 3 || call.len() > 10 {
-            return false;
-        }
-        // Must contain at least one letter and one number
-        call.chars().any(|c| c.is_ascii_alphabetic())
-            && call.chars().any(|c| c.is_ascii_digit())
-            && call.chars().all(|c| c.is_ascii_alphanumeric() || c == '/')
+        Some(call.clone())
     }
 }
 
@@ -99,12 +197,6 @@ pub struct FileCallsignSource {
 }
 
 impl FileCallsignSource {
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        Ok(Self {
-            pool: CallsignPool::load(path)?,
-        })
-    }
-
     pub fn default_pool() -> Self {
         Self {
             pool: CallsignPool::default_pool(),
@@ -112,15 +204,253 @@ impl FileCallsignSource {
     }
 }
 
+impl CallsignFile for FileCallsignSource {
+    const NOTHING_VALID: &'static str = "No valid callsigns found in file";
+
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (pool, rejects) = CallsignPool::parse(content);
+        (Self { pool }, rejects)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.pool.callsigns.len()
+    }
+}
+
 impl CallsignSource for FileCallsignSource {
     fn random(
         &mut self,
         contest: &dyn Contest,
         serial: u32,
         settings: &toml::Value,
+        rng: &mut dyn RngCore,
     ) -> Option<(String, Exchange)> {
-        let callsign = self.pool.random()?;
-        let exchange = contest.generate_exchange(&callsign, serial, settings);
+        let callsign = self.pool.random(rng)?;
+        let exchange = contest.generate_exchange(&callsign, serial, settings, rng);
         Some((callsign, exchange))
     }
 }
+
+/// A source standing in for a file that couldn't be used, remembering why
+/// so the user can be told
+pub struct FallbackCallsignSource {
+    inner: Box<dyn CallsignSource>,
+    reason: String,
+    /// What is used instead, as it reads after "using"
+    using: &'static str,
+}
+
+impl FallbackCallsignSource {
+    /// A built-in pool in place of the callsign file at `path`
+    pub fn new(
+        inner: impl CallsignSource + 'static,
+        path: &str,
+        error: impl std::fmt::Display,
+    ) -> Self {
+        Self::using(inner, path, error, "the built-in calls")
+    }
+
+    pub fn using(
+        inner: impl CallsignSource + 'static,
+        path: &str,
+        error: impl std::fmt::Display,
+        using: &'static str,
+    ) -> Self {
+        Self {
+            inner: Box::new(inner),
+            reason: format!("{}: {}", path, error),
+            using,
+        }
+    }
+}
+
+impl CallsignSource for FallbackCallsignSource {
+    fn random(
+        &mut self,
+        contest: &dyn Contest,
+        serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<(String, Exchange)> {
+        self.inner.random(contest, serial, settings, rng)
+    }
+
+    fn history(&self, callsign: &str) -> Option<Vec<String>> {
+        self.inner.history(callsign)
+    }
+
+    fn fallback(&self) -> Option<(&str, &'static str)> {
+        Some((&self.reason, self.using))
+    }
+}
+
+/// Portable indicators a generated call may carry, besides a call-area digit
+const PORTABLE_SUFFIXES: [&str; 3] = ["/P", "/QRP", "/M"];
+
+/// Longest cty.dat prefix used to build calls; longer ones are mostly
+/// special-event and island calls
+const MAX_SYNTHETIC_PREFIX_LEN: usize = 3;
+
+/// Callsigns made up from cty.dat prefixes, so there is no finite pool to
+/// memorize. Prefixes without a digit get a random call area.
+pub struct SyntheticCallsignSource {
+    prefixes: Vec<String>,
+    settings: SyntheticCallSettings,
+}
+
+impl SyntheticCallsignSource {
+    pub fn new(cty: &CtyDat, settings: SyntheticCallSettings) -> Self {
+        let prefixes = cty
+            .prefixes()
+            .filter(|prefix| {
+                prefix.len() <= MAX_SYNTHETIC_PREFIX_LEN
+                    && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+                    && prefix.chars().any(|c| c.is_ascii_alphabetic())
+                    // The call area digit ends the prefix, or is added after it
+                    && (prefix.ends_with(|c: char| c.is_ascii_digit())
+                        || !prefix.chars().skip(1).any(|c| c.is_ascii_digit()))
+            })
+            .map(str::to_string)
+            .collect();
+        Self { prefixes, settings }
+    }
+
+    pub fn generate(&self, rng: &mut dyn RngCore) -> Option<String> {
+        let mut call = self.prefixes.choose(rng)?.clone();
+        // The digit after the first character (as in 9A1 or K1) is the call area
+        if !call.chars().skip(1).any(|c| c.is_ascii_digit()) {
+            call.push(char::from(b'0' + rng.gen_range(0..10u8)));
+        }
+
+        let suffix_len = [1, 2, 3]
+            .choose_weighted(rng, |len| {
+                self.settings.suffix_length_weights[len - 1].max(0.0)
+            })
+            .copied()
+            .unwrap_or(2);
+        for _ in 0..suffix_len {
+            call.push(char::from(b'A' + rng.gen_range(0..26u8)));
+        }
+
+        if rng.gen::<f32>() < self.settings.portable_probability {
+            if rng.gen::<bool>() {
+                call.push_str(PORTABLE_SUFFIXES.choose(rng)?);
+            } else {
+                call.push('/');
+                call.push(char::from(b'0' + rng.gen_range(0..10u8)));
+            }
+        }
+        Some(call)
+    }
+}
+
+impl CallsignSource for SyntheticCallsignSource {
+    fn random(
+        &mut self,
+        contest: &dyn Contest,
+        serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<(String, Exchange)> {
+        let callsign = self.generate(rng)?;
+        let exchange = contest.generate_exchange(&callsign, serial, settings, rng);
+        Some((callsign, exchange))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_weight_parsing() {
+        assert_eq!(parse_activity_weight(Some(" 25 ")), 25.0);
+        assert_eq!(parse_activity_weight(Some("0.5")), 0.5);
+        assert_eq!(parse_activity_weight(Some("")), DEFAULT_ACTIVITY_WEIGHT);
+        assert_eq!(parse_activity_weight(Some("0")), DEFAULT_ACTIVITY_WEIGHT);
+        assert_eq!(parse_activity_weight(Some("abc")), DEFAULT_ACTIVITY_WEIGHT);
+        assert_eq!(parse_activity_weight(None), DEFAULT_ACTIVITY_WEIGHT);
+    }
+
+    #[test]
+    fn active_calls_come_up_first() {
+        let mut heavy_first = 0;
+        for _ in 0..200 {
+            let mut pool = CallsignPool {
+                callsigns: vec![("K3LR".to_string(), 50.0), ("W1XYZ".to_string(), 1.0)],
+                used: HashSet::new(),
+            };
+            if pool.random(&mut rand::thread_rng()).as_deref() == Some("K3LR") {
+                heavy_first += 1;
+            }
+        }
+        assert!(heavy_first > 170, "heavy call first {} of 200", heavy_first);
+    }
+
+    #[test]
+    fn unusable_lines_are_reported() {
+        let (pool, rejects) = CallsignPool::parse("# calls\nK3LR,25\n\nNOTACALL\n!skip\nw1aw\n");
+        assert_eq!(pool.callsigns.len(), 2);
+        assert_eq!(
+            rejects,
+            vec![RejectedLine {
+                line: 4,
+                text: "NOTACALL".to_string(),
+                reason: "NOTACALL is not a valid callsign".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn portable_calls_are_valid() {
+        for call in [
+            "K1ABC",
+            "F/K1ABC",
+            "K1ABC/7",
+            "VP2E/NT0X",
+            "K1ABC/QRP",
+            "F/K1ABC/P",
+        ] {
+            assert!(is_valid_callsign(call), "{}", call);
+        }
+        for call in ["K1ABC/", "/K1ABC", "ABC/P", "K1-ABC", "F/K1ABC/P/M"] {
+            assert!(!is_valid_callsign(call), "{}", call);
+        }
+    }
+
+    #[test]
+    fn synthetic_calls_follow_the_settings() {
+        let cty = CtyDat::parse(include_str!("../../data/cty.dat"));
+        let source = SyntheticCallsignSource::new(
+            &cty,
+            SyntheticCallSettings {
+                enabled: true,
+                suffix_length_weights: [0.0, 0.0, 1.0],
+                portable_probability: 0.0,
+            },
+        );
+        for _ in 0..200 {
+            let call = source.generate(&mut rand::thread_rng()).unwrap();
+            assert!(is_valid_callsign(&call), "{}", call);
+            assert!(cty.lookup_prefix(&call).is_some(), "{}", call);
+            let suffix: String = call
+                .chars()
+                .rev()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect();
+            assert_eq!(suffix.len(), 3, "{}", call);
+        }
+
+        let portable = SyntheticCallsignSource::new(
+            &cty,
+            SyntheticCallSettings {
+                portable_probability: 1.0,
+                ..SyntheticCallSettings::default()
+            },
+        );
+        assert!((0..20).all(|_| portable
+            .generate(&mut rand::thread_rng())
+            .unwrap()
+            .contains('/')));
+    }
+}
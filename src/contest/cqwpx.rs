@@ -1,10 +1,12 @@
-use rand::Rng;
+use rand::{Rng, RngCore};
 use toml::value::Table;
 
-use super::callsign::FileCallsignSource;
+use super::callsign::{
+    check_callsign_file, CallsignFile, FallbackCallsignSource, FileCallsignSource,
+};
 use super::types::{
-    CallsignSource, Contest, Exchange, ExchangeField, FieldKind, SettingField, SettingFieldGroup,
-    SettingFieldKind, ValidationResult,
+    non_599_setting, pick_rst, CallsignSource, Contest, ContestHelp, Exchange, ExchangeField, FieldKind,
+    FileCheck, SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult, NON_599_KEY,
 };
 
 pub const CONTEST_ID: &str = "cqwpx";
@@ -96,17 +98,6 @@ fn parse_serial(value: &str) -> Option<u32> {
     normalized.parse::<u32>().ok()
 }
 
-fn pick_rst() -> &'static str {
-    let roll = rand::thread_rng().gen_range(0..100);
-    if roll < 5 {
-        "ENN"
-    } else if roll < 15 {
-        "599"
-    } else {
-        "5NN"
-    }
-}
-
 impl Contest for CqWpxContest {
     fn id(&self) -> &'static str {
         CONTEST_ID
@@ -116,10 +107,23 @@ impl Contest for CqWpxContest {
         DISPLAY_NAME
     }
 
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "Everyone works everyone. The exchange is a signal report \
+                    and a serial number counting up from 001; each new prefix \
+                    is a multiplier.",
+            example_exchange: "5NN 123",
+            typical_wpm: Some((28, 36)),
+        })
+    }
+
     fn exchange_fields(&self) -> Vec<ExchangeField> {
         vec![
-            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN"),
-            ExchangeField::new("SER", "SER", 5, FieldKind::Alnum).focus_on_enter(),
+            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN")
+                .cut_numbers(),
+            ExchangeField::new("SER", "SER", 5, FieldKind::Alnum)
+                .focus_on_enter()
+                .cut_numbers(),
         ]
     }
 
@@ -141,6 +145,7 @@ impl Contest for CqWpxContest {
                 kind: SettingFieldKind::FilePath,
                 group: SettingFieldGroup::Contest,
             },
+            non_599_setting(),
             SettingField {
                 key: "serial_min",
                 label: "Serial Min",
@@ -184,6 +189,7 @@ impl Contest for CqWpxContest {
             "serial_max".to_string(),
             toml::Value::Integer(SERIAL_MAX_DEFAULT),
         );
+        table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
         toml::Value::Table(table)
     }
 
@@ -210,18 +216,62 @@ impl Contest for CqWpxContest {
         Self::get_string(settings, "cq_message", "CQ TEST")
     }
 
+    fn synthetic_calls(&self) -> bool {
+        true
+    }
+
     fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
         let path = Self::get_string(settings, "callsign_file", "callsigns.txt");
         match FileCallsignSource::load(&path) {
             Ok(source) => Ok(Box::new(source)),
-            Err(_) => Ok(Box::new(FileCallsignSource::default_pool())),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                FileCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
         }
     }
 
-    fn generate_exchange(&self, _callsign: &str, _serial: u32, settings: &toml::Value) -> Exchange {
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<FileCallsignSource>(path, self, settings)
+    }
+
+    fn generate_exchange(&self, _callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
         let (min, max) = Self::serial_range(settings);
-        let serial = rand::thread_rng().gen_range(min..=max);
-        Exchange::new(vec![pick_rst().to_string(), Self::format_serial(serial)])
+        let serial = rng.gen_range(min..=max);
+        Exchange::new(vec![pick_rst(settings, rng), Self::format_serial(serial)])
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => {
+                let rst = normalize_rst(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            1 => parse_serial(value).is_some(),
+            _ => true,
+        }
+    }
+
+    fn caller_serial_index(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    fn format_caller_serial(&self, serial: u32) -> String {
+        Self::format_serial(serial)
+    }
+
+    fn fill_requests(&self, _settings: &toml::Value) -> Vec<Option<String>> {
+        vec![None, Some("NR?".to_string())]
+    }
+
+    fn repeat_request(&self, index: usize, _settings: &toml::Value) -> String {
+        if index == 0 { "RST?" } else { "NR?" }.to_string()
     }
 
     fn user_exchange_fields(
@@ -258,11 +308,13 @@ impl Contest for CqWpxContest {
         ValidationResult {
             callsign_correct,
             exchange_correct,
+            field_correct: vec![rst_ok, serial_ok],
             points: if callsign_correct && exchange_correct {
                 1
             } else {
                 0
             },
+            remarks: Vec::new(),
         }
     }
 }
@@ -1,18 +1,26 @@
-use rand::Rng;
+use rand::{Rng, RngCore};
+use std::sync::Arc;
 use toml::value::Table;
 
-use super::callsign::FileCallsignSource;
+use super::callsign::{
+    check_callsign_file, CallsignFile, FallbackCallsignSource, FileCallsignSource,
+};
 use super::types::{
-    Contest, Exchange, ExchangeField, FieldKind, SettingField, SettingFieldGroup, SettingFieldKind,
-    ValidationResult,
+    non_599_setting, pick_rst, Contest, ContestHelp, Exchange, ExchangeField, FieldKind, FileCheck,
+    SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult, NON_599_KEY,
 };
 use crate::cty::CtyDat;
+use crate::data_update;
 
 pub const CONTEST_ID: &str = "cqww";
 pub const DISPLAY_NAME: &str = "CQ World Wide";
 
+/// Chance a caller sends a zone next to the one cty.dat gives its call, as
+/// stations near a zone line (a W1 in zone 4, say) do on the air
+const UNUSUAL_ZONE_CHANCE: f64 = 0.03;
+
 pub struct CqWwContest {
-    cty: CtyDat,
+    cty: Arc<CtyDat>,
 }
 
 pub fn make_contest() -> Box<dyn Contest> {
@@ -21,10 +29,10 @@ pub fn make_contest() -> Box<dyn Contest> {
 
 impl CqWwContest {
     pub fn new() -> Self {
-        // Load embedded cty.dat
-        let cty_data = include_str!("../../data/cty.dat");
-        let cty = CtyDat::parse(cty_data);
-        Self { cty }
+        // Downloaded cty.dat, or the one built in
+        Self {
+            cty: data_update::cty(),
+        }
     }
 
     /// Determine CQ zone from callsign using CTY database
@@ -32,6 +40,55 @@ impl CqWwContest {
         self.cty.lookup_cq_zone(callsign).unwrap_or(5)
     }
 
+    /// The zone we send: the one set in the settings, or else the one
+    /// cty.dat gives our call
+    fn user_zone(&self, user_callsign: &str, settings: &toml::Value) -> u8 {
+        Self::zone_override(settings)
+            .or_else(|| self.cty.lookup_cq_zone(user_callsign.trim()))
+            .unwrap_or(5)
+    }
+
+    /// A zone set in the settings; empty (or unreadable) leaves it to the call
+    fn zone_override(settings: &toml::Value) -> Option<u8> {
+        Self::get_string(settings, "user_zone", "")
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|zone| (1..=40).contains(zone))
+    }
+
+    /// The zone a caller sends: usually its call's zone, now and then one
+    /// next to it
+    fn zone_to_send(&self, callsign: &str, rng: &mut dyn RngCore) -> u8 {
+        let zone = self.zone_for_callsign(callsign);
+        if !rng.gen_bool(UNUSUAL_ZONE_CHANCE) {
+            return zone;
+        }
+        match zone {
+            1 => 2,
+            40 => 39,
+            _ if rng.gen_bool(0.5) => zone - 1,
+            _ => zone + 1,
+        }
+    }
+
+    /// Set a zone copy against the one cty.dat gives the call, to tell a
+    /// miscopy from a caller sending an unusual zone
+    fn zone_remark(&self, callsign: &str, sent: u8, received: Option<u8>) -> Option<String> {
+        let usual = self.cty.lookup_cq_zone(callsign)?;
+        let received = received?;
+        if sent == usual {
+            return (received != sent)
+                .then(|| format!("Zone miscopied: {} is in zone {}", callsign, usual));
+        }
+        let unusual = format!("{} sent zone {}, not its usual {}", callsign, sent, usual);
+        Some(if received == usual {
+            format!("{}: copy the zone rather than assume it", unusual)
+        } else {
+            unusual
+        })
+    }
+
     fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
         settings
             .get(key)
@@ -41,17 +98,6 @@ impl CqWwContest {
     }
 }
 
-fn pick_rst() -> &'static str {
-    let roll = rand::thread_rng().gen_range(0..100);
-    if roll < 5 {
-        "ENN"
-    } else if roll < 15 {
-        "599"
-    } else {
-        "5NN"
-    }
-}
-
 fn normalize_rst(value: &str) -> String {
     value
         .trim()
@@ -75,10 +121,22 @@ impl Contest for CqWwContest {
         DISPLAY_NAME
     }
 
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "Everyone works everyone. The exchange is a signal report and \
+                    your CQ zone; zones and countries are the multipliers.",
+            example_exchange: "5NN 5",
+            typical_wpm: Some((28, 38)),
+        })
+    }
+
     fn exchange_fields(&self) -> Vec<ExchangeField> {
         vec![
-            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN"),
-            ExchangeField::new("Zone", "05", 2, FieldKind::Number).focus_on_enter(),
+            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).with_default_value("5NN")
+                .cut_numbers(),
+            ExchangeField::new("Zone", "05", 2, FieldKind::Number)
+                .focus_on_enter()
+                .cut_numbers(),
         ]
     }
 
@@ -100,10 +158,11 @@ impl Contest for CqWwContest {
                 kind: SettingFieldKind::FilePath,
                 group: SettingFieldGroup::Contest,
             },
+            non_599_setting(),
             SettingField {
                 key: "user_zone",
                 label: "Your Zone",
-                placeholder: "05",
+                placeholder: "auto",
                 width_chars: 2,
                 kind: SettingFieldKind::Text,
                 group: SettingFieldGroup::UserExchange,
@@ -123,15 +182,31 @@ impl Contest for CqWwContest {
         );
         table.insert(
             "user_zone".to_string(),
-            toml::Value::String("05".to_string()),
+            toml::Value::String(String::new()),
         );
+        table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
         toml::Value::Table(table)
     }
 
+    fn validate_settings(&self, settings: &toml::Value) -> Result<(), String> {
+        let zone = Self::get_string(settings, "user_zone", "");
+        if !zone.trim().is_empty() && Self::zone_override(settings).is_none() {
+            return Err(
+                "Your Zone must be between 1 and 40, or empty to use your call's zone."
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
     fn cq_message(&self, settings: &toml::Value) -> String {
         Self::get_string(settings, "cq_message", "CQ TEST")
     }
 
+    fn synthetic_calls(&self) -> bool {
+        true
+    }
+
     fn callsign_source(
         &self,
         settings: &toml::Value,
@@ -139,23 +214,49 @@ impl Contest for CqWwContest {
         let path = Self::get_string(settings, "callsign_file", "callsigns.txt");
         match FileCallsignSource::load(&path) {
             Ok(source) => Ok(Box::new(source)),
-            Err(_) => Ok(Box::new(FileCallsignSource::default_pool())),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                FileCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
         }
     }
 
-    fn generate_exchange(&self, callsign: &str, _serial: u32, _settings: &toml::Value) -> Exchange {
-        let zone = self.zone_for_callsign(callsign);
-        Exchange::new(vec![pick_rst().to_string(), format!("{:02}", zone)])
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<FileCallsignSource>(path, self, settings)
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => {
+                let rst = normalize_rst(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            1 => value
+                .parse::<u8>()
+                .map(|zone| (1..=40).contains(&zone))
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+
+    fn generate_exchange(&self, callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
+        let zone = self.zone_to_send(callsign, rng);
+        Exchange::new(vec![pick_rst(settings, rng), format!("{:02}", zone)])
     }
 
     fn user_exchange_fields(
         &self,
-        _user_callsign: &str,
+        user_callsign: &str,
         _serial: u32,
         settings: &toml::Value,
     ) -> Vec<String> {
-        let zone_str = Self::get_string(settings, "user_zone", "05");
-        let zone = zone_str.parse::<u8>().unwrap_or(5);
+        let zone = self.user_zone(user_callsign, settings);
         vec!["5NN".to_string(), format!("{:02}", zone)]
     }
 
@@ -189,15 +290,127 @@ impl Contest for CqWwContest {
         };
 
         let exchange_correct = rst_ok && zone_ok;
+        let remarks = expected_zone
+            .and_then(|sent| self.zone_remark(expected_call, sent, received_zone))
+            .into_iter()
+            .collect();
 
         ValidationResult {
             callsign_correct,
             exchange_correct,
+            field_correct: vec![rst_ok, zone_ok],
             points: if callsign_correct && exchange_correct {
                 1
             } else {
                 0
             },
+            remarks,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(zone: &str) -> Exchange {
+        Exchange::new(vec!["5NN".to_string(), zone.to_string()])
+    }
+
+    fn received(zone: &str) -> Vec<String> {
+        vec!["599".to_string(), zone.to_string()]
+    }
+
+    #[test]
+    fn zone_copy_is_set_against_cty() {
+        let contest = CqWwContest::new();
+        let settings = contest.default_settings();
+        assert_eq!(contest.zone_for_callsign("K1ABC"), 5);
+
+        // The usual zone, copied right: nothing to say
+        let result = contest.validate(
+            "K1ABC",
+            &exchange("05"),
+            "K1ABC",
+            &received("05"),
+            &settings,
+        );
+        assert!(result.exchange_correct);
+        assert!(result.remarks.is_empty());
+
+        // The usual zone, miscopied
+        let result = contest.validate(
+            "K1ABC",
+            &exchange("05"),
+            "K1ABC",
+            &received("15"),
+            &settings,
+        );
+        assert_eq!(result.field_correct, vec![true, false]);
+        assert_eq!(result.remarks, ["Zone miscopied: K1ABC is in zone 5"]);
+
+        // An unusual zone copied right, and assumed from the call
+        let result = contest.validate(
+            "K1ABC",
+            &exchange("04"),
+            "K1ABC",
+            &received("04"),
+            &settings,
+        );
+        assert!(result.exchange_correct);
+        assert_eq!(result.remarks, ["K1ABC sent zone 4, not its usual 5"]);
+        let result = contest.validate(
+            "K1ABC",
+            &exchange("04"),
+            "K1ABC",
+            &received("05"),
+            &settings,
+        );
+        assert!(!result.exchange_correct);
+        assert_eq!(
+            result.remarks,
+            ["K1ABC sent zone 4, not its usual 5: copy the zone rather than assume it"]
+        );
+    }
+
+    #[test]
+    fn our_zone_comes_from_our_call_unless_set() {
+        let contest = CqWwContest::new();
+        let mut settings = contest.default_settings();
+        assert!(contest.validate_settings(&settings).is_ok());
+        assert_eq!(contest.user_exchange_fields("K1ABC", 1, &settings), ["5NN", "05"]);
+        assert_eq!(contest.user_exchange_fields("dl1abc", 1, &settings), ["5NN", "14"]);
+        assert_eq!(contest.user_exchange_fields("JA1XYZ", 1, &settings), ["5NN", "25"]);
+
+        let table = settings.as_table_mut().unwrap();
+        table.insert("user_zone".to_string(), toml::Value::String("4".to_string()));
+        assert!(contest.validate_settings(&settings).is_ok());
+        assert_eq!(contest.user_exchange_fields("K1ABC", 1, &settings), ["5NN", "04"]);
+
+        let table = settings.as_table_mut().unwrap();
+        table.insert("user_zone".to_string(), toml::Value::String("41".to_string()));
+        assert!(contest.validate_settings(&settings).is_err());
+    }
+
+    #[test]
+    fn non_599_reports_must_be_copied() {
+        let contest = CqWwContest::new();
+        let mut settings = contest.default_settings();
+        assert!((0..50).all(|_| pick_rst(&settings, &mut rand::thread_rng()) != "57N"));
+        settings
+            .as_table_mut()
+            .unwrap()
+            .insert(NON_599_KEY.to_string(), toml::Value::Integer(50));
+        let reports: Vec<String> = (0..400).map(|_| pick_rst(&settings, &mut rand::thread_rng())).collect();
+        assert!(reports.iter().any(|r| normalize_rst(r) == "579"));
+        assert!(reports.iter().any(|r| normalize_rst(r) == "559"));
+
+        // Typing 599 out of habit doesn't match a 579
+        let sent = Exchange::new(vec!["57N".to_string(), "05".to_string()]);
+        let result = contest.validate("K1ABC", &sent, "K1ABC", &received("05"), &settings);
+        assert_eq!(result.field_correct, vec![false, true]);
+        let logged = vec!["579".to_string(), "05".to_string()];
+        let result = contest.validate("K1ABC", &sent, "K1ABC", &logged, &settings);
+        assert!(result.exchange_correct);
+    }
+}
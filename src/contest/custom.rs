@@ -0,0 +1,841 @@
+// Contests defined at runtime: one TOML file per contest in the user's
+// contests folder describes the exchange, how callers fill it in and how
+// the logged copy is checked, so a contest can be added without a rebuild
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use regex::Regex;
+use serde::Deserialize;
+use toml::value::Table;
+
+use super::callsign::{
+    check_callsign_file, CallsignFile, FallbackCallsignSource, FileCallsignSource,
+};
+use super::types::{
+    non_599_setting, pick_rst, CallsignSource, Contest, ContestDescriptor, ContestHelp, Exchange,
+    ExchangeField, FieldKind, FileCheck, SettingField, SettingFieldGroup, SettingFieldKind,
+    ValidationResult, NON_599_KEY,
+};
+use crate::config::AppSettings;
+use crate::cty::CtyDat;
+use crate::data_update;
+
+const DEFAULT_CQ_MESSAGE: &str = "CQ TEST";
+const DEFAULT_CALLSIGN_FILE: &str = "callsigns.txt";
+const DEFAULT_NUMBER_RANGE: (u32, u32) = (1, 99);
+const DEFAULT_SERIAL_RANGE: (u32, u32) = (1, 1500);
+
+/// How a field is generated for callers and checked when logged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomFieldKind {
+    /// One of `values`, compared as typed
+    #[default]
+    Text,
+    /// One of `values`, letters and digits
+    Alnum,
+    /// One of `values`, or a number between `min` and `max`
+    Number,
+    /// The caller's serial number, counting up from between `min` and `max`
+    Serial,
+    /// A signal report, 5NN by default
+    Rst,
+    /// The caller's CQ zone, from cty.dat
+    CqZone,
+}
+
+impl CustomFieldKind {
+    fn numeric(self) -> bool {
+        matches!(self, Self::Number | Self::Serial | Self::CqZone)
+    }
+}
+
+/// A field as written in the contest file
+#[derive(Debug, Deserialize)]
+struct FieldFile {
+    label: String,
+    #[serde(default)]
+    kind: CustomFieldKind,
+    placeholder: Option<String>,
+    width: Option<u8>,
+    #[serde(default)]
+    values: Vec<String>,
+    min: Option<u32>,
+    max: Option<u32>,
+    /// Regular expression a logged value must match in full
+    pattern: Option<String>,
+    /// Pre-filled in the entry box (e.g. 5NN)
+    default: Option<String>,
+    /// What the user sends for this field, until changed in Settings
+    user_value: Option<String>,
+    #[serde(default)]
+    focus: bool,
+}
+
+/// A contest file as written
+#[derive(Debug, Deserialize)]
+struct ContestFile {
+    id: String,
+    name: String,
+    cq_message: Option<String>,
+    callsign_file: Option<String>,
+    points: Option<u32>,
+    /// Shown with the contest for anyone who hasn't worked it
+    rules: Option<String>,
+    example_exchange: Option<String>,
+    /// Slowest and fastest speeds typical of the field, in WPM
+    typical_wpm: Option<[u32; 2]>,
+    #[serde(default, rename = "field")]
+    fields: Vec<FieldFile>,
+}
+
+#[derive(Clone, Debug)]
+struct CustomField {
+    label: &'static str,
+    placeholder: &'static str,
+    width_chars: u8,
+    kind: CustomFieldKind,
+    values: Vec<String>,
+    range: (u32, u32),
+    pattern: Option<Regex>,
+    default_value: Option<&'static str>,
+    focus_on_enter: bool,
+    /// Settings key and label for what the user sends, for fields the
+    /// user fills in themselves
+    user_setting: Option<(&'static str, &'static str)>,
+    user_value: String,
+}
+
+/// A contest loaded from a file
+#[derive(Clone, Debug)]
+pub struct CustomContestDef {
+    pub id: &'static str,
+    pub display_name: &'static str,
+    /// File the contest was loaded from
+    pub path: PathBuf,
+    cq_message: String,
+    callsign_file: String,
+    points: u32,
+    help: Option<ContestHelp>,
+    fields: Vec<CustomField>,
+}
+
+impl CustomContestDef {
+    pub fn descriptor(self: &Arc<Self>) -> ContestDescriptor {
+        let def = Arc::clone(self);
+        ContestDescriptor {
+            id: self.id,
+            display_name: self.display_name,
+            factory: Arc::new(move || Box::new(CustomContest::new(Arc::clone(&def)))),
+        }
+    }
+}
+
+/// Contest ids and labels outlive any one load; keep one copy of each so
+/// loading the same files again doesn't grow memory
+fn intern(value: &str) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = interned.get(value) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(value.to_string().into_boxed_str());
+    interned.insert(leaked);
+    leaked
+}
+
+/// Settings key for a label: "Power Class" -> "my_power_class"
+fn user_setting_key(label: &str) -> String {
+    let slug: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("my_{}", slug.trim_matches('_'))
+}
+
+/// Parse and check one contest file
+pub fn parse_definition(content: &str, path: &Path) -> Result<CustomContestDef, String> {
+    let file: ContestFile = toml::from_str(content).map_err(|e| e.to_string())?;
+
+    let id = file.id.trim();
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(format!(
+            "id \"{}\" must be lowercase letters, digits and _",
+            id
+        ));
+    }
+    if file.name.trim().is_empty() {
+        return Err("name is empty".to_string());
+    }
+    if file.fields.is_empty() {
+        return Err("no [[field]] entries".to_string());
+    }
+    if file
+        .fields
+        .iter()
+        .filter(|f| f.kind == CustomFieldKind::Serial)
+        .count()
+        > 1
+    {
+        return Err("only one field can be a serial".to_string());
+    }
+
+    let mut fields = Vec::with_capacity(file.fields.len());
+    for field in file.fields {
+        let label = field.label.trim();
+        if label.is_empty() {
+            return Err("a field has no label".to_string());
+        }
+        if matches!(field.kind, CustomFieldKind::Text | CustomFieldKind::Alnum)
+            && field.values.is_empty()
+        {
+            return Err(format!("field {}: values are needed for callers", label));
+        }
+        let default_range = match field.kind {
+            CustomFieldKind::Serial => DEFAULT_SERIAL_RANGE,
+            _ => DEFAULT_NUMBER_RANGE,
+        };
+        let range = (
+            field.min.unwrap_or(default_range.0),
+            field.max.unwrap_or(default_range.1),
+        );
+        if range.0 > range.1 {
+            return Err(format!("field {}: min is more than max", label));
+        }
+        let pattern = field
+            .pattern
+            .as_deref()
+            .map(|p| Regex::new(&format!("^(?:{})$", p)))
+            .transpose()
+            .map_err(|e| format!("field {}: bad pattern: {}", label, e))?;
+        let user_setting = match field.kind {
+            CustomFieldKind::Rst | CustomFieldKind::Serial => None,
+            _ => Some((
+                intern(&user_setting_key(label)),
+                intern(&format!("My {}", label)),
+            )),
+        };
+        let default_value = match (&field.default, field.kind) {
+            (Some(value), _) => Some(intern(&value.to_uppercase())),
+            (None, CustomFieldKind::Rst) => Some("5NN"),
+            (None, _) => None,
+        };
+        fields.push(CustomField {
+            label: intern(label),
+            placeholder: intern(field.placeholder.as_deref().unwrap_or(label)),
+            width_chars: field.width.unwrap_or(6),
+            kind: field.kind,
+            values: field.values.iter().map(|v| v.to_uppercase()).collect(),
+            range,
+            pattern,
+            default_value,
+            focus_on_enter: field.focus,
+            user_setting,
+            user_value: field.user_value.unwrap_or_default().to_uppercase(),
+        });
+    }
+
+    if matches!(file.typical_wpm, Some([low, high]) if low > high) {
+        return Err("typical_wpm is slowest first".to_string());
+    }
+    let help = (file.rules.is_some() || file.example_exchange.is_some()).then(|| ContestHelp {
+        rules: intern(file.rules.as_deref().unwrap_or_default().trim()),
+        example_exchange: intern(
+            &file
+                .example_exchange
+                .as_deref()
+                .unwrap_or_default()
+                .trim()
+                .to_uppercase(),
+        ),
+        typical_wpm: file.typical_wpm.map(|[low, high]| (low, high)),
+    });
+
+    Ok(CustomContestDef {
+        id: intern(id),
+        display_name: intern(file.name.trim()),
+        path: path.to_path_buf(),
+        cq_message: file
+            .cq_message
+            .unwrap_or_else(|| DEFAULT_CQ_MESSAGE.to_string()),
+        callsign_file: file
+            .callsign_file
+            .unwrap_or_else(|| DEFAULT_CALLSIGN_FILE.to_string()),
+        points: file.points.unwrap_or(1),
+        help,
+        fields,
+    })
+}
+
+/// The folder custom contest files are read from, next to the settings file
+pub fn contests_dir() -> PathBuf {
+    AppSettings::config_path().with_file_name("contests")
+}
+
+/// Contests loaded from the contests folder, and what went wrong with the
+/// files that couldn't be
+#[derive(Default)]
+pub struct CustomContests {
+    pub contests: Vec<Arc<CustomContestDef>>,
+    pub errors: Vec<String>,
+}
+
+/// Load every `.toml` file in `dir`. Ids must be unique and not taken by
+/// a built-in contest.
+pub fn load_dir(dir: &Path, builtin_ids: &[&str]) -> CustomContests {
+    let mut loaded = CustomContests::default();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return loaded;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("toml"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_definition(&content, &path));
+        match result {
+            Ok(def) if builtin_ids.contains(&def.id) => loaded.errors.push(format!(
+                "{}: id \"{}\" is a built-in contest",
+                path.display(),
+                def.id
+            )),
+            Ok(def) if loaded.contests.iter().any(|c| c.id == def.id) => {
+                loaded.errors.push(format!(
+                    "{}: id \"{}\" is already used by another file",
+                    path.display(),
+                    def.id
+                ))
+            }
+            Ok(def) => loaded.contests.push(Arc::new(def)),
+            Err(e) => loaded.errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    loaded
+}
+
+static LOADED: RwLock<Option<Arc<CustomContests>>> = RwLock::new(None);
+
+/// The custom contests, read from the contests folder on first use
+pub fn loaded() -> Arc<CustomContests> {
+    if let Some(loaded) = LOADED.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Arc::clone(loaded);
+    }
+    reload()
+}
+
+/// Read the contests folder again
+pub fn reload() -> Arc<CustomContests> {
+    let builtin: Vec<&str> = super::generated_contest_registry()
+        .iter()
+        .map(|entry| entry.id)
+        .collect();
+    let loaded = Arc::new(load_dir(&contests_dir(), &builtin));
+    *LOADED.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&loaded));
+    loaded
+}
+
+/// A contest described by a file
+pub struct CustomContest {
+    def: Arc<CustomContestDef>,
+    /// Only loaded when a field needs CQ zones
+    cty: Option<Arc<CtyDat>>,
+}
+
+impl CustomContest {
+    pub fn new(def: Arc<CustomContestDef>) -> Self {
+        let cty = def
+            .fields
+            .iter()
+            .any(|f| f.kind == CustomFieldKind::CqZone)
+            .then(data_update::cty);
+        Self { def, cty }
+    }
+
+    fn has_rst(&self) -> bool {
+        self.def
+            .fields
+            .iter()
+            .any(|f| f.kind == CustomFieldKind::Rst)
+    }
+
+    fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string()
+    }
+
+    fn caller_value(
+        &self,
+        field: &CustomField,
+        callsign: &str,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> String {
+        match field.kind {
+            CustomFieldKind::Rst => pick_rst(settings, rng),
+            CustomFieldKind::CqZone => self
+                .cty
+                .as_ref()
+                .and_then(|cty| cty.lookup_cq_zone(callsign))
+                .map(|zone| zone.to_string())
+                .unwrap_or_default(),
+            CustomFieldKind::Serial => format_serial(rng.gen_range(field.range.0..=field.range.1)),
+            CustomFieldKind::Number if field.values.is_empty() => {
+                rng.gen_range(field.range.0..=field.range.1).to_string()
+            }
+            _ => field.values.choose(rng).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+fn format_serial(serial: u32) -> String {
+    if serial < 100 {
+        format!("{:03}", serial)
+    } else {
+        serial.to_string()
+    }
+}
+
+/// Read cut numbers as digits (T=0, N=9, A=1, E=5)
+fn normalize_cw_digits(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'T' | 'O' => '0',
+            'N' => '9',
+            'A' => '1',
+            'E' => '5',
+            _ => c,
+        })
+        .collect()
+}
+
+fn parse_number(value: &str) -> Option<u32> {
+    let digits = normalize_cw_digits(value);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn values_match(kind: CustomFieldKind, expected: &str, received: &str) -> bool {
+    match kind {
+        CustomFieldKind::Rst => {
+            let expected = normalize_cw_digits(expected);
+            !expected.is_empty() && expected == normalize_cw_digits(received)
+        }
+        kind if kind.numeric() => {
+            parse_number(expected).is_some() && parse_number(expected) == parse_number(received)
+        }
+        _ => expected.trim().eq_ignore_ascii_case(received.trim()),
+    }
+}
+
+impl Contest for CustomContest {
+    fn id(&self) -> &'static str {
+        self.def.id
+    }
+
+    fn display_name(&self) -> &'static str {
+        self.def.display_name
+    }
+
+    fn help(&self) -> Option<ContestHelp> {
+        self.def.help
+    }
+
+    fn exchange_fields(&self) -> Vec<ExchangeField> {
+        self.def
+            .fields
+            .iter()
+            .map(|field| {
+                let kind = match field.kind {
+                    CustomFieldKind::Text | CustomFieldKind::Rst => FieldKind::Text,
+                    _ => FieldKind::Alnum,
+                };
+                let mut entry =
+                    ExchangeField::new(field.label, field.placeholder, field.width_chars, kind);
+                if let Some(value) = field.default_value {
+                    entry = entry.with_default_value(value);
+                }
+                if field.focus_on_enter {
+                    entry = entry.focus_on_enter();
+                }
+                if matches!(
+                    field.kind,
+                    CustomFieldKind::Rst | CustomFieldKind::Serial | CustomFieldKind::CqZone
+                ) {
+                    entry = entry.cut_numbers();
+                }
+                entry
+            })
+            .collect()
+    }
+
+    fn settings_fields(&self) -> Vec<SettingField> {
+        let mut fields = vec![
+            SettingField {
+                key: "cq_message",
+                label: "CQ Message",
+                placeholder: "CQ TEST",
+                width_chars: 12,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "callsign_file",
+                label: "Callsign File",
+                placeholder: "callsigns.txt",
+                width_chars: 24,
+                kind: SettingFieldKind::FilePath,
+                group: SettingFieldGroup::Contest,
+            },
+        ];
+        if self.has_rst() {
+            fields.push(non_599_setting());
+        }
+        fields.extend(self.def.fields.iter().filter_map(|field| {
+            let (key, label) = field.user_setting?;
+            Some(SettingField {
+                key,
+                label,
+                placeholder: field.placeholder,
+                width_chars: field.width_chars.max(4),
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::UserExchange,
+            })
+        }));
+        fields
+    }
+
+    fn default_settings(&self) -> toml::Value {
+        let mut table = Table::new();
+        table.insert(
+            "cq_message".to_string(),
+            toml::Value::String(self.def.cq_message.clone()),
+        );
+        table.insert(
+            "callsign_file".to_string(),
+            toml::Value::String(self.def.callsign_file.clone()),
+        );
+        if self.has_rst() {
+            table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
+        }
+        for field in &self.def.fields {
+            if let Some((key, _)) = field.user_setting {
+                table.insert(
+                    key.to_string(),
+                    toml::Value::String(field.user_value.clone()),
+                );
+            }
+        }
+        toml::Value::Table(table)
+    }
+
+    fn cq_message(&self, settings: &toml::Value) -> String {
+        Self::get_string(settings, "cq_message", &self.def.cq_message)
+    }
+
+    fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
+        let path = Self::get_string(settings, "callsign_file", &self.def.callsign_file);
+        match FileCallsignSource::load(&path) {
+            Ok(source) => Ok(Box::new(source)),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                FileCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
+        }
+    }
+
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<FileCallsignSource>(path, self, settings)
+    }
+
+    fn generate_exchange(
+        &self,
+        callsign: &str,
+        _serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Exchange {
+        Exchange::new(
+            self.def
+                .fields
+                .iter()
+                .map(|field| self.caller_value(field, callsign, settings, rng))
+                .collect(),
+        )
+    }
+
+    fn qso_points(&self) -> u32 {
+        self.def.points
+    }
+
+    fn caller_serial_index(&self) -> Option<usize> {
+        self.def
+            .fields
+            .iter()
+            .position(|f| f.kind == CustomFieldKind::Serial)
+    }
+
+    fn format_caller_serial(&self, serial: u32) -> String {
+        format_serial(serial)
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        let Some(field) = self.def.fields.get(index) else {
+            return true;
+        };
+        if let Some(pattern) = &field.pattern {
+            return pattern.is_match(&value.trim().to_uppercase());
+        }
+        match field.kind {
+            CustomFieldKind::Rst => {
+                let rst = normalize_cw_digits(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            kind if kind.numeric() => parse_number(value).is_some(),
+            _ => true,
+        }
+    }
+
+    fn user_exchange_fields(
+        &self,
+        _user_callsign: &str,
+        serial: u32,
+        settings: &toml::Value,
+    ) -> Vec<String> {
+        self.def
+            .fields
+            .iter()
+            .map(|field| match (field.kind, field.user_setting) {
+                (CustomFieldKind::Serial, _) => format_serial(serial),
+                (_, Some((key, _))) => {
+                    Self::get_string(settings, key, &field.user_value).to_uppercase()
+                }
+                _ => "5NN".to_string(),
+            })
+            .collect()
+    }
+
+    fn validate(
+        &self,
+        expected_call: &str,
+        expected_exchange: &Exchange,
+        received_call: &str,
+        received_fields: &[String],
+        _settings: &toml::Value,
+    ) -> ValidationResult {
+        let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
+        let field_correct: Vec<bool> = self
+            .def
+            .fields
+            .iter()
+            .enumerate()
+            .map(
+                |(i, field)| match (expected_exchange.fields.get(i), received_fields.get(i)) {
+                    (Some(expected), Some(received)) => {
+                        values_match(field.kind, expected, received)
+                    }
+                    _ => false,
+                },
+            )
+            .collect();
+        let exchange_correct = field_correct.iter().all(|ok| *ok);
+
+        ValidationResult {
+            callsign_correct,
+            exchange_correct,
+            field_correct,
+            points: if callsign_correct && exchange_correct {
+                self.def.points
+            } else {
+                0
+            },
+            remarks: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPRINT_LIKE: &str = r#"
+id = "club_sprint"
+name = "Club Sprint"
+cq_message = "CQ CS"
+points = 2
+rules = "Members work everyone for an hour."
+example_exchange = "5nn 12 100"
+typical_wpm = [22, 30]
+
+[[field]]
+label = "RST"
+kind = "rst"
+
+[[field]]
+label = "Nr"
+kind = "serial"
+min = 1
+max = 50
+focus = true
+
+[[field]]
+label = "Power"
+kind = "alnum"
+values = ["100", "5", "KW"]
+pattern = "[0-9]+|KW"
+user_value = "100"
+"#;
+
+    fn contest() -> CustomContest {
+        let def = parse_definition(SPRINT_LIKE, Path::new("club_sprint.toml")).unwrap();
+        CustomContest::new(Arc::new(def))
+    }
+
+    #[test]
+    fn definition_builds_fields_and_settings() {
+        let contest = contest();
+        assert_eq!(contest.id(), "club_sprint");
+        assert_eq!(contest.display_name(), "Club Sprint");
+        let help = contest.help().unwrap();
+        assert_eq!(help.example_exchange, "5NN 12 100");
+        assert_eq!(help.typical_wpm, Some((22, 30)));
+
+        let fields = contest.exchange_fields();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].default_value, Some("5NN"));
+        assert!(fields[1].focus_on_enter);
+        assert_eq!(contest.caller_serial_index(), Some(1));
+
+        let settings = contest.default_settings();
+        assert_eq!(contest.cq_message(&settings), "CQ CS");
+        assert_eq!(
+            settings.get("my_power").and_then(|v| v.as_str()),
+            Some("100")
+        );
+        assert_eq!(
+            contest.user_exchange_fields("K1ABC", 7, &settings),
+            vec!["5NN", "007", "100"]
+        );
+    }
+
+    #[test]
+    fn callers_follow_the_generation_rules() {
+        let contest = contest();
+        for _ in 0..50 {
+            let exchange = contest.generate_exchange(
+                "K1ABC",
+                1,
+                &toml::Value::Boolean(false),
+                &mut rand::thread_rng(),
+            );
+            let serial: u32 = exchange.fields[1].parse().unwrap();
+            assert!((1..=50).contains(&serial));
+            assert!(["100", "5", "KW"].contains(&exchange.fields[2].as_str()));
+        }
+    }
+
+    #[test]
+    fn logged_exchange_is_checked_per_field() {
+        let contest = contest();
+        let expected = Exchange::new(vec!["5NN".into(), "012".into(), "KW".into()]);
+        let settings = contest.default_settings();
+
+        let logged = ["599".to_string(), "T12".to_string(), "kw".to_string()];
+        let result = contest.validate("K1ABC", &expected, "K1ABC", &logged, &settings);
+        assert!(result.exchange_correct);
+        assert_eq!(result.points, 2);
+
+        let logged = ["5NN".to_string(), "13".to_string(), "KW".to_string()];
+        let result = contest.validate("K1ABC", &expected, "K1ABC", &logged, &settings);
+        assert_eq!(result.field_correct, vec![true, false, true]);
+        assert_eq!(result.points, 0);
+
+        assert!(contest.field_input_valid(2, "kw"));
+        assert!(!contest.field_input_valid(2, "QRP"));
+        assert!(contest.field_input_valid(1, "A2T"));
+        assert!(!contest.field_input_valid(1, "X"));
+    }
+
+    #[test]
+    fn bad_definitions_are_reported() {
+        let path = Path::new("bad.toml");
+        let err = |content: &str| parse_definition(content, path).unwrap_err();
+
+        assert!(err("id = \"x\"").contains("name"));
+        assert!(
+            err("id = \"Bad Id\"\nname = \"X\"\n[[field]]\nlabel = \"A\"\nkind = \"rst\"")
+                .contains("lowercase")
+        );
+        assert!(err("id = \"x\"\nname = \"X\"").contains("field"));
+        assert!(err("id = \"x\"\nname = \"X\"\n[[field]]\nlabel = \"Name\"").contains("values"));
+        assert!(err(
+            "id = \"x\"\nname = \"X\"\n[[field]]\nlabel = \"N\"\nkind = \"number\"\nmin = 9\nmax = 1"
+        )
+        .contains("min"));
+        assert!(err(
+            "id = \"x\"\nname = \"X\"\n[[field]]\nlabel = \"N\"\nkind = \"number\"\npattern = \"(\""
+        )
+        .contains("pattern"));
+        assert!(err(
+            "id = \"x\"\nname = \"X\"\ntypical_wpm = [30, 20]\n[[field]]\nlabel = \"A\"\nkind = \"rst\""
+        )
+        .contains("typical_wpm"));
+    }
+
+    #[test]
+    fn folder_loading_skips_taken_ids() {
+        let dir = std::env::temp_dir().join(format!("custom_contests_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.toml"), SPRINT_LIKE).unwrap();
+        std::fs::write(dir.join("b.toml"), SPRINT_LIKE).unwrap();
+        std::fs::write(
+            dir.join("c.toml"),
+            SPRINT_LIKE.replace("club_sprint", "cwt"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a contest").unwrap();
+
+        let loaded = load_dir(&dir, &["cwt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.contests.len(), 1);
+        assert_eq!(loaded.contests[0].id, "club_sprint");
+        assert_eq!(loaded.errors.len(), 2);
+        assert!(loaded.errors.iter().any(|e| e.contains("already used")));
+        assert!(loaded.errors.iter().any(|e| e.contains("built-in")));
+    }
+}
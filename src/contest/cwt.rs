@@ -1,16 +1,24 @@
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 
 use rand::seq::SliceRandom;
+use rand::RngCore;
 use toml::value::Table;
 
+use super::callsign::{
+    check_callsign_file, is_valid_callsign, parse_activity_weight, parse_lines, CallsignFile,
+    FallbackCallsignSource, DEFAULT_ACTIVITY_WEIGHT,
+};
 use super::types::{
-    CallsignSource, Contest, Exchange, ExchangeField, FieldKind, SettingField, SettingFieldGroup,
-    SettingFieldKind, ValidationResult,
+    CallsignSource, Contest, ContestHelp, Exchange, ExchangeField, FieldKind, FileCheck, RejectedLine,
+    SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult,
 };
+use crate::data_update::{self, DataFile};
 
 pub const CONTEST_ID: &str = "cwt";
 pub const DISPLAY_NAME: &str = "CWT";
+const DEFAULT_CALLSIGN_FILE: &str = "cwt_callsigns.txt";
+/// Setting for the file of calls seen in recent sessions
+const ACTIVITY_FILE_KEY: &str = "activity_file";
 
 /// CWT (CW Ops CW Test) contest
 /// Exchange: Name + Member Number (or state/country if not a member)
@@ -32,6 +40,55 @@ impl CwtContest {
             .unwrap_or(default)
             .to_string()
     }
+
+    /// The bundled callsign file gives way to a downloaded roster; a file
+    /// the user picked is always used as is
+    fn roster_path(path: &str) -> String {
+        match data_update::downloaded(DataFile::CwtRoster) {
+            Some(roster) if path == DEFAULT_CALLSIGN_FILE => roster.to_string_lossy().into_owned(),
+            _ => path.to_string(),
+        }
+    }
+
+    /// The roster from the settings, or the built-in stations if it can't
+    /// be read
+    fn roster(settings: &toml::Value) -> Result<CwtCallsignSource, FallbackCallsignSource> {
+        let path = Self::roster_path(&Self::get_string(
+            settings,
+            "callsign_file",
+            DEFAULT_CALLSIGN_FILE,
+        ));
+        CwtCallsignSource::load(&path)
+            .map_err(|e| FallbackCallsignSource::new(CwtCallsignSource::default_pool(), &path, e))
+    }
+}
+
+/// Calls seen in recent CWT sessions, each with the number of sessions it
+/// was in. A line is a call, optionally followed by its number of sessions;
+/// without one, each line counts as a session, so session logs can simply
+/// be appended to the file.
+fn parse_activity(content: &str) -> (HashMap<String, f32>, Vec<RejectedLine>) {
+    let (entries, rejects) = parse_lines(content, |line| {
+        let mut fields = line.split(',').map(|f| f.trim());
+        let callsign = fields.next().unwrap_or("").to_uppercase();
+        if !is_valid_callsign(&callsign) {
+            return Err(format!("{} is not a valid callsign", callsign));
+        }
+        let sessions = match fields.next().filter(|f| !f.is_empty()) {
+            Some(field) => field
+                .parse::<f32>()
+                .ok()
+                .filter(|n| n.is_finite() && *n > 0.0)
+                .ok_or_else(|| format!("{} is not a number of sessions", field))?,
+            None => 1.0,
+        };
+        Ok((callsign, sessions))
+    });
+    let mut activity = HashMap::new();
+    for (callsign, sessions) in entries {
+        *activity.entry(callsign).or_insert(0.0) += sessions;
+    }
+    (activity, rejects)
 }
 
 /// CWT station data (callsign + name + member number)
@@ -40,6 +97,8 @@ struct CwtStation {
     callsign: String,
     name: String,
     number: String,
+    /// Activity weight; higher comes up more often
+    weight: f32,
 }
 
 /// Pool of CWT stations with name/number data
@@ -48,49 +107,75 @@ struct CwtCallsignSource {
     used: HashSet<String>,
 }
 
-impl CwtCallsignSource {
-    /// Load CWT stations from a file
+impl CallsignFile for CwtCallsignSource {
+    const NOTHING_VALID: &'static str = "No valid CWT stations found in file";
+
+    /// Read CWT stations from a file's contents
     ///
-    /// Format: CSV with fields: callsign, name, number (member # or state/country)
+    /// Format: CSV with fields: callsign, name, number (member # or state/country),
+    /// user text, and an optional activity weight
     /// Lines starting with # or ! are ignored
     /// Only lines with non-blank first three fields are accepted
-    fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        let content = std::fs::read_to_string(path)?;
-        let stations: Vec<CwtStation> = content
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
-            .filter_map(|line| {
-                let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
-                if fields.len() >= 3 {
-                    let callsign = fields[0].to_uppercase();
-                    let name = fields[1].to_uppercase();
-                    let number = fields[2].to_uppercase();
-
-                    if !callsign.is_empty()
-                        && !name.is_empty()
-                        && !number.is_empty()
-                        && is_valid_callsign(&callsign)
-                    {
-                        return Some(CwtStation {
-                            callsign,
-                            name,
-                            number,
-                        });
-                    }
-                }
-                None
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (stations, rejects) = parse_lines(content, |line| {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() < 3 {
+                return Err("expected Call,Name,Number".to_string());
+            }
+            let callsign = fields[0].to_uppercase();
+            let name = fields[1].to_uppercase();
+            let number = fields[2].to_uppercase();
+
+            if name.is_empty() {
+                return Err("missing name".to_string());
+            }
+            if number.is_empty() {
+                return Err("missing number".to_string());
+            }
+            if callsign.is_empty() {
+                return Err("missing callsign".to_string());
+            }
+            if !is_valid_callsign(&callsign) {
+                return Err(format!("{} is not a valid callsign", callsign));
+            }
+            Ok(CwtStation {
+                callsign,
+                name,
+                number,
+                weight: parse_activity_weight(fields.get(4).copied()),
             })
-            .collect();
+        });
+        let source = Self {
+            stations,
+            used: HashSet::new(),
+        };
+        (source, rejects)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.stations.len()
+    }
+}
 
+impl CwtCallsignSource {
+    /// Members who turned up in `activity`, weighted by how often. Returns
+    /// None if none of them did.
+    fn active_only(&self, activity: &HashMap<String, f32>) -> Option<Self> {
+        let stations: Vec<CwtStation> = self
+            .stations
+            .iter()
+            .filter_map(|station| {
+                let sessions = activity.get(&station.callsign)?;
+                Some(CwtStation {
+                    weight: *sessions,
+                    ..station.clone()
+                })
+            })
+            .collect();
         if stations.is_empty() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "No valid CWT stations found in file",
-            ));
+            return None;
         }
-
-        Ok(Self {
+        Some(Self {
             stations,
             used: HashSet::new(),
         })
@@ -102,26 +187,31 @@ impl CwtCallsignSource {
                 callsign: "W1AW".to_string(),
                 name: "JOE".to_string(),
                 number: "1".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             CwtStation {
                 callsign: "K5ZD".to_string(),
                 name: "RANDY".to_string(),
                 number: "2".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             CwtStation {
                 callsign: "N1MM".to_string(),
                 name: "TOM".to_string(),
                 number: "100".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             CwtStation {
                 callsign: "K3LR".to_string(),
                 name: "TIM".to_string(),
                 number: "55".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             CwtStation {
                 callsign: "W9RE".to_string(),
                 name: "MIKE".to_string(),
                 number: "IN".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
         ];
 
@@ -131,7 +221,7 @@ impl CwtCallsignSource {
         }
     }
 
-    fn random_station(&mut self) -> Option<CwtStation> {
+    fn random_station(&mut self, rng: &mut dyn RngCore) -> Option<CwtStation> {
         let available: Vec<_> = self
             .stations
             .iter()
@@ -140,9 +230,13 @@ impl CwtCallsignSource {
 
         let station = if available.is_empty() {
             self.used.clear();
-            self.stations.choose(&mut rand::thread_rng())?
+            self.stations
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         } else {
-            *available.choose(&mut rand::thread_rng())?
+            *available
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         };
 
         self.used.insert(station.callsign.clone());
@@ -156,22 +250,19 @@ impl CallsignSource for CwtCallsignSource {
         _contest: &dyn Contest,
         _serial: u32,
         _settings: &toml::Value,
+        rng: &mut dyn RngCore,
     ) -> Option<(String, Exchange)> {
-        let station = self.random_station()?;
+        let station = self.random_station(rng)?;
         Some((
             station.callsign.clone(),
             Exchange::new(vec![station.name.clone(), station.number.clone()]),
         ))
     }
-}
 
-fn is_valid_callsign(call: &str) -> bool {
-    if call.len() < 3 || call.len() > 10 {
-        return false;
+    fn history(&self, callsign: &str) -> Option<Vec<String>> {
+        let station = self.stations.iter().find(|s| s.callsign == callsign)?;
+        Some(vec![station.name.clone(), station.number.clone()])
     }
-    call.chars().any(|c| c.is_ascii_alphabetic())
-        && call.chars().any(|c| c.is_ascii_digit())
-        && call.chars().all(|c| c.is_ascii_alphanumeric() || c == '/')
 }
 
 impl Contest for CwtContest {
@@ -183,6 +274,16 @@ impl Contest for CwtContest {
         DISPLAY_NAME
     }
 
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "A one-hour CW Ops session, held several times a week. \
+                    Members send their name and member number; non-members \
+                    send their name and state, province or country.",
+            example_exchange: "BOB 1234",
+            typical_wpm: Some((26, 34)),
+        })
+    }
+
     fn exchange_fields(&self) -> Vec<ExchangeField> {
         vec![
             ExchangeField::new("Name", "BOB", 8, FieldKind::Text),
@@ -203,7 +304,15 @@ impl Contest for CwtContest {
             SettingField {
                 key: "callsign_file",
                 label: "CWT Callsign File",
-                placeholder: "cwt_callsigns.txt",
+                placeholder: DEFAULT_CALLSIGN_FILE,
+                width_chars: 24,
+                kind: SettingFieldKind::FilePath,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: ACTIVITY_FILE_KEY,
+                label: "Recent Activity File",
+                placeholder: "",
                 width_chars: 24,
                 kind: SettingFieldKind::FilePath,
                 group: SettingFieldGroup::Contest,
@@ -235,7 +344,11 @@ impl Contest for CwtContest {
         );
         table.insert(
             "callsign_file".to_string(),
-            toml::Value::String("cwt_callsigns.txt".to_string()),
+            toml::Value::String(DEFAULT_CALLSIGN_FILE.to_string()),
+        );
+        table.insert(
+            ACTIVITY_FILE_KEY.to_string(),
+            toml::Value::String(String::new()),
         );
         table.insert(
             "user_name".to_string(),
@@ -253,11 +366,60 @@ impl Contest for CwtContest {
     }
 
     fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
-        let path = Self::get_string(settings, "callsign_file", "cwt_callsigns.txt");
-        match CwtCallsignSource::load(&path) {
-            Ok(source) => Ok(Box::new(source)),
-            Err(_) => Ok(Box::new(CwtCallsignSource::default_pool())),
+        let roster = match Self::roster(settings) {
+            Ok(roster) => roster,
+            Err(fallback) => return Ok(Box::new(fallback)),
+        };
+        // With an activity file, only members who have been turning up call
+        let activity_path = Self::get_string(settings, ACTIVITY_FILE_KEY, "");
+        if activity_path.trim().is_empty() {
+            return Ok(Box::new(roster));
+        }
+        let active = std::fs::read_to_string(&activity_path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                roster
+                    .active_only(&parse_activity(&content).0)
+                    .ok_or_else(|| "no roster members in the file".to_string())
+            });
+        match active {
+            Ok(active) => Ok(Box::new(active)),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::using(
+                roster,
+                &activity_path,
+                e,
+                "the whole roster",
+            ))),
+        }
+    }
+
+    fn check_callsign_file(
+        &self,
+        key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        if key != ACTIVITY_FILE_KEY {
+            return check_callsign_file::<CwtCallsignSource>(
+                &Self::roster_path(path),
+                self,
+                settings,
+            );
         }
+        let roster = Self::roster(settings).map_err(|fallback| {
+            fallback
+                .fallback()
+                .map(|(reason, _)| reason.to_string())
+                .unwrap_or_default()
+        })?;
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let (activity, rejects) = parse_activity(&content);
+        let mut active = roster.active_only(&activity);
+        Ok(FileCheck {
+            valid: active.as_ref().map_or(0, |a| a.entry_count()),
+            rejects,
+            sample: active.as_mut().and_then(|a| a.random(self, 1, settings, &mut rand::thread_rng())),
+        })
     }
 
     fn generate_exchange(
@@ -265,10 +427,19 @@ impl Contest for CwtContest {
         _callsign: &str,
         _serial: u32,
         _settings: &toml::Value,
+        _rng: &mut dyn RngCore,
     ) -> Exchange {
         Exchange::new(vec!["BOB".to_string(), "1234".to_string()])
     }
 
+    fn fill_requests(&self, _settings: &toml::Value) -> Vec<Option<String>> {
+        vec![Some("NAME?".to_string()), Some("NR?".to_string())]
+    }
+
+    fn repeat_request(&self, index: usize, _settings: &toml::Value) -> String {
+        if index == 0 { "NAME?" } else { "NR?" }.to_string()
+    }
+
     fn user_exchange_fields(
         &self,
         _user_callsign: &str,
@@ -290,25 +461,56 @@ impl Contest for CwtContest {
     ) -> ValidationResult {
         let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
 
-        let exchange_correct = if expected_exchange.fields.len() >= 2 && received_fields.len() >= 2
-        {
+        let field_correct = if expected_exchange.fields.len() >= 2 && received_fields.len() >= 2 {
             let name_correct =
                 received_fields[0].eq_ignore_ascii_case(&expected_exchange.fields[0]);
             let number_correct =
                 received_fields[1].eq_ignore_ascii_case(&expected_exchange.fields[1]);
-            name_correct && number_correct
+            vec![name_correct, number_correct]
         } else {
-            false
+            vec![false, false]
         };
+        let exchange_correct = field_correct.iter().all(|ok| *ok);
 
         ValidationResult {
             callsign_correct,
             exchange_correct,
+            field_correct,
             points: if callsign_correct && exchange_correct {
                 1
             } else {
                 0
             },
+            remarks: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_narrows_the_roster_to_regulars() {
+        let (roster, _) = CwtCallsignSource::parse("K9CT,Craig,276\nKB6NU,Dan,1418\nKR3E,Ed,133\n");
+        let (activity, rejects) =
+            parse_activity("# last four sessions\nK9CT\nk9ct\nKR3E,3\nW1XYZ\nKB6NU,lots\n");
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0].line, 6);
+
+        let active = roster.active_only(&activity).unwrap();
+        let weights: Vec<(&str, f32)> = active
+            .stations
+            .iter()
+            .map(|s| (s.callsign.as_str(), s.weight))
+            .collect();
+        assert_eq!(weights, [("K9CT", 2.0), ("KR3E", 3.0)]);
+        // Names and numbers still come from the roster
+        assert_eq!(
+            active.history("KR3E"),
+            Some(vec!["ED".to_string(), "133".to_string()])
+        );
+
+        assert!(roster.active_only(&HashMap::new()).is_none());
+    }
+}
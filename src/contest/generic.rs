@@ -0,0 +1,469 @@
+use rand::{Rng, RngCore};
+use toml::value::Table;
+
+use super::callsign::{
+    check_callsign_file, CallsignFile, FallbackCallsignSource, FileCallsignSource,
+};
+use super::types::{
+    non_599_setting, pick_rst, CallsignSource, Contest, ContestHelp, Exchange, ExchangeField,
+    FieldKind, FileCheck, SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult,
+    NON_599_KEY,
+};
+use crate::cty::base_callsign;
+
+pub const CONTEST_ID: &str = "generic";
+pub const DISPLAY_NAME: &str = "Generic Serial Contest";
+
+const SERIAL_MAX_DEFAULT: i64 = 500;
+const SERIAL_MAX_ALLOWED: i64 = 9999;
+
+/// One part of the exchange, as chosen in the settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Element {
+    Rst,
+    Serial,
+    /// Something each station sends the same every QSO, such as a state,
+    /// a member number or a power
+    Fixed,
+}
+
+/// A contest put together from settings for the many small contests
+/// nobody has written a module for: any of a signal report, a serial
+/// number and a fixed exchange, in that order
+pub struct GenericContest {
+    elements: Vec<Element>,
+}
+
+pub fn make_contest() -> Box<dyn Contest> {
+    Box::new(GenericContest::new())
+}
+
+impl GenericContest {
+    pub fn new() -> Self {
+        Self {
+            elements: vec![Element::Rst, Element::Serial],
+        }
+    }
+
+    fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string()
+    }
+
+    fn get_bool(settings: &toml::Value, key: &str) -> bool {
+        settings.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    fn serial_max(settings: &toml::Value) -> Option<i64> {
+        settings.get("serial_max").and_then(|v| v.as_integer()).or_else(|| {
+            settings
+                .get("serial_max")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.trim().parse::<i64>().ok())
+        })
+    }
+
+    /// The values callers pick their fixed exchange from
+    fn fixed_values(settings: &toml::Value) -> Vec<String> {
+        Self::get_string(settings, "fixed_values", "")
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_uppercase())
+            .collect()
+    }
+
+    fn elements_from(settings: &toml::Value) -> Vec<Element> {
+        [
+            ("send_rst", Element::Rst),
+            ("send_serial", Element::Serial),
+            ("send_fixed", Element::Fixed),
+        ]
+        .into_iter()
+        .filter(|(key, _)| Self::get_bool(settings, key))
+        .map(|(_, element)| element)
+        .collect()
+    }
+}
+
+/// FNV-1a over the station's own call, so a station sends the same fixed
+/// exchange however it signs
+fn call_hash(callsign: &str) -> u32 {
+    base_callsign(callsign)
+        .bytes()
+        .fold(0x811c_9dc5u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        })
+}
+
+fn normalize_rst(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'E' => '5',
+            'N' => '9',
+            'T' => '0',
+            _ => c,
+        })
+        .collect()
+}
+
+fn parse_serial(value: &str) -> Option<u32> {
+    let normalized: String = value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'T' => '0',
+            'N' => '9',
+            _ => c,
+        })
+        .collect();
+    if normalized.is_empty() || !normalized.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    normalized.parse::<u32>().ok()
+}
+
+impl Contest for GenericContest {
+    fn id(&self) -> &'static str {
+        CONTEST_ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        DISPLAY_NAME
+    }
+
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "Everyone works everyone. Choose the exchange in \
+                    Settings: a signal report, a serial number counting up \
+                    from 1, a fixed exchange each station always sends (a \
+                    state, member number or power), or any mix of them.",
+            example_exchange: "5NN 12 CT",
+            typical_wpm: None,
+        })
+    }
+
+    fn configure(&mut self, settings: &toml::Value) {
+        self.elements = Self::elements_from(settings);
+    }
+
+    fn exchange_fields(&self) -> Vec<ExchangeField> {
+        let mut fields: Vec<ExchangeField> = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                Element::Rst => {
+                    ExchangeField::new("RST", "5NN", 3, FieldKind::Text)
+                        .with_default_value("5NN")
+                        .cut_numbers()
+                }
+                Element::Serial => ExchangeField::new("NR", "NR", 5, FieldKind::Alnum).cut_numbers(),
+                Element::Fixed => ExchangeField::new("Exch", "EXCH", 8, FieldKind::Text),
+            })
+            .collect();
+        if let Some(first) = fields
+            .iter_mut()
+            .find(|field| field.default_value.is_none())
+        {
+            first.focus_on_enter = true;
+        }
+        fields
+    }
+
+    fn settings_fields(&self) -> Vec<SettingField> {
+        let toggle = |key, label| SettingField {
+            key,
+            label,
+            placeholder: "",
+            width_chars: 0,
+            kind: SettingFieldKind::Toggle,
+            group: SettingFieldGroup::Contest,
+        };
+        vec![
+            SettingField {
+                key: "cq_message",
+                label: "CQ Message",
+                placeholder: "CQ TEST",
+                width_chars: 12,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "callsign_file",
+                label: "Callsign File",
+                placeholder: "callsigns.txt",
+                width_chars: 24,
+                kind: SettingFieldKind::FilePath,
+                group: SettingFieldGroup::Contest,
+            },
+            toggle("send_rst", "Send RST"),
+            non_599_setting(),
+            toggle("send_serial", "Send Serial"),
+            SettingField {
+                key: "serial_max",
+                label: "Serial Max",
+                placeholder: "500",
+                width_chars: 5,
+                kind: SettingFieldKind::Integer {
+                    min: 1,
+                    max: SERIAL_MAX_ALLOWED,
+                },
+                group: SettingFieldGroup::Contest,
+            },
+            toggle("send_fixed", "Send Fixed Exchange"),
+            SettingField {
+                key: "fixed_values",
+                label: "Callers' Fixed Exchanges",
+                placeholder: "CT, MA, NY",
+                width_chars: 24,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "user_fixed",
+                label: "Your Fixed Exchange",
+                placeholder: "CT",
+                width_chars: 8,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::UserExchange,
+            },
+        ]
+    }
+
+    fn default_settings(&self) -> toml::Value {
+        let mut table = Table::new();
+        table.insert(
+            "cq_message".to_string(),
+            toml::Value::String("CQ TEST".to_string()),
+        );
+        table.insert(
+            "callsign_file".to_string(),
+            toml::Value::String("callsigns.txt".to_string()),
+        );
+        table.insert("send_rst".to_string(), toml::Value::Boolean(true));
+        table.insert(NON_599_KEY.to_string(), toml::Value::Integer(0));
+        table.insert("send_serial".to_string(), toml::Value::Boolean(true));
+        table.insert(
+            "serial_max".to_string(),
+            toml::Value::Integer(SERIAL_MAX_DEFAULT),
+        );
+        table.insert("send_fixed".to_string(), toml::Value::Boolean(false));
+        table.insert(
+            "fixed_values".to_string(),
+            toml::Value::String(String::new()),
+        );
+        table.insert(
+            "user_fixed".to_string(),
+            toml::Value::String(String::new()),
+        );
+        toml::Value::Table(table)
+    }
+
+    fn validate_settings(&self, settings: &toml::Value) -> Result<(), String> {
+        let elements = Self::elements_from(settings);
+        if elements.is_empty() {
+            return Err("Choose at least one of RST, serial and fixed exchange.".to_string());
+        }
+        if !Self::serial_max(settings).is_some_and(|max| (1..=SERIAL_MAX_ALLOWED).contains(&max)) {
+            return Err("Serial Max must be an integer between 1 and 9999.".to_string());
+        }
+        if elements.contains(&Element::Fixed) {
+            if Self::fixed_values(settings).is_empty() {
+                return Err("List at least one fixed exchange for callers to send.".to_string());
+            }
+            if Self::get_string(settings, "user_fixed", "").trim().is_empty() {
+                return Err("Your Fixed Exchange can't be empty.".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn cq_message(&self, settings: &toml::Value) -> String {
+        Self::get_string(settings, "cq_message", "CQ TEST")
+    }
+
+    fn synthetic_calls(&self) -> bool {
+        true
+    }
+
+    fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
+        let path = Self::get_string(settings, "callsign_file", "callsigns.txt");
+        match FileCallsignSource::load(&path) {
+            Ok(source) => Ok(Box::new(source)),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                FileCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
+        }
+    }
+
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<FileCallsignSource>(path, self, settings)
+    }
+
+    fn generate_exchange(&self, callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
+        let serial_max = Self::serial_max(settings)
+            .unwrap_or(SERIAL_MAX_DEFAULT)
+            .clamp(1, SERIAL_MAX_ALLOWED) as u32;
+        let fixed_values = Self::fixed_values(settings);
+        let fields = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                Element::Rst => pick_rst(settings, rng),
+                Element::Serial => rng.gen_range(1..=serial_max).to_string(),
+                Element::Fixed if fixed_values.is_empty() => String::new(),
+                Element::Fixed => {
+                    fixed_values[call_hash(callsign) as usize % fixed_values.len()].clone()
+                }
+            })
+            .collect();
+        Exchange::new(fields)
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match self.elements.get(index) {
+            Some(Element::Rst) => {
+                let rst = normalize_rst(value);
+                rst.len() == 3 && rst.chars().all(|c| c.is_ascii_digit())
+            }
+            Some(Element::Serial) => parse_serial(value).is_some(),
+            _ => true,
+        }
+    }
+
+    fn caller_serial_index(&self) -> Option<usize> {
+        self.elements
+            .iter()
+            .position(|element| *element == Element::Serial)
+    }
+
+    fn user_exchange_fields(
+        &self,
+        _user_callsign: &str,
+        serial: u32,
+        settings: &toml::Value,
+    ) -> Vec<String> {
+        self.elements
+            .iter()
+            .map(|element| match element {
+                Element::Rst => "5NN".to_string(),
+                Element::Serial => serial.to_string(),
+                Element::Fixed => Self::get_string(settings, "user_fixed", "")
+                    .trim()
+                    .to_uppercase(),
+            })
+            .collect()
+    }
+
+    fn validate(
+        &self,
+        expected_call: &str,
+        expected_exchange: &Exchange,
+        received_call: &str,
+        received_fields: &[String],
+        _settings: &toml::Value,
+    ) -> ValidationResult {
+        let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
+
+        let field_correct: Vec<bool> = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                let (Some(expected), Some(received)) = (
+                    expected_exchange.fields.get(index),
+                    received_fields.get(index),
+                ) else {
+                    return false;
+                };
+                match element {
+                    Element::Rst => normalize_rst(expected) == normalize_rst(received),
+                    Element::Serial => {
+                        parse_serial(expected).is_some()
+                            && parse_serial(expected) == parse_serial(received)
+                    }
+                    Element::Fixed => expected.eq_ignore_ascii_case(received.trim()),
+                }
+            })
+            .collect();
+        let exchange_correct = field_correct.iter().all(|ok| *ok);
+
+        ValidationResult {
+            callsign_correct,
+            exchange_correct,
+            field_correct,
+            points: if callsign_correct && exchange_correct {
+                1
+            } else {
+                0
+            },
+            remarks: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_exchange_follows_the_settings() {
+        let mut contest = GenericContest::new();
+        let mut settings = contest.default_settings();
+        contest.configure(&settings);
+        let labels: Vec<&str> = contest.exchange_fields().iter().map(|f| f.label).collect();
+        assert_eq!(labels, ["RST", "NR"]);
+        assert_eq!(contest.caller_serial_index(), Some(1));
+
+        let table = settings.as_table_mut().unwrap();
+        table.insert("send_rst".to_string(), toml::Value::Boolean(false));
+        table.insert("send_fixed".to_string(), toml::Value::Boolean(true));
+        assert!(contest.validate_settings(&settings).is_err());
+        let table = settings.as_table_mut().unwrap();
+        table.insert(
+            "fixed_values".to_string(),
+            toml::Value::String("CT, MA,NY".to_string()),
+        );
+        table.insert(
+            "user_fixed".to_string(),
+            toml::Value::String("ri".to_string()),
+        );
+        assert!(contest.validate_settings(&settings).is_ok());
+        contest.configure(&settings);
+        let fields = contest.exchange_fields();
+        assert_eq!(fields[0].label, "NR");
+        assert!(fields[0].focus_on_enter);
+        assert_eq!(contest.caller_serial_index(), Some(0));
+        assert_eq!(
+            contest.user_exchange_fields("K1ABC", 7, &settings),
+            ["7", "RI"]
+        );
+
+        // A station sends the same fixed exchange every time
+        let sent = contest.generate_exchange("W1AW", 1, &settings, &mut rand::thread_rng());
+        let again = contest.generate_exchange("W1AW", 2, &settings, &mut rand::thread_rng());
+        assert!(["CT", "MA", "NY"].contains(&sent.fields[1].as_str()));
+        assert_eq!(sent.fields[1], again.fields[1]);
+        let logged = vec![sent.fields[0].clone(), sent.fields[1].to_lowercase()];
+        let result = contest.validate("W1AW", &sent, "W1AW", &logged, &settings);
+        assert!(result.exchange_correct);
+
+        let table = settings.as_table_mut().unwrap();
+        table.insert("send_serial".to_string(), toml::Value::Boolean(false));
+        table.insert("send_fixed".to_string(), toml::Value::Boolean(false));
+        assert!(contest.validate_settings(&settings).is_err());
+    }
+}
@@ -1,18 +1,24 @@
 pub mod callsign;
+pub mod custom;
+pub mod sections;
 pub mod types;
 
 #[allow(unused_imports)]
-pub use callsign::{CallsignPool, FileCallsignSource};
+pub use callsign::{CallsignPool, FileCallsignSource, SyntheticCallsignSource};
 #[allow(unused_imports)]
 pub use types::{
-    normalize_exchange_input, CallsignSource, Contest, ContestDescriptor, Exchange, ExchangeField,
-    FieldKind, SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult,
+    cut_number_exchange, normalize_exchange_input, CallsignSource, Contest, ContestDescriptor,
+    ContestHelp, Exchange, ExchangeField, FieldKind, FileCheck, RejectedLine, SettingField,
+    SettingFieldGroup, SettingFieldKind, ValidationResult,
 };
 
 include!(concat!(env!("OUT_DIR"), "/contest_registry.rs"));
 
+/// Built-in contests, then those loaded from the custom contests folder
 pub fn registry() -> Vec<ContestDescriptor> {
-    generated_contest_registry()
+    let mut entries = generated_contest_registry();
+    entries.extend(custom::loaded().contests.iter().map(|def| def.descriptor()));
+    entries
 }
 
 pub fn create_contest(id: &str) -> Option<Box<dyn Contest>> {
@@ -0,0 +1,424 @@
+use rand::{Rng, RngCore};
+use toml::value::Table;
+
+use super::callsign::{check_callsign_file, CallsignFile, CallsignPool, FallbackCallsignSource};
+use super::types::{
+    CallsignSource, Contest, ContestHelp, Exchange, ExchangeField, FieldKind, FileCheck,
+    RejectedLine, SettingField, SettingFieldGroup, SettingFieldKind, ValidationResult,
+};
+use crate::cty::{base_callsign, portable_location, Continent};
+use crate::data_update;
+
+pub const CONTEST_ID: &str = "nasprint";
+pub const DISPLAY_NAME: &str = "NA Sprint CW";
+
+/// Serial numbers callers start from; a four-hour Sprint rarely gets far
+/// past a few hundred
+const SERIAL_RANGE: (u32, u32) = (1, 250);
+
+/// Names callers send, one per station
+const NAMES: &[&str] = &[
+    "AL", "ANN", "BILL", "BOB", "CHIP", "DAN", "DAVE", "DON", "ED", "FRED", "GARY", "GEORGE",
+    "HANK", "JACK", "JEFF", "JIM", "JOE", "JOHN", "KEN", "LARRY", "MARK", "MIKE", "NED", "PAT",
+    "PAUL", "RANDY", "RICH", "RON", "SAM", "STEVE", "SUE", "TED", "TIM", "TOM", "WAYNE",
+];
+
+/// US states by call area
+const US_STATES: [&[&str]; 10] = [
+    &["CO", "IA", "KS", "MN", "MO", "ND", "NE", "SD"],
+    &["CT", "MA", "ME", "NH", "RI", "VT"],
+    &["NJ", "NY"],
+    &["DC", "DE", "MD", "PA"],
+    &["AL", "FL", "GA", "KY", "NC", "SC", "TN", "VA"],
+    &["AR", "LA", "MS", "NM", "OK", "TX"],
+    &["CA"],
+    &["AZ", "ID", "MT", "NV", "OR", "UT", "WA", "WY"],
+    &["MI", "OH", "WV"],
+    &["IL", "IN", "WI"],
+];
+
+/// Canadian provinces and territories by call area
+const VE_PROVINCES: [&str; 10] = ["NU", "NS", "QC", "ON", "MB", "SK", "AB", "BC", "NT", "NB"];
+
+/// NA Sprint CW: North American stations work each other, and whoever
+/// calls CQ has to move after each QSO, leaving the frequency to the
+/// station that answered
+/// Exchange: both calls, serial number, name and state, province or
+/// country
+pub struct NaSprintContest;
+
+pub fn make_contest() -> Box<dyn Contest> {
+    Box::new(NaSprintContest::new())
+}
+
+impl NaSprintContest {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string()
+    }
+}
+
+/// FNV-1a over the station's own call, so a station keeps its name and
+/// location however it signs
+fn call_hash(callsign: &str) -> u32 {
+    base_callsign(callsign)
+        .bytes()
+        .fold(0x811c_9dc5u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// Where a station sends from: a state by call area, a province, AK or
+/// HI, or the country prefix elsewhere in North America
+fn location_for_callsign(callsign: &str) -> String {
+    let location = portable_location(callsign).unwrap_or_else(|| callsign.to_string());
+    let area = location
+        .chars()
+        .find(|c| c.is_ascii_digit())
+        .and_then(|c| c.to_digit(10))
+        .unwrap_or(0) as usize;
+    let prefix = data_update::cty()
+        .lookup_prefix(&location)
+        .unwrap_or_default();
+    match prefix.as_str() {
+        "K" => {
+            let states = US_STATES[area];
+            states[(call_hash(callsign) >> 8) as usize % states.len()].to_string()
+        }
+        "KL" => "AK".to_string(),
+        "KH6" => "HI".to_string(),
+        "VE" if location.starts_with("VO") => "NL".to_string(),
+        "VE" if location.starts_with("VY1") => "YT".to_string(),
+        "VE" if location.starts_with("VY2") => "PE".to_string(),
+        "VE" => VE_PROVINCES[area].to_string(),
+        "" => "DX".to_string(),
+        _ => prefix,
+    }
+}
+
+fn name_for_callsign(callsign: &str) -> &'static str {
+    NAMES[call_hash(callsign) as usize % NAMES.len()]
+}
+
+fn parse_serial(value: &str) -> Option<u32> {
+    let normalized: String = value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'T' => '0',
+            'N' => '9',
+            _ => c,
+        })
+        .collect();
+    if normalized.is_empty() || !normalized.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    normalized.parse::<u32>().ok()
+}
+
+/// Callers from a callsign file, keeping only the North American calls
+struct NaSprintCallsignSource {
+    pool: CallsignPool,
+}
+
+impl NaSprintCallsignSource {
+    fn default_pool() -> Self {
+        let mut pool = CallsignPool::default_pool();
+        Self::north_american(&mut pool);
+        Self { pool }
+    }
+
+    fn north_american(pool: &mut CallsignPool) {
+        let cty = data_update::cty();
+        pool.retain(|call| cty.lookup_continent(call) == Some(Continent::NorthAmerica));
+    }
+}
+
+impl CallsignFile for NaSprintCallsignSource {
+    const NOTHING_VALID: &'static str = "No North American callsigns found in file";
+
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (mut pool, rejects) = CallsignPool::parse(content);
+        Self::north_american(&mut pool);
+        (Self { pool }, rejects)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.pool.call_count()
+    }
+}
+
+impl CallsignSource for NaSprintCallsignSource {
+    fn random(
+        &mut self,
+        contest: &dyn Contest,
+        serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<(String, Exchange)> {
+        let callsign = self.pool.random(rng)?;
+        let exchange = contest.generate_exchange(&callsign, serial, settings, rng);
+        Some((callsign, exchange))
+    }
+}
+
+impl Contest for NaSprintContest {
+    fn id(&self) -> &'static str {
+        CONTEST_ID
+    }
+
+    fn display_name(&self) -> &'static str {
+        DISPLAY_NAME
+    }
+
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "North American stations work each other. Both calls go \
+                    in the exchange, then a serial number, your name and \
+                    your state, province or country. After working a station \
+                    that answered your CQ you must move: the frequency is \
+                    theirs, and your next QSO is answering someone else's \
+                    CQ, which leaves you holding that frequency.",
+            example_exchange: "K6XX N5TJ 57 JEFF TX",
+            typical_wpm: Some((30, 38)),
+        })
+    }
+
+    fn exchange_fields(&self) -> Vec<ExchangeField> {
+        vec![
+            ExchangeField::new("Nr", "57", 4, FieldKind::Number)
+                .focus_on_enter()
+                .cut_numbers(),
+            ExchangeField::new("Name", "JEFF", 8, FieldKind::Text),
+            ExchangeField::new("QTH", "TX", 4, FieldKind::Text),
+        ]
+    }
+
+    fn settings_fields(&self) -> Vec<SettingField> {
+        vec![
+            SettingField {
+                key: "cq_message",
+                label: "CQ Message",
+                placeholder: "CQ NA",
+                width_chars: 12,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "callsign_file",
+                label: "Callsign File",
+                placeholder: "callsigns.txt",
+                width_chars: 24,
+                kind: SettingFieldKind::FilePath,
+                group: SettingFieldGroup::Contest,
+            },
+            SettingField {
+                key: "user_name",
+                label: "Your Name",
+                placeholder: "BOB",
+                width_chars: 8,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::UserExchange,
+            },
+            SettingField {
+                key: "user_qth",
+                label: "Your QTH",
+                placeholder: "CT",
+                width_chars: 4,
+                kind: SettingFieldKind::Text,
+                group: SettingFieldGroup::UserExchange,
+            },
+        ]
+    }
+
+    fn default_settings(&self) -> toml::Value {
+        let mut table = Table::new();
+        table.insert(
+            "cq_message".to_string(),
+            toml::Value::String("CQ NA".to_string()),
+        );
+        table.insert(
+            "callsign_file".to_string(),
+            toml::Value::String("callsigns.txt".to_string()),
+        );
+        table.insert(
+            "user_name".to_string(),
+            toml::Value::String("BOB".to_string()),
+        );
+        table.insert(
+            "user_qth".to_string(),
+            toml::Value::String("CT".to_string()),
+        );
+        toml::Value::Table(table)
+    }
+
+    fn cq_message(&self, settings: &toml::Value) -> String {
+        Self::get_string(settings, "cq_message", "CQ NA")
+    }
+
+    fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String> {
+        let path = Self::get_string(settings, "callsign_file", "callsigns.txt");
+        match NaSprintCallsignSource::load(&path) {
+            Ok(source) => Ok(Box::new(source)),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                NaSprintCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
+        }
+    }
+
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<NaSprintCallsignSource>(path, self, settings)
+    }
+
+    fn qsy_after_run(&self) -> bool {
+        true
+    }
+
+    fn generate_exchange(&self, callsign: &str, _serial: u32, _settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
+        let serial = rng.gen_range(SERIAL_RANGE.0..=SERIAL_RANGE.1);
+        Exchange::new(vec![
+            serial.to_string(),
+            name_for_callsign(callsign).to_string(),
+            location_for_callsign(callsign),
+        ])
+    }
+
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => parse_serial(value).is_some(),
+            _ => true,
+        }
+    }
+
+    fn caller_serial_index(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn fill_requests(&self, _settings: &toml::Value) -> Vec<Option<String>> {
+        vec![
+            None,
+            Some("NR?".to_string()),
+            Some("NAME?".to_string()),
+            Some("QTH?".to_string()),
+        ]
+    }
+
+    fn repeat_request(&self, index: usize, _settings: &toml::Value) -> String {
+        ["NR?", "NAME?", "QTH?"]
+            .get(index)
+            .copied()
+            .unwrap_or("?")
+            .to_string()
+    }
+
+    /// Our call goes in along with theirs, which is sent in front of it
+    fn user_exchange_fields(
+        &self,
+        user_callsign: &str,
+        serial: u32,
+        settings: &toml::Value,
+    ) -> Vec<String> {
+        vec![
+            user_callsign.trim().to_uppercase(),
+            serial.to_string(),
+            Self::get_string(settings, "user_name", "BOB").to_uppercase(),
+            Self::get_string(settings, "user_qth", "CT").to_uppercase(),
+        ]
+    }
+
+    fn validate(
+        &self,
+        expected_call: &str,
+        expected_exchange: &Exchange,
+        received_call: &str,
+        received_fields: &[String],
+        _settings: &toml::Value,
+    ) -> ValidationResult {
+        let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
+
+        let field_correct: Vec<bool> = (0..3)
+            .map(|index| {
+                match (
+                    expected_exchange.fields.get(index),
+                    received_fields.get(index),
+                ) {
+                    (Some(expected), Some(received)) if index == 0 => {
+                        parse_serial(expected).is_some()
+                            && parse_serial(expected) == parse_serial(received)
+                    }
+                    (Some(expected), Some(received)) => {
+                        expected.eq_ignore_ascii_case(received.trim())
+                    }
+                    _ => false,
+                }
+            })
+            .collect();
+        let exchange_correct = field_correct.iter().all(|ok| *ok);
+
+        ValidationResult {
+            callsign_correct,
+            exchange_correct,
+            field_correct,
+            points: if callsign_correct && exchange_correct {
+                1
+            } else {
+                0
+            },
+            remarks: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callers_send_a_name_and_location_that_fit_the_call() {
+        let contest = NaSprintContest::new();
+        let settings = contest.default_settings();
+        assert_eq!(location_for_callsign("W6YX"), "CA");
+        assert_eq!(location_for_callsign("K1ABC/6"), "CA");
+        assert!(US_STATES[1].contains(&location_for_callsign("K1ABC").as_str()));
+        assert_eq!(location_for_callsign("VE7CC"), "BC");
+        assert_eq!(location_for_callsign("VO1MP"), "NL");
+        assert_eq!(location_for_callsign("KL7RA"), "AK");
+        assert_eq!(location_for_callsign("XE2X"), "XE");
+
+        let sent = contest.generate_exchange("N5TJ", 1, &settings, &mut rand::thread_rng());
+        let again = contest.generate_exchange("N5TJ", 1, &settings, &mut rand::thread_rng());
+        assert_eq!(sent.fields[1..], again.fields[1..]);
+        let logged: Vec<String> = sent.fields.iter().map(|f| f.to_lowercase()).collect();
+        let result = contest.validate("N5TJ", &sent, "N5TJ", &logged, &settings);
+        assert!(result.exchange_correct);
+        assert_eq!(result.points, 1);
+    }
+
+    #[test]
+    fn our_exchange_carries_our_call() {
+        let contest = NaSprintContest::new();
+        let settings = contest.default_settings();
+        let fields = contest.user_exchange_fields("k6xx", 57, &settings);
+        assert_eq!(fields, ["K6XX", "57", "BOB", "CT"]);
+        assert_eq!(contest.fill_requests(&settings).len(), fields.len());
+        assert!(contest.qsy_after_run());
+        assert!(NaSprintCallsignSource::default_pool().entry_count() > 0);
+        let (source, _) = NaSprintCallsignSource::parse("K1ABC\nDL1ABC\nVE3EJ\n");
+        assert_eq!(source.entry_count(), 2);
+    }
+}
@@ -0,0 +1,181 @@
+// ARRL and RAC sections and the Sweepstakes precedences, shared by the
+// contests that send a section: for giving callers a section that fits
+// their call, and for telling an impossible entry from a miscopied one
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+/// ARRL sections by US call area
+const US_SECTIONS: &[(char, &[&str])] = &[
+    ('1', &["CT", "EMA", "ME", "NH", "RI", "VT", "WMA"]),
+    ('2', &["ENY", "NLI", "NNJ", "NNY", "SNJ", "WNY"]),
+    ('3', &["DE", "EPA", "MDC", "WPA"]),
+    (
+        '4',
+        &[
+            "AL", "GA", "KY", "NC", "NFL", "SC", "SFL", "TN", "VA", "WCF",
+        ],
+    ),
+    ('5', &["AR", "LA", "MS", "NM", "NTX", "OK", "STX", "WTX"]),
+    (
+        '6',
+        &["EB", "LAX", "ORG", "SB", "SCV", "SDG", "SF", "SJV", "SV"],
+    ),
+    (
+        '7',
+        &["AZ", "EWA", "ID", "MT", "NV", "OR", "UT", "WWA", "WY"],
+    ),
+    ('8', &["MI", "OH", "WV"]),
+    ('9', &["IL", "IN", "WI"]),
+    ('0', &["CO", "IA", "KS", "MN", "MO", "ND", "NE", "SD"]),
+];
+
+/// ARRL sections outside the call areas: Alaska, Pacific, Puerto Rico and
+/// the Virgin Islands
+const OUTLYING_SECTIONS: &[&str] = &["AK", "PAC", "PR", "VI"];
+
+const OUTLYING_PREFIXES: &[(&str, &[&str])] = &[
+    ("KL", &["AK"]),
+    ("AL", &["AK"]),
+    ("NL", &["AK"]),
+    ("WL", &["AK"]),
+    ("KH", &["PAC"]),
+    ("AH", &["PAC"]),
+    ("NH", &["PAC"]),
+    ("WH", &["PAC"]),
+    ("KP2", &["VI"]),
+    ("NP2", &["VI"]),
+    ("WP2", &["VI"]),
+    ("KP3", &["PR"]),
+    ("KP4", &["PR"]),
+    ("NP3", &["PR"]),
+    ("NP4", &["PR"]),
+    ("WP3", &["PR"]),
+    ("WP4", &["PR"]),
+];
+
+const RAC_SECTIONS: &[&str] = &[
+    "AB", "BC", "GH", "MB", "NB", "NL", "NS", "ONE", "ONN", "ONS", "PE", "QC", "SK", "TER",
+];
+
+/// RAC sections by Canadian prefix
+const RAC_PREFIXES: &[(&str, &[&str])] = &[
+    ("VE1", &["NS"]),
+    ("VA1", &["NS"]),
+    ("VE2", &["QC"]),
+    ("VA2", &["QC"]),
+    ("VE3", &["ONE", "ONN", "ONS", "GH"]),
+    ("VA3", &["ONE", "ONN", "ONS", "GH"]),
+    ("VE4", &["MB"]),
+    ("VA4", &["MB"]),
+    ("VE5", &["SK"]),
+    ("VA5", &["SK"]),
+    ("VE6", &["AB"]),
+    ("VA6", &["AB"]),
+    ("VE7", &["BC"]),
+    ("VA7", &["BC"]),
+    ("VE8", &["TER"]),
+    ("VE9", &["NB"]),
+    ("VO1", &["NL"]),
+    ("VO2", &["NL"]),
+    ("VY0", &["TER"]),
+    ("VY1", &["TER"]),
+    ("VY2", &["PE"]),
+];
+
+/// Sweepstakes precedences: QRP, low power, high power, unlimited,
+/// multi-op and school club
+pub const PRECEDENCES: &[char] = &['Q', 'A', 'B', 'U', 'M', 'S'];
+
+/// Every ARRL and RAC section abbreviation
+pub fn all_sections() -> impl Iterator<Item = &'static str> {
+    US_SECTIONS
+        .iter()
+        .flat_map(|(_, sections)| sections.iter())
+        .chain(OUTLYING_SECTIONS)
+        .chain(RAC_SECTIONS)
+        .copied()
+}
+
+/// Whether `value` is an ARRL or RAC section (any case)
+pub fn is_section(value: &str) -> bool {
+    let value = value.trim().to_uppercase();
+    all_sections().any(|section| section == value)
+}
+
+/// Whether `value` is a single Sweepstakes precedence letter (any case)
+pub fn is_precedence(value: &str) -> bool {
+    let mut chars = value.trim().chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if PRECEDENCES.contains(&c.to_ascii_uppercase()))
+}
+
+/// The sections a station could be in, from the call it's operating as
+/// (K1ABC/7 is in the 7 area); empty for calls outside the US and Canada
+pub fn sections_for_call(location: &str) -> &'static [&'static str] {
+    let location = location.to_uppercase();
+    let by_prefix = RAC_PREFIXES
+        .iter()
+        .chain(OUTLYING_PREFIXES)
+        .find(|(prefix, _)| has_prefix(&location, prefix));
+    if let Some((_, sections)) = by_prefix {
+        return sections;
+    }
+    let us_call = matches!(location.chars().next(), Some('K' | 'N' | 'W'))
+        || (location.starts_with('A') && location.chars().nth(1) <= Some('L'));
+    if !us_call {
+        return &[];
+    }
+    let digit = location.chars().find(|c| c.is_ascii_digit());
+    US_SECTIONS
+        .iter()
+        .find(|(area, _)| Some(*area) == digit)
+        .map_or(&[], |(_, sections)| sections)
+}
+
+/// Letter-only prefixes like KH or NL only count when the call's digit
+/// follows them, as in KH6ABC or NL7XX
+fn has_prefix(location: &str, prefix: &str) -> bool {
+    let Some(rest) = location.strip_prefix(prefix) else {
+        return false;
+    };
+    prefix.ends_with(|c: char| c.is_ascii_digit()) || rest.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// A section for a station operating as `location`, picked from those its
+/// call could be in
+pub fn random_section_for_call(location: &str, rng: &mut dyn RngCore) -> Option<&'static str> {
+    sections_for_call(location).choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_list_is_complete() {
+        let sections: Vec<&str> = all_sections().collect();
+        assert_eq!(sections.len(), 85);
+        assert!(is_section("ema"));
+        assert!(is_section("GH"));
+        assert!(is_section("TER"));
+        assert!(!is_section("EMX"));
+        assert!(!is_section("DX"));
+        assert!(is_precedence("u"));
+        assert!(!is_precedence("X"));
+        assert!(!is_precedence("AB"));
+    }
+
+    #[test]
+    fn sections_follow_the_call() {
+        assert!(sections_for_call("K1ABC").contains(&"EMA"));
+        assert!(sections_for_call("AA6XY").contains(&"SDG"));
+        assert_eq!(sections_for_call("KH6XX"), ["PAC"]);
+        assert_eq!(sections_for_call("KL7RA"), ["AK"]);
+        assert_eq!(sections_for_call("KP4AA"), ["PR"]);
+        assert_eq!(sections_for_call("NP2X"), ["VI"]);
+        assert_eq!(sections_for_call("VE7CC"), ["BC"]);
+        assert!(sections_for_call("VA3XYZ").contains(&"ONS"));
+        assert!(sections_for_call("DL1ABC").is_empty());
+        assert!(sections_for_call("AP2ABC").is_empty());
+    }
+}
@@ -1,18 +1,22 @@
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, RngCore};
 use std::collections::HashSet;
-use std::path::Path;
 use toml::value::Table;
 
+use super::callsign::{
+    check_callsign_file, is_valid_callsign, parse_activity_weight, parse_lines, CallsignFile,
+    FallbackCallsignSource, DEFAULT_ACTIVITY_WEIGHT,
+};
+use super::sections::{is_precedence, is_section, random_section_for_call, PRECEDENCES};
 use super::types::{
-    Contest, Exchange, ExchangeField, FieldKind, SettingField, SettingFieldGroup, SettingFieldKind,
-    ValidationResult,
+    Contest, ContestHelp, Exchange, ExchangeField, FieldKind, FileCheck, RejectedLine, SettingField,
+    SettingFieldGroup, SettingFieldKind, ValidationResult,
 };
+use crate::cty::portable_location;
 
 pub const CONTEST_ID: &str = "sweepstakes";
 pub const DISPLAY_NAME: &str = "ARRL Sweepstakes";
 
-const PRECEDENCES: &[char] = &['Q', 'A', 'B', 'U', 'M', 'S'];
 const SERIAL_MIN_DEFAULT: i64 = 100;
 const SERIAL_MAX_DEFAULT: i64 = 400;
 const SERIAL_MIN_ALLOWED: i64 = 1;
@@ -29,24 +33,13 @@ impl SweepstakesContest {
         Self
     }
 
-    fn section_for_callsign(callsign: &str) -> String {
-        // Simple mapping based on call area
-        let digit = callsign.chars().find(|c| c.is_ascii_digit());
-
-        match digit {
-            Some('1') => "CT",
-            Some('2') => "NNJ",
-            Some('3') => "EPA",
-            Some('4') => "VA",
-            Some('5') => "NTX",
-            Some('6') => "SDG",
-            Some('7') => "OR",
-            Some('8') => "OH",
-            Some('9') => "IL",
-            Some('0') => "CO",
-            _ => "SDG",
-        }
-        .to_string()
+    fn section_for_callsign(callsign: &str, rng: &mut dyn RngCore) -> String {
+        // One of the sections where the station is operating (K1ABC/7 is in
+        // the 7 area); calls outside the US and Canada get a US section
+        let location = portable_location(callsign).unwrap_or_else(|| callsign.to_string());
+        random_section_for_call(&location, rng)
+            .unwrap_or("SDG")
+            .to_string()
     }
 
     fn get_string(settings: &toml::Value, key: &str, default: &str) -> String {
@@ -110,6 +103,8 @@ struct SweepstakesStation {
     callsign: String,
     section: String,
     check: String,
+    /// Activity weight; higher comes up more often
+    weight: f32,
 }
 
 struct SweepstakesCallsignSource {
@@ -117,77 +112,86 @@ struct SweepstakesCallsignSource {
     used: HashSet<String>,
 }
 
-impl SweepstakesCallsignSource {
-    fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
-        let content = std::fs::read_to_string(path)?;
-        let stations: Vec<SweepstakesStation> = content
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
-            .filter_map(|line| {
-                let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
-                if fields.len() < 4 {
-                    return None;
-                }
-
-                let callsign = fields.get(0).unwrap_or(&"").to_uppercase();
-                let section = fields.get(1).unwrap_or(&"").to_uppercase();
-                let check = fields.get(3).unwrap_or(&"").to_uppercase();
-
-                if callsign.is_empty() || section.is_empty() || check.is_empty() {
-                    return None;
-                }
-
-                if !check.chars().all(|c| c.is_ascii_digit()) {
-                    return None;
-                }
-
-                if !is_valid_callsign(&callsign) {
-                    return None;
-                }
-
-                Some(SweepstakesStation {
-                    callsign,
-                    section,
-                    check,
-                })
+impl CallsignFile for SweepstakesCallsignSource {
+    const NOTHING_VALID: &'static str = "No valid Sweepstakes stations found in file";
+
+    fn parse(content: &str) -> (Self, Vec<RejectedLine>) {
+        let (stations, rejects) = parse_lines(content, |line| {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() < 4 {
+                return Err("expected Call,Sect,State,CK".to_string());
+            }
+
+            let callsign = fields.get(0).unwrap_or(&"").to_uppercase();
+            let section = fields.get(1).unwrap_or(&"").to_uppercase();
+            let check = fields.get(3).unwrap_or(&"").to_uppercase();
+
+            if section.is_empty() {
+                return Err("missing section".to_string());
+            }
+            if !is_section(&section) {
+                return Err(format!("{} is not an ARRL or RAC section", section));
+            }
+            if check.is_empty() {
+                return Err("missing check".to_string());
+            }
+
+            if !check.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("check {} is not a number", check));
+            }
+
+            if callsign.is_empty() {
+                return Err("missing callsign".to_string());
+            }
+            if !is_valid_callsign(&callsign) {
+                return Err(format!("{} is not a valid callsign", callsign));
+            }
+
+            Ok(SweepstakesStation {
+                callsign,
+                section,
+                check,
+                weight: parse_activity_weight(fields.get(5).copied()),
             })
-            .collect();
-
-        if stations.is_empty() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "No valid Sweepstakes stations found in file",
-            ));
-        }
-
-        Ok(Self {
+        });
+        let source = Self {
             stations,
             used: HashSet::new(),
-        })
+        };
+        (source, rejects)
     }
 
+    fn entry_count(&self) -> usize {
+        self.stations.len()
+    }
+}
+
+impl SweepstakesCallsignSource {
     fn default_pool() -> Self {
         let stations = vec![
             SweepstakesStation {
                 callsign: "W1AW".to_string(),
                 section: "CT".to_string(),
                 check: "38".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             SweepstakesStation {
                 callsign: "K5ZD".to_string(),
                 section: "EMA".to_string(),
                 check: "90".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             SweepstakesStation {
                 callsign: "N0AX".to_string(),
                 section: "WCF".to_string(),
                 check: "72".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
             SweepstakesStation {
                 callsign: "K3LR".to_string(),
                 section: "WPA".to_string(),
                 check: "79".to_string(),
+                weight: DEFAULT_ACTIVITY_WEIGHT,
             },
         ];
 
@@ -197,7 +201,7 @@ impl SweepstakesCallsignSource {
         }
     }
 
-    fn random_station(&mut self) -> Option<SweepstakesStation> {
+    fn random_station(&mut self, rng: &mut dyn RngCore) -> Option<SweepstakesStation> {
         let available: Vec<_> = self
             .stations
             .iter()
@@ -206,9 +210,13 @@ impl SweepstakesCallsignSource {
 
         let station = if available.is_empty() {
             self.used.clear();
-            self.stations.choose(&mut rand::thread_rng())?
+            self.stations
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         } else {
-            *available.choose(&mut rand::thread_rng())?
+            *available
+                .choose_weighted(rng, |s| s.weight)
+                .ok()?
         };
 
         self.used.insert(station.callsign.clone());
@@ -222,11 +230,12 @@ impl super::types::CallsignSource for SweepstakesCallsignSource {
         _contest: &dyn Contest,
         _serial: u32,
         settings: &toml::Value,
+        rng: &mut dyn RngCore,
     ) -> Option<(String, Exchange)> {
-        let station = self.random_station()?;
+        let station = self.random_station(rng)?;
         let (min, max) = SweepstakesContest::serial_range(settings);
-        let serial = rand::thread_rng().gen_range(min..=max);
-        let precedence = *PRECEDENCES.choose(&mut rand::thread_rng()).unwrap_or(&'A');
+        let serial = rng.gen_range(min..=max);
+        let precedence = *PRECEDENCES.choose(rng).unwrap_or(&'A');
         let check = station.check.parse::<u16>().ok()?;
 
         Some((
@@ -240,15 +249,17 @@ impl super::types::CallsignSource for SweepstakesCallsignSource {
             ]),
         ))
     }
-}
 
-fn is_valid_callsign(call: &str) -> bool {
-    if call.len() < 3 || call.len() > 10 {
-        return false;
+    fn history(&self, callsign: &str) -> Option<Vec<String>> {
+        let station = self.stations.iter().find(|s| s.callsign == callsign)?;
+        let check = station.check.parse::<u16>().ok()?;
+        Some(vec![
+            String::new(),
+            String::new(),
+            format!("{:02}", check),
+            station.section.clone(),
+        ])
     }
-    call.chars().any(|c| c.is_ascii_alphabetic())
-        && call.chars().any(|c| c.is_ascii_digit())
-        && call.chars().all(|c| c.is_ascii_alphanumeric() || c == '/')
 }
 
 impl Contest for SweepstakesContest {
@@ -260,9 +271,20 @@ impl Contest for SweepstakesContest {
         DISPLAY_NAME
     }
 
+    fn help(&self) -> Option<ContestHelp> {
+        Some(ContestHelp {
+            rules: "US and Canadian stations work each other once. The \
+                    exchange is long and sent in order: serial number, \
+                    precedence, call, check (the year you were first \
+                    licensed) and section.",
+            example_exchange: "123 A K1ABC 99 EMA",
+            typical_wpm: Some((24, 32)),
+        })
+    }
+
     fn exchange_fields(&self) -> Vec<ExchangeField> {
         vec![
-            ExchangeField::new("NR", "001", 4, FieldKind::Number),
+            ExchangeField::new("NR", "001", 4, FieldKind::Number).cut_numbers(),
             ExchangeField::new("P", "A", 1, FieldKind::Text),
             ExchangeField::new("CK", "99", 2, FieldKind::Number),
             ExchangeField::new("Sec", "CT", 3, FieldKind::Section),
@@ -380,19 +402,31 @@ impl Contest for SweepstakesContest {
         let path = Self::get_string(settings, "callsign_file", "ss_callsigns.txt");
         match SweepstakesCallsignSource::load(&path) {
             Ok(source) => Ok(Box::new(source)),
-            Err(_) => Ok(Box::new(SweepstakesCallsignSource::default_pool())),
+            Err(e) => Ok(Box::new(FallbackCallsignSource::new(
+                SweepstakesCallsignSource::default_pool(),
+                &path,
+                e,
+            ))),
         }
     }
 
-    fn generate_exchange(&self, callsign: &str, _serial: u32, settings: &toml::Value) -> Exchange {
-        let mut rng = rand::thread_rng();
+    fn check_callsign_file(
+        &self,
+        _key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String> {
+        check_callsign_file::<SweepstakesCallsignSource>(path, self, settings)
+    }
+
+    fn generate_exchange(&self, callsign: &str, _serial: u32, settings: &toml::Value, rng: &mut dyn RngCore) -> Exchange {
         let precedence = *PRECEDENCES
             .get(rng.gen_range(0..PRECEDENCES.len()))
             .unwrap_or(&'A');
         let (min, max) = Self::serial_range(settings);
         let serial = rng.gen_range(min..=max);
         let check = rng.gen_range(60..=99) as u16;
-        let section = Self::section_for_callsign(callsign);
+        let section = Self::section_for_callsign(callsign, rng);
 
         Exchange::new(vec![
             Self::format_serial(serial),
@@ -403,6 +437,43 @@ impl Contest for SweepstakesContest {
         ])
     }
 
+    fn field_input_valid(&self, index: usize, value: &str) -> bool {
+        match index {
+            0 => parse_serial(value).is_some(),
+            1 => is_precedence(value),
+            2 => value.len() == 2 && value.chars().all(|c| c.is_ascii_digit()),
+            3 => is_section(value),
+            _ => true,
+        }
+    }
+
+    fn qso_points(&self) -> u32 {
+        2
+    }
+
+    fn caller_serial_index(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn format_caller_serial(&self, serial: u32) -> String {
+        Self::format_serial(serial)
+    }
+
+    fn fill_requests(&self, _settings: &toml::Value) -> Vec<Option<String>> {
+        // Nobody asks for our call again here; that's a callsign repeat
+        ["NR?", "PREC?", "", "CK?", "SEC?"]
+            .iter()
+            .map(|request| (!request.is_empty()).then(|| request.to_string()))
+            .collect()
+    }
+
+    fn repeat_request(&self, index: usize, _settings: &toml::Value) -> String {
+        ["NR?", "PREC?", "CK?", "SEC?"]
+            .get(index)
+            .unwrap_or(&"?")
+            .to_string()
+    }
+
     fn user_exchange_fields(
         &self,
         user_callsign: &str,
@@ -438,6 +509,15 @@ impl Contest for SweepstakesContest {
             return Err("Serial Min must be less than or equal to Serial Max.".to_string());
         }
 
+        let precedence = Self::get_string(settings, "user_precedence", "A");
+        if !is_precedence(&precedence) {
+            return Err("Your Precedence must be one of Q, A, B, U, M or S.".to_string());
+        }
+        let section = Self::get_string(settings, "user_section", "CT");
+        if !is_section(&section) {
+            return Err(format!("{} is not an ARRL or RAC section.", section.trim()));
+        }
+
         Ok(())
     }
 
@@ -451,8 +531,7 @@ impl Contest for SweepstakesContest {
     ) -> ValidationResult {
         let callsign_correct = expected_call.eq_ignore_ascii_case(received_call);
 
-        let exchange_correct = if received_fields.len() >= 4 && expected_exchange.fields.len() >= 5
-        {
+        let field_correct = if received_fields.len() >= 4 && expected_exchange.fields.len() >= 5 {
             let serial_ok = match (expected_exchange.fields.get(0), received_fields.get(0)) {
                 (Some(expected), Some(received)) => {
                     parse_serial(expected) == parse_serial(received)
@@ -471,19 +550,119 @@ impl Contest for SweepstakesContest {
                     .and_then(|v| v.parse::<u16>().ok());
             let section_ok = received_fields.get(3).map(|v| v.to_uppercase())
                 == expected_exchange.fields.get(4).map(|v| v.to_uppercase());
-            serial_ok && prec_ok && check_ok && section_ok
+            vec![serial_ok, prec_ok, check_ok, section_ok]
         } else {
-            false
+            vec![false; 4]
         };
+        let exchange_correct = field_correct.iter().all(|ok| *ok);
 
         ValidationResult {
             callsign_correct,
             exchange_correct,
+            field_correct,
             points: if callsign_correct && exchange_correct {
                 2
             } else {
                 0
             },
+            remarks: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::CallsignSource;
+
+    #[test]
+    fn validation_reports_each_field() {
+        let contest = SweepstakesContest::new();
+        let expected = Exchange::new(
+            ["042", "A", "K5ZD", "99", "EMA"]
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+        );
+        let received: Vec<String> = ["42", "A", "98", "EMA"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let settings = contest.default_settings();
+        let result = contest.validate("K5ZD", &expected, "K5ZD", &received, &settings);
+        assert_eq!(result.field_correct, vec![true, true, false, true]);
+        assert!(!result.exchange_correct);
+        let field_results = result.field_results(&contest.exchange_fields());
+        let labels: Vec<(&str, bool)> = field_results
+            .iter()
+            .map(|(label, correct)| (label.as_str(), *correct))
+            .collect();
+        assert_eq!(
+            labels,
+            [("NR", true), ("P", true), ("CK", false), ("Sec", true)]
+        );
+    }
+
+    #[test]
+    fn call_history_fills_check_and_section() {
+        let source = SweepstakesCallsignSource::default_pool();
+        assert_eq!(
+            source.history("K5ZD"),
+            Some(vec![
+                String::new(),
+                String::new(),
+                "90".to_string(),
+                "EMA".to_string()
+            ])
+        );
+        assert_eq!(source.history("W1XYZ"), None);
+    }
+
+    #[test]
+    fn skipped_file_lines_say_why() {
+        let (source, rejects) = SweepstakesCallsignSource::parse(
+            "K5ZD,EMA,MA,90\nW1AW,CT,CT,XX\n# comment\nN0AX,,,72\nK3LR,WPA\nK1XX,MAS,MA,70\n",
+        );
+        assert_eq!(source.entry_count(), 1);
+        let reasons: Vec<(usize, &str)> = rejects
+            .iter()
+            .map(|r| (r.line, r.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (2, "check XX is not a number"),
+                (4, "missing section"),
+                (5, "expected Call,Sect,State,CK"),
+                (6, "MAS is not an ARRL or RAC section")
+            ]
+        );
+    }
+
+    #[test]
+    fn field_entry_checks() {
+        let contest = SweepstakesContest::new();
+        assert!(contest.field_input_valid(0, "TT7"));
+        assert!(!contest.field_input_valid(0, "4X"));
+        assert!(contest.field_input_valid(1, "u"));
+        assert!(!contest.field_input_valid(1, "X"));
+        assert!(!contest.field_input_valid(2, "9"));
+        assert!(contest.field_input_valid(3, "EMA"));
+        assert!(!contest.field_input_valid(3, "E2"));
+        assert!(!contest.field_input_valid(3, "EMX"));
+    }
+
+    #[test]
+    fn fill_requests_line_up_with_our_exchange() {
+        let contest = SweepstakesContest::new();
+        let settings = contest.default_settings();
+        let requests = contest.fill_requests(&settings);
+        assert_eq!(
+            requests.len(),
+            contest.user_exchange_fields("K1ABC", 7, &settings).len()
+        );
+        assert_eq!(requests[0].as_deref(), Some("NR?"));
+        assert_eq!(requests[2], None);
+        assert_eq!(requests[4].as_deref(), Some("SEC?"));
+    }
+}
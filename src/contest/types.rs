@@ -1,3 +1,6 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
 #[derive(Clone, Debug)]
 pub struct Exchange {
     pub fields: Vec<String>,
@@ -26,6 +29,37 @@ pub fn normalize_exchange_input(value: &str, kind: FieldKind) -> String {
     cleaned
 }
 
+/// Send the all-numeric values of the fields that take cut numbers
+/// (0=T, 9=N, 1=A), e.g. 599 -> 5NN, 103 -> AT3. Other fields, such as
+/// member numbers or power, are left untouched.
+pub fn cut_number_exchange(exchange: &Exchange, fields: &[ExchangeField]) -> Exchange {
+    let fields = exchange
+        .fields
+        .iter()
+        .zip(
+            fields
+                .iter()
+                .map(|field| field.cut_numbers)
+                .chain(std::iter::repeat(false)),
+        )
+        .map(|(field, cut)| {
+            if !cut || field.is_empty() || !field.chars().all(|c| c.is_ascii_digit()) {
+                return field.clone();
+            }
+            field
+                .chars()
+                .map(|c| match c {
+                    '0' => 'T',
+                    '9' => 'N',
+                    '1' => 'A',
+                    _ => c,
+                })
+                .collect()
+        })
+        .collect();
+    Exchange::new(fields)
+}
+
 #[derive(Clone, Debug)]
 pub struct ExchangeField {
     pub label: &'static str,
@@ -34,6 +68,8 @@ pub struct ExchangeField {
     pub kind: FieldKind,
     pub default_value: Option<&'static str>,
     pub focus_on_enter: bool,
+    /// Callers may send this field with cut numbers (reports, serials, zones)
+    pub cut_numbers: bool,
 }
 
 impl ExchangeField {
@@ -50,6 +86,7 @@ impl ExchangeField {
             kind,
             default_value: None,
             focus_on_enter: false,
+            cut_numbers: false,
         }
     }
 
@@ -62,13 +99,23 @@ impl ExchangeField {
         self.focus_on_enter = true;
         self
     }
+
+    pub fn cut_numbers(mut self) -> Self {
+        self.cut_numbers = true;
+        self
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SettingFieldKind {
     Text,
     FilePath,
-    Integer { min: i64, max: i64 },
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    /// On or off, stored as a boolean
+    Toggle,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -87,12 +134,77 @@ pub struct SettingField {
     pub group: SettingFieldGroup,
 }
 
+/// Contest settings key for how often callers send a report other than 599
+pub const NON_599_KEY: &str = "non_599_pct";
+
+/// The setting for how often callers send a report other than 599, for
+/// contests with an RST field
+pub fn non_599_setting() -> SettingField {
+    SettingField {
+        key: NON_599_KEY,
+        label: "Non-599 Reports %",
+        placeholder: "0",
+        width_chars: 3,
+        kind: SettingFieldKind::Integer { min: 0, max: 50 },
+        group: SettingFieldGroup::Contest,
+    }
+}
+
+/// A signal report as a caller sends it: mostly 5NN, now and then 599 or
+/// ENN, and the set share of the time 579 or 559 (usually cut, as 57N)
+pub fn pick_rst(settings: &toml::Value, rng: &mut dyn RngCore) -> String {
+    let non_599_pct = settings
+        .get(NON_599_KEY)
+        .and_then(|v| v.as_integer())
+        .or_else(|| {
+            settings
+                .get(NON_599_KEY)
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.trim().parse::<i64>().ok())
+        })
+        .unwrap_or(0)
+        .clamp(0, 100);
+    if rng.gen_range(0..100) < non_599_pct {
+        let report = *["579", "579", "559"].choose(rng).unwrap_or(&"579");
+        return if rng.gen_bool(0.85) {
+            report.replace('9', "N")
+        } else {
+            report.to_string()
+        };
+    }
+    let roll = rng.gen_range(0..100);
+    if roll < 5 {
+        "ENN"
+    } else if roll < 15 {
+        "599"
+    } else {
+        "5NN"
+    }
+    .to_string()
+}
+
 /// Result of validating user's exchange against expected
 #[derive(Clone, Debug)]
 pub struct ValidationResult {
     pub callsign_correct: bool,
     pub exchange_correct: bool,
+    /// Correctness of each logged exchange field, in `exchange_fields` order
+    pub field_correct: Vec<bool>,
     pub points: u32,
+    /// Explanations for the Last QSO line of what the caller sent and how
+    /// it was copied, such as a caller sending an unusual zone
+    pub remarks: Vec<String>,
+}
+
+impl ValidationResult {
+    /// Each exchange element's label with whether it was logged right
+    pub fn field_results(&self, fields: &[ExchangeField]) -> Vec<(String, bool)> {
+        fields
+            .iter()
+            .zip(&self.field_correct)
+            .map(|(field, correct)| (field.label.to_string(), *correct))
+            .collect()
+    }
 }
 
 /// Source of callsigns and exchanges for callers
@@ -102,7 +214,51 @@ pub trait CallsignSource: Send + Sync {
         contest: &dyn Contest,
         serial: u32,
         settings: &toml::Value,
+        rng: &mut dyn RngCore,
     ) -> Option<(String, Exchange)>;
+
+    /// What the call-history file knows about a callsign, as it would be
+    /// logged in the exchange fields (empty where the file has nothing)
+    fn history(&self, _callsign: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Why the contest's file isn't being used and what is used instead,
+    /// when this source is standing in for it
+    fn fallback(&self) -> Option<(&str, &'static str)> {
+        None
+    }
+}
+
+/// A line of a callsign file that was skipped, and why
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedLine {
+    /// Line number, counting from 1
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// What reading a callsign file found, for checking it from Settings
+#[derive(Clone, Debug)]
+pub struct FileCheck {
+    /// Entries callers can be made from
+    pub valid: usize,
+    pub rejects: Vec<RejectedLine>,
+    /// A caller as the file would produce one
+    pub sample: Option<(String, Exchange)>,
+}
+
+/// What to know before practicing a contest, for someone who hasn't
+/// worked it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContestHelp {
+    /// A few sentences on who works whom and what counts
+    pub rules: &'static str,
+    /// An exchange as it's heard on the air
+    pub example_exchange: &'static str,
+    /// Speeds most of the field runs at, in WPM, where known
+    pub typical_wpm: Option<(u32, u32)>,
 }
 
 /// Trait for contest-specific behavior
@@ -110,6 +266,16 @@ pub trait Contest: Send + Sync {
     fn id(&self) -> &'static str;
     fn display_name(&self) -> &'static str;
 
+    /// Rules summary and an example exchange (default: none)
+    fn help(&self) -> Option<ContestHelp> {
+        None
+    }
+
+    /// Take the settings that shape the exchange itself, for contests whose
+    /// exchange fields depend on them; called whenever the settings change
+    /// (default: no-op)
+    fn configure(&mut self, _settings: &toml::Value) {}
+
     /// Exchange fields the user must log
     fn exchange_fields(&self) -> Vec<ExchangeField>;
 
@@ -130,8 +296,61 @@ pub trait Contest: Send + Sync {
     /// Create a callsign source for this contest
     fn callsign_source(&self, settings: &toml::Value) -> Result<Box<dyn CallsignSource>, String>;
 
+    /// Read the file chosen for the `key` setting the way `callsign_source`
+    /// would, without using it, reporting what was accepted and what was
+    /// skipped
+    fn check_callsign_file(
+        &self,
+        key: &str,
+        path: &str,
+        settings: &toml::Value,
+    ) -> Result<FileCheck, String>;
+
+    /// Whether `generate_exchange` works from the callsign alone, so callers
+    /// can use generated calls instead of the callsign file (default: no)
+    fn synthetic_calls(&self) -> bool {
+        false
+    }
+
+    /// Whether whoever calls CQ has to move after each QSO, leaving the
+    /// frequency to the station that answered, so runs and answering CQs
+    /// take turns (the NA Sprint QSY rule; default: no)
+    fn qsy_after_run(&self) -> bool {
+        false
+    }
+
     /// Generate an exchange for a calling station
-    fn generate_exchange(&self, callsign: &str, serial: u32, settings: &toml::Value) -> Exchange;
+    fn generate_exchange(
+        &self,
+        callsign: &str,
+        serial: u32,
+        settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Exchange;
+
+    /// Points a clean QSO is worth, the unit penalties are counted in
+    /// (default: 1)
+    fn qso_points(&self) -> u32 {
+        1
+    }
+
+    /// Position of the serial number in a caller's exchange, for contests
+    /// where each station counts up its own serial (default: none)
+    fn caller_serial_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Format a serial number the way it appears in an exchange, a
+    /// caller's or our own
+    fn format_caller_serial(&self, serial: u32) -> String {
+        serial.to_string()
+    }
+
+    /// Whether a value typed into exchange field `index` looks valid, so the
+    /// entry box can flag it before the QSO is logged (default: anything goes)
+    fn field_input_valid(&self, _index: usize, _value: &str) -> bool {
+        true
+    }
 
     /// Format exchange for Morse transmission
     fn format_exchange(&self, exchange: &Exchange) -> String {
@@ -146,6 +365,29 @@ pub trait Contest: Send + Sync {
         settings: &toml::Value,
     ) -> Vec<String>;
 
+    /// What a caller sends to ask for each element of our exchange again,
+    /// in `user_exchange_fields` order, e.g. "NR?"; None for elements
+    /// nobody asks for, such as the 5NN. By default each exchange field's
+    /// label, skipping fields with a fixed value.
+    fn fill_requests(&self, _settings: &toml::Value) -> Vec<Option<String>> {
+        self.exchange_fields()
+            .iter()
+            .map(|field| {
+                (field.default_value.is_none() && !field.label.eq_ignore_ascii_case("RST"))
+                    .then(|| format!("{}?", field.label.to_uppercase()))
+            })
+            .collect()
+    }
+
+    /// What we send to ask a caller for element `index` of their exchange
+    /// again, e.g. "SEC?" (default: the field's label)
+    fn repeat_request(&self, index: usize, _settings: &toml::Value) -> String {
+        self.exchange_fields().get(index).map_or_else(
+            || "?".to_string(),
+            |field| format!("{}?", field.label.to_uppercase()),
+        )
+    }
+
     /// Format the user's exchange for Morse transmission
     fn format_user_exchange(&self, fields: &[String]) -> String {
         fields.join(" ")
@@ -167,8 +409,56 @@ pub trait Contest: Send + Sync {
     }
 }
 
+/// Builds a contest; built-in contests use their `make_contest`, custom
+/// ones capture their definition
+pub type ContestFactory = std::sync::Arc<dyn Fn() -> Box<dyn Contest> + Send + Sync>;
+
+#[derive(Clone)]
 pub struct ContestDescriptor {
     pub id: &'static str,
     pub display_name: &'static str,
-    pub factory: fn() -> Box<dyn Contest>,
+    pub factory: ContestFactory,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(values: &[&str]) -> Exchange {
+        Exchange::new(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    fn cut_fields(count: usize) -> Vec<ExchangeField> {
+        (0..count)
+            .map(|_| ExchangeField::new("F", "", 4, FieldKind::Alnum).cut_numbers())
+            .collect()
+    }
+
+    #[test]
+    fn numbers_are_sent_cut() {
+        let cut = cut_number_exchange(&fields(&["599", "103", "0", "14"]), &cut_fields(4));
+        assert_eq!(cut.fields, ["5NN", "AT3", "T", "A4"]);
+    }
+
+    #[test]
+    fn fields_that_are_not_plain_numbers_are_left_alone() {
+        let exchange = fields(&["BOB", "CT", "K1ABC", "5A", "", "1B"]);
+        assert_eq!(
+            cut_number_exchange(&exchange, &cut_fields(6)).fields,
+            exchange.fields
+        );
+    }
+
+    #[test]
+    fn only_fields_that_take_cut_numbers_are_cut() {
+        // A report and a serial, then a member number and power
+        let exchange_fields = [
+            ExchangeField::new("RST", "5NN", 3, FieldKind::Text).cut_numbers(),
+            ExchangeField::new("NR", "NR", 5, FieldKind::Alnum).cut_numbers(),
+            ExchangeField::new("Number", "123", 6, FieldKind::Alnum),
+            ExchangeField::new("Exchange", "ST/PWR", 6, FieldKind::Alnum),
+        ];
+        let cut = cut_number_exchange(&fields(&["599", "109", "1901", "100"]), &exchange_fields);
+        assert_eq!(cut.fields, ["5NN", "ATN", "1901", "100"]);
+    }
 }
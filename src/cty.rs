@@ -1,9 +1,60 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
+/// Start of a binary cache file, with the layout version after it
+const CACHE_MAGIC: &[u8; 4] = b"CTYC";
+const CACHE_VERSION: u8 = 1;
+
+/// Continents as abbreviated in cty.dat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Continent {
+    NorthAmerica,
+    SouthAmerica,
+    Europe,
+    Africa,
+    Asia,
+    Oceania,
+}
+
+impl Continent {
+    pub const ALL: [Continent; 6] = [
+        Continent::NorthAmerica,
+        Continent::SouthAmerica,
+        Continent::Europe,
+        Continent::Africa,
+        Continent::Asia,
+        Continent::Oceania,
+    ];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_uppercase().as_str() {
+            "NA" => Some(Continent::NorthAmerica),
+            "SA" => Some(Continent::SouthAmerica),
+            "EU" => Some(Continent::Europe),
+            "AF" => Some(Continent::Africa),
+            "AS" => Some(Continent::Asia),
+            "OC" => Some(Continent::Oceania),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Continent::NorthAmerica => "NA",
+            Continent::SouthAmerica => "SA",
+            Continent::Europe => "EU",
+            Continent::Africa => "AF",
+            Continent::Asia => "AS",
+            Continent::Oceania => "OC",
+        }
+    }
+}
+
 /// Parsed entry from cty.dat representing a DXCC entity
 #[derive(Debug, Clone)]
 pub struct DxccEntity {
     pub cq_zone: u8,
+    pub continent: Option<Continent>,
     pub primary_prefix: String,
 }
 
@@ -11,20 +62,97 @@ pub struct DxccEntity {
 #[derive(Debug, Clone)]
 struct PrefixEntry {
     cq_zone: u8,
+    continent: Option<Continent>,
     country_prefix: String, // the primary prefix for the country this entry belongs to
 }
 
+/// Prefixes stored a character per level, so the longest prefix of a call
+/// is found in one walk down its characters
+#[derive(Default)]
+struct PrefixTrie {
+    /// The root is node 0
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    /// Next character and the node it leads to
+    children: Vec<(u8, usize)>,
+    entry: Option<PrefixEntry>,
+}
+
+impl PrefixTrie {
+    fn new() -> Self {
+        Self {
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    /// Add a prefix; the first entry for a prefix is kept
+    fn insert(&mut self, prefix: &str, entry: PrefixEntry) {
+        let mut node = 0;
+        for byte in prefix.bytes() {
+            node = match self.nodes[node].children.iter().find(|(b, _)| *b == byte) {
+                Some((_, next)) => *next,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].children.push((byte, next));
+                    next
+                }
+            };
+        }
+        self.nodes[node].entry.get_or_insert(entry);
+    }
+
+    /// The entry for the longest prefix `call` starts with
+    fn longest_match(&self, call: &str) -> Option<&PrefixEntry> {
+        let mut node = 0;
+        let mut found = self.nodes[node].entry.as_ref();
+        for byte in call.bytes() {
+            let Some((_, next)) = self.nodes[node].children.iter().find(|(b, _)| *b == byte) else {
+                break;
+            };
+            node = *next;
+            found = self.nodes[node].entry.as_ref().or(found);
+        }
+        found
+    }
+}
+
 /// CTY.DAT database for callsign lookups
 pub struct CtyDat {
     /// Exact callsign matches (highest priority)
     exact_calls: HashMap<String, PrefixEntry>,
-    /// Prefix matches, sorted by length descending for longest-match lookup
-    prefixes: Vec<(String, PrefixEntry)>,
+    /// Prefix matches for longest-match lookup
+    prefix_trie: PrefixTrie,
+    /// Every prefix, longest first
+    prefixes: Vec<String>,
 }
 
 impl CtyDat {
     /// Parse cty.dat content from a string
     pub fn parse(content: &str) -> Self {
+        let (exact_calls, prefixes) = Self::parse_tables(content);
+
+        let mut prefix_trie = PrefixTrie::new();
+        let mut prefix_list = Vec::with_capacity(prefixes.len());
+        for (prefix, entry) in prefixes {
+            prefix_trie.insert(&prefix, entry);
+            prefix_list.push(prefix);
+        }
+        prefix_list.sort_by_key(|prefix| Reverse(prefix.len()));
+
+        Self {
+            exact_calls,
+            prefix_trie,
+            prefixes: prefix_list,
+        }
+    }
+
+    /// Exact-call entries and every (prefix, entry) pair, in file order
+    #[allow(clippy::type_complexity)]
+    fn parse_tables(content: &str) -> (HashMap<String, PrefixEntry>, Vec<(String, PrefixEntry)>) {
         let mut exact_calls: HashMap<String, PrefixEntry> = HashMap::new();
         let mut prefixes: Vec<(String, PrefixEntry)> = Vec::new();
 
@@ -59,13 +187,7 @@ impl CtyDat {
             Self::parse_aliases(&alias_buffer, entity, &mut exact_calls, &mut prefixes);
         }
 
-        // Sort prefixes by length descending for longest-match lookup
-        prefixes.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
-
-        Self {
-            exact_calls,
-            prefixes,
-        }
+        (exact_calls, prefixes)
     }
 
     /// Check if a line is a header line (entity definition)
@@ -86,11 +208,13 @@ impl CtyDat {
         }
 
         let cq_zone = parts[1].trim().parse().unwrap_or(0);
+        let continent = Continent::from_code(parts[3]);
         // parts[4] = lat, parts[5] = lon, parts[6] = tz offset
         let primary_prefix = parts[7].trim().trim_start_matches('*').to_string();
 
         Some(DxccEntity {
             cq_zone,
+            continent,
             primary_prefix,
         })
     }
@@ -115,6 +239,7 @@ impl CtyDat {
 
             let entry = PrefixEntry {
                 cq_zone: cq_override.unwrap_or(entity.cq_zone),
+                continent: entity.continent,
                 country_prefix: entity.primary_prefix.to_uppercase(),
             };
 
@@ -211,42 +336,42 @@ impl CtyDat {
         (result, cq_override, itu_override, is_exact)
     }
 
-    /// Look up CQ zone for a callsign
-    pub fn lookup_cq_zone(&self, callsign: &str) -> Option<u8> {
+    /// The entry for a callsign where it's operating: exact calls first,
+    /// then the longest prefix of its portable location. None for maritime
+    /// and aeronautical mobile, which are in no country.
+    fn lookup_entry(&self, callsign: &str) -> Option<&PrefixEntry> {
         let call = callsign.to_uppercase();
-
-        // First try exact match
         if let Some(entry) = self.exact_calls.get(&call) {
-            return Some(entry.cq_zone);
+            return Some(entry);
         }
 
-        // Then try longest prefix match
-        for (prefix, entry) in &self.prefixes {
-            if call.starts_with(prefix) {
-                return Some(entry.cq_zone);
-            }
+        let location = portable_location(&call)?;
+        if let Some(entry) = self.exact_calls.get(&location) {
+            return Some(entry);
         }
+        self.prefix_trie.longest_match(&location)
+    }
 
-        None
+    /// Look up CQ zone for a callsign
+    pub fn lookup_cq_zone(&self, callsign: &str) -> Option<u8> {
+        self.lookup_entry(callsign).map(|entry| entry.cq_zone)
     }
 
     /// Look up the matching prefix for a callsign (represents the DXCC entity/country)
     pub fn lookup_prefix(&self, callsign: &str) -> Option<String> {
-        let call = callsign.to_uppercase();
-
-        // First try exact match - return the country prefix, not the callsign
-        if let Some(entry) = self.exact_calls.get(&call) {
-            return Some(entry.country_prefix.clone());
-        }
+        self.lookup_entry(callsign)
+            .map(|entry| entry.country_prefix.clone())
+    }
 
-        // Then try longest prefix match
-        for (prefix, entry) in &self.prefixes {
-            if call.starts_with(prefix) {
-                return Some(entry.country_prefix.clone());
-            }
-        }
+    /// Look up the continent for a callsign
+    pub fn lookup_continent(&self, callsign: &str) -> Option<Continent> {
+        self.lookup_entry(callsign)
+            .and_then(|entry| entry.continent)
+    }
 
-        None
+    /// Every prefix (not exact-call entries), longest first
+    pub fn prefixes(&self) -> impl Iterator<Item = &str> {
+        self.prefixes.iter().map(String::as_str)
     }
 
     /// Check if two callsigns are from the same country (matching prefix)
@@ -256,6 +381,222 @@ impl CtyDat {
             _ => false,
         }
     }
+
+    /// The parsed tables, trie and all, as a compact binary cache tagged
+    /// with the hash of the cty.dat it was made from
+    pub fn to_cache(&self, source_hash: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(CACHE_MAGIC);
+        out.push(CACHE_VERSION);
+        out.extend_from_slice(&source_hash.to_le_bytes());
+
+        write_len(&mut out, self.exact_calls.len());
+        for (call, entry) in &self.exact_calls {
+            write_str(&mut out, call);
+            write_entry(&mut out, entry);
+        }
+        write_len(&mut out, self.prefix_trie.nodes.len());
+        for node in &self.prefix_trie.nodes {
+            write_len(&mut out, node.children.len());
+            for (byte, next) in &node.children {
+                out.push(*byte);
+                write_len(&mut out, *next);
+            }
+            match &node.entry {
+                Some(entry) => {
+                    out.push(1);
+                    write_entry(&mut out, entry);
+                }
+                None => out.push(0),
+            }
+        }
+        write_len(&mut out, self.prefixes.len());
+        for prefix in &self.prefixes {
+            write_str(&mut out, prefix);
+        }
+        out
+    }
+
+    /// Read a cache made by `to_cache`. None if it's damaged, from another
+    /// layout version, or made from a different cty.dat.
+    pub fn from_cache(bytes: &[u8], source_hash: u64) -> Option<Self> {
+        let mut reader = CacheReader { bytes, pos: 0 };
+        if reader.take(4)? != CACHE_MAGIC || reader.byte()? != CACHE_VERSION {
+            return None;
+        }
+        if u64::from_le_bytes(reader.take(8)?.try_into().ok()?) != source_hash {
+            return None;
+        }
+
+        let count = reader.len()?;
+        let mut exact_calls = HashMap::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let call = reader.string()?;
+            exact_calls.insert(call, reader.entry()?);
+        }
+        let count = reader.len()?;
+        let mut nodes = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let children = (0..reader.len()?)
+                .map(|_| Some((reader.byte()?, reader.len()?)))
+                .collect::<Option<Vec<_>>>()?;
+            let entry = match reader.byte()? {
+                0 => None,
+                1 => Some(reader.entry()?),
+                _ => return None,
+            };
+            nodes.push(TrieNode { children, entry });
+        }
+        // Every child has to be a node, or a lookup would panic
+        let node_count = nodes.len();
+        if node_count == 0
+            || nodes
+                .iter()
+                .flat_map(|node| &node.children)
+                .any(|(_, next)| *next >= node_count)
+        {
+            return None;
+        }
+        let prefixes = (0..reader.len()?)
+            .map(|_| reader.string())
+            .collect::<Option<Vec<_>>>()?;
+        if reader.pos != bytes.len() {
+            return None;
+        }
+
+        Some(Self {
+            exact_calls,
+            prefix_trie: PrefixTrie { nodes },
+            prefixes,
+        })
+    }
+}
+
+/// FNV-1a hash of cty.dat, to tell whether a cache was made from it
+pub fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn write_str(out: &mut Vec<u8>, text: &str) {
+    write_len(out, text.len());
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn write_entry(out: &mut Vec<u8>, entry: &PrefixEntry) {
+    out.push(entry.cq_zone);
+    // 0 for none, otherwise one past the continent's place in ALL
+    out.push(
+        entry
+            .continent
+            .and_then(|continent| Continent::ALL.iter().position(|c| *c == continent))
+            .map_or(0, |index| index as u8 + 1),
+    );
+    write_str(out, &entry.country_prefix);
+}
+
+struct CacheReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl CacheReader<'_> {
+    fn take(&mut self, count: usize) -> Option<&[u8]> {
+        let end = self.pos.checked_add(count)?;
+        let taken = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(taken)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn entry(&mut self) -> Option<PrefixEntry> {
+        let cq_zone = self.byte()?;
+        let continent = match self.byte()? {
+            0 => None,
+            index => Some(*Continent::ALL.get(usize::from(index) - 1)?),
+        };
+        Some(PrefixEntry {
+            cq_zone,
+            continent,
+            country_prefix: self.string()?,
+        })
+    }
+}
+
+/// Portable indicators that don't change where a station is
+const IGNORED_SUFFIXES: [&str; 7] = ["P", "M", "A", "QRP", "QRPP", "LH", "J"];
+
+/// Maritime and aeronautical mobile: not in any country
+const MOBILE_AT_SEA_OR_AIR: [&str; 2] = ["MM", "AM"];
+
+/// What to look up to find where a station is operating, following the
+/// usual portable rules:
+/// - `K1ABC/P`, `K1ABC/QRP`: the home call (the indicator is ignored)
+/// - `K1ABC/7`: the home call in call area 7 (`K7ABC`)
+/// - `F/K1ABC`, `VP2E/NT0X`, `K1ABC/VE3`: the shorter part is the prefix
+///   of the country operated from
+/// - `K1ABC/MM`, `K1ABC/AM`: None
+pub fn portable_location(callsign: &str) -> Option<String> {
+    let parts: Vec<&str> = callsign
+        .split('/')
+        .filter(|part| !part.is_empty() && !IGNORED_SUFFIXES.contains(part))
+        .collect();
+    if callsign
+        .split('/')
+        .skip(1)
+        .any(|part| MOBILE_AT_SEA_OR_AIR.contains(&part))
+    {
+        return None;
+    }
+
+    match parts.as_slice() {
+        [] => None,
+        [call] => Some(call.to_string()),
+        [first, second, ..] => {
+            // On a tie the prefix comes first, as in VP2E/NT0X
+            let (call, other) = if first.len() > second.len() {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            // A single digit moves the call to another call area
+            if other.len() == 1 && other.chars().all(|c| c.is_ascii_digit()) {
+                let area = call.rfind(|c: char| c.is_ascii_digit());
+                return Some(match area {
+                    Some(index) => format!("{}{}{}", &call[..index], other, &call[index + 1..]),
+                    None => call.to_string(),
+                });
+            }
+            Some(other.to_string())
+        }
+    }
+}
+
+/// The station's own call without any portable prefix or suffix
+/// (`K1ABC` for `F/K1ABC` or `K1ABC/7`)
+pub fn base_callsign(callsign: &str) -> &str {
+    callsign
+        .split('/')
+        .filter(|part| !IGNORED_SUFFIXES.contains(part))
+        .max_by_key(|part| part.len())
+        .unwrap_or(callsign)
 }
 
 #[cfg(test)]
@@ -289,6 +630,7 @@ mod tests {
         let line = "United States:            05:  08:  NA:   37.60:    91.87:     5.0:  K:";
         let entity = CtyDat::parse_header(line).unwrap();
         assert_eq!(entity.cq_zone, 5);
+        assert_eq!(entity.continent, Some(Continent::NorthAmerica));
         assert_eq!(entity.primary_prefix, "K");
     }
 
@@ -356,6 +698,78 @@ Germany:                  14:  28:  EU:   51.00:   -10.00:    -1.0:  DL:
         assert_eq!(cty.lookup_cq_zone("DL1ABC"), Some(14));
     }
 
+    #[test]
+    fn trie_finds_the_longest_prefix() {
+        let content = include_str!("../data/cty.dat");
+        let cty = CtyDat::parse(content);
+        // The linear scan the trie replaced: every (prefix, entry) pair,
+        // longest first, the first listed winning a tie
+        let (_, mut pairs) = CtyDat::parse_tables(content);
+        pairs.sort_by_key(|(prefix, _)| Reverse(prefix.len()));
+        for call in [
+            "K1ABC", "KH6XX", "KL7RA", "VE3ABC", "VK9NS", "DL1ABC", "JA1XYZ", "3DA0RU", "ZZ9Z",
+            "Q1ABC",
+        ] {
+            let expected = pairs
+                .iter()
+                .find(|(prefix, _)| call.starts_with(prefix.as_str()))
+                .map(|(_, entry)| entry);
+            let found = cty.prefix_trie.longest_match(call);
+            assert_eq!(
+                found.map(|entry| (&entry.country_prefix, entry.cq_zone)),
+                expected.map(|entry| (&entry.country_prefix, entry.cq_zone)),
+                "{}",
+                call
+            );
+        }
+    }
+
+    #[test]
+    fn binary_cache_round_trips_and_rejects_stale_or_damaged_data() {
+        let content = include_str!("../data/cty.dat");
+        let hash = content_hash(content);
+        let cty = CtyDat::parse(content);
+        let cache = cty.to_cache(hash);
+
+        let cached = CtyDat::from_cache(&cache, hash).unwrap();
+        assert_eq!(cached.prefixes, cty.prefixes);
+        for call in [
+            "W1AW",
+            "KH6XX",
+            "DL1ABC",
+            "VP2E/K1ABC",
+            "K1ABC/MM",
+            "3DA0RU",
+        ] {
+            assert_eq!(
+                cached.lookup_prefix(call),
+                cty.lookup_prefix(call),
+                "{}",
+                call
+            );
+            assert_eq!(
+                cached.lookup_cq_zone(call),
+                cty.lookup_cq_zone(call),
+                "{}",
+                call
+            );
+            assert_eq!(
+                cached.lookup_continent(call),
+                cty.lookup_continent(call),
+                "{}",
+                call
+            );
+        }
+
+        // Made from another cty.dat, cut short, or from another layout
+        assert!(CtyDat::from_cache(&cache, hash ^ 1).is_none());
+        assert!(CtyDat::from_cache(&cache[..cache.len() - 1], hash).is_none());
+        let mut other_version = cache.clone();
+        other_version[4] += 1;
+        assert!(CtyDat::from_cache(&other_version, hash).is_none());
+        assert!(CtyDat::from_cache(b"", hash).is_none());
+    }
+
     #[test]
     fn test_real_cty_file() {
         // Test with the actual embedded cty.dat
@@ -409,4 +823,49 @@ Germany:                  14:  28:  EU:   51.00:   -10.00:    -1.0:  DL:
         // Two German callsigns should be same country
         assert!(cty.same_country("DL1ABC", "DK2XYZ"));
     }
+
+    #[test]
+    fn test_lookup_continent() {
+        let cty = CtyDat::parse(include_str!("../data/cty.dat"));
+        assert_eq!(cty.lookup_continent("W1AW"), Some(Continent::NorthAmerica));
+        assert_eq!(cty.lookup_continent("DL1ABC"), Some(Continent::Europe));
+        assert_eq!(cty.lookup_continent("JA1ABC"), Some(Continent::Asia));
+        assert_eq!(cty.lookup_continent("VK2ABC"), Some(Continent::Oceania));
+        assert_eq!(
+            cty.lookup_continent("LU1ABC"),
+            Some(Continent::SouthAmerica)
+        );
+        assert_eq!(cty.lookup_continent("ZS6ABC"), Some(Continent::Africa));
+    }
+
+    #[test]
+    fn portable_calls_are_looked_up_where_they_operate() {
+        let content = r#"
+United States:            05:  08:  NA:   37.60:    91.87:     5.0:  K:
+    K,W,N,AA,
+    K0(4)[7],W0(4)[7],N0(4)[7],
+    K7(3)[6],W7(3)[6],N7(3)[6];
+France:                   14:  27:  EU:   46.00:    -2.00:    -1.0:  F:
+    F;
+Anguilla:                 08:  11:  NA:   18.23:    63.00:     4.0:  VP2E:
+    VP2E;
+Canada:                   05:  09:  NA:   44.35:    78.75:     5.0:  VE:
+    VA,VE,VO,VY;
+"#;
+        let cty = CtyDat::parse(content);
+
+        assert_eq!(cty.lookup_cq_zone("K1ABC/7"), Some(3));
+        assert_eq!(cty.lookup_cq_zone("K1ABC/P"), Some(5));
+        assert_eq!(cty.lookup_prefix("F/K1ABC"), Some("F".to_string()));
+        assert_eq!(cty.lookup_prefix("VP2E/NT0X"), Some("VP2E".to_string()));
+        assert_eq!(cty.lookup_prefix("K1ABC/VE3"), Some("VE".to_string()));
+        assert_eq!(cty.lookup_prefix("F/K1ABC/P"), Some("F".to_string()));
+        assert_eq!(cty.lookup_prefix("K1ABC/MM"), None);
+        assert!(!cty.same_country("F/K1ABC", "K1ABC"));
+
+        assert_eq!(base_callsign("F/K1ABC"), "K1ABC");
+        assert_eq!(base_callsign("K1ABC/7"), "K1ABC");
+        assert_eq!(base_callsign("VP2E/NT0X"), "NT0X");
+        assert_eq!(base_callsign("K1ABC/QRP"), "K1ABC");
+    }
 }
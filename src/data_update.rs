@@ -0,0 +1,305 @@
+// Newer copies of the data built into the app (cty.dat and the CWT roster),
+// downloaded into the config folder and used in place of the built-in ones
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::config::{AppSettings, DataUpdateSettings};
+use crate::contest::callsign::CallsignFile;
+use crate::contest::FileCallsignSource;
+use crate::cty::{self, CtyDat};
+use crate::i18n::{tr, tr_args};
+
+const BUILT_IN_CTY: &str = include_str!("../data/cty.dat");
+
+/// Parsed cty.dat, kept between runs so it isn't parsed at every start
+const CTY_CACHE_FILE: &str = "cty.cache";
+/// Give up on a download after this long
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFile {
+    Cty,
+    CwtRoster,
+}
+
+impl DataFile {
+    pub const ALL: [DataFile; 2] = [DataFile::Cty, DataFile::CwtRoster];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DataFile::Cty => "cty.dat",
+            DataFile::CwtRoster => "CWT roster",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            DataFile::Cty => "cty.dat",
+            DataFile::CwtRoster => "cwt_callsigns.txt",
+        }
+    }
+
+    fn url(self, settings: &DataUpdateSettings) -> &str {
+        match self {
+            DataFile::Cty => &settings.cty_url,
+            DataFile::CwtRoster => &settings.cwt_roster_url,
+        }
+    }
+
+    /// Turn away a download that isn't this kind of file (an error page,
+    /// say) so it doesn't replace good data
+    fn check(self, content: &str) -> Result<(), String> {
+        let usable = match self {
+            DataFile::Cty => CtyDat::parse(content).prefixes().next().is_some(),
+            DataFile::CwtRoster => FileCallsignSource::parse(content).0.entry_count() > 0,
+        };
+        if usable {
+            Ok(())
+        } else {
+            Err(format!("the download is not a {} file", self.name()))
+        }
+    }
+}
+
+/// Downloaded files go in a folder next to the settings file
+pub fn data_dir() -> PathBuf {
+    AppSettings::config_path().with_file_name("data")
+}
+
+/// The downloaded copy of `file`, if there is one
+pub fn downloaded(file: DataFile) -> Option<PathBuf> {
+    let path = data_dir().join(file.file_name());
+    path.is_file().then_some(path)
+}
+
+/// cty.dat as last downloaded, or the copy built into the app
+pub fn cty_data() -> Cow<'static, str> {
+    downloaded(DataFile::Cty)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .filter(|content| DataFile::Cty.check(content).is_ok())
+        .map(Cow::Owned)
+        .unwrap_or(Cow::Borrowed(BUILT_IN_CTY))
+}
+
+/// cty.dat parsed once and shared by everything that looks up calls; read
+/// again after an update
+static CTY: RwLock<Option<Arc<CtyDat>>> = RwLock::new(None);
+
+pub fn cty() -> Arc<CtyDat> {
+    if let Some(cty) = CTY.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Arc::clone(cty);
+    }
+    // Tests always parse, leaving no cache behind
+    let cache_dir = (!cfg!(test)).then(data_dir);
+    let cty = Arc::new(cached_cty(&cty_data(), cache_dir.as_deref()));
+    *CTY.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&cty));
+    cty
+}
+
+/// cty.dat from the binary cache in `cache_dir` (next to the downloads)
+/// when the cache was made from this content; otherwise parsed, and cached
+/// for the next start
+fn cached_cty(content: &str, cache_dir: Option<&Path>) -> CtyDat {
+    let Some(cache_dir) = cache_dir else {
+        return CtyDat::parse(content);
+    };
+    let path = cache_dir.join(CTY_CACHE_FILE);
+    let hash = cty::content_hash(content);
+    if let Some(cty) = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| CtyDat::from_cache(&bytes, hash))
+    {
+        return cty;
+    }
+    let cty = CtyDat::parse(content);
+    // Without a cache the next start just parses again
+    let _ =
+        std::fs::create_dir_all(cache_dir).and_then(|()| std::fs::write(&path, cty.to_cache(hash)));
+    cty
+}
+
+/// The release date cty.dat carries as a `=VERyyyymmdd` entry
+fn cty_version(content: &str) -> Option<String> {
+    let start = content.find("=VER")? + 4;
+    let digits: String = content[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    if digits.len() != 8 {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..]
+    ))
+}
+
+/// When a downloaded file was saved
+fn saved_on(path: &Path) -> Option<String> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(
+        chrono::DateTime::<chrono::Local>::from(modified)
+            .format("%Y-%m-%d")
+            .to_string(),
+    )
+}
+
+fn describe(file: DataFile) -> String {
+    let path = downloaded(file);
+    match (file, path) {
+        (DataFile::Cty, Some(path)) => {
+            let version = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| cty_version(&content))
+                .or_else(|| saved_on(&path))
+                .unwrap_or_default();
+            tr_args("{} (downloaded)", &[&version])
+        }
+        (DataFile::Cty, None) => tr_args(
+            "{} (built in)",
+            &[&cty_version(BUILT_IN_CTY).unwrap_or_default()],
+        ),
+        (DataFile::CwtRoster, Some(path)) => {
+            tr_args("downloaded {}", &[&saved_on(&path).unwrap_or_default()])
+        }
+        (DataFile::CwtRoster, None) => tr("the callsign file in Settings").to_string(),
+    }
+}
+
+/// What each data file in use is, read once and again after an update
+static VERSIONS: RwLock<Option<Vec<(DataFile, String)>>> = RwLock::new(None);
+
+pub fn versions() -> Vec<(DataFile, String)> {
+    if let Some(versions) = VERSIONS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return versions.clone();
+    }
+    let versions: Vec<(DataFile, String)> = DataFile::ALL
+        .iter()
+        .map(|file| (*file, describe(*file)))
+        .collect();
+    *VERSIONS.write().unwrap_or_else(|e| e.into_inner()) = Some(versions.clone());
+    versions
+}
+
+fn fetch(url: &str) -> Result<String, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(DOWNLOAD_TIMEOUT))
+        .build()
+        .into();
+    agent
+        .get(url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| e.to_string())
+}
+
+/// Download one file and, if it checks out, save it over the last one
+fn update(file: DataFile, url: &str) -> Result<(), String> {
+    let content = fetch(url)?;
+    file.check(&content)?;
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    // Write beside the old copy first so a failed write doesn't lose it
+    let path = dir.join(file.file_name());
+    let partial = path.with_extension("part");
+    std::fs::write(&partial, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, &path).map_err(|e| e.to_string())
+}
+
+/// How each file's download went
+pub type UpdateResults = Vec<(DataFile, Result<(), String>)>;
+
+/// Runs downloads on a background thread so the app keeps running
+#[derive(Default)]
+pub struct DataUpdater {
+    pending: Option<Receiver<UpdateResults>>,
+}
+
+impl DataUpdater {
+    pub fn running(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Download every file that has an address set, unless a check is
+    /// already under way
+    pub fn start(&mut self, settings: &DataUpdateSettings) {
+        if self.running() {
+            return;
+        }
+        let jobs: Vec<(DataFile, String)> = DataFile::ALL
+            .iter()
+            .map(|file| (*file, file.url(settings).trim().to_string()))
+            .filter(|(_, url)| !url.is_empty())
+            .collect();
+        let (tx, rx) = channel();
+        std::thread::spawn(move || {
+            let results = jobs
+                .into_iter()
+                .map(|(file, url)| (file, update(file, &url)))
+                .collect();
+            let _ = tx.send(results);
+        });
+        self.pending = Some(rx);
+    }
+
+    /// The results, once every download has finished
+    pub fn poll(&mut self) -> Option<UpdateResults> {
+        let received = self.pending.as_ref()?.try_recv();
+        match received {
+            Ok(results) => {
+                self.pending = None;
+                *VERSIONS.write().unwrap_or_else(|e| e.into_inner()) = None;
+                *CTY.write().unwrap_or_else(|e| e.into_inner()) = None;
+                Some(results)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downloads_are_checked_before_use() {
+        assert_eq!(cty_version(BUILT_IN_CTY).as_deref(), Some("2022-02-22"));
+        assert_eq!(cty_version("=VER2022"), None);
+
+        let error_page = "<html><body>404 Not Found</body></html>";
+        assert!(DataFile::Cty.check(BUILT_IN_CTY).is_ok());
+        assert!(DataFile::Cty.check(error_page).is_err());
+        assert!(DataFile::CwtRoster
+            .check("!!Order!!,Call,Name,Exch1\nK9CT,Craig,276\n")
+            .is_ok());
+        assert!(DataFile::CwtRoster.check(error_page).is_err());
+    }
+
+    #[test]
+    fn tests_leave_the_users_data_alone() {
+        assert!(data_dir().starts_with(std::env::temp_dir()));
+        cty();
+        assert!(!data_dir().join(CTY_CACHE_FILE).exists());
+
+        // The cache is written to and read from the folder it is given
+        let dir = std::env::temp_dir().join(format!("cty_cache_{}", std::process::id()));
+        let parsed = cached_cty(BUILT_IN_CTY, Some(&dir));
+        let written = dir.join(CTY_CACHE_FILE).is_file();
+        let cached = cached_cty(BUILT_IN_CTY, Some(&dir));
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(written);
+        assert_eq!(cached.prefixes().count(), parsed.prefixes().count());
+    }
+}
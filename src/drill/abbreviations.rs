@@ -0,0 +1,125 @@
+// Head copy of the abbreviations and short words contest and ragchew
+// exchanges are built from, typed as a sequence or picked from a choice
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Each abbreviation and what it means
+pub const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("TU", "thank you"),
+    ("73", "best regards"),
+    ("HW?", "how copy?"),
+    ("QRZ", "who is calling?"),
+    ("AGN", "again"),
+    ("B4", "before (worked before)"),
+    ("CQ", "calling any station"),
+    ("DE", "from, this is"),
+    ("TEST", "contest"),
+    ("5NN", "599 signal report, cut"),
+    ("NR", "number"),
+    ("NR?", "what is your number?"),
+    ("CL?", "what is your call?"),
+    ("R", "received, roger"),
+    ("K", "over, go ahead"),
+    ("KN", "over, only you"),
+    ("BK", "back to you"),
+    ("?", "say again"),
+    ("EE", "dit dit, a courtesy"),
+    ("GL", "good luck"),
+    ("TNX", "thanks"),
+    ("PSE", "please"),
+    ("SRI", "sorry"),
+    ("FB", "fine business, excellent"),
+    ("UR", "your, you are"),
+    ("ES", "and"),
+    ("OP", "operator"),
+    ("QTH", "location"),
+    ("RST", "signal report"),
+    ("WX", "weather"),
+    ("PWR", "power"),
+    ("ANT", "antenna"),
+    ("CFM", "confirm"),
+    ("QSL", "I confirm receipt"),
+    ("QRL?", "is the frequency in use?"),
+    ("QSY", "change frequency"),
+    ("QRS", "send slower"),
+    ("QRQ", "send faster"),
+    ("QRM", "interference"),
+    ("QRN", "static"),
+    ("QSB", "fading"),
+    ("NIL", "not in log"),
+    ("GM", "good morning"),
+    ("GA", "good afternoon"),
+    ("GE", "good evening"),
+    ("OM", "old man, a fellow ham"),
+    ("DX", "distant station"),
+    ("CPY", "copy"),
+];
+
+/// Abbreviations in a typed sequence
+const SEQUENCE_MIN: usize = 3;
+const SEQUENCE_MAX: usize = 5;
+/// Answers offered in a multiple choice round
+pub const CHOICES: usize = 4;
+
+pub fn meaning(abbreviation: &str) -> Option<&'static str> {
+    ABBREVIATIONS
+        .iter()
+        .find(|(abbr, _)| *abbr == abbreviation)
+        .map(|(_, meaning)| *meaning)
+}
+
+/// A few different abbreviations in random order, to type as sent
+pub fn generate_sequence() -> String {
+    let mut rng = rand::thread_rng();
+    let count = rng.gen_range(SEQUENCE_MIN..=SEQUENCE_MAX);
+    ABBREVIATIONS
+        .choose_multiple(&mut rng, count)
+        .map(|(abbr, _)| *abbr)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One abbreviation to send and the choices offered for it, the right one
+/// among them
+pub fn generate_choice() -> (String, Vec<String>) {
+    let mut rng = rand::thread_rng();
+    let mut options: Vec<String> = ABBREVIATIONS
+        .choose_multiple(&mut rng, CHOICES)
+        .map(|(abbr, _)| abbr.to_string())
+        .collect();
+    let sent = options[0].clone();
+    options.shuffle(&mut rng);
+    (sent, options)
+}
+
+/// The answer given: a choice's number (1-4) or its text
+pub fn chosen<'a>(options: &'a [String], copied: &'a str) -> &'a str {
+    let copied = copied.trim();
+    match copied.parse::<usize>() {
+        Ok(n) if (1..=options.len()).contains(&n) => &options[n - 1],
+        _ => copied,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_use_known_abbreviations() {
+        let sequence = generate_sequence();
+        let words: Vec<&str> = sequence.split(' ').collect();
+        assert!((SEQUENCE_MIN..=SEQUENCE_MAX).contains(&words.len()));
+        assert!(words.iter().all(|w| meaning(w).is_some()));
+
+        let (sent, options) = generate_choice();
+        assert_eq!(options.len(), CHOICES);
+        assert!(options.contains(&sent));
+
+        let options: Vec<String> = ["TU", "73", "QRZ", "B4"].map(String::from).to_vec();
+        assert_eq!(chosen(&options, " 3 "), "QRZ");
+        assert_eq!(chosen(&options, "B4"), "B4");
+        assert_eq!(chosen(&options, "5"), "5");
+    }
+}
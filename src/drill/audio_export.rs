@@ -0,0 +1,220 @@
+// Offline audio drills: batches of pileup or exchange recordings written
+// to WAV files with an answer key, for practice away from the computer
+
+use chrono::Local;
+use rand::Rng;
+use std::fmt::Write as _;
+
+use crate::audio::render::{render_stations, write_wav};
+use crate::config::{AppSettings, AudioDrillKind, AudioDrillSettings, SimulationSettings};
+use crate::contest::{cut_number_exchange, CallsignSource, Contest};
+use crate::export::export_path;
+use crate::messages::{StationId, StationParams};
+
+/// Silence kept after the last station in each recording (ms)
+const TAIL_MS: u32 = 1500;
+/// Latest a pileup caller starts after the recording begins (ms)
+const PILEUP_MAX_START_MS: u32 = 600;
+/// Lead-in before the station in an exchange recording (ms)
+const EXCHANGE_LEAD_IN_MS: u32 = 400;
+
+/// One recording: what each station sends, and what should be copied
+pub struct DrillClip {
+    pub stations: Vec<(StationParams, String)>,
+    pub answer: String,
+}
+
+/// Build `settings.count` recordings from the contest's callsign source
+pub fn generate_clips(
+    settings: &AudioDrillSettings,
+    simulation: &SimulationSettings,
+    contest: &dyn Contest,
+    contest_settings: &toml::Value,
+    cut_number_probability: f32,
+    callsigns: &mut dyn CallsignSource,
+) -> Vec<DrillClip> {
+    let mut rng = rand::thread_rng();
+    let mut serial = 1;
+    let mut next_id = 0;
+    let wpm_min = settings.wpm_min.min(settings.wpm_max);
+    let wpm_max = settings.wpm_min.max(settings.wpm_max);
+    let half_spread = (simulation.frequency_spread_hz / 2.0).max(0.0);
+    let amplitude_min = simulation.amplitude_min.min(simulation.amplitude_max);
+    let amplitude_max = simulation.amplitude_min.max(simulation.amplitude_max);
+    let exchange_fields = contest.exchange_fields();
+
+    let mut clips = Vec::new();
+    for _ in 0..settings.count {
+        let station_count = match settings.kind {
+            AudioDrillKind::Pileup => rng.gen_range(1..=settings.max_callers.max(1)),
+            AudioDrillKind::Exchange => 1,
+        };
+
+        let mut stations = Vec::new();
+        for _ in 0..station_count {
+            let Some((callsign, exchange)) =
+                callsigns.random(contest, serial, contest_settings, &mut rng)
+            else {
+                break;
+            };
+            serial += 1;
+            next_id += 1;
+
+            let (message, reaction_delay_ms, frequency_offset_hz) = match settings.kind {
+                AudioDrillKind::Pileup => (
+                    callsign.clone(),
+                    rng.gen_range(0..=PILEUP_MAX_START_MS),
+                    rng.gen_range(-half_spread..=half_spread),
+                ),
+                AudioDrillKind::Exchange => {
+                    let sent = if rng.gen::<f32>() < cut_number_probability {
+                        cut_number_exchange(&exchange, &exchange_fields)
+                    } else {
+                        exchange.clone()
+                    };
+                    (
+                        format!("{} {}", callsign, contest.format_exchange(&sent)),
+                        EXCHANGE_LEAD_IN_MS,
+                        0.0,
+                    )
+                }
+            };
+
+            let params = StationParams {
+                id: StationId(next_id),
+                callsign,
+                exchange,
+                frequency_offset_hz,
+                wpm: rng.gen_range(wpm_min..=wpm_max),
+                amplitude: rng.gen_range(amplitude_min..=amplitude_max),
+                reaction_delay_ms,
+            };
+            stations.push((params, message));
+        }
+        if stations.is_empty() {
+            break;
+        }
+
+        // Pileups are listed low to high pitch, the way they're heard
+        stations.sort_by(|a, b| a.0.frequency_offset_hz.total_cmp(&b.0.frequency_offset_hz));
+        let answer = match settings.kind {
+            AudioDrillKind::Pileup => stations
+                .iter()
+                .map(|(params, _)| params.callsign.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            AudioDrillKind::Exchange => {
+                let params = &stations[0].0;
+                format!(
+                    "{} {}",
+                    params.callsign,
+                    contest.format_exchange(&params.exchange)
+                )
+            }
+        };
+        clips.push(DrillClip { stations, answer });
+    }
+    clips
+}
+
+/// Render the recordings into a new folder in the export directory, with
+/// an answer key. Returns the folder path.
+pub fn export_audio_drill(settings: &AppSettings, clips: &[DrillClip]) -> Result<String, String> {
+    if clips.is_empty() {
+        return Err("No recordings to export (check the callsign file)".to_string());
+    }
+
+    let drill = &settings.drill.audio_export;
+    let folder = format!("CWCT-AudioDrill-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let dir = export_path(settings, &folder)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    let mut audio = settings.audio.clone();
+    audio.noise_level = drill.noise_level;
+
+    let mut key = String::new();
+    let _ = writeln!(
+        key,
+        "{} drill, {} recordings, {}-{} WPM",
+        match drill.kind {
+            AudioDrillKind::Pileup => "Pileup",
+            AudioDrillKind::Exchange => "Exchange",
+        },
+        clips.len(),
+        drill.wpm_min.min(drill.wpm_max),
+        drill.wpm_min.max(drill.wpm_max)
+    );
+    if drill.kind == AudioDrillKind::Pileup {
+        let _ = writeln!(key, "Callsigns are listed from lowest to highest pitch.");
+    }
+    let _ = writeln!(key);
+
+    for (index, clip) in clips.iter().enumerate() {
+        let name = format!("{:02}.wav", index + 1);
+        let (left, right) = render_stations(&audio, &clip.stations, TAIL_MS);
+        write_wav(&dir.join(&name), audio.sample_rate, &left, &right)?;
+        let _ = writeln!(key, "{}  {}", name, clip.answer);
+    }
+
+    std::fs::write(dir.join("answer-key.txt"), key)
+        .map_err(|e| format!("Failed to write answer key: {}", e))?;
+
+    Ok(dir.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clips_match_their_answer_key() {
+        let contest = crate::contest::create_contest("cqwpx").unwrap();
+        let contest_settings = contest.default_settings();
+        let mut source = contest.callsign_source(&contest_settings).unwrap();
+        let simulation = SimulationSettings::default();
+
+        let pileup = AudioDrillSettings {
+            count: 5,
+            max_callers: 3,
+            ..AudioDrillSettings::default()
+        };
+        let clips = generate_clips(
+            &pileup,
+            &simulation,
+            contest.as_ref(),
+            &contest_settings,
+            0.0,
+            source.as_mut(),
+        );
+        assert_eq!(clips.len(), 5);
+        for clip in &clips {
+            assert!((1..=3).contains(&clip.stations.len()));
+            let calls: Vec<&str> = clip
+                .stations
+                .iter()
+                .map(|(p, _)| p.callsign.as_str())
+                .collect();
+            assert_eq!(clip.answer, calls.join(" "));
+        }
+
+        let exchange = AudioDrillSettings {
+            kind: AudioDrillKind::Exchange,
+            count: 2,
+            ..AudioDrillSettings::default()
+        };
+        let clips = generate_clips(
+            &exchange,
+            &simulation,
+            contest.as_ref(),
+            &contest_settings,
+            0.0,
+            source.as_mut(),
+        );
+        for clip in &clips {
+            let (params, message) = &clip.stations[0];
+            assert_eq!(clip.stations.len(), 1);
+            assert_eq!(&clip.answer, message);
+            assert!(message.starts_with(&params.callsign));
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Two-stage copy verification: a station sends its call, then repeats it.
+// First decide whether the two were the same, then type the call. When they
+// differ the repeat is right, as when you ask a station to send again.
+
+use rand::Rng;
+
+use crate::station::confusion::confused_callsign;
+
+use super::same_call::random_call;
+
+/// Where a round is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallTwiceStage {
+    /// Waiting for same or different
+    Compare,
+    /// Waiting for the call
+    Copy { said_identical: bool },
+}
+
+/// The round being played
+#[derive(Clone, Debug)]
+pub struct CallTwiceRound {
+    /// The call as the station really sent it the second time
+    pub call: String,
+    /// Whether the first sending was the same call
+    pub identical: bool,
+    pub stage: CallTwiceStage,
+}
+
+impl CallTwiceRound {
+    /// Take the same/different answer and move on to copying the call
+    pub fn compare(&mut self, said_identical: bool) {
+        if self.stage == CallTwiceStage::Compare {
+            self.stage = CallTwiceStage::Copy { said_identical };
+        }
+    }
+
+    /// Score the copied call, if the comparison has been answered
+    pub fn finish(&self, copied: &str) -> Option<Verification> {
+        match self.stage {
+            CallTwiceStage::Compare => None,
+            CallTwiceStage::Copy { said_identical } => Some(Verification {
+                identical: self.identical,
+                said_identical,
+                call_correct: copied.trim().eq_ignore_ascii_case(&self.call),
+            }),
+        }
+    }
+}
+
+/// How a finished round went
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verification {
+    pub identical: bool,
+    pub said_identical: bool,
+    pub call_correct: bool,
+}
+
+impl Verification {
+    pub fn compare_correct(&self) -> bool {
+        self.identical == self.said_identical
+    }
+}
+
+/// Totals over finished rounds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallTwiceStats {
+    pub rounds: usize,
+    pub compare_correct: usize,
+    pub calls_correct: usize,
+    /// Different calls taken for the same one
+    pub busts_missed: usize,
+    /// The same call taken for two different ones
+    pub false_alarms: usize,
+}
+
+impl CallTwiceStats {
+    pub fn from_rounds<'a>(rounds: impl IntoIterator<Item = &'a Verification>) -> Self {
+        let mut stats = Self::default();
+        for round in rounds {
+            stats.rounds += 1;
+            if round.compare_correct() {
+                stats.compare_correct += 1;
+            } else if round.identical {
+                stats.false_alarms += 1;
+            } else {
+                stats.busts_missed += 1;
+            }
+            if round.call_correct {
+                stats.calls_correct += 1;
+            }
+        }
+        stats
+    }
+}
+
+/// Start a round. Returns (sent text, round); half the time the first
+/// sending is a dit or dah off.
+pub fn generate_round() -> (String, CallTwiceRound) {
+    let mut rng = rand::thread_rng();
+    let call = random_call(&mut rng);
+    let identical = rng.gen_bool(0.5);
+    let first = if identical {
+        call.clone()
+    } else {
+        confused_callsign(&call, &mut rng)
+    };
+    let round = CallTwiceRound {
+        call: call.clone(),
+        identical,
+        stage: CallTwiceStage::Compare,
+    };
+    (format!("{} {}", first, call), round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_compare_then_copy() {
+        let (sent, mut round) = generate_round();
+        let calls: Vec<&str> = sent.split_whitespace().collect();
+        assert_eq!(calls[1], round.call);
+        assert_eq!(calls[0] == calls[1], round.identical);
+
+        // No result until same/different has been answered
+        assert_eq!(round.finish(&round.call), None);
+        round.compare(round.identical);
+        // A second answer doesn't change the first
+        round.compare(!round.identical);
+        let result = round.finish(&round.call.to_lowercase()).unwrap();
+        assert!(result.compare_correct());
+        assert!(result.call_correct);
+    }
+
+    #[test]
+    fn stats_count_missed_busts_and_false_alarms() {
+        let rounds = [
+            Verification {
+                identical: false,
+                said_identical: true,
+                call_correct: true,
+            },
+            Verification {
+                identical: true,
+                said_identical: false,
+                call_correct: false,
+            },
+            Verification {
+                identical: false,
+                said_identical: false,
+                call_correct: true,
+            },
+        ];
+        assert_eq!(
+            CallTwiceStats::from_rounds(&rounds),
+            CallTwiceStats {
+                rounds: 3,
+                compare_correct: 1,
+                calls_correct: 2,
+                busts_missed: 1,
+                false_alarms: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// Ragchew-style phrases for general head-copy practice
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const NAMES: &[&str] = &[
+    "BOB", "JOE", "TOM", "JIM", "BILL", "MIKE", "DAVE", "STEVE", "JOHN", "RICK", "ANN", "SUE",
+    "MARY", "PAT", "KEN", "DON", "RON", "AL", "ED", "HANS", "PETE", "GARY", "LARRY", "CHAD",
+];
+
+const QTHS: &[&str] = &[
+    "DENVER CO",
+    "BOSTON MA",
+    "AUSTIN TX",
+    "MADISON WI",
+    "RENO NV",
+    "TAMPA FL",
+    "SEATTLE WA",
+    "TUCSON AZ",
+    "OMAHA NE",
+    "DAYTON OH",
+    "NR CHICAGO",
+    "OTTAWA ON",
+    "MUNICH",
+    "TOKYO",
+];
+
+const RIGS: &[&str] = &[
+    "K3",
+    "K4",
+    "IC7300",
+    "IC7610",
+    "FT991",
+    "FTDX10",
+    "TS590",
+    "KX2",
+    "QCX",
+    "FLEX 6600",
+];
+
+const ANTENNAS: &[&str] = &[
+    "DIPOLE",
+    "VERTICAL",
+    "YAGI",
+    "END FED",
+    "LOOP",
+    "G5RV",
+    "HEXBEAM",
+    "LONG WIRE",
+];
+
+const PROSIGNS: &[&str] = &["<BK>", "<KN>", "<AR>", "<SK>"];
+
+const WEATHER: &[&str] = &[
+    "SUNNY", "CLOUDY", "RAIN", "SNOW", "WINDY", "FOGGY", "HOT", "COLD",
+];
+
+/// Generate a short ragchew transmission made of two or three phrases
+pub fn generate_phrase() -> String {
+    let mut rng = rand::thread_rng();
+    let mut phrases = vec![
+        name_phrase(&mut rng),
+        qth_phrase(&mut rng),
+        rig_phrase(&mut rng),
+        weather_phrase(&mut rng),
+    ];
+    phrases.shuffle(&mut rng);
+    let count = rng.gen_range(2..=3);
+    phrases.truncate(count);
+    // Hand the transmission back with a prosign now and then
+    if rng.gen::<f32>() < 0.4 {
+        phrases.push(pick(&mut rng, PROSIGNS).to_string());
+    }
+    phrases.join(" ")
+}
+
+fn pick<R: Rng>(rng: &mut R, items: &[&'static str]) -> &'static str {
+    items.choose(rng).copied().unwrap_or_default()
+}
+
+fn name_phrase<R: Rng>(rng: &mut R) -> String {
+    let name = pick(rng, NAMES);
+    if rng.gen::<bool>() {
+        format!("NAME IS {} {}", name, name)
+    } else {
+        format!("OP HR IS {}", name)
+    }
+}
+
+fn qth_phrase<R: Rng>(rng: &mut R) -> String {
+    format!("QTH {}", pick(rng, QTHS))
+}
+
+fn rig_phrase<R: Rng>(rng: &mut R) -> String {
+    let rig = pick(rng, RIGS);
+    let antenna = pick(rng, ANTENNAS);
+    let power = [5, 10, 50, 100, 500].choose(rng).copied().unwrap_or(100);
+    format!("RIG {} {}W ES ANT {}", rig, power, antenna)
+}
+
+fn weather_phrase<R: Rng>(rng: &mut R) -> String {
+    let temp = rng.gen_range(20..=95);
+    format!("WX {} {}F", pick(rng, WEATHER), temp)
+}
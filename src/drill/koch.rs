@@ -0,0 +1,236 @@
+// Koch method character course: each lesson adds one character, always
+// sent at full character speed with Farnsworth spacing between characters,
+// and the next lesson opens once a lesson is copied at 90%
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::AppSettings;
+
+use super::{match_in_order, CharResult};
+
+/// Order characters are introduced (the order LCWO uses)
+pub const KOCH_ORDER: &str = "KMURESNAPTLWI.JZ=FOY,VG5/Q92H38B?47C1D60X";
+/// Slowest character speed; slower and the sound of each character changes
+pub const MIN_CHAR_WPM: u8 = 20;
+/// Copy accuracy that masters a lesson
+pub const PASS_PCT: f32 = 90.0;
+/// Characters per group, and groups per drill item
+const GROUP_LEN: usize = 5;
+const GROUPS: usize = 3;
+/// Share of characters that are the lesson's new one
+const NEW_CHAR_SHARE: f64 = 0.3;
+
+/// Lessons are numbered from 1; lesson 1 has the first two characters
+pub fn lesson_count() -> u8 {
+    (KOCH_ORDER.len() - 1) as u8
+}
+
+/// The characters in a lesson
+pub fn lesson_chars(lesson: u8) -> &'static str {
+    let lesson = lesson.clamp(1, lesson_count()) as usize;
+    &KOCH_ORDER[..lesson + 1]
+}
+
+/// The character a lesson adds
+pub fn new_char(lesson: u8) -> char {
+    lesson_chars(lesson).chars().last().unwrap_or('K')
+}
+
+/// Random five-character groups from a lesson's characters, favouring the
+/// newest one
+pub fn generate_groups(lesson: u8) -> String {
+    let mut rng = rand::thread_rng();
+    let chars: Vec<char> = lesson_chars(lesson).chars().collect();
+    let newest = new_char(lesson);
+    (0..GROUPS)
+        .map(|_| {
+            (0..GROUP_LEN)
+                .map(|_| {
+                    if rng.gen_bool(NEW_CHAR_SHARE) {
+                        newest
+                    } else {
+                        chars[rng.gen_range(0..chars.len())]
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Score each sent character. Spaces don't count, and a missed or extra
+/// character doesn't throw off the rest.
+pub fn score_copy(expected: &str, copied: &str) -> Vec<CharResult> {
+    let expected: Vec<char> = expected.chars().filter(|c| !c.is_whitespace()).collect();
+    let copied: Vec<char> = copied
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let matched = match_in_order(&expected, &copied);
+
+    expected
+        .iter()
+        .zip(matched)
+        .map(|(&expected, correct)| CharResult {
+            expected,
+            was_cut: false,
+            correct,
+        })
+        .collect()
+}
+
+/// How a lesson has gone over every session
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LessonRecord {
+    pub attempts: u32,
+    pub chars_total: u32,
+    pub chars_correct: u32,
+    pub best_pct: f32,
+}
+
+impl LessonRecord {
+    pub fn accuracy_pct(&self) -> f32 {
+        if self.chars_total == 0 {
+            return 0.0;
+        }
+        self.chars_correct as f32 / self.chars_total as f32 * 100.0
+    }
+
+    pub fn mastered(&self) -> bool {
+        self.best_pct >= PASS_PCT
+    }
+}
+
+/// The course so far, kept between runs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KochProgress {
+    /// Lesson being practiced
+    pub lesson: u8,
+    pub lessons: BTreeMap<u8, LessonRecord>,
+}
+
+impl Default for KochProgress {
+    fn default() -> Self {
+        Self {
+            lesson: 1,
+            lessons: BTreeMap::new(),
+        }
+    }
+}
+
+impl KochProgress {
+    /// Highest lesson that can be practiced: one past the last mastered
+    pub fn unlocked(&self) -> u8 {
+        let mut unlocked = 1;
+        while unlocked < lesson_count()
+            && self
+                .lessons
+                .get(&unlocked)
+                .is_some_and(LessonRecord::mastered)
+        {
+            unlocked += 1;
+        }
+        unlocked
+    }
+
+    /// Add a scored item to the current lesson. Mastering it moves on to
+    /// the next lesson; returns true when that happens.
+    pub fn record(&mut self, results: &[CharResult]) -> bool {
+        let total = results.len() as u32;
+        if total == 0 {
+            return false;
+        }
+        let correct = results.iter().filter(|r| r.correct).count() as u32;
+        let pct = correct as f32 / total as f32 * 100.0;
+        let record = self.lessons.entry(self.lesson).or_default();
+        let was_mastered = record.mastered();
+        record.attempts += 1;
+        record.chars_total += total;
+        record.chars_correct += correct;
+        record.best_pct = record.best_pct.max(pct);
+        if !was_mastered && record.mastered() && self.lesson < lesson_count() {
+            self.lesson += 1;
+            return true;
+        }
+        false
+    }
+}
+
+/// The progress file sits next to the settings file
+fn progress_path() -> PathBuf {
+    AppSettings::config_path().with_file_name("koch-progress.json")
+}
+
+/// Saved progress, or a fresh start
+pub fn load() -> KochProgress {
+    std::fs::read_to_string(progress_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(progress: &KochProgress) -> Result<(), String> {
+    let path = progress_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(progress).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lessons_add_one_character_each() {
+        assert_eq!(lesson_chars(1), "KM");
+        assert_eq!(new_char(3), 'R');
+        assert_eq!(lesson_chars(lesson_count()), KOCH_ORDER);
+        let groups = generate_groups(2);
+        assert_eq!(groups.split(' ').count(), GROUPS);
+        assert!(groups.chars().all(|c| c == ' ' || "KMU".contains(c)));
+    }
+
+    #[test]
+    fn copy_is_scored_per_character() {
+        let results = score_copy("KMKMM UKK", "kmkm ukk");
+        assert_eq!(results.len(), 8);
+        assert_eq!(results.iter().filter(|r| r.correct).count(), 7);
+        // An extra character doesn't cost the ones after it
+        let results = score_copy("KMU", "KXMU");
+        assert!(results.iter().all(|r| r.correct));
+    }
+
+    #[test]
+    fn mastering_a_lesson_unlocks_the_next() {
+        let mut progress = KochProgress::default();
+        assert_eq!(progress.unlocked(), 1);
+        let scored = |correct: usize| {
+            (0..10)
+                .map(|i| CharResult {
+                    expected: 'K',
+                    was_cut: false,
+                    correct: i < correct,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert!(!progress.record(&scored(8)));
+        assert_eq!(progress.lesson, 1);
+        assert!(progress.record(&scored(9)));
+        assert_eq!(progress.lesson, 2);
+        assert_eq!(progress.unlocked(), 2);
+        let record = &progress.lessons[&1];
+        assert_eq!((record.attempts, record.chars_correct), (2, 17));
+
+        // Going back to a mastered lesson doesn't move on again
+        progress.lesson = 1;
+        assert!(!progress.record(&scored(10)));
+        assert_eq!(progress.lesson, 1);
+    }
+}
@@ -0,0 +1,580 @@
+// Copy drills that run outside the contest state machine
+
+pub mod abbreviations;
+pub mod audio_export;
+pub mod call_twice;
+pub mod conversational;
+pub mod koch;
+pub mod recordings;
+pub mod same_call;
+pub mod sandbox;
+pub mod serial;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::DrillSettings;
+use crate::messages::StationId;
+use call_twice::{CallTwiceRound, CallTwiceStage, CallTwiceStats, Verification};
+use koch::KochProgress;
+use recordings::RecordingSet;
+
+/// Station id used for drill audio so it never collides with simulated callers
+pub const DRILL_STATION_ID: StationId = StationId(u32::MAX);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DrillKind {
+    /// Plain-text ragchew phrases scored by word accuracy
+    Conversational,
+    /// Serial numbers with cut numbers, scored per digit
+    SerialNumbers,
+    /// WAV recordings with an answer key, scored by word in any order
+    Recordings,
+    /// A call sent twice, the same or one sound-alike character apart
+    SameCall,
+    /// A call sent and repeated: say whether they matched, then copy it
+    CallTwice,
+    /// Koch method character course, scored per character
+    Koch,
+    /// Contest and QSO abbreviations, typed or picked from a choice
+    Abbreviations,
+}
+
+impl DrillKind {
+    pub const ALL: &'static [DrillKind] = &[
+        DrillKind::Conversational,
+        DrillKind::SerialNumbers,
+        DrillKind::Recordings,
+        DrillKind::SameCall,
+        DrillKind::CallTwice,
+        DrillKind::Koch,
+        DrillKind::Abbreviations,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DrillKind::Conversational => "Conversational",
+            DrillKind::SerialNumbers => "Serial Numbers",
+            DrillKind::Recordings => "Recordings",
+            DrillKind::SameCall => "Same Call?",
+            DrillKind::CallTwice => "Call Twice",
+            DrillKind::Koch => "Koch Course",
+            DrillKind::Abbreviations => "Abbreviations",
+        }
+    }
+
+    /// What a scoring unit is called in results
+    pub fn unit_label(&self) -> &'static str {
+        match self {
+            DrillKind::Conversational | DrillKind::Recordings | DrillKind::Abbreviations => "Words",
+            DrillKind::SerialNumbers => "Digits",
+            DrillKind::SameCall => "Answers",
+            DrillKind::CallTwice => "Steps",
+            DrillKind::Koch => "Characters",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            DrillKind::Conversational => {
+                "Short ragchew phrases (names, QTHs, rigs, weather) for head-copy practice"
+            }
+            DrillKind::SerialNumbers => {
+                "Serial numbers with cut numbers (T=0, N=9, A=1); type the digits you copy"
+            }
+            DrillKind::Recordings => {
+                "Your own WAV recordings, such as real pileups, with an answer key; copy the calls in any order"
+            }
+            DrillKind::SameCall => {
+                "A call sent twice, often one dit or dah off the second time; type S if it was the same call, D if not"
+            }
+            DrillKind::CallTwice => {
+                "A station sends its call and repeats it: type S or D for same or different, then the call (the repeat is right)"
+            }
+            DrillKind::Koch => {
+                "Learn the characters one at a time: groups of the lesson's characters at full speed with extra space between them; 90% copy opens the next lesson"
+            }
+            DrillKind::Abbreviations => {
+                "Abbreviations contests and QSOs are built from (TU, 73, HW?, QRZ, AGN, B4): copy a run of them, or pick the one sent from four"
+            }
+        }
+    }
+}
+
+/// Per-character scoring detail (used by drills scored below word level)
+#[derive(Clone, Copy, Debug)]
+pub struct CharResult {
+    pub expected: char,
+    /// Whether the character was sent as a cut number
+    pub was_cut: bool,
+    pub correct: bool,
+}
+
+/// Text sent for the current drill item
+#[derive(Clone, Debug)]
+pub struct DrillPrompt {
+    pub text: String,
+    pub answer: String,
+    /// Sending speed; 0 for recordings, whose speed isn't known
+    pub wpm: u8,
+    /// Overall speed when slower than `wpm`, from Farnsworth spacing
+    pub effective_wpm: Option<u8>,
+    /// WAV file played instead of sending `text`
+    pub recording: Option<PathBuf>,
+    /// Answers to pick from, numbered from 1; empty when the copy is typed
+    pub choices: Vec<String>,
+}
+
+/// One scored drill item
+#[derive(Clone, Debug)]
+pub struct DrillAttempt {
+    pub kind: DrillKind,
+    pub sent: String,
+    pub expected: String,
+    pub copied: String,
+    pub wpm: u8,
+    pub units_total: usize,
+    pub units_correct: usize,
+    pub char_results: Vec<CharResult>,
+    /// Each expected word and whether it was copied, for drills that keep
+    /// per-word stats
+    pub word_results: Vec<(String, bool)>,
+    /// How a Call Twice round went
+    pub verification: Option<Verification>,
+}
+
+impl DrillAttempt {
+    pub fn accuracy_pct(&self) -> f32 {
+        if self.units_total == 0 {
+            return 0.0;
+        }
+        self.units_correct as f32 / self.units_total as f32 * 100.0
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        self.units_total > 0 && self.units_correct == self.units_total
+    }
+}
+
+/// Aggregate results for one drill kind
+#[derive(Clone, Debug, Default)]
+pub struct DrillSummary {
+    pub attempts: usize,
+    pub perfect: usize,
+    pub units_total: usize,
+    pub units_correct: usize,
+    pub cut_total: usize,
+    pub cut_correct: usize,
+}
+
+impl DrillSummary {
+    pub fn accuracy_pct(&self) -> f32 {
+        if self.units_total == 0 {
+            return 0.0;
+        }
+        self.units_correct as f32 / self.units_total as f32 * 100.0
+    }
+}
+
+/// Drill state shared between the app and the drill window
+pub struct DrillSession {
+    pub kind: DrillKind,
+    pub current: Option<DrillPrompt>,
+    pub copy_input: String,
+    pub attempts: Vec<DrillAttempt>,
+    /// Result of the last audio drill export
+    pub export_status: Option<String>,
+    /// Recordings loaded for the Recordings drill
+    pub recordings: Option<RecordingSet>,
+    /// Result of the last recordings load or playback problem
+    pub recordings_status: Option<String>,
+    /// The Call Twice round being played
+    pub call_twice: Option<CallTwiceRound>,
+    /// Koch course lessons and how they've gone, kept between runs
+    pub koch: KochProgress,
+}
+
+impl DrillSession {
+    pub fn new() -> Self {
+        Self {
+            kind: DrillKind::Conversational,
+            current: None,
+            copy_input: String::new(),
+            attempts: Vec::new(),
+            export_status: None,
+            recordings: None,
+            recordings_status: None,
+            call_twice: None,
+            koch: KochProgress::default(),
+        }
+    }
+
+    /// Generate a new drill item at a random speed within the configured
+    /// range, or take the next recording. None if no recordings are loaded.
+    pub fn next_prompt(&mut self, settings: &DrillSettings) -> Option<DrillPrompt> {
+        let mut rng = rand::thread_rng();
+        let wpm_min = settings.wpm_min.min(settings.wpm_max);
+        let wpm_max = settings.wpm_min.max(settings.wpm_max);
+        self.call_twice = None;
+        let mut choices = Vec::new();
+        let (text, answer) = match self.kind {
+            DrillKind::Conversational => {
+                let text = conversational::generate_phrase();
+                (text.clone(), text)
+            }
+            DrillKind::SerialNumbers => serial::generate_serials(settings.cut_number_probability),
+            DrillKind::SameCall => same_call::generate_pair(),
+            DrillKind::CallTwice => {
+                let (text, round) = call_twice::generate_round();
+                let answer = round.call.clone();
+                self.call_twice = Some(round);
+                (text, answer)
+            }
+            DrillKind::Abbreviations if settings.abbreviation_choices => {
+                let (sent, options) = abbreviations::generate_choice();
+                choices = options;
+                (sent.clone(), sent)
+            }
+            DrillKind::Abbreviations => {
+                let text = abbreviations::generate_sequence();
+                (text.clone(), text)
+            }
+            DrillKind::Recordings => {
+                let clip = self.recordings.as_mut()?.next_clip()?.clone();
+                let prompt = DrillPrompt {
+                    text: clip.name,
+                    answer: clip.answer,
+                    wpm: 0,
+                    effective_wpm: None,
+                    recording: Some(clip.path),
+                    choices: Vec::new(),
+                };
+                self.current = Some(prompt.clone());
+                self.copy_input.clear();
+                return Some(prompt);
+            }
+            DrillKind::Koch => {
+                let text = koch::generate_groups(self.koch.lesson);
+                let wpm = settings.koch.char_wpm.max(koch::MIN_CHAR_WPM);
+                let prompt = DrillPrompt {
+                    text: text.clone(),
+                    answer: text,
+                    wpm,
+                    effective_wpm: Some(settings.koch.effective_wpm.min(wpm)),
+                    recording: None,
+                    choices: Vec::new(),
+                };
+                self.current = Some(prompt.clone());
+                self.copy_input.clear();
+                return Some(prompt);
+            }
+        };
+        let prompt = DrillPrompt {
+            text,
+            answer,
+            wpm: rng.gen_range(wpm_min..=wpm_max),
+            effective_wpm: None,
+            recording: None,
+            choices,
+        };
+        self.current = Some(prompt.clone());
+        self.copy_input.clear();
+        Some(prompt)
+    }
+
+    /// Score the copied text against the current prompt. A Call Twice
+    /// round takes two submits: same or different first, then the call.
+    pub fn submit(&mut self) -> Option<&DrillAttempt> {
+        self.current.as_ref()?;
+        let copied = self.copy_input.trim().to_uppercase();
+        if let Some(round) = self.call_twice.as_mut() {
+            if round.stage == CallTwiceStage::Compare {
+                if let Some(said_identical) = same_call::parse_same(&copied) {
+                    round.compare(said_identical);
+                }
+                self.copy_input.clear();
+                return None;
+            }
+        }
+        let prompt = self.current.take()?;
+        let mut verification = None;
+        let mut word_results = Vec::new();
+        let (units_total, units_correct, char_results) = match self.kind {
+            DrillKind::Conversational => {
+                let (total, correct) = score_words(&prompt.answer, &copied);
+                (total, correct, Vec::new())
+            }
+            DrillKind::SerialNumbers => {
+                let results = serial::score_serials(&prompt.text, &prompt.answer, &copied);
+                let correct = results.iter().filter(|r| r.correct).count();
+                (results.len(), correct, results)
+            }
+            DrillKind::Recordings => {
+                let (total, correct) = score_words_any_order(&prompt.answer, &copied);
+                (total, correct, Vec::new())
+            }
+            DrillKind::SameCall => {
+                let correct = same_call::answer_correct(&prompt.answer, &copied);
+                (1, usize::from(correct), Vec::new())
+            }
+            DrillKind::CallTwice => {
+                verification = self
+                    .call_twice
+                    .take()
+                    .and_then(|round| round.finish(&copied));
+                let correct = verification.map_or(0, |v| {
+                    usize::from(v.compare_correct()) + usize::from(v.call_correct)
+                });
+                (2, correct, Vec::new())
+            }
+            DrillKind::Koch => {
+                let results = koch::score_copy(&prompt.answer, &copied);
+                self.koch.record(&results);
+                let correct = results.iter().filter(|r| r.correct).count();
+                (results.len(), correct, results)
+            }
+            DrillKind::Abbreviations => {
+                word_results = if prompt.choices.is_empty() {
+                    score_each_word(&prompt.answer, &copied)
+                } else {
+                    let answer = abbreviations::chosen(&prompt.choices, &copied);
+                    vec![(prompt.answer.clone(), answer == prompt.answer)]
+                };
+                let correct = word_results.iter().filter(|(_, correct)| *correct).count();
+                (word_results.len(), correct, Vec::new())
+            }
+        };
+        self.attempts.push(DrillAttempt {
+            kind: self.kind,
+            sent: prompt.text,
+            expected: prompt.answer,
+            copied,
+            wpm: prompt.wpm,
+            units_total,
+            units_correct,
+            char_results,
+            word_results,
+            verification,
+        });
+        self.copy_input.clear();
+        self.attempts.last()
+    }
+
+    pub fn summary(&self, kind: DrillKind) -> DrillSummary {
+        let mut summary = DrillSummary::default();
+        for attempt in self.attempts.iter().filter(|a| a.kind == kind) {
+            summary.attempts += 1;
+            if attempt.is_perfect() {
+                summary.perfect += 1;
+            }
+            summary.units_total += attempt.units_total;
+            summary.units_correct += attempt.units_correct;
+            for result in attempt.char_results.iter().filter(|r| r.was_cut) {
+                summary.cut_total += 1;
+                if result.correct {
+                    summary.cut_correct += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    /// Error rate per expected character: (char, error_rate_pct, samples),
+    /// sorted by character
+    pub fn char_error_rates(&self, kind: DrillKind) -> Vec<(char, f32, usize)> {
+        let mut counts: std::collections::BTreeMap<char, (usize, usize)> =
+            std::collections::BTreeMap::new();
+        for attempt in self.attempts.iter().filter(|a| a.kind == kind) {
+            for result in &attempt.char_results {
+                let entry = counts.entry(result.expected).or_insert((0, 0));
+                entry.0 += 1;
+                if !result.correct {
+                    entry.1 += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(ch, (total, errors))| (ch, errors as f32 / total as f32 * 100.0, total))
+            .collect()
+    }
+
+    /// Error rate per expected word: (word, error_rate_pct, samples),
+    /// worst first
+    pub fn word_error_rates(&self, kind: DrillKind) -> Vec<(String, f32, usize)> {
+        let mut counts: std::collections::BTreeMap<&str, (usize, usize)> =
+            std::collections::BTreeMap::new();
+        for attempt in self.attempts.iter().filter(|a| a.kind == kind) {
+            for (word, correct) in &attempt.word_results {
+                let entry = counts.entry(word).or_insert((0, 0));
+                entry.0 += 1;
+                if !correct {
+                    entry.1 += 1;
+                }
+            }
+        }
+        let mut rates: Vec<(String, f32, usize)> = counts
+            .into_iter()
+            .map(|(word, (total, errors))| {
+                (
+                    word.to_string(),
+                    errors as f32 / total as f32 * 100.0,
+                    total,
+                )
+            })
+            .collect();
+        rates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        rates
+    }
+
+    /// Totals over the finished Call Twice rounds
+    pub fn call_twice_stats(&self) -> CallTwiceStats {
+        CallTwiceStats::from_rounds(self.attempts.iter().filter_map(|a| a.verification.as_ref()))
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.call_twice = None;
+        self.copy_input.clear();
+        self.attempts.clear();
+    }
+}
+
+/// Count expected words and how many of them were copied in order.
+/// Uses the longest common subsequence so one missed word doesn't
+/// mark every following word wrong.
+pub fn score_words(expected: &str, copied: &str) -> (usize, usize) {
+    let results = score_each_word(expected, copied);
+    let correct = results.iter().filter(|(_, correct)| *correct).count();
+    (results.len(), correct)
+}
+
+/// Each expected word and whether it was copied, matched in order as in
+/// [`score_words`]
+pub fn score_each_word(expected: &str, copied: &str) -> Vec<(String, bool)> {
+    // Prosigns may be copied with or without angle brackets
+    let normalize = |w: &str| w.trim_matches(|c| c == '<' || c == '>').to_uppercase();
+    let expected: Vec<String> = expected.split_whitespace().map(normalize).collect();
+    let copied: Vec<String> = copied.split_whitespace().map(normalize).collect();
+    let matched = match_in_order(&expected, &copied);
+    expected.into_iter().zip(matched).collect()
+}
+
+/// Which expected items were copied, by longest common subsequence, so a
+/// missed or extra item doesn't throw off the ones after it
+pub fn match_in_order<T: PartialEq>(expected: &[T], copied: &[T]) -> Vec<bool> {
+    let mut lcs = vec![vec![0usize; copied.len() + 1]; expected.len() + 1];
+    for i in 1..=expected.len() {
+        for j in 1..=copied.len() {
+            lcs[i][j] = if expected[i - 1] == copied[j - 1] {
+                lcs[i - 1][j - 1] + 1
+            } else {
+                lcs[i - 1][j].max(lcs[i][j - 1])
+            };
+        }
+    }
+
+    // Walk back to find which of the expected items matched
+    let mut matched = vec![false; expected.len()];
+    let (mut i, mut j) = (expected.len(), copied.len());
+    while i > 0 && j > 0 {
+        if expected[i - 1] == copied[j - 1] {
+            matched[i - 1] = true;
+            i -= 1;
+            j -= 1;
+        } else if lcs[i - 1][j] >= lcs[i][j - 1] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    matched
+}
+
+/// Count expected words and how many of them were copied, in any order.
+/// The calls in a pileup can be copied in whatever order they're picked out.
+pub fn score_words_any_order(expected: &str, copied: &str) -> (usize, usize) {
+    let normalize = |w: &str| w.trim_matches(|c| c == '<' || c == '>').to_uppercase();
+    let expected: Vec<String> = expected.split_whitespace().map(normalize).collect();
+    let mut unmatched: Vec<String> = copied.split_whitespace().map(normalize).collect();
+    let correct = expected
+        .iter()
+        .filter(|word| match unmatched.iter().position(|c| c == *word) {
+            Some(index) => {
+                unmatched.swap_remove(index);
+                true
+            }
+            None => false,
+        })
+        .count();
+    (expected.len(), correct)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_copy_scores_all_words() {
+        assert_eq!(score_words("NAME IS BOB BOB", "name is bob bob"), (4, 4));
+    }
+
+    #[test]
+    fn missed_word_does_not_shift_alignment() {
+        assert_eq!(score_words("QTH DENVER CO", "QTH CO"), (3, 2));
+        assert_eq!(score_words("RIG K3 100W", "RIG KE 100W"), (3, 2));
+    }
+
+    #[test]
+    fn extra_words_are_not_credited() {
+        assert_eq!(score_words("WX SUNNY", "WX IS SUNNY TODAY"), (2, 2));
+        assert_eq!(score_words("WX SUNNY", ""), (2, 0));
+    }
+
+    #[test]
+    fn prosigns_match_without_brackets() {
+        assert_eq!(score_words("QTH RENO NV <KN>", "QTH RENO NV KN"), (4, 4));
+    }
+
+    #[test]
+    fn each_word_is_marked_and_tallied() {
+        let results = score_each_word("TU 73 HW? QRZ", "tu hw qrz");
+        let marks: Vec<bool> = results.iter().map(|(_, correct)| *correct).collect();
+        assert_eq!(marks, [true, false, false, true]);
+
+        let mut session = DrillSession::new();
+        for (word, correct) in [("TU", true), ("B4", false), ("B4", true), ("TU", true)] {
+            session.attempts.push(DrillAttempt {
+                kind: DrillKind::Abbreviations,
+                sent: word.to_string(),
+                expected: word.to_string(),
+                copied: String::new(),
+                wpm: 20,
+                units_total: 1,
+                units_correct: usize::from(correct),
+                char_results: Vec::new(),
+                word_results: vec![(word.to_string(), correct)],
+                verification: None,
+            });
+        }
+        let rates = session.word_error_rates(DrillKind::Abbreviations);
+        assert_eq!(
+            rates,
+            [("B4".to_string(), 50.0, 2), ("TU".to_string(), 0.0, 2)]
+        );
+        assert!(session
+            .word_error_rates(DrillKind::Conversational)
+            .is_empty());
+    }
+
+    #[test]
+    fn recordings_score_words_in_any_order() {
+        assert_eq!(score_words_any_order("K1ABC W2XYZ", "w2xyz k1abc"), (2, 2));
+        assert_eq!(score_words_any_order("K1ABC K1ABC", "K1ABC"), (2, 1));
+        assert_eq!(
+            score_words_any_order("K1ABC W2XYZ", "K1ABD W2XYZ W2XYZ"),
+            (2, 1)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Practice against recordings: a folder of WAV files with an answer key,
+// laid out like an exported audio drill, so real pileups can be scored too
+
+use std::path::{Path, PathBuf};
+
+use crate::audio::wav::{read_wav, resample};
+
+/// Answer key file expected in a recordings folder
+pub const ANSWER_KEY_FILE: &str = "answer-key.txt";
+
+/// One recording and what should be copied from it
+#[derive(Clone, Debug)]
+pub struct RecordingClip {
+    pub name: String,
+    pub path: PathBuf,
+    pub answer: String,
+}
+
+/// The recordings in a folder, played in answer key order
+pub struct RecordingSet {
+    pub folder: PathBuf,
+    pub clips: Vec<RecordingClip>,
+    next: usize,
+}
+
+impl RecordingSet {
+    /// Read the folder's answer key. Key entries whose WAV file is missing
+    /// are skipped.
+    pub fn load(folder: &Path) -> Result<Self, String> {
+        let key_path = folder.join(ANSWER_KEY_FILE);
+        let content = std::fs::read_to_string(&key_path)
+            .map_err(|e| format!("Failed to read {}: {}", key_path.display(), e))?;
+        let clips: Vec<RecordingClip> = parse_answer_key(&content)
+            .into_iter()
+            .map(|(name, answer)| RecordingClip {
+                path: folder.join(&name),
+                name,
+                answer,
+            })
+            .filter(|clip| clip.path.is_file())
+            .collect();
+        if clips.is_empty() {
+            return Err(format!(
+                "No recordings listed in {} were found",
+                key_path.display()
+            ));
+        }
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            clips,
+            next: 0,
+        })
+    }
+
+    /// The next recording, starting over after the last one
+    pub fn next_clip(&mut self) -> Option<&RecordingClip> {
+        if self.clips.is_empty() {
+            return None;
+        }
+        let index = self.next % self.clips.len();
+        self.next = index + 1;
+        self.clips.get(index)
+    }
+}
+
+/// Answer key lines are a WAV file name followed by the expected copy,
+/// e.g. `01.wav  K1ABC W2XYZ`. Anything else (headers, notes) is ignored.
+pub fn parse_answer_key(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (name, answer) = line.split_once(char::is_whitespace)?;
+            let answer = answer.trim();
+            (name.to_lowercase().ends_with(".wav") && !answer.is_empty())
+                .then(|| (name.to_string(), answer.to_uppercase()))
+        })
+        .collect()
+}
+
+/// Load a recording as left/right audio at `sample_rate`
+pub fn load_audio(path: &Path, sample_rate: u32) -> Result<(Vec<f32>, Vec<f32>), String> {
+    let audio = read_wav(path)?;
+    Ok((
+        resample(&audio.left, audio.sample_rate, sample_rate),
+        resample(&audio.right, audio.sample_rate, sample_rate),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_key_skips_headers_and_blank_lines() {
+        let key = "Pileup drill, 2 recordings, 25-35 WPM\n\
+                   Callsigns are listed from lowest to highest pitch.\n\
+                   \n\
+                   01.wav  K1ABC W2XYZ\n\
+                   02.WAV\tdl1abc\n\
+                   03.wav\n";
+        assert_eq!(
+            parse_answer_key(key),
+            vec![
+                ("01.wav".to_string(), "K1ABC W2XYZ".to_string()),
+                ("02.WAV".to_string(), "DL1ABC".to_string()),
+            ]
+        );
+    }
+}
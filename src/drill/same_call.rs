@@ -0,0 +1,74 @@
+// Same or different: a call sent twice, the second time often one dit or
+// dah off, to train hearing the difference a busted call makes
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::station::confusion::confused_callsign;
+
+pub const SAME: &str = "SAME";
+pub const DIFFERENT: &str = "DIFFERENT";
+
+const PREFIXES: &[&str] = &[
+    "K", "W", "N", "AA", "KB", "VE", "DL", "G", "F", "I", "EA", "OH", "SM", "PA", "JA", "UA", "VK",
+    "LU", "PY", "ZS", "HA", "OK", "SP", "YU",
+];
+
+/// A made-up call: a common prefix, a digit and a one to three letter suffix
+pub fn random_call<R: Rng>(rng: &mut R) -> String {
+    let prefix = PREFIXES.choose(rng).unwrap();
+    let digit = rng.gen_range(0..=9);
+    let suffix: String = (0..rng.gen_range(1..=3))
+        .map(|_| rng.gen_range(b'A'..=b'Z') as char)
+        .collect();
+    format!("{}{}{}", prefix, digit, suffix)
+}
+
+/// Generate a pair of calls, the same or one sound-alike character apart.
+/// Returns (sent text, answer).
+pub fn generate_pair() -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let first = random_call(&mut rng);
+    if rng.gen_bool(0.5) {
+        (format!("{} {}", first, first), SAME.to_string())
+    } else {
+        let second = confused_callsign(&first, &mut rng);
+        (format!("{} {}", first, second), DIFFERENT.to_string())
+    }
+}
+
+/// Read a same/different answer: S or Y for the same call, D or N for
+/// different ones (whole words work too)
+pub fn parse_same(copied: &str) -> Option<bool> {
+    match copied.trim().to_uppercase().chars().next() {
+        Some('S' | 'Y') => Some(true),
+        Some('D' | 'N') => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the copied answer is right
+pub fn answer_correct(answer: &str, copied: &str) -> bool {
+    parse_same(copied) == Some(answer == SAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_match_their_answer() {
+        for _ in 0..50 {
+            let (sent, answer) = generate_pair();
+            let calls: Vec<&str> = sent.split_whitespace().collect();
+            assert_eq!(calls.len(), 2);
+            assert_eq!(calls[0] == calls[1], answer == SAME);
+        }
+        assert!(answer_correct(SAME, "s"));
+        assert!(answer_correct(SAME, "yes"));
+        assert!(answer_correct(DIFFERENT, "D"));
+        assert!(answer_correct(DIFFERENT, "no"));
+        assert!(!answer_correct(SAME, "different"));
+        assert!(!answer_correct(DIFFERENT, ""));
+    }
+}
@@ -0,0 +1,99 @@
+// Morse sandbox: play arbitrary text to hear how it sounds
+
+use std::collections::VecDeque;
+
+use crate::messages::StationId;
+
+/// Station id for sandbox audio, kept clear of callers and drill items
+pub const SANDBOX_STATION_ID: StationId = StationId(u32::MAX - 1);
+
+/// One piece of text waiting to be played
+#[derive(Clone, Debug, PartialEq)]
+pub struct SandboxItem {
+    pub text: String,
+    pub wpm: u8,
+    pub pitch_hz: f32,
+}
+
+/// Sandbox state shared between the app and the sandbox window
+pub struct MorseSandbox {
+    pub text: String,
+    pub wpm: u8,
+    pub pitch_hz: f32,
+    pub queue: VecDeque<SandboxItem>,
+    pub playing: Option<SandboxItem>,
+}
+
+impl MorseSandbox {
+    pub fn new(wpm: u8, pitch_hz: f32) -> Self {
+        Self {
+            text: String::new(),
+            wpm,
+            pitch_hz,
+            queue: VecDeque::new(),
+            playing: None,
+        }
+    }
+
+    /// Queue the typed text at the current speed and pitch
+    pub fn enqueue_text(&mut self) {
+        let text = self.text.trim().to_uppercase();
+        if text.is_empty() {
+            return;
+        }
+        self.queue.push_back(SandboxItem {
+            text,
+            wpm: self.wpm,
+            pitch_hz: self.pitch_hz,
+        });
+        self.text.clear();
+    }
+
+    /// Start the next queued item if nothing is playing
+    pub fn start_next(&mut self) -> Option<SandboxItem> {
+        if self.playing.is_some() {
+            return None;
+        }
+        self.playing = self.queue.pop_front();
+        self.playing.clone()
+    }
+
+    /// The item that was playing has finished
+    pub fn finish_current(&mut self) {
+        self.playing = None;
+    }
+
+    pub fn stop(&mut self) {
+        self.queue.clear();
+        self.playing = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_plays_in_order_one_at_a_time() {
+        let mut sandbox = MorseSandbox::new(25, 600.0);
+        sandbox.text = "w1aw".to_string();
+        sandbox.enqueue_text();
+        sandbox.wpm = 35;
+        sandbox.text = "  ".to_string();
+        sandbox.enqueue_text();
+        sandbox.text = "ve3ksf".to_string();
+        sandbox.enqueue_text();
+        assert_eq!(sandbox.queue.len(), 2);
+
+        let first = sandbox.start_next().unwrap();
+        assert_eq!((first.text.as_str(), first.wpm), ("W1AW", 25));
+        assert!(sandbox.start_next().is_none());
+
+        sandbox.finish_current();
+        let second = sandbox.start_next().unwrap();
+        assert_eq!((second.text.as_str(), second.wpm), ("VE3KSF", 35));
+
+        sandbox.stop();
+        assert!(sandbox.playing.is_none() && sandbox.queue.is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+// Serial number copy practice with contest-style cut numbers
+
+use rand::Rng;
+
+use super::CharResult;
+
+/// Map a digit to its common cut-number letter, if it has one
+fn cut_for(digit: char) -> Option<char> {
+    match digit {
+        '0' => Some('T'),
+        '9' => Some('N'),
+        '1' => Some('A'),
+        _ => None,
+    }
+}
+
+/// Expand cut numbers the way a logger would (T=0, N=9, A=1, O=0)
+pub fn expand_cut_numbers(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'T' | 'O' => '0',
+            'N' => '9',
+            'A' => '1',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Generate a few serial numbers.
+/// Returns (sent text with cut numbers, answer with plain digits).
+pub fn generate_serials(cut_probability: f32) -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let count = rng.gen_range(3..=5);
+    let mut sent = Vec::with_capacity(count);
+    let mut answer = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let value: u32 = if rng.gen::<f32>() < 0.3 {
+            rng.gen_range(1..100)
+        } else {
+            rng.gen_range(100..2000)
+        };
+        // Early-contest serials are often sent zero-padded (e.g. 007 as TT7)
+        let digits = if value < 100 && rng.gen::<bool>() {
+            format!("{:03}", value)
+        } else {
+            value.to_string()
+        };
+        let cut: String = digits
+            .chars()
+            .map(|d| match cut_for(d) {
+                Some(letter) if rng.gen::<f32>() < cut_probability => letter,
+                _ => d,
+            })
+            .collect();
+        sent.push(cut);
+        answer.push(digits);
+    }
+
+    (sent.join(" "), answer.join(" "))
+}
+
+/// Score copied serials digit by digit against the answer.
+/// `sent` is used to note which digits were sent as cut numbers.
+pub fn score_serials(sent: &str, answer: &str, copied: &str) -> Vec<CharResult> {
+    let copied = expand_cut_numbers(copied);
+    let copied_words: Vec<&str> = copied.split_whitespace().collect();
+    let mut results = Vec::new();
+
+    for (idx, (sent_word, answer_word)) in sent
+        .split_whitespace()
+        .zip(answer.split_whitespace())
+        .enumerate()
+    {
+        let copied_word: Vec<char> = copied_words
+            .get(idx)
+            .map(|w| w.chars().collect())
+            .unwrap_or_default();
+        for (pos, (sent_char, expected)) in sent_word.chars().zip(answer_word.chars()).enumerate() {
+            results.push(CharResult {
+                expected,
+                was_cut: sent_char != expected,
+                correct: copied_word.get(pos) == Some(&expected),
+            });
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_cut_numbers() {
+        assert_eq!(expand_cut_numbers("tt7"), "007");
+        assert_eq!(expand_cut_numbers("AN3"), "193");
+    }
+
+    #[test]
+    fn scores_each_digit_and_marks_cuts() {
+        let results = score_serials("TT7 1N4", "007 194", "TT7 184");
+        assert_eq!(results.len(), 6);
+        assert!(results[..3].iter().all(|r| r.correct));
+        assert!(results[0].was_cut && results[1].was_cut && !results[2].was_cut);
+        assert!(!results[4].correct && results[4].was_cut);
+        assert!(results[3].correct && results[5].correct);
+    }
+
+    #[test]
+    fn missing_serials_count_as_errors() {
+        let results = score_serials("12 34", "12 34", "12");
+        assert_eq!(results.iter().filter(|r| r.correct).count(), 2);
+        assert_eq!(results.len(), 4);
+    }
+}
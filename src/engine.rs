@@ -0,0 +1,4035 @@
+// The contest simulation without the window: callers, the QSO state
+// machine and its timers, logging and scoring. Everything that moves it
+// along, the passing of time included, comes in through `Engine::tick` as
+// an `Event` and audio goes out over the command channel, so the GUI, the
+// headless runner and tests all drive it the same way.
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::achievements::{self, Achievements};
+use crate::announce::Announcer;
+use crate::audio::meter::approx_snr_db;
+use crate::audio::mixer::passband_gain;
+use crate::audio::morse::message_duration_ms;
+use crate::config::{AppSettings, SimulationSettings, SyntheticCallSettings, MACRO_KEYS};
+use crate::contest::{
+    self, cut_number_exchange, CallsignSource, Contest, ContestDescriptor, Exchange, FieldKind,
+    SettingFieldKind, SyntheticCallsignSource,
+};
+use crate::cty::CtyDat;
+use crate::data_update::{self, DataUpdater};
+use crate::drill::audio_export::{export_audio_drill, generate_clips};
+use crate::drill::recordings::load_audio;
+use crate::drill::sandbox::{MorseSandbox, SANDBOX_STATION_ID};
+use crate::drill::{koch, DrillPrompt, DrillSession, DRILL_STATION_ID};
+use crate::file_watch::FileWatcher;
+use crate::i18n::{tr, tr_args};
+use crate::messages::{
+    AudioCommand, AudioEvent, MessageSegment, MessageSegmentType, StationParams,
+};
+use crate::milestones::MilestoneTracker;
+use crate::notifications::Notifications;
+use crate::personal_bests::{self, PersonalBests};
+use crate::recovery::{self, SavedSession};
+use crate::skimmer::Skimmer;
+use crate::state::{
+    ContestState, FillAnswer, FillRequest, QsoContext, Role, StationTxType, StatusColor,
+    Transition, UserTxType,
+};
+use crate::station::confusion::confused_callsign;
+use crate::station::matching::{best_match, callsign_similarity};
+use crate::station::{
+    calling_message, framed_exchange, give_up_message, make_double, matches_partial_call,
+    with_courtesy_word, AmbienceScheduler, CallerDensity, CallerManager, CallerResponse, CharFocus,
+    FightEvent, FrequencyFight,
+};
+use crate::stats::DensitySample;
+use crate::stats::{DoubleOutcome, NilRecord, QsoRecord, SessionStats};
+use crate::summary::{summarize, SessionSummary};
+use crate::tutorial::{Progress as TutorialProgress, Tutorial};
+
+/// How many sent messages the TX history keeps
+const TX_HISTORY_LEN: usize = 8;
+
+/// Audio commands the channel to the audio holds
+const AUDIO_QUEUE_LEN: usize = 64;
+/// Commands that can wait on our side for room in the channel; past this
+/// the audio has stopped taking them and the session is stopped
+const AUDIO_BACKLOG_LIMIT: usize = 256;
+
+/// How far either way the RIT tunes
+pub const RIT_MAX_HZ: f32 = 1500.0;
+
+/// Log checking takes a busted call or exchange out and charges this many
+/// more QSOs' points; a NIL (the other station logged us, we didn't log
+/// them) costs this many
+const BUST_PENALTY_QSOS: u32 = 1;
+const NIL_PENALTY_QSOS: u32 = 2;
+
+/// Enter this close to the end of the caller's exchange still counts as
+/// copying it, since the key often goes down on the last element
+const EARLY_LOG_ALLOWANCE: Duration = Duration::from_millis(150);
+
+/// Keys for the user macro slots, in the same order as `MACRO_KEYS`
+const MACRO_SLOT_KEYS: [Key; 6] = [Key::F4, Key::F6, Key::F7, Key::F9, Key::F10, Key::F11];
+
+/// Which input field is active
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputField {
+    Callsign,
+    Exchange(usize),
+}
+
+/// Where the text in an exchange box came from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrefillState {
+    /// Typed by the user (or still empty)
+    Typed,
+    /// Filled from call history and not looked at yet
+    Prefilled,
+    /// Filled from call history, and the user has moved into the box
+    Verified,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActiveCaller {
+    pub params: StationParams,
+}
+
+#[derive(Clone, Debug)]
+pub struct QsoResult {
+    pub callsign: String,
+    pub expected_call: String,
+    pub expected_exchange: String,
+    pub callsign_correct: bool,
+    pub exchange_correct: bool,
+    /// Labels of the exchange fields logged wrong
+    pub wrong_fields: Vec<String>,
+    /// Each exchange field's label and whether it was logged right
+    pub field_results: Vec<(String, bool)>,
+    /// Labels of the wrong fields logged with something that can't be right
+    /// at all (not a section, say), as opposed to a plausible miscopy
+    pub impossible_fields: Vec<String>,
+    /// The contest's explanations of what was sent and how it was copied
+    pub remarks: Vec<String>,
+    pub points: u32,
+    /// The worked caller's speed and offset from our pitch
+    pub caller_wpm: u8,
+    pub caller_offset_hz: f32,
+}
+
+impl QsoResult {
+    /// Spoken summary: what was logged and what was wrong
+    pub fn announcement(&self) -> String {
+        let mut text = tr_args("Logged {}", &[&self.callsign]);
+        if !self.callsign_correct {
+            text.push_str(&tr_args(", call wrong, was {}", &[&self.expected_call]));
+        }
+        if !self.exchange_correct {
+            text.push_str(&tr_args(
+                ", exchange wrong, was {}",
+                &[&self.expected_exchange],
+            ));
+        }
+        if self.callsign_correct && self.exchange_correct {
+            text.push_str(&tr_args(
+                ", correct, {} points",
+                &[&self.points.to_string()],
+            ));
+        }
+        text
+    }
+}
+
+/// A message we transmitted, kept so the operator can check what actually went out
+#[derive(Clone, Debug)]
+pub struct TxRecord {
+    pub sent_at: chrono::DateTime<chrono::Local>,
+    pub text: String,
+    pub wpm: u8,
+}
+
+/// The last logged QSO, kept so it can be reopened to fix a typo
+#[derive(Clone, Debug)]
+struct LoggedQso {
+    caller: StationParams,
+    entered_fields: Vec<String>,
+    /// We sent the caller their call right at least once
+    sent_call_right: bool,
+}
+
+/// How the QSO in progress began: when the callers started, how many were
+/// calling, and how long until we came back with a call
+#[derive(Clone, Copy, Debug)]
+struct QsoStart {
+    at: Instant,
+    calling: usize,
+    copied_after: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    pub qso_count: u32,
+    pub total_points: u32,
+    /// Points log checking would take off for busts and NILs
+    pub penalty_points: u32,
+    pub start_time: Option<Instant>,
+}
+
+impl Score {
+    pub fn hourly_rate(&self, now: Instant) -> u32 {
+        if let Some(start) = self.start_time {
+            let elapsed = now.saturating_duration_since(start).as_secs_f64() / 3600.0;
+            if elapsed > 0.01 {
+                return (self.qso_count as f64 / elapsed) as u32;
+            }
+        }
+        0
+    }
+
+    pub fn add_qso(&mut self, points: u32, penalty: u32, now: Instant) {
+        if self.start_time.is_none() {
+            self.start_time = Some(now);
+        }
+        self.qso_count += 1;
+        self.total_points += points;
+        self.penalty_points += penalty;
+    }
+
+    pub fn add_penalty(&mut self, penalty: u32) {
+        self.penalty_points += penalty;
+    }
+
+    /// The score after log checking: points less penalties (can go below zero)
+    pub fn penalized_points(&self) -> i64 {
+        i64::from(self.total_points) - i64::from(self.penalty_points)
+    }
+
+    /// Recompute the totals after logged QSOs were edited or excluded
+    pub fn recount(&mut self, stats: &SessionStats) {
+        self.qso_count = stats.counted().count() as u32;
+        self.total_points = stats.counted().map(|q| q.points).sum();
+        self.penalty_points = stats.penalty_points();
+    }
+}
+
+/// The keys the simulation answers to. The window maps its key presses
+/// onto these and the headless runner reads them from its script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    ArrowUp,
+    ArrowDown,
+    Enter,
+    Escape,
+    Space,
+    Tab,
+}
+
+/// Something that moves the simulation along
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// The simulation clock moved on to `now`: take in the audio engine's
+    /// events, then run the caller and wait timers. Keys pressed after it
+    /// happen at `now` too.
+    Elapsed { now: Instant },
+    /// The operator pressed a key
+    Key { key: Key, shift: bool },
+}
+
+pub struct Engine {
+    pub settings: AppSettings,
+    /// The simulation clock, as of the last `Event::Elapsed`. Every timer
+    /// runs on it rather than the wall clock, so a run can be replayed
+    /// faster than real time.
+    now: Instant,
+    /// Every random choice the simulation makes, so a run started from the
+    /// same seed and fed the same events plays out the same way
+    rng: StdRng,
+    pub state: ContestState,
+    /// Whether we're running or answering CQs, in contests with a QSY rule
+    pub role: Role,
+    pub context: QsoContext,
+    pub score: Score,
+    pub callsign_input: String,
+    pub exchange_inputs: Vec<String>,
+    /// Pre-fill state of each exchange box
+    pub exchange_prefill: Vec<PrefillState>,
+    pub current_field: InputField,
+    pub last_qso_result: Option<QsoResult>,
+    last_logged: Option<LoggedQso>,
+    /// The entry boxes hold the reopened last QSO rather than a new one
+    pub editing_last_qso: bool,
+    /// Why the last Enter didn't log, shown under the entry boxes in strict
+    /// mode until the entry changes
+    pub entry_error: Option<String>,
+    /// Note being typed for a flagged QSO, while the note box is open
+    pub note_input: Option<String>,
+    /// The note box is for the QSO in progress rather than the last one logged
+    note_for_current: bool,
+    /// Note for the QSO in progress, which is flagged when it is logged
+    pending_note: Option<String>,
+
+    // Audio commands out, and completion events back
+    cmd_tx: Sender<AudioCommand>,
+    /// Commands waiting for room in the channel, sent in order before
+    /// anything new
+    audio_backlog: VecDeque<AudioCommand>,
+    /// Audio commands dropped because the audio stopped taking them
+    pub dropped_audio_commands: u32,
+    event_rx: Receiver<AudioEvent>,
+
+    // Contest and station management
+    pub contest: Box<dyn Contest>,
+    pub contest_registry: Vec<ContestDescriptor>,
+    caller_manager: CallerManager,
+    /// Background signals on frequency (QRL?, tune-ups, other CQs)
+    ambience: AmbienceScheduler,
+    /// Someone taking our run frequency, when the simulation allows it
+    fight: FrequencyFight,
+    /// After a QSY, when we'll have found a clear frequency
+    qsy_until: Option<Instant>,
+    /// Pileup size earned by the run, when dynamic density is on
+    density: CallerDensity,
+    user_serial: u32,
+    cty: Arc<CtyDat>,
+    /// Watches the contest's files so edits are reloaded (None if the
+    /// platform's file watching couldn't be started)
+    file_watcher: Option<FileWatcher>,
+    /// Warnings and errors for the user, with what was reloaded
+    pub notifications: Notifications,
+    /// Downloads started from File > Check for Data Updates
+    pub data_updater: DataUpdater,
+
+    // Settings being edited
+    pub settings_changed: bool,
+    pub settings_notice: Option<String>,
+    pub settings_error: Option<String>,
+    pub last_exchange_field_index: usize,
+
+    // Timing for caller spawning
+    last_cq_finished: Option<Instant>,
+    /// When the CQ being sent is expected to end (for tailgating callers)
+    cq_ends_at: Option<Instant>,
+    /// When the exchange being sent is due to end. Timed from when it
+    /// started rather than from the station's complete event, which only
+    /// comes once the audio buffer has drained. None once it has ended.
+    exchange_ends_at: Option<Instant>,
+    /// Pileup size and copy time for the QSO in progress
+    qso_start: Option<QsoStart>,
+    /// The caller our exchange went out to, until they're logged
+    exchange_sent_to: Option<String>,
+    /// Their call in each part of the message going out, until that part
+    /// has been sent
+    calls_sending: VecDeque<String>,
+    /// Calls sent whole since the last QSO was logged
+    calls_sent: Vec<String>,
+
+    // Noise toggle state
+    pub noise_enabled: bool,
+    saved_noise_level: f32,
+
+    // Session statistics
+    pub session_stats: SessionStats,
+    /// Grade and tips for the session just ended, shown until dismissed
+    pub session_summary: Option<SessionSummary>,
+    /// Best figures over all sessions, for the summary to compare against
+    pub personal_bests: PersonalBests,
+    /// Achievements earned over all sessions; None when they aren't kept
+    pub achievements: Option<Achievements>,
+    /// Milestone alerts already given this session
+    milestones: MilestoneTracker,
+    /// Receiver incremental tuning, used in a wide pileup
+    pub rit_hz: f32,
+    /// RIT and filter last sent to the audio engine
+    receiver_tuned: Option<(f32, Option<f32>)>,
+    /// Status changes for screen readers and speech
+    pub announcer: Announcer,
+    /// Most recent messages we sent, oldest first
+    pub tx_history: VecDeque<TxRecord>,
+
+    // Copy drills
+    pub drill: DrillSession,
+    /// A drill or the sandbox is in use, so background stations stay quiet
+    pub drilling: bool,
+    // Simulated CW skimmer
+    pub skimmer: Skimmer,
+    pub sandbox: MorseSandbox,
+
+    // AGN usage tracking for current QSO
+    used_agn_callsign: bool,
+    used_agn_exchange: bool,
+    used_f5_callsign: bool,
+
+    /// Autosave the session for crash recovery (off for headless runs)
+    pub autosave: bool,
+    /// Guided first session, while it runs
+    pub tutorial: Option<Tutorial>,
+}
+
+impl Engine {
+    /// Engine with the user's saved settings
+    pub fn load(now: Instant, seed: u64) -> (Self, Receiver<AudioCommand>, Sender<AudioEvent>) {
+        let load_result = AppSettings::load_with_notice();
+        Self::new(load_result.settings, load_result.notice, now, seed)
+    }
+
+    /// Engine with no sound card attached, its clock starting at `now` and
+    /// its random choices drawn from `seed`. The caller gets the audio
+    /// command and event channels to play or render the audio.
+    pub fn new(
+        mut settings: AppSettings,
+        settings_notice: Option<String>,
+        now: Instant,
+        seed: u64,
+    ) -> (Self, Receiver<AudioCommand>, Sender<AudioEvent>) {
+        let mut settings_changed = false;
+        let mut notifications = Notifications::default();
+        if let Some(notice) = &settings_notice {
+            notifications.warning(notice.clone());
+        }
+        for error in &contest::custom::loaded().errors {
+            notifications.warning(tr_args("Custom contest not loaded: {}", &[error]));
+        }
+
+        let contest_registry = contest::registry();
+        let default_descriptor = contest_registry
+            .first()
+            .expect("No contests registered. Add at least one contest file.");
+        let active_descriptor = contest_registry
+            .iter()
+            .find(|entry| entry.id == settings.contest.active_contest_id)
+            .unwrap_or_else(|| {
+                settings.contest.active_contest_id = default_descriptor.id.to_string();
+                settings_changed = true;
+                default_descriptor
+            });
+
+        // Create channels for audio communication
+        let (cmd_tx, cmd_rx) = bounded::<AudioCommand>(AUDIO_QUEUE_LEN);
+        // Events are passed on from the audio's own queue on this thread,
+        // so there must always be room for them
+        let (event_tx, event_rx) = unbounded::<AudioEvent>();
+
+        // Create contest
+        let mut contest = (active_descriptor.factory)();
+        let needs_settings = !settings.contest.contests.contains_key(active_descriptor.id);
+        let contest_settings = settings.contest.settings_for_mut(contest.as_ref());
+        contest.configure(contest_settings);
+        if needs_settings {
+            settings_changed = true;
+        }
+        let settings_error = contest.validate_settings(contest_settings).err();
+
+        // Load CTY database for country lookups
+        let cty = data_update::cty();
+
+        // Load callsigns and create caller manager
+        let callsign_source = Self::build_callsign_source(
+            contest.as_ref(),
+            contest_settings,
+            &settings.simulation.synthetic_calls,
+            &cty,
+        );
+        Self::report_callsign_fallback(&mut notifications, callsign_source.as_ref());
+        let caller_manager = CallerManager::new(callsign_source, settings.simulation.clone(), now);
+        let density = CallerDensity::new(settings.simulation.station_probability);
+
+        let noise_enabled = settings.audio.noise_level > 0.0;
+        let saved_noise_level = settings.audio.noise_level;
+        let sandbox = MorseSandbox::new(settings.user.wpm, settings.audio.tone_frequency_hz);
+
+        let mut engine = Self {
+            settings,
+            now,
+            rng: StdRng::seed_from_u64(seed),
+            state: ContestState::Idle,
+            role: Role::Running,
+            context: QsoContext::new(),
+            score: Score::default(),
+            callsign_input: String::new(),
+            exchange_inputs: contest
+                .exchange_fields()
+                .iter()
+                .map(|field| field.default_value.unwrap_or("").to_string())
+                .collect(),
+            exchange_prefill: vec![PrefillState::Typed; contest.exchange_fields().len()],
+            current_field: InputField::Callsign,
+            last_qso_result: None,
+            last_logged: None,
+            editing_last_qso: false,
+            entry_error: None,
+            note_input: None,
+            note_for_current: false,
+            pending_note: None,
+            cmd_tx,
+            audio_backlog: VecDeque::new(),
+            dropped_audio_commands: 0,
+            event_rx,
+            contest,
+            contest_registry,
+            caller_manager,
+            ambience: AmbienceScheduler::new(),
+            fight: FrequencyFight::new(),
+            qsy_until: None,
+            density,
+            user_serial: 1,
+            cty,
+            file_watcher: FileWatcher::new().ok(),
+            notifications,
+            data_updater: DataUpdater::default(),
+            settings_changed,
+            settings_notice,
+            settings_error,
+            last_exchange_field_index: 0,
+            last_cq_finished: None,
+            cq_ends_at: None,
+            exchange_ends_at: None,
+            qso_start: None,
+            exchange_sent_to: None,
+            calls_sending: VecDeque::new(),
+            calls_sent: Vec::new(),
+            noise_enabled,
+            saved_noise_level,
+            session_stats: SessionStats::new(),
+            session_summary: None,
+            personal_bests: PersonalBests::default(),
+            achievements: None,
+            milestones: MilestoneTracker::default(),
+            rit_hz: 0.0,
+            receiver_tuned: None,
+            announcer: Announcer::new(),
+            tx_history: VecDeque::with_capacity(TX_HISTORY_LEN),
+            drill: DrillSession::new(),
+            drilling: false,
+            skimmer: Skimmer::new(),
+            sandbox,
+            used_agn_callsign: false,
+            used_agn_exchange: false,
+            used_f5_callsign: false,
+            autosave: false,
+            tutorial: None,
+        };
+        engine.watch_contest_files();
+        (engine, cmd_rx, event_tx)
+    }
+
+    /// Report a status change to the screen reader and speech command
+    fn announce(&mut self, text: String) {
+        self.announcer
+            .announce(text, &self.settings.user.speech_command);
+    }
+
+    /// Queue a command for the audio without ever waiting on it. What
+    /// doesn't fit in the channel waits here, in order; if the audio stops
+    /// taking commands altogether the session can't go on as heard, so the
+    /// waiting commands are dropped and counted and the session is stopped.
+    fn send_audio(&mut self, cmd: AudioCommand) {
+        self.audio_backlog.push_back(cmd);
+        self.flush_audio();
+        if self.audio_backlog.len() > AUDIO_BACKLOG_LIMIT {
+            self.dropped_audio_commands += self.audio_backlog.len() as u32;
+            self.audio_backlog.clear();
+            self.notifications
+                .error(tr("The audio stopped responding; the session was stopped"));
+            self.pause();
+        }
+    }
+
+    /// Hand the audio whatever has been waiting for room
+    fn flush_audio(&mut self) {
+        while let Some(cmd) = self.audio_backlog.pop_front() {
+            if let Err(error) = self.cmd_tx.try_send(cmd) {
+                self.audio_backlog.push_front(error.into_inner());
+                break;
+            }
+        }
+    }
+
+    /// Commands waiting for the audio to take them
+    pub fn queued_audio_commands(&self) -> usize {
+        self.cmd_tx.len() + self.audio_backlog.len()
+    }
+
+    /// Key a message and remember it in the TX history
+    fn transmit(&mut self, segments: Vec<MessageSegment>, wpm: u8) {
+        let text = segments
+            .iter()
+            .map(|segment| segment.content.trim())
+            .filter(|content| !content.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if self.tx_history.len() == TX_HISTORY_LEN {
+            self.tx_history.pop_front();
+        }
+        self.tx_history.push_back(TxRecord {
+            sent_at: chrono::Local::now(),
+            text,
+            wpm,
+        });
+        // A new message cuts off whatever was still going out
+        self.calls_sending = segments
+            .iter()
+            .filter(|segment| segment.segment_type == MessageSegmentType::TheirCallsign)
+            .map(|segment| segment.content.trim().to_uppercase())
+            .collect();
+
+        self.send_audio(AudioCommand::PlayUserMessageSegmented { segments, wpm });
+    }
+
+    /// Cut off everything that's playing
+    pub fn stop_audio(&mut self) {
+        self.send_audio(AudioCommand::StopAll);
+    }
+
+    /// Announce callers that just started calling
+    fn announce_callers(&mut self) {
+        let text = match self.context.active_callers.len() {
+            0 => return,
+            1 => tr("Station calling").to_string(),
+            count => tr_args("{} stations calling", &[&count.to_string()]),
+        };
+        self.announce(text);
+    }
+
+    /// Simulation settings in effect: the tutorial's while it runs,
+    /// otherwise the user's
+    fn simulation(&self) -> &SimulationSettings {
+        match &self.tutorial {
+            Some(tutorial) => tutorial.simulation(),
+            None => &self.settings.simulation,
+        }
+    }
+
+    /// Stop whatever is going on and start the guided first session
+    pub fn start_tutorial(&mut self) {
+        self.send_audio(AudioCommand::StopAll);
+        self.context.reset();
+        self.state = ContestState::Idle;
+        self.callsign_input.clear();
+        self.clear_exchange_inputs();
+        self.current_field = InputField::Callsign;
+        let tutorial = Tutorial::new(&self.settings.simulation, self.score.qso_count);
+        // Callers already waiting were made with the user's settings
+        self.caller_manager
+            .update_settings(tutorial.simulation().clone());
+        self.caller_manager.clear_queue();
+        self.tutorial = Some(tutorial);
+        self.sync_density();
+    }
+
+    /// Back to normal operation; applying the settings brings back the
+    /// user's callers and saves that the tutorial is done
+    pub fn end_tutorial(&mut self) {
+        self.tutorial = None;
+        self.settings.user.tutorial_done = true;
+        self.settings_changed = true;
+    }
+
+    pub fn observe_tutorial(&mut self) {
+        let Some(tutorial) = self.tutorial.as_mut() else {
+            return;
+        };
+        tutorial.observe(&TutorialProgress {
+            state: self.state,
+            exchange_entered: !self.exchange_inputs.is_empty()
+                && self
+                    .exchange_inputs
+                    .iter()
+                    .all(|value| !value.trim().is_empty()),
+            qsos: self.score.qso_count,
+        });
+    }
+
+    /// RIT and filter width in effect: only a wide pileup tunes the
+    /// receiver, otherwise every caller is heard as placed
+    fn receiver_tuning(&self) -> (f32, Option<f32>) {
+        let wide = &self.simulation().wide_pileup;
+        if wide.enabled {
+            (self.rit_hz, Some(wide.filter_hz))
+        } else {
+            (0.0, None)
+        }
+    }
+
+    pub fn apply_receiver_tuning(&mut self) {
+        let tuning = self.receiver_tuning();
+        if self.receiver_tuned != Some(tuning) {
+            let (rit_hz, filter_hz) = tuning;
+            self.send_audio(AudioCommand::Tune { rit_hz, filter_hz });
+            self.receiver_tuned = Some(tuning);
+        }
+    }
+
+    pub fn wide_pileup_enabled(&self) -> bool {
+        self.simulation().wide_pileup.enabled
+    }
+
+    /// Change the wide pileup's receive filter and save it
+    pub fn set_receive_filter(&mut self, filter_hz: f32) {
+        self.settings.simulation.wide_pileup.filter_hz = filter_hz;
+        self.settings_changed = true;
+    }
+
+    /// Approximate SNR of a caller with the receiver as it is set now
+    fn caller_snr_db(&self, params: &StationParams) -> f32 {
+        let (rit_hz, filter_hz) = self.receiver_tuning();
+        let audio = &self.settings.audio;
+        let amplitude =
+            params.amplitude * passband_gain(params.frequency_offset_hz - rit_hz, filter_hz);
+        approx_snr_db(
+            amplitude,
+            audio.noise_level,
+            filter_hz.unwrap_or(audio.noise_bandwidth),
+            &audio.qsb,
+        )
+    }
+
+    /// Move the RIT by `delta_hz`, within its range
+    pub fn adjust_rit(&mut self, delta_hz: f32) {
+        self.rit_hz = (self.rit_hz + delta_hz).clamp(-RIT_MAX_HZ, RIT_MAX_HZ);
+    }
+
+    pub fn reset_score(&mut self) {
+        self.score = Score::default();
+        self.exchange_sent_to = None;
+        self.last_qso_result = None;
+        self.last_logged = None;
+        self.editing_last_qso = false;
+        self.note_input = None;
+        self.pending_note = None;
+        self.user_serial = 1;
+        self.role = Role::Running;
+        self.calls_sent.clear();
+        self.milestones = MilestoneTracker::default();
+        self.fight = FrequencyFight::new();
+        self.qsy_until = None;
+        self.caller_manager.set_heard_share(1.0);
+        self.density = CallerDensity::new(self.simulation().station_probability);
+        self.sync_density();
+        self.caller_manager.reset_session(self.now);
+    }
+
+    /// Count the QSO just logged towards the achievements, announcing any
+    /// it unlocks
+    fn count_achievements(&mut self, entered_fields: &[String]) {
+        let (Some(achievements), Some(qso)) =
+            (self.achievements.as_mut(), self.session_stats.qsos.last())
+        else {
+            return;
+        };
+        // The section as logged, when it was copied right
+        let section = self
+            .contest
+            .exchange_fields()
+            .iter()
+            .zip(&qso.field_results)
+            .zip(entered_fields)
+            .find(|((field, (_, correct)), _)| field.kind == FieldKind::Section && *correct)
+            .map(|(_, value)| value.as_str());
+        for achievement in achievements.on_qso(qso, section) {
+            self.notifications
+                .info(tr_args("Achievement unlocked: {}", &[&achievement.name()]));
+        }
+        if let Err(e) = achievements::save(achievements) {
+            self.notifications
+                .error(tr_args("Couldn't save achievements: {}", &[&e]));
+        }
+    }
+
+    /// Point out any milestone the QSO just logged reached, as a banner,
+    /// a chime, or both
+    fn check_milestones(&mut self) {
+        let alerts = self.settings.user.alerts.clone();
+        if !alerts.banner && !alerts.chime {
+            return;
+        }
+        let best_rate = self.personal_bests.rate_10min;
+        for milestone in self
+            .milestones
+            .on_qso(&self.session_stats, best_rate, &alerts)
+        {
+            if alerts.banner {
+                self.notifications.info(milestone.message());
+            }
+            if alerts.chime {
+                self.send_audio(AudioCommand::PlayUiSound(milestone.sound()));
+            }
+        }
+    }
+
+    /// End the session: keep its summary for the summary dialog, then start fresh
+    pub fn end_session(&mut self) {
+        self.session_summary = summarize(&self.session_stats);
+        if let Some(summary) = self.session_summary.as_mut() {
+            let session = PersonalBests::of_session(&self.session_stats);
+            summary.bests = self.personal_bests.record(&session);
+            if summary.bests.iter().any(|best| best.new_best) {
+                if let Err(e) = personal_bests::save(&self.personal_bests) {
+                    self.notifications
+                        .error(tr_args("Couldn't save personal bests: {}", &[&e]));
+                }
+            }
+        }
+        self.reset_score();
+        self.session_stats.clear();
+        self.refresh_char_focus();
+        self.skimmer.clear();
+        if self.autosave {
+            recovery::discard();
+        }
+    }
+
+    /// Write the session to the recovery file
+    fn autosave_session(&mut self) {
+        if !self.autosave || self.settings.user.autosave_every == 0 {
+            return;
+        }
+        let session = SavedSession {
+            saved_at: chrono::Local::now().to_rfc3339(),
+            qsos: self.session_stats.qsos.clone(),
+            doubles: self.session_stats.doubles.clone(),
+            callers_lost: self.session_stats.callers_lost,
+            nils: self.session_stats.nils.clone(),
+            wide_pileup: self.session_stats.wide_pileup.clone(),
+            fights: self.session_stats.fights.clone(),
+            density: self.session_stats.density.clone(),
+            user_serial: self.user_serial,
+            elapsed_secs: self
+                .score
+                .start_time
+                .map(|start| self.now.saturating_duration_since(start).as_secs_f64())
+                .unwrap_or(0.0),
+        };
+        if let Err(e) = recovery::save(&session) {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to autosave session: {}", e);
+            self.notifications.warning(tr_args(
+                "Couldn't autosave the session: {}",
+                &[&e.to_string()],
+            ));
+        }
+    }
+
+    /// Pick up an autosaved session where it left off
+    pub fn restore_session(&mut self, saved: SavedSession) {
+        self.session_stats.clear();
+        self.score.start_time = self.now.checked_sub(saved.elapsed());
+        self.user_serial = saved.user_serial.max(1);
+        self.session_stats.qsos = saved.qsos;
+        self.session_stats.doubles = saved.doubles;
+        self.session_stats.callers_lost = saved.callers_lost;
+        self.session_stats.nils = saved.nils;
+        self.session_stats.wide_pileup = saved.wide_pileup;
+        self.session_stats.fights = saved.fights;
+        self.session_stats.density = saved.density;
+        // The pileup picks up where the session left it
+        self.density = CallerDensity::new(
+            self.session_stats
+                .density
+                .last()
+                .map_or(self.simulation().station_probability, |sample| {
+                    sample.probability
+                }),
+        );
+        self.sync_density();
+        self.score.recount(&self.session_stats);
+        self.refresh_char_focus();
+    }
+
+    /// Point the weak-character focus at the session's latest error rates
+    fn refresh_char_focus(&mut self) {
+        self.caller_manager
+            .set_char_focus(CharFocus::from_error_rates(
+                &self.session_stats.char_error_rates(),
+            ));
+    }
+
+    /// Let the pileup follow the run's density while dynamic density is
+    /// on; while it's off, keep it ready to start from the station
+    /// probability
+    fn sync_density(&mut self) {
+        if self.simulation().dynamic_density.enabled {
+            self.caller_manager
+                .set_density(Some(self.density.probability()));
+        } else {
+            self.density = CallerDensity::new(self.simulation().station_probability);
+            self.caller_manager.set_density(None);
+        }
+    }
+
+    /// Build or thin the pileup after a QSO that took `qso_time`, and
+    /// note the density for the stats chart
+    fn update_density(&mut self, qso_time: Option<Duration>, clean: bool) {
+        let settings = self.simulation().dynamic_density.clone();
+        let Some(qso_time) = qso_time.filter(|_| settings.enabled) else {
+            return;
+        };
+        let minutes = self
+            .score
+            .start_time
+            .map(|start| self.now.saturating_duration_since(start).as_secs_f32() / 60.0)
+            .unwrap_or(0.0);
+        if self.session_stats.density.is_empty() {
+            self.session_stats.density.push(DensitySample {
+                minutes: 0.0,
+                probability: self.density.probability(),
+            });
+        }
+        let probability = self.density.on_qso(qso_time, clean, &settings);
+        self.session_stats.density.push(DensitySample {
+            minutes,
+            probability,
+        });
+        self.sync_density();
+    }
+
+    pub fn toggle_noise(&mut self) {
+        if self.noise_enabled {
+            // Save current level and disable
+            self.saved_noise_level = self.settings.audio.noise_level;
+            self.settings.audio.noise_level = 0.0;
+            self.noise_enabled = false;
+        } else {
+            // Restore saved level (use default if saved was 0)
+            self.settings.audio.noise_level = if self.saved_noise_level > 0.0 {
+                self.saved_noise_level
+            } else {
+                0.15
+            };
+            self.noise_enabled = true;
+        }
+        // Send updated settings to audio engine
+        self.send_audio(AudioCommand::UpdateSettings(self.settings.audio.clone()));
+    }
+
+    /// Get the status text and color for UI display
+    pub fn get_status(&self) -> (&'static str, StatusColor) {
+        if self.editing_last_qso {
+            return (
+                "Editing last QSO (Enter saves, Esc cancels)",
+                StatusColor::Orange,
+            );
+        }
+        if self.qsy_pending() {
+            return ("QSY - finding a clear frequency...", StatusColor::Orange);
+        }
+        if self.fight.squatter().is_some()
+            && matches!(
+                self.state,
+                ContestState::Idle | ContestState::CallingCq | ContestState::WaitingForCallers
+            )
+        {
+            return (
+                "Frequency taken! Keep CQing or QSY (Alt+Q)",
+                StatusColor::Orange,
+            );
+        }
+        if self.role == Role::Answering {
+            match self.state {
+                ContestState::Idle | ContestState::QsoComplete => {
+                    return ("QSY! Press F1/Enter to find a CQ", StatusColor::Orange);
+                }
+                ContestState::StationsCalling if !self.context.correction_in_progress => {
+                    return ("Station calling CQ - enter their call", StatusColor::Green);
+                }
+                _ => {}
+            }
+        }
+        self.state.status_text(&self.context)
+    }
+
+    fn send_cq(&mut self) {
+        // Calling CQ takes over the audio from the sandbox
+        self.sandbox.stop();
+        if self.role == Role::Answering {
+            self.find_cq();
+            return;
+        }
+        if self.qsy_pending() {
+            self.announce(tr("Still looking for a clear frequency").to_string());
+            return;
+        }
+        self.qsy_until = None;
+        self.fight.on_cq(self.now);
+        let cq_prefix = self
+            .contest
+            .cq_message(
+                self.settings
+                    .contest
+                    .settings_for_mut(self.contest.as_ref()),
+            )
+            .trim()
+            .to_string();
+        let callsign = self.settings.user.callsign.trim();
+        let message = format!("{} {}", cq_prefix, callsign);
+        let wpm = self.settings.user.wpm;
+        self.cq_ends_at =
+            Some(self.now + Duration::from_millis(message_duration_ms(&message, wpm)));
+
+        let segments = vec![MessageSegment {
+            content: message,
+            segment_type: MessageSegmentType::Cq,
+        }];
+
+        self.transmit(segments, wpm);
+
+        self.state = ContestState::CallingCq;
+        self.pending_note = None;
+        self.qso_start = None;
+        self.check_dropped_qso();
+
+        // Reset AGN tracking for new QSO
+        self.used_agn_callsign = false;
+        self.used_agn_exchange = false;
+        self.used_f5_callsign = false;
+
+        // Reset context for new QSO
+        self.context.reset();
+    }
+
+    /// Under a QSY rule, after a run QSO: move off and tune in someone
+    /// else's CQ to answer. Working them leaves the frequency to us.
+    fn find_cq(&mut self) {
+        self.pending_note = None;
+        self.qso_start = None;
+        self.check_dropped_qso();
+        self.used_agn_callsign = false;
+        self.used_agn_exchange = false;
+        self.used_f5_callsign = false;
+        self.context.reset();
+
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let cq_prefix = self.contest.cq_message(contest_settings).trim().to_string();
+        let Some(params) =
+            self.caller_manager
+                .cq_station(self.contest.as_ref(), contest_settings, &mut self.rng)
+        else {
+            self.state = ContestState::Idle;
+            return;
+        };
+
+        let mut audio = params.clone();
+        audio.callsign = format!("{} {}", cq_prefix, params.callsign);
+        self.spot(&params);
+        self.send_audio(AudioCommand::StartStation(audio));
+        self.context.set_callers(vec![ActiveCaller { params }]);
+        self.start_qso(1);
+        self.state = ContestState::StationsCalling;
+    }
+
+    /// Moving on after our exchange went out, without logging the caller:
+    /// they may well have logged us, which log checking counts as a NIL
+    fn check_dropped_qso(&mut self) {
+        let Some(callsign) = self.exchange_sent_to.take() else {
+            return;
+        };
+        use rand::Rng;
+        let probability = self.simulation().nil_probability.clamp(0.0, 1.0);
+        if !self.rng.gen_bool(f64::from(probability)) {
+            return;
+        }
+        let penalty = self.contest.qso_points() * NIL_PENALTY_QSOS;
+        self.session_stats.log_nil(NilRecord {
+            callsign,
+            penalty,
+            at: chrono::Local::now().to_rfc3339(),
+        });
+        self.score.add_penalty(penalty);
+    }
+
+    /// What log checking charges for a QSO logged with a busted call or
+    /// exchange
+    fn bust_penalty(&self, result: &QsoResult) -> u32 {
+        if result.callsign_correct && result.exchange_correct {
+            0
+        } else {
+            self.contest.qso_points() * BUST_PENALTY_QSOS
+        }
+    }
+
+    fn send_exchange(&mut self, their_call: &str) {
+        self.context.awaiting_user_exchange = false;
+        self.answer_fill(FillAnswer::FullExchange);
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let exchange_fields = self.contest.user_exchange_fields(
+            &self.settings.user.callsign,
+            self.user_serial,
+            contest_settings,
+        );
+        let exchange = self.contest.format_user_exchange(&exchange_fields);
+
+        let wpm = self.settings.user.wpm;
+
+        // Use segmented message for element-level tracking
+        // Word gap is automatically added between segments by SegmentedUserStation
+        let segments = vec![
+            MessageSegment {
+                content: their_call.to_string(),
+                segment_type: MessageSegmentType::TheirCallsign,
+            },
+            MessageSegment {
+                content: exchange,
+                segment_type: MessageSegmentType::OurExchange,
+            },
+        ];
+
+        self.transmit(segments, wpm);
+    }
+
+    fn send_exchange_only(&mut self) {
+        self.context.awaiting_user_exchange = false;
+        self.answer_fill(FillAnswer::FullExchange);
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let exchange_fields = self.contest.user_exchange_fields(
+            &self.settings.user.callsign,
+            self.user_serial,
+            contest_settings,
+        );
+        let exchange = self.contest.format_user_exchange(&exchange_fields);
+
+        let wpm = self.settings.user.wpm;
+
+        // Use segmented message for element-level tracking
+        let segments = vec![MessageSegment {
+            content: exchange,
+            segment_type: MessageSegmentType::OurExchange,
+        }];
+
+        self.transmit(segments, wpm);
+    }
+
+    fn send_tu(&mut self) {
+        use rand::Rng;
+
+        // Vary the sign-off when the template offers alternatives
+        let variants = self.settings.user.tu_message_variants();
+        let template = variants
+            .get(self.rng.gen_range(0..variants.len().max(1)))
+            .copied()
+            .unwrap_or("");
+        let message = template.replace("{MYCALL}", self.settings.user.callsign.trim());
+        let wpm = self.settings.user.wpm;
+
+        let segments = vec![MessageSegment {
+            content: message,
+            segment_type: MessageSegmentType::Tu,
+        }];
+
+        self.transmit(segments, wpm);
+    }
+
+    /// Give our own call to a station we found calling CQ
+    fn send_our_call(&mut self) {
+        let wpm = self.settings.user.wpm;
+        let segments = vec![MessageSegment {
+            content: self.settings.user.callsign.trim().to_uppercase(),
+            segment_type: MessageSegmentType::OurCallsign,
+        }];
+
+        self.transmit(segments, wpm);
+    }
+
+    fn send_his_call(&mut self) {
+        let their_call = self.callsign_input.trim().to_uppercase();
+        if their_call.is_empty() {
+            return;
+        }
+
+        let wpm = self.settings.user.wpm;
+
+        // Use segmented message for element-level tracking
+        let segments = vec![MessageSegment {
+            content: their_call,
+            segment_type: MessageSegmentType::TheirCallsign,
+        }];
+
+        self.transmit(segments, wpm);
+    }
+
+    /// The contest's callsign source, or generated calls when they're
+    /// turned on and the contest's exchange can be made up from the call
+    fn build_callsign_source(
+        contest: &dyn Contest,
+        contest_settings: &toml::Value,
+        synthetic: &SyntheticCallSettings,
+        cty: &CtyDat,
+    ) -> Box<dyn CallsignSource> {
+        if synthetic.enabled && contest.synthetic_calls() {
+            return Box::new(SyntheticCallsignSource::new(cty, synthetic.clone()));
+        }
+        contest
+            .callsign_source(contest_settings)
+            .unwrap_or_else(|_| {
+                contest
+                    .callsign_source(&contest.default_settings())
+                    .expect("Failed to build callsign source")
+            })
+    }
+
+    /// Tell the user when one of the contest's files couldn't be used
+    fn report_callsign_fallback(notifications: &mut Notifications, source: &dyn CallsignSource) {
+        if let Some((reason, using)) = source.fallback() {
+            notifications.warning(tr_args("Couldn't load {}; using {}", &[reason, tr(using)]));
+        }
+    }
+
+    /// The active contest's file settings (callsign and call-history files)
+    fn contest_file_paths(&mut self) -> Vec<PathBuf> {
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        self.contest
+            .settings_fields()
+            .iter()
+            .filter(|field| field.kind == SettingFieldKind::FilePath)
+            .filter_map(|field| contest_settings.get(field.key)?.as_str())
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn watch_contest_files(&mut self) {
+        let paths = self.contest_file_paths();
+        if let Some(watcher) = self.file_watcher.as_mut() {
+            watcher.watch(&paths);
+        }
+    }
+
+    /// Rebuild the callsign pool when one of the contest's files has been
+    /// edited; callers already on frequency are kept
+    pub fn reload_changed_files(&mut self) {
+        let Some(watcher) = self.file_watcher.as_mut() else {
+            return;
+        };
+        let changed = watcher.poll(self.now);
+        if changed.is_empty() {
+            return;
+        }
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let callsign_source = Self::build_callsign_source(
+            self.contest.as_ref(),
+            contest_settings,
+            &self.settings.simulation.synthetic_calls,
+            &self.cty,
+        );
+        Self::report_callsign_fallback(&mut self.notifications, callsign_source.as_ref());
+        self.caller_manager.reload_callsigns(callsign_source);
+
+        let names: Vec<String> = changed
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        self.notifications
+            .info(tr_args("Reloaded {}", &[&names.join(", ")]));
+    }
+
+    /// Switch to newly downloaded data once a data update has finished
+    pub fn finish_data_update(&mut self) {
+        let Some(results) = self.data_updater.poll() else {
+            return;
+        };
+        if results.is_empty() {
+            self.notifications
+                .warning(tr("No download addresses are set for data updates"));
+            return;
+        }
+        let mut updated = false;
+        for (file, result) in results {
+            match result {
+                Ok(()) => {
+                    updated = true;
+                    self.notifications
+                        .info(tr_args("{} updated", &[file.name()]));
+                }
+                Err(e) => self
+                    .notifications
+                    .error(tr_args("Couldn't update {}: {}", &[file.name(), &e])),
+            }
+        }
+        if !updated {
+            return;
+        }
+
+        self.cty = data_update::cty();
+        // Contests keep their own copy of what they read at start
+        if let Some(descriptor) = self
+            .contest_registry
+            .iter()
+            .find(|entry| entry.id == self.contest.id())
+        {
+            self.contest = (descriptor.factory)();
+        }
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        self.contest.configure(contest_settings);
+        let callsign_source = Self::build_callsign_source(
+            self.contest.as_ref(),
+            contest_settings,
+            &self.settings.simulation.synthetic_calls,
+            &self.cty,
+        );
+        Self::report_callsign_fallback(&mut self.notifications, callsign_source.as_ref());
+        self.caller_manager.reload_callsigns(callsign_source);
+    }
+
+    /// Play drill text as a single station, outside the contest state machine
+    fn play_drill_text(&mut self, text: String, wpm: u8, effective_wpm: Option<u8>) {
+        self.send_audio(AudioCommand::StopAll);
+        self.sandbox.stop();
+        let params = StationParams {
+            id: DRILL_STATION_ID,
+            callsign: text,
+            exchange: Exchange::new(Vec::new()),
+            // Heard at pitch whatever the RIT is set to
+            frequency_offset_hz: self.receiver_tuning().0,
+            wpm,
+            amplitude: 0.8,
+            reaction_delay_ms: 0,
+        };
+        self.send_audio(match effective_wpm {
+            Some(effective_wpm) => AudioCommand::StartFarnsworth {
+                params,
+                effective_wpm,
+            },
+            None => AudioCommand::StartStation(params),
+        });
+    }
+
+    /// Play a recording for the Recordings drill, resampled to the output rate
+    fn play_drill_recording(&mut self, path: &std::path::Path) {
+        let (left, right) = match load_audio(path, self.settings.audio.sample_rate) {
+            Ok(audio) => audio,
+            Err(e) => {
+                self.drill.recordings_status = Some(e);
+                return;
+            }
+        };
+        self.drill.recordings_status = None;
+        self.send_audio(AudioCommand::StopAll);
+        self.sandbox.stop();
+        self.send_audio(AudioCommand::PlayRecording {
+            id: DRILL_STATION_ID,
+            left,
+            right,
+        });
+    }
+
+    pub fn play_drill_prompt(&mut self, prompt: DrillPrompt) {
+        match prompt.recording {
+            Some(path) => self.play_drill_recording(&path),
+            None => self.play_drill_text(prompt.text, prompt.wpm, prompt.effective_wpm),
+        }
+    }
+
+    /// Start the next queued sandbox item if nothing is playing
+    pub fn play_next_sandbox_item(&mut self) {
+        if let Some(item) = self.sandbox.start_next() {
+            self.send_audio(AudioCommand::StartStation(StationParams {
+                id: SANDBOX_STATION_ID,
+                callsign: item.text,
+                exchange: Exchange::new(Vec::new()),
+                frequency_offset_hz: item.pitch_hz - self.settings.audio.tone_frequency_hz
+                    + self.receiver_tuning().0,
+                wpm: item.wpm,
+                amplitude: 0.8,
+                reaction_delay_ms: 0,
+            }));
+        }
+    }
+
+    /// Save the Koch course after an item, saying so when it opened the
+    /// next lesson
+    pub fn koch_lesson_scored(&mut self, lesson: u8) {
+        let next = self.drill.koch.lesson;
+        if next > lesson {
+            self.notifications.info(tr_args(
+                "Koch lesson {} mastered; lesson {} adds {}",
+                &[
+                    &lesson.to_string(),
+                    &next.to_string(),
+                    &koch::new_char(next).to_string(),
+                ],
+            ));
+        }
+        if let Err(e) = koch::save(&self.drill.koch) {
+            self.notifications
+                .error(tr_args("Couldn't save Koch course progress: {}", &[&e]));
+        }
+    }
+
+    pub fn export_audio_drill(&mut self) {
+        let cut_number_probability = self.settings.contest.cut_number_probability(
+            self.contest.id(),
+            self.settings.simulation.cut_number_probability,
+        );
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref())
+            .clone();
+        let mut source = Self::build_callsign_source(
+            self.contest.as_ref(),
+            &contest_settings,
+            &self.settings.simulation.synthetic_calls,
+            &self.cty,
+        );
+        let clips = generate_clips(
+            &self.settings.drill.audio_export,
+            &self.settings.simulation,
+            self.contest.as_ref(),
+            &contest_settings,
+            cut_number_probability,
+            source.as_mut(),
+        );
+        let result = export_audio_drill(&self.settings, &clips);
+        self.drill.export_status = Some(match result {
+            Ok(path) => format!("Saved to {}", path),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    fn clear_exchange_inputs(&mut self) {
+        self.entry_error = None;
+        self.exchange_inputs = self.exchange_default_values();
+        self.exchange_prefill = vec![PrefillState::Typed; self.exchange_inputs.len()];
+    }
+
+    fn reset_exchange_inputs(&mut self) {
+        self.exchange_inputs = self.exchange_default_values();
+        self.exchange_prefill = vec![PrefillState::Typed; self.exchange_inputs.len()];
+        if self.exchange_inputs.is_empty() {
+            self.last_exchange_field_index = 0;
+        } else if self.last_exchange_field_index >= self.exchange_inputs.len() {
+            self.last_exchange_field_index = self.exchange_inputs.len() - 1;
+        }
+    }
+
+    /// Refill the exchange from call history after the callsign changes.
+    /// Boxes the user typed in are left alone.
+    pub fn update_exchange_prefill(&mut self) {
+        let defaults = self.exchange_default_values();
+        self.exchange_prefill
+            .resize(self.exchange_inputs.len(), PrefillState::Typed);
+
+        // Drop what was filled in for the previous call
+        for (idx, state) in self.exchange_prefill.iter_mut().enumerate() {
+            if *state != PrefillState::Typed {
+                self.exchange_inputs[idx] = defaults.get(idx).cloned().unwrap_or_default();
+                *state = PrefillState::Typed;
+            }
+        }
+
+        if !self.settings.user.exchange_prefill {
+            return;
+        }
+        let callsign = self.callsign_input.trim().to_uppercase();
+        let Some(history) = self.caller_manager.call_history(&callsign) else {
+            return;
+        };
+        for (idx, value) in history.into_iter().enumerate() {
+            let Some(input) = self.exchange_inputs.get_mut(idx) else {
+                break;
+            };
+            let untouched = input.is_empty() || defaults.get(idx) == Some(input);
+            if !value.is_empty() && untouched {
+                *input = value;
+                self.exchange_prefill[idx] = PrefillState::Prefilled;
+            }
+        }
+    }
+
+    fn set_exchange_field(&mut self, index: usize) {
+        if self.exchange_inputs.is_empty() {
+            self.current_field = InputField::Callsign;
+            return;
+        }
+        let index = index.min(self.exchange_inputs.len() - 1);
+        self.last_exchange_field_index = index;
+        self.current_field = InputField::Exchange(index);
+    }
+
+    fn advance_field_forward(&mut self) {
+        match self.current_field {
+            InputField::Callsign => {
+                if !self.exchange_inputs.is_empty() {
+                    self.set_exchange_field(0);
+                }
+            }
+            InputField::Exchange(index) => {
+                let next = index + 1;
+                if next < self.exchange_inputs.len() {
+                    self.set_exchange_field(next);
+                } else {
+                    self.current_field = InputField::Callsign;
+                }
+            }
+        }
+    }
+
+    fn advance_field_backward(&mut self) {
+        match self.current_field {
+            InputField::Callsign => {
+                if !self.exchange_inputs.is_empty() {
+                    self.set_exchange_field(self.exchange_inputs.len() - 1);
+                }
+            }
+            InputField::Exchange(index) => {
+                if index > 0 {
+                    self.set_exchange_field(index - 1);
+                } else {
+                    self.current_field = InputField::Callsign;
+                }
+            }
+        }
+    }
+
+    fn normalized_exchange_inputs(&self) -> Vec<String> {
+        let field_defs = self.contest.exchange_fields();
+        self.exchange_inputs
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                let kind = field_defs
+                    .get(idx)
+                    .map(|field| field.kind)
+                    .unwrap_or(FieldKind::Text);
+                contest::normalize_exchange_input(value, kind)
+            })
+            .collect()
+    }
+
+    pub fn exchange_default_values(&self) -> Vec<String> {
+        self.contest
+            .exchange_fields()
+            .iter()
+            .map(|field| field.default_value.unwrap_or("").to_string())
+            .collect()
+    }
+
+    fn exchange_focus_index(&self) -> usize {
+        self.contest
+            .exchange_fields()
+            .iter()
+            .position(|field| field.focus_on_enter)
+            .unwrap_or(0)
+    }
+
+    /// Find the caller most like the entered text, if any is close enough
+    fn find_similar_caller<'a>(
+        entered: &str,
+        callers: &'a [ActiveCaller],
+        threshold: f32,
+    ) -> Option<&'a ActiveCaller> {
+        best_match(
+            entered,
+            callers.iter().map(|c| c.params.callsign.as_str()),
+            threshold,
+        )
+        .map(|(index, _)| &callers[index])
+    }
+
+    /// F5 - Send his call (callsign field contents only)
+    /// Available in any state with an active caller
+    /// Record how we answered the caller's fill request, if one is waiting
+    fn answer_fill(&mut self, answer: FillAnswer) {
+        if self.context.fill_request.is_some() && self.context.fill_answer.is_none() {
+            self.context.fill_answer = Some(answer);
+        }
+    }
+
+    /// Shift+F2: send just the element of our exchange the caller asked
+    /// for, twice; with no fill request waiting, the same as F2
+    fn handle_fill(&mut self) {
+        let Some(index) = self
+            .context
+            .fill_request
+            .as_ref()
+            .filter(|_| self.context.fill_answer.is_none())
+            .map(|fill| fill.index)
+        else {
+            self.handle_f2_exchange();
+            return;
+        };
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let exchange_fields = self.contest.user_exchange_fields(
+            &self.settings.user.callsign,
+            self.user_serial,
+            contest_settings,
+        );
+        let Some(element) = exchange_fields.get(index) else {
+            self.handle_f2_exchange();
+            return;
+        };
+        let content = format!("{} {}", element, element);
+
+        self.send_audio(AudioCommand::StopAll);
+        self.answer_fill(FillAnswer::Element);
+        self.context.awaiting_user_exchange = false;
+        let segments = vec![MessageSegment {
+            content,
+            segment_type: MessageSegmentType::OurExchange,
+        }];
+        self.transmit(segments, self.settings.user.wpm);
+        self.state = ContestState::UserTransmitting {
+            tx_type: UserTxType::ExchangeOnly,
+        };
+    }
+
+    fn handle_f5_his_call(&mut self) {
+        let entered_call = self.callsign_input.trim().to_uppercase();
+        if entered_call.is_empty() {
+            return;
+        }
+
+        // Need at least one active caller
+        if self.context.active_callers.is_empty() {
+            return;
+        }
+
+        self.resolve_double_with_call(&entered_call, true);
+
+        // Stop any current audio
+        self.send_audio(AudioCommand::StopAll);
+
+        // A partial query ("W1?" or a fragment of a call) is answered by every
+        // caller whose call fits it; everyone else stands by
+        let exact_caller = self
+            .context
+            .active_callers
+            .iter()
+            .any(|c| c.params.callsign == entered_call);
+        let partial_matches: Vec<ActiveCaller> = if exact_caller {
+            Vec::new()
+        } else {
+            self.context
+                .active_callers
+                .iter()
+                .filter(|c| matches_partial_call(&entered_call, &c.params.callsign))
+                .cloned()
+                .collect()
+        };
+
+        if !partial_matches.is_empty() {
+            self.context.partial_matches = partial_matches
+                .iter()
+                .map(|c| c.params.callsign.clone())
+                .collect();
+        }
+
+        if partial_matches.len() > 1 {
+            // Several callers fit - they all answer and nobody is selected yet
+            self.context.set_callers(partial_matches);
+        } else {
+            // Find matching caller and select them (clone to avoid borrow issues)
+            let matching_caller = partial_matches.into_iter().next().or_else(|| {
+                Self::find_similar_caller(
+                    &entered_call,
+                    &self.context.active_callers,
+                    self.settings.simulation.call_match_threshold,
+                )
+                .cloned()
+            });
+            if let Some(caller) = matching_caller {
+                // If multiple callers, narrow down to just this one
+                let multiple_callers = self.context.active_callers.len() > 1;
+                self.context.select_caller(caller.clone());
+                if multiple_callers {
+                    self.context.set_callers(vec![caller]);
+                }
+            }
+        }
+
+        let exact_match = self
+            .context
+            .get_current_caller()
+            .map(|c| entered_call == c.params.callsign)
+            .unwrap_or(false);
+
+        // Send his call
+        self.used_f5_callsign = true;
+        self.send_his_call();
+
+        // If we have an exact match and no exchange sent yet, wait for the user to send exchange.
+        self.context.awaiting_user_exchange =
+            exact_match && !self.context.progress.sent_our_exchange;
+
+        // Only expect a repeat when the callsign isn't an exact match.
+        self.context.expecting_callsign_repeat = true;
+        self.context.allow_callsign_repeat_ack = exact_match;
+
+        self.state = ContestState::UserTransmitting {
+            tx_type: UserTxType::CallsignOnly,
+        };
+    }
+
+    /// F2 - Send exchange only
+    /// Available in any state with an active caller
+    fn handle_f2_exchange(&mut self) {
+        // Need at least one active caller
+        if self.context.active_callers.is_empty() {
+            return;
+        }
+
+        let entered_call = self.callsign_input.trim().to_uppercase();
+        if !entered_call.is_empty() {
+            self.resolve_double_with_call(&entered_call, false);
+            self.mark_call_copied();
+        }
+
+        // Stop any current audio
+        self.send_audio(AudioCommand::StopAll);
+
+        self.select_entered_caller();
+
+        // Send exchange only
+        self.send_exchange_only();
+
+        self.state = ContestState::UserTransmitting {
+            tx_type: UserTxType::ExchangeOnly,
+        };
+    }
+
+    /// If we have an entered callsign, try to select a matching caller
+    fn select_entered_caller(&mut self) {
+        // Clone to avoid borrow issues
+        let entered_call = self.callsign_input.trim().to_uppercase();
+        if !entered_call.is_empty() {
+            let matching_caller = Self::find_similar_caller(
+                &entered_call,
+                &self.context.active_callers,
+                self.settings.simulation.call_match_threshold,
+            )
+            .cloned();
+            if let Some(caller) = matching_caller {
+                // If multiple callers, narrow down to just this one
+                let multiple_callers = self.context.active_callers.len() > 1;
+                self.context.select_caller(caller.clone());
+                if multiple_callers {
+                    self.context.set_callers(vec![caller]);
+                }
+            }
+        }
+    }
+
+    /// Split a macro template into message segments.
+    /// {HISCALL} and {EXCH} become their own segments so QSO progress is
+    /// tracked the same way as for F5/F2; {MYCALL} and {SERIAL} are inlined.
+    fn macro_segments(&mut self, template: &str) -> Vec<MessageSegment> {
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let exchange_fields = self.contest.user_exchange_fields(
+            &self.settings.user.callsign,
+            self.user_serial,
+            contest_settings,
+        );
+        let exchange = self.contest.format_user_exchange(&exchange_fields);
+        let his_call = self.callsign_input.trim().to_uppercase();
+
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let flush = |text: &mut String, segments: &mut Vec<MessageSegment>| {
+            if !text.trim().is_empty() {
+                segments.push(MessageSegment {
+                    content: text.trim().to_string(),
+                    segment_type: MessageSegmentType::Macro,
+                });
+            }
+            text.clear();
+        };
+
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let token = &rest[start + 1..start + len];
+            match token.to_uppercase().as_str() {
+                "MYCALL" => text.push_str(self.settings.user.callsign.trim()),
+                "SERIAL" => text.push_str(&self.contest.format_caller_serial(self.user_serial)),
+                "HISCALL" => {
+                    flush(&mut text, &mut segments);
+                    if !his_call.is_empty() {
+                        segments.push(MessageSegment {
+                            content: his_call.clone(),
+                            segment_type: MessageSegmentType::TheirCallsign,
+                        });
+                    }
+                }
+                "EXCH" => {
+                    flush(&mut text, &mut segments);
+                    segments.push(MessageSegment {
+                        content: exchange.clone(),
+                        segment_type: MessageSegmentType::OurExchange,
+                    });
+                }
+                _ => text.push_str(&rest[start..=start + len]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        text.push_str(rest);
+        flush(&mut text, &mut segments);
+
+        segments
+    }
+
+    /// Send the user macro assigned to a function key, if any
+    fn send_macro(&mut self, key_name: &str) {
+        let Some(template) = self
+            .settings
+            .contest
+            .macro_for(self.contest.id(), key_name)
+            .map(str::to_string)
+        else {
+            return;
+        };
+
+        let sends_exchange = template.to_uppercase().contains("{EXCH}");
+        if sends_exchange && !self.context.active_callers.is_empty() {
+            self.select_entered_caller();
+        }
+
+        let segments = self.macro_segments(&template);
+        if segments.is_empty() {
+            return;
+        }
+        let sends_call = segments
+            .iter()
+            .any(|segment| segment.segment_type == MessageSegmentType::TheirCallsign);
+
+        self.send_audio(AudioCommand::StopAll);
+        self.transmit(segments, self.settings.user.wpm);
+
+        // A macro carrying our exchange behaves like F2 (or Enter when it includes his call)
+        if sends_exchange && !self.context.active_callers.is_empty() {
+            self.context.awaiting_user_exchange = false;
+            self.state = ContestState::UserTransmitting {
+                tx_type: if sends_call {
+                    UserTxType::Exchange
+                } else {
+                    UserTxType::ExchangeOnly
+                },
+            };
+        }
+    }
+
+    /// Score how the user came back to a double, the first time they act
+    /// on it. Sending one of the two calls pulls it out; a partial query that
+    /// fits asks again; any other call is garbled.
+    fn resolve_double_with_call(&mut self, entered_call: &str, partial_query: bool) {
+        let Some(calls) = self.context.double_calls() else {
+            return;
+        };
+        let outcome = if calls.contains(&entered_call) {
+            DoubleOutcome::PulledOut
+        } else if partial_query
+            && calls
+                .iter()
+                .any(|call| matches_partial_call(entered_call, call))
+        {
+            DoubleOutcome::AskedAgain
+        } else {
+            DoubleOutcome::Garbled
+        };
+        self.finish_double(outcome);
+    }
+
+    fn finish_double(&mut self, outcome: DoubleOutcome) {
+        let Some([_, second]) = self.context.double.take() else {
+            return;
+        };
+        self.session_stats.log_double(outcome);
+        if outcome == DoubleOutcome::AskedAgain {
+            // Asked again, the second station stands by and the first repeats alone
+            self.context
+                .active_callers
+                .retain(|c| c.params.id != second);
+        }
+    }
+
+    fn handle_callsign_submit(&mut self) {
+        use rand::Rng;
+
+        let entered_call = self.callsign_input.trim().to_uppercase();
+        if entered_call.is_empty() {
+            return;
+        }
+
+        // Only works when stations are calling
+        if self.state != ContestState::StationsCalling {
+            return;
+        }
+        self.resolve_double_with_call(&entered_call, false);
+
+        // User has entered a callsign, so they've "received" it
+        self.context.progress.received_their_call = true;
+
+        // Find the most similar caller, or fall back to first caller if none match
+        let caller = Self::find_similar_caller(
+            &entered_call,
+            &self.context.active_callers,
+            self.settings.simulation.call_match_threshold,
+        )
+        .or_else(|| self.context.active_callers.first())
+        .cloned();
+
+        if let Some(caller) = caller {
+            // Select this caller as the current one
+            self.context.select_caller(caller.clone());
+
+            // Answering a CQ: we only give our call, and the CQ station
+            // sends its exchange before we send ours
+            if self.role == Role::Answering {
+                self.mark_call_copied();
+                self.send_our_call();
+                self.state = ContestState::UserTransmitting {
+                    tx_type: UserTxType::OurCallsign,
+                };
+                self.set_exchange_field(self.exchange_focus_index());
+                return;
+            }
+
+            // Check if the entered callsign is correct
+            let is_exact_match = entered_call == caller.params.callsign;
+
+            if is_exact_match {
+                // Correct callsign - clear any correction state
+                self.context.end_correction();
+            } else {
+                // Incorrect callsign - check if caller will correct
+                let rng = &mut self.rng;
+                let settings = &self.settings.simulation.call_correction;
+
+                let should_correct = rng.gen::<f32>() < settings.correction_probability
+                    && self.context.correction_attempts < settings.max_correction_attempts;
+
+                if should_correct {
+                    self.context.correction_in_progress = true;
+                    self.context.increment_correction_attempt();
+                } else {
+                    // Caller won't correct anymore - clear correction state
+                    self.context.end_correction();
+                }
+            }
+
+            // Send our exchange
+            self.mark_call_copied();
+            self.send_exchange(&entered_call);
+            self.state = ContestState::UserTransmitting {
+                tx_type: UserTxType::Exchange,
+            };
+            self.set_exchange_field(self.exchange_focus_index());
+        }
+    }
+
+    fn handle_exchange_submit(&mut self) {
+        let entered_fields = self.normalized_exchange_inputs();
+        let entered_callsign = self.callsign_input.trim().to_uppercase();
+
+        // User has entered an exchange, so they've "received" it
+        if entered_fields.iter().any(|field| !field.is_empty()) {
+            self.context.progress.received_their_exchange = true;
+        }
+
+        // Get the expected caller info
+        let caller = match self.context.get_current_caller() {
+            Some(c) => c.clone(),
+            None => return,
+        };
+
+        // Must be receiving exchange
+        if !matches!(
+            self.state,
+            ContestState::StationTransmitting {
+                tx_type: StationTxType::SendingExchange
+            }
+        ) {
+            return;
+        }
+
+        self.entry_error = None;
+        if self.settings.user.strict_logging {
+            if let Some(error) = self.strict_logging_error(&entered_fields) {
+                self.announce(error.clone());
+                self.entry_error = Some(error);
+                return;
+            }
+        }
+
+        let sent_call_right = self.calls_sent.contains(&caller.params.callsign);
+        let sent_busted_call = !sent_call_right && !self.calls_sent.is_empty();
+        self.calls_sent.clear();
+
+        let result = self.grade_entry(
+            &caller.params,
+            &entered_callsign,
+            &entered_fields,
+            sent_call_right,
+        );
+        let entered_exchange = self.contest.format_received_exchange(&entered_fields);
+
+        // Did we log a different station that answered our partial query?
+        let worked_similarity = callsign_similarity(&entered_callsign, &caller.params.callsign);
+        let worked_wrong_station = !result.callsign_correct
+            && self.context.partial_matches.iter().any(|call| {
+                *call != caller.params.callsign
+                    && callsign_similarity(&entered_callsign, call) > worked_similarity
+            });
+
+        // Log QSO to session stats
+        let used_prefill = self
+            .exchange_prefill
+            .iter()
+            .any(|state| *state != PrefillState::Typed);
+        let prefill_unverified = self.exchange_prefill.contains(&PrefillState::Prefilled);
+        let worked_before_exchange = self
+            .session_stats
+            .previous_exchange(&caller.params.callsign);
+        let qso_start = self.qso_start.take();
+        let qso_time = qso_start.map(|start| self.now.saturating_duration_since(start.at));
+        let logged_early = self.exchange_still_sending();
+        self.exchange_ends_at = None;
+        if logged_early && self.settings.user.warn_early_log {
+            self.notifications.warning(tr(
+                "Logged before the caller finished the exchange; wait for the last character",
+            ));
+        }
+        self.session_stats.log_qso(QsoRecord {
+            expected_callsign: caller.params.callsign.clone(),
+            entered_callsign,
+            callsign_correct: result.callsign_correct,
+            expected_exchange: result.expected_exchange.clone(),
+            entered_exchange,
+            exchange_correct: result.exchange_correct,
+            station_wpm: caller.params.wpm,
+            snr_db: Some(self.caller_snr_db(&caller.params)),
+            pileup_size: qso_start.map(|start| start.calling),
+            copy_secs: qso_start
+                .and_then(|start| start.copied_after)
+                .map(|copied| copied.as_secs_f32()),
+            points: result.points,
+            penalty: self.bust_penalty(&result),
+            used_agn_callsign: self.used_agn_callsign,
+            used_agn_exchange: self.used_agn_exchange,
+            used_f5_callsign: self.used_f5_callsign,
+            fill_request: self
+                .context
+                .fill_request
+                .as_ref()
+                .map(|fill| fill.request.clone()),
+            fill_answer: self.context.fill_answer,
+            repeat_requests: self.context.repeat_requests.clone(),
+            worked_wrong_station,
+            worked_before_exchange,
+            wrong_fields: result.wrong_fields.clone(),
+            field_results: result.field_results.clone(),
+            impossible_fields: result.impossible_fields.clone(),
+            used_prefill,
+            prefill_unverified,
+            logged_early,
+            sent_busted_call,
+            excluded: false,
+            flagged: self.pending_note.is_some(),
+            note: self.pending_note.take().unwrap_or_default(),
+            logged_at: chrono::Local::now().to_rfc3339(),
+        });
+        if self.simulation().wide_pileup.enabled && result.callsign_correct {
+            self.session_stats
+                .wide_pileup
+                .recover(&caller.params.callsign);
+        }
+        self.count_achievements(&entered_fields);
+        self.check_milestones();
+        self.refresh_char_focus();
+        self.last_logged = Some(LoggedQso {
+            caller: caller.params.clone(),
+            entered_fields,
+            sent_call_right,
+        });
+
+        // Update score
+        self.exchange_sent_to = None;
+        self.score
+            .add_qso(result.points, self.bust_penalty(&result), self.now);
+        self.update_density(qso_time, result.callsign_correct && result.exchange_correct);
+        self.user_serial += 1;
+
+        let every = (self.settings.user.autosave_every as usize).max(1);
+        if self.session_stats.qsos.len().is_multiple_of(every) {
+            self.autosave_session();
+        }
+
+        // Mark caller as worked in the caller manager
+        self.caller_manager.on_qso_complete(caller.params.id);
+        let answered_cq = self.role == Role::Answering;
+        if self.contest.qsy_after_run() {
+            self.role = self.role.after_qso();
+        }
+
+        // Send TU, or our exchange to the CQ station we answered
+        if answered_cq {
+            self.send_exchange_only();
+        } else {
+            self.send_tu();
+        }
+
+        self.announce(result.announcement());
+        self.last_qso_result = Some(result);
+        self.state = ContestState::QsoComplete;
+
+        // Clear inputs and reset correction state
+        self.callsign_input.clear();
+        self.clear_exchange_inputs();
+        self.current_field = InputField::Callsign;
+        self.context.end_correction();
+    }
+
+    /// Whether the caller is still sending the exchange, allowing for Enter
+    /// going down on its last element
+    fn exchange_still_sending(&self) -> bool {
+        self.exchange_ends_at
+            .is_some_and(|ends_at| self.now + EARLY_LOG_ALLOWANCE < ends_at)
+    }
+
+    /// Why strict mode won't log the entry yet, if it won't
+    fn strict_logging_error(&self, entered_fields: &[String]) -> Option<String> {
+        if self.exchange_still_sending() {
+            return Some(tr("Wait for the caller to finish the exchange").to_string());
+        }
+        let empty: Vec<&str> = self
+            .contest
+            .exchange_fields()
+            .iter()
+            .zip(entered_fields)
+            .filter(|(_, value)| value.is_empty())
+            .map(|(field, _)| field.label)
+            .collect();
+        (!empty.is_empty()).then(|| tr_args("Fill in {} before logging", &[&empty.join(", ")]))
+    }
+
+    /// Check an entry against the station that was worked, and whether we
+    /// ever sent them their call right
+    fn grade_entry(
+        &mut self,
+        caller: &StationParams,
+        entered_callsign: &str,
+        entered_fields: &[String],
+        sent_call_right: bool,
+    ) -> QsoResult {
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let validation = self.contest.validate(
+            &caller.callsign,
+            &caller.exchange,
+            entered_callsign,
+            entered_fields,
+            contest_settings,
+        );
+        let field_results = validation.field_results(&self.contest.exchange_fields());
+        let wrong_fields = field_results
+            .iter()
+            .filter(|(_, correct)| !correct)
+            .map(|(label, _)| label.clone())
+            .collect();
+        let impossible_fields = field_results
+            .iter()
+            .zip(entered_fields)
+            .enumerate()
+            .filter(|(index, ((_, correct), value))| {
+                !correct && !value.is_empty() && !self.contest.field_input_valid(*index, value)
+            })
+            .map(|(_, ((label, _), _))| label.clone())
+            .collect();
+        let mut remarks = validation.remarks;
+        let mut points = validation.points;
+        if self.simulation().require_sent_call && !sent_call_right {
+            points = 0;
+            remarks.push(tr_args(
+                "No points: {} never heard their call from you right",
+                &[&caller.callsign],
+            ));
+        }
+
+        QsoResult {
+            callsign: entered_callsign.to_string(),
+            expected_call: caller.callsign.clone(),
+            expected_exchange: self.contest.format_exchange(&caller.exchange),
+            callsign_correct: validation.callsign_correct,
+            exchange_correct: validation.exchange_correct,
+            wrong_fields,
+            field_results,
+            impossible_fields,
+            remarks,
+            points,
+            caller_wpm: caller.wpm,
+            caller_offset_hz: caller.frequency_offset_hz,
+        }
+    }
+
+    /// Put the last logged QSO back in the entry boxes so a typo can be fixed
+    pub fn reopen_last_qso(&mut self) {
+        // Only between QSOs, and never over a call being typed
+        let between_qsos = matches!(self.state, ContestState::Idle | ContestState::QsoComplete);
+        if !between_qsos || !self.callsign_input.trim().is_empty() {
+            return;
+        }
+        let (Some(logged), Some(record)) = (&self.last_logged, self.session_stats.qsos.last())
+        else {
+            return;
+        };
+
+        self.callsign_input = record.entered_callsign.clone();
+        for (input, value) in self.exchange_inputs.iter_mut().zip(&logged.entered_fields) {
+            *input = value.clone();
+        }
+        for state in &mut self.exchange_prefill {
+            *state = PrefillState::Typed;
+        }
+        self.current_field = InputField::Callsign;
+        self.editing_last_qso = true;
+    }
+
+    /// Re-grade the reopened QSO with what is now in the entry boxes
+    fn save_edited_qso(&mut self) {
+        self.editing_last_qso = false;
+        let (Some(logged), Some(previous)) = (
+            self.last_logged.clone(),
+            self.session_stats.qsos.last().cloned(),
+        ) else {
+            return;
+        };
+
+        let entered_fields = self.normalized_exchange_inputs();
+        let entered_callsign = self.callsign_input.trim().to_uppercase();
+        let result = self.grade_entry(
+            &logged.caller,
+            &entered_callsign,
+            &entered_fields,
+            logged.sent_call_right,
+        );
+        self.session_stats.replace_last_qso(QsoRecord {
+            entered_callsign,
+            callsign_correct: result.callsign_correct,
+            entered_exchange: self.contest.format_received_exchange(&entered_fields),
+            exchange_correct: result.exchange_correct,
+            points: result.points,
+            penalty: self.bust_penalty(&result),
+            worked_wrong_station: previous.worked_wrong_station && !result.callsign_correct,
+            wrong_fields: result.wrong_fields.clone(),
+            field_results: result.field_results.clone(),
+            impossible_fields: result.impossible_fields.clone(),
+            prefill_unverified: false,
+            ..previous
+        });
+        self.score.recount(&self.session_stats);
+        self.refresh_char_focus();
+        self.last_logged = Some(LoggedQso {
+            entered_fields,
+            ..logged
+        });
+
+        self.autosave_session();
+
+        self.announce(result.announcement());
+        self.last_qso_result = Some(result);
+        self.cancel_qso_edit();
+    }
+
+    fn cancel_qso_edit(&mut self) {
+        self.editing_last_qso = false;
+        self.callsign_input.clear();
+        self.clear_exchange_inputs();
+        self.current_field = InputField::Callsign;
+    }
+
+    /// Flag the QSO in progress, or else the last one logged, and open the
+    /// note box
+    pub fn start_qso_note(&mut self) {
+        let in_progress = !self.context.active_callers.is_empty()
+            && !matches!(self.state, ContestState::Idle | ContestState::QsoComplete);
+        let note = if in_progress {
+            self.pending_note.get_or_insert_with(String::new).clone()
+        } else {
+            let Some(index) = self.session_stats.qsos.len().checked_sub(1) else {
+                return;
+            };
+            let note = self.session_stats.qsos[index].note.clone();
+            self.session_stats.flag_qso(index, note.clone());
+            note
+        };
+        self.note_for_current = in_progress;
+        self.note_input = Some(note);
+        self.announce(tr("QSO flagged").to_string());
+    }
+
+    /// Close the note box; the QSO stays flagged either way
+    pub fn finish_qso_note(&mut self, save: bool) {
+        let Some(note) = self.note_input.take() else {
+            return;
+        };
+        if !save {
+            return;
+        }
+        let note = note.trim().to_string();
+        if self.note_for_current {
+            // The QSO may have been abandoned while the box was open
+            if self.pending_note.is_some() {
+                self.pending_note = Some(note);
+            }
+        } else if let Some(index) = self.session_stats.qsos.len().checked_sub(1) {
+            self.session_stats.flag_qso(index, note);
+            self.autosave_session();
+        }
+    }
+
+    /// Count or stop counting a logged QSO, e.g. from the stats window
+    pub fn set_qso_counted(&mut self, index: usize, counted: bool) {
+        self.session_stats.set_counted(index, counted);
+        self.score.recount(&self.session_stats);
+        self.refresh_char_focus();
+        self.autosave_session();
+    }
+
+    /// F8 after the caller's exchange; with `element_only`, ask for just
+    /// the element in the focused box instead of the whole exchange
+    fn handle_agn_request(&mut self, element_only: bool) {
+        // Only works when receiving exchange
+        if !matches!(
+            self.state,
+            ContestState::StationTransmitting {
+                tx_type: StationTxType::SendingExchange
+            }
+        ) {
+            return;
+        }
+
+        // Stop any current station audio
+        self.send_audio(AudioCommand::StopAll);
+
+        // Send the AGN message, or the request for one element
+        let element = match self.current_field {
+            InputField::Exchange(index) if element_only => Some(index),
+            _ => None,
+        };
+        let message = match element {
+            Some(index) => {
+                let contest_settings = self
+                    .settings
+                    .contest
+                    .settings_for_mut(self.contest.as_ref());
+                let request = self.contest.repeat_request(index, contest_settings);
+                let label = self
+                    .contest
+                    .exchange_fields()
+                    .get(index)
+                    .map_or_else(|| request.clone(), |field| field.label.to_string());
+                self.context.repeat_requests.push(label);
+                request
+            }
+            None => {
+                self.context.repeat_requests.push("Exchange".to_string());
+                self.settings.user.agn_message.clone()
+            }
+        };
+        self.context.element_repeat = element;
+        let segments = vec![MessageSegment {
+            content: message,
+            segment_type: MessageSegmentType::Agn,
+        }];
+        self.transmit(segments, self.settings.user.wpm);
+
+        self.state = ContestState::UserTransmitting {
+            tx_type: UserTxType::Agn,
+        };
+        self.used_agn_exchange = true;
+    }
+
+    /// F8 while copying the call; with `element_only`, send "CALL?"
+    fn handle_callsign_agn_request(&mut self, element_only: bool) {
+        // Works when stations are calling, or during call correction
+        let allowed_in_correction = self.context.correction_in_progress
+            && matches!(
+                self.state,
+                ContestState::StationTransmitting {
+                    tx_type: StationTxType::Correction
+                }
+            );
+        if self.state != ContestState::StationsCalling && !allowed_in_correction {
+            return;
+        }
+        self.finish_double(DoubleOutcome::AskedAgain);
+
+        // Stop any current station audio
+        self.send_audio(AudioCommand::StopAll);
+
+        // Send the AGN message
+        let agn_message = if element_only {
+            "CALL?".to_string()
+        } else {
+            self.settings.user.agn_message.clone()
+        };
+        let segments = vec![MessageSegment {
+            content: agn_message,
+            segment_type: MessageSegmentType::Agn,
+        }];
+        self.transmit(segments, self.settings.user.wpm);
+        self.context.repeat_requests.push("Call".to_string());
+
+        // Mark that we expect the caller to repeat their callsign
+        self.context.expecting_callsign_repeat = true;
+        self.context.allow_callsign_repeat_ack = false;
+
+        self.state = ContestState::UserTransmitting {
+            tx_type: UserTxType::Agn,
+        };
+        self.used_agn_callsign = true;
+    }
+
+    fn on_audio_event(&mut self, event: AudioEvent) {
+        match event {
+            AudioEvent::StationComplete(SANDBOX_STATION_ID) => {
+                self.sandbox.finish_current();
+                self.play_next_sandbox_item();
+            }
+            AudioEvent::StationComplete(id) => {
+                self.caller_manager.station_audio_complete(id);
+                self.on_station_audio_complete(id);
+            }
+            AudioEvent::UserMessageComplete => {
+                self.on_user_message_complete();
+            }
+            AudioEvent::UserSegmentComplete(segment_type) => {
+                // Update QsoProgress based on which segment completed
+                match segment_type {
+                    MessageSegmentType::TheirCallsign => {
+                        self.context.progress.sent_their_call = true;
+                        if let Some(call) = self.calls_sending.pop_front() {
+                            self.calls_sent.push(call);
+                        }
+                    }
+                    MessageSegmentType::OurCallsign => {
+                        self.context.progress.sent_our_call = true;
+                    }
+                    // The exchange that ends a QSO we answered goes out
+                    // after it's logged
+                    MessageSegmentType::OurExchange if self.state == ContestState::QsoComplete => {}
+                    MessageSegmentType::OurExchange => {
+                        self.context.progress.sent_our_exchange = true;
+                        self.exchange_sent_to = self
+                            .context
+                            .get_current_caller()
+                            .map(|caller| caller.params.callsign.clone());
+                    }
+                    MessageSegmentType::Cq
+                    | MessageSegmentType::Tu
+                    | MessageSegmentType::Agn
+                    | MessageSegmentType::Macro => {}
+                }
+            }
+        }
+    }
+
+    fn on_station_audio_complete(&mut self, id: crate::messages::StationId) {
+        let ContestState::StationTransmitting { tx_type } = self.state else {
+            return;
+        };
+        // Only the station we're working moves the QSO along; lids calling
+        // over it don't
+        if self.context.get_current_caller().map(|c| c.params.id) != Some(id) {
+            return;
+        }
+        if tx_type == StationTxType::SendingExchange {
+            self.exchange_ends_at = None;
+        }
+        let announcement = match tx_type {
+            StationTxType::RequestingAgn => "Station asked for a repeat",
+            StationTxType::RequestingFill => "Station asked for a fill",
+            StationTxType::Correction => "Station sent a correction",
+            StationTxType::SendingExchange => "Exchange received",
+        };
+        self.apply_transition(self.state.on_station_message_complete());
+        self.announce(tr(announcement).to_string());
+    }
+
+    /// Carry out a step of the state machine in `state.rs`
+    fn apply_transition(&mut self, transition: Transition) {
+        match transition {
+            Transition::Stay => {}
+            Transition::To(state) => self.state = state,
+            Transition::AwaitStation => {
+                let timing = self.simulation().timing.clone();
+                let gap = timing.reply_gap_ms(&mut self.rng);
+                self.context.set_wait(gap, self.now);
+                self.state = ContestState::WaitingForStation;
+            }
+            Transition::StationReplies => self.handle_station_response(),
+            Transition::QsoOver => self.try_spawn_tail_ender(),
+        }
+    }
+
+    /// Escape while sending: cut off our message but keep the QSO going.
+    /// Segments that finished before the abort already updated QsoProgress,
+    /// so the caller reacts to whatever part they actually heard.
+    fn abort_user_message(&mut self) {
+        match self.state {
+            ContestState::CallingCq => {
+                // Not even a tailgater answers
+                self.send_audio(AudioCommand::StopAll);
+                self.caller_manager.on_cq_restart(self.now, &mut self.rng);
+                self.context.reset();
+            }
+            ContestState::UserTransmitting { tx_type } => {
+                self.send_audio(AudioCommand::StopUserMessage);
+                if tx_type != UserTxType::Tu {
+                    self.context.progress.user_tx_aborted = true;
+                }
+            }
+            _ => {
+                self.send_audio(AudioCommand::StopAll);
+            }
+        }
+        self.apply_transition(self.state.on_user_abort());
+    }
+
+    fn on_user_message_complete(&mut self) {
+        self.context.progress.user_tx_aborted = false;
+        match self.state {
+            ContestState::CallingCq => self.last_cq_finished = Some(self.now),
+            ContestState::UserTransmitting { tx_type } if tx_type != UserTxType::Tu => {
+                self.start_lid_callers();
+            }
+            _ => {}
+        }
+        let callers_waiting = !self.context.active_callers.is_empty();
+        self.apply_transition(self.state.on_user_message_complete(callers_waiting));
+    }
+
+    /// Callers have started answering: a new QSO begins with this many calling
+    fn start_qso(&mut self, calling: usize) {
+        self.qso_start = Some(QsoStart {
+            at: self.now,
+            calling,
+            copied_after: None,
+        });
+    }
+
+    /// We came back with a call; the first time is the time to copy
+    fn mark_call_copied(&mut self) {
+        if let Some(start) = &mut self.qso_start {
+            start
+                .copied_after
+                .get_or_insert(self.now.saturating_duration_since(start.at));
+        }
+    }
+
+    /// Give the skimmer a caller to spot, and count them in a wide pileup
+    fn spot(&mut self, params: &StationParams) {
+        self.skimmer.hear(
+            params,
+            self.now,
+            &self.settings.simulation.skimmer,
+            &mut self.rng,
+        );
+        if self.simulation().wide_pileup.enabled {
+            self.session_stats.wide_pileup.hear(&params.callsign);
+        }
+    }
+
+    /// Let lids who don't wait their turn call after our transmission
+    fn start_lid_callers(&mut self) {
+        let Some(working) = self.context.get_current_caller().map(|c| c.params.id) else {
+            return;
+        };
+        for params in self
+            .caller_manager
+            .on_user_transmission_complete(working, &mut self.rng)
+        {
+            self.spot(&params);
+            self.send_audio(AudioCommand::StartStation(params));
+        }
+    }
+
+    /// Try to spawn a tail-ender after TU
+    fn try_spawn_tail_ender(&mut self) {
+        // We've handed the frequency over, so nobody is left to call us
+        if self.role == Role::Answering {
+            self.state = ContestState::Idle;
+            return;
+        }
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let tail_ender = self.caller_manager.try_spawn_tail_ender(
+            self.contest.as_ref(),
+            contest_settings,
+            Some(&self.settings.user.callsign),
+            Some(&self.cty),
+            self.now,
+            &mut self.rng,
+        );
+
+        let Some(params) = tail_ender else {
+            self.state = ContestState::Idle;
+            return;
+        };
+
+        // Prepare the tail-ender
+        let callers = vec![ActiveCaller {
+            params: params.clone(),
+        }];
+
+        // Reset for new QSO
+        self.used_agn_callsign = false;
+        self.used_agn_exchange = false;
+        self.used_f5_callsign = false;
+        self.context.reset();
+        self.context.set_callers(callers);
+        self.start_qso(1);
+
+        // Start tail-ender audio immediately (reaction_delay_ms handles the delay)
+        self.spot(&params);
+        self.send_audio(AudioCommand::StartStation(params));
+        self.state = ContestState::StationsCalling;
+        self.announce_callers();
+    }
+
+    /// Act on the wait set in QsoContext once it runs out
+    fn check_waiting_states(&mut self) {
+        if self.context.wait_elapsed(self.now) {
+            self.apply_transition(self.state.on_wait_elapsed());
+        }
+    }
+
+    /// One element of our exchange for the caller to ask for, if the
+    /// contest has any that get asked for
+    fn pick_fill_request(&mut self) -> Option<FillRequest> {
+        use rand::seq::SliceRandom;
+
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let requests: Vec<FillRequest> = self
+            .contest
+            .fill_requests(contest_settings)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, request)| {
+                Some(FillRequest {
+                    index,
+                    request: request?,
+                })
+            })
+            .collect();
+        requests.choose(&mut self.rng).cloned()
+    }
+
+    /// Format a caller's exchange for sending, possibly using cut numbers
+    fn format_caller_exchange(&mut self, exchange: &Exchange) -> String {
+        use rand::Rng;
+
+        let probability = self
+            .settings
+            .contest
+            .cut_number_probability(self.contest.id(), self.simulation().cut_number_probability);
+        if self.rng.gen::<f32>() < probability {
+            self.contest.format_exchange(&cut_number_exchange(
+                exchange,
+                &self.contest.exchange_fields(),
+            ))
+        } else {
+            self.contest.format_exchange(exchange)
+        }
+    }
+
+    /// Handle station response based on QsoProgress
+    /// Have the station we're working send its exchange, or part of it,
+    /// noting when it should end so logging before then counts as early
+    fn send_caller_exchange(&mut self, params: StationParams) {
+        let duration =
+            message_duration_ms(&params.callsign, params.wpm) + u64::from(params.reaction_delay_ms);
+        self.exchange_ends_at = Some(self.now + Duration::from_millis(duration));
+        self.send_audio(AudioCommand::StartStation(params));
+        self.state = ContestState::StationTransmitting {
+            tx_type: StationTxType::SendingExchange,
+        };
+    }
+
+    fn handle_station_response(&mut self) {
+        use rand::Rng;
+
+        let caller = match self.context.get_current_caller() {
+            Some(c) => c.clone(),
+            None => {
+                // No current caller - have active callers resend their callsign
+                // (e.g. everyone who matched a partial query answers together)
+                self.context.expecting_callsign_repeat = false;
+                self.context.allow_callsign_repeat_ack = false;
+                let params: Vec<StationParams> = self
+                    .context
+                    .active_callers
+                    .iter()
+                    .map(|caller| caller.params.clone())
+                    .collect();
+                for params in params {
+                    self.send_audio(AudioCommand::StartStation(params));
+                }
+                self.state = ContestState::StationsCalling;
+                return;
+            }
+        };
+
+        // If we're expecting a callsign repeat (after partial query or F8), send callsign or "R R"
+        if self.context.expecting_callsign_repeat {
+            let allow_ack = self.context.allow_callsign_repeat_ack;
+            self.context.expecting_callsign_repeat = false;
+            self.context.allow_callsign_repeat_ack = false;
+
+            let message = if allow_ack && self.rng.gen::<bool>() {
+                "R R".to_string()
+            } else {
+                caller.params.callsign.clone()
+            };
+
+            self.send_audio(AudioCommand::StartStation(StationParams {
+                id: caller.params.id,
+                callsign: message,
+                exchange: caller.params.exchange.clone(),
+                frequency_offset_hz: caller.params.frequency_offset_hz,
+                wpm: caller.params.wpm,
+                amplitude: caller.params.amplitude,
+                reaction_delay_ms: 0,
+            }));
+
+            self.state = ContestState::StationsCalling;
+            return;
+        }
+
+        // Asked for one element of their exchange: send just that, twice
+        if let Some(index) = self.context.element_repeat.take() {
+            if let Some(element) = caller.params.exchange.fields.get(index) {
+                self.send_caller_exchange(StationParams {
+                    id: caller.params.id,
+                    callsign: format!("{} {}", element, element),
+                    exchange: caller.params.exchange.clone(),
+                    frequency_offset_hz: caller.params.frequency_offset_hz,
+                    wpm: caller.params.wpm,
+                    amplitude: caller.params.amplitude,
+                    reaction_delay_ms: 0,
+                });
+                return;
+            }
+        }
+
+        // If we're in correction mode, send the correction
+        if self.context.correction_in_progress {
+            let rng = &mut self.rng;
+            // Send callsign once (75%) or twice (25%) for emphasis
+            let message = if rng.gen::<f32>() < 0.75 {
+                caller.params.callsign.clone()
+            } else {
+                format!("{} {}", caller.params.callsign, caller.params.callsign)
+            };
+
+            self.send_audio(AudioCommand::StartStation(StationParams {
+                id: caller.params.id,
+                callsign: message,
+                exchange: caller.params.exchange.clone(),
+                frequency_offset_hz: caller.params.frequency_offset_hz,
+                wpm: caller.params.wpm,
+                amplitude: caller.params.amplitude,
+                reaction_delay_ms: 0,
+            }));
+
+            self.state = ContestState::StationTransmitting {
+                tx_type: StationTxType::Correction,
+            };
+            return;
+        }
+
+        // The CQ station we answered sends its exchange once it has our
+        // call, and asks again if it didn't get it
+        if self.role == Role::Answering {
+            if self.context.progress.sent_our_call {
+                let exchange_str = self.format_caller_exchange(&caller.params.exchange);
+                self.send_caller_exchange(StationParams {
+                    callsign: format!(
+                        "{} {}",
+                        self.settings.user.callsign.trim().to_uppercase(),
+                        exchange_str
+                    ),
+                    reaction_delay_ms: 0,
+                    ..caller.params.clone()
+                });
+                self.context.caller_exchange_sent_once = true;
+            } else {
+                self.send_audio(AudioCommand::StartStation(StationParams {
+                    callsign: "?".to_string(),
+                    reaction_delay_ms: 0,
+                    ..caller.params.clone()
+                }));
+                self.state = ContestState::StationsCalling;
+            }
+            return;
+        }
+
+        // Determine caller response based on what they've heard
+        let response =
+            CallerResponse::from_progress_and_context(&self.context.progress, &self.context);
+
+        match response {
+            CallerResponse::Confused => {
+                self.context.increment_confused_attempt();
+
+                // max_correction_attempts is used for both the correction loop
+                // and the confused loop escape threshold
+                let max_attempts = self
+                    .settings
+                    .simulation
+                    .call_correction
+                    .max_correction_attempts;
+
+                if self.context.confused_attempts > max_attempts {
+                    // Caller gives up waiting and sends callsign + exchange together,
+                    // like an impatient operator in a real contest
+                    let exchange_str = self.format_caller_exchange(&caller.params.exchange);
+                    let message = format!("{} {}", caller.params.callsign, exchange_str);
+
+                    self.send_caller_exchange(StationParams {
+                        id: caller.params.id,
+                        callsign: message,
+                        exchange: caller.params.exchange.clone(),
+                        frequency_offset_hz: caller.params.frequency_offset_hz,
+                        wpm: caller.params.wpm,
+                        amplitude: caller.params.amplitude,
+                        reaction_delay_ms: 0,
+                    });
+
+                    // Advance progress so the QSO can complete
+                    self.context.progress.sent_their_call = true;
+                    self.context.progress.sent_our_exchange = true;
+                    self.context.caller_exchange_sent_once = true;
+                } else {
+                    // Caller didn't hear their callsign - resend it or send "?"
+                    let rng = &mut self.rng;
+                    let message = if rng.gen::<bool>() {
+                        caller.params.callsign.clone()
+                    } else {
+                        "?".to_string()
+                    };
+
+                    self.send_audio(AudioCommand::StartStation(StationParams {
+                        id: caller.params.id,
+                        callsign: message,
+                        exchange: caller.params.exchange.clone(),
+                        frequency_offset_hz: caller.params.frequency_offset_hz,
+                        wpm: caller.params.wpm,
+                        amplitude: caller.params.amplitude,
+                        reaction_delay_ms: 0,
+                    }));
+
+                    self.state = ContestState::StationsCalling;
+                }
+            }
+            CallerResponse::RequestAgn => {
+                // Caller heard their call but not our exchange - request AGN
+                let rng = &mut self.rng;
+                let agn_message = if rng.gen::<bool>() { "AGN" } else { "?" };
+
+                self.send_audio(AudioCommand::StartStation(StationParams {
+                    id: caller.params.id,
+                    callsign: agn_message.to_string(),
+                    exchange: caller.params.exchange.clone(),
+                    frequency_offset_hz: caller.params.frequency_offset_hz,
+                    wpm: caller.params.wpm,
+                    amplitude: caller.params.amplitude,
+                    reaction_delay_ms: 0,
+                }));
+
+                self.state = ContestState::StationTransmitting {
+                    tx_type: StationTxType::RequestingAgn,
+                };
+            }
+            CallerResponse::SendExchange => {
+                // Caller heard everything - send their exchange
+
+                // Only allow a random AGN or fill request before the caller
+                // has sent their exchange once, and never after a fill
+                let allow_random_agn =
+                    !self.context.caller_exchange_sent_once && self.context.fill_request.is_none();
+                let fill = if allow_random_agn
+                    && self.rng.gen::<f32>() < self.simulation().fill_request_probability
+                {
+                    self.pick_fill_request()
+                } else {
+                    None
+                };
+                if let Some(fill) = fill {
+                    // Caller missed one element of our exchange and asks for it
+                    self.send_audio(AudioCommand::StartStation(StationParams {
+                        id: caller.params.id,
+                        callsign: fill.request.clone(),
+                        exchange: caller.params.exchange.clone(),
+                        frequency_offset_hz: caller.params.frequency_offset_hz,
+                        wpm: caller.params.wpm,
+                        amplitude: caller.params.amplitude,
+                        reaction_delay_ms: 0,
+                    }));
+
+                    self.context.fill_request = Some(fill);
+                    self.state = ContestState::StationTransmitting {
+                        tx_type: StationTxType::RequestingFill,
+                    };
+                } else if allow_random_agn
+                    && self.rng.gen::<f32>() < self.simulation().agn_request_probability
+                {
+                    let agn_message = if self.rng.gen::<bool>() { "AGN" } else { "?" };
+
+                    self.send_audio(AudioCommand::StartStation(StationParams {
+                        id: caller.params.id,
+                        callsign: agn_message.to_string(),
+                        exchange: caller.params.exchange.clone(),
+                        frequency_offset_hz: caller.params.frequency_offset_hz,
+                        wpm: caller.params.wpm,
+                        amplitude: caller.params.amplitude,
+                        reaction_delay_ms: 0,
+                    }));
+
+                    self.state = ContestState::StationTransmitting {
+                        tx_type: StationTxType::RequestingAgn,
+                    };
+                } else {
+                    // Normal flow - send their exchange
+                    let mut exchange_str = self.format_caller_exchange(&caller.params.exchange);
+                    if self.rng.gen::<f32>() < self.simulation().courtesy_probability {
+                        exchange_str = with_courtesy_word(&exchange_str, &mut self.rng);
+                    }
+                    if self.rng.gen::<f32>() < self.simulation().lids.wrong_call_probability {
+                        // Careless caller sends our call wrong first
+                        exchange_str = format!(
+                            "{} {}",
+                            confused_callsign(self.settings.user.callsign.trim(), &mut self.rng),
+                            exchange_str
+                        );
+                    } else if self.tutorial.is_none() {
+                        exchange_str = framed_exchange(
+                            &self.settings.contest.caller_format(self.contest.id()),
+                            self.settings.user.callsign.trim(),
+                            &caller.params.callsign,
+                            &exchange_str,
+                            &mut self.rng,
+                        );
+                    }
+
+                    self.send_caller_exchange(StationParams {
+                        id: caller.params.id,
+                        callsign: exchange_str,
+                        exchange: caller.params.exchange.clone(),
+                        frequency_offset_hz: caller.params.frequency_offset_hz,
+                        wpm: caller.params.wpm,
+                        amplitude: caller.params.amplitude,
+                        reaction_delay_ms: 0,
+                    });
+                    self.context.caller_exchange_sent_once = true;
+
+                    if let Some(lid) = self
+                        .caller_manager
+                        .out_of_turn_caller(caller.params.id, &mut self.rng)
+                    {
+                        self.spot(&lid);
+                        self.send_audio(AudioCommand::StartStation(lid));
+                    }
+                }
+            }
+            CallerResponse::Wait => {
+                // Caller waits silently for the user's exchange.
+                self.context.clear_wait();
+                self.state = ContestState::StationsCalling;
+            }
+        }
+    }
+
+    /// Speed and pitch offset of the caller being worked, for the live
+    /// readout
+    pub fn current_caller_speed(&self) -> Option<(u8, f32)> {
+        self.context
+            .get_current_caller()
+            .map(|caller| (caller.params.wpm, caller.params.frequency_offset_hz))
+    }
+
+    /// Stations waiting in the pileup, for the optional depth indicator
+    pub fn pileup_depth(&self) -> usize {
+        self.caller_manager.pileup_depth()
+    }
+
+    /// Let a background station send a QRL?, tune up or call CQ nearby.
+    /// They aren't callers, so nothing they send moves the QSO along.
+    fn maybe_play_ambience(&mut self) {
+        if self.settings_error.is_some() || self.drilling {
+            return;
+        }
+        let running = self.state != ContestState::Idle;
+        let ambience = self.simulation().ambience.clone();
+        let Some(event) = self
+            .ambience
+            .poll(self.now, &ambience, running, &mut self.rng)
+        else {
+            return;
+        };
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        if let Some(params) = self.caller_manager.ambience_station(
+            event,
+            self.contest.as_ref(),
+            contest_settings,
+            &mut self.rng,
+        ) {
+            self.send_audio(AudioCommand::StartStation(params));
+        }
+    }
+
+    /// Someone turning up on our run frequency, CQing over us, or giving up
+    /// and moving off
+    fn run_frequency_fight(&mut self) {
+        if self.settings_error.is_some() || self.drilling {
+            return;
+        }
+        let now = self.now;
+        let settings = self.simulation().frequency_fight.clone();
+        let Some(event) = self.fight.poll(now, &settings, &mut self.rng) else {
+            return;
+        };
+        match event {
+            FightEvent::Arrives => {
+                let contest_settings = self
+                    .settings
+                    .contest
+                    .settings_for_mut(self.contest.as_ref());
+                let Some(params) = self.caller_manager.squatter_station(
+                    self.contest.as_ref(),
+                    contest_settings,
+                    &mut self.rng,
+                ) else {
+                    return;
+                };
+                let cq_duration =
+                    Duration::from_millis(message_duration_ms(&params.callsign, params.wpm));
+                self.fight.squat(now, params, cq_duration, &mut self.rng);
+                self.session_stats.fights.fights += 1;
+                self.caller_manager.set_heard_share(settings.callers_heard);
+                self.announce(tr("Someone is calling CQ on your frequency").to_string());
+            }
+            FightEvent::Cq => {
+                if let Some(squatter) = self.fight.squatter() {
+                    let params = squatter.params.clone();
+                    self.send_audio(AudioCommand::StartStation(params));
+                }
+            }
+            FightEvent::Leaves { fought } => {
+                self.session_stats.fights.fought += fought;
+                self.caller_manager.set_heard_share(1.0);
+                self.announce(tr("The frequency is clear again").to_string());
+            }
+        }
+    }
+
+    fn qsy_pending(&self) -> bool {
+        self.qsy_until.is_some_and(|until| self.now < until)
+    }
+
+    /// Give up the run frequency and look for a clear one: callers and
+    /// whoever was CQing over us stay behind, and it takes a while before
+    /// we can CQ again
+    pub fn qsy(&mut self) {
+        if self.qsy_pending() || self.role == Role::Answering {
+            return;
+        }
+        let now = self.now;
+        self.send_audio(AudioCommand::StopAll);
+        self.caller_manager.on_qsy();
+        self.caller_manager.set_heard_share(1.0);
+        self.context.reset();
+        self.callsign_input.clear();
+        self.clear_exchange_inputs();
+        self.current_field = InputField::Callsign;
+        self.state = ContestState::Idle;
+        self.cq_ends_at = None;
+
+        if let Some(fought) = self.fight.leave_frequency(now) {
+            self.session_stats.fights.fought += fought;
+        }
+        let search = Duration::from_secs(u64::from(self.simulation().frequency_fight.qsy_secs));
+        let fights = &mut self.session_stats.fights;
+        fights.qsys += 1;
+        fights.qsy_time += search;
+        self.qsy_until = Some(now + search);
+        self.announce(tr_args(
+            "QSY: {} s to find a clear frequency",
+            &[&search.as_secs().to_string()],
+        ));
+    }
+
+    /// Count callers who gave up, and let them be heard leaving if the
+    /// settings say so. They're heard after our CQ if one is going out.
+    fn handle_given_up_callers(&mut self) {
+        use rand::Rng;
+
+        let given_up = self.caller_manager.take_given_up();
+        if given_up.is_empty() {
+            return;
+        }
+        self.session_stats.log_callers_lost(given_up.len());
+
+        let behavior = self.settings.simulation.pileup.give_up;
+        let after_cq_ms = self
+            .cq_ends_at
+            .map(|ends_at| ends_at.saturating_duration_since(self.now).as_millis() as u32)
+            .unwrap_or(0);
+        for mut params in given_up {
+            let Some(message) = give_up_message(behavior, &params.callsign) else {
+                continue;
+            };
+            params.callsign = message;
+            params.reaction_delay_ms = after_cq_ms + self.rng.gen_range(100..400);
+            self.send_audio(AudioCommand::StartStation(params));
+        }
+    }
+
+    fn maybe_spawn_callers(&mut self) {
+        if self.settings_error.is_some() {
+            return;
+        }
+
+        let cq_remaining_ms = match self.state {
+            ContestState::CallingCq => {
+                // Pick callers near the end of the CQ so tailgaters can
+                // start before it finishes; everyone else is delayed past it
+                if !self.context.active_callers.is_empty() {
+                    return;
+                }
+                let Some(ends_at) = self.cq_ends_at else {
+                    return;
+                };
+                let remaining = ends_at.saturating_duration_since(self.now);
+                let tailgate_window = self.settings.simulation.reaction_time.tailgate_max_ms;
+                if remaining.as_millis() > tailgate_window as u128 {
+                    return;
+                }
+                remaining.as_millis() as u32 + self.simulation().timing.post_cq_gap_ms
+            }
+            ContestState::WaitingForCallers => {
+                // Wait a bit after CQ before callers respond
+                // (callers also have individual reaction_delay_ms applied in audio)
+                if let Some(finished) = self.last_cq_finished {
+                    let gap_ms = self.simulation().timing.post_cq_gap_ms;
+                    if self.now.saturating_duration_since(finished).as_millis() < gap_ms as u128 {
+                        return;
+                    }
+                }
+                0
+            }
+            _ => return,
+        };
+
+        // Get callers from the persistent queue
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        let mut responding = self.caller_manager.on_cq_complete(
+            self.contest.as_ref(),
+            contest_settings,
+            Some(&self.settings.user.callsign),
+            Some(&self.cty),
+            cq_remaining_ms,
+            self.now,
+            &mut self.rng,
+        );
+        let double = make_double(
+            &mut responding,
+            self.simulation().double_probability,
+            &mut self.rng,
+        );
+
+        if !responding.is_empty() {
+            for params in &responding {
+                self.spot(params);
+            }
+            let callers: Vec<ActiveCaller> = responding
+                .into_iter()
+                .map(|params| {
+                    let mut audio = params.clone();
+                    audio.callsign = calling_message(
+                        &params.callsign,
+                        self.simulation().call_twice_probability,
+                        &mut self.rng,
+                    );
+                    self.send_audio(AudioCommand::StartStation(audio));
+                    ActiveCaller { params }
+                })
+                .collect();
+
+            self.start_qso(callers.len());
+            self.context.set_callers(callers);
+            self.context.double = double;
+            if self.state == ContestState::WaitingForCallers {
+                self.state = ContestState::StationsCalling;
+            }
+            self.announce_callers();
+        }
+    }
+
+    /// Act on one key press, exactly as the keyboard handler would
+    fn handle_key(&mut self, key: Key, shift: bool) {
+        let settings_valid = self.settings_error.is_none();
+        match key {
+            // F1 - Send CQ (always available)
+            Key::F1 => {
+                if settings_valid {
+                    self.editing_last_qso = false;
+                    self.send_audio(AudioCommand::StopAll);
+                    self.caller_manager.on_cq_restart(self.now, &mut self.rng);
+                    self.callsign_input.clear();
+                    self.clear_exchange_inputs();
+                    self.current_field = InputField::Callsign;
+                    self.send_cq();
+                }
+            }
+
+            // Shift+F2 - Send the element the caller asked for
+            Key::F2 if shift => {
+                self.handle_fill();
+            }
+
+            // F2 - Send Exchange only (available in any state with active caller)
+            Key::F2 => {
+                self.handle_f2_exchange();
+            }
+
+            // F3 - Send TU
+            Key::F3 => {
+                self.send_tu();
+                self.state = ContestState::UserTransmitting {
+                    tx_type: UserTxType::Tu,
+                };
+            }
+
+            // F5 - Send his call only (available in any state with active caller)
+            Key::F5 => {
+                self.handle_f5_his_call();
+            }
+
+            // F8 - Request AGN; Shift+F8 asks for just the element in the
+            // focused box ("CALL?", "NR?")
+            Key::F8 => {
+                if self.context.wants_callsign_repeat()
+                    || self.current_field == InputField::Callsign
+                {
+                    self.handle_callsign_agn_request(shift);
+                } else {
+                    self.handle_agn_request(shift);
+                }
+            }
+
+            // F12 - Wipe
+            Key::F12 => {
+                self.editing_last_qso = false;
+                self.callsign_input.clear();
+                self.clear_exchange_inputs();
+                self.current_field = InputField::Callsign;
+            }
+
+            // Up/Down arrows - WPM adjustment
+            Key::ArrowUp => {
+                if self.settings.user.wpm < 50 {
+                    self.settings.user.wpm += 1;
+                    self.settings_changed = true;
+                }
+            }
+            Key::ArrowDown => {
+                if self.settings.user.wpm > 15 {
+                    self.settings.user.wpm -= 1;
+                    self.settings_changed = true;
+                }
+            }
+
+            // Enter - Save the reopened QSO
+            Key::Enter if self.editing_last_qso => {
+                self.save_edited_qso();
+            }
+
+            // Enter - Submit current field
+            Key::Enter => match self.current_field {
+                InputField::Callsign => {
+                    if self.callsign_input.trim().is_empty() {
+                        // Empty callsign field - act like F1
+                        self.send_audio(AudioCommand::StopAll);
+                        self.caller_manager.on_cq_restart(self.now, &mut self.rng);
+                        self.callsign_input.clear();
+                        self.clear_exchange_inputs();
+                        self.current_field = InputField::Callsign;
+                        self.send_cq();
+                    } else {
+                        self.handle_callsign_submit();
+                    }
+                }
+                InputField::Exchange(_) => {
+                    self.handle_exchange_submit();
+                }
+            },
+
+            // Escape - Leave the reopened QSO as it was
+            Key::Escape if self.editing_last_qso => {
+                self.cancel_qso_edit();
+            }
+
+            // Escape - Stop transmission
+            Key::Escape => {
+                self.abort_user_message();
+            }
+
+            // Space - advance exchange field (contest logger convention)
+            // Tab - Switch fields
+            Key::Space | Key::Tab => {
+                if shift {
+                    self.advance_field_backward();
+                } else {
+                    self.advance_field_forward();
+                }
+            }
+
+            // F4/F6/F7/F9/F10/F11 - User message macros
+            _ => {
+                if let Some(index) = MACRO_SLOT_KEYS.iter().position(|k| *k == key) {
+                    self.send_macro(MACRO_KEYS[index]);
+                }
+            }
+        }
+    }
+
+    /// Put edited settings into effect and save them; false if nothing
+    /// had changed
+    pub fn apply_settings_changes(&mut self) -> bool {
+        if !self.settings_changed {
+            return false;
+        }
+        let active_id = self.settings.contest.active_contest_id.clone();
+        let default_descriptor = self
+            .contest_registry
+            .first()
+            .expect("No contests registered. Add at least one contest file.");
+        let active_descriptor = if let Some(entry) = self
+            .contest_registry
+            .iter()
+            .find(|entry| entry.id == active_id)
+        {
+            entry
+        } else {
+            self.settings.contest.active_contest_id = default_descriptor.id.to_string();
+            default_descriptor
+        };
+
+        let contest_changed = active_descriptor.id != self.contest.id();
+        if contest_changed {
+            self.contest = (active_descriptor.factory)();
+            self.role = Role::Running;
+            self.callsign_input.clear();
+        }
+
+        // Some contests build their exchange from the settings
+        let labels_before: Vec<&str> = self
+            .contest
+            .exchange_fields()
+            .iter()
+            .map(|field| field.label)
+            .collect();
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        self.contest.configure(contest_settings);
+        let labels_after: Vec<&str> = self
+            .contest
+            .exchange_fields()
+            .iter()
+            .map(|field| field.label)
+            .collect();
+        if contest_changed || labels_before != labels_after {
+            self.reset_exchange_inputs();
+            self.clear_exchange_inputs();
+            self.current_field = InputField::Callsign;
+        }
+
+        let contest_settings = self
+            .settings
+            .contest
+            .settings_for_mut(self.contest.as_ref());
+        self.settings_error = self.contest.validate_settings(contest_settings).err();
+        let callsign_source = Self::build_callsign_source(
+            self.contest.as_ref(),
+            contest_settings,
+            &self.settings.simulation.synthetic_calls,
+            &self.cty,
+        );
+        Self::report_callsign_fallback(&mut self.notifications, callsign_source.as_ref());
+        self.caller_manager.update_callsigns(callsign_source);
+        self.watch_contest_files();
+
+        self.caller_manager
+            .update_settings(self.simulation().clone());
+        self.sync_density();
+
+        self.send_audio(AudioCommand::UpdateSettings(self.settings.audio.clone()));
+
+        if let Err(e) = self.settings.save() {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to save settings: {}", e);
+            self.notifications
+                .error(tr_args("Couldn't save settings: {}", &[&e.to_string()]));
+        }
+
+        self.settings_changed = false;
+        true
+    }
+
+    /// The simulation clock's time
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Advance the simulation by one event
+    pub fn tick(&mut self, event: Event) {
+        match event {
+            Event::Elapsed { now } => {
+                // The clock never runs backwards
+                self.now = self.now.max(now);
+                self.run_timers();
+            }
+            Event::Key { key, shift } => self.handle_key(key, shift),
+        }
+    }
+
+    /// Audio events, new callers and wait timers
+    fn run_timers(&mut self) {
+        self.flush_audio();
+
+        // Process audio events
+        while let Ok(event) = self.event_rx.try_recv() {
+            self.on_audio_event(event);
+        }
+
+        // Maybe spawn callers
+        self.maybe_spawn_callers();
+
+        // Maybe put something else on frequency
+        self.maybe_play_ambience();
+        self.run_frequency_fight();
+
+        // Callers who ran out of patience
+        self.handle_given_up_callers();
+
+        self.skimmer.update(self.now);
+
+        // Check waiting states
+        self.check_waiting_states();
+
+        self.session_stats.timing.observe(self.state, self.now);
+    }
+
+    /// Stop the session where it is while the operator is away: audio
+    /// off, callers cleared, back to Idle
+    pub fn pause(&mut self) {
+        self.send_audio(AudioCommand::StopAll);
+        self.caller_manager.on_cq_restart(self.now, &mut self.rng);
+        if let Some(fought) = self.fight.leave_frequency(self.now) {
+            self.session_stats.fights.fought += fought;
+            self.caller_manager.set_heard_share(1.0);
+        }
+        self.context.reset();
+        self.state = ContestState::Idle;
+        self.session_stats.timing.observe(self.state, self.now);
+    }
+
+    /// Carry on after a pause, with the time `away` taken out of the rate
+    /// and the cycle timing
+    pub fn resume(&mut self, away: Duration) {
+        self.score.start_time = self.score.start_time.map(|start| start + away);
+        self.session_stats.timing.skip_gap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_and_audio_events_drive_the_cq_cycle() {
+        let (mut engine, cmd_rx, event_tx) =
+            Engine::new(AppSettings::default(), None, Instant::now(), 1);
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::CallingCq);
+        assert!(cmd_rx
+            .try_iter()
+            .any(|cmd| matches!(cmd, AudioCommand::PlayUserMessageSegmented { .. })));
+
+        // Nothing moves until the audio says the CQ is over
+        engine.tick(Event::Elapsed { now: engine.now() });
+        assert_eq!(engine.state, ContestState::CallingCq);
+        event_tx.send(AudioEvent::UserMessageComplete).unwrap();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        assert!(matches!(
+            engine.state,
+            ContestState::WaitingForCallers | ContestState::StationsCalling
+        ));
+
+        // Escape cuts a CQ short
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        engine.tick(Event::Key {
+            key: Key::Escape,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::Idle);
+        assert!(cmd_rx
+            .try_iter()
+            .any(|cmd| matches!(cmd, AudioCommand::StopAll)));
+    }
+
+    #[test]
+    fn qsy_rule_alternates_running_and_answering_cqs() {
+        let mut settings = AppSettings::default();
+        settings.contest.active_contest_id = "nasprint".to_string();
+        let (mut engine, cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        assert!(engine.contest.qsy_after_run());
+
+        // Log the QSO with the one station on frequency, copied right
+        let log_qso = |engine: &mut Engine| {
+            let caller = engine.context.active_callers[0].params.clone();
+            engine.callsign_input = caller.callsign.clone();
+            engine.exchange_inputs = caller.exchange.fields.clone();
+            engine.current_field = InputField::Exchange(0);
+            engine.state = ContestState::StationTransmitting {
+                tx_type: StationTxType::SendingExchange,
+            };
+            engine.tick(Event::Key {
+                key: Key::Enter,
+                shift: false,
+            });
+        };
+
+        // Someone answered our CQ: once they're worked the frequency is theirs
+        let contest_settings = engine.contest.default_settings();
+        let caller = engine
+            .caller_manager
+            .cq_station(
+                engine.contest.as_ref(),
+                &contest_settings,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        engine
+            .context
+            .set_callers(vec![ActiveCaller { params: caller }]);
+        log_qso(&mut engine);
+        assert_eq!(engine.role, Role::Answering);
+        event_tx.send(AudioEvent::UserMessageComplete).unwrap();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        assert_eq!(engine.state, ContestState::Idle);
+        assert_eq!(engine.get_status().1, StatusColor::Orange);
+
+        // F1 now finds someone else's CQ instead of calling one
+        cmd_rx.try_iter().count();
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::StationsCalling);
+        assert_eq!(engine.context.active_callers.len(), 1);
+        let cq = cmd_rx.try_iter().find_map(|cmd| match cmd {
+            AudioCommand::StartStation(params) => Some(params.callsign),
+            _ => None,
+        });
+        let call = &engine.context.active_callers[0].params.callsign;
+        assert_eq!(cq, Some(format!("CQ NA {}", call)));
+
+        // Working them leaves us holding their frequency
+        log_qso(&mut engine);
+        assert_eq!(engine.role, Role::Running);
+        assert_eq!(engine.session_stats.qsos.len(), 2);
+    }
+
+    #[test]
+    fn answering_a_cq_sends_our_call_then_our_exchange() {
+        let mut settings = AppSettings::default();
+        settings.contest.active_contest_id = "nasprint".to_string();
+        let (mut engine, cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        engine.role = Role::Answering;
+        let press = |engine: &mut Engine, key| engine.tick(Event::Key { key, shift: false });
+
+        // Find a CQ and answer it with the call copied right
+        press(&mut engine, Key::F1);
+        let cq = engine.context.active_callers[0].params.clone();
+        engine.callsign_input = cq.callsign.clone();
+        press(&mut engine, Key::Enter);
+        assert_eq!(
+            engine.state,
+            ContestState::UserTransmitting {
+                tx_type: UserTxType::OurCallsign
+            }
+        );
+        event_tx
+            .send(AudioEvent::UserSegmentComplete(
+                MessageSegmentType::OurCallsign,
+            ))
+            .unwrap();
+        event_tx.send(AudioEvent::UserMessageComplete).unwrap();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        engine.tick(Event::Elapsed {
+            now: engine.now() + Duration::from_secs(5),
+        });
+
+        // They send their exchange, then we log and send ours
+        assert_eq!(
+            engine.state,
+            ContestState::StationTransmitting {
+                tx_type: StationTxType::SendingExchange
+            }
+        );
+        event_tx.send(AudioEvent::StationComplete(cq.id)).unwrap();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        engine.exchange_inputs = cq.exchange.fields.clone();
+        engine.current_field = InputField::Exchange(0);
+        press(&mut engine, Key::Enter);
+        assert!(engine.session_stats.qsos[0].is_correct());
+
+        let sent: Vec<(&str, String)> = cmd_rx
+            .try_iter()
+            .filter_map(|cmd| match cmd {
+                AudioCommand::PlayUserMessageSegmented { segments, .. } => {
+                    let [segment] = segments.as_slice() else {
+                        panic!("Expected one segment: {:?}", segments);
+                    };
+                    let who = match segment.segment_type {
+                        MessageSegmentType::OurCallsign => "our call",
+                        MessageSegmentType::OurExchange => "our exchange",
+                        other => panic!("Unexpected {:?}", other),
+                    };
+                    Some((who, segment.content.clone()))
+                }
+                AudioCommand::StartStation(params) if params.id == cq.id => {
+                    Some(("them", params.callsign))
+                }
+                _ => None,
+            })
+            .collect();
+        let our_call = engine.settings.user.callsign.trim().to_uppercase();
+        let order: Vec<&str> = sent.iter().map(|(who, _)| *who).collect();
+        assert_eq!(order, ["them", "our call", "them", "our exchange"]);
+        assert!(sent[0].1.starts_with("CQ "));
+        assert_eq!(sent[1].1, our_call);
+        assert!(sent[2].1.starts_with(&format!("{} ", our_call)));
+    }
+
+    #[test]
+    fn logging_before_the_exchange_ends_counts_as_early() {
+        let (mut engine, _cmd_rx, event_tx) =
+            Engine::new(AppSettings::default(), None, Instant::now(), 1);
+        let contest_settings = engine.contest.default_settings();
+        let caller = engine
+            .caller_manager
+            .cq_station(
+                engine.contest.as_ref(),
+                &contest_settings,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+
+        // The caller starts their exchange and Enter goes down at once
+        let log_qso = |engine: &mut Engine, heard_it_all: bool| {
+            engine.context.set_callers(vec![ActiveCaller {
+                params: caller.clone(),
+            }]);
+            engine.send_caller_exchange(StationParams {
+                callsign: "5NN 14 ".repeat(3),
+                ..caller.clone()
+            });
+            if heard_it_all {
+                event_tx
+                    .send(AudioEvent::StationComplete(caller.id))
+                    .unwrap();
+                engine.tick(Event::Elapsed { now: engine.now() });
+            }
+            engine.callsign_input = caller.callsign.clone();
+            engine.exchange_inputs = caller.exchange.fields.clone();
+            engine.current_field = InputField::Exchange(0);
+            engine.tick(Event::Key {
+                key: Key::Enter,
+                shift: false,
+            });
+            engine.session_stats.qsos.last().unwrap().logged_early
+        };
+
+        assert!(log_qso(&mut engine, false));
+        assert!(engine
+            .notifications
+            .entries()
+            .iter()
+            .any(|n| n.severity == crate::notifications::Severity::Warning));
+        assert!(!log_qso(&mut engine, true));
+        let analysis = engine.session_stats.analyze();
+        assert_eq!((analysis.early_count, analysis.early_correct_count), (1, 1));
+    }
+
+    #[test]
+    fn strict_mode_logs_only_a_finished_and_filled_in_exchange() {
+        let mut settings = AppSettings::default();
+        settings.user.strict_logging = true;
+        let (mut engine, _cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        let contest_settings = engine.contest.default_settings();
+        let caller = engine
+            .caller_manager
+            .cq_station(
+                engine.contest.as_ref(),
+                &contest_settings,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        engine.context.set_callers(vec![ActiveCaller {
+            params: caller.clone(),
+        }]);
+        engine.send_caller_exchange(StationParams {
+            callsign: "5NN 14 ".repeat(3),
+            ..caller.clone()
+        });
+        engine.callsign_input = caller.callsign.clone();
+        engine.exchange_inputs = caller.exchange.fields.clone();
+        engine.current_field = InputField::Exchange(0);
+        let enter = |engine: &mut Engine| {
+            engine.tick(Event::Key {
+                key: Key::Enter,
+                shift: false,
+            })
+        };
+
+        // Still sending
+        enter(&mut engine);
+        assert!(engine.session_stats.qsos.is_empty());
+        assert!(engine.entry_error.is_some());
+
+        // Finished, but a box is empty
+        event_tx
+            .send(AudioEvent::StationComplete(caller.id))
+            .unwrap();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        engine.exchange_inputs[0].clear();
+        enter(&mut engine);
+        assert!(engine.session_stats.qsos.is_empty());
+        let label = engine.contest.exchange_fields()[0].label;
+        assert!(engine.entry_error.as_ref().unwrap().contains(label));
+
+        engine.exchange_inputs = caller.exchange.fields.clone();
+        enter(&mut engine);
+        assert_eq!(engine.session_stats.qsos.len(), 1);
+        assert!(!engine.session_stats.qsos[0].logged_early);
+        assert_eq!(engine.entry_error, None);
+    }
+
+    #[test]
+    fn points_can_require_sending_the_right_call() {
+        let mut settings = AppSettings::default();
+        settings.simulation.require_sent_call = true;
+        let (mut engine, _cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        let contest_settings = engine.contest.default_settings();
+        let caller = engine
+            .caller_manager
+            .cq_station(
+                engine.contest.as_ref(),
+                &contest_settings,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+
+        // Send `sent` as their call, then log them copied right
+        let work = |engine: &mut Engine, sent: &str| {
+            engine.context.set_callers(vec![ActiveCaller {
+                params: caller.clone(),
+            }]);
+            engine.transmit(
+                vec![MessageSegment {
+                    content: sent.to_string(),
+                    segment_type: MessageSegmentType::TheirCallsign,
+                }],
+                30,
+            );
+            event_tx
+                .send(AudioEvent::UserSegmentComplete(
+                    MessageSegmentType::TheirCallsign,
+                ))
+                .unwrap();
+            engine.tick(Event::Elapsed { now: engine.now() });
+            engine.state = ContestState::StationTransmitting {
+                tx_type: StationTxType::SendingExchange,
+            };
+            engine.callsign_input = caller.callsign.clone();
+            engine.exchange_inputs = caller.exchange.fields.clone();
+            engine.current_field = InputField::Exchange(0);
+            engine.tick(Event::Key {
+                key: Key::Enter,
+                shift: false,
+            });
+            engine.session_stats.qsos.last().unwrap().clone()
+        };
+
+        let busted = work(&mut engine, &format!("{}X", caller.callsign));
+        assert!(busted.is_correct());
+        assert!(busted.sent_busted_call);
+        assert_eq!(busted.points, 0);
+
+        let clean = work(&mut engine, &caller.callsign);
+        assert!(!clean.sent_busted_call);
+        assert!(clean.points > 0);
+        assert_eq!(engine.session_stats.analyze().sent_busted_call_count, 1);
+    }
+
+    #[test]
+    fn pausing_stops_the_session_and_resuming_keeps_the_rate() {
+        let (mut engine, cmd_rx, _event_tx) =
+            Engine::new(AppSettings::default(), None, Instant::now(), 1);
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        let started = Instant::now() - Duration::from_secs(600);
+        engine.score.start_time = Some(started);
+
+        engine.pause();
+        assert_eq!(engine.state, ContestState::Idle);
+        assert!(cmd_rx
+            .try_iter()
+            .any(|cmd| matches!(cmd, AudioCommand::StopAll)));
+        engine.resume(Duration::from_secs(300));
+        assert_eq!(
+            engine.score.start_time,
+            Some(started + Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn audio_commands_wait_for_room_and_a_stalled_audio_stops_the_session() {
+        let (mut engine, cmd_rx, _event_tx) =
+            Engine::new(AppSettings::default(), None, Instant::now(), 1);
+        let tune = |rit_hz| AudioCommand::Tune {
+            rit_hz,
+            filter_hz: None,
+        };
+
+        // Past what the channel holds, commands wait and go in order
+        for rit in 0..AUDIO_QUEUE_LEN + 10 {
+            engine.send_audio(tune(rit as f32));
+        }
+        engine.stop_audio();
+        assert_eq!(engine.queued_audio_commands(), AUDIO_QUEUE_LEN + 11);
+        let mut heard: Vec<_> = cmd_rx.try_iter().collect();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        heard.extend(cmd_rx.try_iter());
+        assert_eq!(heard.len(), AUDIO_QUEUE_LEN + 11);
+        assert!(heard[..AUDIO_QUEUE_LEN + 10].iter().enumerate().all(
+            |(rit, cmd)| matches!(cmd, AudioCommand::Tune { rit_hz, .. } if *rit_hz == rit as f32)
+        ));
+        assert!(matches!(heard.last(), Some(AudioCommand::StopAll)));
+        assert_eq!(engine.dropped_audio_commands, 0);
+
+        // An audio that takes nothing at all stops the session, and says so
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::CallingCq);
+        for _ in 0..AUDIO_QUEUE_LEN + AUDIO_BACKLOG_LIMIT {
+            engine.send_audio(tune(0.0));
+        }
+        assert_eq!(engine.state, ContestState::Idle);
+        assert!(engine.dropped_audio_commands > 0);
+        assert!(engine
+            .notifications
+            .entries()
+            .iter()
+            .any(|entry| entry.severity == crate::notifications::Severity::Error));
+        // Once the audio is back, it's told to stop it all
+        cmd_rx.try_iter().count();
+        engine.tick(Event::Elapsed { now: engine.now() });
+        assert!(cmd_rx
+            .try_iter()
+            .any(|cmd| matches!(cmd, AudioCommand::StopAll)));
+    }
+
+    #[test]
+    fn macro_templates_split_into_segments() {
+        let mut settings = AppSettings::default();
+        settings.contest.active_contest_id = "cqwpx".to_string();
+        settings.user.callsign = "W1AW".to_string();
+        let (mut engine, _cmd_rx, _event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        engine.callsign_input = "k1abc".to_string();
+        let segments = |engine: &mut Engine, template: &str| {
+            engine
+                .macro_segments(template)
+                .into_iter()
+                .map(|segment| (segment.content, segment.segment_type))
+                .collect::<Vec<_>>()
+        };
+
+        // Our call and serial go inline, padded as the exchange sends it;
+        // their call and our exchange are tracked as their own segments
+        assert_eq!(
+            segments(&mut engine, "{HISCALL} DE {MYCALL} NR {SERIAL} {EXCH} TU"),
+            [
+                ("K1ABC".to_string(), MessageSegmentType::TheirCallsign),
+                ("DE W1AW NR 001".to_string(), MessageSegmentType::Macro),
+                ("5NN 001".to_string(), MessageSegmentType::OurExchange),
+                ("TU".to_string(), MessageSegmentType::Macro),
+            ]
+        );
+        // Tokens are read in any case
+        assert_eq!(
+            segments(&mut engine, "{hiscall} {mycall}"),
+            [
+                ("K1ABC".to_string(), MessageSegmentType::TheirCallsign),
+                ("W1AW".to_string(), MessageSegmentType::Macro),
+            ]
+        );
+        // Unknown tokens and an unclosed brace are sent as typed
+        assert_eq!(
+            segments(&mut engine, "{QTH} PSE {MYCALL"),
+            [("{QTH} PSE {MYCALL".to_string(), MessageSegmentType::Macro)]
+        );
+        // No call entered: nothing is sent for it
+        engine.callsign_input.clear();
+        assert_eq!(
+            segments(&mut engine, "{HISCALL} {EXCH}"),
+            [("5NN 001".to_string(), MessageSegmentType::OurExchange)]
+        );
+    }
+
+    #[test]
+    fn a_qsy_costs_time_before_the_next_cq() {
+        let (mut engine, cmd_rx, _event_tx) =
+            Engine::new(AppSettings::default(), None, Instant::now(), 1);
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::CallingCq);
+        engine.callsign_input = "K1A".to_string();
+
+        engine.qsy();
+        assert_eq!(engine.state, ContestState::Idle);
+        assert!(engine.callsign_input.is_empty());
+        assert!(cmd_rx
+            .try_iter()
+            .any(|cmd| matches!(cmd, AudioCommand::StopAll)));
+        assert_eq!(engine.get_status().0, "QSY - finding a clear frequency...");
+        let fights = &engine.session_stats.fights;
+        assert_eq!(fights.qsys, 1);
+        assert_eq!(fights.time_lost(), Duration::from_secs(20));
+
+        // No CQ until a clear frequency turns up, and a second QSY meanwhile
+        // doesn't count again
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::Idle);
+        engine.qsy();
+        assert_eq!(engine.session_stats.fights.qsys, 1);
+
+        engine.qsy_until = Some(engine.now());
+        engine.tick(Event::Key {
+            key: Key::F1,
+            shift: false,
+        });
+        assert_eq!(engine.state, ContestState::CallingCq);
+    }
+
+    #[test]
+    fn dynamic_density_follows_the_run_and_is_charted() {
+        let mut settings = AppSettings::default();
+        settings.simulation.station_probability = 0.5;
+        let (mut engine, _cmd_rx, _event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        let fast = Some(Duration::from_secs(10));
+
+        // Off: nothing moves and nothing is charted
+        engine.update_density(fast, true);
+        assert!(engine.session_stats.density.is_empty());
+
+        engine.settings.simulation.dynamic_density.enabled = true;
+        engine.sync_density();
+        engine.update_density(fast, true);
+        engine.update_density(fast, true);
+        let charted: Vec<f32> = engine
+            .session_stats
+            .density
+            .iter()
+            .map(|sample| sample.probability)
+            .collect();
+        assert_eq!(charted.len(), 3);
+        assert_eq!(charted[0], 0.5);
+        assert!((charted[2] - 0.56).abs() < 1e-4);
+
+        engine.update_density(fast, false);
+        assert!((engine.density.probability() - 0.48).abs() < 1e-4);
+
+        // A new session starts over from the station probability
+        engine.reset_score();
+        assert_eq!(engine.density.probability(), 0.5);
+    }
+}
@@ -1,16 +1,55 @@
 use crate::config::AppSettings;
-use crate::stats::SessionStats;
+use crate::state::FillAnswer;
+use crate::stats::{format_counts, format_seconds, QsoRecord, SessionStats, StatsAnalysis};
 use chrono::Local;
+use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
-/// Export session statistics to a markdown file.
+/// File format for exported session statistics
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Readable report with every section of the stats window
+    #[default]
+    Markdown,
+    /// One row per QSO, for spreadsheets
+    Csv,
+    /// The full analysis plus the QSO log, for scripts
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Markdown,
+        ExportFormat::Csv,
+        ExportFormat::Json,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Export session statistics in the chosen format.
 /// Uses the configured export directory, or the current directory if not set.
 /// Returns Ok(filepath) on success, Err(error_message) on failure.
 pub fn export_session_stats(
     settings: &AppSettings,
     stats: &SessionStats,
+    format: ExportFormat,
 ) -> Result<String, String> {
     let now = Local::now();
     let callsign = settings.user.callsign.trim();
@@ -20,18 +59,20 @@ pub fn export_session_stats(
         callsign.to_uppercase()
     };
 
-    let filename = format!("CWCT-{}-{}.md", callsign_safe, now.format("%Y%m%d-%H%M"));
+    let filename = format!(
+        "CWCT-{}-{}.{}",
+        callsign_safe,
+        now.format("%Y%m%d-%H%M"),
+        format.extension()
+    );
 
-    let filepath = if settings.user.export_directory.is_empty() {
-        PathBuf::from(&filename)
-    } else {
-        let dir = PathBuf::from(&settings.user.export_directory);
-        std::fs::create_dir_all(&dir)
-            .map_err(|e| format!("Failed to create export directory: {}", e))?;
-        dir.join(&filename)
-    };
+    let filepath = export_path(settings, &filename)?;
 
-    let content = build_markdown_content(settings, stats);
+    let content = match format {
+        ExportFormat::Markdown => build_markdown_content(settings, stats),
+        ExportFormat::Csv => build_csv_content(stats),
+        ExportFormat::Json => build_json_content(settings, stats)?,
+    };
 
     let mut file = File::create(&filepath).map_err(|e| format!("Failed to create file: {}", e))?;
     file.write_all(content.as_bytes())
@@ -40,7 +81,105 @@ pub fn export_session_stats(
     Ok(filepath.to_string_lossy().into_owned())
 }
 
-fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> String {
+/// Path for `name` in the configured export directory (created if needed),
+/// or in the current directory if none is set
+pub fn export_path(settings: &AppSettings, name: &str) -> Result<PathBuf, String> {
+    if settings.user.export_directory.is_empty() {
+        Ok(PathBuf::from(name))
+    } else {
+        let dir = PathBuf::from(&settings.user.export_directory);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+        Ok(dir.join(name))
+    }
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn build_csv_content(stats: &SessionStats) -> String {
+    let mut csv = String::from(
+        "qso,expected_call,entered_call,call_ok,expected_exch,entered_exch,exch_ok,\
+         wrong_fields,wpm,snr_db,pileup,copy_secs,points,agn_call,agn_exch,f5_used,fill_request,\
+         fill_answer,repeats,wrong_station,used_prefill,prefill_unverified,logged_early,\
+         sent_busted_call,counts,flagged,note\n",
+    );
+    for (i, qso) in stats.qsos.iter().enumerate() {
+        let row = [
+            (i + 1).to_string(),
+            csv_field(&qso.expected_callsign),
+            csv_field(&qso.entered_callsign),
+            qso.callsign_correct.to_string(),
+            csv_field(&qso.expected_exchange),
+            csv_field(&qso.entered_exchange),
+            qso.exchange_correct.to_string(),
+            csv_field(&qso.wrong_fields.join(" ")),
+            qso.station_wpm.to_string(),
+            qso.snr_db
+                .map(|snr| format!("{:.1}", snr))
+                .unwrap_or_default(),
+            qso.pileup_size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            qso.copy_secs
+                .map(|secs| format!("{:.1}", secs))
+                .unwrap_or_default(),
+            qso.points.to_string(),
+            qso.used_agn_callsign.to_string(),
+            qso.used_agn_exchange.to_string(),
+            qso.used_f5_callsign.to_string(),
+            csv_field(qso.fill_request.as_deref().unwrap_or_default()),
+            match qso.fill_answer {
+                Some(FillAnswer::Element) => "element",
+                Some(FillAnswer::FullExchange) => "full",
+                None => "",
+            }
+            .to_string(),
+            csv_field(&qso.repeat_requests.join(" ")),
+            qso.worked_wrong_station.to_string(),
+            qso.used_prefill.to_string(),
+            qso.prefill_unverified.to_string(),
+            qso.logged_early.to_string(),
+            qso.sent_busted_call.to_string(),
+            (!qso.excluded).to_string(),
+            qso.flagged.to_string(),
+            csv_field(&qso.note),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Everything in a JSON export
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    callsign: &'a str,
+    exported: String,
+    analysis: StatsAnalysis,
+    qsos: &'a [QsoRecord],
+}
+
+pub(crate) fn build_json_content(
+    settings: &AppSettings,
+    stats: &SessionStats,
+) -> Result<String, String> {
+    let export = JsonExport {
+        callsign: settings.user.callsign.trim(),
+        exported: Local::now().to_rfc3339(),
+        analysis: stats.analyze(),
+        qsos: &stats.qsos,
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to build JSON: {}", e))
+}
+
+pub(crate) fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> String {
     let now = Local::now();
     let analysis = stats.analyze();
     let mut md = String::new();
@@ -60,7 +199,13 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
         "- Correct QSOs: {} ({:.1}%)\n",
         analysis.correct_qsos, analysis.correct_rate
     ));
-    md.push_str(&format!("- Total Points: {}\n\n", analysis.total_points));
+    md.push_str(&format!("- Total Points: {}\n", analysis.total_points));
+    md.push_str(&format!("- Penalties: {}\n", analysis.penalty_points));
+    md.push_str(&format!("- NILs: {}\n", analysis.nil_count));
+    md.push_str(&format!(
+        "- Penalized Score: {}\n\n",
+        analysis.penalized_points
+    ));
 
     // Accuracy
     md.push_str("## Accuracy\n\n");
@@ -69,9 +214,22 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
         analysis.correct_callsigns, analysis.total_qsos, analysis.callsign_accuracy
     ));
     md.push_str(&format!(
-        "- Exchange Accuracy: {}/{} ({:.1}%)\n\n",
+        "- Exchange Accuracy: {}/{} ({:.1}%)\n",
         analysis.correct_exchanges, analysis.total_qsos, analysis.exchange_accuracy
     ));
+    for field in &analysis.field_accuracy {
+        md.push_str(&format!(
+            "  - {}: {}/{} ({:.1}%)\n",
+            field.label, field.correct, field.total, field.accuracy_pct
+        ));
+    }
+    if analysis.impossible_entry_count > 0 {
+        md.push_str(&format!(
+            "  - Impossible Entries: {}\n",
+            analysis.impossible_entry_count
+        ));
+    }
+    md.push('\n');
 
     // Streaks
     md.push_str("## Streaks\n\n");
@@ -92,16 +250,93 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
         "- F5 (His Call): {}\n",
         analysis.f5_callsign_count
     ));
+    md.push_str(&format!(
+        "- Wrong Partial Match: {}\n",
+        analysis.wrong_station_count
+    ));
     md.push_str(&format!("- F8 Callsign: {}\n", analysis.agn_callsign_count));
     md.push_str(&format!("- F8 Exchange: {}\n", analysis.agn_exchange_count));
     if analysis.total_qsos > 0 {
         let agn_pct = (analysis.agn_any_count as f32 / analysis.total_qsos as f32) * 100.0;
         md.push_str(&format!(
-            "- Total with F8: {} ({:.1}%)\n\n",
+            "- Total with F8: {} ({:.1}%)\n",
             analysis.agn_any_count, agn_pct
         ));
     } else {
-        md.push_str(&format!("- Total with F8: {}\n\n", analysis.agn_any_count));
+        md.push_str(&format!("- Total with F8: {}\n", analysis.agn_any_count));
+    }
+    if !analysis.repeats_by_element.is_empty() {
+        md.push_str(&format!(
+            "- Repeats Asked For: {}\n",
+            format_counts(&analysis.repeats_by_element)
+        ));
+    }
+    if analysis.fill_request_count > 0 {
+        let fill_pct =
+            analysis.fill_element_count as f32 / analysis.fill_request_count as f32 * 100.0;
+        md.push_str(&format!(
+            "- Fill Requests: {}\n",
+            analysis.fill_request_count
+        ));
+        md.push_str(&format!(
+            "- Answered with Fill: {} ({:.1}%)\n",
+            analysis.fill_element_count, fill_pct
+        ));
+        md.push_str(&format!(
+            "- Resent Whole Exchange: {}\n",
+            analysis.fill_full_count
+        ));
+        md.push_str(&format!(
+            "- Fill Not Sent: {}\n",
+            analysis.fill_request_count - analysis.fill_element_count - analysis.fill_full_count
+        ));
+    }
+    md.push('\n');
+
+    // Doubles
+    md.push_str("## Doubles\n\n");
+    let doubles = &analysis.doubles;
+    if doubles.total == 0 {
+        md.push_str("No doubles heard.\n\n");
+    } else {
+        md.push_str(&format!("- Doubles Heard: {}\n", doubles.total));
+        md.push_str(&format!("- Asked Again: {}\n", doubles.asked_again));
+        md.push_str(&format!("- Pulled Out a Call: {}\n", doubles.pulled_out));
+        md.push_str(&format!("- Garbled Call: {}\n", doubles.garbled));
+        md.push_str(&format!("- Resolved: {:.1}%\n\n", doubles.success_pct));
+    }
+
+    // Efficiency: CQ cycle timing
+    md.push_str("## Efficiency\n\n");
+    let efficiency = &analysis.efficiency;
+    if efficiency.session_time.is_zero() {
+        md.push_str("No CQs sent yet.\n\n");
+    } else {
+        md.push_str(&format!(
+            "- Session Time: {}\n",
+            format_seconds(efficiency.session_time)
+        ));
+        md.push_str(&format!("- CQs Sent: {}\n", efficiency.cq_count));
+        md.push_str(&format!(
+            "- Unanswered CQs: {}\n",
+            efficiency.unanswered_cqs
+        ));
+        if let (Some(avg), Some(median)) = (efficiency.avg_tu_to_cq, efficiency.median_tu_to_cq) {
+            md.push_str(&format!(
+                "- TU to Next CQ: {} avg, {} median\n",
+                format_seconds(avg),
+                format_seconds(median)
+            ));
+        }
+        md.push_str(&format!(
+            "- Dead Time: {}\n",
+            format_seconds(efficiency.dead_time)
+        ));
+        md.push_str(&format!(
+            "- Transmitting / Listening / Idle: {:.1}% / {:.1}% / {:.1}%\n",
+            efficiency.transmit_pct, efficiency.listen_pct, efficiency.idle_pct
+        ));
+        md.push_str(&format!("- Callers Lost: {}\n\n", efficiency.callers_lost));
     }
 
     // Calling Station Speed
@@ -132,6 +367,58 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
         md.push('\n');
     }
 
+    // SNR Accuracy buckets
+    md.push_str("## SNR Accuracy (5 dB buckets)\n\n");
+    if analysis.snr_buckets.is_empty() {
+        md.push_str("No QSOs with a signal-to-noise reading yet.\n\n");
+    } else {
+        md.push_str("| SNR | Total | Correct | Accuracy |\n");
+        md.push_str("|-----|-------|---------|----------|\n");
+        for bucket in &analysis.snr_buckets {
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.1}% |\n",
+                bucket.label, bucket.total, bucket.correct, bucket.accuracy_pct
+            ));
+        }
+        md.push('\n');
+    }
+
+    // Accuracy by pileup size
+    md.push_str("## Accuracy by Pileup Size\n\n");
+    if analysis.pileup_sizes.is_empty() {
+        md.push_str("No QSOs with a pileup size yet.\n\n");
+    } else {
+        md.push_str("| Callers | Total | Correct | Accuracy | Avg Copy Time |\n");
+        md.push_str("|---------|-------|---------|----------|---------------|\n");
+        for size in &analysis.pileup_sizes {
+            let copy = size
+                .avg_copy_secs
+                .map(|secs| format!("{:.1} s", secs))
+                .unwrap_or_else(|| "-".to_string());
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.1}% | {} |\n",
+                size.label, size.total, size.correct, size.accuracy_pct, copy
+            ));
+        }
+        md.push('\n');
+    }
+
+    // QSOs by Hour
+    md.push_str("## QSOs by Hour (UTC)\n\n");
+    if analysis.hours.is_empty() {
+        md.push_str("No QSOs with a logged time yet.\n\n");
+    } else {
+        md.push_str("| Hour | QSOs | Correct | Accuracy | Points |\n");
+        md.push_str("|------|------|---------|----------|--------|\n");
+        for hour in &analysis.hours {
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.1}% | {} |\n",
+                hour.label, hour.qsos, hour.correct, hour.accuracy_pct, hour.points
+            ));
+        }
+        md.push('\n');
+    }
+
     // Character Error Analysis
     md.push_str("## Character Error Analysis\n\n");
     let errors_with_rate: Vec<_> = analysis
@@ -159,22 +446,48 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
         md.push('\n');
     }
 
+    // Flagged QSOs with the user's notes, for review
+    let flagged: Vec<(usize, &QsoRecord)> = stats
+        .qsos
+        .iter()
+        .enumerate()
+        .filter(|(_, q)| q.flagged)
+        .collect();
+    if !flagged.is_empty() {
+        md.push_str("## Flagged QSOs\n\n");
+        md.push_str("| # | Expected Call | Entered Call | Expected Exch | Entered Exch | Note |\n");
+        md.push_str("|---|---------------|--------------|---------------|--------------|------|\n");
+        for (i, qso) in flagged {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                i + 1,
+                qso.expected_callsign,
+                qso.entered_callsign,
+                qso.expected_exchange,
+                qso.entered_exchange,
+                qso.note.replace('|', "\\|")
+            ));
+        }
+        md.push('\n');
+    }
+
     // QSO Log table with all QsoRecord fields
     md.push_str("## QSO Log\n\n");
     if stats.qsos.is_empty() {
         md.push_str("No QSOs logged yet.\n");
     } else {
-        md.push_str("| # | Expected Call | Entered Call | Call OK | Expected Exch | Entered Exch | Exch OK | WPM | Points | AGN Call | AGN Exch | F5 Used |\n");
-        md.push_str("|---|---------------|--------------|---------|---------------|--------------|---------|-----|--------|----------|----------|--------|\n");
+        md.push_str("| # | Expected Call | Entered Call | Call OK | Expected Exch | Entered Exch | Exch OK | WPM | Points | AGN Call | AGN Exch | F5 Used | Counts |\n");
+        md.push_str("|---|---------------|--------------|---------|---------------|--------------|---------|-----|--------|----------|----------|---------|--------|\n");
         for (i, qso) in stats.qsos.iter().enumerate() {
             let call_ok = if qso.callsign_correct { "Yes" } else { "No" };
             let exch_ok = if qso.exchange_correct { "Yes" } else { "No" };
             let agn_call = if qso.used_agn_callsign { "Yes" } else { "No" };
             let agn_exch = if qso.used_agn_exchange { "Yes" } else { "No" };
             let f5_used = if qso.used_f5_callsign { "Yes" } else { "No" };
+            let counts = if qso.excluded { "No" } else { "Yes" };
 
             md.push_str(&format!(
-                "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
                 i + 1,
                 qso.expected_callsign,
                 qso.entered_callsign,
@@ -186,10 +499,66 @@ fn build_markdown_content(settings: &AppSettings, stats: &SessionStats) -> Strin
                 qso.points,
                 agn_call,
                 agn_exch,
-                f5_used
+                f5_used,
+                counts
             ));
         }
     }
 
     md
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_stats() -> SessionStats {
+        let mut stats = SessionStats::new();
+        stats.log_qso(QsoRecord {
+            expected_callsign: "K1ABC".to_string(),
+            entered_callsign: "K1ABC".to_string(),
+            callsign_correct: true,
+            expected_exchange: "5NN 05".to_string(),
+            entered_exchange: "5NN 05".to_string(),
+            exchange_correct: true,
+            station_wpm: 28,
+            points: 1,
+            ..Default::default()
+        });
+        stats.log_qso(QsoRecord {
+            expected_callsign: "W9XYZ".to_string(),
+            entered_callsign: "W9XY".to_string(),
+            station_wpm: 32,
+            flagged: true,
+            note: "QSB, \"brutal\"".to_string(),
+            ..Default::default()
+        });
+        stats
+    }
+
+    #[test]
+    fn csv_has_a_row_per_qso_with_quoted_notes() {
+        let csv = build_csv_content(&sample_stats());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("qso,expected_call,entered_call,"));
+        assert_eq!(
+            lines[0].split(',').count(),
+            lines[1].split(',').count(),
+            "{}",
+            lines[1]
+        );
+        assert!(lines[2].starts_with("2,W9XYZ,W9XY,false,"));
+        assert!(lines[2].ends_with(",true,true,\"QSB, \"\"brutal\"\"\""));
+    }
+
+    #[test]
+    fn json_holds_analysis_and_qsos() {
+        let json = build_json_content(&AppSettings::default(), &sample_stats()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["analysis"]["total_qsos"], 2);
+        assert_eq!(value["analysis"]["correct_qsos"], 1);
+        assert!(value["analysis"]["efficiency"]["session_time"].is_f64());
+        assert_eq!(value["qsos"][1]["note"], "QSB, \"brutal\"");
+    }
+}
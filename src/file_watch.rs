@@ -0,0 +1,136 @@
+// Watch the contest's data files (callsign and call-history files) so edits
+// are picked up without reopening Settings
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// How long a file must be quiet after a change before it is reloaded, so a
+/// save that writes in several steps is read once, complete
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    /// Watched files, as absolute paths
+    files: Vec<PathBuf>,
+    /// Folders being watched; files are watched through their folder since
+    /// many editors save by replacing the file
+    dirs: Vec<PathBuf>,
+    /// Files changed since they were last reported, and when the last of
+    /// those changes happened
+    changed: Vec<PathBuf>,
+    changed_at: Option<Instant>,
+}
+
+/// Absolute form of `path`, with its folder resolved through any links
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    let dir = path.parent()?.canonicalize().ok()?;
+    Some(dir.join(path.file_name()?))
+}
+
+impl FileWatcher {
+    pub fn new() -> Result<Self, String> {
+        let (tx, events) = channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(Self {
+            watcher,
+            events,
+            files: Vec::new(),
+            dirs: Vec::new(),
+            changed: Vec::new(),
+            changed_at: None,
+        })
+    }
+
+    /// Watch these files instead of the ones watched before. Files whose
+    /// folder doesn't exist are skipped.
+    pub fn watch(&mut self, files: &[PathBuf]) {
+        let files: Vec<PathBuf> = files.iter().filter_map(|f| resolve(f)).collect();
+        let mut dirs: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|f| f.parent().map(Path::to_path_buf))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+
+        for dir in self.dirs.iter().filter(|d| !dirs.contains(d)) {
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.iter().filter(|d| !self.dirs.contains(d)) {
+            let _ = self.watcher.watch(dir, RecursiveMode::NonRecursive);
+        }
+        self.files = files;
+        self.dirs = dirs;
+        self.changed.clear();
+        self.changed_at = None;
+        // Drop events from before the switch
+        while self.events.try_recv().is_ok() {}
+    }
+
+    /// Watched files that changed and have since settled, each reported once
+    pub fn poll(&mut self, now: Instant) -> Vec<PathBuf> {
+        while let Ok(event) = self.events.try_recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                continue;
+            }
+            for path in event.paths.iter().filter(|p| self.files.contains(p)) {
+                if !self.changed.contains(path) {
+                    self.changed.push(path.clone());
+                }
+                self.changed_at = Some(now);
+            }
+        }
+        match self.changed_at {
+            Some(at) if now.saturating_duration_since(at) >= SETTLE_TIME => {
+                self.changed_at = None;
+                std::mem::take(&mut self.changed)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_reported_once_settled() {
+        let dir = std::env::temp_dir().join(format!("file_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("calls.txt");
+        std::fs::write(&file, "K1ABC\n").unwrap();
+
+        let expected = vec![resolve(&file).unwrap()];
+        let mut watcher = FileWatcher::new().unwrap();
+        watcher.watch(std::slice::from_ref(&file));
+        std::fs::write(dir.join("other.txt"), "x").unwrap();
+        std::fs::write(&file, "K1ABC\nW2XYZ\n").unwrap();
+
+        // Wait for the change to arrive, then for it to settle
+        let start = Instant::now();
+        let mut changed = Vec::new();
+        while changed.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(50));
+            // Not reported while the file may still be being written
+            assert!(watcher.poll(Instant::now()).is_empty());
+            changed = watcher.poll(Instant::now() + SETTLE_TIME);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(changed, expected);
+    }
+}
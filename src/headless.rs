@@ -0,0 +1,484 @@
+// Headless mode: run the contest simulation from a script, without the GUI
+// or a sound card, rendering the audio to a WAV file
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::audio::backend::OfflineRenderer;
+use crate::audio::wav::WavWriter;
+use crate::engine::{Engine, Event, InputField, Key};
+use crate::messages::{AudioCommand, AudioEvent};
+use crate::state::{ContestState, StationTxType};
+
+/// Audio is rendered in blocks this long (ms), and the engine ticks once per block
+const BLOCK_MS: u32 = 10;
+/// How long `until` waits when no timeout is given (ms)
+const DEFAULT_UNTIL_TIMEOUT_MS: u32 = 10_000;
+
+pub const USAGE: &str = "Usage: contest_trainer headless <script> [--wav <file>] [--seed <n>]
+
+Runs the contest simulation without the GUI, using your saved settings.
+The seed is printed at the start; run again with --seed and the same seed
+to replay the same callers.
+Each script line is one step ('#' starts a comment):
+  key <F1..F12|Enter|Escape|Space|Tab|Up|Down> [shift]
+  type <text>          type into the current field (spaces move to the next field)
+  copy call            type the current caller's callsign as sent
+  copy exchange        type the current caller's exchange as sent
+  wait <ms>            let time pass
+  until <state> [ms]   wait for a state (fails after the timeout, default 10000)
+  expect qsos <n>      fail unless exactly n QSOs are logged
+States: idle, calling-cq, waiting-for-callers, stations-calling,
+user-transmitting, waiting-for-station, station-transmitting,
+receiving-exchange, qso-complete";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptStep {
+    Key { key: Key, shift: bool },
+    Type(String),
+    CopyCall,
+    CopyExchange,
+    Wait(u32),
+    Until { state: String, timeout_ms: u32 },
+    ExpectQsos(usize),
+}
+
+struct Options {
+    script: PathBuf,
+    wav: Option<PathBuf>,
+    seed: Option<u64>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut script = None;
+    let mut wav = None;
+    let mut seed = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--wav" => {
+                let path = iter.next().ok_or("--wav needs a file name")?;
+                wav = Some(PathBuf::from(path));
+            }
+            "--seed" => {
+                seed = Some(parse_number(iter.next().map(String::as_str), "seed")?);
+            }
+            _ if script.is_none() && !arg.starts_with("--") => {
+                script = Some(PathBuf::from(arg));
+            }
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    Ok(Options {
+        script: script.ok_or("No script given (see --help)")?,
+        wav,
+        seed,
+    })
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "enter" => Key::Enter,
+        "escape" | "esc" => Key::Escape,
+        "space" => Key::Space,
+        "tab" => Key::Tab,
+        "up" => Key::ArrowUp,
+        "down" => Key::ArrowDown,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, what: &str) -> Result<T, String> {
+    let word = word.ok_or_else(|| format!("Missing {}", what))?;
+    word.parse()
+        .map_err(|_| format!("Invalid {}: {}", what, word))
+}
+
+/// Parse a script into steps. Errors name the offending line.
+pub fn parse_script(text: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut steps = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let mut words = rest.split_whitespace();
+        let step = match command.to_ascii_lowercase().as_str() {
+            "key" => {
+                let name = words.next().unwrap_or("");
+                let key = parse_key(name).ok_or_else(|| format!("Unknown key: {}", name))?;
+                let shift = words.next() == Some("shift");
+                Ok(ScriptStep::Key { key, shift })
+            }
+            "type" if !rest.is_empty() => Ok(ScriptStep::Type(rest.to_string())),
+            "copy" => match words.next() {
+                Some("call") => Ok(ScriptStep::CopyCall),
+                Some("exchange") => Ok(ScriptStep::CopyExchange),
+                _ => Err("Expected 'copy call' or 'copy exchange'".to_string()),
+            },
+            "wait" => parse_number(words.next(), "wait time").map(ScriptStep::Wait),
+            "until" => match words.next() {
+                Some(state) if STATE_NAMES.contains(&state) => {
+                    let timeout_ms = match words.next() {
+                        Some(word) => parse_number(Some(word), "timeout")?,
+                        None => DEFAULT_UNTIL_TIMEOUT_MS,
+                    };
+                    Ok(ScriptStep::Until {
+                        state: state.to_string(),
+                        timeout_ms,
+                    })
+                }
+                Some(state) => Err(format!("Unknown state: {}", state)),
+                None => Err("Missing state".to_string()),
+            },
+            "expect" if words.next() == Some("qsos") => {
+                parse_number(words.next(), "QSO count").map(ScriptStep::ExpectQsos)
+            }
+            _ => Err(format!("Unknown step: {}", line)),
+        };
+        steps.push(step.map_err(|e| format!("Line {}: {}", index + 1, e))?);
+    }
+    Ok(steps)
+}
+
+const STATE_NAMES: [&str; 9] = [
+    "idle",
+    "calling-cq",
+    "waiting-for-callers",
+    "stations-calling",
+    "user-transmitting",
+    "waiting-for-station",
+    "station-transmitting",
+    "receiving-exchange",
+    "qso-complete",
+];
+
+fn state_name(state: &ContestState) -> &'static str {
+    match state {
+        ContestState::Idle => "idle",
+        ContestState::CallingCq => "calling-cq",
+        ContestState::WaitingForCallers => "waiting-for-callers",
+        ContestState::StationsCalling => "stations-calling",
+        ContestState::UserTransmitting { .. } => "user-transmitting",
+        ContestState::WaitingForStation => "waiting-for-station",
+        ContestState::StationTransmitting {
+            tx_type: StationTxType::SendingExchange,
+        } => "receiving-exchange",
+        ContestState::StationTransmitting { .. } => "station-transmitting",
+        ContestState::QsoComplete => "qso-complete",
+    }
+}
+
+/// The engine plus an offline renderer standing in for the sound card
+struct Session {
+    engine: Engine,
+    renderer: OfflineRenderer,
+    wav: Option<WavWriter>,
+    sample_rate: u32,
+    frames_rendered: u64,
+    started: Instant,
+    last_state: ContestState,
+}
+
+impl Session {
+    fn new(
+        engine: Engine,
+        cmd_rx: Receiver<AudioCommand>,
+        event_tx: Sender<AudioEvent>,
+        wav: Option<WavWriter>,
+    ) -> Self {
+        Self {
+            renderer: OfflineRenderer::new(cmd_rx, event_tx, engine.settings.audio.clone()),
+            sample_rate: engine.settings.audio.sample_rate,
+            started: engine.now(),
+            engine,
+            wav,
+            frames_rendered: 0,
+            last_state: ContestState::Idle,
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.frames_rendered * 1000 / self.sample_rate as u64
+    }
+
+    /// The simulation clock: the start plus the audio rendered so far
+    fn now(&self) -> Instant {
+        self.started + Duration::from_millis(self.elapsed_ms())
+    }
+
+    /// Render one block of audio and tick the engine on the audio's clock,
+    /// so a script runs as fast as it can be rendered
+    fn step(&mut self) -> Result<(), String> {
+        let frames = (self.sample_rate * BLOCK_MS / 1000) as usize;
+        let mut left = vec![0.0f32; frames];
+        let mut right = vec![0.0f32; frames];
+        self.renderer.render(&mut left, &mut right);
+        if let Some(wav) = self.wav.as_mut() {
+            wav.write_frames(&left, &right)?;
+        }
+        self.frames_rendered += frames as u64;
+
+        self.engine.tick(Event::Elapsed { now: self.now() });
+        self.report_state();
+        Ok(())
+    }
+
+    fn report_state(&mut self) {
+        if self.engine.state != self.last_state {
+            self.last_state = self.engine.state;
+            println!(
+                "{:>7} ms  state {}",
+                self.elapsed_ms(),
+                state_name(&self.engine.state)
+            );
+        }
+    }
+
+    fn run_for(&mut self, ms: u32) -> Result<(), String> {
+        for _ in 0..ms.div_ceil(BLOCK_MS) {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Type text as the keyboard would: each space moves to the next field
+    fn type_text(&mut self, text: &str) {
+        for (index, word) in text.split_whitespace().enumerate() {
+            if index > 0 {
+                self.engine.tick(Event::Key {
+                    key: Key::Space,
+                    shift: false,
+                });
+            }
+            match self.engine.current_field {
+                InputField::Callsign => self.engine.callsign_input = word.to_uppercase(),
+                InputField::Exchange(field) => {
+                    if let Some(input) = self.engine.exchange_inputs.get_mut(field) {
+                        *input = word.to_uppercase();
+                    }
+                }
+            }
+        }
+    }
+
+    fn run_step(&mut self, step: &ScriptStep) -> Result<(), String> {
+        match step {
+            ScriptStep::Key { key, shift } => {
+                self.engine.tick(Event::Key {
+                    key: *key,
+                    shift: *shift,
+                });
+                self.engine.tick(Event::Elapsed { now: self.now() });
+                self.report_state();
+            }
+            ScriptStep::Type(text) => self.type_text(text),
+            ScriptStep::CopyCall => {
+                let call = self
+                    .engine
+                    .context
+                    .get_current_caller()
+                    .or_else(|| self.engine.context.active_callers.first())
+                    .map(|caller| caller.params.callsign.clone())
+                    .ok_or("No caller to copy")?;
+                self.engine.current_field = InputField::Callsign;
+                self.type_text(&call);
+            }
+            ScriptStep::CopyExchange => {
+                let fields = self
+                    .engine
+                    .context
+                    .get_current_caller()
+                    .map(|caller| caller.params.exchange.fields.clone())
+                    .ok_or("No caller to copy")?;
+                for (input, field) in self.engine.exchange_inputs.iter_mut().zip(fields) {
+                    *input = field;
+                }
+            }
+            ScriptStep::Wait(ms) => self.run_for(*ms)?,
+            ScriptStep::Until { state, timeout_ms } => {
+                let deadline = self.elapsed_ms() + *timeout_ms as u64;
+                while state_name(&self.engine.state) != state {
+                    if self.elapsed_ms() >= deadline {
+                        return Err(format!(
+                            "Timed out after {} ms waiting for {} (state is {})",
+                            timeout_ms,
+                            state,
+                            state_name(&self.engine.state)
+                        ));
+                    }
+                    self.step()?;
+                }
+            }
+            ScriptStep::ExpectQsos(count) => {
+                let logged = self.engine.session_stats.qsos.len();
+                if logged != *count {
+                    return Err(format!("Expected {} QSOs, {} logged", count, logged));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry point for `contest_trainer headless ...`
+pub fn run(args: &[String]) -> Result<(), String> {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let options = parse_args(args)?;
+    let text = std::fs::read_to_string(&options.script)
+        .map_err(|e| format!("Failed to read {}: {}", options.script.display(), e))?;
+    let steps = parse_script(&text)?;
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Seed {}", seed);
+    let (engine, cmd_rx, event_tx) = Engine::load(Instant::now(), seed);
+    let wav = match &options.wav {
+        Some(path) => Some(WavWriter::create(path, engine.settings.audio.sample_rate)?),
+        None => None,
+    };
+    let mut session = Session::new(engine, cmd_rx, event_tx, wav);
+
+    let mut result = Ok(());
+    for step in &steps {
+        println!("{:>7} ms  {:?}", session.elapsed_ms(), step);
+        if let Err(e) = session.run_step(step) {
+            result = Err(e);
+            break;
+        }
+    }
+
+    for qso in &session.engine.session_stats.qsos {
+        println!(
+            "QSO {} {} (sent {} {}) {}",
+            qso.entered_callsign,
+            qso.entered_exchange,
+            qso.expected_callsign,
+            qso.expected_exchange,
+            if qso.callsign_correct && qso.exchange_correct {
+                "OK"
+            } else {
+                "BUSTED"
+            }
+        );
+    }
+    println!(
+        "{} QSOs, {} points ({} after penalties)",
+        session.engine.score.qso_count,
+        session.engine.score.total_points,
+        session.engine.score.penalized_points()
+    );
+
+    if let Some(wav) = session.wav.take() {
+        wav.finish()?;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppSettings;
+
+    #[test]
+    fn parses_a_script() {
+        let script = "# work one station\nkey F1\nuntil stations-calling 8000\ncopy call\nkey Tab shift\ntype 5nn 05\nwait 250\nexpect qsos 1\n";
+        let steps = parse_script(script).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                ScriptStep::Key {
+                    key: Key::F1,
+                    shift: false
+                },
+                ScriptStep::Until {
+                    state: "stations-calling".to_string(),
+                    timeout_ms: 8000
+                },
+                ScriptStep::CopyCall,
+                ScriptStep::Key {
+                    key: Key::Tab,
+                    shift: true
+                },
+                ScriptStep::Type("5nn 05".to_string()),
+                ScriptStep::Wait(250),
+                ScriptStep::ExpectQsos(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_script_works_a_qso_faster_than_real_time() {
+        let mut settings = AppSettings::default();
+        settings.audio.sample_rate = 8000;
+        settings.audio.noise_level = 0.0;
+        let (mut engine, cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), 1);
+        // The tutorial's one strong caller who keeps calling until worked
+        engine.start_tutorial();
+        let mut session = Session::new(engine, cmd_rx, event_tx, None);
+
+        let script = "key F1\nuntil stations-calling 15000\ncopy call\nkey Enter\nuntil receiving-exchange 15000\ncopy exchange\nwait 7000\nkey Enter\nwait 2000\nexpect qsos 1\n";
+        let wall_clock = Instant::now();
+        for step in parse_script(script).unwrap() {
+            session.run_step(&step).unwrap();
+        }
+
+        let qso = &session.engine.session_stats.qsos[0];
+        assert!(qso.is_correct() && !qso.logged_early);
+        // The timers ran on the rendered audio, not the wall clock
+        assert!(wall_clock.elapsed() < Duration::from_millis(session.elapsed_ms()));
+    }
+
+    #[test]
+    fn the_same_seed_brings_the_same_callers() {
+        let callers = |seed| {
+            let mut settings = AppSettings::default();
+            settings.audio.sample_rate = 8000;
+            let (engine, cmd_rx, event_tx) = Engine::new(settings, None, Instant::now(), seed);
+            let mut session = Session::new(engine, cmd_rx, event_tx, None);
+            let script = "key F1\nuntil stations-calling 15000\nwait 1000\n";
+            for step in parse_script(script).unwrap() {
+                session.run_step(&step).unwrap();
+            }
+            session
+                .engine
+                .context
+                .active_callers
+                .iter()
+                .map(|caller| (caller.params.callsign.clone(), caller.params.wpm))
+                .collect::<Vec<_>>()
+        };
+        let first = callers(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, callers(7));
+    }
+
+    #[test]
+    fn script_errors_name_the_line() {
+        assert_eq!(
+            parse_script("key F1\nuntil lunch").unwrap_err(),
+            "Line 2: Unknown state: lunch"
+        );
+        assert!(parse_script("key F13").is_err());
+        assert!(parse_script("wait soon").is_err());
+    }
+}
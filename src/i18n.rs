@@ -0,0 +1,1607 @@
+// Translations for user-visible UI text
+//
+// The English text is the lookup key, so anything without a translation
+// simply shows in English.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::config::Language;
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Name of the language in that language, for the settings picker
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn table(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::German => GERMAN,
+        }
+    }
+}
+
+/// Switch the UI language (called every frame from the settings)
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|l| *l == language)
+        .unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL
+        .get(CURRENT.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Translate a piece of UI text into the current language
+pub fn tr(text: &'static str) -> &'static str {
+    lookup(language(), text)
+}
+
+/// Translate text containing `{}` placeholders, then fill them in order
+pub fn tr_args(text: &'static str, args: &[&str]) -> String {
+    let mut out = tr(text).to_string();
+    for arg in args {
+        if let Some(pos) = out.find("{}") {
+            out.replace_range(pos..pos + 2, arg);
+        }
+    }
+    out
+}
+
+fn lookup(language: Language, text: &'static str) -> &'static str {
+    language
+        .table()
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map(|(_, translated)| *translated)
+        .unwrap_or(text)
+}
+
+const GERMAN: &[(&str, &str)] = &[
+    // Menu and windows
+    ("File", "Datei"),
+    ("Settings", "Einstellungen"),
+    ("Quit", "Beenden"),
+    ("View", "Ansicht"),
+    ("Help", "Hilfe"),
+    ("Tutorial", "Einführung"),
+    ("Normal", "Normal"),
+    ("Compact", "Kompakt"),
+    ("Expanded", "Erweitert"),
+    // Tutorial
+    ("Step {} of {}", "Schritt {} von {}"),
+    ("Call CQ", "CQ rufen"),
+    ("Copy the call", "Rufzeichen aufnehmen"),
+    ("Send your exchange", "Deinen Austausch senden"),
+    ("Copy their exchange", "Seinen Austausch aufnehmen"),
+    ("Log the QSO", "QSO loggen"),
+    ("QSO complete", "QSO fertig"),
+    (
+        "Press F1 (or Enter with the Call box empty) to call CQ. One station \
+         will answer, slowly and loudly.",
+        "Drücke F1 (oder Enter bei leerem Rufzeichenfeld), um CQ zu rufen. Eine \
+         Station antwortet, langsam und laut.",
+    ),
+    (
+        "A station is calling. Type the callsign you hear in the Call box, then \
+         press Enter to send their call and your exchange. F8 asks them to send \
+         it again.",
+        "Eine Station ruft. Tippe das gehörte Rufzeichen ins Rufzeichenfeld und \
+         drücke Enter, um sein Rufzeichen und deinen Austausch zu senden. Mit F8 \
+         bittest du um Wiederholung.",
+    ),
+    (
+        "You are sending their call and your exchange. Listen: they answer with \
+         their exchange.",
+        "Du sendest sein Rufzeichen und deinen Austausch. Hör zu: Er antwortet \
+         mit seinem Austausch.",
+    ),
+    (
+        "Type their exchange in the boxes after the call; Space moves to the next \
+         box. F8 asks for a repeat.",
+        "Tippe seinen Austausch in die Felder nach dem Rufzeichen; die Leertaste \
+         springt ins nächste Feld. Mit F8 bittest du um Wiederholung.",
+    ),
+    (
+        "Press Enter to send TU and log the QSO. Then the next CQ starts it all \
+         again.",
+        "Drücke Enter, um TU zu senden und das QSO zu loggen. Mit dem nächsten CQ \
+         beginnt alles von vorn.",
+    ),
+    (
+        "That's a whole QSO: CQ, their call, your exchange, their exchange, TU. \
+         Work another here, or start contesting with your own settings.",
+        "Das war ein ganzes QSO: CQ, sein Rufzeichen, dein Austausch, sein \
+         Austausch, TU. Arbeite hier noch eins, oder starte den Contest mit deinen \
+         eigenen Einstellungen.",
+    ),
+    ("Another QSO", "Noch ein QSO"),
+    ("Start Contesting", "Contest starten"),
+    ("Skip Tutorial", "Einführung überspringen"),
+    // Main panel
+    ("Dismiss", "Schließen"),
+    ("Reset Stats", "Statistik zurücksetzen"),
+    ("Toggle Static (ON)", "Störgeräusche (AN)"),
+    ("Toggle Static (OFF)", "Störgeräusche (AUS)"),
+    ("Session Stats", "Sitzungsstatistik"),
+    ("TX History", "Sendeverlauf"),
+    ("Nothing sent yet", "Noch nichts gesendet"),
+    ("Drills", "Übungen"),
+    ("Skimmer", "Skimmer"),
+    ("Points:", "Punkte:"),
+    ("Penalized:", "Nach Abzügen:"),
+    (
+        "Points after log checking: busted QSOs and NILs cost points",
+        "Punkte nach der Logprüfung: fehlerhafte QSOs und NILs kosten Punkte",
+    ),
+    ("{}/hr", "{}/h"),
+    ("Run WPM:", "Eigenes Tempo:"),
+    ("{} waiting", "{} wartend"),
+    (
+        "Stations waiting to call, including those calling now",
+        "Stationen, die rufen wollen, einschließlich der gerade rufenden",
+    ),
+    ("{} WPM, {} Hz", "{} WpM, {} Hz"),
+    ("Call", "Rufzeichen"),
+    ("Note:", "Notiz:"),
+    (
+        "Why flag this QSO? (optional)",
+        "Warum markieren? (optional)",
+    ),
+    ("Callsign", "Rufzeichen"),
+    ("Not a valid {}", "Ungültig: {}"),
+    ("Exchange", "Austausch"),
+    ("His Call", "Sein Rufz."),
+    ("Wipe", "Löschen"),
+    ("Submit", "Loggen"),
+    ("Stop", "Stopp"),
+    ("Last QSO:", "Letztes QSO:"),
+    ("None yet", "Noch keins"),
+    ("{} QSOs  {} pts  {}/hr", "{} QSOs  {} Pkt.  {}/h"),
+    ("({} after penalties)", "({} nach Abzügen)"),
+    ("Call: {}", "Rufz.: {}"),
+    ("Exch: {}", "Austausch: {}"),
+    ("{} not valid", "{} ungültig"),
+    ("+{} pts", "+{} Pkt."),
+    ("Expected: {} {}", "Erwartet: {} {}"),
+    // Contest help
+    ("New to {}?", "Neu bei {}?"),
+    ("Got It", "Verstanden"),
+    ("Example exchange:", "Beispiel-Austausch:"),
+    ("Typical speed:", "Übliches Tempo:"),
+    ("{}-{} WPM", "{}-{} WpM"),
+    // Status announcements
+    ("QSO flagged", "QSO markiert"),
+    ("Station calling", "Station ruft"),
+    ("{} stations calling", "{} Stationen rufen"),
+    ("Exchange received", "Austausch empfangen"),
+    (
+        "Logged before the caller finished the exchange; wait for the last character",
+        "Geloggt, bevor der Anrufer den Austausch beendet hatte; warte auf das letzte Zeichen",
+    ),
+    (
+        "Wait for the caller to finish the exchange",
+        "Warte, bis der Anrufer den Austausch beendet hat",
+    ),
+    ("Fill in {} before logging", "Fülle vor dem Loggen {} aus"),
+    (
+        "No points: {} never heard their call from you right",
+        "Keine Punkte: {} hat das eigene Rufzeichen nie richtig von dir gehört",
+    ),
+    (
+        "Station asked for a repeat",
+        "Station bittet um Wiederholung",
+    ),
+    (
+        "Station asked for a fill",
+        "Station fragt nach einem Teil des Austauschs",
+    ),
+    ("Station sent a correction", "Station sendet Korrektur"),
+    (
+        "Someone is calling CQ on your frequency",
+        "Jemand ruft CQ auf deiner Frequenz",
+    ),
+    ("The frequency is clear again", "Die Frequenz ist wieder frei"),
+    (
+        "QSY: {} s to find a clear frequency",
+        "QSY: {} s, um eine freie Frequenz zu finden",
+    ),
+    (
+        "Still looking for a clear frequency",
+        "Noch auf der Suche nach einer freien Frequenz",
+    ),
+    ("Logged {}", "{} geloggt"),
+    (", call wrong, was {}", ", Rufzeichen falsch, richtig: {}"),
+    (
+        ", exchange wrong, was {}",
+        ", Austausch falsch, richtig: {}",
+    ),
+    (", correct, {} points", ", richtig, {} Punkte"),
+    // Status line
+    (
+        "Press F1/Enter to call CQ",
+        "F1/Enter drücken, um CQ zu rufen",
+    ),
+    ("Calling CQ...", "Rufe CQ..."),
+    (
+        "QSY! Press F1/Enter to find a CQ",
+        "QSY! F1/Enter drücken, um einen CQ-Ruf zu suchen",
+    ),
+    (
+        "Station calling CQ - enter their call",
+        "Station ruft CQ - Rufzeichen eingeben",
+    ),
+    ("Waiting for callers...", "Warte auf Anrufer..."),
+    (
+        "QSY - finding a clear frequency...",
+        "QSY - suche eine freie Frequenz...",
+    ),
+    (
+        "Frequency taken! Keep CQing or QSY (Alt+Q)",
+        "Frequenz belegt! Weiter CQ rufen oder QSY (Alt+Q)",
+    ),
+    (
+        "Fix callsign and press Enter",
+        "Rufzeichen korrigieren und Enter drücken",
+    ),
+    (
+        "Station calling - enter callsign",
+        "Station ruft - Rufzeichen eingeben",
+    ),
+    ("Sending exchange...", "Sende Austausch..."),
+    (
+        "Editing last QSO (Enter saves, Esc cancels)",
+        "Letztes QSO bearbeiten (Enter speichert, Esc bricht ab)",
+    ),
+    ("Querying partial...", "Frage Teilrufzeichen ab..."),
+    ("Sending callsign...", "Sende Rufzeichen..."),
+    ("Requesting repeat...", "Bitte um Wiederholung..."),
+    ("Sending TU...", "Sende TU..."),
+    ("Waiting for correction...", "Warte auf Korrektur..."),
+    ("Waiting for response...", "Warte auf Antwort..."),
+    (
+        "Receiving exchange - press Enter to log",
+        "Empfange Austausch - Enter zum Loggen",
+    ),
+    (
+        "Station requests repeat - press F2",
+        "Station bittet um Wiederholung - F2 drücken",
+    ),
+    (
+        "Station asks for a fill - press Shift+F2",
+        "Station fragt nach einem Teil - Umschalt+F2 drücken",
+    ),
+    (
+        "Station correcting callsign...",
+        "Station korrigiert Rufzeichen...",
+    ),
+    (
+        "QSO logged! Press F1 for next",
+        "QSO geloggt! F1 für das nächste",
+    ),
+    // Calendar prompt and export dialog
+    ("Upcoming Contest", "Bevorstehender Contest"),
+    ("{} \u{2014} practice now?", "{} \u{2014} jetzt üben?"),
+    ("Practice {}", "{} üben"),
+    ("Not now", "Nicht jetzt"),
+    ("Don't ask again", "Nicht mehr fragen"),
+    ("Export Complete", "Export abgeschlossen"),
+    ("Session exported to:", "Sitzung exportiert nach:"),
+    // Sandbox
+    ("Morse Sandbox", "Morse-Sandbox"),
+    (
+        "Type any text to hear it over the current band noise",
+        "Beliebigen Text eingeben und über dem aktuellen Bandrauschen anhören",
+    ),
+    ("WPM:", "WpM:"),
+    ("Pitch (Hz):", "Tonhöhe (Hz):"),
+    (
+        "Text to send, then press Enter",
+        "Zu sendender Text, dann Enter drücken",
+    ),
+    ("Play", "Abspielen"),
+    (
+        "Finish or wipe the current contest QSO first",
+        "Erst das laufende Contest-QSO beenden oder löschen",
+    ),
+    ("Playing: {} ({} WPM, {} Hz)", "Spielt: {} ({} WpM, {} Hz)"),
+    ("Queued: {} ({} WPM, {} Hz)", "Wartet: {} ({} WpM, {} Hz)"),
+    // Session statistics
+    ("Session Statistics", "Sitzungsstatistik"),
+    ("Export Stats", "Statistik exportieren"),
+    (
+        "Markdown for reading, CSV (one row per QSO) for spreadsheets, JSON (full analysis) for scripts",
+        "Markdown zum Lesen, CSV (eine Zeile pro QSO) für Tabellen, JSON (vollständige Auswertung) für Skripte",
+    ),
+    ("Error: {}", "Fehler: {}"),
+    ("Session Summary", "Zusammenfassung"),
+    ("Total QSOs:", "QSOs gesamt:"),
+    ("Correct QSOs:", "Korrekte QSOs:"),
+    ("Total Points:", "Punkte gesamt:"),
+    ("Penalties:", "Abzüge:"),
+    (
+        "Log checking takes out a busted call or exchange and charges one more QSO's points; a NIL, a QSO you dropped after sending your exchange that the other station logged, costs two",
+        "Die Logprüfung streicht ein falsches Rufzeichen oder einen falschen Austausch und zieht die Punkte eines weiteren QSOs ab; ein NIL, ein nach deinem Austausch abgebrochenes QSO, das die Gegenstation geloggt hat, kostet zwei",
+    ),
+    ("NILs:", "NILs:"),
+    ("Penalized Score:", "Punkte nach Abzügen:"),
+    ("Accuracy", "Genauigkeit"),
+    ("Callsign Accuracy:", "Rufzeichen-Genauigkeit:"),
+    ("Exchange Accuracy:", "Austausch-Genauigkeit:"),
+    ("Impossible Entries:", "Unmögliche Eingaben:"),
+    (
+        "Wrong fields logged with something that can't be right, like a section that doesn't exist, rather than a miscopy",
+        "Falsche Felder mit einem Wert, der gar nicht stimmen kann, etwa einer nicht existierenden Sektion, statt eines Hörfehlers",
+    ),
+    ("Streaks", "Serien"),
+    ("Current Clean:", "Aktuell fehlerfrei:"),
+    ("Max Clean:", "Max. fehlerfrei:"),
+    ("Current Error:", "Aktuell fehlerhaft:"),
+    ("Max Error:", "Max. fehlerhaft:"),
+    (
+        "Clean = callsign and exchange both correct",
+        "Fehlerfrei = Rufzeichen und Austausch korrekt",
+    ),
+    ("F5/F8 Usage", "F5/F8-Nutzung"),
+    ("F5 (His Call):", "F5 (Sein Rufz.):"),
+    ("Wrong Partial Match:", "Falscher Teiltreffer:"),
+    (
+        "QSOs logged with the call of another station that answered your partial query",
+        "QSOs mit dem Rufzeichen einer anderen Station, die auf deine Teilabfrage geantwortet hat",
+    ),
+    ("F8 Callsign:", "F8 Rufzeichen:"),
+    ("F8 Exchange:", "F8 Austausch:"),
+    ("Total with F8:", "Gesamt mit F8:"),
+    ("Pre-filled:", "Vorausgefüllt:"),
+    ("Repeats Asked For:", "Erbetene Wiederholungen:"),
+    (
+        "What you asked callers to repeat, most often first (Shift+F8 asks \
+         for just the element in the focused box)",
+        "Was du dir wiederholen lassen hast, das Häufigste zuerst (Umschalt+F8 \
+         fragt nur nach dem Teil im aktiven Feld)",
+    ),
+    ("Fill Requests:", "Nachfragen:"),
+    (
+        "QSOs where the caller asked for one part of your exchange",
+        "QSOs, in denen die Gegenstation nach einem Teil deines Austauschs gefragt hat",
+    ),
+    ("Answered with Fill:", "Nur den Teil gesendet:"),
+    (
+        "Sent just the part asked for (Shift+F2)",
+        "Nur den erfragten Teil gesendet (Umschalt+F2)",
+    ),
+    ("Resent Whole Exchange:", "Ganzen Austausch wiederholt:"),
+    ("Fill Not Sent:", "Nachfrage unbeantwortet:"),
+    (
+        "QSOs logged without answering the fill request",
+        "QSOs, die ohne Antwort auf die Nachfrage geloggt wurden",
+    ),
+    ("Pre-fill Unchecked:", "Vorausfüllung ungeprüft:"),
+    (
+        "QSOs logged without moving into the pre-filled fields to check them",
+        "QSOs, bei denen die vorausgefüllten Felder nicht geprüft wurden",
+    ),
+    ("Sent Busted Call:", "Falsches Rufzeichen gesendet:"),
+    (
+        "QSOs where every call you sent the station was wrong",
+        "QSOs, in denen jedes Rufzeichen, das du der Station gesendet hast, falsch war",
+    ),
+    ("Logged Early:", "Zu früh geloggt:"),
+    ("{} ({} right)", "{} ({} richtig)"),
+    (
+        "QSOs logged before the caller finished sending the exchange",
+        "QSOs, die geloggt wurden, bevor der Anrufer den Austausch fertig gesendet hatte",
+    ),
+    ("Doubles", "Doppelungen"),
+    ("No doubles heard yet", "Noch keine Doppelungen gehört"),
+    ("Doubles Heard:", "Doppelungen gehört:"),
+    ("Asked Again:", "Nachgefragt:"),
+    (
+        "Sent AGN or a partial query instead of guessing",
+        "AGN oder Teilabfrage gesendet statt zu raten",
+    ),
+    ("Pulled Out a Call:", "Ein Rufzeichen herausgehört:"),
+    (
+        "Came back to one of the two calls, copied right",
+        "Eines der beiden Rufzeichen richtig aufgenommen",
+    ),
+    ("Garbled Call:", "Falsches Rufzeichen:"),
+    (
+        "Came back to a call that was neither station",
+        "Ein Rufzeichen gegeben, das keine der beiden Stationen war",
+    ),
+    ("Resolved:", "Aufgelöst:"),
+    ("Efficiency", "Effizienz"),
+    ("No CQs sent yet", "Noch keine CQ-Rufe"),
+    ("CQs Sent:", "CQ-Rufe:"),
+    ("Unanswered CQs:", "Unbeantwortete CQs:"),
+    ("TU to Next CQ:", "TU bis nächster CQ:"),
+    ("{} avg, {} median", "{} Mittel, {} Median"),
+    (
+        "From the end of your TU to the start of your next CQ (gaps over 30 s count as breaks)",
+        "Vom Ende deines TU bis zum Beginn des nächsten CQ (Lücken über 30 s zählen als Pause)",
+    ),
+    ("Dead Time:", "Leerlauf nach CQ:"),
+    (
+        "Listening after a CQ with nobody calling",
+        "Hören nach einem CQ, ohne dass jemand ruft",
+    ),
+    ("Transmitting:", "Senden:"),
+    ("Listening:", "Hören:"),
+    ("Idle:", "Untätig:"),
+    ("Callers Lost:", "Verlorene Anrufer:"),
+    (
+        "Callers who ran out of patience and left before you worked them",
+        "Anrufer, denen die Geduld ausging und die gingen, bevor du sie gearbeitet hast",
+    ),
+    ("Calling Station Speed", "Tempo der rufenden Stationen"),
+    ("Average WPM:", "Mittleres Tempo:"),
+    ("WPM Range:", "Tempobereich:"),
+    ("No QSOs logged yet", "Noch keine QSOs geloggt"),
+    (
+        "WPM Accuracy (2-WPM buckets)",
+        "Genauigkeit nach Tempo (2-WpM-Stufen)",
+    ),
+    ("Bucket", "Stufe"),
+    ("Accuracy by Pileup Size", "Genauigkeit nach Pileup-Größe"),
+    ("No QSOs with a pileup size yet", "Noch keine QSOs mit Pileup-Größe"),
+    ("Callers", "Anrufer"),
+    ("Stations calling when the QSO started", "Rufende Stationen zu Beginn des QSOs"),
+    ("Avg Copy Time", "Mittlere Aufnahmezeit"),
+    ("QSOs by Hour (UTC)", "QSOs pro Stunde (UTC)"),
+    ("No QSOs with a logged time yet", "Noch keine QSOs mit Logzeit"),
+    ("Hour", "Stunde"),
+    ("Points", "Punkte"),
+    (
+        "From the callers starting to your reply with a call",
+        "Vom Beginn der Anrufe bis zu deiner Antwort mit einem Rufzeichen",
+    ),
+    (
+        "SNR Accuracy (5 dB buckets)",
+        "Genauigkeit nach Störabstand (5-dB-Stufen)",
+    ),
+    (
+        "No QSOs with a signal-to-noise reading yet",
+        "Noch keine QSOs mit Störabstand",
+    ),
+    ("SNR", "SNR"),
+    (
+        "Caller's signal over the band noise in the receive filter, approximate",
+        "Signal des Anrufers über dem Bandrauschen im Empfangsfilter, ungefähr",
+    ),
+    ("Total", "Gesamt"),
+    ("Correct", "Korrekt"),
+    ("Character Error Analysis", "Zeichenfehler-Analyse"),
+    (
+        "Not enough data for character analysis",
+        "Nicht genug Daten für die Zeichenanalyse",
+    ),
+    (
+        "Characters with highest error rates:",
+        "Zeichen mit den höchsten Fehlerraten:",
+    ),
+    ("Char", "Zeichen"),
+    ("Error Rate", "Fehlerrate"),
+    ("Samples", "Anzahl"),
+    ("[space]", "[Leerzeichen]"),
+    ("Recent QSOs", "Letzte QSOs"),
+    ("WPM", "WpM"),
+    ("Result", "Ergebnis"),
+    ("Counts", "Zählt"),
+    ("Note", "Notiz"),
+    ("flagged", "markiert"),
+    ("Flagged only", "Nur markierte"),
+    ("No flagged QSOs", "Keine markierten QSOs"),
+    (
+        "Untick if this QSO should not count",
+        "Abwählen, wenn dieses QSO nicht zählen soll",
+    ),
+    ("told you {} last time", "letztes Mal: {}"),
+    (
+        "AGN: C=callsign, X=exchange | ok=correct with AGN",
+        "AGN: C=Rufzeichen, X=Austausch | ok=korrekt mit AGN",
+    ),
+    // QSO log window
+    ("QSO Log", "QSO-Log"),
+    ("Search:", "Suche:"),
+    ("callsign", "Rufzeichen"),
+    ("Errors only", "Nur Fehler"),
+    ("AGN used", "Mit AGN"),
+    ("Clear Filters", "Filter zurücksetzen"),
+    ("Page {} of {}", "Seite {} von {}"),
+    ("{} of {} QSOs", "{} von {} QSOs"),
+    ("No QSOs match", "Keine passenden QSOs"),
+    ("Time", "Zeit"),
+    ("Sent", "Gesendet"),
+    ("Sent: {}", "Gesendet: {}"),
+    ("yes", "ja"),
+    // Wide pileup receiver
+    (
+        "Ctrl+Left/Right to tune, Ctrl+Down to clear",
+        "Strg+Links/Rechts zum Abstimmen, Strg+Runter zum Löschen",
+    ),
+    ("Filter:", "Filter:"),
+    ("Recovered:", "Herausgeholt:"),
+    ("{} of {}", "{} von {}"),
+    (
+        "Callers worked out of those heard calling",
+        "Gearbeitete Anrufer von allen, die gerufen haben",
+    ),
+    ("Wide Pileup Recovered:", "Breites Pileup herausgeholt:"),
+    ("Frequency Fights:", "Frequenzkämpfe:"),
+    ("{} ({} QSYs)", "{} ({} QSYs)"),
+    (
+        "Stations that started calling CQ on your frequency, and the times you moved off",
+        "Stationen, die auf deiner Frequenz CQ gerufen haben, und wie oft du ausgewichen bist",
+    ),
+    ("Time Lost to Fights:", "Durch Kämpfe verlorene Zeit:"),
+    ("Caller Density", "Anruferdichte"),
+    (
+        "Station probability now {}, between {} and {} this session",
+        "Stationswahrscheinlichkeit jetzt {}, in dieser Sitzung zwischen {} und {}",
+    ),
+    ("{} min", "{} Min."),
+    (
+        "Time sharing your frequency plus time finding a new one",
+        "Zeit auf der geteilten Frequenz plus Zeit für die Suche nach einer neuen",
+    ),
+    // Past exports window
+    ("Past Exports", "Frühere Exporte"),
+    ("Refresh", "Aktualisieren"),
+    ("Oldest first", "Älteste zuerst"),
+    ("Newest first", "Neueste zuerst"),
+    (
+        "Couldn't read the export directory: {}",
+        "Exportverzeichnis konnte nicht gelesen werden: {}",
+    ),
+    (
+        "No exported reports yet. Export Markdown or JSON from Session Stats.",
+        "Noch keine exportierten Berichte. Exportiere Markdown oder JSON in der Sitzungsstatistik.",
+    ),
+    ("Exported", "Exportiert"),
+    ("Format", "Format"),
+    ("QSOs", "QSOs"),
+    ("Change", "Änderung"),
+    (
+        "Correct QSOs compared with the export before",
+        "Korrekte QSOs im Vergleich zum vorherigen Export",
+    ),
+    ("Call / Exch", "Ruf / Austausch"),
+    ("unreadable", "nicht lesbar"),
+    // Session recovery prompt
+    ("Restore Session?", "Sitzung wiederherstellen?"),
+    (
+        "The last session was not ended with Reset Stats. Autosaved {}: {} QSOs, {} points.",
+        "Die letzte Sitzung wurde nicht mit Statistik zurücksetzen beendet. Automatisch gespeichert {}: {} QSOs, {} Punkte.",
+    ),
+    ("Restore", "Wiederherstellen"),
+    ("Start fresh", "Neu beginnen"),
+    ("Are You Still There?", "Bist du noch da?"),
+    (
+        "Paused after {} minutes without input. Callers, timing and rate are on hold, so the time away doesn't count against your stats.",
+        "Nach {} Minuten ohne Eingabe pausiert. Anrufer, Zeitmessung und Rate ruhen, damit die Abwesenheit nicht in deine Statistik einfließt.",
+    ),
+    ("I'm Back", "Bin wieder da"),
+    // Session summary
+    ("Score: {}/100", "Wertung: {}/100"),
+    ("{} QSOs, {} correct, {}/hr", "{} QSOs, {} korrekt, {}/h"),
+    (
+        "Repeats asked on {} of QSOs",
+        "Wiederholung erbeten bei {} der QSOs",
+    ),
+    ("Tips", "Tipps"),
+    ("Personal Bests", "Persönliche Bestwerte"),
+    ("Best 10-minute rate", "Beste 10-Minuten-Rate"),
+    ("Accuracy at 30+ WPM", "Genauigkeit ab 30 WpM"),
+    ("Longest clean streak", "Längste fehlerfreie Serie"),
+    ("New PB!", "Neuer Bestwert!"),
+    ("best {}", "Bestwert {}"),
+    (
+        "Your callsign errors cluster on {}-character calls ({}% wrong vs {}% overall)",
+        "Deine Rufzeichenfehler häufen sich bei {}-stelligen Rufzeichen ({}% falsch gegenüber {}% insgesamt)",
+    ),
+    (", most often missing {}", ", am häufigsten fehlt {}"),
+    (
+        "; try a pileup audio drill at {} WPM.",
+        "; probiere eine Pileup-Audioübung mit {} WPM.",
+    ),
+    (
+        "Accuracy drops to {}% with callers at {} WPM; cap the caller WPM range around {} and work up from there.",
+        "Die Genauigkeit sinkt auf {}% bei Anrufern mit {} WPM; begrenze das Anrufertempo auf etwa {} und steigere dich von dort.",
+    ),
+    (
+        "Most exchange errors were in {} ({} QSOs).",
+        "Die meisten Austauschfehler betrafen {} ({} QSOs).",
+    ),
+    (
+        "Try the Serial Numbers drill.",
+        "Probiere die Übung Seriennummern.",
+    ),
+    (
+        "You asked for repeats on {}% of QSOs; a partial query (F5) often gets the call quicker than AGN.",
+        "Bei {}% der QSOs hast du um Wiederholung gebeten; eine Teilabfrage (F5) bringt das Rufzeichen oft schneller als AGN.",
+    ),
+    (
+        "{} times you logged another station that answered your partial query; make sure of the whole call before sending the exchange.",
+        "{} Mal hast du eine andere Station geloggt, die auf deine Teilabfrage geantwortet hat; sichere das ganze Rufzeichen, bevor du den Austausch sendest.",
+    ),
+    (
+        "{} QSOs were logged with pre-filled boxes you never checked; Tab through them before logging.",
+        "{} QSOs wurden mit ungeprüften vorausgefüllten Feldern geloggt; gehe sie vor dem Loggen mit Tab durch.",
+    ),
+    (
+        "{} times every call you sent the station was wrong; fix the call and send it again (F5) before the TU.",
+        "{}-mal war jedes Rufzeichen, das du der Station gesendet hast, falsch; korrigiere es und sende es vor dem TU erneut (F5).",
+    ),
+    (
+        "{} QSOs were logged before the caller finished the exchange ({} right anyway); wait for the last character, a guess that works in practice costs you in a contest.",
+        "{} QSOs wurden geloggt, bevor der Anrufer den Austausch beendet hatte ({} trotzdem richtig); warte auf das letzte Zeichen, ein Raten, das im Training klappt, kostet dich im Contest.",
+    ),
+    (
+        "{} doubles ended with a garbled call; when callers double, ask again (F8) rather than guessing.",
+        "{} Doppelanrufe endeten mit einem verstümmelten Rufzeichen; frage bei Doppelanrufen nach (F8), statt zu raten.",
+    ),
+    (
+        "You averaged {} from TU to the next CQ; call CQ as soon as the TU ends to keep the rate up.",
+        "Von TU bis zum nächsten CQ vergingen im Schnitt {}; rufe CQ direkt nach dem TU, um die Rate hoch zu halten.",
+    ),
+    (
+        "No weak spots stood out. Raise the caller WPM range or Max Callers for more of a challenge.",
+        "Keine Schwächen aufgefallen. Erhöhe das Anrufertempo oder die Anruferzahl für mehr Herausforderung.",
+    ),
+    // Trophies window
+    ("Trophies", "Trophäen"),
+    ("{} of {} earned", "{} von {} erreicht"),
+    ("Earned {}", "Erreicht am {}"),
+    ("Not yet", "Noch nicht"),
+    ("{}/{} QSOs", "{}/{} QSOs"),
+    ("{}/{} sections", "{}/{} Sections"),
+    ("First 100 QSOs", "Die ersten 100 QSOs"),
+    ("Clean Sweep", "Clean Sweep"),
+    ("Speed Copy", "Tempo-Mitschrift"),
+    ("Pileup Survivor", "Pileup überstanden"),
+    ("Log 100 QSOs", "Logge 100 QSOs"),
+    (
+        "Log every ARRL and RAC section correctly",
+        "Logge jede ARRL- und RAC-Section richtig",
+    ),
+    ("Copy a caller at 40 WPM or faster", "Nimm einen Anrufer mit 40 WpM oder schneller auf"),
+    (
+        "Log a QSO right with 10 or more stations calling",
+        "Logge ein QSO richtig, während 10 oder mehr Stationen rufen",
+    ),
+    ("Achievement unlocked: {}", "Erfolg freigeschaltet: {}"),
+    // Milestone alerts
+    ("{} QSOs", "{} QSOs"),
+    (
+        "New best rate: {}/hr over 10 minutes",
+        "Neue Bestrate: {}/h über 10 Minuten",
+    ),
+    (
+        "Accuracy down to {} over the last {} QSOs; slow down and copy",
+        "Genauigkeit bei {} in den letzten {} QSOs; werde langsamer und hör genau hin",
+    ),
+    // Audio diagnostics window
+    ("Audio Diagnostics", "Audiodiagnose"),
+    ("Callbacks:", "Callbacks:"),
+    ("Callback load:", "Callback-Last:"),
+    (
+        "Time spent filling each buffer, as a share of the time it plays; last and highest",
+        "Zeit zum Füllen jedes Puffers im Verhältnis zu seiner Spieldauer; zuletzt und höchste",
+    ),
+    ("Underruns:", "Unterläufe:"),
+    ("Delayed events:", "Verzögerte Ereignisse:"),
+    ("Queued commands:", "Wartende Befehle:"),
+    ("Dropped commands:", "Verworfene Befehle:"),
+    (
+        "No sound card open, so there is no callback to time",
+        "Keine Soundkarte geöffnet, daher gibt es keinen Callback zu messen",
+    ),
+    // Skimmer window
+    ("Accuracy:", "Genauigkeit:"),
+    (
+        "Share of spots with the call decoded right",
+        "Anteil der Spots mit richtig dekodiertem Rufzeichen",
+    ),
+    ("Delay (ms):", "Verzögerung (ms):"),
+    (
+        "How long after the call ends the spot appears",
+        "Wie lange nach dem Rufzeichen der Spot erscheint",
+    ),
+    (
+        "Spots can be wrong: check every call by ear",
+        "Spots können falsch sein: jedes Rufzeichen nach Gehör prüfen",
+    ),
+    ("No spots yet", "Noch keine Spots"),
+    ("Offset", "Ablage"),
+    ("Age", "Alter"),
+    ("{} s", "{} s"),
+    // Settings sections
+    ("User Settings", "Benutzereinstellungen"),
+    ("Language:", "Sprache:"),
+    ("Theme:", "Farbschema:"),
+    ("Accent:", "Akzent:"),
+    ("Layout:", "Layout:"),
+    ("UI Font:", "Schriftart:"),
+    ("Entry Font:", "Eingabeschrift:"),
+    ("Speech Command:", "Sprachausgabe:"),
+    ("Autosave Every:", "Automatisch speichern alle:"),
+    ("Pause When Idle:", "Pause bei Untätigkeit nach:"),
+    ("Milestone Alerts:", "Meilenstein-Hinweise:"),
+    ("Banner", "Banner"),
+    ("Chime", "Klang"),
+    ("Every:", "Alle:"),
+    ("New best 10-minute rate", "Neue beste 10-Minuten-Rate"),
+    ("Accuracy Below:", "Genauigkeit unter:"),
+    ("Contest Settings", "Contest-Einstellungen"),
+    ("Active Contest", "Aktiver Contest"),
+    ("Message Macros", "Textbausteine"),
+    ("Simulation Settings", "Simulationseinstellungen"),
+    ("Caller Geography", "Herkunft der Anrufer"),
+    ("Synthetic Callsigns", "Erzeugte Rufzeichen"),
+    ("Reloaded {}", "{} neu geladen"),
+    // Data updates
+    ("Check for Data Updates", "Nach Datenaktualisierungen suchen"),
+    ("Checking for data updates...", "Suche nach Datenaktualisierungen..."),
+    (
+        "No download addresses are set for data updates",
+        "Für Datenaktualisierungen sind keine Download-Adressen eingetragen",
+    ),
+    ("{} updated", "{} aktualisiert"),
+    ("Couldn't update {}: {}", "{} konnte nicht aktualisiert werden: {}"),
+    ("Data Updates", "Datenaktualisierungen"),
+    ("{} (downloaded)", "{} (heruntergeladen)"),
+    ("{} (built in)", "{} (eingebaut)"),
+    ("downloaded {}", "heruntergeladen am {}"),
+    ("the callsign file in Settings", "die Rufzeichendatei aus den Einstellungen"),
+    ("CWT callsign file format", "Format der CWT-Rufzeichendatei"),
+    (
+        "Used in place of the bundled cwt_callsigns.txt; leave blank to skip",
+        "Ersetzt die mitgelieferte cwt_callsigns.txt; leer lassen zum Überspringen",
+    ),
+    (
+        "Download with File > Check for Data Updates",
+        "Herunterladen mit Datei > Nach Datenaktualisierungen suchen",
+    ),
+    // Callsign file check
+    ("Test file", "Datei prüfen"),
+    (
+        "Read the file as this contest would and show what it found",
+        "Liest die Datei wie dieser Contest und zeigt, was gefunden wurde",
+    ),
+    ("Couldn't read the file: {}", "Datei konnte nicht gelesen werden: {}"),
+    ("{} usable entries, {} lines skipped", "{} brauchbare Einträge, {} Zeilen übersprungen"),
+    (
+        "Nothing usable; the built-in calls will be used",
+        "Nichts Brauchbares; die eingebauten Rufzeichen werden verwendet",
+    ),
+    ("Line {}: {} ({})", "Zeile {}: {} ({})"),
+    ("...and {} more", "...und {} weitere"),
+    ("Sample caller: {} {}", "Beispiel-Anrufer: {} {}"),
+    // Notifications
+    ("Warnings and errors", "Warnungen und Fehler"),
+    ("Notifications", "Meldungen"),
+    ("Nothing to report", "Keine Meldungen"),
+    ("Clear", "Leeren"),
+    ("({} times)", "({}-mal)"),
+    (
+        "No audio output, running silently: {}",
+        "Keine Audioausgabe, es läuft ohne Ton: {}",
+    ),
+    (
+        "Koch lesson {} mastered; lesson {} adds {}",
+        "Koch-Lektion {} gemeistert; Lektion {} bringt {} dazu",
+    ),
+    (
+        "Couldn't save Koch course progress: {}",
+        "Koch-Kursfortschritt konnte nicht gespeichert werden: {}",
+    ),
+    (
+        "Couldn't save personal bests: {}",
+        "Persönliche Bestwerte konnten nicht gespeichert werden: {}",
+    ),
+    (
+        "Couldn't save achievements: {}",
+        "Erfolge konnten nicht gespeichert werden: {}",
+    ),
+    ("Quick drill hotkey not available: {}", "Schnellübungs-Tastenkürzel nicht verfügbar: {}"),
+    ("Custom contest not loaded: {}", "Eigener Contest nicht geladen: {}"),
+    (
+        "The audio stopped responding; the session was stopped",
+        "Das Audio reagiert nicht mehr; die Sitzung wurde angehalten",
+    ),
+    ("Couldn't autosave the session: {}", "Sitzung konnte nicht automatisch gesichert werden: {}"),
+    ("Couldn't save settings: {}", "Einstellungen konnten nicht gespeichert werden: {}"),
+    (
+        "Couldn't load {}; using {}",
+        "{} konnte nicht geladen werden; verwendet werden {}",
+    ),
+    ("the built-in calls", "die eingebauten Rufzeichen"),
+    ("the whole roster", "alle Mitglieder der Liste"),
+    ("Lids and Bad Behavior", "Lids und schlechtes Benehmen"),
+    ("Background Signals", "Hintergrundsignale"),
+    ("Frequency Fights", "Frequenzkämpfe"),
+    ("Audio Settings", "Audioeinstellungen"),
+    ("Share Settings", "Einstellungen teilen"),
+    // Copy drills window
+    ("Copy Drills", "Hörübungen"),
+    ("Drill:", "Übung:"),
+    ("Conversational", "Gespräch"),
+    ("Serial Numbers", "Seriennummern"),
+    ("Recordings", "Aufnahmen"),
+    ("Same Call?", "Gleiches Rufzeichen?"),
+    ("Call Twice", "Zweimal gerufen"),
+    ("Koch Course", "Koch-Kurs"),
+    ("Abbreviations", "Abkürzungen"),
+    (
+        "Short ragchew phrases (names, QTHs, rigs, weather) for head-copy practice",
+        "Kurze Plauderfloskeln (Namen, QTHs, Geräte, Wetter) zum Mitlesen im Kopf",
+    ),
+    (
+        "Serial numbers with cut numbers (T=0, N=9, A=1); type the digits you copy",
+        "Seriennummern mit gekürzten Ziffern (T=0, N=9, A=1); tippe die aufgenommenen Ziffern",
+    ),
+    (
+        "Your own WAV recordings, such as real pileups, with an answer key; copy the calls in any order",
+        "Deine eigenen WAV-Aufnahmen, etwa echte Pileups, mit Lösungsschlüssel; nimm die Rufzeichen in beliebiger Reihenfolge auf",
+    ),
+    (
+        "A call sent twice, often one dit or dah off the second time; type S if it was the same call, D if not",
+        "Ein Rufzeichen zweimal gesendet, beim zweiten Mal oft um einen Punkt oder Strich verändert; tippe S, wenn es dasselbe war, sonst D",
+    ),
+    (
+        "A station sends its call and repeats it: type S or D for same or different, then the call (the repeat is right)",
+        "Eine Station sendet ihr Rufzeichen und wiederholt es: tippe S oder D für gleich oder verschieden, dann das Rufzeichen (die Wiederholung stimmt)",
+    ),
+    (
+        "Learn the characters one at a time: groups of the lesson's characters at full speed with extra space between them; 90% copy opens the next lesson",
+        "Lerne die Zeichen einzeln: Gruppen aus den Zeichen der Lektion mit vollem Tempo und mehr Abstand dazwischen; 90 % richtig öffnet die nächste Lektion",
+    ),
+    (
+        "Abbreviations contests and QSOs are built from (TU, 73, HW?, QRZ, AGN, B4): copy a run of them, or pick the one sent from four",
+        "Abkürzungen, aus denen Contests und QSOs bestehen (TU, 73, HW?, QRZ, AGN, B4): nimm eine Reihe davon auf oder wähle die gesendete aus vier",
+    ),
+    ("Cut Number Probability:", "Wahrscheinlichkeit gekürzter Ziffern:"),
+    (
+        "Chance each 0, 9 or 1 is sent as T, N or A",
+        "Chance, dass jede 0, 9 oder 1 als T, N oder A gesendet wird",
+    ),
+    ("Multiple Choice", "Auswahl"),
+    (
+        "Send one abbreviation and pick it from four, instead of copying a run of them",
+        "Eine Abkürzung senden und aus vier auswählen, statt eine Reihe aufzunehmen",
+    ),
+    ("Load a recordings folder first", "Lade zuerst einen Aufnahmeordner"),
+    ("Play Next", "Nächstes abspielen"),
+    ("Replay", "Wiederholen"),
+    ("{} WPM", "{} WpM"),
+    ("Same or different?", "Gleich oder verschieden?"),
+    (
+        "Type S (same) or D (different), then press Enter",
+        "Tippe S (gleich) oder D (verschieden) und drücke Enter",
+    ),
+    ("Call:", "Rufzeichen:"),
+    (
+        "Type the call as repeated, then press Enter",
+        "Tippe das Rufzeichen wie wiederholt und drücke Enter",
+    ),
+    ("Which was sent?", "Was wurde gesendet?"),
+    (
+        "Type its number or click it, then press Enter",
+        "Tippe die Nummer oder klicke darauf und drücke Enter",
+    ),
+    ("Copy:", "Aufnahme:"),
+    ("Type what you heard, then press Enter", "Tippe, was du gehört hast, und drücke Enter"),
+    ("Sent:", "Gesendet:"),
+    ("Answer:", "Lösung:"),
+    ("Copied:", "Aufgenommen:"),
+    ("Speed:", "Tempo:"),
+    ("Words", "Wörter"),
+    ("Digits", "Ziffern"),
+    ("Answers", "Antworten"),
+    ("Steps", "Schritte"),
+    ("Characters", "Zeichen"),
+    ("Meaning:", "Bedeutung:"),
+    ("Attempts:", "Versuche:"),
+    ("Perfect Copies:", "Fehlerfrei:"),
+    ("{} Accuracy:", "Genauigkeit {}:"),
+    ("Cut Digits:", "Gekürzte Ziffern:"),
+    ("Character", "Zeichen"),
+    ("Digit", "Ziffer"),
+    ("Abbreviation", "Abkürzung"),
+    ("Reset Drill Stats", "Übungsstatistik zurücksetzen"),
+    ("Same/Different Right:", "Gleich/verschieden richtig:"),
+    ("Calls Right:", "Rufzeichen richtig:"),
+    ("Busts Missed:", "Fehler übersehen:"),
+    (
+        "Different calls you took for the same one",
+        "Verschiedene Rufzeichen, die du für dasselbe gehalten hast",
+    ),
+    ("False Alarms:", "Falscher Alarm:"),
+    (
+        "The same call twice that you took for different ones",
+        "Zweimal dasselbe Rufzeichen, das du für verschiedene gehalten hast",
+    ),
+    ("Character WPM:", "Zeichentempo:"),
+    (
+        "Each character is sent this fast, so it is learned by its sound",
+        "Jedes Zeichen wird so schnell gesendet, damit du es am Klang lernst",
+    ),
+    ("Effective WPM:", "Effektives Tempo:"),
+    (
+        "Overall speed; the gaps between characters are stretched to match",
+        "Gesamttempo; die Pausen zwischen den Zeichen werden passend gedehnt",
+    ),
+    ("Lesson:", "Lektion:"),
+    (
+        "Lessons open as each one before is mastered",
+        "Lektionen öffnen sich, sobald die vorige gemeistert ist",
+    ),
+    ("New in this lesson", "Neu in dieser Lektion"),
+    ("Course Progress", "Kursfortschritt"),
+    ("No lessons practiced yet", "Noch keine Lektion geübt"),
+    ("Lesson", "Lektion"),
+    ("New", "Neu"),
+    ("Items", "Aufgaben"),
+    ("Best", "Bestwert"),
+    ("Mastered", "Gemeistert"),
+    ("Folder:", "Ordner:"),
+    ("None loaded", "Nichts geladen"),
+    ("Load Recordings...", "Aufnahmen laden..."),
+    (
+        "Pick a folder of WAV files with an answer-key.txt, laid out like an exported audio drill",
+        "Wähle einen Ordner mit WAV-Dateien und einer answer-key.txt, aufgebaut wie eine exportierte Audioübung",
+    ),
+    ("{} recordings", "{} Aufnahmen"),
+    ("Export Audio Drill", "Audioübung exportieren"),
+    (
+        "Save recordings as WAV files with an answer key, for practice away from the computer",
+        "Aufnahmen als WAV-Dateien mit Lösungsschlüssel speichern, zum Üben ohne Computer",
+    ),
+    ("Recordings:", "Aufnahmen:"),
+    ("Exchanges", "Austausche"),
+    ("Count:", "Anzahl:"),
+    ("Max Callers:", "Max. Anrufer:"),
+    (
+        "Each recording has between one and this many callers",
+        "Jede Aufnahme hat zwischen einem und so vielen Anrufern",
+    ),
+    ("Noise Level:", "Rauschpegel:"),
+    ("Export", "Exportieren"),
+    // Main panel controls
+    ("CQ", "CQ"),
+    ("TU", "TU"),
+    ("AGN", "AGN"),
+    ("Contest:", "Contest:"),
+    ("Sandbox", "Sandkasten"),
+    ("Caller:", "Anrufer:"),
+    ("RIT:", "RIT:"),
+    (" Hz", " Hz"),
+    ("QSOs:", "QSOs:"),
+    ("Rate:", "Rate:"),
+    ("UTC:", "UTC:"),
+    ("S-Meter:", "S-Meter:"),
+    ("Pileup:", "Pileup:"),
+    ("Status:", "Status:"),
+    ("OK", "OK"),
+    (
+        "Receiver audio {}-{} Hz; the center line is your pitch",
+        "Empfängeraudio {}-{} Hz; die Mittellinie ist deine Tonhöhe",
+    ),
+    // User settings
+    ("Your Callsign:", "Dein Rufzeichen:"),
+    ("Your WPM:", "Dein Tempo:"),
+    ("Font Size:", "Schriftgröße:"),
+    (
+        "Font for the call and exchange boxes and the status line. A monospace font keeps 0/O and 1/I easy to tell apart at speed",
+        "Schrift für die Rufzeichen- und Austauschfelder und die Statuszeile. Mit einer Festbreitenschrift bleiben 0/O und 1/I auch bei Tempo gut unterscheidbar",
+    ),
+    ("AGN Message:", "AGN-Text:"),
+    ("TU Message:", "TU-Text:"),
+    (
+        "Sent with F3. {MYCALL} is replaced with your callsign; prosigns can be entered as <SK>, <KN>, <BK>, <AR>. Separate alternatives with | to vary it (TU {MYCALL}|R TU|73 {MYCALL})",
+        "Wird mit F3 gesendet. {MYCALL} wird durch dein Rufzeichen ersetzt; Betriebszeichen kannst du als <SK>, <KN>, <BK>, <AR> eingeben. Trenne Varianten mit |, um abzuwechseln (TU {MYCALL}|R TU|73 {MYCALL})",
+    ),
+    ("Show Status Line", "Statuszeile anzeigen"),
+    ("Show S-Meter", "S-Meter anzeigen"),
+    ("Signal strength of the receiver audio", "Signalstärke des Empfängeraudios"),
+    ("Show Pileup Depth", "Pileup-Tiefe anzeigen"),
+    (
+        "How many stations are waiting to call; a learning aid, turn it off to practice by ear",
+        "Wie viele Stationen rufen wollen; eine Lernhilfe, schalte sie aus, um nach Gehör zu üben",
+    ),
+    ("Caller Speed Readout:", "Tempo des Anrufers:"),
+    ("Off", "Aus"),
+    ("After the QSO", "Nach dem QSO"),
+    ("Live", "Laufend"),
+    (
+        "Show the caller's speed and offset from your pitch, on the Last QSO line or while you work them, to calibrate your sense of speed",
+        "Zeigt Tempo und Ablage des Anrufers von deiner Tonhöhe, in der Zeile Letztes QSO oder während du ihn arbeitest, um dein Tempogefühl zu eichen",
+    ),
+    ("Show Waterfall", "Wasserfall anzeigen"),
+    (
+        "Scrolling spectrum of the receiver audio around your pitch",
+        "Laufendes Spektrum des Empfängeraudios um deine Tonhöhe",
+    ),
+    ("Dark", "Dunkel"),
+    ("Light", "Hell"),
+    ("High Contrast", "Hoher Kontrast"),
+    ("Colorblind", "Farbenblind"),
+    (
+        "Colorblind shows right/wrong in blue/orange instead of green/red",
+        "Farbenblind zeigt richtig/falsch in Blau/Orange statt Grün/Rot",
+    ),
+    ("Blue", "Blau"),
+    ("Green", "Grün"),
+    ("Orange", "Orange"),
+    ("Purple", "Lila"),
+    ("Teal", "Türkis"),
+    (
+        "Compact is one row for running beside a logger; Expanded adds a side panel. Also under View in the menu bar.",
+        "Kompakt ist eine Zeile für den Betrieb neben einem Logprogramm; Erweitert fügt eine Seitenleiste hinzu. Auch unter Ansicht in der Menüleiste.",
+    ),
+    ("Always on Top", "Immer im Vordergrund"),
+    ("Keep the trainer above other windows", "Den Trainer über anderen Fenstern halten"),
+    ("Quick Drill Hotkey", "Tastenkürzel für Schnellübung"),
+    (
+        "Bind a key in your desktop's keyboard shortcut settings to run `contest_trainer quick-drill`; it starts a drill, or stops it if one is open, even while another program has focus",
+        "Lege in den Tastenkürzel-Einstellungen deines Desktops eine Taste auf `contest_trainer quick-drill`; sie startet eine Übung oder beendet eine offene, auch wenn ein anderes Programm den Fokus hat",
+    ),
+    ("Show Main Field Hints", "Hinweise im Hauptfeld anzeigen"),
+    ("Pre-fill Exchange", "Austausch vorausfüllen"),
+    (
+        "Fill the exchange from the call-history file when you enter a known call. Pre-filled fields stay gray until you move into them to check.",
+        "Füllt den Austausch aus der Call-History-Datei, wenn du ein bekanntes Rufzeichen eingibst. Vorausgefüllte Felder bleiben grau, bis du zum Prüfen hineinwechselst.",
+    ),
+    ("Strict Logging", "Strenges Loggen"),
+    (
+        "Enter won't log until the caller has finished sending the exchange and every exchange box is filled, to build the habit of copying it all",
+        "Enter loggt erst, wenn der Anrufer den Austausch fertig gesendet hat und jedes Austauschfeld gefüllt ist, damit du dir angewöhnst, alles aufzunehmen",
+    ),
+    ("Warn When Logging Early", "Bei zu frühem Loggen warnen"),
+    (
+        "Point it out when you log before the caller has finished sending the exchange. Such QSOs are counted as Logged Early in the stats either way.",
+        "Weist darauf hin, wenn du loggst, bevor der Anrufer den Austausch fertig gesendet hat. Solche QSOs zählen in der Statistik so oder so als zu früh geloggt.",
+    ),
+    (
+        "Run with each status announcement (station calling, exchange received, QSO logged) as its last argument, e.g. espeak-ng, say or notify-send. Leave empty to only update the screen reader.",
+        "Wird bei jeder Statusansage (Station ruft, Austausch empfangen, QSO geloggt) mit der Ansage als letztem Argument ausgeführt, z. B. espeak-ng, say oder notify-send. Leer lassen, um nur den Bildschirmleser zu aktualisieren.",
+    ),
+    (
+        "Save the session after this many QSOs so it can be restored after a crash or an accidental close. 0 turns autosave off.",
+        "Sichert die Sitzung nach so vielen QSOs, damit sie nach einem Absturz oder versehentlichem Schließen wiederhergestellt werden kann. 0 schaltet das automatische Sichern aus.",
+    ),
+    (" QSOs", " QSOs"),
+    (" min", " Min."),
+    (
+        "Pause the session after this many minutes without a key press or click, so time away isn't counted. 0 never pauses.",
+        "Pausiert die Sitzung nach so vielen Minuten ohne Tastendruck oder Klick, damit die Abwesenheit nicht zählt. Bei 0 wird nie pausiert.",
+    ),
+    ("Show a short note over the main window", "Eine kurze Notiz über dem Hauptfenster zeigen"),
+    (
+        "Play a short chime on the UI sounds bus; it waits until you finish sending and never mutes the callers",
+        "Spielt einen kurzen Klang auf dem Kanal für Bedienklänge; er wartet, bis du fertig gesendet hast, und stummt die Anrufer nie",
+    ),
+    ("0 turns the QSO count alert off", "0 schaltet den Hinweis zur QSO-Zahl aus"),
+    (
+        "Once a session, when the rate over the last 10 minutes passes your personal best",
+        "Einmal pro Sitzung, wenn die Rate der letzten 10 Minuten deinen Bestwert übertrifft",
+    ),
+    (
+        "When fewer of the last 10 QSOs than this were logged right. 0 turns the accuracy alert off.",
+        "Wenn weniger der letzten 10 QSOs als dieser Wert richtig geloggt wurden. 0 schaltet den Genauigkeitshinweis aus.",
+    ),
+    ("Stats Export Directory:", "Ordner für Statistikexporte:"),
+    ("(current directory)", "(aktueller Ordner)"),
+    ("Browse...", "Durchsuchen..."),
+    // Contest settings
+    ("Contest Type:", "Contest-Art:"),
+    ("Suggest upcoming contests on launch", "Beim Start anstehende Contests vorschlagen"),
+    (
+        "Offer to switch to a contest that is running now or starts within 24 hours",
+        "Bietet an, zu einem Contest zu wechseln, der gerade läuft oder in den nächsten 24 Stunden beginnt",
+    ),
+    (
+        "Saved per contest. Tokens: {MYCALL} {HISCALL} {SERIAL} {EXCH}. Prosigns can be entered as <SK>, <KN>, <BK>, <AR>.",
+        "Pro Contest gespeichert. Platzhalter: {MYCALL} {HISCALL} {SERIAL} {EXCH}. Betriebszeichen kannst du als <SK>, <KN>, <BK>, <AR> eingeben.",
+    ),
+    ("e.g. {HISCALL} {EXCH}", "z. B. {HISCALL} {EXCH}"),
+    ("Override Cut Number Probability", "Eigene Wahrscheinlichkeit gekürzter Ziffern"),
+    (
+        "Use a different cut number probability for this contest",
+        "Für diesen Contest eine andere Wahrscheinlichkeit gekürzter Ziffern verwenden",
+    ),
+    ("Contest Cut Number Probability:", "Gekürzte Ziffern in diesem Contest:"),
+    ("Callers Send Your Call:", "Anrufer senden dein Rufzeichen:"),
+    (
+        "Probability a caller starts their exchange with your call, as in W1AW 5NN 05",
+        "Wahrscheinlichkeit, dass ein Anrufer seinen Austausch mit deinem Rufzeichen beginnt, wie in W1AW 5NN 05",
+    ),
+    ("Callers Sign with DE:", "Anrufer zeichnen mit DE:"),
+    (
+        "Probability a caller signs with DE and their call, as in DE K3LR 5NN 05",
+        "Wahrscheinlichkeit, dass ein Anrufer mit DE und seinem Rufzeichen zeichnet, wie in DE K3LR 5NN 05",
+    ),
+    ("Callers Sign Twice:", "Anrufer zeichnen doppelt:"),
+    (
+        "Probability a caller sends their own call twice before their exchange",
+        "Wahrscheinlichkeit, dass ein Anrufer vor seinem Austausch sein Rufzeichen zweimal sendet",
+    ),
+    ("Custom contest from {}", "Eigener Contest aus {}"),
+    ("Custom contests: .toml files in {}", "Eigene Contests: .toml-Dateien in {}"),
+    (
+        "See docs/ADDING_CONTESTS.md for the file format",
+        "Das Dateiformat steht in docs/ADDING_CONTESTS.md",
+    ),
+    ("Contest", "Contest"),
+    ("Your Exchange", "Dein Austausch"),
+    // Simulation settings
+    ("Max Simultaneous Stations:", "Max. gleichzeitige Stationen:"),
+    ("Station Probability:", "Stationswahrscheinlichkeit:"),
+    ("Dynamic Density", "Dynamische Dichte"),
+    (
+        "Fast, clean QSOs build the pileup and slow or busted ones thin it, starting from the Station Probability",
+        "Schnelle, saubere QSOs lassen das Pileup wachsen, langsame oder fehlerhafte dünnen es aus, ausgehend von der Stationswahrscheinlichkeit",
+    ),
+    ("Density Range:", "Dichtebereich:"),
+    ("min", "min"),
+    ("max", "max"),
+    ("Fast QSO (s):", "Schnelles QSO (s):"),
+    (
+        "A clean QSO this quick builds the pileup",
+        "Ein so schnelles, sauberes QSO lässt das Pileup wachsen",
+    ),
+    ("Slow QSO (s):", "Langsames QSO (s):"),
+    (
+        "A QSO longer than this thins the pileup, as does a bust",
+        "Ein längeres QSO dünnt das Pileup aus, ebenso ein Fehler",
+    ),
+    ("Build Step:", "Zuwachs:"),
+    (
+        "Station probability added by each fast, clean QSO",
+        "Stationswahrscheinlichkeit, die jedes schnelle, saubere QSO hinzufügt",
+    ),
+    ("Thin Step:", "Abnahme:"),
+    (
+        "Station probability taken off by each slow or busted QSO",
+        "Stationswahrscheinlichkeit, die jedes langsame oder fehlerhafte QSO abzieht",
+    ),
+    ("Filter Width (Hz):", "Filterbreite (Hz):"),
+    ("Wide Pileup", "Breites Pileup"),
+    (
+        "Spread callers well beyond the filter; tune them in with the RIT",
+        "Verteilt Anrufer weit über das Filter hinaus; stimme sie mit der RIT ab",
+    ),
+    ("Pileup Width (Hz):", "Pileup-Breite (Hz):"),
+    ("Receive Filter (Hz):", "Empfangsfilter (Hz):"),
+    ("Signal Strength Range:", "Bereich der Signalstärke:"),
+    ("Weak Signal (QRP) Preset", "Vorgabe schwache Signale (QRP)"),
+    (
+        "Callers a few dB over the noise with QSB on: signal strength 0.015-0.05, noise level at least 0.25, fading depth 0.6",
+        "Anrufer wenige dB über dem Rauschen mit QSB: Signalstärke 0,015-0,05, Rauschpegel mindestens 0,25, Fading-Tiefe 0,6",
+    ),
+    ("Reaction Time (ms):", "Reaktionszeit (ms):"),
+    (
+        "How soon on-time callers start after your CQ ends",
+        "Wie bald pünktliche Anrufer nach dem Ende deines CQ beginnen",
+    ),
+    ("Reply Gap (ms):", "Antwortpause (ms):"),
+    (
+        "How soon the station comes back after your exchange, AGN or partial query; picked at random within the range each time",
+        "Wie bald die Station nach deinem Austausch, AGN oder deiner Teilabfrage antwortet; jedes Mal zufällig im Bereich gewählt",
+    ),
+    ("Post-CQ Gap (ms):", "Pause nach CQ (ms):"),
+    (
+        "Pause after your CQ before callers start; their reaction time comes on top of it",
+        "Pause nach deinem CQ, bevor Anrufer beginnen; ihre Reaktionszeit kommt noch dazu",
+    ),
+    ("Operator Tempo:", "Betriebstempo:"),
+    ("Fast (CWT)", "Schnell (CWT)"),
+    ("Relaxed (DX)", "Gemütlich (DX)"),
+    (
+        "Set the reaction time and gaps above for this pace",
+        "Setzt Reaktionszeit und Pausen oben auf dieses Tempo",
+    ),
+    ("Tailgater Probability:", "Wahrscheinlichkeit für Drängler:"),
+    (
+        "Probability that a caller starts before your CQ has finished",
+        "Wahrscheinlichkeit, dass ein Anrufer beginnt, bevor dein CQ zu Ende ist",
+    ),
+    ("Late Caller Probability:", "Wahrscheinlichkeit für Nachzügler:"),
+    (
+        "Probability that a caller responds a second or more late",
+        "Wahrscheinlichkeit, dass ein Anrufer eine Sekunde oder mehr zu spät antwortet",
+    ),
+    ("Calls Twice Probability:", "Wahrscheinlichkeit für Doppelanruf:"),
+    (
+        "Probability that a caller sends their call twice",
+        "Wahrscheinlichkeit, dass ein Anrufer sein Rufzeichen zweimal sendet",
+    ),
+    ("Callers Give Up:", "Anrufer geben auf:"),
+    ("Silently", "Still"),
+    ("With a Final Call", "Mit letztem Anruf"),
+    ("With \"E E\"", "Mit \"E E\""),
+    (
+        "What a caller who runs out of patience sends as they leave (Session Stats counts them as Callers Lost)",
+        "Was ein Anrufer ohne Geduld beim Gehen sendet (die Sitzungsstatistik zählt ihn als verlorenen Anrufer)",
+    ),
+    ("Caller Needs Repeat Probability:", "Wahrscheinlichkeit für Wiederholungswunsch:"),
+    (
+        "Probability that a caller will request you repeat your exchange",
+        "Wahrscheinlichkeit, dass ein Anrufer dich um Wiederholung deines Austauschs bittet",
+    ),
+    ("Fill Request Probability:", "Wahrscheinlichkeit für Teilabfrage:"),
+    (
+        "Probability that a caller asks for one part of your exchange, such as NR? or SEC?; answer with Shift+F2",
+        "Wahrscheinlichkeit, dass ein Anrufer nach einem Teil deines Austauschs fragt, etwa NR? oder SEC?; antworte mit Umschalt+F2",
+    ),
+    ("Doubles Probability:", "Wahrscheinlichkeit für Doppler:"),
+    (
+        "Probability that two callers double: same pitch, same moment, so neither call copies cleanly (needs Max Callers of 2 or more)",
+        "Wahrscheinlichkeit, dass zwei Anrufer gleichzeitig rufen: gleiche Tonhöhe, gleicher Moment, sodass keins der Rufzeichen sauber ankommt (braucht Max. Anrufer von 2 oder mehr)",
+    ),
+    ("NIL Probability:", "NIL-Wahrscheinlichkeit:"),
+    (
+        "Probability that a caller you sent your exchange to, then dropped by calling CQ without logging them, logged you anyway: a NIL that costs two QSOs' points in the penalized score",
+        "Wahrscheinlichkeit, dass ein Anrufer, dem du deinen Austausch gesendet und den du dann ohne Loggen mit CQ fallen gelassen hast, dich trotzdem geloggt hat: ein NIL, das in der Wertung nach Abzügen die Punkte von zwei QSOs kostet",
+    ),
+    ("Require Sent Call", "Gesendetes Rufzeichen verlangen"),
+    (
+        "A QSO scores no points unless you sent the caller's call right at least once, as in a real QSO where they must know you have them",
+        "Ein QSO zählt nur, wenn du das Rufzeichen des Anrufers mindestens einmal richtig gesendet hast, wie im echten QSO, wo er wissen muss, dass du ihn hast",
+    ),
+    (
+        "Probability that a caller sends numbers as cut numbers (5NN, T03, A2)",
+        "Wahrscheinlichkeit, dass ein Anrufer Zahlen als gekürzte Ziffern sendet (5NN, T03, A2)",
+    ),
+    ("Courtesy Word Probability:", "Wahrscheinlichkeit für Höflichkeitswörter:"),
+    (
+        "Probability that a caller adds TU, EE, 73 or GL to their exchange (not part of the exchange, so don't log it)",
+        "Wahrscheinlichkeit, dass ein Anrufer TU, EE, 73 oder GL an seinen Austausch hängt (nicht Teil des Austauschs, also nicht loggen)",
+    ),
+    ("Weak Character Focus:", "Fokus auf schwache Zeichen:"),
+    (
+        "How strongly callers are picked for the characters you miscopy most this session (see Session Stats); 0 = off",
+        "Wie stark Anrufer nach den Zeichen gewählt werden, die du in dieser Sitzung am häufigsten falsch aufnimmst (siehe Sitzungsstatistik); 0 = aus",
+    ),
+    ("Call Match Threshold:", "Schwelle für Rufzeichenabgleich:"),
+    (
+        "How close a partial or busted call must be to a caller's call to select them. Characters that sound alike in CW (S/H, U/V, N/A) count as closer than others; 1.0 = exact calls only",
+        "Wie nah ein unvollständiges oder fehlerhaftes Rufzeichen dem eines Anrufers sein muss, um ihn auszuwählen. In CW ähnlich klingende Zeichen (S/H, U/V, N/A) zählen als näher als andere; 1,0 = nur exakte Rufzeichen",
+    ),
+    ("Out of Turn Caller:", "Anrufer außer der Reihe:"),
+    (
+        "Probability that a new station calls while the caller you're working sends their exchange",
+        "Wahrscheinlichkeit, dass eine neue Station ruft, während der Anrufer, den du arbeitest, seinen Austausch sendet",
+    ),
+    ("Calls Over QSO:", "Ruft ins QSO:"),
+    (
+        "Probability that a caller you didn't pick calls again while you work someone else",
+        "Wahrscheinlichkeit, dass ein nicht gewählter Anrufer erneut ruft, während du jemand anderen arbeitest",
+    ),
+    ("Continuous Caller:", "Dauerrufer:"),
+    (
+        "Probability that a new caller never listens and calls after every transmission",
+        "Wahrscheinlichkeit, dass ein neuer Anrufer nie zuhört und nach jeder Sendung ruft",
+    ),
+    ("Sends Your Call Wrong:", "Sendet dein Rufzeichen falsch:"),
+    (
+        "Probability that a caller sends your call with a mistake before their exchange",
+        "Wahrscheinlichkeit, dass ein Anrufer vor seinem Austausch dein Rufzeichen mit einem Fehler sendet",
+    ),
+    ("Events per Minute:", "Ereignisse pro Minute:"),
+    (
+        "How often something unrelated to your run is heard on frequency (0 = never)",
+        "Wie oft etwas, das nichts mit deinem Betrieb zu tun hat, auf der Frequenz zu hören ist (0 = nie)",
+    ),
+    ("Someone asks whether the frequency is in use", "Jemand fragt, ob die Frequenz belegt ist"),
+    ("Tune-Ups", "Abstimmträger"),
+    (
+        "Someone sends a brief carrier while tuning up",
+        "Jemand sendet beim Abstimmen kurz einen Träger",
+    ),
+    ("CQ on Frequency", "CQ auf der Frequenz"),
+    (
+        "Someone calls CQ on your frequency while you aren't running",
+        "Jemand ruft CQ auf deiner Frequenz, während du nicht rufst",
+    ),
+    (
+        "After a while running, a loud station may start calling CQ on your frequency. Hold on until they leave or QSY with Alt+Q.",
+        "Nach einer Weile Betrieb kann eine laute Station auf deiner Frequenz CQ rufen. Halte durch, bis sie geht, oder wechsle mit Alt+Q die Frequenz.",
+    ),
+    ("Run Before a Fight (min):", "Betrieb vor einem Kampf (Min.):"),
+    (
+        "How long you hold a frequency before anyone tries to take it",
+        "Wie lange du eine Frequenz hältst, bevor jemand versucht, sie zu übernehmen",
+    ),
+    ("Chance per Minute:", "Chance pro Minute:"),
+    (
+        "Chance each minute after that of someone turning up",
+        "Chance pro Minute danach, dass jemand auftaucht",
+    ),
+    ("Callers Heard:", "Gehörte Anrufer:"),
+    (
+        "Share of callers who still get through while someone is CQing over you",
+        "Anteil der Anrufer, die noch durchkommen, während jemand über dich CQ ruft",
+    ),
+    ("QSY Time (s):", "QSY-Dauer (s):"),
+    (
+        "How long finding a clear frequency takes after Alt+Q",
+        "Wie lange die Suche nach einer freien Frequenz nach Alt+Q dauert",
+    ),
+    ("Filter Callers by Country", "Anrufer nach Land filtern"),
+    (
+        "When enabled, controls how often callers are from your country",
+        "Wenn aktiv, steuert es, wie oft Anrufer aus deinem Land kommen",
+    ),
+    ("Same Country Probability:", "Wahrscheinlichkeit eigenes Land:"),
+    (
+        "Probability that a caller will be from the same country as you",
+        "Wahrscheinlichkeit, dass ein Anrufer aus deinem Land kommt",
+    ),
+    (
+        "Relative share of callers from each continent (from cty.dat)",
+        "Relativer Anteil der Anrufer je Kontinent (aus cty.dat)",
+    ),
+    ("North America", "Nordamerika"),
+    ("South America", "Südamerika"),
+    ("Europe", "Europa"),
+    ("Africa", "Afrika"),
+    ("Asia", "Asien"),
+    ("Oceania", "Ozeanien"),
+    (
+        "1.0 keeps every caller from this continent; lower values thin them out compared with the highest weight",
+        "1,0 behält jeden Anrufer von diesem Kontinent; kleinere Werte dünnen sie im Vergleich zum höchsten Gewicht aus",
+    ),
+    ("Reset", "Zurücksetzen"),
+    ("Generate Callsigns", "Rufzeichen erzeugen"),
+    (
+        "Make up calls from cty.dat prefixes instead of reading the callsign file, so there's no pool to memorize (CQ WPX and CQ WW only)",
+        "Erfindet Rufzeichen aus cty.dat-Präfixen, statt die Rufzeichendatei zu lesen, sodass es keinen Vorrat zum Auswendiglernen gibt (nur CQ WPX und CQ WW)",
+    ),
+    ("Suffix Letters:", "Buchstaben im Suffix:"),
+    (
+        "Relative share of calls with this many letters after the digit",
+        "Relativer Anteil der Rufzeichen mit so vielen Buchstaben nach der Ziffer",
+    ),
+    ("Portable Probability:", "Wahrscheinlichkeit für Portabel:"),
+    (
+        "Probability a call ends in /P, /QRP, /M or a call area such as /7",
+        "Wahrscheinlichkeit, dass ein Rufzeichen auf /P, /QRP, /M oder ein Rufzeichengebiet wie /7 endet",
+    ),
+    // Audio settings
+    ("Tone Frequency (Hz):", "Tonfrequenz (Hz):"),
+    ("Noise Bandwidth (Hz):", "Rauschbandbreite (Hz):"),
+    (
+        "Simulates receiver CW filter bandwidth",
+        "Bildet die Bandbreite des CW-Filters im Empfänger nach",
+    ),
+    ("Master Volume:", "Gesamtlautstärke:"),
+    ("UI Sounds Volume:", "Lautstärke der Bedienklänge:"),
+    (
+        "Level of the milestone chimes, apart from the CW",
+        "Pegel der Meilenstein-Klänge, getrennt von der CW",
+    ),
+    (
+        "Mute RX during TX (callers + noise)",
+        "Empfang beim Senden stummschalten (Anrufer + Rauschen)",
+    ),
+    ("QSK (full break-in)", "QSK (volles Mithören)"),
+    (
+        "Mute the receiver only while the key is down, so you hear callers between your dits and dahs",
+        "Den Empfänger nur bei gedrückter Taste stummschalten, damit du Anrufer zwischen deinen Punkten und Strichen hörst",
+    ),
+    (
+        "How long the receiver stays muted after each element; longer delays behave like semi break-in",
+        "Wie lange der Empfänger nach jedem Element stumm bleibt; längere Verzögerungen wirken wie Semi-Break-in",
+    ),
+    ("Mute sidetone during TX", "Mithörton beim Senden stummschalten"),
+    ("Spatial audio", "Räumlicher Klang"),
+    (
+        "Pan each caller left or right by how far it is from your pitch (needs stereo headphones)",
+        "Verteilt jeden Anrufer nach links oder rechts, je nach Abstand zu deiner Tonhöhe (braucht Stereokopfhörer)",
+    ),
+    ("Output:", "Ausgabe:"),
+    ("Sound card", "Soundkarte"),
+    ("None (silent)", "Keine (stumm)"),
+    (
+        "Where the audio goes. JACK plays through a running JACK server for routing to other programs. With no output the session still runs in real time, without sound",
+        "Wohin das Audio geht. JACK spielt über einen laufenden JACK-Server, um es an andere Programme weiterzuleiten. Ohne Ausgabe läuft die Sitzung trotzdem in Echtzeit, nur ohne Ton",
+    ),
+    ("Buffer Size:", "Puffergröße:"),
+    ("Default", "Standard"),
+    ("{} frames", "{} Frames"),
+    (
+        "Frames per audio callback. Raise this if CW sounds choppy; lower it for less delay",
+        "Frames pro Audio-Callback. Erhöhe den Wert, wenn CW abgehackt klingt; verringere ihn für weniger Verzögerung",
+    ),
+    ("Underruns: {}", "Aussetzer: {}"),
+    ("Sidetone Settings", "Mithörton"),
+    ("Sidetone matches tone frequency", "Mithörton folgt der Tonfrequenz"),
+    ("Sidetone Pitch (Hz):", "Tonhöhe des Mithörtons (Hz):"),
+    ("Sidetone Level:", "Pegel des Mithörtons:"),
+    ("Rise/Fall Time (ms):", "Anstiegs-/Abfallzeit (ms):"),
+    (
+        "Keying envelope; shorter sounds harder, longer sounds softer",
+        "Tasthüllkurve; kürzer klingt härter, länger weicher",
+    ),
+    ("Timbre:", "Klangfarbe:"),
+    ("Sine", "Sinus"),
+    ("Rich", "Voll"),
+    (
+        "Rich adds a little harmonic content so your signal sounds different from the callers",
+        "Voll fügt etwas Obertöne hinzu, damit sich dein Signal von den Anrufern abhebt",
+    ),
+    ("Static/QRN Settings", "Störungen/QRN"),
+    ("Crash Rate:", "Krachrate:"),
+    ("/sec", "/s"),
+    ("Static crashes per second", "Statische Entladungen pro Sekunde"),
+    ("Crash Intensity:", "Krachstärke:"),
+    ("Volume of static crashes", "Lautstärke der statischen Entladungen"),
+    ("Pop Rate:", "Knackrate:"),
+    ("Clicks/pops per second", "Klicks/Knackser pro Sekunde"),
+    ("Pop Intensity:", "Knackstärke:"),
+    ("Volume of pops/clicks", "Lautstärke der Knackser/Klicks"),
+    ("QRN Intensity:", "QRN-Stärke:"),
+    ("Atmospheric noise rumble", "Atmosphärisches Grollen"),
+    ("QSB (Fading) Settings", "QSB (Fading)"),
+    ("Enable QSB", "QSB aktivieren"),
+    ("Simulate signal fading on caller signals", "Fading der Anrufersignale nachbilden"),
+    ("Fade Depth:", "Fading-Tiefe:"),
+    (
+        "How much the signal fades (0 = none, 1 = full fade to silence)",
+        "Wie stark das Signal schwindet (0 = gar nicht, 1 = ganz bis zur Stille)",
+    ),
+    ("Fade Rate:", "Fading-Rate:"),
+    (" cpm", " /min"),
+    (
+        "Fading cycles per minute (higher = faster fading)",
+        "Fading-Zyklen pro Minute (höher = schnelleres Fading)",
+    ),
+    ("AGC Settings", "AGC"),
+    ("Fast", "Schnell"),
+    ("Slow", "Langsam"),
+    ("AGC:", "AGC:"),
+    (
+        "Receiver gain control: loud callers and static crashes pull the gain down, then it recovers (quickly with Fast, slowly with Slow)",
+        "Verstärkungsregelung des Empfängers: laute Anrufer und Krachen ziehen die Verstärkung herunter, danach erholt sie sich (schnell mit Schnell, langsam mit Langsam)",
+    ),
+    // Data and sharing settings
+    ("cty.dat URL:", "cty.dat-URL:"),
+    ("CWT roster URL:", "URL der CWT-Mitgliederliste:"),
+    (
+        "Export or import the full configuration as a single file. Your callsign and export directory are never overwritten on import.",
+        "Exportiert oder importiert die ganze Konfiguration als eine Datei. Dein Rufzeichen und der Exportordner werden beim Import nie überschrieben.",
+    ),
+    ("Include callsign file paths", "Pfade der Rufzeichendateien einschließen"),
+    (
+        "File paths are usually only valid on the exporting machine",
+        "Dateipfade gelten meist nur auf dem exportierenden Rechner",
+    ),
+    ("Export Settings...", "Einstellungen exportieren..."),
+    ("Import Settings...", "Einstellungen importieren..."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untranslated_text_falls_back_to_english() {
+        assert_eq!(lookup(Language::German, "Calling CQ..."), "Rufe CQ...");
+        assert_eq!(
+            lookup(Language::German, "Not in the table"),
+            "Not in the table"
+        );
+        assert_eq!(lookup(Language::English, "Calling CQ..."), "Calling CQ...");
+    }
+
+    #[test]
+    fn translations_keep_placeholders() {
+        for (english, translated) in GERMAN {
+            assert_eq!(
+                english.matches("{}").count(),
+                translated.matches("{}").count(),
+                "{}",
+                english
+            );
+            assert_eq!(
+                GERMAN.iter().filter(|(e, _)| e == english).count(),
+                1,
+                "duplicate key {}",
+                english
+            );
+        }
+    }
+
+    #[test]
+    fn ui_labels_go_through_tr() {
+        let literal = regex::Regex::new(
+            r#"(?s)\.(?:label|button|heading)\(\s*"((?:[^"\\]|\\.)*)"|\.checkbox\(\s*[^,]+,\s*"((?:[^"\\]|\\.)*)""#,
+        )
+        .unwrap();
+        let escape = regex::Regex::new(r"\\u\{[0-9a-fA-F]+\}").unwrap();
+        let mut untranslated = Vec::new();
+        for (path, source) in sources(&std::path::Path::new(file!()).with_file_name("ui")) {
+            for caps in literal.captures_iter(&source) {
+                let text = caps.get(1).or(caps.get(2)).unwrap().as_str();
+                // Symbols like the bullet need no translation
+                if escape
+                    .replace_all(text, "")
+                    .chars()
+                    .any(char::is_alphabetic)
+                {
+                    untranslated.push(format!("{}: {}", path.display(), text));
+                }
+            }
+        }
+        assert!(untranslated.is_empty(), "wrap in tr(): {:#?}", untranslated);
+    }
+
+    #[test]
+    fn every_tr_text_has_a_german_entry() {
+        let literal = regex::Regex::new(r#"(?s)\btr(?:_args)?\(\s*"((?:[^"\\]|\\.)*)""#).unwrap();
+        let src = std::path::Path::new(file!()).parent().unwrap();
+        let mut missing = Vec::new();
+        for (path, source) in sources(src) {
+            for caps in literal.captures_iter(&source) {
+                let text = unescape(&caps[1]);
+                if !GERMAN.iter().any(|(english, _)| *english == text) {
+                    missing.push(format!("{}: {}", path.display(), text));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "no German for: {:#?}", missing);
+    }
+
+    /// Every .rs file under `dir`, without its tests
+    fn sources(dir: &std::path::Path) -> Vec<(std::path::PathBuf, String)> {
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                found.extend(sources(&path));
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                let source = source.split("#[cfg(test)]").next().unwrap().to_string();
+                found.push((path, source));
+            }
+        }
+        found
+    }
+
+    /// The text of a string literal's body as written in the source
+    fn unescape(body: &str) -> String {
+        let mut text = String::new();
+        let mut chars = body.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('\n') => while chars.next_if(|c| c.is_whitespace()).is_some() {},
+                Some('n') => text.push('\n'),
+                Some('u') => {
+                    let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                    text.extend(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32));
+                }
+                Some(other) => text.push(other),
+                None => {}
+            }
+        }
+        text
+    }
+}
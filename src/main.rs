@@ -1,21 +1,55 @@
 // Prevent console window on Windows in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod achievements;
+mod announce;
 mod app;
 mod audio;
+mod calendar;
 mod config;
 mod contest;
 mod cty;
+mod data_update;
+mod drill;
+mod engine;
 mod export;
+mod file_watch;
+mod headless;
+mod i18n;
 mod messages;
+mod milestones;
+mod notifications;
+mod past_exports;
+mod personal_bests;
+mod quick_drill;
+mod recovery;
+mod skimmer;
 mod state;
 mod station;
 mod stats;
+mod summary;
+mod tutorial;
 mod ui;
 
 use app::ContestApp;
 
 fn main() -> Result<(), eframe::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("headless") {
+        if let Err(e) = headless::run(&args[1..]) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.first().map(String::as_str) == Some("quick-drill") {
+        if let Err(e) = quick_drill::trigger() {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([640.0, 375.0])
@@ -13,12 +13,26 @@ pub enum MessageSegmentType {
     TheirCallsign,
     /// Our exchange (e.g., "5NN 05")
     OurExchange,
+    /// Our own callsign, answering someone's CQ (e.g., "K1ABC")
+    OurCallsign,
     /// CQ call (e.g., "CQ TEST K1ABC")
     Cq,
     /// Thank you / QSO complete
     Tu,
     /// AGN or ? request
     Agn,
+    /// Free text from a user-defined message macro
+    Macro,
+}
+
+/// Short sounds for in-session alerts, mixed on their own bus apart from
+/// the CW
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UiSound {
+    /// Something went well: a rising pair of notes
+    Milestone,
+    /// Something to watch: a falling pair
+    Warning,
 }
 
 /// A segment of a user message with its type
@@ -46,14 +60,37 @@ pub struct StationParams {
 pub enum AudioCommand {
     /// Start playing morse for a station
     StartStation(StationParams),
+    /// Like StartStation, with the gaps between characters stretched to
+    /// an overall `effective_wpm` (Farnsworth spacing)
+    StartFarnsworth {
+        params: StationParams,
+        effective_wpm: u8,
+    },
     /// Play a segmented message with element-level completion tracking
     /// Each segment will emit a UserSegmentComplete event when finished
     PlayUserMessageSegmented {
         segments: Vec<MessageSegment>,
         wpm: u8,
     },
+    /// Play recorded audio (at the output sample rate) into the receiver;
+    /// reports StationComplete with `id` when it ends
+    PlayRecording {
+        id: StationId,
+        left: Vec<f32>,
+        right: Vec<f32>,
+    },
+    /// Play an alert chime on the UI sounds bus, held back while our own
+    /// message is playing
+    PlayUiSound(UiSound),
     /// Update global audio settings
     UpdateSettings(AudioSettings),
+    /// Retune the receiver: callers are heard `rit_hz` lower in pitch, and
+    /// with a filter width, callers outside it are heard faintly through
+    /// the filter skirt and the noise is narrowed to it
+    Tune { rit_hz: f32, filter_hz: Option<f32> },
+    /// Stop the user's message only, without a completion event
+    /// (callers and noise keep playing)
+    StopUserMessage,
     /// Stop all audio (except noise)
     StopAll,
 }
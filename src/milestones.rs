@@ -0,0 +1,150 @@
+// Milestones reached during a session, for the optional banner and chime:
+// a round number of QSOs, a rate past the personal best, or accuracy
+// slipping
+
+use chrono::Duration;
+
+use crate::config::AlertSettings;
+use crate::i18n::tr_args;
+use crate::messages::UiSound;
+use crate::stats::SessionStats;
+
+/// QSOs the accuracy alert looks back over
+const ACCURACY_WINDOW: usize = 10;
+/// Window the running rate is measured over
+const RATE_WINDOW_MINUTES: i64 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Milestone {
+    /// A multiple of the QSO step
+    Qsos(usize),
+    /// The running 10-minute rate passed the personal best
+    BestRate(u32),
+    /// Accuracy over the last few QSOs fell below the floor
+    AccuracyLow(f32),
+}
+
+impl Milestone {
+    pub fn message(&self) -> String {
+        match self {
+            Milestone::Qsos(count) => tr_args("{} QSOs", &[&count.to_string()]),
+            Milestone::BestRate(rate) => {
+                tr_args("New best rate: {}/hr over 10 minutes", &[&rate.to_string()])
+            }
+            Milestone::AccuracyLow(pct) => tr_args(
+                "Accuracy down to {} over the last {} QSOs; slow down and copy",
+                &[&format!("{:.0}%", pct), &ACCURACY_WINDOW.to_string()],
+            ),
+        }
+    }
+
+    pub fn sound(&self) -> UiSound {
+        match self {
+            Milestone::Qsos(_) | Milestone::BestRate(_) => UiSound::Milestone,
+            Milestone::AccuracyLow(_) => UiSound::Warning,
+        }
+    }
+}
+
+/// What has already been pointed out this session, so each alert comes
+/// once rather than after every QSO
+#[derive(Clone, Debug, Default)]
+pub struct MilestoneTracker {
+    rate_passed: bool,
+    accuracy_low: bool,
+}
+
+impl MilestoneTracker {
+    /// Milestones the QSO just logged reached, against the best rate so far
+    pub fn on_qso(
+        &mut self,
+        stats: &SessionStats,
+        best_rate: u32,
+        settings: &AlertSettings,
+    ) -> Vec<Milestone> {
+        let mut milestones = Vec::new();
+        let qsos: Vec<_> = stats.counted().collect();
+
+        let count = qsos.len();
+        if settings.qso_step > 0 && count > 0 && count % settings.qso_step as usize == 0 {
+            milestones.push(Milestone::Qsos(count));
+        }
+
+        // QSOs in the ten minutes up to the last one, as an hourly rate
+        if settings.best_rate && best_rate > 0 && !self.rate_passed {
+            let times: Vec<_> = qsos.iter().filter_map(|q| q.logged_utc()).collect();
+            if let Some(last) = times.iter().max() {
+                let window = Duration::minutes(RATE_WINDOW_MINUTES);
+                let recent = times.iter().filter(|t| *last - **t < window).count();
+                let rate = (recent as i64 * 60 / RATE_WINDOW_MINUTES) as u32;
+                if rate > best_rate {
+                    self.rate_passed = true;
+                    milestones.push(Milestone::BestRate(rate));
+                }
+            }
+        }
+
+        if settings.accuracy_floor_pct > 0 && count >= ACCURACY_WINDOW {
+            let correct = qsos[count - ACCURACY_WINDOW..]
+                .iter()
+                .filter(|q| q.is_correct())
+                .count();
+            let pct = correct as f32 / ACCURACY_WINDOW as f32 * 100.0;
+            let low = pct < settings.accuracy_floor_pct as f32;
+            // Once it recovers, a later slip is worth another word
+            if low && !self.accuracy_low {
+                milestones.push(Milestone::AccuracyLow(pct));
+            }
+            self.accuracy_low = low;
+        }
+        milestones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::QsoRecord;
+
+    fn log(stats: &mut SessionStats, minute: usize, correct: bool) {
+        stats.log_qso(QsoRecord {
+            callsign_correct: correct,
+            exchange_correct: true,
+            logged_at: format!("2026-03-14T09:{:02}:00+00:00", minute),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn each_milestone_is_pointed_out_once() {
+        let settings = AlertSettings {
+            qso_step: 5,
+            ..AlertSettings::default()
+        };
+        let mut tracker = MilestoneTracker::default();
+        let mut stats = SessionStats::new();
+        let mut heard = Vec::new();
+        // Two QSOs a minute for twelve minutes, then a run of busts
+        for i in 0..24 {
+            log(&mut stats, i / 2, true);
+            heard.extend(tracker.on_qso(&stats, 100, &settings));
+        }
+        for i in 0..3 {
+            log(&mut stats, 12 + i, false);
+            heard.extend(tracker.on_qso(&stats, 100, &settings));
+        }
+        assert_eq!(
+            heard,
+            [
+                Milestone::Qsos(5),
+                Milestone::Qsos(10),
+                Milestone::Qsos(15),
+                Milestone::BestRate(102),
+                Milestone::Qsos(20),
+                Milestone::Qsos(25),
+                Milestone::AccuracyLow(70.0),
+            ]
+        );
+        assert_eq!(Milestone::AccuracyLow(70.0).sound(), UiSound::Warning);
+    }
+}
@@ -0,0 +1,167 @@
+// Problems the user should know about, such as a callsign file that couldn't
+// be read or settings that didn't save. Each is kept with the time it
+// happened; the window flashes the latest and lists them all (see
+// `ui::notifications`).
+
+use std::time::{Duration, Instant};
+
+/// How long a new notification shows over the main window
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// The oldest entries are dropped beyond this many
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    /// When it last happened
+    pub time: chrono::DateTime<chrono::Local>,
+    /// How many times in a row it happened
+    pub count: u32,
+    seen: bool,
+}
+
+#[derive(Default)]
+pub struct Notifications {
+    entries: Vec<Notification>,
+    /// Whether the list window is open
+    pub show: bool,
+    toast_since: Option<Instant>,
+}
+
+impl Notifications {
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message.into());
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message.into());
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message.into());
+    }
+
+    /// Record a notification. The same one repeated is counted rather than
+    /// listed again.
+    pub fn push(&mut self, severity: Severity, message: String) {
+        let time = chrono::Local::now();
+        match self.entries.last_mut() {
+            Some(last) if last.severity == severity && last.message == message => {
+                last.count += 1;
+                last.time = time;
+                last.seen = false;
+            }
+            _ => {
+                self.entries.push(Notification {
+                    severity,
+                    message,
+                    time,
+                    count: 1,
+                    seen: false,
+                });
+                if self.entries.len() > MAX_ENTRIES {
+                    self.entries.remove(0);
+                }
+            }
+        }
+        self.toast_since = Some(Instant::now());
+    }
+
+    pub fn entries(&self) -> &[Notification] {
+        &self.entries
+    }
+
+    /// Warnings and errors not yet looked at
+    pub fn unread(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|n| !n.seen && n.severity != Severity::Info)
+            .count()
+    }
+
+    /// The most serious kind among the unread ones
+    pub fn unread_severity(&self) -> Option<Severity> {
+        let unread = self.entries.iter().filter(|n| !n.seen);
+        if unread.clone().any(|n| n.severity == Severity::Error) {
+            Some(Severity::Error)
+        } else if unread.clone().any(|n| n.severity == Severity::Warning) {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    }
+
+    pub fn mark_all_seen(&mut self) {
+        for entry in &mut self.entries {
+            entry.seen = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.toast_since = None;
+    }
+
+    /// The latest notification, while it is still being flashed
+    pub fn toast(&mut self, now: Instant) -> Option<&Notification> {
+        let since = self.toast_since?;
+        if now.saturating_duration_since(since) > TOAST_DURATION {
+            self.toast_since = None;
+            return None;
+        }
+        self.entries.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_are_counted_and_info_is_not_unread() {
+        let mut notifications = Notifications::default();
+        notifications.info("Reloaded calls.txt");
+        assert_eq!(notifications.unread(), 0);
+
+        notifications.warning("calls.txt: not found");
+        notifications.warning("calls.txt: not found");
+        notifications.error("Couldn't save settings");
+        assert_eq!(notifications.entries().len(), 3);
+        assert_eq!(notifications.entries()[1].count, 2);
+        assert_eq!(notifications.unread(), 2);
+        assert_eq!(notifications.unread_severity(), Some(Severity::Error));
+
+        notifications.mark_all_seen();
+        assert_eq!(notifications.unread(), 0);
+        // Happening again makes it unread again
+        notifications.error("Couldn't save settings");
+        assert_eq!(notifications.unread(), 1);
+        assert_eq!(notifications.entries().len(), 3);
+
+        for i in 0..MAX_ENTRIES {
+            notifications.info(format!("{}", i));
+        }
+        assert_eq!(notifications.entries().len(), MAX_ENTRIES);
+    }
+
+    #[test]
+    fn toast_shows_the_latest_for_a_while() {
+        let mut notifications = Notifications::default();
+        let now = Instant::now();
+        assert!(notifications.toast(now).is_none());
+        notifications.warning("first");
+        notifications.warning("second");
+        assert_eq!(notifications.toast(now).unwrap().message, "second");
+        assert!(notifications
+            .toast(now + TOAST_DURATION + Duration::from_secs(1))
+            .is_none());
+    }
+}
@@ -0,0 +1,315 @@
+// Session reports exported earlier, found in the export directory: their
+// headline numbers for comparing sessions, and the Markdown reports broken
+// into blocks for showing in the app
+
+use chrono::NaiveDateTime;
+use std::path::{Path, PathBuf};
+
+use crate::config::AppSettings;
+use crate::export::ExportFormat;
+
+/// Headline numbers from a report
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportSummary {
+    pub total_qsos: usize,
+    pub correct_rate: f32,
+    pub callsign_accuracy: f32,
+    pub exchange_accuracy: f32,
+    pub total_points: u32,
+}
+
+/// A report found in the export directory
+#[derive(Clone, Debug)]
+pub struct PastExport {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    pub callsign: String,
+    /// When it was exported, from the file name
+    pub exported: NaiveDateTime,
+    /// None if the report couldn't be read
+    pub summary: Option<ExportSummary>,
+}
+
+/// Where exports are written: the configured directory, or the current one
+pub fn export_dir(settings: &AppSettings) -> PathBuf {
+    if settings.user.export_directory.is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(&settings.user.export_directory)
+    }
+}
+
+/// Markdown and JSON reports in `dir`, newest first. CSV exports hold only
+/// the QSO rows, so they aren't listed.
+pub fn scan(dir: &Path) -> Result<Vec<PastExport>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+    let mut exports: Vec<PastExport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (callsign, exported, format) = parse_file_name(&path)?;
+            let summary = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| match format {
+                    ExportFormat::Json => json_summary(&content),
+                    _ => markdown_summary(&content),
+                });
+            Some(PastExport {
+                path,
+                format,
+                callsign,
+                exported,
+                summary,
+            })
+        })
+        .collect();
+    exports.sort_by(|a, b| b.exported.cmp(&a.exported).then(a.path.cmp(&b.path)));
+    Ok(exports)
+}
+
+/// Callsign, time and format from `CWCT-<call>-<date>-<time>.<ext>`
+fn parse_file_name(path: &Path) -> Option<(String, NaiveDateTime, ExportFormat)> {
+    let format = match path.extension()?.to_str()? {
+        "md" => ExportFormat::Markdown,
+        "json" => ExportFormat::Json,
+        _ => return None,
+    };
+    let stem = path.file_stem()?.to_str()?.strip_prefix("CWCT-")?;
+    let mut parts = stem.rsplitn(3, '-');
+    let (time, date, callsign) = (parts.next()?, parts.next()?, parts.next()?);
+    let exported =
+        NaiveDateTime::parse_from_str(&format!("{}{}", date, time), "%Y%m%d%H%M").ok()?;
+    Some((callsign.to_string(), exported, format))
+}
+
+/// The number in parentheses before a percent sign, as in `3/4 (75.0%)`
+fn percent(value: &str) -> Option<f32> {
+    let start = value.find('(')? + 1;
+    let end = start + value[start..].find('%')?;
+    value[start..end].trim().parse().ok()
+}
+
+/// Leading whole number, as in `12 (75.0%)`
+fn count<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn markdown_summary(content: &str) -> Option<ExportSummary> {
+    let mut summary = ExportSummary::default();
+    let mut found = false;
+    for line in content.lines() {
+        let Some((label, value)) = line.strip_prefix("- ").and_then(|l| l.split_once(": ")) else {
+            continue;
+        };
+        match label {
+            "Total QSOs" => {
+                summary.total_qsos = count(value)?;
+                found = true;
+            }
+            "Correct QSOs" => summary.correct_rate = percent(value)?,
+            "Total Points" => summary.total_points = count(value)?,
+            "Callsign Accuracy" => summary.callsign_accuracy = percent(value)?,
+            "Exchange Accuracy" => summary.exchange_accuracy = percent(value)?,
+            _ => {}
+        }
+    }
+    found.then_some(summary)
+}
+
+fn json_summary(content: &str) -> Option<ExportSummary> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let analysis = value.get("analysis")?;
+    let number = |key: &str| analysis.get(key).and_then(|v| v.as_f64());
+    Some(ExportSummary {
+        total_qsos: number("total_qsos")? as usize,
+        correct_rate: number("correct_rate")? as f32,
+        callsign_accuracy: number("callsign_accuracy")? as f32,
+        exchange_accuracy: number("exchange_accuracy")? as f32,
+        total_points: number("total_points")? as u32,
+    })
+}
+
+/// Change in correct-QSO rate from the export before each one (by time),
+/// in the order given; None for the oldest or where either is unreadable
+pub fn accuracy_changes(exports: &[PastExport]) -> Vec<Option<f32>> {
+    exports
+        .iter()
+        .map(|export| {
+            let previous = exports
+                .iter()
+                .filter(|other| other.exported < export.exported && other.summary.is_some())
+                .max_by_key(|other| other.exported)?;
+            let rate = export.summary.as_ref()?.correct_rate;
+            Some(rate - previous.summary.as_ref()?.correct_rate)
+        })
+        .collect()
+}
+
+/// A piece of a Markdown report, in the forms the exports use
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    Heading {
+        level: usize,
+        text: String,
+    },
+    Bullet(String),
+    /// Header row first
+    Table(Vec<Vec<String>>),
+    Text(String),
+}
+
+/// Bold markers and hard line breaks aren't shown as such
+fn plain(text: &str) -> String {
+    text.replace("**", "").trim_end().to_string()
+}
+
+fn table_row(line: &str) -> Vec<String> {
+    // Escaped bars are part of a cell
+    let line = line.trim().replace("\\|", "\u{0}");
+    line.trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().replace('\u{0}', "|"))
+        .collect()
+}
+
+/// Whether a table line is the `|---|---|` row under the header
+fn is_separator(line: &str) -> bool {
+    line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+pub fn parse_markdown(content: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut table: Vec<Vec<String>> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('|') {
+            if !is_separator(trimmed) {
+                table.push(table_row(trimmed));
+            }
+            continue;
+        }
+        if !table.is_empty() {
+            blocks.push(Block::Table(std::mem::take(&mut table)));
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if level > 0 && trimmed[level..].starts_with(' ') {
+            blocks.push(Block::Heading {
+                level,
+                text: plain(trimmed[level..].trim()),
+            });
+        } else if let Some(item) = trimmed.strip_prefix("- ") {
+            blocks.push(Block::Bullet(plain(item)));
+        } else {
+            blocks.push(Block::Text(plain(trimmed)));
+        }
+    }
+    if !table.is_empty() {
+        blocks.push(Block::Table(table));
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{build_json_content, build_markdown_content};
+    use crate::stats::{QsoRecord, SessionStats};
+
+    fn stats() -> SessionStats {
+        let mut stats = SessionStats::new();
+        for (call, correct) in [("K1ABC", true), ("W9XYZ", false), ("N1MM", true)] {
+            stats.log_qso(QsoRecord {
+                expected_callsign: call.to_string(),
+                entered_callsign: call.to_string(),
+                callsign_correct: correct,
+                exchange_correct: true,
+                points: u32::from(correct),
+                note: "QSB | brutal".to_string(),
+                flagged: !correct,
+                ..Default::default()
+            });
+        }
+        stats
+    }
+
+    #[test]
+    fn summaries_read_back_from_both_formats() {
+        let settings = AppSettings::default();
+        let expected = ExportSummary {
+            total_qsos: 3,
+            correct_rate: 66.7,
+            callsign_accuracy: 66.7,
+            exchange_accuracy: 100.0,
+            total_points: 2,
+        };
+        let markdown = markdown_summary(&build_markdown_content(&settings, &stats())).unwrap();
+        assert_eq!(markdown, expected);
+        let json = json_summary(&build_json_content(&settings, &stats()).unwrap()).unwrap();
+        assert_eq!(json.total_qsos, 3);
+        assert!((json.correct_rate - 66.67).abs() < 0.01);
+        assert!(markdown_summary("# Notes\n\nNothing here").is_none());
+    }
+
+    #[test]
+    fn file_names_give_call_and_time() {
+        let (call, exported, format) =
+            parse_file_name(Path::new("out/CWCT-K1ABC-20260314-0930.json")).unwrap();
+        assert_eq!(call, "K1ABC");
+        assert_eq!(exported.to_string(), "2026-03-14 09:30:00");
+        assert_eq!(format, ExportFormat::Json);
+        assert!(parse_file_name(Path::new("CWCT-K1ABC-20260314-0930.csv")).is_none());
+        assert!(parse_file_name(Path::new("notes-20260314-0930.md")).is_none());
+    }
+
+    #[test]
+    fn accuracy_changes_compare_with_the_session_before() {
+        let export = |time: &str, rate: Option<f32>| PastExport {
+            path: PathBuf::new(),
+            format: ExportFormat::Markdown,
+            callsign: String::new(),
+            exported: NaiveDateTime::parse_from_str(time, "%Y%m%d%H%M").unwrap(),
+            summary: rate.map(|correct_rate| ExportSummary {
+                correct_rate,
+                ..Default::default()
+            }),
+        };
+        let exports = [
+            export("202603141000", Some(80.0)),
+            export("202603130900", None),
+            export("202603120900", Some(70.0)),
+        ];
+        assert_eq!(accuracy_changes(&exports), [Some(10.0), None, None]);
+    }
+
+    #[test]
+    fn markdown_reports_split_into_blocks() {
+        let blocks = parse_markdown(&build_markdown_content(&AppSettings::default(), &stats()));
+        assert_eq!(
+            blocks[0],
+            Block::Heading {
+                level: 1,
+                text: "CWCT Session Export".to_string()
+            }
+        );
+        assert!(blocks.contains(&Block::Bullet("Total QSOs: 3".to_string())));
+        let tables: Vec<&Vec<Vec<String>>> = blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Table(rows) => Some(rows),
+                _ => None,
+            })
+            .collect();
+        // Flagged QSOs, then the QSO log; a note's bar stays in its cell
+        let flagged = tables[tables.len() - 2];
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[1].last().unwrap(), "QSB | brutal");
+        assert_eq!(tables[tables.len() - 1].len(), 4);
+    }
+}
@@ -0,0 +1,220 @@
+// Personal bests kept across sessions, so each session's summary can say
+// how it compares and point out a new best
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::AppSettings;
+use crate::i18n::tr;
+use crate::stats::SessionStats;
+
+/// Caller speed that counts as fast for the accuracy best
+pub const FAST_WPM: u8 = 30;
+/// Fewest fast QSOs before a session's accuracy at speed counts
+const MIN_FAST_QSOS: usize = 10;
+/// Window the best rate is measured over
+const RATE_WINDOW_MINUTES: i64 = 10;
+
+/// The best figures so far, or one session's own
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonalBests {
+    /// Most QSOs in any ten minutes, as an hourly rate
+    pub rate_10min: u32,
+    /// Share of QSOs with callers at `FAST_WPM` or faster logged right;
+    /// None until a session has enough of them
+    pub fast_accuracy_pct: Option<f32>,
+    /// Most clean QSOs in a row
+    pub clean_streak: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BestKind {
+    Rate,
+    FastAccuracy,
+    CleanStreak,
+}
+
+impl BestKind {
+    pub fn label(&self) -> String {
+        match self {
+            BestKind::Rate => tr("Best 10-minute rate"),
+            BestKind::FastAccuracy => tr("Accuracy at 30+ WPM"),
+            BestKind::CleanStreak => tr("Longest clean streak"),
+        }
+        .to_string()
+    }
+}
+
+/// One figure from the session set against the best before it
+#[derive(Clone, Debug, PartialEq)]
+pub struct BestResult {
+    pub kind: BestKind,
+    pub session: String,
+    /// The best before this session; None the first time
+    pub previous: Option<String>,
+    pub new_best: bool,
+}
+
+impl PersonalBests {
+    /// The figures one session reached
+    pub fn of_session(stats: &SessionStats) -> Self {
+        let mut times: Vec<_> = stats.counted().filter_map(|q| q.logged_utc()).collect();
+        times.sort();
+        let window = Duration::minutes(RATE_WINDOW_MINUTES);
+        let most_in_window = (0..times.len())
+            .map(|start| {
+                times[start..]
+                    .iter()
+                    .take_while(|t| **t - times[start] < window)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+
+        let fast: Vec<bool> = stats
+            .counted()
+            .filter(|q| q.station_wpm >= FAST_WPM)
+            .map(|q| q.is_correct())
+            .collect();
+        let fast_accuracy_pct = (fast.len() >= MIN_FAST_QSOS).then(|| {
+            fast.iter().filter(|correct| **correct).count() as f32 / fast.len() as f32 * 100.0
+        });
+
+        Self {
+            rate_10min: (most_in_window as i64 * 60 / RATE_WINDOW_MINUTES) as u32,
+            fast_accuracy_pct,
+            clean_streak: stats.analyze().streaks.max_clean,
+        }
+    }
+
+    /// Take in a session's figures, keeping whichever are better, and say
+    /// how each compared
+    pub fn record(&mut self, session: &PersonalBests) -> Vec<BestResult> {
+        let mut results = Vec::new();
+        let rate = |value: u32| format!("{}/hr", value);
+        results.push(BestResult {
+            kind: BestKind::Rate,
+            session: rate(session.rate_10min),
+            previous: (self.rate_10min > 0).then(|| rate(self.rate_10min)),
+            new_best: session.rate_10min > self.rate_10min,
+        });
+        self.rate_10min = self.rate_10min.max(session.rate_10min);
+
+        if let Some(pct) = session.fast_accuracy_pct {
+            let previous = self.fast_accuracy_pct;
+            let new_best = previous.is_none_or(|best| pct > best);
+            results.push(BestResult {
+                kind: BestKind::FastAccuracy,
+                session: format!("{:.0}%", pct),
+                previous: previous.map(|best| format!("{:.0}%", best)),
+                new_best,
+            });
+            if new_best {
+                self.fast_accuracy_pct = Some(pct);
+            }
+        }
+
+        results.push(BestResult {
+            kind: BestKind::CleanStreak,
+            session: session.clean_streak.to_string(),
+            previous: (self.clean_streak > 0).then(|| self.clean_streak.to_string()),
+            new_best: session.clean_streak > self.clean_streak,
+        });
+        self.clean_streak = self.clean_streak.max(session.clean_streak);
+        results
+    }
+}
+
+/// The bests file sits next to the settings file
+fn bests_path() -> PathBuf {
+    AppSettings::config_path().with_file_name("personal-bests.json")
+}
+
+/// Saved bests, or none yet
+pub fn load() -> PersonalBests {
+    std::fs::read_to_string(bests_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(bests: &PersonalBests) -> Result<(), String> {
+    let path = bests_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(bests).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::QsoRecord;
+
+    fn session(qsos: &[(u32, u8, bool)]) -> SessionStats {
+        let mut stats = SessionStats::new();
+        for &(minute, station_wpm, correct) in qsos {
+            stats.log_qso(QsoRecord {
+                callsign_correct: correct,
+                exchange_correct: true,
+                station_wpm,
+                logged_at: format!("2026-03-14T09:{:02}:00+00:00", minute),
+                ..Default::default()
+            });
+        }
+        stats
+    }
+
+    #[test]
+    fn session_figures_come_from_the_log() {
+        // Five QSOs inside ten minutes, then one long after
+        let bests = PersonalBests::of_session(&session(&[
+            (0, 25, true),
+            (2, 25, true),
+            (4, 25, false),
+            (6, 25, true),
+            (9, 25, true),
+            (40, 25, true),
+        ]));
+        assert_eq!(bests.rate_10min, 30);
+        assert_eq!(bests.clean_streak, 3);
+        assert_eq!(bests.fast_accuracy_pct, None);
+
+        let fast: Vec<(u32, u8, bool)> = (0..10).map(|i| (i, 32, i != 0)).collect();
+        let bests = PersonalBests::of_session(&session(&fast));
+        assert_eq!(bests.fast_accuracy_pct, Some(90.0));
+    }
+
+    #[test]
+    fn only_better_figures_are_new_bests() {
+        let mut bests = PersonalBests {
+            rate_10min: 120,
+            fast_accuracy_pct: None,
+            clean_streak: 8,
+        };
+        let results = bests.record(&PersonalBests {
+            rate_10min: 90,
+            fast_accuracy_pct: Some(80.0),
+            clean_streak: 12,
+        });
+        let new: Vec<BestKind> = results
+            .iter()
+            .filter(|r| r.new_best)
+            .map(|r| r.kind)
+            .collect();
+        assert_eq!(new, [BestKind::FastAccuracy, BestKind::CleanStreak]);
+        assert_eq!(results[0].previous.as_deref(), Some("120/hr"));
+        assert_eq!(results[0].session, "90/hr");
+        assert_eq!(
+            bests,
+            PersonalBests {
+                rate_10min: 120,
+                fast_accuracy_pct: Some(80.0),
+                clean_streak: 12,
+            }
+        );
+    }
+}
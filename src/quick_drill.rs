@@ -0,0 +1,184 @@
+// Quick drill from anywhere: bind a key in the desktop's own shortcut
+// settings to run `contest_trainer quick-drill`, which asks the running
+// trainer over a local socket to start or stop a drill. This works the same
+// on every platform and needs no permission to watch the keyboard.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// Local port the trainer listens on while the trigger is turned on
+const PORT: u16 = 47391;
+/// What the trigger sends
+const REQUEST: &[u8] = b"quick-drill\n";
+/// How long the trigger or the trainer waits on the other
+const TIMEOUT: Duration = Duration::from_millis(500);
+/// Connections still waiting to send the request; past this the oldest are
+/// dropped
+const MAX_PENDING: usize = 8;
+
+fn address(port: u16) -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, port))
+}
+
+/// Listens for the trigger; checked each frame, so it never blocks
+pub struct QuickDrillListener {
+    listener: TcpListener,
+    pending: Vec<Connection>,
+}
+
+/// An accepted connection and what it has sent so far
+struct Connection {
+    stream: TcpStream,
+    received: Vec<u8>,
+    since: Instant,
+}
+
+impl Connection {
+    /// Read whatever has arrived without waiting: Some(true) for the
+    /// trigger, Some(false) for anything else, None if it's still to come
+    fn check(&mut self) -> Option<bool> {
+        let mut buf = [0u8; REQUEST.len()];
+        loop {
+            let wanted = REQUEST.len() - self.received.len();
+            match self.stream.read(&mut buf[..wanted]) {
+                Ok(0) => return Some(false),
+                Ok(n) => {
+                    self.received.extend_from_slice(&buf[..n]);
+                    if !REQUEST.starts_with(&self.received) {
+                        return Some(false);
+                    }
+                    if self.received.len() == REQUEST.len() {
+                        return Some(true);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return None,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return Some(false),
+            }
+        }
+    }
+}
+
+impl QuickDrillListener {
+    pub fn start() -> Result<Self, String> {
+        Self::on_port(PORT)
+    }
+
+    fn on_port(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(address(port)).map_err(|e| e.to_string())?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self {
+            listener,
+            pending: Vec::new(),
+        })
+    }
+
+    /// How many times the trigger was run since the last check
+    pub fn poll(&mut self) -> usize {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> usize {
+        // Accepting fails with WouldBlock once nothing more is waiting
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+            if self.pending.len() == MAX_PENDING {
+                self.pending.remove(0);
+            }
+            self.pending.push(Connection {
+                stream,
+                received: Vec::new(),
+                since: now,
+            });
+        }
+
+        let mut triggers = 0;
+        // Anything that hasn't sent the request in time isn't the trigger
+        self.pending
+            .retain_mut(|connection| match connection.check() {
+                Some(is_trigger) => {
+                    triggers += usize::from(is_trigger);
+                    false
+                }
+                None => now.saturating_duration_since(connection.since) < TIMEOUT,
+            });
+        triggers
+    }
+}
+
+/// Entry point for `contest_trainer quick-drill`
+pub fn trigger() -> Result<(), String> {
+    send(PORT)
+}
+
+fn send(port: u16) -> Result<(), String> {
+    let mut stream = TcpStream::connect_timeout(&address(port), TIMEOUT).map_err(|_| {
+        "The trainer isn't running, or Quick Drill Hotkey is off in its settings".to_string()
+    })?;
+    stream.write_all(REQUEST).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_reaches_the_listener() {
+        // An unused port, so a running trainer isn't disturbed
+        let port = TcpListener::bind(address(0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut listener = QuickDrillListener::on_port(port).unwrap();
+        assert_eq!(listener.poll(), 0);
+
+        send(port).unwrap();
+        send(port).unwrap();
+        // Something else connecting doesn't count
+        TcpStream::connect(address(port))
+            .unwrap()
+            .write_all(b"GET / HTTP/1.1\r\n")
+            .unwrap();
+        assert_eq!(listener.poll(), 2);
+        assert_eq!(listener.poll(), 0);
+
+        drop(listener);
+        assert!(send(port).is_err());
+    }
+
+    #[test]
+    fn silent_connections_never_hold_up_a_poll() {
+        let port = TcpListener::bind(address(0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut listener = QuickDrillListener::on_port(port).unwrap();
+        let start = Instant::now();
+
+        // Connected but nothing sent yet: the poll returns at once
+        let mut slow = TcpStream::connect(address(port)).unwrap();
+        let _silent = TcpStream::connect(address(port)).unwrap();
+        assert_eq!(listener.poll_at(start), 0);
+        assert!(start.elapsed() < TIMEOUT);
+        assert_eq!(listener.pending.len(), 2);
+
+        // The request in two pieces still counts once it's all there
+        slow.write_all(&REQUEST[..5]).unwrap();
+        slow.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(listener.poll_at(start), 0);
+        slow.write_all(&REQUEST[5..]).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(listener.poll_at(start), 1);
+
+        // The silent one is dropped once it has had its time
+        assert_eq!(listener.pending.len(), 1);
+        assert_eq!(listener.poll_at(start + TIMEOUT), 0);
+        assert!(listener.pending.is_empty());
+    }
+}
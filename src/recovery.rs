@@ -0,0 +1,150 @@
+// Session autosave, so a crash or an accidental close doesn't lose the session
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::AppSettings;
+use crate::stats::{
+    DensitySample, DoubleOutcome, FightTally, NilRecord, QsoRecord, WidePileupTally,
+};
+
+/// What is kept of a session between runs. CQ cycle timing is not kept,
+/// so the Efficiency figures start over after a restore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// When the file was written (local time, RFC 3339)
+    pub saved_at: String,
+    pub qsos: Vec<QsoRecord>,
+    #[serde(default)]
+    pub doubles: Vec<DoubleOutcome>,
+    /// Our next serial number
+    pub user_serial: u32,
+    /// Seconds from the first QSO to the save, so the rate carries on
+    #[serde(default)]
+    pub elapsed_secs: f64,
+    /// Callers who gave up before being worked
+    #[serde(default)]
+    pub callers_lost: usize,
+    /// QSOs dropped that the other station logged
+    #[serde(default)]
+    pub nils: Vec<NilRecord>,
+    /// Callers heard and recovered in a wide pileup
+    #[serde(default)]
+    pub wide_pileup: WidePileupTally,
+    /// Frequency fights, QSYs and the time they cost
+    #[serde(default)]
+    pub fights: FightTally,
+    /// Caller density after each QSO, with dynamic density on
+    #[serde(default)]
+    pub density: Vec<DensitySample>,
+}
+
+impl SavedSession {
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed_secs.max(0.0))
+    }
+}
+
+/// The recovery file sits next to the settings file
+pub fn recovery_path() -> PathBuf {
+    AppSettings::config_path().with_file_name("session-recovery.json")
+}
+
+pub fn save(session: &SavedSession) -> Result<(), String> {
+    let path = recovery_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(session).map_err(|e| e.to_string())?;
+    // Write then rename, so a crash mid-write leaves the previous save intact
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&temp, &path).map_err(|e| e.to_string())
+}
+
+/// The last autosaved session, if there is one worth offering
+pub fn load() -> Option<SavedSession> {
+    let content = std::fs::read_to_string(recovery_path()).ok()?;
+    let session: SavedSession = serde_json::from_str(&content).ok()?;
+    (!session.qsos.is_empty()).then_some(session)
+}
+
+pub fn discard() {
+    let _ = std::fs::remove_file(recovery_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_session_round_trips() {
+        let mut session = SavedSession {
+            saved_at: "2026-01-01T12:00:00+00:00".to_string(),
+            qsos: vec![QsoRecord {
+                expected_callsign: "K1ABC".to_string(),
+                entered_callsign: "K1ABC".to_string(),
+                callsign_correct: true,
+                points: 1,
+                flagged: true,
+                note: "fast".to_string(),
+                ..Default::default()
+            }],
+            doubles: vec![DoubleOutcome::PulledOut],
+            user_serial: 2,
+            elapsed_secs: 90.5,
+            callers_lost: 3,
+            nils: vec![NilRecord {
+                callsign: "W9XYZ".to_string(),
+                penalty: 2,
+                at: String::new(),
+            }],
+            wide_pileup: WidePileupTally::default(),
+            fights: FightTally {
+                fights: 2,
+                qsys: 1,
+                fought: Duration::from_secs(75),
+                qsy_time: Duration::from_secs(20),
+            },
+            density: vec![
+                DensitySample {
+                    minutes: 0.0,
+                    probability: 0.7,
+                },
+                DensitySample {
+                    minutes: 1.5,
+                    probability: 0.73,
+                },
+            ],
+        };
+        session.wide_pileup.hear("K1ABC");
+        session.wide_pileup.hear("W9XYZ");
+        session.wide_pileup.recover("K1ABC");
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: SavedSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.qsos[0].note, "fast");
+        assert_eq!(restored.doubles, vec![DoubleOutcome::PulledOut]);
+        assert_eq!(restored.user_serial, 2);
+        assert_eq!(restored.elapsed(), Duration::from_millis(90_500));
+        assert_eq!(restored.callers_lost, 3);
+        assert_eq!(restored.nils, session.nils);
+        assert_eq!(restored.wide_pileup, session.wide_pileup);
+        assert_eq!(restored.fights, session.fights);
+        assert_eq!(restored.density, session.density);
+    }
+
+    #[test]
+    fn records_from_older_saves_get_defaults() {
+        let json = r#"{"saved_at":"x","qsos":[{"expected_callsign":"W9XYZ"}],"user_serial":5}"#;
+        let restored: SavedSession = serde_json::from_str(json).unwrap();
+        assert_eq!(restored.qsos[0].expected_callsign, "W9XYZ");
+        assert!(!restored.qsos[0].flagged);
+        assert!(restored.doubles.is_empty());
+        assert_eq!(restored.callers_lost, 0);
+        assert!(restored.nils.is_empty());
+        assert_eq!(restored.wide_pileup.heard(), 0);
+        assert_eq!(restored.fights, FightTally::default());
+        assert!(restored.density.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+// Simulated CW skimmer: spots the stations calling us, a little late and
+// not always right
+
+use rand::{Rng, RngCore};
+use std::time::{Duration, Instant};
+
+use crate::audio::morse::message_duration_ms;
+use crate::config::SkimmerSettings;
+use crate::messages::StationParams;
+use crate::station::confusion::confused_callsign;
+
+/// How long a spot stays on the list
+const SPOT_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Most spots shown at once
+const MAX_SPOTS: usize = 20;
+
+/// One decoded call, as the skimmer reports it
+#[derive(Clone, Debug)]
+pub struct Spot {
+    /// What the skimmer decoded; not always the call that was sent
+    pub callsign: String,
+    /// Offset from our receive pitch (Hz)
+    pub offset_hz: f32,
+    pub wpm: u8,
+    pub spotted_at: Instant,
+}
+
+#[derive(Default)]
+pub struct Skimmer {
+    /// Spots waiting for the skimmer to finish decoding them
+    pending: Vec<Spot>,
+    /// Spots on the list, newest first
+    spots: Vec<Spot>,
+}
+
+impl Skimmer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A caller started sending; their spot appears once the call has been
+    /// sent and the skimmer's delay has passed. With less than perfect
+    /// accuracy some spots carry a busted call.
+    pub fn hear(
+        &mut self,
+        caller: &StationParams,
+        now: Instant,
+        settings: &SkimmerSettings,
+        rng: &mut dyn RngCore,
+    ) {
+        let callsign = if rng.gen::<f32>() < settings.accuracy {
+            caller.callsign.clone()
+        } else {
+            confused_callsign(&caller.callsign, rng)
+        };
+        let decoded_after = caller.reaction_delay_ms as u64
+            + message_duration_ms(&caller.callsign, caller.wpm)
+            + settings.delay_ms as u64;
+        self.pending.push(Spot {
+            callsign,
+            offset_hz: caller.frequency_offset_hz,
+            wpm: caller.wpm,
+            spotted_at: now + Duration::from_millis(decoded_after),
+        });
+    }
+
+    /// Post spots that are due and drop old ones
+    pub fn update(&mut self, now: Instant) {
+        let (due, waiting): (Vec<Spot>, Vec<Spot>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|spot| spot.spotted_at <= now);
+        self.pending = waiting;
+        for spot in due {
+            // A call spotted again moves back to the top
+            self.spots.retain(|s| s.callsign != spot.callsign);
+            self.spots.insert(0, spot);
+        }
+        self.spots
+            .retain(|spot| now.saturating_duration_since(spot.spotted_at) < SPOT_LIFETIME);
+        self.spots.truncate(MAX_SPOTS);
+    }
+
+    pub fn spots(&self) -> &[Spot] {
+        &self.spots
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.spots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::Exchange;
+    use crate::messages::StationId;
+
+    fn caller(callsign: &str) -> StationParams {
+        StationParams {
+            id: StationId(1),
+            callsign: callsign.to_string(),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 120.0,
+            wpm: 30,
+            amplitude: 0.5,
+            reaction_delay_ms: 200,
+        }
+    }
+
+    #[test]
+    fn spots_appear_after_the_call_and_delay() {
+        let settings = SkimmerSettings {
+            accuracy: 1.0,
+            delay_ms: 1000,
+        };
+        let mut skimmer = Skimmer::new();
+        let now = Instant::now();
+        skimmer.hear(&caller("K1ABC"), now, &settings, &mut rand::thread_rng());
+
+        skimmer.update(now + Duration::from_millis(1000));
+        assert!(skimmer.spots().is_empty());
+
+        let sent = 200 + message_duration_ms("K1ABC", 30) + 1000;
+        skimmer.update(now + Duration::from_millis(sent));
+        assert_eq!(skimmer.spots().len(), 1);
+        assert_eq!(skimmer.spots()[0].callsign, "K1ABC");
+        assert_eq!(skimmer.spots()[0].offset_hz, 120.0);
+
+        // Spotted again: still one entry
+        skimmer.hear(&caller("K1ABC"), now, &settings, &mut rand::thread_rng());
+        skimmer.update(now + Duration::from_millis(sent));
+        assert_eq!(skimmer.spots().len(), 1);
+
+        skimmer.update(now + SPOT_LIFETIME + Duration::from_millis(sent));
+        assert!(skimmer.spots().is_empty());
+    }
+
+    #[test]
+    fn inaccurate_skimmer_busts_calls() {
+        let settings = SkimmerSettings {
+            accuracy: 0.0,
+            delay_ms: 0,
+        };
+        let mut skimmer = Skimmer::new();
+        let now = Instant::now();
+        skimmer.hear(&caller("K1ABC"), now, &settings, &mut rand::thread_rng());
+        skimmer.update(now + Duration::from_secs(10));
+        let spot = &skimmer.spots()[0];
+        assert_ne!(spot.callsign, "K1ABC");
+        assert_eq!(spot.callsign.len(), 5);
+    }
+}
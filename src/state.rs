@@ -3,9 +3,11 @@
 //! This module defines the information-driven state machine that tracks
 //! QSO progress and allows flexible user actions.
 
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
-use crate::app::ActiveCaller;
+use crate::engine::ActiveCaller;
+use crate::messages::StationId;
 
 /// Tracks what information has been successfully communicated during a QSO
 #[derive(Clone, Debug, Default)]
@@ -14,10 +16,14 @@ pub struct QsoProgress {
     pub sent_their_call: bool,
     /// We have completed sending our exchange
     pub sent_our_exchange: bool,
+    /// We have completed sending our own call, answering someone's CQ
+    pub sent_our_call: bool,
     /// We have received the caller's callsign (user entered something)
     pub received_their_call: bool,
     /// We have received the caller's exchange (user entered something)
     pub received_their_exchange: bool,
+    /// Our last transmission was aborted before it finished
+    pub user_tx_aborted: bool,
 }
 
 impl QsoProgress {
@@ -31,6 +37,24 @@ impl QsoProgress {
     }
 }
 
+/// A caller's request for one element of our exchange, e.g. "NR?"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FillRequest {
+    /// Index into our exchange fields
+    pub index: usize,
+    /// What the caller sent
+    pub request: String,
+}
+
+/// How we answered a fill request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillAnswer {
+    /// Just the element asked for
+    Element,
+    /// The whole exchange again
+    FullExchange,
+}
+
 /// Context data for the current QSO, separate from the state enum
 #[derive(Clone, Debug)]
 pub struct QsoContext {
@@ -56,6 +80,21 @@ pub struct QsoContext {
     pub caller_exchange_sent_once: bool,
     /// Whether we expect to send our exchange next (suppress caller response)
     pub awaiting_user_exchange: bool,
+    /// Callsigns that answered our last partial query (e.g. "W1?")
+    pub partial_matches: Vec<String>,
+    /// Two callers doubling on top of each other, until the user deals with it
+    pub double: Option<[StationId; 2]>,
+    /// The element of our exchange the caller asked for; once asked, the
+    /// caller doesn't ask again in this QSO
+    pub fill_request: Option<FillRequest>,
+    /// How we answered the fill request, once we have
+    pub fill_answer: Option<FillAnswer>,
+    /// Element of the caller's exchange we asked for ("SEC?"), until they
+    /// send it
+    pub element_repeat: Option<usize>,
+    /// Labels of the elements we asked to have repeated this QSO ("Call"
+    /// for the callsign), once per request
+    pub repeat_requests: Vec<String>,
 }
 
 impl Default for QsoContext {
@@ -78,6 +117,12 @@ impl QsoContext {
             allow_callsign_repeat_ack: false,
             caller_exchange_sent_once: false,
             awaiting_user_exchange: false,
+            partial_matches: Vec::new(),
+            double: None,
+            fill_request: None,
+            fill_answer: None,
+            element_repeat: None,
+            repeat_requests: Vec::new(),
         }
     }
 
@@ -94,6 +139,12 @@ impl QsoContext {
         self.allow_callsign_repeat_ack = false;
         self.caller_exchange_sent_once = false;
         self.awaiting_user_exchange = false;
+        self.partial_matches.clear();
+        self.double = None;
+        self.fill_request = None;
+        self.fill_answer = None;
+        self.element_repeat = None;
+        self.repeat_requests.clear();
     }
 
     /// Set up context for a new set of callers
@@ -125,6 +176,18 @@ impl QsoContext {
         !self.progress.received_their_call
     }
 
+    /// Callsigns of the doubling pair, if a double is on the air
+    pub fn double_calls(&self) -> Option<Vec<&str>> {
+        let pair = self.double?;
+        Some(
+            self.active_callers
+                .iter()
+                .filter(|c| pair.contains(&c.params.id))
+                .map(|c| c.params.callsign.as_str())
+                .collect(),
+        )
+    }
+
     /// Increment correction attempt
     pub fn increment_correction_attempt(&mut self) {
         self.correction_attempts += 1;
@@ -140,15 +203,15 @@ impl QsoContext {
         self.correction_in_progress = false;
     }
 
-    /// Set the wait timer
-    pub fn set_wait(&mut self, duration_ms: u64) {
-        self.wait_until = Some(Instant::now() + std::time::Duration::from_millis(duration_ms));
+    /// Set the wait timer to run out `duration_ms` after `now`
+    pub fn set_wait(&mut self, duration_ms: u64, now: Instant) {
+        self.wait_until = Some(now + std::time::Duration::from_millis(duration_ms));
     }
 
-    /// Check if wait timer has elapsed
-    pub fn wait_elapsed(&self) -> bool {
+    /// Check if wait timer has elapsed by `now`
+    pub fn wait_elapsed(&self, now: Instant) -> bool {
         match self.wait_until {
-            Some(until) => Instant::now() >= until,
+            Some(until) => now >= until,
             None => true,
         }
     }
@@ -168,6 +231,8 @@ pub enum UserTxType {
     CallsignOnly,
     /// Sending just our exchange (F2)
     ExchangeOnly,
+    /// Sending just our callsign, answering a CQ (Enter in callsign field)
+    OurCallsign,
     /// Sending AGN/? request (F8)
     Agn,
     /// Sending TU (F3 or after logging)
@@ -182,6 +247,8 @@ pub enum StationTxType {
     SendingExchange,
     /// Station requesting AGN (sending "AGN" or "?")
     RequestingAgn,
+    /// Station asking for one element of our exchange (e.g. "NR?")
+    RequestingFill,
     /// Station sending callsign correction
     Correction,
 }
@@ -218,7 +285,101 @@ pub enum ContestState {
     QsoComplete,
 }
 
+/// Which side of the QSOs we're on in a contest with a QSY rule, where
+/// the frequency goes to whoever answered the CQ
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Role {
+    /// Holding a frequency and calling CQ
+    #[default]
+    Running,
+    /// Moved off after a run QSO: the next QSO answers someone else's CQ
+    Answering,
+}
+
+impl Role {
+    /// The side we're on after logging a QSO: a run QSO hands the
+    /// frequency over, and answering a CQ leaves us holding it
+    pub fn after_qso(self) -> Self {
+        match self {
+            Role::Running => Role::Answering,
+            Role::Answering => Role::Running,
+        }
+    }
+}
+
+/// What the app does next when a transmission ends or a wait runs out.
+/// States with no side effects are entered directly; the rest name the
+/// step so the app can do its part (timers, tail-enders, replies).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// Nothing changes
+    Stay,
+    /// Move to this state
+    To(ContestState),
+    /// Give the station the reply gap, then WaitingForStation
+    AwaitStation,
+    /// The wait is over and the station answers
+    StationReplies,
+    /// The QSO is over: a tail-ender may call, otherwise back to Idle
+    QsoOver,
+}
+
 impl ContestState {
+    /// Our CQ or message finished playing. `callers_waiting` is whether
+    /// anyone started calling before the CQ ended.
+    pub fn on_user_message_complete(self, callers_waiting: bool) -> Transition {
+        match self {
+            ContestState::CallingCq if callers_waiting => {
+                Transition::To(ContestState::StationsCalling)
+            }
+            ContestState::CallingCq => Transition::To(ContestState::WaitingForCallers),
+            ContestState::UserTransmitting {
+                tx_type: UserTxType::Tu,
+            }
+            | ContestState::QsoComplete => Transition::QsoOver,
+            ContestState::UserTransmitting { .. } => Transition::AwaitStation,
+            _ => Transition::Stay,
+        }
+    }
+
+    /// We cut our own transmission short with Escape
+    pub fn on_user_abort(self) -> Transition {
+        match self {
+            // Nobody answers a CQ that was cut off
+            ContestState::CallingCq => Transition::To(ContestState::Idle),
+            // A clipped TU still ends the QSO
+            ContestState::UserTransmitting {
+                tx_type: UserTxType::Tu,
+            } => Transition::QsoOver,
+            // The caller reacts to whatever part they heard
+            ContestState::UserTransmitting { .. } => Transition::AwaitStation,
+            _ => Transition::Stay,
+        }
+    }
+
+    /// The station we're working finished sending
+    pub fn on_station_message_complete(self) -> Transition {
+        match self {
+            // A request or correction hands the turn back to us
+            ContestState::StationTransmitting {
+                tx_type:
+                    StationTxType::RequestingAgn
+                    | StationTxType::RequestingFill
+                    | StationTxType::Correction,
+            } => Transition::To(ContestState::StationsCalling),
+            // The exchange stays up until we log it
+            _ => Transition::Stay,
+        }
+    }
+
+    /// The wait set in QsoContext ran out
+    pub fn on_wait_elapsed(self) -> Transition {
+        match self {
+            ContestState::WaitingForStation => Transition::StationReplies,
+            _ => Transition::Stay,
+        }
+    }
+
     /// Get status text and color for UI display
     pub fn status_text(&self, context: &QsoContext) -> (&'static str, StatusColor) {
         match self {
@@ -242,6 +403,7 @@ impl ContestState {
                     }
                 }
                 UserTxType::ExchangeOnly => ("Sending exchange...", StatusColor::Yellow),
+                UserTxType::OurCallsign => ("Sending callsign...", StatusColor::Yellow),
                 UserTxType::Agn => ("Requesting repeat...", StatusColor::Yellow),
                 UserTxType::Tu => ("Sending TU...", StatusColor::Yellow),
             },
@@ -260,6 +422,10 @@ impl ContestState {
                 StationTxType::RequestingAgn => {
                     ("Station requests repeat - press F2", StatusColor::Orange)
                 }
+                StationTxType::RequestingFill => (
+                    "Station asks for a fill - press Shift+F2",
+                    StatusColor::Orange,
+                ),
                 StationTxType::Correction => {
                     ("Station correcting callsign...", StatusColor::Orange)
                 }
@@ -308,6 +474,141 @@ mod tests {
         assert!(!progress.received_their_exchange);
     }
 
+    const USER_TX: [UserTxType; 6] = [
+        UserTxType::Exchange,
+        UserTxType::CallsignOnly,
+        UserTxType::ExchangeOnly,
+        UserTxType::OurCallsign,
+        UserTxType::Agn,
+        UserTxType::Tu,
+    ];
+
+    const STATION_TX: [StationTxType; 4] = [
+        StationTxType::SendingExchange,
+        StationTxType::RequestingAgn,
+        StationTxType::RequestingFill,
+        StationTxType::Correction,
+    ];
+
+    /// Every state, with each transmission type
+    fn all_states() -> Vec<ContestState> {
+        let mut states = vec![
+            ContestState::Idle,
+            ContestState::CallingCq,
+            ContestState::WaitingForCallers,
+            ContestState::StationsCalling,
+            ContestState::WaitingForStation,
+            ContestState::QsoComplete,
+        ];
+        states.extend(USER_TX.map(|tx_type| ContestState::UserTransmitting { tx_type }));
+        states.extend(STATION_TX.map(|tx_type| ContestState::StationTransmitting { tx_type }));
+        states
+    }
+
+    #[test]
+    fn roles_take_turns_under_the_qsy_rule() {
+        assert_eq!(Role::default(), Role::Running);
+        assert_eq!(Role::Running.after_qso(), Role::Answering);
+        assert_eq!(Role::Answering.after_qso(), Role::Running);
+    }
+
+    #[test]
+    fn finished_cq_waits_for_callers_unless_someone_jumped_in() {
+        assert_eq!(
+            ContestState::CallingCq.on_user_message_complete(false),
+            Transition::To(ContestState::WaitingForCallers)
+        );
+        assert_eq!(
+            ContestState::CallingCq.on_user_message_complete(true),
+            Transition::To(ContestState::StationsCalling)
+        );
+    }
+
+    #[test]
+    fn finished_messages_wait_for_the_station_or_end_the_qso() {
+        for tx_type in USER_TX {
+            let expected = if tx_type == UserTxType::Tu {
+                Transition::QsoOver
+            } else {
+                Transition::AwaitStation
+            };
+            let state = ContestState::UserTransmitting { tx_type };
+            assert_eq!(state.on_user_message_complete(false), expected);
+            assert_eq!(state.on_user_message_complete(true), expected);
+        }
+        assert_eq!(
+            ContestState::QsoComplete.on_user_message_complete(false),
+            Transition::QsoOver
+        );
+        for state in [
+            ContestState::Idle,
+            ContestState::WaitingForCallers,
+            ContestState::StationsCalling,
+            ContestState::WaitingForStation,
+        ] {
+            assert_eq!(state.on_user_message_complete(true), Transition::Stay);
+        }
+    }
+
+    #[test]
+    fn aborting_keeps_the_qso_going_except_for_cq_and_tu() {
+        assert_eq!(
+            ContestState::CallingCq.on_user_abort(),
+            Transition::To(ContestState::Idle)
+        );
+        for tx_type in USER_TX {
+            let expected = if tx_type == UserTxType::Tu {
+                Transition::QsoOver
+            } else {
+                Transition::AwaitStation
+            };
+            assert_eq!(
+                ContestState::UserTransmitting { tx_type }.on_user_abort(),
+                expected
+            );
+        }
+        for state in all_states() {
+            if !matches!(
+                state,
+                ContestState::CallingCq | ContestState::UserTransmitting { .. }
+            ) {
+                assert_eq!(state.on_user_abort(), Transition::Stay);
+            }
+        }
+    }
+
+    #[test]
+    fn station_requests_hand_the_turn_back() {
+        for tx_type in STATION_TX {
+            let expected = if tx_type == StationTxType::SendingExchange {
+                Transition::Stay
+            } else {
+                Transition::To(ContestState::StationsCalling)
+            };
+            assert_eq!(
+                ContestState::StationTransmitting { tx_type }.on_station_message_complete(),
+                expected
+            );
+        }
+        for state in all_states() {
+            if !matches!(state, ContestState::StationTransmitting { .. }) {
+                assert_eq!(state.on_station_message_complete(), Transition::Stay);
+            }
+        }
+    }
+
+    #[test]
+    fn only_waiting_for_station_is_timed() {
+        for state in all_states() {
+            let expected = if state == ContestState::WaitingForStation {
+                Transition::StationReplies
+            } else {
+                Transition::Stay
+            };
+            assert_eq!(state.on_wait_elapsed(), expected);
+        }
+    }
+
     #[test]
     fn test_qso_context_callers() {
         use crate::contest::Exchange;
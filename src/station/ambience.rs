@@ -0,0 +1,195 @@
+// Background signals on frequency that aren't calling us: QRL?, tune-ups
+// and other stations' CQs
+
+use rand::{Rng, RngCore};
+use std::time::{Duration, Instant};
+
+use crate::config::AmbienceSettings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmbienceEvent {
+    /// Someone checks whether the frequency is in use
+    QrlQuery,
+    /// A brief carrier from someone tuning up
+    TuneUp,
+    /// Someone else calls CQ on our frequency
+    CqOnFrequency,
+}
+
+impl AmbienceEvent {
+    /// What the station sends; `callsign` signs a CQ
+    pub fn message(self, callsign: &str, rng: &mut dyn RngCore) -> String {
+        match self {
+            AmbienceEvent::QrlQuery => {
+                if rng.gen::<bool>() {
+                    "QRL?".to_string()
+                } else {
+                    "QRL? QRL?".to_string()
+                }
+            }
+            AmbienceEvent::TuneUp => {
+                const TUNE_UPS: [&str; 3] = ["~", "~ ~", "VVV ~"];
+                TUNE_UPS[rng.gen_range(0..TUNE_UPS.len())].to_string()
+            }
+            AmbienceEvent::CqOnFrequency => format!("CQ TEST {} {}", callsign, callsign),
+        }
+    }
+}
+
+/// Decides when the next background event happens and what it is
+#[derive(Default)]
+pub struct AmbienceScheduler {
+    next_at: Option<Instant>,
+}
+
+impl AmbienceScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The event due at `now`, if any. A CQ on frequency only happens
+    /// while we're not running, the way someone finds a clear frequency.
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        settings: &AmbienceSettings,
+        running: bool,
+        rng: &mut dyn RngCore,
+    ) -> Option<AmbienceEvent> {
+        if settings.events_per_minute <= 0.0 {
+            self.next_at = None;
+            return None;
+        }
+
+        let mean = Duration::from_secs_f32(60.0 / settings.events_per_minute);
+        match self.next_at {
+            // Raising the rate shouldn't leave a long wait from the old one
+            Some(next_at) if next_at <= now + mean.mul_f32(1.5) => {
+                if now < next_at {
+                    return None;
+                }
+            }
+            _ => {
+                self.next_at = Some(now + random_interval(mean, rng));
+                return None;
+            }
+        }
+        self.next_at = Some(now + random_interval(mean, rng));
+
+        let kinds: Vec<AmbienceEvent> = [
+            (AmbienceEvent::QrlQuery, settings.qrl_query),
+            (AmbienceEvent::TuneUp, settings.tune_up),
+            (
+                AmbienceEvent::CqOnFrequency,
+                settings.cq_on_frequency && !running,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(kind, _)| kind)
+        .collect();
+        if kinds.is_empty() {
+            return None;
+        }
+        Some(kinds[rng.gen_range(0..kinds.len())])
+    }
+}
+
+/// Between half and one and a half times the mean, so events don't tick
+/// like a clock
+fn random_interval(mean: Duration, rng: &mut dyn RngCore) -> Duration {
+    mean.mul_f32(rng.gen_range(0.5..1.5))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(events_per_minute: f32) -> AmbienceSettings {
+        AmbienceSettings {
+            events_per_minute,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn events_follow_the_rate() {
+        let mut scheduler = AmbienceScheduler::new();
+        let start = Instant::now();
+        assert_eq!(
+            scheduler.poll(start, &settings(0.0), false, &mut rand::thread_rng()),
+            None
+        );
+
+        // One a minute: nothing yet, then one within a minute and a half
+        let per_minute = settings(1.0);
+        assert_eq!(
+            scheduler.poll(start, &per_minute, false, &mut rand::thread_rng()),
+            None
+        );
+        assert_eq!(
+            scheduler.poll(
+                start + Duration::from_secs(29),
+                &per_minute,
+                false,
+                &mut rand::thread_rng()
+            ),
+            None
+        );
+        assert!(scheduler
+            .poll(
+                start + Duration::from_secs(90),
+                &per_minute,
+                false,
+                &mut rand::thread_rng()
+            )
+            .is_some());
+        // The next one is at least half a minute away
+        assert_eq!(
+            scheduler.poll(
+                start + Duration::from_secs(100),
+                &per_minute,
+                false,
+                &mut rand::thread_rng()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn cq_on_frequency_waits_until_we_stop_running() {
+        let only_cq = AmbienceSettings {
+            events_per_minute: 60.0,
+            qrl_query: false,
+            tune_up: false,
+            cq_on_frequency: true,
+        };
+        let mut scheduler = AmbienceScheduler::new();
+        let start = Instant::now();
+        scheduler.poll(start, &only_cq, true, &mut rand::thread_rng());
+        let later = start + Duration::from_secs(2);
+        assert_eq!(
+            scheduler.poll(later, &only_cq, true, &mut rand::thread_rng()),
+            None
+        );
+        let later = later + Duration::from_secs(2);
+        assert_eq!(
+            scheduler.poll(later, &only_cq, false, &mut rand::thread_rng()),
+            Some(AmbienceEvent::CqOnFrequency)
+        );
+    }
+
+    #[test]
+    fn messages_are_not_calls() {
+        assert!(AmbienceEvent::QrlQuery
+            .message("K1ABC", &mut rand::thread_rng())
+            .starts_with("QRL?"));
+        assert!(AmbienceEvent::TuneUp
+            .message("K1ABC", &mut rand::thread_rng())
+            .contains('~'));
+        assert_eq!(
+            AmbienceEvent::CqOnFrequency.message("K1ABC", &mut rand::thread_rng()),
+            "CQ TEST K1ABC K1ABC"
+        );
+    }
+}
@@ -1,12 +1,41 @@
-use rand::Rng;
+use rand::{Rng, RngCore};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-use crate::config::{PileupSettings, SimulationSettings};
-use crate::contest::{CallsignSource, Contest};
+use crate::config::{
+    CallerFormat, GiveUpBehavior, PileupSettings, ReactionTimeSettings, SimulationSettings,
+};
+use crate::contest::{CallsignSource, Contest, Exchange};
 use crate::cty::CtyDat;
 use crate::messages::{StationId, StationParams};
 use crate::state::{QsoContext, QsoProgress};
+use crate::station::ambience::AmbienceEvent;
+use crate::station::focus::CharFocus;
+
+/// Range of QSOs per minute a simulated station makes, which sets how fast
+/// caller serial numbers count up over the session
+const SERIAL_RATE_MIN: f32 = 0.5;
+const SERIAL_RATE_MAX: f32 = 2.0;
+
+/// Smallest gap between the starts of two callers in a pileup (ms), so
+/// each call can be picked out from the others
+const MIN_CALLER_START_GAP_MS: u32 = 50;
+
+/// Closest two callers in a wide pileup sit (Hz), so RIT can bring in one
+/// at a time
+const WIDE_MIN_SEPARATION_HZ: f32 = 150.0;
+/// Placements tried before settling for the least crowded one
+const WIDE_PLACEMENT_TRIES: usize = 20;
+
+/// Largest pitch difference between two doubling callers (Hz)
+const DOUBLE_MAX_SPLIT_HZ: f32 = 15.0;
+/// Largest difference in when two doubling callers start (ms)
+const DOUBLE_MAX_START_GAP_MS: u32 = 150;
+
+/// How far off zero beat a CQ we tune to can be (Hz)
+const CQ_TUNING_ERROR_HZ: f32 = 40.0;
+/// How much louder than the loudest caller a station taking our frequency is
+const SQUATTER_LOUDNESS: f32 = 1.5;
 
 /// How a caller should respond based on what they've heard
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -41,8 +70,13 @@ impl CallerResponse {
     }
 
     /// Determine caller response using both QSO progress and context.
+    /// A caller only waits quietly for our exchange if our last message
+    /// wasn't cut off; after an abort they ask for the rest.
     pub fn from_progress_and_context(progress: &QsoProgress, context: &QsoContext) -> Self {
-        if context.awaiting_user_exchange && progress.sent_their_call && !progress.sent_our_exchange
+        if context.awaiting_user_exchange
+            && !progress.user_tx_aborted
+            && progress.sent_their_call
+            && !progress.sent_our_exchange
         {
             return CallerResponse::Wait;
         }
@@ -76,12 +110,14 @@ pub struct PersistentCaller {
     pub state: CallerState,
     /// When the caller will be ready to try again
     pub ready_at: Instant,
+    /// Never listens: calls after every one of our transmissions
+    pub continuous: bool,
 }
 
 impl PersistentCaller {
     /// Check if this caller is ready to call (waiting and delay elapsed)
-    pub fn is_ready_to_call(&self) -> bool {
-        self.state == CallerState::Waiting && Instant::now() >= self.ready_at
+    pub fn is_ready_to_call(&self, now: Instant) -> bool {
+        self.state == CallerState::Waiting && now >= self.ready_at
     }
 
     /// Check if caller has given up (exceeded patience)
@@ -94,11 +130,16 @@ impl PersistentCaller {
         self.attempts += 1;
     }
 
-    /// Set delay before next call attempt
-    pub fn set_retry_delay(&mut self, min_ms: u32, max_ms: u32) {
-        let mut rng = rand::thread_rng();
+    /// Set delay before next call attempt, counted from `now`
+    pub fn set_retry_delay(
+        &mut self,
+        min_ms: u32,
+        max_ms: u32,
+        now: Instant,
+        rng: &mut dyn RngCore,
+    ) {
         let delay = rng.gen_range(min_ms..=max_ms);
-        self.ready_at = Instant::now() + Duration::from_millis(delay as u64);
+        self.ready_at = now + Duration::from_millis(delay as u64);
         self.state = CallerState::Waiting;
     }
 
@@ -129,10 +170,35 @@ pub struct CallerManager {
 
     /// Last time we tried to add callers to the queue
     last_replenish: Instant,
+
+    /// Exchange each callsign has sent this session (and when), so a station
+    /// that calls again sends the same name/check/section as before
+    exchange_cache: HashMap<String, (Exchange, Instant)>,
+
+    /// When the session started; caller serials grow with time on the air
+    session_start: Instant,
+
+    /// Callers who gave up since the app last asked
+    given_up: Vec<StationParams>,
+
+    /// Characters the user miscopies, for the weak-character focus
+    char_focus: CharFocus,
+
+    /// Share of callers who get through a signal on our frequency (1.0
+    /// when it's clear)
+    heard_share: f32,
+
+    /// Station probability earned by the run, in place of the configured
+    /// one while dynamic density is on
+    density: Option<f32>,
 }
 
 impl CallerManager {
-    pub fn new(callsigns: Box<dyn CallsignSource>, settings: SimulationSettings) -> Self {
+    pub fn new(
+        callsigns: Box<dyn CallsignSource>,
+        settings: SimulationSettings,
+        now: Instant,
+    ) -> Self {
         let pileup_settings = settings.pileup.clone();
         Self {
             callsigns,
@@ -142,7 +208,13 @@ impl CallerManager {
             serial_counter: 1,
             queue: Vec::new(),
             active_ids: Vec::new(),
-            last_replenish: Instant::now(),
+            last_replenish: now,
+            exchange_cache: HashMap::new(),
+            session_start: now,
+            given_up: Vec::new(),
+            char_focus: CharFocus::default(),
+            heard_share: 1.0,
+            density: None,
         }
     }
 
@@ -156,8 +228,60 @@ impl CallerManager {
     pub fn update_callsigns(&mut self, callsigns: Box<dyn CallsignSource>) {
         self.callsigns = callsigns;
         // Clear queue when callsigns change
+        self.clear_queue();
+    }
+
+    /// Send away every waiting caller, e.g. so new settings apply to all
+    pub fn clear_queue(&mut self) {
         self.queue.clear();
         self.active_ids.clear();
+        self.exchange_cache.clear();
+        self.given_up.clear();
+    }
+
+    /// Swap in a reloaded pool of the same callsigns file. Callers already
+    /// on frequency stay; new ones come from the reloaded pool.
+    pub fn reload_callsigns(&mut self, callsigns: Box<dyn CallsignSource>) {
+        self.callsigns = callsigns;
+    }
+
+    /// How many callers get through whatever else is on our frequency
+    pub fn set_heard_share(&mut self, share: f32) {
+        self.heard_share = share.clamp(0.0, 1.0);
+    }
+
+    /// We moved to a new frequency: the callers who found us on the old one
+    /// are left behind, though they keep the exchanges they've sent
+    pub fn on_qsy(&mut self) {
+        self.queue.clear();
+        self.active_ids.clear();
+    }
+
+    /// Follow the run's caller density, or the configured station
+    /// probability with `None`
+    pub fn set_density(&mut self, density: Option<f32>) {
+        self.density = density;
+    }
+
+    fn station_probability(&self) -> f32 {
+        self.density.unwrap_or(self.settings.station_probability)
+    }
+
+    /// Favor callers whose calls have the characters the user misses
+    pub fn set_char_focus(&mut self, focus: CharFocus) {
+        self.char_focus = focus;
+    }
+
+    /// Call-history entry for a callsign (see `CallsignSource::history`)
+    pub fn call_history(&self, callsign: &str) -> Option<Vec<String>> {
+        self.callsigns.history(callsign)
+    }
+
+    /// Start a new session at `now`: repeat callers may send fresh
+    /// exchanges again
+    pub fn reset_session(&mut self, now: Instant) {
+        self.exchange_cache.clear();
+        self.session_start = now;
     }
 
     /// Add new callers to the queue (call periodically to simulate stations finding frequency)
@@ -167,21 +291,25 @@ impl CallerManager {
         contest_settings: &toml::Value,
         user_callsign: Option<&str>,
         cty: Option<&CtyDat>,
+        now: Instant,
+        rng: &mut dyn RngCore,
     ) {
-        let mut rng = rand::thread_rng();
-
         // Don't replenish too often
-        if self.last_replenish.elapsed().as_millis() < 500 {
+        if now
+            .saturating_duration_since(self.last_replenish)
+            .as_millis()
+            < 500
+        {
             return;
         }
-        self.last_replenish = Instant::now();
+        self.last_replenish = now;
 
         // Target queue size based on station probability (more likely = bigger pileup)
         let target_queue_size =
             (self.settings.max_simultaneous_stations as f32 * 2.5).ceil() as usize;
 
         // Count active callers (not given up, not worked)
-        let active_in_queue = self
+        let mut active_in_queue = self
             .queue
             .iter()
             .filter(|c| c.state != CallerState::GaveUp && c.state != CallerState::Worked)
@@ -190,13 +318,15 @@ impl CallerManager {
         // Add callers if below target
         while active_in_queue < target_queue_size {
             // Probability check for adding each caller
-            if rng.gen::<f32>() > self.settings.station_probability {
+            if rng.gen::<f32>() > self.station_probability() {
                 break;
             }
 
-            if let Some(caller) = self.create_caller(contest, contest_settings, user_callsign, cty)
+            if let Some(caller) =
+                self.create_caller(contest, contest_settings, user_callsign, cty, now, rng)
             {
                 self.queue.push(caller);
+                active_in_queue += 1;
             } else {
                 break;
             }
@@ -210,17 +340,17 @@ impl CallerManager {
         contest_settings: &toml::Value,
         user_callsign: Option<&str>,
         cty: Option<&CtyDat>,
+        now: Instant,
+        rng: &mut dyn RngCore,
     ) -> Option<PersistentCaller> {
-        let mut rng = rand::thread_rng();
-
-        // Pick a random callsign with same-country filtering
-        let max_retries = 10;
+        // Pick a random callsign with same-country and continent filtering
+        let max_retries = 25;
         let mut callsign_and_exchange = None;
 
         for _ in 0..max_retries {
             let Some((callsign, exchange)) =
                 self.callsigns
-                    .random(contest, self.serial_counter, contest_settings)
+                    .random(contest, self.serial_counter, contest_settings, rng)
             else {
                 break;
             };
@@ -242,7 +372,22 @@ impl CallerManager {
                 false
             };
 
-            if !should_reject {
+            // Thin out continents the user has weighted down
+            let continent_reject = cty
+                .and_then(|cty_db| cty_db.lookup_continent(&callsign))
+                .map(|continent| {
+                    rng.gen::<f32>() >= self.settings.continent_weights.keep_probability(continent)
+                })
+                .unwrap_or(false);
+
+            // Pass over calls without the characters the user misses most
+            let focus_reject = !self.char_focus.is_empty()
+                && rng.gen::<f32>()
+                    >= self
+                        .char_focus
+                        .keep_probability(&callsign, self.settings.weak_character_focus);
+
+            if !should_reject && !continent_reject && !focus_reject {
                 callsign_and_exchange = Some((callsign, exchange));
                 break;
             }
@@ -250,18 +395,33 @@ impl CallerManager {
 
         let (callsign, exchange) = callsign_and_exchange?;
 
+        let exchange = self.session_exchange(contest, &callsign, exchange, now, rng);
+
         // Random parameters
         let wpm = rng.gen_range(self.settings.wpm_min..=self.settings.wpm_max);
-        let half_width = (self.settings.frequency_spread_hz / 2.0).max(0.0);
-        let freq_offset = rng.gen_range(-half_width..half_width);
-        let amplitude = rng.gen_range(self.settings.amplitude_min..self.settings.amplitude_max);
+        let freq_offset = if self.settings.wide_pileup.enabled {
+            let taken: Vec<f32> = self
+                .queue
+                .iter()
+                .map(|c| c.params.frequency_offset_hz)
+                .collect();
+            wide_offset(&taken, self.settings.wide_pileup.spread_hz / 2.0, rng)
+        } else {
+            let half_width = (self.settings.frequency_spread_hz / 2.0).max(0.0);
+            rng.gen_range(-half_width..half_width)
+        };
+        let amplitude = rng.gen_range(self.settings.amplitude_min..=self.settings.amplitude_max);
 
         // Random patience (1-7 attempts)
         let patience =
             rng.gen_range(self.pileup_settings.min_patience..=self.pileup_settings.max_patience);
 
+        let continuous = rng.gen::<f32>() < self.settings.lids.continuous_caller_probability;
+
         // Random reaction time (faster operators call sooner)
-        let reaction_delay_ms = rng.gen_range(100..800);
+        let reaction = &self.settings.reaction_time;
+        let reaction_delay_ms = rng
+            .gen_range(reaction.min_ms.min(reaction.max_ms)..=reaction.max_ms.max(reaction.min_ms));
 
         self.next_id += 1;
 
@@ -278,23 +438,61 @@ impl CallerManager {
             patience,
             attempts: 0,
             state: CallerState::Waiting,
-            ready_at: Instant::now(),
+            ready_at: now,
+            continuous,
         })
     }
 
-    /// Called when CQ completes - select callers to respond
-    /// Returns list of callers that will call (as StationParams for audio)
+    /// Exchange a caller sends this session. A station heard before keeps
+    /// its exchange, with its serial moved on by the QSOs it has made since;
+    /// a new station's serial starts higher the longer the session has run.
+    fn session_exchange(
+        &mut self,
+        contest: &dyn Contest,
+        callsign: &str,
+        fresh: Exchange,
+        now: Instant,
+        rng: &mut dyn RngCore,
+    ) -> Exchange {
+        let serial_index = contest.caller_serial_index();
+        let exchange = match self.exchange_cache.get(callsign) {
+            Some((previous, heard_at)) => {
+                let mut exchange = previous.clone();
+                // Calling again means at least one more QSO in their log
+                let qsos = serial_growth(now.saturating_duration_since(*heard_at), rng).max(1);
+                advance_serial(contest, &mut exchange, serial_index, qsos);
+                exchange
+            }
+            None => {
+                let mut exchange = fresh;
+                let qsos = serial_growth(now.saturating_duration_since(self.session_start), rng);
+                advance_serial(contest, &mut exchange, serial_index, qsos);
+                exchange
+            }
+        };
+        self.exchange_cache
+            .insert(callsign.to_string(), (exchange.clone(), now));
+        exchange
+    }
+
+    /// Called when CQ completes (or is about to) - select callers to respond
+    /// Returns list of callers that will call (as StationParams for audio).
+    /// `cq_remaining_ms` is how long until the CQ ends; each caller's
+    /// reaction delay is measured from that point, so tailgaters can start
+    /// before it and late callers well after.
+    #[allow(clippy::too_many_arguments)]
     pub fn on_cq_complete(
         &mut self,
         contest: &dyn Contest,
         contest_settings: &toml::Value,
         user_callsign: Option<&str>,
         cty: Option<&CtyDat>,
+        cq_remaining_ms: u32,
+        now: Instant,
+        rng: &mut dyn RngCore,
     ) -> Vec<StationParams> {
-        let mut rng = rand::thread_rng();
-
         // First, replenish the queue
-        self.replenish_queue(contest, contest_settings, user_callsign, cty);
+        self.replenish_queue(contest, contest_settings, user_callsign, cty, now, rng);
 
         // Clean up worked/given-up callers
         self.queue
@@ -327,8 +525,13 @@ impl CallerManager {
             }
 
             // Probability check - more persistent callers are more likely to call
+            // (continuous callers always call)
             let call_probability = 0.5 + (caller.patience as f32 - 1.0) * 0.1;
-            if rng.gen::<f32>() > call_probability {
+            if !caller.continuous && rng.gen::<f32>() > call_probability {
+                continue;
+            }
+            // Lost under a louder signal this time round
+            if self.heard_share < 1.0 && rng.gen::<f32>() >= self.heard_share {
                 continue;
             }
 
@@ -336,24 +539,35 @@ impl CallerManager {
             caller.mark_calling();
             caller.record_attempt();
             self.active_ids.push(caller.params.id);
-            responding.push(caller.params.clone());
+            let mut params = caller.params.clone();
+            params.reaction_delay_ms = response_delay_ms(
+                &self.settings.reaction_time,
+                params.reaction_delay_ms,
+                cq_remaining_ms,
+                rng,
+            );
+            responding.push(params);
         }
 
+        stagger_start_times(&mut responding, MIN_CALLER_START_GAP_MS);
         responding
     }
 
     /// Called when user presses F1 again without completing QSO
     /// Callers that were calling get another chance (patience permitting)
-    pub fn on_cq_restart(&mut self) {
+    pub fn on_cq_restart(&mut self, now: Instant, rng: &mut dyn RngCore) {
         for caller in &mut self.queue {
             if caller.state == CallerState::Calling {
                 if caller.has_given_up() {
                     caller.state = CallerState::GaveUp;
+                    self.given_up.push(caller.params.clone());
                 } else {
                     // Set retry delay
                     caller.set_retry_delay(
                         self.pileup_settings.retry_delay_min_ms,
                         self.pileup_settings.retry_delay_max_ms,
+                        now,
+                        rng,
                     );
                 }
             }
@@ -369,6 +583,169 @@ impl CallerManager {
         self.active_ids.retain(|id| *id != station_id);
     }
 
+    /// Lids who call after one of our transmissions while we're working
+    /// someone else: pileup callers we didn't pick who call over the QSO,
+    /// and continuous callers who never listen
+    pub fn on_user_transmission_complete(
+        &mut self,
+        working: StationId,
+        rng: &mut dyn RngCore,
+    ) -> Vec<StationParams> {
+        let call_over_probability = self.settings.lids.call_over_probability;
+        let mut calling = Vec::new();
+
+        for caller in &mut self.queue {
+            if caller.params.id == working {
+                continue;
+            }
+            let calls = match caller.state {
+                CallerState::Calling => {
+                    caller.continuous || rng.gen::<f32>() < call_over_probability
+                }
+                CallerState::Waiting => caller.continuous,
+                CallerState::GaveUp | CallerState::Worked => false,
+            };
+            if !calls {
+                continue;
+            }
+
+            if caller.continuous {
+                // Even a lid runs out of patience eventually
+                caller.record_attempt();
+                if caller.has_given_up() {
+                    caller.state = CallerState::GaveUp;
+                    self.given_up.push(caller.params.clone());
+                    continue;
+                }
+            }
+
+            let mut params = caller.params.clone();
+            params.reaction_delay_ms = rng.gen_range(100..600);
+            calling.push(params);
+        }
+
+        calling
+    }
+
+    /// A station outside the pileup who calls while the caller we're working
+    /// is sending their exchange
+    pub fn out_of_turn_caller(
+        &mut self,
+        working: StationId,
+        rng: &mut dyn RngCore,
+    ) -> Option<StationParams> {
+        if rng.gen::<f32>() >= self.settings.lids.out_of_turn_probability {
+            return None;
+        }
+
+        let active_ids = &self.active_ids;
+        let caller = self.queue.iter().find(|c| {
+            c.state == CallerState::Waiting
+                && c.params.id != working
+                && !active_ids.contains(&c.params.id)
+        })?;
+
+        let mut params = caller.params.clone();
+        params.reaction_delay_ms = rng.gen_range(0..1000);
+        Some(params)
+    }
+
+    /// A station for a background event: off to one side of our
+    /// frequency, not part of the pileup and never worked
+    pub fn ambience_station(
+        &mut self,
+        event: AmbienceEvent,
+        contest: &dyn Contest,
+        contest_settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<StationParams> {
+        let (callsign, _) =
+            self.callsigns
+                .random(contest, self.serial_counter, contest_settings, rng)?;
+
+        let wpm = rng.gen_range(self.settings.wpm_min..=self.settings.wpm_max);
+        let half_width = (self.settings.frequency_spread_hz / 2.0).max(1.0);
+        let amplitude = rng.gen_range(self.settings.amplitude_min..=self.settings.amplitude_max);
+        self.next_id += 1;
+
+        Some(StationParams {
+            id: StationId(self.next_id),
+            callsign: event.message(&callsign, rng),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: rng.gen_range(-half_width..half_width),
+            wpm,
+            amplitude,
+            reaction_delay_ms: 0,
+        })
+    }
+
+    /// A loud station that starts calling CQ right on our run frequency:
+    /// not part of the pileup and never worked
+    pub fn squatter_station(
+        &mut self,
+        contest: &dyn Contest,
+        contest_settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<StationParams> {
+        let (callsign, _) =
+            self.callsigns
+                .random(contest, self.serial_counter, contest_settings, rng)?;
+        self.next_id += 1;
+
+        Some(StationParams {
+            id: StationId(self.next_id),
+            callsign: AmbienceEvent::CqOnFrequency.message(&callsign, rng),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: rng.gen_range(-CQ_TUNING_ERROR_HZ..CQ_TUNING_ERROR_HZ),
+            wpm: rng.gen_range(self.settings.wpm_min..=self.settings.wpm_max),
+            // Louder than anyone calling us
+            amplitude: self.settings.amplitude_max * SQUATTER_LOUDNESS,
+            reaction_delay_ms: 0,
+        })
+    }
+
+    /// A station calling CQ on a frequency we've tuned to, for us to
+    /// answer: not part of the pileup
+    pub fn cq_station(
+        &mut self,
+        contest: &dyn Contest,
+        contest_settings: &toml::Value,
+        rng: &mut dyn RngCore,
+    ) -> Option<StationParams> {
+        let (callsign, exchange) =
+            self.callsigns
+                .random(contest, self.serial_counter, contest_settings, rng)?;
+
+        let wpm = rng.gen_range(self.settings.wpm_min..=self.settings.wpm_max);
+        let amplitude = rng.gen_range(self.settings.amplitude_min..=self.settings.amplitude_max);
+        self.next_id += 1;
+
+        Some(StationParams {
+            id: StationId(self.next_id),
+            callsign,
+            exchange,
+            // Tuned in by ear, so close to zero beat
+            frequency_offset_hz: rng.gen_range(-CQ_TUNING_ERROR_HZ..CQ_TUNING_ERROR_HZ),
+            wpm,
+            amplitude,
+            reaction_delay_ms: 0,
+        })
+    }
+
+    /// How many stations are in the pileup: waiting or calling, not yet
+    /// worked and not given up
+    pub fn pileup_depth(&self) -> usize {
+        self.queue
+            .iter()
+            .filter(|c| c.state == CallerState::Waiting || c.state == CallerState::Calling)
+            .count()
+    }
+
+    /// Callers who ran out of patience since the last call
+    pub fn take_given_up(&mut self) -> Vec<StationParams> {
+        std::mem::take(&mut self.given_up)
+    }
+
     /// Called when audio for a station completes
     pub fn station_audio_complete(&mut self, _id: StationId) {
         // Currently just for tracking - caller remains in active state
@@ -383,16 +760,16 @@ impl CallerManager {
         contest_settings: &toml::Value,
         user_callsign: Option<&str>,
         cty: Option<&CtyDat>,
+        now: Instant,
+        rng: &mut dyn RngCore,
     ) -> Option<StationParams> {
-        let mut rng = rand::thread_rng();
-
         // Probability check
-        if rng.gen::<f32>() > self.settings.station_probability {
+        if rng.gen::<f32>() > self.station_probability() {
             return None;
         }
 
         // Replenish queue first
-        self.replenish_queue(contest, contest_settings, user_callsign, cty);
+        self.replenish_queue(contest, contest_settings, user_callsign, cty, now, rng);
 
         // Clean up worked/given-up callers
         self.queue
@@ -403,7 +780,7 @@ impl CallerManager {
 
         // Find a waiting caller to be the tail-ender
         for caller in &mut self.queue {
-            if caller.state == CallerState::Waiting && caller.is_ready_to_call() {
+            if caller.state == CallerState::Waiting && caller.is_ready_to_call(now) {
                 caller.mark_calling();
                 caller.record_attempt();
                 self.active_ids.push(caller.params.id);
@@ -415,6 +792,219 @@ impl CallerManager {
     }
 }
 
+/// QSOs a station plausibly made over a stretch of time
+fn serial_growth(elapsed: Duration, rng: &mut dyn RngCore) -> u32 {
+    let rate = rng.gen_range(SERIAL_RATE_MIN..=SERIAL_RATE_MAX);
+    (elapsed.as_secs_f32() / 60.0 * rate).round() as u32
+}
+
+/// Move the serial number in an exchange on by `qsos`
+fn advance_serial(
+    contest: &dyn Contest,
+    exchange: &mut Exchange,
+    serial_index: Option<usize>,
+    qsos: u32,
+) {
+    let Some(field) = serial_index.and_then(|i| exchange.fields.get_mut(i)) else {
+        return;
+    };
+    if let Ok(serial) = field.parse::<u32>() {
+        *field = contest.format_caller_serial(serial + qsos);
+    }
+}
+
+/// Whether `callsign` fits a partial query like "W1?" or "?ABC". The known
+/// pieces between '?' wildcards must appear in the call in order; a plain
+/// fragment without '?' matches anywhere in the call.
+pub fn matches_partial_call(fragment: &str, callsign: &str) -> bool {
+    let mut rest = callsign;
+    let mut matched_any = false;
+    for piece in fragment.split('?').filter(|p| !p.is_empty()) {
+        match rest.find(piece) {
+            Some(idx) => {
+                rest = &rest[idx + piece.len()..];
+                matched_any = true;
+            }
+            None => return false,
+        }
+    }
+    matched_any
+}
+
+/// Sign-off words callers add around their exchange. None of them is part
+/// of the exchange, so none of them should be logged.
+pub const COURTESY_WORDS: [&str; 4] = ["TU", "EE", "73", "GL"];
+
+/// The exchange with a courtesy word added: "TU" may come first, as in
+/// "TU 5NN 05", the others always follow the exchange
+pub fn with_courtesy_word(exchange: &str, rng: &mut dyn RngCore) -> String {
+    let word = COURTESY_WORDS[rng.gen_range(0..COURTESY_WORDS.len())];
+    if word == "TU" && rng.gen::<bool>() {
+        format!("{} {}", word, exchange)
+    } else {
+        format!("{} {}", exchange, word)
+    }
+}
+
+/// The exchange with the extra calls some callers send ahead of it: our
+/// call ("W1AW 5NN 05"), "DE" and their own call ("W1AW DE K3LR 5NN 05"),
+/// or their call signed twice ("K3LR K3LR 5NN 05"). Each is rolled on its
+/// own, so they combine.
+pub fn framed_exchange(
+    format: &CallerFormat,
+    our_call: &str,
+    their_call: &str,
+    exchange: &str,
+    rng: &mut dyn RngCore,
+) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if !our_call.is_empty() && rng.gen::<f32>() < format.our_call {
+        parts.push(our_call);
+    }
+    let de = rng.gen::<f32>() < format.de;
+    let twice = rng.gen::<f32>() < format.sign_twice;
+    if de {
+        parts.extend(["DE", their_call]);
+    }
+    if twice {
+        parts.extend(if de {
+            vec![their_call]
+        } else {
+            vec![their_call, their_call]
+        });
+    }
+    parts.push(exchange);
+    parts.join(" ")
+}
+
+/// Our callsign as a careless caller might send it, with one character wrong
+pub fn wrong_callsign(callsign: &str, rng: &mut dyn RngCore) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    let mut chars: Vec<char> = callsign.chars().collect();
+    let positions: Vec<usize> = chars
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_ascii_alphanumeric())
+        .map(|(i, _)| i)
+        .collect();
+    if positions.is_empty() {
+        return callsign.to_string();
+    }
+    let pos = positions[rng.gen_range(0..positions.len())];
+
+    // Keep letters as letters and digits as digits so the bust sounds plausible
+    let original = chars[pos].to_ascii_uppercase();
+    let pool: Vec<char> = CHARS
+        .iter()
+        .map(|&b| b as char)
+        .filter(|c| *c != original && c.is_ascii_digit() == original.is_ascii_digit())
+        .collect();
+    chars[pos] = pool[rng.gen_range(0..pool.len())];
+    chars.into_iter().collect()
+}
+
+/// Push apart callers who would start within `min_gap_ms` of each other;
+/// real pileups are copyable because calls start one after another
+fn stagger_start_times(callers: &mut [StationParams], min_gap_ms: u32) {
+    let mut order: Vec<usize> = (0..callers.len()).collect();
+    order.sort_by_key(|&i| callers[i].reaction_delay_ms);
+    let mut previous: Option<u32> = None;
+    for i in order {
+        if let Some(previous) = previous {
+            let earliest = previous + min_gap_ms;
+            if callers[i].reaction_delay_ms < earliest {
+                callers[i].reaction_delay_ms = earliest;
+            }
+        }
+        previous = Some(callers[i].reaction_delay_ms);
+    }
+}
+
+/// What a caller who gives up sends on the way out, if anything
+pub fn give_up_message(behavior: GiveUpBehavior, callsign: &str) -> Option<String> {
+    match behavior {
+        GiveUpBehavior::Silent => None,
+        GiveUpBehavior::FinalCall => Some(callsign.to_string()),
+        GiveUpBehavior::Dits => Some("E E".to_string()),
+    }
+}
+
+/// A caller's answer to our CQ: their call, sometimes sent twice
+pub fn calling_message(
+    callsign: &str,
+    call_twice_probability: f32,
+    rng: &mut dyn RngCore,
+) -> String {
+    if rng.gen::<f32>() < call_twice_probability {
+        format!("{} {}", callsign, callsign)
+    } else {
+        callsign.to_string()
+    }
+}
+
+/// An offset within ± `half_width` for a caller in a wide pileup, clear of
+/// the callers already `taken` where there's room
+fn wide_offset(taken: &[f32], half_width: f32, rng: &mut dyn RngCore) -> f32 {
+    let half_width = half_width.max(1.0);
+    let clearance = |offset: f32| {
+        taken
+            .iter()
+            .map(|other| (offset - other).abs())
+            .fold(f32::INFINITY, f32::min)
+    };
+    let mut best = (0.0, f32::NEG_INFINITY);
+    for _ in 0..WIDE_PLACEMENT_TRIES {
+        let offset = rng.gen_range(-half_width..=half_width);
+        let room = clearance(offset);
+        if room >= WIDE_MIN_SEPARATION_HZ {
+            return offset;
+        }
+        if room > best.1 {
+            best = (offset, room);
+        }
+    }
+    best.0
+}
+
+/// With the given probability, put the first two callers on top of each
+/// other: nearly the same pitch, strength and start time, so neither call
+/// copies cleanly. Returns the ids of the doubling pair.
+pub fn make_double(
+    callers: &mut [StationParams],
+    probability: f32,
+    rng: &mut dyn RngCore,
+) -> Option<[StationId; 2]> {
+    if callers.len() < 2 || rng.gen::<f32>() >= probability {
+        return None;
+    }
+    let (first, rest) = callers.split_first_mut()?;
+    let second = &mut rest[0];
+    second.frequency_offset_hz =
+        first.frequency_offset_hz + rng.gen_range(-DOUBLE_MAX_SPLIT_HZ..=DOUBLE_MAX_SPLIT_HZ);
+    second.reaction_delay_ms = first.reaction_delay_ms + rng.gen_range(0..=DOUBLE_MAX_START_GAP_MS);
+    second.amplitude = first.amplitude * rng.gen_range(0.8..=1.2);
+    Some([first.id, second.id])
+}
+
+/// Delay from now until a caller starts sending, given their usual reaction
+/// time and how long until our CQ ends. Most callers wait for the CQ to
+/// finish; some tailgate the end of it and some are seconds late.
+fn response_delay_ms(
+    settings: &ReactionTimeSettings,
+    base_ms: u32,
+    cq_remaining_ms: u32,
+    rng: &mut dyn RngCore,
+) -> u32 {
+    let roll = rng.gen::<f32>();
+    if roll < settings.tailgate_probability {
+        cq_remaining_ms.saturating_sub(rng.gen_range(0..=settings.tailgate_max_ms))
+    } else if roll < settings.tailgate_probability + settings.late_probability {
+        cq_remaining_ms + base_ms + rng.gen_range(1000..=settings.late_max_ms.max(1000))
+    } else {
+        cq_remaining_ms + base_ms
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -425,8 +1015,10 @@ mod tests {
         let progress = QsoProgress {
             sent_their_call: false,
             sent_our_exchange: false,
+            sent_our_call: false,
             received_their_call: false,
             received_their_exchange: false,
+            user_tx_aborted: false,
         };
         assert_eq!(
             CallerResponse::from_progress(&progress),
@@ -437,8 +1029,10 @@ mod tests {
         let progress = QsoProgress {
             sent_their_call: true,
             sent_our_exchange: false,
+            sent_our_call: false,
             received_their_call: false,
             received_their_exchange: false,
+            user_tx_aborted: false,
         };
         assert_eq!(
             CallerResponse::from_progress(&progress),
@@ -449,8 +1043,10 @@ mod tests {
         let progress = QsoProgress {
             sent_their_call: true,
             sent_our_exchange: true,
+            sent_our_call: false,
             received_their_call: false,
             received_their_exchange: false,
+            user_tx_aborted: false,
         };
         assert_eq!(
             CallerResponse::from_progress(&progress),
@@ -461,8 +1057,10 @@ mod tests {
         let progress = QsoProgress {
             sent_their_call: false,
             sent_our_exchange: true,
+            sent_our_call: false,
             received_their_call: false,
             received_their_exchange: false,
+            user_tx_aborted: false,
         };
         assert_eq!(
             CallerResponse::from_progress(&progress),
@@ -475,8 +1073,10 @@ mod tests {
         let progress = QsoProgress {
             sent_their_call: true,
             sent_our_exchange: false,
+            sent_our_call: false,
             received_their_call: false,
             received_their_exchange: false,
+            user_tx_aborted: false,
         };
         let mut context = QsoContext::new();
         context.awaiting_user_exchange = true;
@@ -486,4 +1086,390 @@ mod tests {
             CallerResponse::Wait
         );
     }
+
+    #[test]
+    fn test_caller_response_after_aborted_exchange() {
+        // Their call went out, our exchange was cut off mid-message
+        let progress = QsoProgress {
+            sent_their_call: true,
+            sent_our_exchange: false,
+            sent_our_call: false,
+            received_their_call: false,
+            received_their_exchange: false,
+            user_tx_aborted: true,
+        };
+        let mut context = QsoContext::new();
+        context.awaiting_user_exchange = true;
+
+        assert_eq!(
+            CallerResponse::from_progress_and_context(&progress, &context),
+            CallerResponse::RequestAgn
+        );
+    }
+
+    #[test]
+    fn test_response_delay_distribution() {
+        let mut settings = ReactionTimeSettings {
+            tailgate_probability: 0.0,
+            late_probability: 0.0,
+            ..ReactionTimeSettings::default()
+        };
+        // On-time callers start their usual reaction time after the CQ
+        assert_eq!(
+            response_delay_ms(&settings, 300, 500, &mut rand::thread_rng()),
+            800
+        );
+
+        // Tailgaters start before the CQ has finished
+        settings.tailgate_probability = 1.0;
+        for _ in 0..20 {
+            assert!(response_delay_ms(&settings, 300, 1000, &mut rand::thread_rng()) <= 1000);
+        }
+
+        // Late callers are at least a second behind
+        settings.tailgate_probability = 0.0;
+        settings.late_probability = 1.0;
+        for _ in 0..20 {
+            let delay = response_delay_ms(&settings, 300, 0, &mut rand::thread_rng());
+            assert!((1300..=3300).contains(&delay));
+        }
+    }
+
+    /// Always the same call, with a different exchange every time
+    struct RepeatingSource(u32);
+
+    impl CallsignSource for RepeatingSource {
+        fn random(
+            &mut self,
+            _contest: &dyn Contest,
+            _serial: u32,
+            _settings: &toml::Value,
+            _rng: &mut dyn RngCore,
+        ) -> Option<(String, Exchange)> {
+            self.0 += 1;
+            Some((
+                "K3LR".to_string(),
+                Exchange::new(vec!["TIM".to_string(), self.0.to_string()]),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_repeat_caller_sends_same_exchange() {
+        let contest = crate::contest::create_contest("cwt").unwrap();
+        let settings = toml::Value::Table(Default::default());
+        let mut manager = CallerManager::new(
+            Box::new(RepeatingSource(0)),
+            SimulationSettings::default(),
+            Instant::now(),
+        );
+
+        let first = manager
+            .create_caller(
+                contest.as_ref(),
+                &settings,
+                None,
+                None,
+                Instant::now(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        let second = manager
+            .create_caller(
+                contest.as_ref(),
+                &settings,
+                None,
+                None,
+                Instant::now(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        assert_eq!(first.params.exchange.fields, second.params.exchange.fields);
+
+        manager.reset_session(Instant::now());
+        let third = manager
+            .create_caller(
+                contest.as_ref(),
+                &settings,
+                None,
+                None,
+                Instant::now(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        assert_ne!(first.params.exchange.fields, third.params.exchange.fields);
+    }
+
+    /// Always K3LR with a CQ WPX exchange carrying serial 7
+    struct SerialSource;
+
+    impl CallsignSource for SerialSource {
+        fn random(
+            &mut self,
+            _contest: &dyn Contest,
+            _serial: u32,
+            _settings: &toml::Value,
+            _rng: &mut dyn RngCore,
+        ) -> Option<(String, Exchange)> {
+            Some((
+                "K3LR".to_string(),
+                Exchange::new(vec!["5NN".to_string(), "007".to_string()]),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_repeat_caller_serial_counts_up() {
+        let contest = crate::contest::create_contest("cqwpx").unwrap();
+        let settings = toml::Value::Table(Default::default());
+        let mut manager = CallerManager::new(
+            Box::new(SerialSource),
+            SimulationSettings::default(),
+            Instant::now(),
+        );
+
+        let mut serials = Vec::new();
+        for _ in 0..3 {
+            let caller = manager
+                .create_caller(
+                    contest.as_ref(),
+                    &settings,
+                    None,
+                    None,
+                    Instant::now(),
+                    &mut rand::thread_rng(),
+                )
+                .unwrap();
+            serials.push(caller.params.exchange.fields[1].clone());
+        }
+        assert_eq!(serials, vec!["007", "008", "009"]);
+    }
+
+    #[test]
+    fn test_fixed_amplitude_and_speed() {
+        // As the tutorial sets them: one strong, steady caller
+        let contest = crate::contest::create_contest("cwt").unwrap();
+        let settings = toml::Value::Table(Default::default());
+        let simulation = SimulationSettings {
+            amplitude_min: 1.0,
+            amplitude_max: 1.0,
+            wpm_min: 18,
+            wpm_max: 18,
+            ..SimulationSettings::default()
+        };
+        let mut manager =
+            CallerManager::new(Box::new(RepeatingSource(0)), simulation, Instant::now());
+        let caller = manager
+            .create_caller(
+                contest.as_ref(),
+                &settings,
+                None,
+                None,
+                Instant::now(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        assert_eq!(caller.params.amplitude, 1.0);
+        assert_eq!(caller.params.wpm, 18);
+    }
+
+    #[test]
+    fn test_callers_who_give_up_are_reported_once() {
+        let contest = crate::contest::create_contest("cwt").unwrap();
+        let settings = toml::Value::Table(Default::default());
+        let mut manager = CallerManager::new(
+            Box::new(RepeatingSource(0)),
+            SimulationSettings::default(),
+            Instant::now(),
+        );
+
+        let mut caller = manager
+            .create_caller(
+                contest.as_ref(),
+                &settings,
+                None,
+                None,
+                Instant::now(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+        caller.mark_calling();
+        caller.attempts = caller.patience;
+        manager.queue.push(caller);
+
+        assert_eq!(manager.pileup_depth(), 1);
+        manager.on_cq_restart(Instant::now(), &mut rand::thread_rng());
+        assert_eq!(manager.pileup_depth(), 0);
+        let given_up = manager.take_given_up();
+        assert_eq!(given_up.len(), 1);
+        assert_eq!(given_up[0].callsign, "K3LR");
+        assert!(manager.take_given_up().is_empty());
+
+        assert_eq!(give_up_message(GiveUpBehavior::Silent, "K3LR"), None);
+        assert_eq!(
+            give_up_message(GiveUpBehavior::FinalCall, "K3LR").as_deref(),
+            Some("K3LR")
+        );
+        assert_eq!(
+            give_up_message(GiveUpBehavior::Dits, "K3LR").as_deref(),
+            Some("E E")
+        );
+    }
+
+    #[test]
+    fn test_serial_growth_follows_session_time() {
+        assert_eq!(serial_growth(Duration::ZERO, &mut rand::thread_rng()), 0);
+        for _ in 0..20 {
+            let hour = serial_growth(Duration::from_secs(3600), &mut rand::thread_rng());
+            assert!((30..=120).contains(&hour));
+        }
+    }
+
+    #[test]
+    fn test_matches_partial_call() {
+        assert!(matches_partial_call("W1?", "W1ABC"));
+        assert!(matches_partial_call("W1?", "KW1X"));
+        assert!(matches_partial_call("?ABC", "K1ABC"));
+        assert!(matches_partial_call("K?BC", "K1ABC"));
+        assert!(matches_partial_call("1AB", "K1ABC"));
+        assert!(!matches_partial_call("W1?", "K1ABC"));
+        assert!(!matches_partial_call("BC?K1", "K1ABC"));
+        assert!(!matches_partial_call("?", "K1ABC"));
+    }
+
+    #[test]
+    fn test_wrong_callsign_changes_one_character() {
+        for _ in 0..50 {
+            let busted = wrong_callsign("K1ABC", &mut rand::thread_rng());
+            assert_eq!(busted.len(), 5);
+            let diffs: Vec<(char, char)> = busted
+                .chars()
+                .zip("K1ABC".chars())
+                .filter(|(a, b)| a != b)
+                .collect();
+            assert_eq!(diffs.len(), 1);
+            let (new, old) = diffs[0];
+            assert_eq!(new.is_ascii_digit(), old.is_ascii_digit());
+        }
+        assert_eq!(wrong_callsign("", &mut rand::thread_rng()), "");
+    }
+
+    #[test]
+    fn test_courtesy_word_surrounds_exchange() {
+        for _ in 0..50 {
+            let sent = with_courtesy_word("5NN 05", &mut rand::thread_rng());
+            let extra = sent.replacen("5NN 05", "", 1);
+            let word = extra.trim();
+            assert!(COURTESY_WORDS.contains(&word), "unexpected {:?}", sent);
+            if sent.starts_with(word) {
+                assert_eq!(word, "TU");
+            }
+        }
+    }
+
+    #[test]
+    fn test_framed_exchange_adds_calls() {
+        let format = |our_call, de, sign_twice| CallerFormat {
+            our_call,
+            de,
+            sign_twice,
+        };
+        let framed = |f: CallerFormat| {
+            framed_exchange(&f, "W1AW", "K3LR", "5NN 05", &mut rand::thread_rng())
+        };
+        assert_eq!(framed(format(0.0, 0.0, 0.0)), "5NN 05");
+        assert_eq!(framed(format(1.0, 0.0, 0.0)), "W1AW 5NN 05");
+        assert_eq!(framed(format(1.0, 1.0, 0.0)), "W1AW DE K3LR 5NN 05");
+        assert_eq!(framed(format(0.0, 0.0, 1.0)), "K3LR K3LR 5NN 05");
+        assert_eq!(framed(format(1.0, 1.0, 1.0)), "W1AW DE K3LR K3LR 5NN 05");
+        // Without our call there's nothing to send for it
+        assert_eq!(
+            framed_exchange(
+                &format(1.0, 0.0, 0.0),
+                "",
+                "K3LR",
+                "5NN 05",
+                &mut rand::thread_rng()
+            ),
+            "5NN 05"
+        );
+    }
+
+    #[test]
+    fn test_pileup_starts_are_staggered() {
+        let caller = |id: u32, delay: u32| StationParams {
+            id: StationId(id),
+            callsign: format!("K{}ABC", id),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 0.0,
+            wpm: 30,
+            amplitude: 0.5,
+            reaction_delay_ms: delay,
+        };
+
+        let mut callers = vec![
+            caller(1, 320),
+            caller(2, 300),
+            caller(3, 330),
+            caller(4, 700),
+        ];
+        stagger_start_times(&mut callers, 50);
+        let delays: Vec<u32> = callers.iter().map(|c| c.reaction_delay_ms).collect();
+        assert_eq!(delays, vec![350, 300, 400, 700]);
+
+        assert_eq!(
+            calling_message("K1ABC", 0.0, &mut rand::thread_rng()),
+            "K1ABC"
+        );
+        assert_eq!(
+            calling_message("K1ABC", 1.0, &mut rand::thread_rng()),
+            "K1ABC K1ABC"
+        );
+    }
+
+    #[test]
+    fn test_make_double_stacks_two_callers() {
+        let caller = |id: u32, offset: f32, delay: u32| StationParams {
+            id: StationId(id),
+            callsign: format!("K{}ABC", id),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: offset,
+            wpm: 30,
+            amplitude: 0.5,
+            reaction_delay_ms: delay,
+        };
+
+        let mut callers = vec![caller(1, -100.0, 300), caller(2, 200.0, 900)];
+        assert_eq!(
+            make_double(&mut callers, 0.0, &mut rand::thread_rng()),
+            None
+        );
+        assert_eq!(callers[1].frequency_offset_hz, 200.0);
+
+        let pair = make_double(&mut callers, 1.0, &mut rand::thread_rng());
+        assert_eq!(pair, Some([StationId(1), StationId(2)]));
+        assert!((callers[1].frequency_offset_hz + 100.0).abs() <= DOUBLE_MAX_SPLIT_HZ);
+        assert!(callers[1].reaction_delay_ms >= 300);
+        assert!(callers[1].reaction_delay_ms <= 300 + DOUBLE_MAX_START_GAP_MS);
+
+        // A lone caller can't double
+        let mut single = vec![caller(3, 0.0, 0)];
+        assert_eq!(make_double(&mut single, 1.0, &mut rand::thread_rng()), None);
+    }
+
+    #[test]
+    fn test_wide_pileup_callers_spread_apart() {
+        let mut taken = Vec::new();
+        for _ in 0..4 {
+            let offset = wide_offset(&taken, 1000.0, &mut rand::thread_rng());
+            assert!(offset.abs() <= 1000.0);
+            taken.push(offset);
+        }
+        // Four callers leave most of 2 kHz free, so each finds a clear spot
+        for (i, a) in taken.iter().enumerate() {
+            for b in &taken[i + 1..] {
+                assert!((a - b).abs() >= WIDE_MIN_SEPARATION_HZ, "{:?}", taken);
+            }
+        }
+    }
 }
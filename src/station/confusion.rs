@@ -0,0 +1,65 @@
+// Plausible CW busts: a call miscopied the way ears really miscopy it,
+// one dit or dah off (S/H, N/A, U/V), rather than with a random character
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use super::matching::sounds_alike;
+use super::wrong_callsign;
+
+const CALLSIGN_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Letters and digits that sound like `ch`
+pub fn confusions(ch: char) -> Vec<char> {
+    if !ch.is_ascii_alphanumeric() {
+        return Vec::new();
+    }
+    CALLSIGN_CHARS
+        .chars()
+        .filter(|other| sounds_alike(ch, *other))
+        .collect()
+}
+
+/// `callsign` with one character swapped for one that sounds like it.
+/// Falls back to an ordinary one-character bust if nothing in the call has
+/// a sound-alike.
+pub fn confused_callsign(callsign: &str, rng: &mut dyn RngCore) -> String {
+    let mut chars: Vec<char> = callsign.chars().collect();
+    let candidates: Vec<(usize, Vec<char>)> = chars
+        .iter()
+        .enumerate()
+        .map(|(i, ch)| (i, confusions(ch.to_ascii_uppercase())))
+        .filter(|(_, alike)| !alike.is_empty())
+        .collect();
+    let Some((pos, alike)) = candidates.choose(rng) else {
+        return wrong_callsign(callsign, rng);
+    };
+    chars[*pos] = *alike.choose(rng).unwrap();
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confused_calls_are_one_sound_alike_character_off() {
+        assert!(confusions('S').contains(&'H'));
+        assert!(confusions('N').contains(&'A'));
+        assert!(!confusions('S').contains(&'S'));
+        assert!(confusions('/').is_empty());
+
+        for _ in 0..50 {
+            let busted = confused_callsign("DL1SAM/P", &mut rand::thread_rng());
+            let diffs: Vec<(char, char)> = busted
+                .chars()
+                .zip("DL1SAM/P".chars())
+                .filter(|(a, b)| a != b)
+                .collect();
+            assert_eq!(busted.len(), 8);
+            assert_eq!(diffs.len(), 1);
+            assert!(sounds_alike(diffs[0].0, diffs[0].1));
+        }
+        assert_eq!(confused_callsign("", &mut rand::thread_rng()), "");
+    }
+}
@@ -0,0 +1,79 @@
+// Caller density that follows the run: fast clean QSOs draw more callers
+// to the frequency a little at a time, slow or busted ones send them away
+
+use std::time::Duration;
+
+use crate::config::DynamicDensitySettings;
+
+/// The station probability as the run has earned it
+#[derive(Clone, Debug)]
+pub struct CallerDensity {
+    probability: f32,
+}
+
+impl CallerDensity {
+    /// Start a run at the configured station probability
+    pub fn new(start: f32) -> Self {
+        Self { probability: start }
+    }
+
+    pub fn probability(&self) -> f32 {
+        self.probability
+    }
+
+    /// Move the density for a QSO that took `qso_time` from the first call
+    /// to logging, and return where it ends up
+    pub fn on_qso(
+        &mut self,
+        qso_time: Duration,
+        clean: bool,
+        settings: &DynamicDensitySettings,
+    ) -> f32 {
+        let secs = qso_time.as_secs_f32();
+        if !clean || secs > settings.slow_qso_secs {
+            self.probability -= settings.thin_step;
+        } else if secs <= settings.fast_qso_secs {
+            self.probability += settings.build_step;
+        }
+        let low = settings.min_probability.clamp(0.0, 1.0);
+        let high = settings.max_probability.clamp(low, 1.0);
+        self.probability = self.probability.clamp(low, high);
+        self.probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fast_clean_qsos_build_the_pileup_and_busts_thin_it() {
+        let settings = DynamicDensitySettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let fast = Duration::from_secs(10);
+        let mut density = CallerDensity::new(0.5);
+
+        // Builds a step at a time, up to the ceiling
+        assert!((density.on_qso(fast, true, &settings) - 0.53).abs() < 1e-4);
+        for _ in 0..30 {
+            density.on_qso(fast, true, &settings);
+        }
+        assert_eq!(density.probability(), 1.0);
+
+        // In between fast and slow leaves it alone
+        assert_eq!(
+            density.on_qso(Duration::from_secs(25), true, &settings),
+            1.0
+        );
+
+        // A bust and a slow QSO each thin it, down to the floor
+        assert!((density.on_qso(fast, false, &settings) - 0.92).abs() < 1e-4);
+        assert!((density.on_qso(Duration::from_secs(60), true, &settings) - 0.84).abs() < 1e-4);
+        for _ in 0..20 {
+            density.on_qso(fast, false, &settings);
+        }
+        assert_eq!(density.probability(), 0.2);
+    }
+}
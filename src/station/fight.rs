@@ -0,0 +1,252 @@
+// A loud station taking over our run frequency: when one turns up after
+// we've held it a while, how often they CQ over us, and when they give up
+// if we hold on
+
+use rand::{Rng, RngCore};
+use std::time::{Duration, Instant};
+
+use crate::config::FrequencyFightSettings;
+use crate::messages::StationParams;
+
+/// How often the chance of someone taking the frequency is rolled
+const ROLL_INTERVAL: Duration = Duration::from_secs(60);
+/// How long a squatter keeps at it before moving on, if we hold on
+const SQUAT_SECS: std::ops::Range<u64> = 60..180;
+/// Pause between the end of one of their CQs and the next
+const CQ_GAP_MS: std::ops::Range<u64> = 1500..4000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FightEvent {
+    /// Someone has started calling CQ on our frequency
+    Arrives,
+    /// They call CQ again
+    Cq,
+    /// They gave up and moved off, after this long on our frequency
+    Leaves { fought: Duration },
+}
+
+/// The station CQing on our frequency
+#[derive(Clone, Debug)]
+pub struct Squatter {
+    /// What they send each time: their CQ, loud and near zero beat
+    pub params: StationParams,
+    pub since: Instant,
+    cq_duration: Duration,
+    next_cq_at: Instant,
+    leaves_at: Instant,
+}
+
+/// How long we've held the frequency, and who is fighting us for it
+#[derive(Default)]
+pub struct FrequencyFight {
+    run_since: Option<Instant>,
+    last_roll: Option<Instant>,
+    squatter: Option<Squatter>,
+}
+
+impl FrequencyFight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note a CQ from us: the first one starts our run on this frequency
+    pub fn on_cq(&mut self, now: Instant) {
+        self.run_since.get_or_insert(now);
+    }
+
+    pub fn squatter(&self) -> Option<&Squatter> {
+        self.squatter.as_ref()
+    }
+
+    /// We've left the frequency (a QSY, a pause, the end of the session):
+    /// the run starts over, and whoever was on it stays behind. Returns how
+    /// long they'd been there.
+    pub fn leave_frequency(&mut self, now: Instant) -> Option<Duration> {
+        self.run_since = None;
+        self.last_roll = None;
+        self.squatter
+            .take()
+            .map(|squatter| now.saturating_duration_since(squatter.since))
+    }
+
+    /// Put the station that just turned up on the frequency; `cq_duration`
+    /// is how long each of their CQs takes
+    pub fn squat(
+        &mut self,
+        now: Instant,
+        params: StationParams,
+        cq_duration: Duration,
+        rng: &mut dyn RngCore,
+    ) {
+        self.squatter = Some(Squatter {
+            params,
+            since: now,
+            cq_duration,
+            next_cq_at: now,
+            leaves_at: now + Duration::from_secs(rng.gen_range(SQUAT_SECS)),
+        });
+    }
+
+    /// What happens at `now`, if anything
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        settings: &FrequencyFightSettings,
+        rng: &mut dyn RngCore,
+    ) -> Option<FightEvent> {
+        if let Some(squatter) = self.squatter.as_mut() {
+            if now >= squatter.leaves_at {
+                let fought = now.saturating_duration_since(squatter.since);
+                self.squatter = None;
+                // Whoever moves off, the frequency is ours again for a while
+                self.run_since = Some(now);
+                self.last_roll = None;
+                return Some(FightEvent::Leaves { fought });
+            }
+            if now >= squatter.next_cq_at {
+                let gap = Duration::from_millis(rng.gen_range(CQ_GAP_MS));
+                squatter.next_cq_at = now + squatter.cq_duration + gap;
+                return Some(FightEvent::Cq);
+            }
+            return None;
+        }
+
+        let run_since = self.run_since?;
+        if !settings.enabled {
+            return None;
+        }
+        let held = now.saturating_duration_since(run_since);
+        if held < Duration::from_secs_f32(settings.min_run_minutes.max(0.0) * 60.0) {
+            return None;
+        }
+        let last_roll = *self.last_roll.get_or_insert(now);
+        if now.saturating_duration_since(last_roll) < ROLL_INTERVAL {
+            return None;
+        }
+        self.last_roll = Some(now);
+        (rng.gen::<f32>() < settings.chance_per_minute).then_some(FightEvent::Arrives)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contest::Exchange;
+    use crate::messages::StationId;
+
+    fn squatter() -> StationParams {
+        StationParams {
+            id: StationId(1),
+            callsign: "CQ TEST K1ABC K1ABC".to_string(),
+            exchange: Exchange::new(Vec::new()),
+            frequency_offset_hz: 0.0,
+            wpm: 30,
+            amplitude: 1.0,
+            reaction_delay_ms: 0,
+        }
+    }
+
+    #[test]
+    fn someone_turns_up_only_after_a_long_enough_run() {
+        let settings = FrequencyFightSettings {
+            enabled: true,
+            min_run_minutes: 5.0,
+            chance_per_minute: 1.0,
+            ..Default::default()
+        };
+        let mut fight = FrequencyFight::new();
+        let start = Instant::now();
+        // Not running yet
+        assert_eq!(
+            fight.poll(
+                start + Duration::from_secs(600),
+                &settings,
+                &mut rand::thread_rng()
+            ),
+            None
+        );
+
+        fight.on_cq(start);
+        let minute = Duration::from_secs(60);
+        assert_eq!(
+            fight.poll(start + 4 * minute, &settings, &mut rand::thread_rng()),
+            None
+        );
+        assert_eq!(
+            fight.poll(start + 5 * minute, &settings, &mut rand::thread_rng()),
+            None
+        );
+        assert_eq!(
+            fight.poll(start + 6 * minute, &settings, &mut rand::thread_rng()),
+            Some(FightEvent::Arrives)
+        );
+
+        let disabled = FrequencyFightSettings {
+            enabled: false,
+            ..settings
+        };
+        assert_eq!(
+            fight.poll(start + 8 * minute, &disabled, &mut rand::thread_rng()),
+            None
+        );
+    }
+
+    #[test]
+    fn a_squatter_cqs_until_they_give_up_or_we_move() {
+        let settings = FrequencyFightSettings::default();
+        let mut fight = FrequencyFight::new();
+        let start = Instant::now();
+        fight.on_cq(start);
+        fight.squat(
+            start,
+            squatter(),
+            Duration::from_secs(3),
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            fight.poll(start, &settings, &mut rand::thread_rng()),
+            Some(FightEvent::Cq)
+        );
+        // Not again until their CQ and a pause are over
+        assert_eq!(
+            fight.poll(
+                start + Duration::from_secs(4),
+                &settings,
+                &mut rand::thread_rng()
+            ),
+            None
+        );
+        assert_eq!(
+            fight.poll(
+                start + Duration::from_secs(8),
+                &settings,
+                &mut rand::thread_rng()
+            ),
+            Some(FightEvent::Cq)
+        );
+
+        // Held on long enough: they go, and the frequency is ours again
+        let later = start + Duration::from_secs(SQUAT_SECS.end);
+        assert_eq!(
+            fight.poll(later, &settings, &mut rand::thread_rng()),
+            Some(FightEvent::Leaves {
+                fought: Duration::from_secs(SQUAT_SECS.end)
+            })
+        );
+        assert!(fight.squatter().is_none());
+
+        // Moving off leaves them behind
+        fight.squat(
+            later,
+            squatter(),
+            Duration::from_secs(3),
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            fight.leave_frequency(later + Duration::from_secs(20)),
+            Some(Duration::from_secs(20))
+        );
+        assert!(fight.squatter().is_none());
+        assert_eq!(fight.leave_frequency(later), None);
+    }
+}
@@ -0,0 +1,61 @@
+// Training focus on the characters the user miscopies most: callers whose
+// calls contain them are favored when the pileup is filled
+
+use std::collections::HashMap;
+
+/// Least chance a call without any weak characters is kept, so the pileup
+/// never shrinks to the few calls that have them
+const MIN_KEEP_PROBABILITY: f32 = 0.1;
+
+/// How weak each character is, relative to the worst one (0.0 - 1.0)
+#[derive(Clone, Debug, Default)]
+pub struct CharFocus {
+    weakness: HashMap<char, f32>,
+}
+
+impl CharFocus {
+    /// From the session's character error rates: (char, error rate %, samples)
+    pub fn from_error_rates(rates: &[(char, f32, usize)]) -> Self {
+        let worst = rates.iter().map(|(_, rate, _)| *rate).fold(0.0, f32::max);
+        if worst <= 0.0 {
+            return Self::default();
+        }
+        let weakness = rates
+            .iter()
+            .filter(|(_, rate, _)| *rate > 0.0)
+            .map(|(ch, rate, _)| (*ch, rate / worst))
+            .collect();
+        Self { weakness }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weakness.is_empty()
+    }
+
+    /// Chance a callsign drawn for the pileup is kept. Calls with the
+    /// worst character always are; the rest less often as `strength` goes
+    /// from 0.0 (no focus) to 1.0.
+    pub fn keep_probability(&self, callsign: &str, strength: f32) -> f32 {
+        let score = callsign
+            .chars()
+            .filter_map(|ch| self.weakness.get(&ch))
+            .fold(0.0, |max: f32, w| max.max(*w));
+        (1.0 - strength.clamp(0.0, 1.0) * (1.0 - score)).max(MIN_KEEP_PROBABILITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_with_weak_characters_are_kept() {
+        let focus = CharFocus::from_error_rates(&[('Q', 40.0, 5), ('Y', 20.0, 5), ('K', 0.0, 9)]);
+        assert_eq!(focus.keep_probability("K1QRS", 1.0), 1.0);
+        assert!((focus.keep_probability("W1XYZ", 1.0) - 0.5).abs() < 1e-6);
+        assert_eq!(focus.keep_probability("K1ABC", 1.0), MIN_KEEP_PROBABILITY);
+        assert_eq!(focus.keep_probability("K1ABC", 0.0), 1.0);
+
+        assert!(CharFocus::from_error_rates(&[('K', 0.0, 9)]).is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+// Matching what the user typed against the calls in the pileup: an edit
+// distance where characters that sound alike in CW are cheap to swap
+
+use crate::audio::morse::{char_to_morse, MorseElement};
+use crate::cty::base_callsign;
+
+/// Cost of swapping two characters one dit or dah apart (S/H, N/A, U/V...)
+const SOUND_ALIKE_COST: f32 = 0.5;
+
+/// Cost of any other substitution, an insertion, a deletion, or swapping
+/// two neighbouring characters
+const EDIT_COST: f32 = 1.0;
+
+/// Just short of an exact match, so the right station with its portable
+/// indicator missed or wrong beats any other caller
+const PORTABLE_MISMATCH_SIMILARITY: f32 = 0.95;
+
+/// The dits and dahs of a callsign character
+fn tones(ch: char) -> Option<Vec<MorseElement>> {
+    if !ch.is_ascii_alphanumeric() && ch != '/' {
+        return None;
+    }
+    char_to_morse(ch).map(|code| code.into_iter().filter(|e| e.is_tone()).collect())
+}
+
+/// Whether two different characters are one dit or dah apart: one added
+/// or dropped (S/H, E/I, T/M, N/D), one heard the wrong way (U/S), or two
+/// neighbouring ones heard in the wrong order (N/A)
+pub fn sounds_alike(a: char, b: char) -> bool {
+    let (a, b) = (a.to_ascii_uppercase(), b.to_ascii_uppercase());
+    if a == b {
+        return false;
+    }
+    let (Some(a), Some(b)) = (tones(a), tones(b)) else {
+        return false;
+    };
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    match longer.len() - shorter.len() {
+        0 => {
+            let differ: Vec<usize> = (0..shorter.len())
+                .filter(|&i| shorter[i] != longer[i])
+                .collect();
+            match differ[..] {
+                [_] => true,
+                [i, j] => j == i + 1 && shorter[i] == longer[j] && shorter[j] == longer[i],
+                _ => false,
+            }
+        }
+        1 => (0..longer.len()).any(|skip| {
+            longer
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != skip)
+                .map(|(_, e)| e)
+                .eq(shorter.iter())
+        }),
+        _ => false,
+    }
+}
+
+fn substitution_cost(a: char, b: char) -> f32 {
+    if a == b {
+        0.0
+    } else if sounds_alike(a, b) {
+        SOUND_ALIKE_COST
+    } else {
+        EDIT_COST
+    }
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment) with CW-aware
+/// substitution costs
+pub fn edit_distance(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().map(|c| c.to_ascii_uppercase()).collect();
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_uppercase()).collect();
+
+    // d[i][j] = distance between the first i characters of a and first j of b
+    let mut d = vec![vec![0.0f32; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i as f32 * EDIT_COST;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j as f32 * EDIT_COST;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let mut cost = (d[i - 1][j] + EDIT_COST)
+                .min(d[i][j - 1] + EDIT_COST)
+                .min(d[i - 1][j - 1] + substitution_cost(a[i - 1], b[j - 1]));
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cost = cost.min(d[i - 2][j - 2] + EDIT_COST);
+            }
+            d[i][j] = cost;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// How close an entered call is to a caller's call, from 0.0 (nothing in
+/// common) to 1.0 (the same call)
+pub fn callsign_similarity(entered: &str, callsign: &str) -> f32 {
+    if entered.is_empty() || callsign.is_empty() {
+        return 0.0;
+    }
+    if entered.eq_ignore_ascii_case(callsign) {
+        return 1.0;
+    }
+    if base_callsign(entered).eq_ignore_ascii_case(base_callsign(callsign)) {
+        return PORTABLE_MISMATCH_SIMILARITY;
+    }
+    let longer = entered.chars().count().max(callsign.chars().count()) as f32;
+    (1.0 - edit_distance(entered, callsign) / longer).max(0.0)
+}
+
+/// The call in `callsigns` most like `entered`, if any reaches `threshold`
+pub fn best_match<'a, I>(entered: &str, callsigns: I, threshold: f32) -> Option<(usize, f32)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    callsigns
+        .into_iter()
+        .map(|call| callsign_similarity(entered, call))
+        .enumerate()
+        .filter(|(_, sim)| *sim >= threshold)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default Call Match Threshold
+    const THRESHOLD: f32 = 0.4;
+
+    fn best<'a>(entered: &str, calls: &[&'a str]) -> Option<&'a str> {
+        best_match(entered, calls.iter().copied(), THRESHOLD).map(|(i, _)| calls[i])
+    }
+
+    #[test]
+    fn characters_one_element_apart_sound_alike() {
+        for (a, b) in [
+            ('S', 'H'),
+            ('E', 'I'),
+            ('T', 'M'),
+            ('N', 'D'),
+            ('U', 'V'),
+            ('N', 'A'),
+            ('B', '6'),
+            ('J', '1'),
+            ('5', 'H'),
+            ('D', 'B'),
+            ('w', 'a'),
+        ] {
+            assert!(sounds_alike(a, b), "{} / {}", a, b);
+            assert!(sounds_alike(b, a), "{} / {}", b, a);
+        }
+        for (a, b) in [
+            ('S', 'S'),
+            ('E', 'M'),
+            ('K', 'S'),
+            ('S', 'O'),
+            ('1', '9'),
+            ('-', 'E'),
+        ] {
+            assert!(!sounds_alike(a, b), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn edit_distance_weights_cw_confusions() {
+        assert_eq!(edit_distance("K1ABC", "K1ABC"), 0.0);
+        assert_eq!(edit_distance("K1ABC", "k1abc"), 0.0);
+        assert_eq!(edit_distance("", "K1ABC"), 5.0);
+        // A sound-alike swap costs less than any other
+        assert_eq!(edit_distance("W1SBC", "W1HBC"), SOUND_ALIKE_COST);
+        assert_eq!(edit_distance("W1SBC", "W1KBC"), EDIT_COST);
+        // Swapped neighbours are one edit, not two
+        assert_eq!(edit_distance("K1ACB", "K1ABC"), EDIT_COST);
+        // Dropped and extra characters
+        assert_eq!(edit_distance("K1AB", "K1ABC"), EDIT_COST);
+        assert_eq!(edit_distance("DL1ABC", "L1ABC"), EDIT_COST);
+    }
+
+    #[test]
+    fn similarity_of_partial_and_busted_calls() {
+        assert_eq!(callsign_similarity("K1ABC", "K1ABC"), 1.0);
+        assert_eq!(callsign_similarity("", "K1ABC"), 0.0);
+        assert_eq!(callsign_similarity("K1ABC", ""), 0.0);
+        assert!((callsign_similarity("K1A", "K1ABC") - 0.6).abs() < 1e-6);
+        assert!((callsign_similarity("ABC", "K1ABC") - 0.6).abs() < 1e-6);
+        assert!(callsign_similarity("K1ABC", "W9XYZ") < THRESHOLD);
+        // Portable indicator missed or wrong
+        assert_eq!(
+            callsign_similarity("K1ABC", "K1ABC/P"),
+            PORTABLE_MISMATCH_SIMILARITY
+        );
+        assert_eq!(
+            callsign_similarity("K1ABC/4", "K1ABC/P"),
+            PORTABLE_MISMATCH_SIMILARITY
+        );
+        // Sound-alike busts stay closer than random ones
+        assert!(callsign_similarity("K1HBC", "K1SBC") > callsign_similarity("K1XBC", "K1SBC"));
+    }
+
+    #[test]
+    fn best_match_picks_the_caller_that_was_meant() {
+        // Dropped dit: S copied as I
+        assert_eq!(best("DL1IAM", &["DL1SAM", "DL1OAM"]), Some("DL1SAM"));
+        // Extra dit: S copied as H
+        assert_eq!(best("W1HBC", &["W1KBC", "W1SBC"]), Some("W1SBC"));
+        // Elements heard out of order: N copied as A
+        assert_eq!(best("JA1AAA", &["JA1NAA", "JA1OAA"]), Some("JA1NAA"));
+        // U / V
+        assert_eq!(best("UA9VV", &["UA9VU", "UA9VR"]), Some("UA9VU"));
+        // Transposed letters
+        assert_eq!(best("K1ACB", &["K1ABC", "K1AXY"]), Some("K1ABC"));
+        // Partials pick the call that contains them
+        assert_eq!(best("XYZ", &["K1ABC", "W9XYZ"]), Some("W9XYZ"));
+        assert_eq!(best("OH2", &["OH2BH", "OK1RR"]), Some("OH2BH"));
+        // Missing prefix character
+        assert_eq!(best("L1ABC", &["DL1ABC", "G4ABC"]), Some("DL1ABC"));
+        // Portable
+        assert_eq!(best("VP2V/K1ABC", &["K1ABC", "K1ABD"]), Some("K1ABC"));
+        // Nothing close enough
+        assert_eq!(best("K1ABC", &["JA7XYZ", "VU2PQR"]), None);
+        assert_eq!(best("", &["K1ABC"]), None);
+        assert_eq!(best("K1ABC", &[]), None);
+    }
+
+    #[test]
+    fn threshold_is_respected() {
+        let calls = ["K1ABC"];
+        let sim = callsign_similarity("K1A", "K1ABC");
+        assert!(best_match("K1A", calls.iter().copied(), sim).is_some());
+        assert!(best_match("K1A", calls.iter().copied(), sim + 0.01).is_none());
+        assert!(best_match("K1ABC", calls.iter().copied(), 1.0).is_some());
+    }
+}
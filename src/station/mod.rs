@@ -1,3 +1,16 @@
+pub mod ambience;
 pub mod caller_manager;
+pub mod confusion;
+pub mod density;
+pub mod fight;
+pub mod focus;
+pub mod matching;
 
-pub use caller_manager::{CallerManager, CallerResponse};
+pub use ambience::AmbienceScheduler;
+pub use caller_manager::{
+    calling_message, framed_exchange, give_up_message, make_double, matches_partial_call,
+    with_courtesy_word, wrong_callsign, CallerManager, CallerResponse,
+};
+pub use density::CallerDensity;
+pub use fight::{FightEvent, FrequencyFight};
+pub use focus::CharFocus;
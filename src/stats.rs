@@ -1,7 +1,15 @@
-use std::collections::HashMap;
+use crate::state::{ContestState, FillAnswer, UserTxType};
+use serde::{Deserialize, Serialize, Serializer};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// A TU-to-CQ gap longer than this is a break, not part of the cycle
+const CYCLE_BREAK: Duration = Duration::from_secs(30);
 
 /// Record of a single QSO for analysis
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct QsoRecord {
     pub expected_callsign: String,
     pub entered_callsign: String,
@@ -10,26 +18,259 @@ pub struct QsoRecord {
     pub entered_exchange: String,
     pub exchange_correct: bool,
     pub station_wpm: u8,
+    /// Approximate signal-to-noise ratio of the caller in dB (None in
+    /// older saves)
+    pub snr_db: Option<f32>,
+    /// Stations calling when the QSO started (None in older saves)
+    pub pileup_size: Option<usize>,
+    /// Seconds from the callers starting to our reply with a call
+    pub copy_secs: Option<f32>,
     pub points: u32,
+    /// Points log checking takes off for a busted call or exchange, on top
+    /// of the QSO's own points
+    pub penalty: u32,
     pub used_agn_callsign: bool,
     pub used_agn_exchange: bool,
     pub used_f5_callsign: bool,
+    /// What the caller sent to ask for one element of our exchange, if
+    /// they did
+    pub fill_request: Option<String>,
+    /// How we answered the fill request (None if we didn't)
+    pub fill_answer: Option<FillAnswer>,
+    /// What we asked the caller to repeat, one entry per request: "Call",
+    /// "Exchange", or an exchange field's label
+    pub repeat_requests: Vec<String>,
+    /// Logged a call closer to another station that answered our partial
+    /// query than to the station we actually worked
+    pub worked_wrong_station: bool,
+    /// Exchange the station sent the last time it was logged this session
+    pub worked_before_exchange: Option<String>,
+    /// Labels of the exchange fields logged wrong
+    pub wrong_fields: Vec<String>,
+    /// Each exchange field's label and whether it was logged right (empty
+    /// in older saves)
+    pub field_results: Vec<(String, bool)>,
+    /// Labels of the wrong fields logged with a value that can't exist
+    pub impossible_fields: Vec<String>,
+    /// Some of the exchange came from call-history pre-fill
+    pub used_prefill: bool,
+    /// Logged with pre-filled fields the user never moved into to check
+    pub prefill_unverified: bool,
+    /// Logged before the caller's exchange had finished playing, so the
+    /// end of it was guessed rather than copied
+    pub logged_early: bool,
+    /// Every call we sent the station was wrong, so they never heard
+    /// their own call from us
+    pub sent_busted_call: bool,
+    /// Marked by the user as not counting; kept in the log but left out of
+    /// the score and the analysis
+    pub excluded: bool,
+    /// Flagged by the user for later review
+    pub flagged: bool,
+    /// The user's note on a flagged QSO (may be empty)
+    pub note: String,
+    /// Local time the QSO was logged, RFC 3339 (empty in older saves)
+    pub logged_at: String,
+}
+
+impl QsoRecord {
+    pub fn used_agn(&self) -> bool {
+        self.used_agn_callsign || self.used_agn_exchange
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.callsign_correct && self.exchange_correct
+    }
+
+    /// When the QSO was logged, in UTC, if known
+    pub fn logged_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(&self.logged_at)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    }
+
+    /// Time of day the QSO was logged, if known
+    pub fn logged_time(&self) -> Option<String> {
+        chrono::DateTime::parse_from_rfc3339(&self.logged_at)
+            .ok()
+            .map(|t| t.format("%H:%M:%S").to_string())
+    }
+}
+
+/// How the user dealt with two callers doubling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DoubleOutcome {
+    /// Asked for a repeat (AGN or a partial query) instead of guessing
+    AskedAgain,
+    /// Copied one of the two calls correctly through the double
+    PulledOut,
+    /// Came back to a call that was neither station
+    Garbled,
+}
+
+/// A QSO dropped after our exchange went out, which the other station
+/// logged: we're not in each other's logs, and it counts against us
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NilRecord {
+    pub callsign: String,
+    pub penalty: u32,
+    /// Local time the QSO was dropped, RFC 3339
+    pub at: String,
 }
 
 /// Session statistics collector and analyzer
 #[derive(Clone, Debug, Default)]
 pub struct SessionStats {
     pub qsos: Vec<QsoRecord>,
+    /// Outcome of each double the user heard
+    pub doubles: Vec<DoubleOutcome>,
+    /// On-air timeline for CQ cycle timing
+    pub timing: CycleTiming,
+    /// Callers who ran out of patience and left before being worked
+    pub callers_lost: usize,
+    /// QSOs we dropped that the other station logged
+    pub nils: Vec<NilRecord>,
+    /// Callers spread across a wide pileup and the ones worked
+    pub wide_pileup: WidePileupTally,
+    /// Stations that took our frequency and what they cost us
+    pub fights: FightTally,
+    /// Station probability over the session while dynamic density is on
+    pub density: Vec<DensitySample>,
 }
 
-/// Analysis results for display
+/// Caller density after a QSO, for the chart in the stats window
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DensitySample {
+    /// Minutes into the session
+    pub minutes: f32,
+    pub probability: f32,
+}
+
+/// Frequency fights: stations that started CQing on our run frequency,
+/// the QSYs we made, and the time each cost
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FightTally {
+    pub fights: usize,
+    pub qsys: usize,
+    /// Time spent sharing the frequency with someone CQing over us
+    pub fought: Duration,
+    /// Time spent finding a clear frequency after a QSY
+    pub qsy_time: Duration,
+}
+
+impl FightTally {
+    pub fn time_lost(&self) -> Duration {
+        self.fought + self.qsy_time
+    }
+}
+
+/// Distinct callers heard in a wide pileup, and how many were tuned in and
+/// worked with the right call
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WidePileupTally {
+    heard: HashSet<String>,
+    recovered: HashSet<String>,
+}
+
+impl WidePileupTally {
+    pub fn hear(&mut self, callsign: &str) {
+        self.heard.insert(callsign.to_string());
+    }
+
+    /// Only callers heard in the pileup count as recovered
+    pub fn recover(&mut self, callsign: &str) {
+        if self.heard.contains(callsign) {
+            self.recovered.insert(callsign.to_string());
+        }
+    }
+
+    pub fn heard(&self) -> usize {
+        self.heard.len()
+    }
+
+    pub fn recovered(&self) -> usize {
+        self.recovered.len()
+    }
+}
+
+/// Where the operating time goes, built by sampling the contest state
 #[derive(Clone, Debug, Default)]
+pub struct CycleTiming {
+    last: Option<(ContestState, Instant)>,
+    transmitting: Duration,
+    listening: Duration,
+    /// Listening after a CQ with nobody calling (part of `listening`)
+    dead: Duration,
+    idle: Duration,
+    cq_count: usize,
+    /// CQs nobody answered before the next one
+    unanswered_cqs: usize,
+    /// The last CQ hasn't drawn a caller yet
+    cq_pending: bool,
+    /// When the last TU finished, until the next CQ
+    tu_finished: Option<Instant>,
+    tu_to_cq: Vec<Duration>,
+}
+
+/// How well doubles were resolved
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DoubleStats {
+    pub total: usize,
+    pub asked_again: usize,
+    pub pulled_out: usize,
+    pub garbled: usize,
+    /// Share of doubles resolved without sending a garbled call
+    pub success_pct: f32,
+}
+
+/// CQ cycle timing for display and export
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct EfficiencyStats {
+    #[serde(serialize_with = "as_seconds")]
+    pub session_time: Duration,
+    pub cq_count: usize,
+    pub unanswered_cqs: usize,
+    /// Average time from the end of a TU to the start of the next CQ
+    #[serde(serialize_with = "as_optional_seconds")]
+    pub avg_tu_to_cq: Option<Duration>,
+    #[serde(serialize_with = "as_optional_seconds")]
+    pub median_tu_to_cq: Option<Duration>,
+    #[serde(serialize_with = "as_seconds")]
+    pub dead_time: Duration,
+    pub transmit_pct: f32,
+    pub listen_pct: f32,
+    pub idle_pct: f32,
+    /// Callers who gave up waiting to be worked
+    pub callers_lost: usize,
+}
+
+/// Durations are exported as plain seconds
+fn as_seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+fn as_optional_seconds<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => as_seconds(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Analysis results for display
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct StatsAnalysis {
     pub total_qsos: usize,
     pub correct_callsigns: usize,
     pub correct_exchanges: usize,
     pub correct_qsos: usize, // Both callsign and exchange correct (may have used AGN)
     pub total_points: u32,
+    pub penalty_points: u32, // Lost to busts and NILs
+    pub penalized_points: i64,
+    pub nil_count: usize,
     pub callsign_accuracy: f32,
     pub exchange_accuracy: f32,
     pub correct_rate: f32, // Percentage of correct QSOs
@@ -37,15 +278,153 @@ pub struct StatsAnalysis {
     pub min_station_wpm: u8,
     pub max_station_wpm: u8,
     pub wpm_buckets: Vec<WpmBucketStat>,
+    pub snr_buckets: Vec<SnrBucketStat>,
+    pub pileup_sizes: Vec<PileupSizeStat>,
+    pub hours: Vec<HourStat>,
     pub streaks: StreakStats,
     pub char_error_rates: Vec<(char, f32, usize)>, // (char, error_rate, total_count)
     pub agn_callsign_count: usize,                 // QSOs where AGN was used for callsign
     pub agn_exchange_count: usize,                 // QSOs where AGN was used for exchange
     pub agn_any_count: usize,                      // QSOs where any AGN was used
     pub f5_callsign_count: usize,                  // QSOs where F5 was used for callsign
+    pub wrong_station_count: usize, // QSOs logged with another partial-match station's call
+    pub fill_request_count: usize,  // QSOs where the caller asked for a fill
+    pub fill_element_count: usize,  // ...answered with just the element asked for
+    pub fill_full_count: usize,     // ...answered with the whole exchange
+    pub field_errors: Vec<(String, usize)>, // (field label, QSOs with that field wrong)
+    pub repeats_by_element: Vec<(String, usize)>, // (element, repeats asked for), most first
+    pub field_accuracy: Vec<FieldAccuracyStat>,
+    pub impossible_entry_count: usize, // exchange fields logged with a value that can't exist
+    pub prefill_count: usize,          // QSOs logged with call-history pre-fill
+    pub prefill_unverified_count: usize, // ...where pre-filled fields went unchecked
+    pub early_count: usize,            // QSOs logged before the exchange finished
+    pub early_correct_count: usize,    // ...that turned out right anyway
+    pub sent_busted_call_count: usize, // QSOs where the call we sent was never right
+    pub efficiency: EfficiencyStats,
+    pub doubles: DoubleStats,
 }
 
-#[derive(Clone, Debug, Default)]
+impl CycleTiming {
+    fn is_transmitting(state: ContestState) -> bool {
+        // QsoComplete lasts while the TU is being sent
+        matches!(
+            state,
+            ContestState::CallingCq
+                | ContestState::UserTransmitting { .. }
+                | ContestState::QsoComplete
+        )
+    }
+
+    fn is_tu(state: ContestState) -> bool {
+        matches!(
+            state,
+            ContestState::QsoComplete
+                | ContestState::UserTransmitting {
+                    tx_type: UserTxType::Tu
+                }
+        )
+    }
+
+    /// Record the current state; call regularly (every tick). Time since
+    /// the previous call is charged to the previous state.
+    pub fn observe(&mut self, state: ContestState, now: Instant) {
+        let Some((prev, since)) = self.last else {
+            self.last = Some((state, now));
+            return;
+        };
+        let elapsed = now.saturating_duration_since(since);
+        match prev {
+            ContestState::Idle => self.idle += elapsed,
+            ContestState::WaitingForCallers => {
+                self.dead += elapsed;
+                self.listening += elapsed;
+            }
+            prev if Self::is_transmitting(prev) => self.transmitting += elapsed,
+            _ => self.listening += elapsed,
+        }
+
+        if prev != state {
+            match state {
+                ContestState::CallingCq => {
+                    self.cq_count += 1;
+                    if self.cq_pending {
+                        self.unanswered_cqs += 1;
+                    }
+                    self.cq_pending = true;
+                    if let Some(tu) = self.tu_finished.take() {
+                        let gap = now.saturating_duration_since(tu);
+                        if gap <= CYCLE_BREAK {
+                            self.tu_to_cq.push(gap);
+                        }
+                    }
+                }
+                ContestState::StationsCalling => {
+                    // Answered, or a tail-ender saved us the CQ
+                    self.cq_pending = false;
+                    self.tu_finished = None;
+                }
+                ContestState::Idle if Self::is_tu(prev) => {
+                    self.tu_finished = Some(now);
+                }
+                _ => {}
+            }
+        }
+        self.last = Some((state, now));
+    }
+
+    /// Leave the time since the last observation out, as when the session
+    /// was paused
+    pub fn skip_gap(&mut self) {
+        self.last = None;
+    }
+
+    pub fn analyze(&self) -> EfficiencyStats {
+        let session_time = self.transmitting + self.listening + self.idle;
+        let pct = |part: Duration| {
+            if session_time.is_zero() {
+                0.0
+            } else {
+                part.as_secs_f32() / session_time.as_secs_f32() * 100.0
+            }
+        };
+
+        let mut gaps = self.tu_to_cq.clone();
+        gaps.sort();
+        let avg_tu_to_cq =
+            (!gaps.is_empty()).then(|| gaps.iter().sum::<Duration>() / gaps.len() as u32);
+        let median_tu_to_cq = gaps.get(gaps.len() / 2).copied();
+
+        EfficiencyStats {
+            session_time,
+            cq_count: self.cq_count,
+            unanswered_cqs: self.unanswered_cqs,
+            avg_tu_to_cq,
+            median_tu_to_cq,
+            dead_time: self.dead,
+            transmit_pct: pct(self.transmitting),
+            listen_pct: pct(self.listening),
+            idle_pct: pct(self.idle),
+            // Filled in by SessionStats, which does the counting
+            callers_lost: 0,
+        }
+    }
+}
+
+/// Seconds with millisecond resolution, e.g. "1.234 s"
+pub fn format_seconds(duration: Duration) -> String {
+    format!("{:.3} s", duration.as_secs_f64())
+}
+
+/// Labels with their counts, as in "Sec 3, Call 1"
+pub fn format_counts(counts: &[(String, usize)]) -> String {
+    counts
+        .iter()
+        .map(|(label, count)| format!("{} {}", label, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct StreakStats {
     pub current_clean: usize,
     pub max_clean: usize,
@@ -53,7 +432,7 @@ pub struct StreakStats {
     pub max_error: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct WpmBucketStat {
     pub start_wpm: u8,
     pub label: String,
@@ -62,62 +441,277 @@ pub struct WpmBucketStat {
     pub accuracy_pct: f32,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct SnrBucketStat {
+    pub start_db: i32,
+    pub label: String,
+    pub total: usize,
+    pub correct: usize,
+    pub accuracy_pct: f32,
+}
+
+/// How often one exchange element was logged right
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldAccuracyStat {
+    pub label: String,
+    pub total: usize,
+    pub correct: usize,
+    pub accuracy_pct: f32,
+}
+
+/// Accuracy and copy time with a given number of stations calling
+#[derive(Clone, Debug, Serialize)]
+pub struct PileupSizeStat {
+    /// Stations calling; the last group holds this many or more
+    pub callers: usize,
+    pub label: String,
+    pub total: usize,
+    pub correct: usize,
+    pub accuracy_pct: f32,
+    /// Average seconds to come back with a call, where known
+    pub avg_copy_secs: Option<f32>,
+}
+
+/// Pileups this big or bigger are grouped together
+const PILEUP_SIZE_GROUP_MAX: usize = 5;
+
+/// QSOs, accuracy and points in one clock hour (UTC), as in a contest's
+/// hour-by-hour rate sheet
+#[derive(Clone, Debug, Serialize)]
+pub struct HourStat {
+    /// Start of the hour, e.g. "1400Z" (with the day when the session
+    /// runs past midnight UTC, e.g. "15 0100Z")
+    pub label: String,
+    pub qsos: usize,
+    pub correct: usize,
+    pub accuracy_pct: f32,
+    pub points: u32,
+}
+
+/// Column the log viewer is sorted on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogSort {
+    /// Order logged
+    #[default]
+    Number,
+    Callsign,
+    Wpm,
+    /// Errors, then correct with AGN, then clean
+    Result,
+}
+
+/// Which logged QSOs the log viewer shows, and in what order
+#[derive(Clone, Debug)]
+pub struct LogQuery {
+    /// Part of the callsign logged or sent, any case
+    pub search: String,
+    pub errors_only: bool,
+    pub agn_only: bool,
+    pub wpm_min: u8,
+    pub wpm_max: u8,
+    pub sort: LogSort,
+    pub descending: bool,
+}
+
+impl Default for LogQuery {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            errors_only: false,
+            agn_only: false,
+            wpm_min: 0,
+            wpm_max: 99,
+            sort: LogSort::Number,
+            descending: true,
+        }
+    }
+}
+
+impl LogQuery {
+    pub fn matches(&self, qso: &QsoRecord) -> bool {
+        let search = self.search.trim().to_uppercase();
+        (search.is_empty()
+            || qso.entered_callsign.contains(&search)
+            || qso.expected_callsign.contains(&search))
+            && (!self.errors_only || !qso.is_correct())
+            && (!self.agn_only || qso.used_agn())
+            && (self.wpm_min..=self.wpm_max).contains(&qso.station_wpm)
+    }
+}
+
 impl SessionStats {
     pub fn new() -> Self {
-        Self { qsos: Vec::new() }
+        Self::default()
     }
 
     pub fn log_qso(&mut self, record: QsoRecord) {
         self.qsos.push(record);
     }
 
+    pub fn log_double(&mut self, outcome: DoubleOutcome) {
+        self.doubles.push(outcome);
+    }
+
+    pub fn log_callers_lost(&mut self, count: usize) {
+        self.callers_lost += count;
+    }
+
+    pub fn log_nil(&mut self, nil: NilRecord) {
+        self.nils.push(nil);
+    }
+
+    /// Points lost to busted QSOs that count and to NILs
+    pub fn penalty_points(&self) -> u32 {
+        self.counted().map(|q| q.penalty).sum::<u32>()
+            + self.nils.iter().map(|nil| nil.penalty).sum::<u32>()
+    }
+
+    /// Replace the most recent QSO with a corrected copy
+    pub fn replace_last_qso(&mut self, record: QsoRecord) {
+        if let Some(last) = self.qsos.last_mut() {
+            *last = record;
+        }
+    }
+
+    /// Mark a logged QSO as counting or not
+    pub fn set_counted(&mut self, index: usize, counted: bool) {
+        if let Some(qso) = self.qsos.get_mut(index) {
+            qso.excluded = !counted;
+        }
+    }
+
+    /// Flag a logged QSO for review, replacing any earlier note
+    pub fn flag_qso(&mut self, index: usize, note: String) {
+        if let Some(qso) = self.qsos.get_mut(index) {
+            qso.flagged = true;
+            qso.note = note;
+        }
+    }
+
+    /// QSOs that count toward the score and the analysis
+    pub fn counted(&self) -> impl Iterator<Item = &QsoRecord> {
+        self.qsos.iter().filter(|q| !q.excluded)
+    }
+
     pub fn clear(&mut self) {
         self.qsos.clear();
+        self.doubles.clear();
+        self.timing = CycleTiming::default();
+        self.callers_lost = 0;
+        self.nils.clear();
+        self.wide_pileup = WidePileupTally::default();
+        self.fights = FightTally::default();
+        self.density.clear();
+    }
+
+    /// Exchange a callsign sent in its most recent logged QSO, if any
+    pub fn previous_exchange(&self, callsign: &str) -> Option<String> {
+        self.qsos
+            .iter()
+            .rev()
+            .find(|q| q.expected_callsign == callsign)
+            .map(|q| q.expected_exchange.clone())
+    }
+
+    /// Indexes of the logged QSOs the query shows, in its order
+    pub fn query_log(&self, query: &LogQuery) -> Vec<usize> {
+        let mut shown: Vec<usize> = (0..self.qsos.len())
+            .filter(|&i| query.matches(&self.qsos[i]))
+            .collect();
+        // Stable, so ties stay in the order logged
+        shown.sort_by(|&a, &b| {
+            let (a_qso, b_qso) = (&self.qsos[a], &self.qsos[b]);
+            match query.sort {
+                LogSort::Number => a.cmp(&b),
+                LogSort::Callsign => a_qso.entered_callsign.cmp(&b_qso.entered_callsign),
+                LogSort::Wpm => a_qso.station_wpm.cmp(&b_qso.station_wpm),
+                LogSort::Result => (a_qso.is_correct(), !a_qso.used_agn())
+                    .cmp(&(b_qso.is_correct(), !b_qso.used_agn())),
+            }
+        });
+        if query.descending {
+            shown.reverse();
+        }
+        shown
     }
 
     pub fn analyze(&self) -> StatsAnalysis {
-        if self.qsos.is_empty() {
-            return StatsAnalysis::default();
+        let mut efficiency = self.timing.analyze();
+        efficiency.callers_lost = self.callers_lost;
+        let doubles = self.analyze_doubles();
+        let penalty_points = self.penalty_points();
+        let nil_count = self.nils.len();
+        if self.counted().next().is_none() {
+            return StatsAnalysis {
+                penalty_points,
+                penalized_points: -i64::from(penalty_points),
+                nil_count,
+                efficiency,
+                doubles,
+                ..Default::default()
+            };
         }
 
-        let total_qsos = self.qsos.len();
-        let correct_callsigns = self.qsos.iter().filter(|q| q.callsign_correct).count();
-        let correct_exchanges = self.qsos.iter().filter(|q| q.exchange_correct).count();
+        let total_qsos = self.counted().count();
+        let correct_callsigns = self.counted().filter(|q| q.callsign_correct).count();
+        let correct_exchanges = self.counted().filter(|q| q.exchange_correct).count();
 
         // Correct QSOs: both callsign and exchange correct (may have used AGN)
         let correct_qsos = self
-            .qsos
-            .iter()
+            .counted()
             .filter(|q| q.callsign_correct && q.exchange_correct)
             .count();
 
-        let total_points: u32 = self.qsos.iter().map(|q| q.points).sum();
+        let total_points: u32 = self.counted().map(|q| q.points).sum();
 
         let callsign_accuracy = (correct_callsigns as f32 / total_qsos as f32) * 100.0;
         let exchange_accuracy = (correct_exchanges as f32 / total_qsos as f32) * 100.0;
         let correct_rate = (correct_qsos as f32 / total_qsos as f32) * 100.0;
 
         // AGN usage stats
-        let agn_callsign_count = self.qsos.iter().filter(|q| q.used_agn_callsign).count();
-        let agn_exchange_count = self.qsos.iter().filter(|q| q.used_agn_exchange).count();
+        let agn_callsign_count = self.counted().filter(|q| q.used_agn_callsign).count();
+        let agn_exchange_count = self.counted().filter(|q| q.used_agn_exchange).count();
         let agn_any_count = self
-            .qsos
-            .iter()
+            .counted()
             .filter(|q| q.used_agn_callsign || q.used_agn_exchange)
             .count();
-        let f5_callsign_count = self.qsos.iter().filter(|q| q.used_f5_callsign).count();
+        let f5_callsign_count = self.counted().filter(|q| q.used_f5_callsign).count();
+        let wrong_station_count = self.counted().filter(|q| q.worked_wrong_station).count();
+        let fill_request_count = self.counted().filter(|q| q.fill_request.is_some()).count();
+        let fill_answered = |answer: FillAnswer| {
+            self.counted()
+                .filter(|q| q.fill_request.is_some() && q.fill_answer == Some(answer))
+                .count()
+        };
+        let fill_element_count = fill_answered(FillAnswer::Element);
+        let fill_full_count = fill_answered(FillAnswer::FullExchange);
+        let prefill_count = self.counted().filter(|q| q.used_prefill).count();
+        let prefill_unverified_count = self.counted().filter(|q| q.prefill_unverified).count();
+        let early_count = self.counted().filter(|q| q.logged_early).count();
+        let sent_busted_call_count = self.counted().filter(|q| q.sent_busted_call).count();
+        let early_correct_count = self
+            .counted()
+            .filter(|q| q.logged_early && q.is_correct())
+            .count();
+        let impossible_entry_count = self.counted().map(|q| q.impossible_fields.len()).sum();
 
         // WPM stats
-        let wpms: Vec<u8> = self.qsos.iter().map(|q| q.station_wpm).collect();
+        let wpms: Vec<u8> = self.counted().map(|q| q.station_wpm).collect();
         let avg_station_wpm = wpms.iter().map(|&w| w as f32).sum::<f32>() / wpms.len() as f32;
         let min_station_wpm = *wpms.iter().min().unwrap_or(&0);
         let max_station_wpm = *wpms.iter().max().unwrap_or(&0);
 
         let wpm_buckets = self.analyze_wpm_buckets(2);
+        let snr_buckets = self.analyze_snr_buckets(5);
+        let pileup_sizes = self.analyze_pileup_sizes();
+        let hours = self.analyze_hours();
         let streaks = self.analyze_streaks();
 
         // Character error analysis
         let char_error_rates = self.analyze_character_errors();
+        let field_errors = self.analyze_field_errors();
+        let repeats_by_element = self.analyze_repeat_requests();
+        let field_accuracy = self.analyze_field_accuracy();
 
         StatsAnalysis {
             total_qsos,
@@ -125,6 +719,9 @@ impl SessionStats {
             correct_exchanges,
             correct_qsos,
             total_points,
+            penalty_points,
+            penalized_points: i64::from(total_points) - i64::from(penalty_points),
+            nil_count,
             callsign_accuracy,
             exchange_accuracy,
             correct_rate,
@@ -132,20 +729,115 @@ impl SessionStats {
             min_station_wpm,
             max_station_wpm,
             wpm_buckets,
+            snr_buckets,
+            pileup_sizes,
+            hours,
             streaks,
             char_error_rates,
             agn_callsign_count,
             agn_exchange_count,
             agn_any_count,
             f5_callsign_count,
+            wrong_station_count,
+            fill_request_count,
+            fill_element_count,
+            fill_full_count,
+            field_errors,
+            repeats_by_element,
+            field_accuracy,
+            impossible_entry_count,
+            prefill_count,
+            prefill_unverified_count,
+            early_count,
+            early_correct_count,
+            sent_busted_call_count,
+            efficiency,
+            doubles,
+        }
+    }
+
+    fn analyze_doubles(&self) -> DoubleStats {
+        let count = |outcome| self.doubles.iter().filter(|d| **d == outcome).count();
+        let total = self.doubles.len();
+        let garbled = count(DoubleOutcome::Garbled);
+        DoubleStats {
+            total,
+            asked_again: count(DoubleOutcome::AskedAgain),
+            pulled_out: count(DoubleOutcome::PulledOut),
+            garbled,
+            success_pct: if total > 0 {
+                (total - garbled) as f32 / total as f32 * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// How often each exchange field was logged wrong, in the order the
+    /// fields first went wrong
+    fn analyze_field_errors(&self) -> Vec<(String, usize)> {
+        let mut field_errors: Vec<(String, usize)> = Vec::new();
+        for label in self.counted().flat_map(|q| &q.wrong_fields) {
+            match field_errors.iter_mut().find(|(l, _)| l == label) {
+                Some((_, count)) => *count += 1,
+                None => field_errors.push((label.clone(), 1)),
+            }
         }
+        field_errors
+    }
+
+    /// How often we asked for each element to be repeated, most asked for
+    /// first
+    fn analyze_repeat_requests(&self) -> Vec<(String, usize)> {
+        let mut repeats: Vec<(String, usize)> = Vec::new();
+        for label in self.counted().flat_map(|q| &q.repeat_requests) {
+            match repeats.iter_mut().find(|(l, _)| l == label) {
+                Some((_, count)) => *count += 1,
+                None => repeats.push((label.clone(), 1)),
+            }
+        }
+        repeats.sort_by_key(|r| Reverse(r.1));
+        repeats
+    }
+
+    /// Accuracy of each exchange element, in the order first logged
+    fn analyze_field_accuracy(&self) -> Vec<FieldAccuracyStat> {
+        let mut stats: Vec<FieldAccuracyStat> = Vec::new();
+        for (label, correct) in self.counted().flat_map(|q| &q.field_results) {
+            let index = match stats.iter().position(|s| s.label == *label) {
+                Some(index) => index,
+                None => {
+                    stats.push(FieldAccuracyStat {
+                        label: label.clone(),
+                        total: 0,
+                        correct: 0,
+                        accuracy_pct: 0.0,
+                    });
+                    stats.len() - 1
+                }
+            };
+            stats[index].total += 1;
+            if *correct {
+                stats[index].correct += 1;
+            }
+        }
+        for stat in &mut stats {
+            stat.accuracy_pct = stat.correct as f32 / stat.total as f32 * 100.0;
+        }
+        stats
+    }
+
+    /// Error rate per character over the counted QSOs, worst first:
+    /// (char, error_rate_pct, samples)
+    pub fn char_error_rates(&self) -> Vec<(char, f32, usize)> {
+        self.analyze_character_errors()
     }
 
     fn analyze_character_errors(&self) -> Vec<(char, f32, usize)> {
         let mut char_totals: HashMap<char, usize> = HashMap::new();
         let mut char_errors: HashMap<char, usize> = HashMap::new();
 
-        for qso in &self.qsos {
+        for qso in self.counted() {
             // Always count totals for all characters encountered
             Self::count_chars(&qso.expected_callsign, &mut char_totals);
             Self::count_chars(&qso.expected_exchange, &mut char_totals);
@@ -190,7 +882,7 @@ impl SessionStats {
     fn analyze_wpm_buckets(&self, bucket_size: u8) -> Vec<WpmBucketStat> {
         let mut buckets: HashMap<u8, (usize, usize)> = HashMap::new();
 
-        for qso in &self.qsos {
+        for qso in self.counted() {
             let bucket_start = (qso.station_wpm / bucket_size) * bucket_size;
             let entry = buckets.entry(bucket_start).or_insert((0, 0));
             entry.0 += 1;
@@ -223,10 +915,125 @@ impl SessionStats {
         stats
     }
 
+    /// Accuracy by caller SNR, in buckets `bucket_db` wide; QSOs without an
+    /// SNR are left out
+    fn analyze_snr_buckets(&self, bucket_db: i32) -> Vec<SnrBucketStat> {
+        let mut buckets: HashMap<i32, (usize, usize)> = HashMap::new();
+
+        for qso in self.counted() {
+            let Some(snr_db) = qso.snr_db else {
+                continue;
+            };
+            let bucket_start = (snr_db.floor() as i32).div_euclid(bucket_db) * bucket_db;
+            let entry = buckets.entry(bucket_start).or_insert((0, 0));
+            entry.0 += 1;
+            if qso.is_correct() {
+                entry.1 += 1;
+            }
+        }
+
+        let mut stats: Vec<SnrBucketStat> = buckets
+            .into_iter()
+            .map(|(start, (total, correct))| SnrBucketStat {
+                start_db: start,
+                label: format!("{} to {} dB", start, start + bucket_db),
+                total,
+                correct,
+                accuracy_pct: correct as f32 / total as f32 * 100.0,
+            })
+            .collect();
+
+        stats.sort_by_key(|stat| stat.start_db);
+
+        stats
+    }
+
+    /// Accuracy and copy time by the number of stations calling when each
+    /// QSO started; QSOs without a pileup size are left out
+    fn analyze_pileup_sizes(&self) -> Vec<PileupSizeStat> {
+        // callers -> (total, correct, copy seconds)
+        let mut groups: HashMap<usize, (usize, usize, Vec<f32>)> = HashMap::new();
+
+        for qso in self.counted() {
+            let Some(size) = qso.pileup_size else {
+                continue;
+            };
+            let entry = groups
+                .entry(size.clamp(1, PILEUP_SIZE_GROUP_MAX))
+                .or_default();
+            entry.0 += 1;
+            if qso.is_correct() {
+                entry.1 += 1;
+            }
+            entry.2.extend(qso.copy_secs);
+        }
+
+        let mut stats: Vec<PileupSizeStat> = groups
+            .into_iter()
+            .map(|(callers, (total, correct, copy_secs))| PileupSizeStat {
+                callers,
+                label: if callers == PILEUP_SIZE_GROUP_MAX {
+                    format!("{}+", callers)
+                } else {
+                    callers.to_string()
+                },
+                total,
+                correct,
+                accuracy_pct: correct as f32 / total as f32 * 100.0,
+                avg_copy_secs: (!copy_secs.is_empty())
+                    .then(|| copy_secs.iter().sum::<f32>() / copy_secs.len() as f32),
+            })
+            .collect();
+
+        stats.sort_by_key(|stat| stat.callers);
+
+        stats
+    }
+
+    /// QSOs by the UTC hour they were logged in, oldest first; QSOs from
+    /// older saves without a time are left out
+    fn analyze_hours(&self) -> Vec<HourStat> {
+        use chrono::{Datelike, Timelike};
+
+        // (date, hour) -> (qsos, correct, points)
+        let mut hours: HashMap<(chrono::NaiveDate, u32), (usize, usize, u32)> = HashMap::new();
+        for qso in self.counted() {
+            let Some(time) = qso.logged_utc() else {
+                continue;
+            };
+            let entry = hours.entry((time.date_naive(), time.hour())).or_default();
+            entry.0 += 1;
+            if qso.is_correct() {
+                entry.1 += 1;
+            }
+            entry.2 += qso.points;
+        }
+
+        let mut keys: Vec<_> = hours.keys().copied().collect();
+        keys.sort();
+        let one_day = keys.first().map(|k| k.0) == keys.last().map(|k| k.0);
+        keys.into_iter()
+            .map(|(date, hour)| {
+                let (qsos, correct, points) = hours[&(date, hour)];
+                HourStat {
+                    label: if one_day {
+                        format!("{:02}00Z", hour)
+                    } else {
+                        format!("{:02} {:02}00Z", date.day(), hour)
+                    },
+                    qsos,
+                    correct,
+                    accuracy_pct: correct as f32 / qsos as f32 * 100.0,
+                    points,
+                }
+            })
+            .collect()
+    }
+
     fn analyze_streaks(&self) -> StreakStats {
         let mut streaks = StreakStats::default();
 
-        for qso in &self.qsos {
+        for qso in self.counted() {
             let clean = qso.callsign_correct && qso.exchange_correct;
             if clean {
                 streaks.current_clean += 1;
@@ -276,3 +1083,398 @@ impl SessionStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StationTxType;
+
+    #[test]
+    fn cycle_timing_splits_transmit_listen_and_dead_time() {
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+        let mut timing = CycleTiming::default();
+        let timeline = [
+            (0, ContestState::Idle),
+            (1000, ContestState::CallingCq),
+            (3000, ContestState::WaitingForCallers),
+            // Nobody came back: CQ again
+            (5000, ContestState::CallingCq),
+            (7000, ContestState::WaitingForCallers),
+            (7500, ContestState::StationsCalling),
+            (
+                9000,
+                ContestState::UserTransmitting {
+                    tx_type: UserTxType::Exchange,
+                },
+            ),
+            (10000, ContestState::WaitingForStation),
+            (
+                10200,
+                ContestState::StationTransmitting {
+                    tx_type: StationTxType::SendingExchange,
+                },
+            ),
+            (12000, ContestState::QsoComplete),
+            (13000, ContestState::Idle),
+            (14250, ContestState::CallingCq),
+            (16250, ContestState::CallingCq),
+        ];
+        for (ms, state) in timeline {
+            timing.observe(state, at(ms));
+        }
+
+        let stats = timing.analyze();
+        assert_eq!(stats.cq_count, 3);
+        assert_eq!(stats.unanswered_cqs, 1);
+        assert_eq!(stats.avg_tu_to_cq, Some(Duration::from_millis(1250)));
+        assert_eq!(stats.dead_time, Duration::from_millis(2500));
+        assert_eq!(stats.session_time, Duration::from_millis(16250));
+        // CQs 2+2+2 s, exchange 1 s, TU 1 s
+        let transmit = 8.0 / 16.25 * 100.0;
+        assert!((stats.transmit_pct - transmit).abs() < 0.01);
+        let idle = 2.25 / 16.25 * 100.0;
+        assert!((stats.idle_pct - idle).abs() < 0.01);
+    }
+
+    #[test]
+    fn doubles_count_garbled_calls_as_failures() {
+        let mut stats = SessionStats::new();
+        stats.log_double(DoubleOutcome::AskedAgain);
+        stats.log_double(DoubleOutcome::PulledOut);
+        stats.log_double(DoubleOutcome::Garbled);
+        stats.log_double(DoubleOutcome::AskedAgain);
+
+        let doubles = stats.analyze().doubles;
+        assert_eq!(doubles.total, 4);
+        assert_eq!(doubles.asked_again, 2);
+        assert_eq!(doubles.garbled, 1);
+        assert_eq!(doubles.success_pct, 75.0);
+
+        stats.clear();
+        assert_eq!(stats.analyze().doubles.total, 0);
+    }
+
+    #[test]
+    fn snr_buckets_skip_qsos_without_a_reading() {
+        let mut stats = SessionStats::new();
+        for (snr_db, correct) in [
+            (Some(-2.5), false),
+            (Some(3.0), true),
+            (Some(4.9), false),
+            (Some(12.0), true),
+            (None, false),
+        ] {
+            stats.log_qso(QsoRecord {
+                snr_db,
+                callsign_correct: correct,
+                exchange_correct: true,
+                ..Default::default()
+            });
+        }
+        let buckets = stats.analyze().snr_buckets;
+        let summary: Vec<(&str, usize, usize)> = buckets
+            .iter()
+            .map(|b| (b.label.as_str(), b.total, b.correct))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("-5 to 0 dB", 1, 0),
+                ("0 to 5 dB", 2, 1),
+                ("10 to 15 dB", 1, 1)
+            ]
+        );
+        assert_eq!(buckets[1].accuracy_pct, 50.0);
+    }
+
+    #[test]
+    fn field_accuracy_counts_each_element() {
+        let mut stats = SessionStats::new();
+        let fields = |nr: bool, sec: bool| vec![("NR".to_string(), nr), ("Sec".to_string(), sec)];
+        for field_results in [fields(true, true), fields(false, true), fields(true, false)] {
+            // The wrong section was one that doesn't exist
+            let impossible_fields = if field_results[1].1 {
+                Vec::new()
+            } else {
+                vec!["Sec".to_string()]
+            };
+            stats.log_qso(QsoRecord {
+                field_results,
+                impossible_fields,
+                ..Default::default()
+            });
+        }
+        // An older save, with no per-element results
+        stats.log_qso(QsoRecord::default());
+        let analysis = stats.analyze();
+        assert_eq!(analysis.impossible_entry_count, 1);
+        let accuracy = analysis.field_accuracy;
+        let summary: Vec<(&str, usize, usize)> = accuracy
+            .iter()
+            .map(|f| (f.label.as_str(), f.total, f.correct))
+            .collect();
+        assert_eq!(summary, [("NR", 3, 2), ("Sec", 3, 2)]);
+        assert!((accuracy[0].accuracy_pct - 66.67).abs() < 0.01);
+    }
+
+    #[test]
+    fn pileup_sizes_group_big_pileups_together() {
+        let mut stats = SessionStats::new();
+        for (size, copy_secs, correct) in [
+            (Some(1), Some(1.0), true),
+            (Some(1), Some(2.0), true),
+            (Some(3), None, false),
+            (Some(5), Some(4.0), true),
+            (Some(7), Some(6.0), false),
+            (None, Some(9.0), true),
+        ] {
+            stats.log_qso(QsoRecord {
+                pileup_size: size,
+                copy_secs,
+                callsign_correct: correct,
+                exchange_correct: true,
+                ..Default::default()
+            });
+        }
+        let sizes = stats.analyze().pileup_sizes;
+        let summary: Vec<(&str, usize, f32, Option<f32>)> = sizes
+            .iter()
+            .map(|s| (s.label.as_str(), s.total, s.accuracy_pct, s.avg_copy_secs))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("1", 2, 100.0, Some(1.5)),
+                ("3", 1, 0.0, None),
+                ("5+", 2, 50.0, Some(5.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_pause_after_tu_is_not_a_cycle() {
+        let t0 = Instant::now();
+        let mut timing = CycleTiming::default();
+        timing.observe(ContestState::QsoComplete, t0);
+        timing.observe(ContestState::Idle, t0 + Duration::from_secs(1));
+        timing.observe(ContestState::CallingCq, t0 + Duration::from_secs(120));
+        assert_eq!(timing.analyze().avg_tu_to_cq, None);
+    }
+
+    #[test]
+    fn skipped_gaps_are_not_session_time() {
+        let t0 = Instant::now();
+        let mut timing = CycleTiming::default();
+        timing.observe(ContestState::WaitingForCallers, t0);
+        timing.observe(ContestState::Idle, t0 + Duration::from_secs(2));
+        timing.skip_gap();
+        timing.observe(ContestState::Idle, t0 + Duration::from_secs(600));
+        timing.observe(ContestState::CallingCq, t0 + Duration::from_secs(601));
+        let efficiency = timing.analyze();
+        assert_eq!(efficiency.session_time, Duration::from_secs(3));
+        assert_eq!(efficiency.dead_time, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn busts_and_nils_are_penalized() {
+        let mut stats = SessionStats::new();
+        for (points, penalty) in [(1, 0), (1, 0), (0, 1)] {
+            stats.log_qso(QsoRecord {
+                points,
+                penalty,
+                ..Default::default()
+            });
+        }
+        stats.log_nil(NilRecord {
+            callsign: "W9XYZ".to_string(),
+            penalty: 2,
+            at: String::new(),
+        });
+        let analysis = stats.analyze();
+        assert_eq!(analysis.total_points, 2);
+        assert_eq!(analysis.penalty_points, 3);
+        assert_eq!(analysis.nil_count, 1);
+        assert_eq!(analysis.penalized_points, -1);
+
+        // An excluded bust no longer costs anything
+        stats.set_counted(2, false);
+        assert_eq!(stats.penalty_points(), 2);
+    }
+
+    #[test]
+    fn fill_answers_are_counted() {
+        let mut stats = SessionStats::new();
+        for answer in [
+            Some(FillAnswer::Element),
+            Some(FillAnswer::Element),
+            Some(FillAnswer::FullExchange),
+            None,
+        ] {
+            stats.log_qso(QsoRecord {
+                fill_request: Some("NR?".to_string()),
+                fill_answer: answer,
+                ..Default::default()
+            });
+        }
+        stats.log_qso(QsoRecord::default());
+        let analysis = stats.analyze();
+        assert_eq!(analysis.fill_request_count, 4);
+        assert_eq!(analysis.fill_element_count, 2);
+        assert_eq!(analysis.fill_full_count, 1);
+    }
+
+    #[test]
+    fn repeat_requests_are_tallied_by_element() {
+        let mut stats = SessionStats::new();
+        for requests in [vec!["Call", "Sec"], vec!["Sec", "Sec"], vec!["NR"]] {
+            stats.log_qso(QsoRecord {
+                repeat_requests: requests.into_iter().map(String::from).collect(),
+                ..Default::default()
+            });
+        }
+        assert_eq!(
+            stats.analyze().repeats_by_element,
+            [
+                ("Sec".to_string(), 3),
+                ("Call".to_string(), 1),
+                ("NR".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn qsos_are_grouped_by_utc_hour() {
+        let mut stats = SessionStats::new();
+        for (at, correct) in [
+            ("2026-03-14T23:50:00-05:00", true),
+            ("2026-03-15T04:10:00Z", false),
+            ("2026-03-15T04:59:59+00:00", true),
+            ("2026-03-15T05:00:00Z", true),
+            ("", true),
+        ] {
+            stats.log_qso(QsoRecord {
+                callsign_correct: correct,
+                exchange_correct: true,
+                points: u32::from(correct),
+                logged_at: at.to_string(),
+                ..Default::default()
+            });
+        }
+        let labels = |stats: &SessionStats| -> Vec<String> {
+            stats.analyze().hours.into_iter().map(|h| h.label).collect()
+        };
+        let hours = stats.analyze().hours;
+        assert_eq!(labels(&stats), ["0400Z", "0500Z"]);
+        assert_eq!(hours[0].qsos, 3);
+        assert_eq!(hours[0].correct, 2);
+        assert_eq!(hours[0].points, 2);
+        assert_eq!(hours[1].qsos, 1);
+
+        // Past midnight UTC the day is shown too
+        stats.log_qso(QsoRecord {
+            logged_at: "2026-03-14T23:10:00Z".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(labels(&stats), ["14 2300Z", "15 0400Z", "15 0500Z"]);
+    }
+
+    #[test]
+    fn edited_and_excluded_qsos_change_the_analysis() {
+        let qso = |call: &str, correct: bool| QsoRecord {
+            expected_callsign: call.to_string(),
+            entered_callsign: call.to_string(),
+            callsign_correct: correct,
+            exchange_correct: true,
+            points: if correct { 1 } else { 0 },
+            ..Default::default()
+        };
+        let mut stats = SessionStats::new();
+        stats.log_qso(qso("K1ABC", true));
+        stats.log_qso(qso("W9XYZ", false));
+        assert_eq!(stats.analyze().correct_callsigns, 1);
+
+        // Fix the typo in the last QSO
+        stats.replace_last_qso(qso("W9XYZ", true));
+        let analysis = stats.analyze();
+        assert_eq!(analysis.correct_callsigns, 2);
+        assert_eq!(analysis.total_points, 2);
+
+        stats.set_counted(0, false);
+        let analysis = stats.analyze();
+        assert_eq!(analysis.total_qsos, 1);
+        assert_eq!(analysis.total_points, 1);
+        assert_eq!(stats.qsos.len(), 2);
+
+        stats.set_counted(0, true);
+        assert_eq!(stats.analyze().total_qsos, 2);
+    }
+
+    #[test]
+    fn log_query_filters_and_sorts() {
+        let qso = |call: &str, wpm: u8, correct: bool, agn: bool| QsoRecord {
+            expected_callsign: call.to_string(),
+            entered_callsign: call.to_string(),
+            callsign_correct: correct,
+            exchange_correct: true,
+            station_wpm: wpm,
+            used_agn_exchange: agn,
+            ..Default::default()
+        };
+        let mut stats = SessionStats::new();
+        stats.log_qso(qso("K1ABC", 28, true, false));
+        stats.log_qso(qso("W9XYZ", 32, false, false));
+        stats.log_qso(qso("N1MM", 24, true, true));
+        stats.log_qso(qso("K1AB", 36, true, false));
+
+        let mut query = LogQuery::default();
+        assert_eq!(stats.query_log(&query), [3, 2, 1, 0]);
+        query.search = "k1ab".to_string();
+        assert_eq!(stats.query_log(&query), [3, 0]);
+
+        query = LogQuery {
+            errors_only: true,
+            ..Default::default()
+        };
+        assert_eq!(stats.query_log(&query), [1]);
+        query = LogQuery {
+            agn_only: true,
+            ..Default::default()
+        };
+        assert_eq!(stats.query_log(&query), [2]);
+        query = LogQuery {
+            wpm_min: 25,
+            wpm_max: 32,
+            ..Default::default()
+        };
+        assert_eq!(stats.query_log(&query), [1, 0]);
+
+        query = LogQuery {
+            sort: LogSort::Wpm,
+            descending: false,
+            ..Default::default()
+        };
+        assert_eq!(stats.query_log(&query), [2, 0, 1, 3]);
+        query.sort = LogSort::Callsign;
+        assert_eq!(stats.query_log(&query), [3, 0, 2, 1]);
+        // Errors first, then correct with AGN, then clean in order logged
+        query.sort = LogSort::Result;
+        assert_eq!(stats.query_log(&query), [1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn wide_pileup_counts_each_caller_once() {
+        let mut stats = SessionStats::new();
+        for call in ["K1ABC", "W9XYZ", "K1ABC", "N1MM"] {
+            stats.wide_pileup.hear(call);
+        }
+        stats.wide_pileup.recover("K1ABC");
+        stats.wide_pileup.recover("K1ABC");
+        // Never heard in the pileup
+        stats.wide_pileup.recover("DL1ABC");
+        assert_eq!(stats.wide_pileup.heard(), 3);
+        assert_eq!(stats.wide_pileup.recovered(), 1);
+        stats.clear();
+        assert_eq!(stats.wide_pileup.heard(), 0);
+    }
+}
@@ -0,0 +1,399 @@
+// End-of-session summary: an overall grade plus tips drawn from the stats
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::i18n::{tr, tr_args};
+use crate::personal_bests::BestResult;
+use crate::stats::{format_seconds, QsoRecord, SessionStats, StatsAnalysis};
+
+/// QSOs per hour that earn full marks for speed
+const FULL_MARKS_RATE: f32 = 120.0;
+/// Fewest QSOs in a group (call length, WPM range) before it gets a tip
+const MIN_GROUP_QSOS: usize = 3;
+/// TU-to-CQ gap worth a tip
+const SLOW_TU_TO_CQ: Duration = Duration::from_secs(2);
+
+/// A concrete suggestion drawn from the session
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tip {
+    /// Callsign errors are much more common at one call length
+    CallLength {
+        length: usize,
+        wrong_pct: f32,
+        overall_pct: f32,
+        /// Character most often missed in those calls
+        missed: Option<char>,
+        /// Speed to drill at, a little under where the errors happened
+        drill_wpm: u8,
+    },
+    /// Accuracy falls off in one caller WPM range
+    WpmRange {
+        label: String,
+        accuracy_pct: f32,
+        practice_wpm: u8,
+    },
+    /// One exchange field accounts for most exchange errors
+    ExchangeField {
+        label: String,
+        count: usize,
+        serial: bool,
+    },
+    /// Lots of AGN requests
+    ManyRepeats { pct: f32 },
+    /// Logged a different station that answered a partial query
+    WrongStation { count: usize },
+    /// Logged pre-filled exchanges without checking them
+    UncheckedPrefill { count: usize },
+    /// Never sent the caller their call right
+    SentBustedCall { count: usize },
+    /// Logged before the caller finished the exchange
+    LoggedEarly { count: usize, correct: usize },
+    /// Guessed at doubles instead of asking again
+    GarbledDoubles { count: usize },
+    /// Slow to call CQ again after the TU
+    SlowTuToCq { avg: Duration },
+    /// Nothing stood out
+    StepUp,
+}
+
+impl Tip {
+    pub fn text(&self) -> String {
+        match self {
+            Tip::CallLength {
+                length,
+                wrong_pct,
+                overall_pct,
+                missed,
+                drill_wpm,
+            } => {
+                let mut text = tr_args(
+                    "Your callsign errors cluster on {}-character calls ({}% wrong vs {}% overall)",
+                    &[
+                        &length.to_string(),
+                        &format!("{:.0}", wrong_pct),
+                        &format!("{:.0}", overall_pct),
+                    ],
+                );
+                if let Some(missed) = missed {
+                    text.push_str(&tr_args(", most often missing {}", &[&missed.to_string()]));
+                }
+                text.push_str(&tr_args(
+                    "; try a pileup audio drill at {} WPM.",
+                    &[&drill_wpm.to_string()],
+                ));
+                text
+            }
+            Tip::WpmRange {
+                label,
+                accuracy_pct,
+                practice_wpm,
+            } => tr_args(
+                "Accuracy drops to {}% with callers at {} WPM; cap the caller WPM range around {} and work up from there.",
+                &[
+                    &format!("{:.0}", accuracy_pct),
+                    label,
+                    &practice_wpm.to_string(),
+                ],
+            ),
+            Tip::ExchangeField {
+                label,
+                count,
+                serial,
+            } => {
+                let mut text = tr_args(
+                    "Most exchange errors were in {} ({} QSOs).",
+                    &[label, &count.to_string()],
+                );
+                if *serial {
+                    text.push(' ');
+                    text.push_str(tr("Try the Serial Numbers drill."));
+                }
+                text
+            }
+            Tip::ManyRepeats { pct } => tr_args(
+                "You asked for repeats on {}% of QSOs; a partial query (F5) often gets the call quicker than AGN.",
+                &[&format!("{:.0}", pct)],
+            ),
+            Tip::WrongStation { count } => tr_args(
+                "{} times you logged another station that answered your partial query; make sure of the whole call before sending the exchange.",
+                &[&count.to_string()],
+            ),
+            Tip::UncheckedPrefill { count } => tr_args(
+                "{} QSOs were logged with pre-filled boxes you never checked; Tab through them before logging.",
+                &[&count.to_string()],
+            ),
+            Tip::SentBustedCall { count } => tr_args(
+                "{} times every call you sent the station was wrong; fix the call and send it again (F5) before the TU.",
+                &[&count.to_string()],
+            ),
+            Tip::LoggedEarly { count, correct } => tr_args(
+                "{} QSOs were logged before the caller finished the exchange ({} right anyway); wait for the last character, a guess that works in practice costs you in a contest.",
+                &[&count.to_string(), &correct.to_string()],
+            ),
+            Tip::GarbledDoubles { count } => tr_args(
+                "{} doubles ended with a garbled call; when callers double, ask again (F8) rather than guessing.",
+                &[&count.to_string()],
+            ),
+            Tip::SlowTuToCq { avg } => tr_args(
+                "You averaged {} from TU to the next CQ; call CQ as soon as the TU ends to keep the rate up.",
+                &[&format_seconds(*avg)],
+            ),
+            Tip::StepUp => tr(
+                "No weak spots stood out. Raise the caller WPM range or Max Callers for more of a challenge.",
+            )
+            .to_string(),
+        }
+    }
+}
+
+/// Grade and tips for a finished session
+#[derive(Clone, Debug)]
+pub struct SessionSummary {
+    /// Letter grade, A to F
+    pub grade: char,
+    /// Overall score out of 100
+    pub score: u32,
+    pub qsos: usize,
+    pub accuracy_pct: f32,
+    pub rate_per_hour: f32,
+    pub agn_pct: f32,
+    pub tips: Vec<Tip>,
+    /// The session set against the personal bests before it
+    pub bests: Vec<BestResult>,
+}
+
+/// Summarize a session; None when no QSOs counted
+pub fn summarize(stats: &SessionStats) -> Option<SessionSummary> {
+    let analysis = stats.analyze();
+    if analysis.total_qsos == 0 {
+        return None;
+    }
+
+    let hours = analysis.efficiency.session_time.as_secs_f32() / 3600.0;
+    let rate_per_hour = if hours > 0.0 {
+        analysis.total_qsos as f32 / hours
+    } else {
+        0.0
+    };
+    let agn_pct = analysis.agn_any_count as f32 / analysis.total_qsos as f32 * 100.0;
+
+    // Accuracy matters most; speed and clean copy (no repeats) share the rest
+    let speed_score = (rate_per_hour / FULL_MARKS_RATE).min(1.0) * 100.0;
+    let score = (analysis.correct_rate * 0.6 + speed_score * 0.2 + (100.0 - agn_pct) * 0.2)
+        .round()
+        .clamp(0.0, 100.0) as u32;
+
+    Some(SessionSummary {
+        grade: grade_for(score),
+        score,
+        qsos: analysis.total_qsos,
+        accuracy_pct: analysis.correct_rate,
+        rate_per_hour,
+        agn_pct,
+        tips: tips(stats, &analysis),
+        bests: Vec::new(),
+    })
+}
+
+fn grade_for(score: u32) -> char {
+    match score {
+        90.. => 'A',
+        80..=89 => 'B',
+        70..=79 => 'C',
+        60..=69 => 'D',
+        _ => 'F',
+    }
+}
+
+fn tips(stats: &SessionStats, analysis: &StatsAnalysis) -> Vec<Tip> {
+    let qsos: Vec<&QsoRecord> = stats.counted().collect();
+    let total = qsos.len();
+    let mut tips = Vec::new();
+
+    tips.extend(call_length_tip(&qsos, analysis));
+
+    // The worst WPM range well below the session's accuracy
+    if let Some(bucket) = analysis
+        .wpm_buckets
+        .iter()
+        .filter(|b| b.total >= MIN_GROUP_QSOS && b.accuracy_pct <= analysis.correct_rate - 20.0)
+        .min_by(|a, b| a.accuracy_pct.total_cmp(&b.accuracy_pct))
+    {
+        tips.push(Tip::WpmRange {
+            label: bucket.label.clone(),
+            accuracy_pct: bucket.accuracy_pct,
+            practice_wpm: bucket.start_wpm.saturating_sub(2),
+        });
+    }
+
+    if let Some((label, count)) = analysis.field_errors.iter().max_by_key(|(_, count)| *count) {
+        if *count >= 2 && *count * 100 >= total * 15 {
+            tips.push(Tip::ExchangeField {
+                label: label.clone(),
+                count: *count,
+                serial: matches!(label.as_str(), "SER" | "NR" | "#"),
+            });
+        }
+    }
+
+    let repeat_pct = analysis.agn_any_count as f32 / total as f32 * 100.0;
+    if repeat_pct > 30.0 {
+        tips.push(Tip::ManyRepeats { pct: repeat_pct });
+    }
+    if analysis.wrong_station_count > 0 {
+        tips.push(Tip::WrongStation {
+            count: analysis.wrong_station_count,
+        });
+    }
+    if analysis.prefill_unverified_count > 0 {
+        tips.push(Tip::UncheckedPrefill {
+            count: analysis.prefill_unverified_count,
+        });
+    }
+    if analysis.sent_busted_call_count > 0 {
+        tips.push(Tip::SentBustedCall {
+            count: analysis.sent_busted_call_count,
+        });
+    }
+    if analysis.early_count > 0 {
+        tips.push(Tip::LoggedEarly {
+            count: analysis.early_count,
+            correct: analysis.early_correct_count,
+        });
+    }
+    let doubles = &analysis.doubles;
+    if doubles.garbled >= 2 && doubles.garbled > doubles.asked_again {
+        tips.push(Tip::GarbledDoubles {
+            count: doubles.garbled,
+        });
+    }
+    if let Some(avg) = analysis.efficiency.avg_tu_to_cq {
+        if avg > SLOW_TU_TO_CQ {
+            tips.push(Tip::SlowTuToCq { avg });
+        }
+    }
+
+    if tips.is_empty() {
+        tips.push(Tip::StepUp);
+    }
+    tips
+}
+
+/// Find a call length where callsign errors run well above the session's
+/// rate, and the character most often missed in those calls
+fn call_length_tip(qsos: &[&QsoRecord], analysis: &StatsAnalysis) -> Option<Tip> {
+    let overall_pct = 100.0 - analysis.callsign_accuracy;
+    let mut by_length: HashMap<usize, (usize, usize)> = HashMap::new();
+    for qso in qsos {
+        let entry = by_length.entry(qso.expected_callsign.len()).or_default();
+        entry.0 += 1;
+        if !qso.callsign_correct {
+            entry.1 += 1;
+        }
+    }
+
+    let (length, wrong_pct) = by_length
+        .iter()
+        .filter(|(_, (total, wrong))| *total >= MIN_GROUP_QSOS && *wrong >= 2)
+        .map(|(length, (total, wrong))| (*length, *wrong as f32 / *total as f32 * 100.0))
+        .filter(|(_, pct)| *pct >= 25.0 && *pct >= overall_pct * 1.5)
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?;
+
+    let missed_calls: Vec<&&QsoRecord> = qsos
+        .iter()
+        .filter(|q| !q.callsign_correct && q.expected_callsign.len() == length)
+        .collect();
+    let mut missed: HashMap<char, usize> = HashMap::new();
+    for qso in &missed_calls {
+        let entered: Vec<char> = qso.entered_callsign.chars().collect();
+        for (i, ch) in qso.expected_callsign.chars().enumerate() {
+            if entered.get(i) != Some(&ch) {
+                *missed.entry(ch).or_default() += 1;
+            }
+        }
+    }
+    let missed = missed
+        .into_iter()
+        .filter(|(_, count)| *count >= 2)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(ch, _)| ch);
+
+    let avg_wpm = missed_calls
+        .iter()
+        .map(|q| q.station_wpm as f32)
+        .sum::<f32>()
+        / missed_calls.len() as f32;
+
+    Some(Tip::CallLength {
+        length,
+        wrong_pct,
+        overall_pct,
+        missed,
+        drill_wpm: (avg_wpm.round() as u8).saturating_sub(2),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qso(expected: &str, entered: &str, wpm: u8) -> QsoRecord {
+        QsoRecord {
+            expected_callsign: expected.to_string(),
+            entered_callsign: entered.to_string(),
+            callsign_correct: expected == entered,
+            exchange_correct: true,
+            station_wpm: wpm,
+            points: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grades_follow_the_score() {
+        assert_eq!(grade_for(100), 'A');
+        assert_eq!(grade_for(85), 'B');
+        assert_eq!(grade_for(70), 'C');
+        assert_eq!(grade_for(61), 'D');
+        assert_eq!(grade_for(12), 'F');
+        assert!(summarize(&SessionStats::new()).is_none());
+    }
+
+    #[test]
+    fn long_call_errors_give_a_targeted_tip() {
+        let mut stats = SessionStats::new();
+        for call in ["K1AB", "W2XY", "N3QQ", "K4RR", "W5SS", "N6TT"] {
+            stats.log_qso(qso(call, call, 30));
+        }
+        stats.log_qso(qso("KN4NNA", "KN4NA", 30));
+        stats.log_qso(qso("WN2NXB", "WN2TXB", 30));
+        stats.log_qso(qso("AA1NZZ", "AA1NZZ", 30));
+
+        let summary = summarize(&stats).unwrap();
+        assert_eq!(summary.qsos, 9);
+        let Some(Tip::CallLength {
+            length,
+            missed,
+            drill_wpm,
+            ..
+        }) = summary.tips.first()
+        else {
+            panic!("expected a call length tip, got {:?}", summary.tips);
+        };
+        assert_eq!(*length, 6);
+        assert_eq!(*missed, Some('N'));
+        assert_eq!(*drill_wpm, 28);
+    }
+
+    #[test]
+    fn clean_session_suggests_stepping_up() {
+        let mut stats = SessionStats::new();
+        for call in ["K1AB", "W2XY", "N3QQ"] {
+            stats.log_qso(qso(call, call, 25));
+        }
+        let summary = summarize(&stats).unwrap();
+        assert_eq!(summary.tips, vec![Tip::StepUp]);
+        assert_eq!(summary.accuracy_pct, 100.0);
+    }
+}
@@ -0,0 +1,238 @@
+// Guided first session: prompts walk a new user through a QSO (CQ, copy the
+// call, send the exchange, copy theirs, log it) with one slow, strong caller
+// at a time, then hand over to normal operation
+
+use crate::config::{
+    AmbienceSettings, DynamicDensitySettings, FrequencyFightSettings, LidSettings, PileupSettings,
+    SimulationSettings, WidePileupSettings,
+};
+use crate::state::{ContestState, StationTxType, UserTxType};
+
+/// Callers' speed during the tutorial
+pub const TUTORIAL_WPM: u8 = 18;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TutorialStep {
+    CallCq,
+    CopyCall,
+    SendExchange,
+    CopyExchange,
+    LogQso,
+    Done,
+}
+
+impl TutorialStep {
+    /// The steps of a QSO, in order, leaving out Done
+    pub const QSO: [TutorialStep; 5] = [
+        TutorialStep::CallCq,
+        TutorialStep::CopyCall,
+        TutorialStep::SendExchange,
+        TutorialStep::CopyExchange,
+        TutorialStep::LogQso,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            TutorialStep::CallCq => "Call CQ",
+            TutorialStep::CopyCall => "Copy the call",
+            TutorialStep::SendExchange => "Send your exchange",
+            TutorialStep::CopyExchange => "Copy their exchange",
+            TutorialStep::LogQso => "Log the QSO",
+            TutorialStep::Done => "QSO complete",
+        }
+    }
+
+    pub fn instructions(self) -> &'static str {
+        match self {
+            TutorialStep::CallCq => {
+                "Press F1 (or Enter with the Call box empty) to call CQ. One station \
+                 will answer, slowly and loudly."
+            }
+            TutorialStep::CopyCall => {
+                "A station is calling. Type the callsign you hear in the Call box, then \
+                 press Enter to send their call and your exchange. F8 asks them to send \
+                 it again."
+            }
+            TutorialStep::SendExchange => {
+                "You are sending their call and your exchange. Listen: they answer with \
+                 their exchange."
+            }
+            TutorialStep::CopyExchange => {
+                "Type their exchange in the boxes after the call; Space moves to the next \
+                 box. F8 asks for a repeat."
+            }
+            TutorialStep::LogQso => {
+                "Press Enter to send TU and log the QSO. Then the next CQ starts it all \
+                 again."
+            }
+            TutorialStep::Done => {
+                "That's a whole QSO: CQ, their call, your exchange, their exchange, TU. \
+                 Work another here, or start contesting with your own settings."
+            }
+        }
+    }
+}
+
+/// What the tutorial watches in the app to know when a step is done
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub state: ContestState,
+    /// Something is typed in every exchange box
+    pub exchange_entered: bool,
+    pub qsos: u32,
+}
+
+pub struct Tutorial {
+    step: TutorialStep,
+    /// QSOs logged before this round of the tutorial
+    qsos_before: u32,
+    simulation: SimulationSettings,
+}
+
+impl Tutorial {
+    pub fn new(user: &SimulationSettings, qsos: u32) -> Self {
+        Self {
+            step: TutorialStep::CallCq,
+            qsos_before: qsos,
+            simulation: simulation(user),
+        }
+    }
+
+    pub fn step(&self) -> TutorialStep {
+        self.step
+    }
+
+    /// Simulation settings to use in place of the user's while the
+    /// tutorial runs
+    pub fn simulation(&self) -> &SimulationSettings {
+        &self.simulation
+    }
+
+    /// Work another QSO with the prompts
+    pub fn again(&mut self, qsos: u32) {
+        self.step = TutorialStep::CallCq;
+        self.qsos_before = qsos;
+    }
+
+    /// Move on when the app shows the current step has been done
+    pub fn observe(&mut self, seen: &Progress) {
+        use TutorialStep::*;
+        if self.step == Done {
+            return;
+        }
+        if seen.qsos > self.qsos_before {
+            self.step = Done;
+            return;
+        }
+        self.step = match (self.step, seen.state) {
+            // Stopped, or the caller was lost: back to calling CQ
+            (_, ContestState::Idle) => CallCq,
+            (CallCq, ContestState::WaitingForCallers | ContestState::StationsCalling) => CopyCall,
+            (
+                CopyCall,
+                ContestState::UserTransmitting {
+                    tx_type: UserTxType::Exchange,
+                },
+            ) => SendExchange,
+            (
+                SendExchange,
+                ContestState::StationTransmitting {
+                    tx_type: StationTxType::SendingExchange,
+                },
+            ) => CopyExchange,
+            (CopyExchange, _) if seen.exchange_entered => LogQso,
+            (LogQso, _) if !seen.exchange_entered => CopyExchange,
+            (step, _) => step,
+        };
+    }
+}
+
+/// The user's settings with a single slow, strong, well-behaved caller who
+/// keeps calling until worked
+fn simulation(user: &SimulationSettings) -> SimulationSettings {
+    let mut reaction_time = user.reaction_time.clone();
+    reaction_time.tailgate_probability = 0.0;
+    reaction_time.late_probability = 0.0;
+    SimulationSettings {
+        max_simultaneous_stations: 1,
+        station_probability: 1.0,
+        wpm_min: TUTORIAL_WPM,
+        wpm_max: TUTORIAL_WPM,
+        amplitude_min: 1.0,
+        amplitude_max: 1.0,
+        agn_request_probability: 0.0,
+        fill_request_probability: 0.0,
+        call_twice_probability: 0.0,
+        double_probability: 0.0,
+        nil_probability: 0.0,
+        cut_number_probability: 0.0,
+        courtesy_probability: 0.0,
+        weak_character_focus: 0.0,
+        pileup: PileupSettings {
+            min_patience: 10,
+            max_patience: 10,
+            ..user.pileup.clone()
+        },
+        reaction_time,
+        lids: LidSettings::default(),
+        wide_pileup: WidePileupSettings::default(),
+        frequency_fight: FrequencyFightSettings::default(),
+        dynamic_density: DynamicDensitySettings::default(),
+        ambience: AmbienceSettings {
+            events_per_minute: 0.0,
+            ..user.ambience.clone()
+        },
+        ..user.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seen(state: ContestState, exchange_entered: bool, qsos: u32) -> Progress {
+        Progress {
+            state,
+            exchange_entered,
+            qsos,
+        }
+    }
+
+    #[test]
+    fn steps_follow_the_qso() {
+        let mut tutorial = Tutorial::new(&SimulationSettings::default(), 3);
+        assert_eq!(tutorial.simulation().max_simultaneous_stations, 1);
+
+        tutorial.observe(&seen(ContestState::CallingCq, false, 3));
+        assert_eq!(tutorial.step(), TutorialStep::CallCq);
+        tutorial.observe(&seen(ContestState::StationsCalling, false, 3));
+        assert_eq!(tutorial.step(), TutorialStep::CopyCall);
+        let sending = ContestState::UserTransmitting {
+            tx_type: UserTxType::Exchange,
+        };
+        tutorial.observe(&seen(sending, false, 3));
+        assert_eq!(tutorial.step(), TutorialStep::SendExchange);
+        let receiving = ContestState::StationTransmitting {
+            tx_type: StationTxType::SendingExchange,
+        };
+        tutorial.observe(&seen(receiving, false, 3));
+        assert_eq!(tutorial.step(), TutorialStep::CopyExchange);
+        tutorial.observe(&seen(receiving, true, 3));
+        assert_eq!(tutorial.step(), TutorialStep::LogQso);
+        // Clearing a box goes back a step
+        tutorial.observe(&seen(ContestState::StationsCalling, false, 3));
+        assert_eq!(tutorial.step(), TutorialStep::CopyExchange);
+        tutorial.observe(&seen(ContestState::QsoComplete, true, 4));
+        assert_eq!(tutorial.step(), TutorialStep::Done);
+        // Done stays done until another round is asked for
+        tutorial.observe(&seen(ContestState::Idle, false, 4));
+        assert_eq!(tutorial.step(), TutorialStep::Done);
+
+        tutorial.again(4);
+        tutorial.observe(&seen(ContestState::StationsCalling, false, 4));
+        assert_eq!(tutorial.step(), TutorialStep::CopyCall);
+        // Stopping starts over
+        tutorial.observe(&seen(ContestState::Idle, false, 4));
+        assert_eq!(tutorial.step(), TutorialStep::CallCq);
+    }
+}
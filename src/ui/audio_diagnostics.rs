@@ -0,0 +1,95 @@
+use crate::audio::backend::AudioTelemetry;
+use crate::i18n::tr;
+use crate::ui::Palette;
+use egui::RichText;
+
+/// Callback load above this share of the buffer time is shown as a warning
+const LOAD_WARNING: f32 = 0.7;
+
+/// What the audio queues hold and drop, as seen from the app
+pub struct AudioQueueStats {
+    pub queued_commands: usize,
+    pub dropped_commands: u32,
+}
+
+/// How the audio is keeping up: callback timing and underruns from the
+/// sound card, and the command queue between the app and the audio
+pub fn render_audio_diagnostics(
+    ctx: &egui::Context,
+    telemetry: Option<AudioTelemetry>,
+    queues: AudioQueueStats,
+    palette: &Palette,
+    show_diagnostics: &mut bool,
+) {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("audio_diagnostics_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Audio Diagnostics"))
+            .with_inner_size([320.0, 240.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let count = |ui: &mut egui::Ui, value: u32| {
+                    let text = RichText::new(format!("{}", value));
+                    ui.label(if value > 0 {
+                        text.color(palette.bad)
+                    } else {
+                        text
+                    });
+                };
+                egui::Grid::new("audio_diagnostics_grid")
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .show(ui, |ui| {
+                        if let Some(telemetry) = telemetry {
+                            ui.label(tr("Callbacks:"));
+                            ui.label(format!("{}", telemetry.callbacks));
+                            ui.end_row();
+
+                            ui.label(tr("Callback load:"))
+                                .on_hover_text(tr(
+                                    "Time spent filling each buffer, as a share of the time it plays; last and highest",
+                                ));
+                            let text = RichText::new(format!(
+                                "{:.0}% ({:.0}%)",
+                                telemetry.load * 100.0,
+                                telemetry.peak_load * 100.0
+                            ));
+                            ui.label(if telemetry.peak_load > LOAD_WARNING {
+                                text.color(palette.bad)
+                            } else {
+                                text
+                            });
+                            ui.end_row();
+
+                            ui.label(tr("Underruns:"));
+                            count(ui, telemetry.underruns);
+                            ui.end_row();
+
+                            ui.label(tr("Delayed events:"));
+                            count(ui, telemetry.delayed_events);
+                            ui.end_row();
+                        }
+
+                        ui.label(tr("Queued commands:"));
+                        ui.label(format!("{}", queues.queued_commands));
+                        ui.end_row();
+
+                        ui.label(tr("Dropped commands:"));
+                        count(ui, queues.dropped_commands);
+                        ui.end_row();
+                    });
+                if telemetry.is_none() {
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(tr("No sound card open, so there is no callback to time"))
+                            .weak(),
+                    );
+                }
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_diagnostics = false;
+            }
+        },
+    );
+}
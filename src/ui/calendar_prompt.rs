@@ -0,0 +1,45 @@
+use crate::calendar::UpcomingContest;
+use crate::i18n::{tr, tr_args};
+
+/// User's answer to the upcoming contest prompt
+pub enum CalendarPromptResponse {
+    Practice,
+    Dismiss,
+    DisableSuggestions,
+}
+
+pub fn render_calendar_prompt(
+    ctx: &egui::Context,
+    suggestion: &UpcomingContest,
+) -> Option<CalendarPromptResponse> {
+    let mut response = None;
+    let message = suggestion.describe(chrono::Utc::now());
+
+    egui::Window::new(tr("Upcoming Contest"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.label(tr_args("{} \u{2014} practice now?", &[&message]));
+            ui.add_space(12.0);
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button(tr_args("Practice {}", &[suggestion.name]))
+                    .clicked()
+                {
+                    response = Some(CalendarPromptResponse::Practice);
+                }
+                if ui.button(tr("Not now")).clicked() {
+                    response = Some(CalendarPromptResponse::Dismiss);
+                }
+                if ui.button(tr("Don't ask again")).clicked() {
+                    response = Some(CalendarPromptResponse::DisableSuggestions);
+                }
+            });
+            ui.add_space(4.0);
+        });
+
+    response
+}
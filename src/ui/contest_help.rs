@@ -0,0 +1,24 @@
+use crate::contest::ContestHelp;
+use crate::i18n::{tr, tr_args};
+use egui::RichText;
+
+/// A contest's rules summary, an exchange as heard on the air and the
+/// speeds to expect
+pub fn render_contest_help(ui: &mut egui::Ui, help: &ContestHelp) {
+    if !help.rules.is_empty() {
+        ui.label(help.rules);
+        ui.add_space(4.0);
+    }
+    if !help.example_exchange.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(RichText::new(tr("Example exchange:")).strong());
+            ui.label(RichText::new(help.example_exchange).monospace());
+        });
+    }
+    if let Some((low, high)) = help.typical_wpm {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(tr("Typical speed:")).strong());
+            ui.label(tr_args("{}-{} WPM", &[&low.to_string(), &high.to_string()]));
+        });
+    }
+}
@@ -0,0 +1,617 @@
+use crate::config::{AudioDrillKind, AudioDrillSettings, DrillSettings, KochSettings};
+use crate::drill::call_twice::{CallTwiceStage, CallTwiceStats};
+use crate::drill::{abbreviations, koch, DrillKind, DrillSession};
+use crate::i18n::{tr, tr_args};
+use egui::{Color32, Key, RichText};
+use egui_file_dialog::FileDialog;
+use std::path::PathBuf;
+
+/// Actions the drill window asks the app to perform
+pub enum DrillWindowAction {
+    Play,
+    Replay,
+    Submit,
+    /// Render the audio drill recordings to files
+    ExportAudio,
+    /// Load the recordings in a folder for the Recordings drill
+    LoadRecordings(PathBuf),
+}
+
+pub fn render_drill_window(
+    ctx: &egui::Context,
+    drill: &mut DrillSession,
+    settings: &mut DrillSettings,
+    settings_changed: &mut bool,
+    can_play: bool,
+    folder_dialog: &mut FileDialog,
+    show_drill: &mut bool,
+) -> Option<DrillWindowAction> {
+    let mut action = None;
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("drill_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Copy Drills"))
+            .with_inner_size([450.0, 450.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Drill:"));
+                        egui::ComboBox::from_id_salt("drill_kind")
+                            .selected_text(tr(drill.kind.label()))
+                            .show_ui(ui, |ui| {
+                                for kind in DrillKind::ALL {
+                                    if ui
+                                        .selectable_value(&mut drill.kind, *kind, tr(kind.label()))
+                                        .changed()
+                                    {
+                                        drill.current = None;
+                                        drill.copy_input.clear();
+                                    }
+                                }
+                            });
+                    });
+                    ui.label(RichText::new(tr(drill.kind.description())).small().italics());
+
+                    ui.add_space(4.0);
+
+                    if drill.kind == DrillKind::Recordings {
+                        render_recordings(ui, drill, folder_dialog);
+                    } else if drill.kind == DrillKind::Koch {
+                        render_koch(ui, drill, &mut settings.koch, settings_changed);
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(tr("WPM Range:"));
+                            let mut changed = false;
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut settings.wpm_min).range(10..=50))
+                                .changed();
+                            ui.label("-");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut settings.wpm_max).range(10..=50))
+                                .changed();
+                            if changed {
+                                // Ensure min <= max
+                                if settings.wpm_min > settings.wpm_max {
+                                    settings.wpm_max = settings.wpm_min;
+                                }
+                                *settings_changed = true;
+                            }
+                        });
+                    }
+
+                    if drill.kind == DrillKind::SerialNumbers {
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Cut Number Probability:"));
+                            if ui
+                                .add(
+                                    egui::Slider::new(
+                                        &mut settings.cut_number_probability,
+                                        0.0..=1.0,
+                                    )
+                                    .fixed_decimals(2),
+                                )
+                                .on_hover_text(tr("Chance each 0, 9 or 1 is sent as T, N or A"))
+                                .changed()
+                            {
+                                *settings_changed = true;
+                            }
+                        });
+                    }
+
+                    if drill.kind == DrillKind::Abbreviations
+                        && ui
+                            .checkbox(&mut settings.abbreviation_choices, tr("Multiple Choice"))
+                            .on_hover_text(
+                                tr("Send one abbreviation and pick it from four, instead of copying a run of them"),
+                            )
+                            .changed()
+                    {
+                        *settings_changed = true;
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(8.0);
+
+                    let has_items =
+                        drill.kind != DrillKind::Recordings || drill.recordings.is_some();
+                    ui.horizontal(|ui| {
+                        let disabled_reason = if has_items {
+                            "Finish or wipe the current contest QSO first"
+                        } else {
+                            "Load a recordings folder first"
+                        };
+                        if ui
+                            .add_enabled(can_play && has_items, egui::Button::new(tr("Play Next")))
+                            .on_disabled_hover_text(tr(disabled_reason))
+                            .clicked()
+                        {
+                            action = Some(DrillWindowAction::Play);
+                        }
+                        if ui
+                            .add_enabled(
+                                can_play && drill.current.is_some(),
+                                egui::Button::new(tr("Replay")),
+                            )
+                            .clicked()
+                        {
+                            action = Some(DrillWindowAction::Replay);
+                        }
+                        if let Some(prompt) = &drill.current {
+                            if prompt.recording.is_some() {
+                                ui.label(&prompt.text);
+                            } else {
+                                ui.label(tr_args("{} WPM", &[&prompt.wpm.to_string()]));
+                            }
+                        }
+                    });
+
+                    ui.add_space(4.0);
+
+                    let (copy_label, hint) = match drill.call_twice.as_ref().map(|r| r.stage) {
+                        Some(CallTwiceStage::Compare) => (
+                            "Same or different?",
+                            "Type S (same) or D (different), then press Enter",
+                        ),
+                        Some(CallTwiceStage::Copy { .. }) => {
+                            ("Call:", "Type the call as repeated, then press Enter")
+                        }
+                        None if drill.current.as_ref().is_some_and(|p| !p.choices.is_empty()) => (
+                            "Which was sent?",
+                            "Type its number or click it, then press Enter",
+                        ),
+                        None => ("Copy:", "Type what you heard, then press Enter"),
+                    };
+                    ui.label(tr(copy_label));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut drill.copy_input)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .hint_text(tr(hint)),
+                    );
+                    if response.lost_focus()
+                        && ui.input(|i| i.key_pressed(Key::Enter))
+                        && drill.current.is_some()
+                    {
+                        action = Some(DrillWindowAction::Submit);
+                    }
+                    if let Some(prompt) = &drill.current {
+                        let mut picked = None;
+                        ui.horizontal(|ui| {
+                            for (i, choice) in prompt.choices.iter().enumerate() {
+                                let label = RichText::new(format!("{} {}", i + 1, choice)).monospace();
+                                if ui.button(label).clicked() {
+                                    picked = Some(choice.clone());
+                                }
+                            }
+                        });
+                        if let Some(choice) = picked {
+                            drill.copy_input = choice;
+                            action = Some(DrillWindowAction::Submit);
+                        }
+                    }
+
+                    ui.add_space(8.0);
+
+                    if let Some(last) = drill.attempts.last().filter(|a| a.kind == drill.kind) {
+                        let color = if last.is_perfect() {
+                            Color32::GREEN
+                        } else {
+                            Color32::YELLOW
+                        };
+                        egui::Grid::new("drill_last_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label(tr("Sent:"));
+                                ui.label(RichText::new(&last.sent).monospace());
+                                ui.end_row();
+
+                                if last.expected != last.sent {
+                                    ui.label(tr("Answer:"));
+                                    ui.label(RichText::new(&last.expected).monospace());
+                                    ui.end_row();
+                                }
+
+                                ui.label(tr("Copied:"));
+                                ui.label(RichText::new(&last.copied).monospace().color(color));
+                                ui.end_row();
+
+                                if last.wpm > 0 {
+                                    ui.label(tr("Speed:"));
+                                    ui.label(tr_args("{} WPM", &[&last.wpm.to_string()]));
+                                    ui.end_row();
+                                }
+
+                                ui.label(format!("{}:", tr(drill.kind.unit_label())));
+                                ui.label(format!(
+                                    "{}/{} ({:.1}%)",
+                                    last.units_correct,
+                                    last.units_total,
+                                    last.accuracy_pct()
+                                ));
+                                ui.end_row();
+
+                                if last.kind == DrillKind::Abbreviations {
+                                    ui.label(tr("Meaning:"));
+                                    ui.vertical(|ui| {
+                                        for (word, _) in &last.word_results {
+                                            if let Some(meaning) = abbreviations::meaning(word) {
+                                                ui.label(format!("{} = {}", word, meaning));
+                                            }
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(8.0);
+
+                    let summary = drill.summary(drill.kind);
+                    egui::Grid::new("drill_summary_grid")
+                        .num_columns(2)
+                        .spacing([40.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label(tr("Attempts:"));
+                            ui.label(format!("{}", summary.attempts));
+                            ui.end_row();
+
+                            ui.label(tr("Perfect Copies:"));
+                            ui.label(format!("{}", summary.perfect));
+                            ui.end_row();
+
+                            ui.label(tr_args("{} Accuracy:", &[tr(drill.kind.unit_label())]));
+                            ui.label(format!(
+                                "{}/{} ({:.1}%)",
+                                summary.units_correct,
+                                summary.units_total,
+                                summary.accuracy_pct()
+                            ));
+                            ui.end_row();
+
+                            if drill.kind == DrillKind::CallTwice {
+                                render_call_twice_stats(ui, &drill.call_twice_stats());
+                            }
+
+                            if summary.cut_total > 0 {
+                                ui.label(tr("Cut Digits:"));
+                                ui.label(format!(
+                                    "{}/{} ({:.1}%)",
+                                    summary.cut_correct,
+                                    summary.cut_total,
+                                    summary.cut_correct as f32 / summary.cut_total as f32 * 100.0
+                                ));
+                                ui.end_row();
+                            }
+                        });
+
+                    let char_error_rates = drill.char_error_rates(drill.kind);
+                    if !char_error_rates.is_empty() {
+                        ui.add_space(8.0);
+                        egui::Grid::new("drill_char_error_grid")
+                            .num_columns(3)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                let unit = if drill.kind == DrillKind::Koch {
+                                    "Character"
+                                } else {
+                                    "Digit"
+                                };
+                                ui.label(RichText::new(tr(unit)).strong());
+                                ui.label(RichText::new(tr("Error Rate")).strong());
+                                ui.label(RichText::new(tr("Samples")).strong());
+                                ui.end_row();
+
+                                for (ch, error_rate, count) in &char_error_rates {
+                                    ui.label(RichText::new(ch.to_string()).monospace());
+                                    ui.label(format!("{:.1}%", error_rate));
+                                    ui.label(format!("{}", count));
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
+                    let word_error_rates = drill.word_error_rates(drill.kind);
+                    if !word_error_rates.is_empty() {
+                        ui.add_space(8.0);
+                        egui::Grid::new("drill_word_error_grid")
+                            .num_columns(3)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label(RichText::new(tr("Abbreviation")).strong());
+                                ui.label(RichText::new(tr("Error Rate")).strong());
+                                ui.label(RichText::new(tr("Samples")).strong());
+                                ui.end_row();
+
+                                for (word, error_rate, count) in &word_error_rates {
+                                    ui.label(RichText::new(word).monospace());
+                                    ui.label(format!("{:.1}%", error_rate));
+                                    ui.label(format!("{}", count));
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
+                    ui.add_space(8.0);
+                    if ui.button(tr("Reset Drill Stats")).clicked() {
+                        drill.clear();
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    if render_audio_export(ui, drill, &mut settings.audio_export, settings_changed)
+                    {
+                        action = Some(DrillWindowAction::ExportAudio);
+                    }
+                });
+            });
+
+            folder_dialog.update(ctx);
+            if let Some(path) = folder_dialog.take_picked() {
+                action = Some(DrillWindowAction::LoadRecordings(path));
+            }
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_drill = false;
+            }
+        },
+    );
+
+    action
+}
+
+/// Call Twice totals, as rows of the summary grid
+fn render_call_twice_stats(ui: &mut egui::Ui, stats: &CallTwiceStats) {
+    let pct = |n: usize| {
+        if stats.rounds == 0 {
+            0.0
+        } else {
+            n as f32 / stats.rounds as f32 * 100.0
+        }
+    };
+    ui.label(tr("Same/Different Right:"));
+    ui.label(format!(
+        "{}/{} ({:.1}%)",
+        stats.compare_correct,
+        stats.rounds,
+        pct(stats.compare_correct)
+    ));
+    ui.end_row();
+
+    ui.label(tr("Calls Right:"));
+    ui.label(format!(
+        "{}/{} ({:.1}%)",
+        stats.calls_correct,
+        stats.rounds,
+        pct(stats.calls_correct)
+    ));
+    ui.end_row();
+
+    ui.label(tr("Busts Missed:"))
+        .on_hover_text(tr("Different calls you took for the same one"));
+    ui.label(format!("{}", stats.busts_missed));
+    ui.end_row();
+
+    ui.label(tr("False Alarms:"))
+        .on_hover_text(tr("The same call twice that you took for different ones"));
+    ui.label(format!("{}", stats.false_alarms));
+    ui.end_row();
+}
+
+/// Koch course speeds, the lesson being practiced and progress so far
+fn render_koch(
+    ui: &mut egui::Ui,
+    drill: &mut DrillSession,
+    settings: &mut KochSettings,
+    settings_changed: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Character WPM:"));
+        let mut changed = ui
+            .add(egui::DragValue::new(&mut settings.char_wpm).range(koch::MIN_CHAR_WPM..=50))
+            .on_hover_text(tr(
+                "Each character is sent this fast, so it is learned by its sound",
+            ))
+            .changed();
+        ui.label(tr("Effective WPM:"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.effective_wpm).range(5..=50))
+            .on_hover_text(tr(
+                "Overall speed; the gaps between characters are stretched to match",
+            ))
+            .changed();
+        if changed {
+            settings.effective_wpm = settings.effective_wpm.min(settings.char_wpm);
+            *settings_changed = true;
+        }
+    });
+
+    let unlocked = drill.koch.unlocked().max(drill.koch.lesson);
+    ui.horizontal(|ui| {
+        ui.label(tr("Lesson:"));
+        egui::ComboBox::from_id_salt("koch_lesson")
+            .selected_text(format!("{}", drill.koch.lesson))
+            .show_ui(ui, |ui| {
+                for lesson in 1..=unlocked {
+                    let label = format!("{} ({})", lesson, koch::new_char(lesson));
+                    if ui
+                        .selectable_value(&mut drill.koch.lesson, lesson, label)
+                        .changed()
+                    {
+                        drill.current = None;
+                        drill.copy_input.clear();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(tr("Lessons open as each one before is mastered"));
+        let chars = koch::lesson_chars(drill.koch.lesson);
+        let (known, newest) = chars.split_at(chars.len() - 1);
+        ui.label(RichText::new(known).monospace());
+        ui.label(RichText::new(newest).monospace().strong().underline())
+            .on_hover_text(tr("New in this lesson"));
+    });
+
+    egui::CollapsingHeader::new(tr("Course Progress"))
+        .id_salt("koch_progress")
+        .default_open(false)
+        .show(ui, |ui| {
+            if drill.koch.lessons.is_empty() {
+                ui.label(RichText::new(tr("No lessons practiced yet")).weak());
+                return;
+            }
+            egui::Grid::new("koch_progress_grid")
+                .num_columns(5)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    for header in ["Lesson", "New", "Items", "Accuracy", "Best"] {
+                        ui.label(RichText::new(tr(header)).strong());
+                    }
+                    ui.end_row();
+                    for (lesson, record) in &drill.koch.lessons {
+                        ui.label(format!("{}", lesson));
+                        ui.label(RichText::new(koch::new_char(*lesson).to_string()).monospace());
+                        ui.label(format!("{}", record.attempts));
+                        ui.label(format!("{:.1}%", record.accuracy_pct()));
+                        let best = RichText::new(format!("{:.0}%", record.best_pct));
+                        if record.mastered() {
+                            ui.label(best.color(Color32::GREEN))
+                                .on_hover_text(tr("Mastered"));
+                        } else {
+                            ui.label(best);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// The loaded recordings folder and the button to pick another
+fn render_recordings(ui: &mut egui::Ui, drill: &DrillSession, folder_dialog: &mut FileDialog) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Folder:"));
+        match &drill.recordings {
+            Some(set) => {
+                ui.label(RichText::new(set.folder.display().to_string()).monospace());
+            }
+            None => {
+                ui.label(RichText::new(tr("None loaded")).weak());
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        if ui
+            .button(tr("Load Recordings..."))
+            .on_hover_text(
+                tr("Pick a folder of WAV files with an answer-key.txt, laid out like an exported audio drill"),
+            )
+            .clicked()
+        {
+            folder_dialog.pick_directory();
+        }
+        if let Some(set) = &drill.recordings {
+            ui.label(tr_args("{} recordings", &[&set.clips.len().to_string()]));
+        }
+    });
+    if let Some(status) = &drill.recordings_status {
+        ui.label(RichText::new(status).small());
+    }
+}
+
+/// Settings for offline recordings; returns true when Export is clicked
+fn render_audio_export(
+    ui: &mut egui::Ui,
+    drill: &DrillSession,
+    settings: &mut AudioDrillSettings,
+    settings_changed: &mut bool,
+) -> bool {
+    let mut export = false;
+    egui::CollapsingHeader::new(RichText::new(tr("Export Audio Drill")).strong())
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new(
+                    tr("Save recordings as WAV files with an answer key, for practice away from the computer"),
+                )
+                .small()
+                .italics(),
+            );
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Recordings:"));
+                for (kind, label) in [
+                    (AudioDrillKind::Pileup, "Pileups"),
+                    (AudioDrillKind::Exchange, "Exchanges"),
+                ] {
+                    if ui.selectable_value(&mut settings.kind, kind, tr(label)).changed() {
+                        *settings_changed = true;
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Count:"));
+                if ui
+                    .add(egui::DragValue::new(&mut settings.count).range(1..=100))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("WPM Range:"));
+                let mut changed = false;
+                changed |= ui
+                    .add(egui::DragValue::new(&mut settings.wpm_min).range(10..=50))
+                    .changed();
+                ui.label("-");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut settings.wpm_max).range(10..=50))
+                    .changed();
+                if changed {
+                    if settings.wpm_min > settings.wpm_max {
+                        settings.wpm_max = settings.wpm_min;
+                    }
+                    *settings_changed = true;
+                }
+            });
+
+            if settings.kind == AudioDrillKind::Pileup {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Max Callers:"));
+                    if ui
+                        .add(egui::DragValue::new(&mut settings.max_callers).range(1..=8))
+                        .on_hover_text(tr("Each recording has between one and this many callers"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Noise Level:"));
+                if ui
+                    .add(egui::Slider::new(&mut settings.noise_level, 0.0..=1.0).fixed_decimals(2))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+            });
+
+            ui.add_space(4.0);
+            if ui.button(tr("Export")).clicked() {
+                export = true;
+            }
+            if let Some(status) = &drill.export_status {
+                ui.label(RichText::new(status).small());
+            }
+        });
+    export
+}
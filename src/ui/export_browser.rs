@@ -0,0 +1,264 @@
+use crate::export::ExportFormat;
+use crate::i18n::{tr, tr_args};
+use crate::past_exports::{accuracy_changes, parse_markdown, scan, Block, PastExport};
+use crate::ui::Palette;
+use egui::RichText;
+use std::path::Path;
+
+/// Reports found in the export directory and the one being read
+#[derive(Default)]
+pub struct ExportBrowser {
+    pub exports: Vec<PastExport>,
+    /// Oldest first instead of newest first
+    pub oldest_first: bool,
+    /// Index into `exports` of the report shown, and its text
+    pub open: Option<(usize, Result<String, String>)>,
+    /// Why the export directory couldn't be read
+    pub error: Option<String>,
+}
+
+impl ExportBrowser {
+    /// Look through the export directory again
+    pub fn refresh(&mut self, dir: &Path) {
+        self.open = None;
+        match scan(dir) {
+            Ok(exports) => {
+                self.exports = exports;
+                self.error = None;
+            }
+            Err(e) => {
+                self.exports.clear();
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn open(&mut self, index: usize) {
+        let content = std::fs::read_to_string(&self.exports[index].path).map_err(|e| e.to_string());
+        self.open = Some((index, content));
+    }
+}
+
+/// Exported session reports, with how each session's accuracy compares to
+/// the one before, and the selected report shown below
+pub fn render_export_browser(
+    ctx: &egui::Context,
+    browser: &mut ExportBrowser,
+    dir: &Path,
+    palette: &Palette,
+    show_exports: &mut bool,
+) {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("export_browser_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Past Exports"))
+            .with_inner_size([620.0, 640.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Refresh")).clicked() {
+                        browser.refresh(dir);
+                    }
+                    let order = if browser.oldest_first {
+                        tr("Oldest first")
+                    } else {
+                        tr("Newest first")
+                    };
+                    if ui.button(order).clicked() {
+                        browser.oldest_first = !browser.oldest_first;
+                    }
+                    ui.label(RichText::new(dir.display().to_string()).weak());
+                });
+                ui.add_space(4.0);
+
+                if let Some(error) = &browser.error {
+                    ui.label(
+                        RichText::new(tr_args("Couldn't read the export directory: {}", &[error]))
+                            .color(palette.bad),
+                    );
+                } else if browser.exports.is_empty() {
+                    ui.label(tr(
+                        "No exported reports yet. Export Markdown or JSON from Session Stats.",
+                    ));
+                } else {
+                    let mut clicked = None;
+                    egui::ScrollArea::vertical()
+                        .id_salt("export_list")
+                        .max_height(220.0)
+                        .show(ui, |ui| {
+                            clicked = render_export_list(ui, browser, palette);
+                        });
+                    if let Some(index) = clicked {
+                        browser.open(index);
+                    }
+                }
+
+                if let Some((index, content)) = &browser.open {
+                    ui.add_space(4.0);
+                    ui.separator();
+                    let export = &browser.exports[*index];
+                    egui::ScrollArea::vertical()
+                        .id_salt("export_report")
+                        .show(ui, |ui| match content {
+                            Err(e) => {
+                                ui.label(RichText::new(e).color(palette.bad));
+                            }
+                            Ok(text) if export.format == ExportFormat::Json => {
+                                render_json_report(ui, export, text);
+                            }
+                            Ok(text) => render_markdown(ui, text),
+                        });
+                }
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_exports = false;
+            }
+        },
+    );
+}
+
+/// One row per report; returns the one clicked
+fn render_export_list(
+    ui: &mut egui::Ui,
+    browser: &ExportBrowser,
+    palette: &Palette,
+) -> Option<usize> {
+    let mut clicked = None;
+    let changes = accuracy_changes(&browser.exports);
+    let mut order: Vec<usize> = (0..browser.exports.len()).collect();
+    if browser.oldest_first {
+        order.reverse();
+    }
+    let open = browser.open.as_ref().map(|(index, _)| *index);
+
+    egui::Grid::new("export_list_grid")
+        .num_columns(7)
+        .striped(true)
+        .spacing([12.0, 4.0])
+        .show(ui, |ui| {
+            ui.label(RichText::new(tr("Exported")).strong());
+            ui.label(RichText::new(tr("Callsign")).strong());
+            ui.label(RichText::new(tr("Format")).strong());
+            ui.label(RichText::new(tr("QSOs")).strong());
+            ui.label(RichText::new(tr("Correct")).strong());
+            ui.label(RichText::new(tr("Change")).strong())
+                .on_hover_text(tr("Correct QSOs compared with the export before"));
+            ui.label(RichText::new(tr("Call / Exch")).strong());
+            ui.end_row();
+
+            for index in order {
+                let export = &browser.exports[index];
+                let date = export.exported.format("%Y-%m-%d %H:%M").to_string();
+                if ui.selectable_label(open == Some(index), date).clicked() {
+                    clicked = Some(index);
+                }
+                ui.label(RichText::new(&export.callsign).monospace());
+                ui.label(export.format.label());
+                match &export.summary {
+                    Some(summary) => {
+                        ui.label(format!("{}", summary.total_qsos));
+                        ui.label(format!("{:.1}%", summary.correct_rate));
+                        match changes[index] {
+                            Some(change) => {
+                                let color = if change >= 0.0 {
+                                    palette.good
+                                } else {
+                                    palette.bad
+                                };
+                                ui.label(RichText::new(format!("{:+.1}", change)).color(color));
+                            }
+                            None => {
+                                ui.label("-");
+                            }
+                        }
+                        ui.label(format!(
+                            "{:.1}% / {:.1}%",
+                            summary.callsign_accuracy, summary.exchange_accuracy
+                        ));
+                    }
+                    None => {
+                        ui.label(RichText::new(tr("unreadable")).weak());
+                        ui.label("");
+                        ui.label("");
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    clicked
+}
+
+/// A Markdown report, in the few forms the exports use
+fn render_markdown(ui: &mut egui::Ui, text: &str) {
+    for (i, block) in parse_markdown(text).into_iter().enumerate() {
+        match block {
+            Block::Heading { level: 1, text } => {
+                ui.heading(text);
+            }
+            Block::Heading { text, .. } => {
+                ui.add_space(6.0);
+                ui.label(RichText::new(text).strong().size(16.0));
+            }
+            Block::Bullet(text) => {
+                ui.label(format!("• {}", text));
+            }
+            Block::Text(text) => {
+                ui.label(text);
+            }
+            Block::Table(rows) => {
+                let columns = rows.first().map_or(0, |row| row.len());
+                egui::Grid::new(("export_report_table", i))
+                    .num_columns(columns)
+                    .striped(true)
+                    .spacing([12.0, 2.0])
+                    .show(ui, |ui| {
+                        for (row_index, row) in rows.iter().enumerate() {
+                            for cell in row {
+                                let cell = RichText::new(cell);
+                                ui.label(if row_index == 0 {
+                                    cell.strong()
+                                } else {
+                                    cell.monospace()
+                                });
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+        }
+    }
+}
+
+/// A JSON export's headline numbers, with the file itself below
+fn render_json_report(ui: &mut egui::Ui, export: &PastExport, text: &str) {
+    if let Some(summary) = &export.summary {
+        egui::Grid::new("export_json_summary")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(tr("Total QSOs:"));
+                ui.label(format!("{}", summary.total_qsos));
+                ui.end_row();
+                ui.label(tr("Correct QSOs:"));
+                ui.label(format!("{:.1}%", summary.correct_rate));
+                ui.end_row();
+                ui.label(tr("Callsign Accuracy:"));
+                ui.label(format!("{:.1}%", summary.callsign_accuracy));
+                ui.end_row();
+                ui.label(tr("Exchange Accuracy:"));
+                ui.label(format!("{:.1}%", summary.exchange_accuracy));
+                ui.end_row();
+                ui.label(tr("Total Points:"));
+                ui.label(format!("{}", summary.total_points));
+                ui.end_row();
+            });
+        ui.add_space(8.0);
+    }
+    egui::CollapsingHeader::new("JSON")
+        .default_open(export.summary.is_none())
+        .show(ui, |ui| {
+            ui.label(RichText::new(text).monospace().small());
+        });
+}
@@ -0,0 +1,93 @@
+// Font selection: the UI font plus a separate font for the entry boxes
+
+use crate::config::{FontChoice, UserSettings};
+use egui::{FontData, FontDefinitions, FontFamily, FontId};
+use std::sync::Arc;
+
+/// Bundled so the entry boxes have a clear monospace font on every system
+const DEJAVU_SANS_MONO: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// Font family used for the call/exchange boxes and the status line
+const ENTRY_FAMILY: &str = "entry";
+
+fn font_key(choice: FontChoice) -> &'static str {
+    match choice {
+        FontChoice::Ubuntu => "Ubuntu-Light",
+        FontChoice::Hack => "Hack",
+        FontChoice::DejaVuSansMono => "DejaVuSansMono",
+    }
+}
+
+pub fn font_name(choice: FontChoice) -> &'static str {
+    match choice {
+        FontChoice::Ubuntu => "Ubuntu Light",
+        FontChoice::Hack => "Hack",
+        FontChoice::DejaVuSansMono => "DejaVu Sans Mono",
+    }
+}
+
+/// Font for the call/exchange boxes at the given size
+pub fn entry_font(size: f32) -> FontId {
+    FontId::new(size, FontFamily::Name(ENTRY_FAMILY.into()))
+}
+
+/// Move a font to the front of a family, keeping the others as fallbacks
+/// for glyphs it doesn't have
+fn put_first(family: &mut Vec<String>, key: &str) {
+    family.retain(|name| name != key);
+    family.insert(0, key.to_string());
+}
+
+fn font_definitions(ui_font: FontChoice, entry: FontChoice) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert(
+        font_key(FontChoice::DejaVuSansMono).to_string(),
+        Arc::new(FontData::from_static(DEJAVU_SANS_MONO)),
+    );
+
+    if let Some(proportional) = fonts.families.get_mut(&FontFamily::Proportional) {
+        put_first(proportional, font_key(ui_font));
+    }
+
+    let mut entry_family = fonts
+        .families
+        .get(&FontFamily::Monospace)
+        .cloned()
+        .unwrap_or_default();
+    put_first(&mut entry_family, font_key(entry));
+    fonts
+        .families
+        .insert(FontFamily::Name(ENTRY_FAMILY.into()), entry_family);
+    fonts
+}
+
+/// Load the user's fonts into egui
+pub fn install_fonts(ctx: &egui::Context, user: &UserSettings) {
+    ctx.set_fonts(font_definitions(user.ui_font, user.entry_font));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chosen_fonts_come_first_with_fallbacks() {
+        let fonts = font_definitions(FontChoice::Hack, FontChoice::DejaVuSansMono);
+        let proportional = &fonts.families[&FontFamily::Proportional];
+        assert_eq!(proportional[0], "Hack");
+        assert!(proportional.contains(&"Ubuntu-Light".to_string()));
+
+        let entry = &fonts.families[&FontFamily::Name(ENTRY_FAMILY.into())];
+        assert_eq!(entry[0], "DejaVuSansMono");
+        assert_eq!(
+            entry
+                .iter()
+                .filter(|name| *name == "DejaVuSansMono")
+                .count(),
+            1
+        );
+        for name in proportional.iter().chain(entry.iter()) {
+            assert!(fonts.font_data.contains_key(name), "missing {}", name);
+        }
+    }
+}
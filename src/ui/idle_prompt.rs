@@ -0,0 +1,27 @@
+use crate::i18n::{tr, tr_args};
+
+/// Shown while the session is paused for lack of input; true once the
+/// user says they're back
+pub fn render_idle_prompt(ctx: &egui::Context, idle_minutes: u32) -> bool {
+    let mut resume = false;
+
+    egui::Window::new(tr("Are You Still There?"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.label(tr_args(
+                "Paused after {} minutes without input. Callers, timing and rate are on hold, so the time away doesn't count against your stats.",
+                &[&idle_minutes.to_string()],
+            ));
+            ui.add_space(12.0);
+
+            if ui.button(tr("I'm Back")).clicked() {
+                resume = true;
+            }
+            ui.add_space(4.0);
+        });
+
+    resume
+}
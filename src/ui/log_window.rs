@@ -0,0 +1,203 @@
+use crate::i18n::{tr, tr_args};
+use crate::stats::{LogQuery, LogSort, SessionStats};
+use crate::ui::Palette;
+use egui::RichText;
+
+/// QSOs on one page of the log
+const PAGE_SIZE: usize = 25;
+
+/// The log viewer's filters and the page being shown
+#[derive(Default)]
+pub struct LogView {
+    pub query: LogQuery,
+    pub page: usize,
+}
+
+/// The whole session log, searchable and sortable, a page at a time
+pub fn render_log_window(
+    ctx: &egui::Context,
+    stats: &SessionStats,
+    palette: &Palette,
+    view: &mut LogView,
+    show_log: &mut bool,
+) {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("log_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("QSO Log"))
+            .with_inner_size([560.0, 600.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                // A new search or filter starts from the first page
+                if render_filters(ui, &mut view.query) {
+                    view.page = 0;
+                }
+
+                let shown = stats.query_log(&view.query);
+                let pages = shown.len().div_ceil(PAGE_SIZE).max(1);
+                view.page = view.page.min(pages - 1);
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(view.page > 0, egui::Button::new("<"))
+                        .clicked()
+                    {
+                        view.page -= 1;
+                    }
+                    ui.label(tr_args(
+                        "Page {} of {}",
+                        &[&(view.page + 1).to_string(), &pages.to_string()],
+                    ));
+                    if ui
+                        .add_enabled(view.page + 1 < pages, egui::Button::new(">"))
+                        .clicked()
+                    {
+                        view.page += 1;
+                    }
+                    ui.add_space(12.0);
+                    ui.label(
+                        RichText::new(tr_args(
+                            "{} of {} QSOs",
+                            &[&shown.len().to_string(), &stats.qsos.len().to_string()],
+                        ))
+                        .weak(),
+                    );
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if stats.qsos.is_empty() {
+                        ui.label(tr("No QSOs logged yet"));
+                        return;
+                    }
+                    if shown.is_empty() {
+                        ui.label(tr("No QSOs match"));
+                        return;
+                    }
+                    let page = shown.iter().skip(view.page * PAGE_SIZE).take(PAGE_SIZE);
+                    egui::Grid::new("log_grid")
+                        .num_columns(8)
+                        .striped(true)
+                        .spacing([12.0, 4.0])
+                        .show(ui, |ui| {
+                            sort_header(ui, &mut view.query, LogSort::Number, "#");
+                            ui.label(RichText::new(tr("Time")).strong());
+                            sort_header(ui, &mut view.query, LogSort::Callsign, tr("Callsign"));
+                            ui.label(RichText::new(tr("Sent")).strong());
+                            ui.label(RichText::new(tr("Exchange")).strong());
+                            sort_header(ui, &mut view.query, LogSort::Wpm, tr("WPM"));
+                            ui.label(RichText::new(tr("AGN")).strong());
+                            sort_header(ui, &mut view.query, LogSort::Result, tr("Result"));
+                            ui.end_row();
+
+                            for &index in page {
+                                let qso = &stats.qsos[index];
+                                let number = RichText::new(format!("{}", index + 1));
+                                ui.label(if qso.excluded {
+                                    number.strikethrough()
+                                } else {
+                                    number
+                                });
+                                ui.label(qso.logged_time().unwrap_or_else(|| "-".to_string()));
+                                ui.label(
+                                    RichText::new(&qso.entered_callsign)
+                                        .monospace()
+                                        .color(palette.correct(qso.callsign_correct)),
+                                );
+                                ui.label(RichText::new(&qso.expected_callsign).monospace().weak());
+                                ui.label(
+                                    RichText::new(&qso.entered_exchange)
+                                        .monospace()
+                                        .color(palette.correct(qso.exchange_correct)),
+                                )
+                                .on_hover_text(tr_args("Sent: {}", &[&qso.expected_exchange]));
+                                ui.label(format!("{}", qso.station_wpm));
+                                if qso.used_agn() {
+                                    ui.label(RichText::new(tr("yes")).color(palette.warning));
+                                } else {
+                                    ui.label("-");
+                                }
+                                let (result_text, result_color) = if !qso.is_correct() {
+                                    ("ERR", palette.bad)
+                                } else if qso.used_agn() || qso.used_f5_callsign {
+                                    ("ok", palette.partial)
+                                } else {
+                                    ("OK", palette.good)
+                                };
+                                ui.label(RichText::new(result_text).color(result_color));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_log = false;
+            }
+        },
+    );
+}
+
+/// Search and filter controls; true when any of them changed
+fn render_filters(ui: &mut egui::Ui, query: &mut LogQuery) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(tr("Search:"));
+        changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut query.search)
+                    .desired_width(120.0)
+                    .hint_text(tr("callsign")),
+            )
+            .changed();
+        changed |= ui
+            .checkbox(&mut query.errors_only, tr("Errors only"))
+            .changed();
+        changed |= ui.checkbox(&mut query.agn_only, tr("AGN used")).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("WPM:"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut query.wpm_min).range(0..=99))
+            .changed();
+        ui.label("-");
+        changed |= ui
+            .add(egui::DragValue::new(&mut query.wpm_max).range(0..=99))
+            .changed();
+        if query.wpm_min > query.wpm_max {
+            query.wpm_max = query.wpm_min;
+        }
+        if ui.button(tr("Clear Filters")).clicked() {
+            *query = LogQuery {
+                sort: query.sort,
+                descending: query.descending,
+                ..Default::default()
+            };
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// A column heading that sorts on the column, or flips the order when it
+/// already does
+fn sort_header(ui: &mut egui::Ui, query: &mut LogQuery, sort: LogSort, label: &str) {
+    let selected = query.sort == sort;
+    let text = match (selected, query.descending) {
+        (true, true) => format!("{} v", label),
+        (true, false) => format!("{} ^", label),
+        (false, _) => label.to_string(),
+    };
+    if ui
+        .selectable_label(selected, RichText::new(text).strong())
+        .clicked()
+    {
+        if selected {
+            query.descending = !query.descending;
+        } else {
+            query.sort = sort;
+            query.descending = false;
+        }
+    }
+}
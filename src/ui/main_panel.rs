@@ -1,42 +1,85 @@
-use crate::app::{ContestApp, InputField, Score};
+use crate::app::ContestApp;
+use crate::audio::meter::SReading;
+use crate::config::{CallerSpeedReadout, UiLayout};
 use crate::contest::normalize_exchange_input;
-use crate::state::StatusColor;
-use egui::{Color32, RichText, Vec2};
+use crate::engine::{InputField, PrefillState, Score, TxRecord, RIT_MAX_HZ};
+use crate::i18n::{tr, tr_args};
+use crate::ui::{entry_font, render_contest_help, render_waterfall, Palette};
+use egui::{RichText, Vec2};
+use std::collections::VecDeque;
+use std::time::Instant;
 
 pub fn render_main_panel(ui: &mut egui::Ui, app: &mut ContestApp) {
+    match app.engine.settings.user.layout {
+        UiLayout::Compact => render_compact_panel(ui, app),
+        UiLayout::Normal | UiLayout::Expanded => render_full_panel(ui, app),
+    }
+}
+
+fn render_full_panel(ui: &mut egui::Ui, app: &mut ContestApp) {
+    let palette = Palette::for_user(&app.engine.settings.user);
+    // In the expanded layout the meters and QSO details are in the side panel
+    let expanded = app.engine.settings.user.layout == UiLayout::Expanded;
+
     // Contest type display
     ui.horizontal_top(|ui| {
-        ui.label(RichText::new("Contest:").strong());
-        ui.label(app.contest.display_name());
+        ui.label(RichText::new(tr("Contest:")).strong());
+        ui.label(app.engine.contest.display_name());
     });
 
     ui.add_space(4.0);
+    render_contest_intro(ui, app);
 
-    if let Some(notice) = app.settings_notice.clone() {
+    if let Some(notice) = app.engine.settings_notice.clone() {
         ui.horizontal(|ui| {
-            ui.label(RichText::new(notice).color(Color32::YELLOW));
-            if ui.button("Dismiss").clicked() {
-                app.settings_notice = None;
+            ui.label(RichText::new(notice).color(palette.warning));
+            if ui.button(tr("Dismiss")).clicked() {
+                app.engine.settings_notice = None;
             }
         });
         ui.add_space(4.0);
     }
 
     // Top bar: Score display
-    render_score_bar(ui, &app.score, app.settings.user.wpm);
+    render_score_bar(
+        ui,
+        &app.engine.score,
+        app.engine.now(),
+        app.engine.settings.user.wpm,
+    );
 
     ui.add_space(8.0);
     ui.separator();
     ui.add_space(8.0);
 
+    if !expanded {
+        render_meters(ui, app, &palette);
+    }
+
+    if app.engine.settings.user.show_waterfall {
+        render_waterfall(
+            ui,
+            &mut app.waterfall,
+            app.engine.settings.audio.tone_frequency_hz,
+        );
+        ui.add_space(8.0);
+    }
+
+    if app.engine.wide_pileup_enabled() {
+        render_receiver(ui, app);
+        ui.add_space(8.0);
+    }
+
     // Status indicator
-    if app.settings.user.show_status_line {
-        render_status(ui, app);
+    if app.engine.settings.user.show_status_line {
+        render_status(ui, app, &palette);
         ui.add_space(12.0);
     }
+    render_announcement(ui, app);
 
     // Input fields
-    render_input_fields(ui, app);
+    render_input_fields(ui, app, &palette, false);
+    render_note_box(ui, app);
 
     ui.add_space(12.0);
     ui.separator();
@@ -47,149 +90,491 @@ pub fn render_main_panel(ui: &mut egui::Ui, app: &mut ContestApp) {
 
     ui.add_space(8.0);
 
-    // Last QSO info
-    if let Some(ref last) = app.last_qso_result {
-        render_last_qso(ui, last);
-    }
+    if !expanded {
+        // Last QSO info
+        if let Some(ref last) = app.engine.last_qso_result {
+            let show_speed =
+                app.engine.settings.user.caller_speed_readout != CallerSpeedReadout::Off;
+            render_last_qso(ui, last, show_speed, &palette);
+        }
 
-    ui.add_space(8.0);
-    ui.separator();
-    ui.add_space(8.0);
+        ui.add_space(4.0);
+        render_tx_history(
+            ui,
+            &app.engine.tx_history,
+            app.engine.settings.user.font_size,
+            false,
+        );
+
+        ui.add_space(8.0);
+        ui.separator();
+        ui.add_space(8.0);
+    }
 
     // Bottom buttons
-    ui.horizontal(|ui| {
-        if ui.button("Reset Stats").clicked() {
-            app.reset_score();
-            app.session_stats.clear();
+    ui.horizontal_wrapped(|ui| {
+        if ui.button(tr("Reset Stats")).clicked() {
+            app.engine.end_session();
         }
 
         ui.add_space(10.0);
 
-        let noise_label = if app.noise_enabled {
+        let noise_label = if app.engine.noise_enabled {
             "Toggle Static (ON)"
         } else {
             "Toggle Static (OFF)"
         };
-        if ui.button(noise_label).clicked() {
-            app.toggle_noise();
+        if ui.button(tr(noise_label)).clicked() {
+            app.engine.toggle_noise();
         }
 
         ui.add_space(10.0);
 
-        if ui.button("Session Stats").clicked() {
+        if ui.button(tr("Session Stats")).clicked() {
             app.show_stats = !app.show_stats;
         }
+
+        ui.add_space(10.0);
+
+        if ui.button(tr("QSO Log")).clicked() {
+            app.show_log = !app.show_log;
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button(tr("Drills")).clicked() {
+            app.show_drill = !app.show_drill;
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button(tr("Skimmer")).clicked() {
+            app.show_skimmer = !app.show_skimmer;
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button(tr("Sandbox")).clicked() {
+            app.show_sandbox = !app.show_sandbox;
+        }
     });
 }
 
-fn render_score_bar(ui: &mut egui::Ui, score: &Score, user_wpm: u8) {
+/// One row for a small window or running beside a real logger: the entry
+/// boxes, the score and the status, with no hints or history
+fn render_compact_panel(ui: &mut egui::Ui, app: &mut ContestApp) {
+    let palette = Palette::for_user(&app.engine.settings.user);
+    render_announcement(ui, app);
+
+    ui.horizontal(|ui| {
+        render_input_fields(ui, app, &palette, true);
+        ui.add_space(12.0);
+
+        let score = &app.engine.score;
+        ui.label(
+            RichText::new(tr_args(
+                "{} QSOs  {} pts  {}/hr",
+                &[
+                    &score.qso_count.to_string(),
+                    &score.total_points.to_string(),
+                    &score.hourly_rate(app.engine.now()).to_string(),
+                ],
+            ))
+            .strong(),
+        );
+        if score.penalty_points > 0 {
+            ui.label(
+                RichText::new(tr_args(
+                    "({} after penalties)",
+                    &[&score.penalized_points().to_string()],
+                ))
+                .color(palette.warning),
+            );
+        }
+
+        if app.engine.settings.user.show_status_line {
+            ui.add_space(12.0);
+            let (status_text, status_color) = app.engine.get_status();
+            ui.label(RichText::new(tr(status_text)).color(palette.status_color(status_color)));
+        }
+    });
+    render_note_box(ui, app);
+}
+
+/// The active contest's rules and an example exchange, until they're put
+/// away; shown once per contest
+fn render_contest_intro(ui: &mut egui::Ui, app: &mut ContestApp) {
+    let contest_id = app.engine.contest.id();
+    let Some(help) = app.engine.contest.help() else {
+        return;
+    };
+    let contest = &mut app.engine.settings.contest;
+    if contest.help_seen.iter().any(|id| id == contest_id) {
+        return;
+    }
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.label(
+            RichText::new(tr_args("New to {}?", &[app.engine.contest.display_name()])).strong(),
+        );
+        render_contest_help(ui, &help);
+        if ui.button(tr("Got It")).clicked() {
+            contest.help_seen.push(contest_id.to_string());
+            app.engine.settings_changed = true;
+        }
+    });
+    ui.add_space(4.0);
+}
+
+/// Side panel for the expanded layout: the meters, the last QSO and what
+/// was sent, all kept open
+pub fn render_side_panel(ui: &mut egui::Ui, app: &mut ContestApp) {
+    let palette = Palette::for_user(&app.engine.settings.user);
+
+    render_meters(ui, app, &palette);
+
+    ui.label(RichText::new(tr("Last QSO:")).strong());
+    match app.engine.last_qso_result {
+        Some(ref last) => {
+            let show_speed =
+                app.engine.settings.user.caller_speed_readout != CallerSpeedReadout::Off;
+            render_last_qso(ui, last, show_speed, &palette);
+        }
+        None => {
+            ui.label(RichText::new(tr("None yet")).weak());
+        }
+    }
+
+    ui.add_space(8.0);
+    ui.separator();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        render_tx_history(
+            ui,
+            &app.engine.tx_history,
+            app.engine.settings.user.font_size,
+            true,
+        );
+    });
+}
+
+/// The S-meter, pileup depth and live caller speed, each if turned on
+fn render_meters(ui: &mut egui::Ui, app: &ContestApp, palette: &Palette) {
+    if app.engine.settings.user.show_s_meter {
+        render_s_meter(ui, app.rx_level, palette);
+        ui.add_space(4.0);
+    }
+
+    if app.engine.settings.user.show_pileup_depth {
+        render_pileup_depth(ui, app.engine.pileup_depth());
+        ui.add_space(4.0);
+    }
+
+    if app.engine.settings.user.caller_speed_readout == CallerSpeedReadout::Live {
+        if let Some((wpm, offset_hz)) = app.engine.current_caller_speed() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(tr("Caller:")).strong());
+                ui.label(caller_speed_text(wpm, offset_hz));
+            });
+            ui.add_space(4.0);
+        }
+    }
+}
+
+/// RIT and filter width for finding callers in a wide pileup, and how many
+/// of them have been worked
+fn render_receiver(ui: &mut egui::Ui, app: &mut ContestApp) {
     ui.horizontal(|ui| {
-        ui.label(RichText::new("QSOs:").strong());
+        ui.label(RichText::new(tr("RIT:")).strong());
+        ui.add(
+            egui::Slider::new(&mut app.engine.rit_hz, -RIT_MAX_HZ..=RIT_MAX_HZ)
+                .step_by(10.0)
+                .suffix(tr(" Hz")),
+        )
+        .on_hover_text(tr("Ctrl+Left/Right to tune, Ctrl+Down to clear"));
+        if ui.button(tr("Clear")).clicked() {
+            app.engine.rit_hz = 0.0;
+        }
+
+        ui.add_space(20.0);
+
+        ui.label(RichText::new(tr("Filter:")).strong());
+        let mut filter_hz = app.engine.settings.simulation.wide_pileup.filter_hz;
+        if ui
+            .add(
+                egui::DragValue::new(&mut filter_hz)
+                    .range(100.0..=1000.0)
+                    .speed(10.0)
+                    .suffix(tr(" Hz")),
+            )
+            .changed()
+        {
+            app.engine.set_receive_filter(filter_hz);
+        }
+
+        ui.add_space(20.0);
+
+        let tally = &app.engine.session_stats.wide_pileup;
+        ui.label(RichText::new(tr("Recovered:")).strong());
+        ui.label(tr_args(
+            "{} of {}",
+            &[&tally.recovered().to_string(), &tally.heard().to_string()],
+        ))
+        .on_hover_text(tr("Callers worked out of those heard calling"));
+    });
+}
+
+fn render_score_bar(ui: &mut egui::Ui, score: &Score, now: Instant, user_wpm: u8) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(tr("QSOs:")).strong());
         ui.label(format!("{}", score.qso_count));
 
         ui.add_space(20.0);
 
-        ui.label(RichText::new("Points:").strong());
+        ui.label(RichText::new(tr("Points:")).strong());
         ui.label(format!("{}", score.total_points));
 
         ui.add_space(20.0);
 
-        ui.label(RichText::new("Rate:").strong());
-        ui.label(format!("{}/hr", score.hourly_rate()));
+        ui.label(RichText::new(tr("Penalized:")).strong())
+            .on_hover_text(tr(
+                "Points after log checking: busted QSOs and NILs cost points",
+            ));
+        ui.label(format!("{}", score.penalized_points()));
+
+        ui.add_space(20.0);
+
+        ui.label(RichText::new(tr("Rate:")).strong());
+        ui.label(tr_args("{}/hr", &[&score.hourly_rate(now).to_string()]));
 
         ui.add_space(20.0);
 
-        ui.label(RichText::new("Run WPM:").strong());
+        ui.label(RichText::new(tr("Run WPM:")).strong());
         ui.label(format!("{}", user_wpm));
+
+        ui.add_space(20.0);
+
+        ui.label(RichText::new(tr("UTC:")).strong());
+        ui.label(RichText::new(chrono::Utc::now().format("%H:%M:%SZ").to_string()).monospace());
     });
 }
 
-fn render_status(ui: &mut egui::Ui, app: &ContestApp) {
-    let (status_text, status_color) = app.get_status();
-    let color = match status_color {
-        StatusColor::Gray => Color32::GRAY,
-        StatusColor::Yellow => Color32::YELLOW,
-        StatusColor::LightBlue => Color32::LIGHT_BLUE,
-        StatusColor::Green => Color32::from_rgb(100, 200, 100),
-        StatusColor::Orange => Color32::from_rgb(255, 165, 0),
+fn render_s_meter(ui: &mut egui::Ui, level: f32, palette: &Palette) {
+    let reading = SReading::from_level(level);
+    // S0-S9 fill the first 60% of the scale, S9+40 dB the rest
+    let fraction = reading.s_units / 9.0 * 0.6 + (reading.db_over_s9 / 40.0).min(1.0) * 0.4;
+    let fill = if reading.db_over_s9 > 0.0 {
+        palette.bad
+    } else {
+        palette.active
     };
 
     ui.horizontal(|ui| {
-        ui.label(RichText::new("Status:").strong());
-        ui.label(RichText::new(status_text).color(color));
+        ui.label(RichText::new(tr("S-Meter:")).strong());
+        ui.add(
+            egui::ProgressBar::new(fraction)
+                .desired_width(160.0)
+                .fill(fill)
+                .text(reading.label()),
+        );
     });
 }
 
-fn render_input_fields(ui: &mut egui::Ui, app: &mut ContestApp) {
-    let exchange_fields = app.contest.exchange_fields();
-    if app.exchange_inputs.len() != exchange_fields.len() {
-        let defaults = app.exchange_default_values();
+/// How many stations are waiting to call, as a skimmer would show them
+/// Caller speed and offset from our pitch, e.g. "28 WPM, +120 Hz"
+fn caller_speed_text(wpm: u8, offset_hz: f32) -> String {
+    tr_args(
+        "{} WPM, {} Hz",
+        &[&wpm.to_string(), &format!("{:+.0}", offset_hz)],
+    )
+}
+
+fn render_pileup_depth(ui: &mut egui::Ui, depth: usize) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(tr("Pileup:")).strong());
+        ui.label(tr_args("{} waiting", &[&depth.to_string()]))
+            .on_hover_text(tr("Stations waiting to call, including those calling now"));
+    });
+}
+
+fn render_status(ui: &mut egui::Ui, app: &ContestApp, palette: &Palette) {
+    let (status_text, status_color) = app.engine.get_status();
+    let color = palette.status_color(status_color);
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(tr("Status:")).strong());
+        ui.label(
+            RichText::new(tr(status_text))
+                .font(entry_font(app.engine.settings.user.font_size))
+                .color(color),
+        );
+    });
+}
+
+/// Expose the latest status announcement to screen readers as a live
+/// region. It has no visible widget; sighted users have the status line.
+fn render_announcement(ui: &mut egui::Ui, app: &ContestApp) {
+    use egui::accesskit::{Live, Role};
+
+    let Some(announcement) = app.engine.announcer.latest() else {
+        return;
+    };
+    // A new node per announcement, so a repeated message is read again
+    let id = ui.id().with(("status_announcement", announcement.seq));
+    let text = announcement.text.clone();
+    ui.ctx().accesskit_node_builder(id, |node| {
+        node.set_role(Role::Status);
+        node.set_live(Live::Polite);
+        node.set_label(text);
+    });
+}
+
+/// The call and exchange boxes. `compact` leaves out the labels above them
+/// (naming each in its tooltip instead) and the hints inside them.
+fn render_input_fields(ui: &mut egui::Ui, app: &mut ContestApp, palette: &Palette, compact: bool) {
+    let exchange_fields = app.engine.contest.exchange_fields();
+    if app.engine.exchange_inputs.len() != exchange_fields.len() {
+        let defaults = app.engine.exchange_default_values();
         let mut next = Vec::with_capacity(exchange_fields.len());
         for idx in 0..exchange_fields.len() {
             let value = app
+                .engine
                 .exchange_inputs
                 .get(idx)
                 .cloned()
                 .unwrap_or_else(|| defaults.get(idx).cloned().unwrap_or_default());
             next.push(value);
         }
-        app.exchange_inputs = next;
+        app.engine.exchange_inputs = next;
+        app.engine.exchange_prefill = vec![PrefillState::Typed; exchange_fields.len()];
     }
 
-    let label_size = (app.settings.user.font_size - 4.0).max(8.0);
+    let label_size = (app.engine.settings.user.font_size - 4.0).max(8.0);
     egui::Grid::new("input_fields_grid")
         .num_columns(exchange_fields.len() + 1)
         .spacing([6.0, 2.0])
         .show(ui, |ui| {
-            ui.label(RichText::new("Call").size(label_size));
-            for field in exchange_fields.iter() {
-                ui.label(RichText::new(field.label).size(label_size));
+            let mut call_label = None;
+            let mut field_labels = vec![None; exchange_fields.len()];
+            if !compact {
+                call_label = Some(ui.label(RichText::new(tr("Call")).size(label_size)).id);
+                for (label, field) in field_labels.iter_mut().zip(&exchange_fields) {
+                    *label = Some(ui.label(RichText::new(field.label).size(label_size)).id);
+                }
+                ui.end_row();
             }
-            ui.end_row();
+            let show_hints = app.engine.settings.user.show_main_hints && !compact;
 
-            let mut call_edit = egui::TextEdit::singleline(&mut app.callsign_input)
-                .font(egui::TextStyle::Monospace);
-            if app.settings.user.show_main_hints {
-                call_edit = call_edit.hint_text("Callsign");
+            let mut call_edit = egui::TextEdit::singleline(&mut app.engine.callsign_input)
+                .font(entry_font(app.engine.settings.user.font_size));
+            if show_hints {
+                call_edit = call_edit.hint_text(tr("Callsign"));
             }
-            let call_response = ui.add_sized(Vec2::new(120.0, 24.0), call_edit);
+            let call_response = labelled(
+                ui.add_sized(Vec2::new(120.0, 24.0), call_edit),
+                call_label,
+                tr("Call"),
+            );
 
             if call_response.changed() {
-                app.callsign_input = app.callsign_input.to_uppercase();
+                app.engine.entry_error = None;
+                app.engine.callsign_input = app.engine.callsign_input.to_uppercase();
+                app.engine.update_exchange_prefill();
             }
 
-            if app.current_field == InputField::Callsign && !app.show_settings {
+            let take_focus = !app.show_settings && app.engine.note_input.is_none();
+            if app.engine.current_field == InputField::Callsign && take_focus {
                 call_response.request_focus();
             }
             if call_response.clicked() {
-                app.current_field = InputField::Callsign;
+                app.engine.current_field = InputField::Callsign;
             }
 
             for (idx, field) in exchange_fields.iter().enumerate() {
                 let width_px =
-                    exchange_field_width(ui, field.width_chars, app.settings.user.font_size);
-                let mut exchange_edit = egui::TextEdit::singleline(&mut app.exchange_inputs[idx])
-                    .font(egui::TextStyle::Monospace);
-                if app.settings.user.show_main_hints {
+                    exchange_field_width(ui, field.width_chars, app.engine.settings.user.font_size);
+                // Flag entries that can't be right for this field
+                let value = &app.engine.exchange_inputs[idx];
+                let invalid =
+                    !value.is_empty() && !app.engine.contest.field_input_valid(idx, value);
+                let focused = app.engine.current_field == InputField::Exchange(idx);
+                let prefill = app.engine.exchange_prefill.get(idx).copied();
+                // Moving into a pre-filled box counts as checking it
+                if focused && prefill == Some(PrefillState::Prefilled) {
+                    app.engine.exchange_prefill[idx] = PrefillState::Verified;
+                }
+                let unchecked = !focused && prefill == Some(PrefillState::Prefilled);
+                let mut exchange_edit =
+                    egui::TextEdit::singleline(&mut app.engine.exchange_inputs[idx])
+                        .font(entry_font(app.engine.settings.user.font_size));
+                if show_hints {
                     exchange_edit = exchange_edit.hint_text(field.placeholder);
                 }
-                let response = ui.add_sized(Vec2::new(width_px, 24.0), exchange_edit);
+                if invalid {
+                    exchange_edit = exchange_edit.text_color(palette.bad);
+                } else if unchecked {
+                    exchange_edit = exchange_edit.text_color(palette.muted);
+                }
+                let mut response = labelled(
+                    ui.add_sized(Vec2::new(width_px, 24.0), exchange_edit),
+                    field_labels[idx],
+                    field.label,
+                );
+                if invalid {
+                    response = response.on_hover_text(tr_args("Not a valid {}", &[field.label]));
+                }
                 if response.changed() {
+                    app.engine.entry_error = None;
                     let normalized =
-                        normalize_exchange_input(&app.exchange_inputs[idx], field.kind);
-                    app.exchange_inputs[idx] = normalized;
+                        normalize_exchange_input(&app.engine.exchange_inputs[idx], field.kind);
+                    app.engine.exchange_inputs[idx] = normalized;
+                    if let Some(state) = app.engine.exchange_prefill.get_mut(idx) {
+                        *state = PrefillState::Typed;
+                    }
                 }
 
-                if app.current_field == InputField::Exchange(idx) && !app.show_settings {
+                if app.engine.current_field == InputField::Exchange(idx) && take_focus {
                     response.request_focus();
                 }
                 if response.clicked() {
-                    app.current_field = InputField::Exchange(idx);
-                    app.last_exchange_field_index = idx;
+                    app.engine.current_field = InputField::Exchange(idx);
+                    app.engine.last_exchange_field_index = idx;
                 }
             }
             ui.end_row();
         });
+
+    // Strict mode refused the last Enter
+    if let Some(error) = &app.engine.entry_error {
+        ui.label(RichText::new(error).color(palette.bad));
+    }
+}
+
+/// Tie an entry box to its label, or when there is no label shown, name it
+/// in a tooltip
+fn labelled(response: egui::Response, label: Option<egui::Id>, name: &str) -> egui::Response {
+    match label {
+        Some(label) => response.labelled_by(label),
+        None => response.on_hover_text(name),
+    }
+}
+
+/// Note for a flagged QSO; Enter keeps it, Esc leaves the QSO flagged
+/// without a note
+fn render_note_box(ui: &mut egui::Ui, app: &mut ContestApp) {
+    let Some(note) = app.engine.note_input.as_mut() else {
+        return;
+    };
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        let label = ui.label(tr("Note:")).id;
+        let response = ui
+            .add(
+                egui::TextEdit::singleline(note)
+                    .desired_width(300.0)
+                    .hint_text(tr("Why flag this QSO? (optional)")),
+            )
+            .labelled_by(label);
+        response.request_focus();
+    });
 }
 
 fn exchange_field_width(ui: &egui::Ui, width_chars: u8, font_size: f32) -> f32 {
@@ -201,19 +586,19 @@ fn exchange_field_width(ui: &egui::Ui, width_chars: u8, font_size: f32) -> f32 {
 fn render_key_hints(ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
         ui.label(RichText::new("F1").strong().monospace());
-        ui.label("CQ");
+        ui.label(tr("CQ"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("F2").strong().monospace());
-        ui.label("Exchange");
+        ui.label(tr("Exchange"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("F3").strong().monospace());
-        ui.label("TU");
+        ui.label(tr("TU"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("F5").strong().monospace());
-        ui.label("His Call");
+        ui.label(tr("His Call"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("F8").strong().monospace());
@@ -221,42 +606,66 @@ fn render_key_hints(ui: &mut egui::Ui) {
         ui.add_space(10.0);
 
         ui.label(RichText::new("F12").strong().monospace());
-        ui.label("Wipe");
+        ui.label(tr("Wipe"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("Enter").strong().monospace());
-        ui.label("Submit");
+        ui.label(tr("Submit"));
         ui.add_space(10.0);
 
         ui.label(RichText::new("Esc").strong().monospace());
-        ui.label("Stop");
+        ui.label(tr("Stop"));
     });
 }
 
-fn render_last_qso(ui: &mut egui::Ui, result: &crate::app::QsoResult) {
+fn render_last_qso(
+    ui: &mut egui::Ui,
+    result: &crate::engine::QsoResult,
+    show_speed: bool,
+    palette: &Palette,
+) {
     ui.add_space(4.0);
 
     let call_indicator = if result.callsign_correct { "OK" } else { "X" };
     let exch_indicator = if result.exchange_correct { "OK" } else { "X" };
 
-    let call_color = if result.callsign_correct {
-        Color32::GREEN
-    } else {
-        Color32::RED
-    };
-    let exch_color = if result.exchange_correct {
-        Color32::GREEN
-    } else {
-        Color32::RED
-    };
+    let call_color = palette.correct(result.callsign_correct);
+    let exch_color = palette.correct(result.exchange_correct);
 
     ui.horizontal(|ui| {
-        ui.label("Last QSO:");
+        ui.label(tr("Last QSO:"));
         ui.label(&result.callsign);
-        ui.label(RichText::new(format!("Call: {}", call_indicator)).color(call_color));
-        ui.label(RichText::new(format!("Exch: {}", exch_indicator)).color(exch_color));
+        ui.label(RichText::new(tr_args("Call: {}", &[call_indicator])).color(call_color));
+        ui.label(RichText::new(tr_args("Exch: {}", &[exch_indicator])).color(exch_color));
+        if !result.wrong_fields.is_empty() {
+            // Fields that can't be right at all are marked, apart from miscopies
+            let wrong: Vec<String> = result
+                .wrong_fields
+                .iter()
+                .map(|label| {
+                    if result.impossible_fields.contains(label) {
+                        tr_args("{} not valid", &[label])
+                    } else {
+                        label.clone()
+                    }
+                })
+                .collect();
+            ui.label(RichText::new(format!("({})", wrong.join(", "))).color(exch_color));
+        }
         if result.points > 0 {
-            ui.label(RichText::new(format!("+{} pts", result.points)).color(Color32::GREEN));
+            ui.label(
+                RichText::new(tr_args("+{} pts", &[&result.points.to_string()]))
+                    .color(palette.good),
+            );
+        }
+        if show_speed {
+            ui.label(
+                RichText::new(caller_speed_text(
+                    result.caller_wpm,
+                    result.caller_offset_hz,
+                ))
+                .weak(),
+            );
         }
     });
 
@@ -265,12 +674,42 @@ fn render_last_qso(ui: &mut egui::Ui, result: &crate::app::QsoResult) {
         ui.horizontal(|ui| {
             ui.add_space(60.0);
             ui.label(
-                RichText::new(format!(
+                RichText::new(tr_args(
                     "Expected: {} {}",
-                    result.expected_call, result.expected_exchange
+                    &[&result.expected_call, &result.expected_exchange],
                 ))
                 .weak(),
             );
         });
     }
+    for remark in &result.remarks {
+        ui.horizontal(|ui| {
+            ui.add_space(60.0);
+            ui.label(RichText::new(remark).weak());
+        });
+    }
+}
+
+/// What we actually sent, newest first, for checking a busted QSO
+fn render_tx_history(ui: &mut egui::Ui, history: &VecDeque<TxRecord>, font_size: f32, open: bool) {
+    egui::CollapsingHeader::new(tr("TX History"))
+        .id_salt(("tx_history", open))
+        .default_open(open)
+        .show(ui, |ui| {
+            if history.is_empty() {
+                ui.label(RichText::new(tr("Nothing sent yet")).weak());
+                return;
+            }
+            for record in history.iter().rev() {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(record.sent_at.format("%H:%M:%S").to_string())
+                            .monospace()
+                            .weak(),
+                    );
+                    ui.label(RichText::new(&record.text).font(entry_font(font_size * 0.8)));
+                    ui.label(RichText::new(tr_args("{} WPM", &[&record.wpm.to_string()])).weak());
+                });
+            }
+        });
 }
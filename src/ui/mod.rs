@@ -1,9 +1,45 @@
+pub mod audio_diagnostics;
+pub mod calendar_prompt;
+pub mod contest_help;
+pub mod drill_window;
+pub mod export_browser;
 pub mod export_dialog;
+pub mod fonts;
+pub mod idle_prompt;
+pub mod log_window;
 pub mod main_panel;
+pub mod notifications;
+pub mod recovery_prompt;
+pub mod sandbox_window;
 pub mod settings_panel;
+pub mod skimmer_window;
 pub mod stats_window;
+pub mod summary_dialog;
+pub mod theme;
+pub mod trophies_window;
+pub mod tutorial_prompt;
+pub mod waterfall;
 
+pub use audio_diagnostics::{render_audio_diagnostics, AudioQueueStats};
+pub use calendar_prompt::{render_calendar_prompt, CalendarPromptResponse};
+pub use contest_help::render_contest_help;
+pub use drill_window::{render_drill_window, DrillWindowAction};
+pub use export_browser::{render_export_browser, ExportBrowser};
 pub use export_dialog::render_export_dialog;
-pub use main_panel::render_main_panel;
+pub use fonts::{entry_font, install_fonts};
+pub use idle_prompt::render_idle_prompt;
+pub use log_window::{render_log_window, LogView};
+pub use main_panel::{render_main_panel, render_side_panel};
+pub use notifications::{
+    render_notification_toast, render_notifications_button, render_notifications_window,
+};
+pub use recovery_prompt::{render_recovery_prompt, RecoveryPromptResponse};
+pub use sandbox_window::{render_sandbox_window, SandboxAction};
 pub use settings_panel::{render_settings_panel, FileDialogTarget};
+pub use skimmer_window::render_skimmer_window;
 pub use stats_window::render_stats_window;
+pub use summary_dialog::render_session_summary;
+pub use theme::{apply_theme, Palette};
+pub use trophies_window::render_trophies_window;
+pub use tutorial_prompt::{render_tutorial_prompt, TutorialPromptResponse};
+pub use waterfall::{render_waterfall, Waterfall};
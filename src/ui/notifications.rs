@@ -0,0 +1,116 @@
+// Notifications in the window: an indicator in the menu bar, the list it
+// opens, and the latest one flashed over the main window
+
+use std::time::Instant;
+
+use egui::RichText;
+
+use crate::i18n::{tr, tr_args};
+use crate::notifications::{Notifications, Severity};
+use crate::ui::Palette;
+
+fn severity_color(severity: Severity, palette: &Palette) -> egui::Color32 {
+    match severity {
+        Severity::Info => palette.info,
+        Severity::Warning => palette.warning,
+        Severity::Error => palette.bad,
+    }
+}
+
+/// Menu bar indicator: highlighted with the count of unread warnings and
+/// errors; opens the list
+pub fn render_notifications_button(
+    ui: &mut egui::Ui,
+    notifications: &mut Notifications,
+    palette: &Palette,
+) {
+    let unread = notifications.unread();
+    let text = match notifications.unread_severity() {
+        Some(severity) if unread > 0 => RichText::new(format!("⚠ {}", unread))
+            .strong()
+            .color(severity_color(severity, palette)),
+        _ => RichText::new("⚠").weak(),
+    };
+    if ui
+        .button(text)
+        .on_hover_text(tr("Warnings and errors"))
+        .clicked()
+    {
+        notifications.show = !notifications.show;
+    }
+}
+
+pub fn render_notifications_window(
+    ctx: &egui::Context,
+    notifications: &mut Notifications,
+    palette: &Palette,
+) {
+    if !notifications.show {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new(tr("Notifications"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if notifications.entries().is_empty() {
+                ui.label(RichText::new(tr("Nothing to report")).weak());
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for entry in notifications.entries().iter().rev() {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(
+                                RichText::new(entry.time.format("%H:%M:%S").to_string())
+                                    .monospace()
+                                    .weak(),
+                            );
+                            ui.label(
+                                RichText::new(&entry.message)
+                                    .color(severity_color(entry.severity, palette)),
+                            );
+                            if entry.count > 1 {
+                                ui.label(
+                                    RichText::new(tr_args(
+                                        "({} times)",
+                                        &[&entry.count.to_string()],
+                                    ))
+                                    .weak(),
+                                );
+                            }
+                        });
+                    }
+                });
+            ui.add_space(8.0);
+            if ui.button(tr("Clear")).clicked() {
+                notifications.clear();
+            }
+        });
+    // Everything listed has now been seen
+    notifications.mark_all_seen();
+    notifications.show = open;
+}
+
+/// The newest notification, shown briefly in the bottom-right corner
+pub fn render_notification_toast(
+    ctx: &egui::Context,
+    notifications: &mut Notifications,
+    palette: &Palette,
+) {
+    let Some(entry) = notifications.toast(Instant::now()) else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("notification_toast"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(RichText::new(&entry.message).color(match entry.severity {
+                    Severity::Info => ui.visuals().text_color(),
+                    severity => severity_color(severity, palette),
+                }));
+            });
+        });
+}
@@ -0,0 +1,49 @@
+use crate::i18n::{tr, tr_args};
+use crate::recovery::SavedSession;
+
+/// User's answer to the session recovery prompt
+pub enum RecoveryPromptResponse {
+    Restore,
+    Discard,
+}
+
+pub fn render_recovery_prompt(
+    ctx: &egui::Context,
+    saved: &SavedSession,
+) -> Option<RecoveryPromptResponse> {
+    let mut response = None;
+    let saved_at = chrono::DateTime::parse_from_rfc3339(&saved.saved_at)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| saved.saved_at.clone());
+    let points: u32 = saved
+        .qsos
+        .iter()
+        .filter(|q| !q.excluded)
+        .map(|q| q.points)
+        .sum();
+
+    egui::Window::new(tr("Restore Session?"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.label(tr_args(
+                "The last session was not ended with Reset Stats. Autosaved {}: {} QSOs, {} points.",
+                &[&saved_at, &saved.qsos.len().to_string(), &points.to_string()],
+            ));
+            ui.add_space(12.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("Restore")).clicked() {
+                    response = Some(RecoveryPromptResponse::Restore);
+                }
+                if ui.button(tr("Start fresh")).clicked() {
+                    response = Some(RecoveryPromptResponse::Discard);
+                }
+            });
+            ui.add_space(4.0);
+        });
+
+    response
+}
@@ -0,0 +1,107 @@
+use crate::drill::sandbox::MorseSandbox;
+use crate::i18n::{tr, tr_args};
+use egui::{Key, RichText};
+
+/// Actions the sandbox window asks the app to perform
+pub enum SandboxAction {
+    /// Text was queued; start playing if idle
+    Play,
+    /// Stop playback and clear the queue
+    Stop,
+}
+
+pub fn render_sandbox_window(
+    ctx: &egui::Context,
+    sandbox: &mut MorseSandbox,
+    can_play: bool,
+    show_sandbox: &mut bool,
+) -> Option<SandboxAction> {
+    let mut action = None;
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("sandbox_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Morse Sandbox"))
+            .with_inner_size([400.0, 300.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label(
+                    RichText::new(tr("Type any text to hear it over the current band noise"))
+                        .small()
+                        .italics(),
+                );
+                ui.add_space(4.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("WPM:"));
+                    ui.add(egui::Slider::new(&mut sandbox.wpm, 10..=60));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("Pitch (Hz):"));
+                    ui.add(egui::Slider::new(&mut sandbox.pitch_hz, 300.0..=1000.0).step_by(10.0));
+                });
+
+                ui.add_space(4.0);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut sandbox.text)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .hint_text(tr("Text to send, then press Enter")),
+                );
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+
+                ui.horizontal(|ui| {
+                    let play = ui
+                        .add_enabled(can_play, egui::Button::new(tr("Play")))
+                        .on_disabled_hover_text(tr("Finish or wipe the current contest QSO first"))
+                        .clicked();
+                    if (play || (submitted && can_play)) && !sandbox.text.trim().is_empty() {
+                        sandbox.enqueue_text();
+                        action = Some(SandboxAction::Play);
+                        response.request_focus();
+                    }
+                    if ui
+                        .add_enabled(
+                            sandbox.playing.is_some() || !sandbox.queue.is_empty(),
+                            egui::Button::new(tr("Stop")),
+                        )
+                        .clicked()
+                    {
+                        action = Some(SandboxAction::Stop);
+                    }
+                });
+
+                ui.add_space(8.0);
+                if let Some(item) = &sandbox.playing {
+                    ui.label(tr_args(
+                        "Playing: {} ({} WPM, {} Hz)",
+                        &[
+                            &item.text,
+                            &item.wpm.to_string(),
+                            &format!("{:.0}", item.pitch_hz),
+                        ],
+                    ));
+                }
+                for item in &sandbox.queue {
+                    ui.label(
+                        RichText::new(tr_args(
+                            "Queued: {} ({} WPM, {} Hz)",
+                            &[
+                                &item.text,
+                                &item.wpm.to_string(),
+                                &format!("{:.0}", item.pitch_hz),
+                            ],
+                        ))
+                        .weak(),
+                    );
+                }
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_sandbox = false;
+            }
+        },
+    );
+
+    action
+}
@@ -1,5 +1,19 @@
-use crate::config::AppSettings;
-use crate::contest::{Contest, ContestDescriptor, SettingFieldGroup, SettingFieldKind};
+use crate::audio::backend::JACK_AVAILABLE;
+use crate::config::{
+    AccentColor, AgcMode, AppSettings, AudioBackendKind, CallerSpeedReadout, ColorTheme,
+    ContinentWeights, FontChoice, GiveUpBehavior, Language, OperatorTempo, SidetoneTimbre,
+    UiLayout, CUT_NUMBER_PROBABILITY_KEY, DE_PROBABILITY_KEY, MACRO_KEYS, OUR_CALL_PROBABILITY_KEY,
+    SIGN_TWICE_PROBABILITY_KEY,
+};
+use crate::contest::{
+    custom, Contest, ContestDescriptor, FileCheck, SettingFieldGroup, SettingFieldKind,
+};
+use crate::cty::Continent;
+use crate::data_update;
+use crate::i18n::{tr, tr_args};
+use crate::ui::fonts::font_name;
+use crate::ui::render_contest_help;
+use crate::ui::theme::{accent_name, theme_name};
 use egui::{RichText, Vec2};
 use egui_file_dialog::FileDialog;
 
@@ -8,8 +22,57 @@ use egui_file_dialog::FileDialog;
 pub enum FileDialogTarget {
     ContestSetting { contest_id: String, key: String },
     ExportDirectory,
+    ExportSettingsBundle { include_file_references: bool },
+    ImportSettingsBundle,
 }
 
+fn continent_name(continent: Continent) -> &'static str {
+    match continent {
+        Continent::NorthAmerica => tr("North America"),
+        Continent::SouthAmerica => tr("South America"),
+        Continent::Europe => tr("Europe"),
+        Continent::Africa => tr("Africa"),
+        Continent::Asia => tr("Asia"),
+        Continent::Oceania => tr("Oceania"),
+    }
+}
+
+fn tempo_name(tempo: OperatorTempo) -> &'static str {
+    match tempo {
+        OperatorTempo::Fast => tr("Fast (CWT)"),
+        OperatorTempo::Normal => tr("Normal"),
+        OperatorTempo::Relaxed => tr("Relaxed (DX)"),
+    }
+}
+
+fn give_up_name(behavior: GiveUpBehavior) -> &'static str {
+    match behavior {
+        GiveUpBehavior::Silent => tr("Silently"),
+        GiveUpBehavior::FinalCall => tr("With a Final Call"),
+        GiveUpBehavior::Dits => tr("With \"E E\""),
+    }
+}
+
+fn speed_readout_name(readout: CallerSpeedReadout) -> &'static str {
+    match readout {
+        CallerSpeedReadout::Off => tr("Off"),
+        CallerSpeedReadout::AfterQso => tr("After the QSO"),
+        CallerSpeedReadout::Live => tr("Live"),
+    }
+}
+
+/// Buffer sizes offered in the audio settings (0 = backend default)
+const BUFFER_FRAME_CHOICES: [u32; 6] = [0, 128, 256, 512, 1024, 2048];
+
+fn buffer_frames_label(frames: u32) -> String {
+    if frames == 0 {
+        tr("Default").to_string()
+    } else {
+        tr_args("{} frames", &[&frames.to_string()])
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_settings_panel(
     ui: &mut egui::Ui,
     settings: &mut AppSettings,
@@ -18,14 +81,15 @@ pub fn render_settings_panel(
     active_contest: &dyn Contest,
     file_dialog: &mut FileDialog,
     file_dialog_target: &mut Option<FileDialogTarget>,
+    audio_underruns: Option<u32>,
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         // User Settings
-        egui::CollapsingHeader::new(RichText::new("User Settings").strong())
+        egui::CollapsingHeader::new(RichText::new(tr("User Settings")).strong())
             .default_open(true)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Your Callsign:");
+                    ui.label(tr("Your Callsign:"));
                     if ui
                         .text_edit_singleline(&mut settings.user.callsign)
                         .changed()
@@ -36,7 +100,7 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Your WPM:");
+                    ui.label(tr("Your WPM:"));
                     if ui
                         .add(egui::Slider::new(&mut settings.user.wpm, 15..=50))
                         .changed()
@@ -46,12 +110,929 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Font Size:");
+                    ui.label(tr("Font Size:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut settings.user.font_size, 10.0..=24.0)
+                                .fixed_decimals(0),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    const FONTS: [FontChoice; 3] = [
+                        FontChoice::Ubuntu,
+                        FontChoice::Hack,
+                        FontChoice::DejaVuSansMono,
+                    ];
+                    ui.label(tr("UI Font:"));
+                    egui::ComboBox::from_id_salt("ui_font")
+                        .selected_text(font_name(settings.user.ui_font))
+                        .show_ui(ui, |ui| {
+                            for font in FONTS {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.ui_font,
+                                        font,
+                                        font_name(font),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        });
+
+                    ui.label(tr("Entry Font:"));
+                    egui::ComboBox::from_id_salt("entry_font")
+                        .selected_text(font_name(settings.user.entry_font))
+                        .show_ui(ui, |ui| {
+                            for font in FONTS {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.entry_font,
+                                        font,
+                                        font_name(font),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("Font for the call and exchange boxes and the status line. \
+                                A monospace font keeps 0/O and 1/I easy to tell apart at speed"),
+                        );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("AGN Message:"));
+                    if ui
+                        .text_edit_singleline(&mut settings.user.agn_message)
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("TU Message:"));
+                    if ui
+                        .text_edit_singleline(&mut settings.user.tu_message)
+                        .on_hover_text(
+                            tr("Sent with F3. {MYCALL} is replaced with your callsign; \
+                                prosigns can be entered as <SK>, <KN>, <BK>, <AR>. \
+                                Separate alternatives with | to vary it (TU {MYCALL}|R TU|73 {MYCALL})"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                if ui
+                    .checkbox(&mut settings.user.show_status_line, tr("Show Status Line"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.show_s_meter, tr("Show S-Meter"))
+                    .on_hover_text(tr("Signal strength of the receiver audio"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.show_pileup_depth, tr("Show Pileup Depth"))
+                    .on_hover_text(
+                        tr("How many stations are waiting to call; a learning aid, \
+                            turn it off to practice by ear"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Caller Speed Readout:"));
+                    egui::ComboBox::from_id_salt("caller_speed_readout")
+                        .selected_text(speed_readout_name(settings.user.caller_speed_readout))
+                        .show_ui(ui, |ui| {
+                            for readout in [
+                                CallerSpeedReadout::Off,
+                                CallerSpeedReadout::AfterQso,
+                                CallerSpeedReadout::Live,
+                            ] {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.caller_speed_readout,
+                                        readout,
+                                        speed_readout_name(readout),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("Show the caller's speed and offset from your pitch, on the \
+                                Last QSO line or while you work them, to calibrate your sense of speed"),
+                        );
+                });
+
+                if ui
+                    .checkbox(&mut settings.user.show_waterfall, tr("Show Waterfall"))
+                    .on_hover_text(tr("Scrolling spectrum of the receiver audio around your pitch"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Language:"));
+                    egui::ComboBox::from_id_salt("ui_language")
+                        .selected_text(settings.user.language.native_name())
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.language,
+                                        language,
+                                        language.native_name(),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Theme:"));
+                    egui::ComboBox::from_id_salt("color_theme")
+                        .selected_text(theme_name(settings.user.theme))
+                        .show_ui(ui, |ui| {
+                            for theme in [
+                                ColorTheme::Dark,
+                                ColorTheme::Light,
+                                ColorTheme::HighContrast,
+                                ColorTheme::Colorblind,
+                            ] {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.theme,
+                                        theme,
+                                        theme_name(theme),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("Colorblind shows right/wrong in blue/orange instead of green/red"),
+                        );
+
+                    ui.label(tr("Accent:"));
+                    egui::ComboBox::from_id_salt("accent_color")
+                        .selected_text(accent_name(settings.user.accent))
+                        .show_ui(ui, |ui| {
+                            for accent in [
+                                AccentColor::Blue,
+                                AccentColor::Green,
+                                AccentColor::Orange,
+                                AccentColor::Purple,
+                                AccentColor::Teal,
+                            ] {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.accent,
+                                        accent,
+                                        accent_name(accent),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Layout:"));
+                    egui::ComboBox::from_id_salt("ui_layout")
+                        .selected_text(tr(settings.user.layout.name()))
+                        .show_ui(ui, |ui| {
+                            for layout in UiLayout::ALL {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.user.layout,
+                                        layout,
+                                        tr(layout.name()),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("Compact is one row for running beside a logger; Expanded adds \
+                                a side panel. Also under View in the menu bar."),
+                        );
+                });
+
+                if ui
+                    .checkbox(&mut settings.user.always_on_top, tr("Always on Top"))
+                    .on_hover_text(tr("Keep the trainer above other windows"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.quick_drill_hotkey, tr("Quick Drill Hotkey"))
+                    .on_hover_text(
+                        tr("Bind a key in your desktop's keyboard shortcut settings to run \
+                            `contest_trainer quick-drill`; it starts a drill, or stops it if one \
+                            is open, even while another program has focus"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.show_main_hints, tr("Show Main Field Hints"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.exchange_prefill, tr("Pre-fill Exchange"))
+                    .on_hover_text(
+                        tr("Fill the exchange from the call-history file when you enter a known call. \
+                            Pre-filled fields stay gray until you move into them to check."),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.strict_logging, tr("Strict Logging"))
+                    .on_hover_text(
+                        tr("Enter won't log until the caller has finished sending the exchange \
+                            and every exchange box is filled, to build the habit of copying it all"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if ui
+                    .checkbox(&mut settings.user.warn_early_log, tr("Warn When Logging Early"))
+                    .on_hover_text(
+                        tr("Point it out when you log before the caller has finished sending \
+                            the exchange. Such QSOs are counted as Logged Early in the stats \
+                            either way."),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Speech Command:"));
+                    if ui
+                        .text_edit_singleline(&mut settings.user.speech_command)
+                        .on_hover_text(
+                            tr("Run with each status announcement (station calling, exchange \
+                                received, QSO logged) as its last argument, e.g. espeak-ng, \
+                                say or notify-send. Leave empty to only update the screen reader."),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Autosave Every:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut settings.user.autosave_every, 0..=25)
+                                .suffix(tr(" QSOs")),
+                        )
+                        .on_hover_text(
+                            tr("Save the session after this many QSOs so it can be restored \
+                                after a crash or an accidental close. 0 turns autosave off."),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Pause When Idle:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut settings.user.idle_pause_minutes, 0..=60)
+                                .suffix(tr(" min")),
+                        )
+                        .on_hover_text(
+                            tr("Pause the session after this many minutes without a key press \
+                                or click, so time away isn't counted. 0 never pauses."),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.add_space(4.0);
+                ui.label(tr("Milestone Alerts:"));
+                let alerts = &mut settings.user.alerts;
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(&mut alerts.banner, tr("Banner"))
+                        .on_hover_text(tr("Show a short note over the main window"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                    if ui
+                        .checkbox(&mut alerts.chime, tr("Chime"))
+                        .on_hover_text(
+                            tr("Play a short chime on the UI sounds bus; it waits until \
+                                you finish sending and never mutes the callers"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+                ui.add_enabled_ui(alerts.banner || alerts.chime, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Every:"));
+                        if ui
+                            .add(egui::Slider::new(&mut alerts.qso_step, 0..=100).suffix(tr(" QSOs")))
+                            .on_hover_text(tr("0 turns the QSO count alert off"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                    if ui
+                        .checkbox(&mut alerts.best_rate, tr("New best 10-minute rate"))
+                        .on_hover_text(
+                            tr("Once a session, when the rate over the last 10 minutes \
+                                passes your personal best"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Accuracy Below:"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut alerts.accuracy_floor_pct, 0..=100)
+                                    .suffix("%"),
+                            )
+                            .on_hover_text(
+                                tr("When fewer of the last 10 QSOs than this were logged right. \
+                                    0 turns the accuracy alert off."),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                });
+
+                ui.add_space(4.0);
+                ui.label(tr("Stats Export Directory:"));
+                ui.horizontal(|ui| {
+                    let display = if settings.user.export_directory.is_empty() {
+                        tr("(current directory)").to_string()
+                    } else {
+                        settings.user.export_directory.clone()
+                    };
+                    ui.add(egui::TextEdit::singleline(&mut display.as_str()).desired_width(250.0));
+                    if ui.button(tr("Browse...")).clicked() {
+                        *file_dialog_target = Some(FileDialogTarget::ExportDirectory);
+                        file_dialog.pick_directory();
+                    }
+                    if !settings.user.export_directory.is_empty() && ui.button(tr("Clear")).clicked() {
+                        settings.user.export_directory.clear();
+                        *settings_changed = true;
+                    }
+                });
+            });
+
+        ui.add_space(8.0);
+
+        // Contest Settings
+        egui::CollapsingHeader::new(RichText::new(tr("Contest Settings")).strong())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Contest Type:"));
+                    egui::ComboBox::from_id_salt("contest_type")
+                        .selected_text(active_contest.display_name())
+                        .show_ui(ui, |ui| {
+                            for contest in contest_registry {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.contest.active_contest_id,
+                                        contest.id.to_string(),
+                                        contest.display_name,
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        });
+                    if let Some(help) = active_contest.help() {
+                        ui.label(RichText::new("(?)").weak())
+                            .on_hover_ui(|ui| {
+                                ui.set_max_width(360.0);
+                                render_contest_help(ui, &help);
+                            });
+                    }
+                });
+
+                render_custom_contests(ui, active_contest.id());
+
+                if ui
+                    .checkbox(
+                        &mut settings.contest.suggest_upcoming,
+                        tr("Suggest upcoming contests on launch"),
+                    )
+                    .on_hover_text(
+                        tr("Offer to switch to a contest that is running now or starts within 24 hours"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+            });
+
+        ui.add_space(8.0);
+
+        // Contest-specific settings
+        egui::CollapsingHeader::new(RichText::new(tr("Active Contest")).strong())
+            .default_open(true)
+            .show(ui, |ui| {
+                let contest_settings = settings.contest.settings_for_mut(active_contest);
+                render_contest_settings(
+                    ui,
+                    active_contest,
+                    contest_settings,
+                    settings_changed,
+                    file_dialog,
+                    file_dialog_target,
+                );
+                render_cut_number_override(
+                    ui,
+                    contest_settings,
+                    settings.simulation.cut_number_probability,
+                    settings_changed,
+                );
+                render_caller_format(ui, contest_settings, settings_changed);
+            });
+
+        ui.add_space(8.0);
+
+        // User message macros for the active contest
+        egui::CollapsingHeader::new(RichText::new(tr("Message Macros")).strong())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new(
+                        tr("Saved per contest. Tokens: {MYCALL} {HISCALL} {SERIAL} {EXCH}. \
+                            Prosigns can be entered as <SK>, <KN>, <BK>, <AR>."),
+                    )
+                    .small(),
+                );
+                ui.add_space(4.0);
+
+                let contest_id = settings.contest.active_contest_id.clone();
+                egui::Grid::new("macro_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        for key in MACRO_KEYS {
+                            ui.label(RichText::new(*key).strong().monospace());
+                            let mut template = settings
+                                .contest
+                                .macros
+                                .get(&contest_id)
+                                .and_then(|macros| macros.get(*key))
+                                .cloned()
+                                .unwrap_or_default();
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut template)
+                                        .desired_width(300.0)
+                                        .hint_text(tr("e.g. {HISCALL} {EXCH}")),
+                                )
+                                .changed()
+                            {
+                                let macros =
+                                    settings.contest.macros.entry(contest_id.clone()).or_default();
+                                if template.is_empty() {
+                                    macros.remove(*key);
+                                } else {
+                                    macros.insert(key.to_string(), template);
+                                }
+                                *settings_changed = true;
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        ui.add_space(8.0);
+
+        // Simulation Settings
+        egui::CollapsingHeader::new(RichText::new(tr("Simulation Settings")).strong())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Max Simultaneous Stations:"));
+                    if ui
+                        .add(egui::Slider::new(
+                            &mut settings.simulation.max_simultaneous_stations,
+                            1..=5,
+                        ))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Station Probability:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.station_probability,
+                                0.1..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                let density = &mut settings.simulation.dynamic_density;
+                if ui
+                    .checkbox(&mut density.enabled, tr("Dynamic Density"))
+                    .on_hover_text(
+                        tr("Fast, clean QSOs build the pileup and slow or busted ones thin it, \
+                            starting from the Station Probability"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+                if density.enabled {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Density Range:"));
+                        let mut changed = false;
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut density.min_probability)
+                                    .range(0.05..=1.0)
+                                    .speed(0.01)
+                                    .fixed_decimals(2),
+                            )
+                            .changed();
+                        ui.label("-");
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut density.max_probability)
+                                    .range(0.05..=1.0)
+                                    .speed(0.01)
+                                    .fixed_decimals(2),
+                            )
+                            .changed();
+                        if changed {
+                            if density.min_probability > density.max_probability {
+                                density.max_probability = density.min_probability;
+                            }
+                            *settings_changed = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Fast QSO (s):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut density.fast_qso_secs, 5.0..=60.0)
+                                    .fixed_decimals(0),
+                            )
+                            .on_hover_text(tr("A clean QSO this quick builds the pileup"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Slow QSO (s):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut density.slow_qso_secs, 10.0..=120.0)
+                                    .fixed_decimals(0),
+                            )
+                            .on_hover_text(tr("A QSO longer than this thins the pileup, as does a bust"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Build Step:"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut density.build_step, 0.0..=0.2)
+                                    .fixed_decimals(2),
+                            )
+                            .on_hover_text(tr("Station probability added by each fast, clean QSO"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Thin Step:"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut density.thin_step, 0.0..=0.3)
+                                    .fixed_decimals(2),
+                            )
+                            .on_hover_text(tr("Station probability taken off by each slow or busted QSO"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("WPM Range:"));
+                    let mut changed = false;
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut settings.simulation.wpm_min).range(10..=50))
+                        .changed();
+                    ui.label("-");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut settings.simulation.wpm_max).range(10..=50))
+                        .changed();
+                    if changed {
+                        // Ensure min <= max
+                        if settings.simulation.wpm_min > settings.simulation.wpm_max {
+                            settings.simulation.wpm_max = settings.simulation.wpm_min;
+                        }
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Filter Width (Hz):"));
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.frequency_spread_hz,
+                                100.0..=500.0,
+                            )
+                            .fixed_decimals(0),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                let wide = &mut settings.simulation.wide_pileup;
+                if ui
+                    .checkbox(&mut wide.enabled, tr("Wide Pileup"))
+                    .on_hover_text(
+                        tr("Spread callers well beyond the filter; tune them in with the RIT"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+                if wide.enabled {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Pileup Width (Hz):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut wide.spread_hz, 1000.0..=3000.0)
+                                    .fixed_decimals(0),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Receive Filter (Hz):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut wide.filter_hz, 100.0..=1000.0)
+                                    .fixed_decimals(0),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Signal Strength Range:"));
+                    let mut changed = false;
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.simulation.amplitude_min, 0.01..=1.0)
+                                .logarithmic(true)
+                                .fixed_decimals(3)
+                                .text(tr("min")),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.simulation.amplitude_max, 0.01..=1.0)
+                                .logarithmic(true)
+                                .fixed_decimals(3)
+                                .text(tr("max")),
+                        )
+                        .changed();
+                    if changed {
+                        if settings.simulation.amplitude_min > settings.simulation.amplitude_max {
+                            settings.simulation.amplitude_max = settings.simulation.amplitude_min;
+                        }
+                        *settings_changed = true;
+                    }
+                });
+
+                if ui
+                    .button(tr("Weak Signal (QRP) Preset"))
+                    .on_hover_text(
+                        tr("Callers a few dB over the noise with QSB on: signal strength \
+                            0.015-0.05, noise level at least 0.25, fading depth 0.6"),
+                    )
+                    .clicked()
+                {
+                    settings.apply_weak_signal_preset();
+                    *settings_changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Reaction Time (ms):"));
+                    let reaction = &mut settings.simulation.reaction_time;
+                    let mut changed = false;
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut reaction.min_ms)
+                                .range(0..=3000)
+                                .speed(10),
+                        )
+                        .changed();
+                    ui.label("-");
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut reaction.max_ms)
+                                .range(0..=3000)
+                                .speed(10),
+                        )
+                        .on_hover_text(tr("How soon on-time callers start after your CQ ends"))
+                        .changed();
+                    if changed {
+                        if reaction.min_ms > reaction.max_ms {
+                            reaction.max_ms = reaction.min_ms;
+                        }
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Reply Gap (ms):"));
+                    let timing = &mut settings.simulation.timing;
+                    let mut changed = false;
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut timing.reply_min_ms)
+                                .range(0..=3000)
+                                .speed(10),
+                        )
+                        .changed();
+                    ui.label("-");
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut timing.reply_max_ms)
+                                .range(0..=3000)
+                                .speed(10),
+                        )
+                        .on_hover_text(
+                            tr("How soon the station comes back after your exchange, AGN or \
+                                partial query; picked at random within the range each time"),
+                        )
+                        .changed();
+                    if changed {
+                        if timing.reply_min_ms > timing.reply_max_ms {
+                            timing.reply_max_ms = timing.reply_min_ms;
+                        }
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Post-CQ Gap (ms):"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut settings.simulation.timing.post_cq_gap_ms)
+                                .range(0..=2000)
+                                .speed(10),
+                        )
+                        .on_hover_text(
+                            tr("Pause after your CQ before callers start; their reaction time \
+                                comes on top of it"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Operator Tempo:"));
+                    for tempo in [
+                        OperatorTempo::Fast,
+                        OperatorTempo::Normal,
+                        OperatorTempo::Relaxed,
+                    ] {
+                        if ui
+                            .button(tempo_name(tempo))
+                            .on_hover_text(tr("Set the reaction time and gaps above for this pace"))
+                            .clicked()
+                        {
+                            settings.apply_tempo(tempo);
+                            *settings_changed = true;
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Tailgater Probability:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.reaction_time.tailgate_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(tr("Probability that a caller starts before your CQ has finished"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Late Caller Probability:"));
                     if ui
                         .add(
-                            egui::Slider::new(&mut settings.user.font_size, 10.0..=24.0)
-                                .fixed_decimals(0),
+                            egui::Slider::new(
+                                &mut settings.simulation.reaction_time.late_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
                         )
+                        .on_hover_text(tr("Probability that a caller responds a second or more late"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -59,108 +1040,64 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("AGN Message:");
+                    ui.label(tr("Calls Twice Probability:"));
                     if ui
-                        .text_edit_singleline(&mut settings.user.agn_message)
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.call_twice_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(tr("Probability that a caller sends their call twice"))
                         .changed()
                     {
                         *settings_changed = true;
                     }
                 });
 
-                if ui
-                    .checkbox(&mut settings.user.show_status_line, "Show Status Line")
-                    .changed()
-                {
-                    *settings_changed = true;
-                }
-
-                if ui
-                    .checkbox(&mut settings.user.show_main_hints, "Show Main Field Hints")
-                    .changed()
-                {
-                    *settings_changed = true;
-                }
-
-                ui.add_space(4.0);
-                ui.label("Stats Export Directory:");
-                ui.horizontal(|ui| {
-                    let display = if settings.user.export_directory.is_empty() {
-                        "(current directory)".to_string()
-                    } else {
-                        settings.user.export_directory.clone()
-                    };
-                    ui.add(egui::TextEdit::singleline(&mut display.as_str()).desired_width(250.0));
-                    if ui.button("Browse...").clicked() {
-                        *file_dialog_target = Some(FileDialogTarget::ExportDirectory);
-                        file_dialog.pick_directory();
-                    }
-                    if !settings.user.export_directory.is_empty() && ui.button("Clear").clicked() {
-                        settings.user.export_directory.clear();
-                        *settings_changed = true;
-                    }
-                });
-            });
-
-        ui.add_space(8.0);
-
-        // Contest Settings
-        egui::CollapsingHeader::new(RichText::new("Contest Settings").strong())
-            .default_open(true)
-            .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Contest Type:");
-                    egui::ComboBox::from_id_salt("contest_type")
-                        .selected_text(active_contest.display_name())
+                    ui.label(tr("Callers Give Up:"));
+                    egui::ComboBox::from_id_salt("give_up_behavior")
+                        .selected_text(give_up_name(settings.simulation.pileup.give_up))
                         .show_ui(ui, |ui| {
-                            for contest in contest_registry {
+                            for behavior in [
+                                GiveUpBehavior::Silent,
+                                GiveUpBehavior::FinalCall,
+                                GiveUpBehavior::Dits,
+                            ] {
                                 if ui
                                     .selectable_value(
-                                        &mut settings.contest.active_contest_id,
-                                        contest.id.to_string(),
-                                        contest.display_name,
+                                        &mut settings.simulation.pileup.give_up,
+                                        behavior,
+                                        give_up_name(behavior),
                                     )
                                     .changed()
                                 {
                                     *settings_changed = true;
                                 }
                             }
-                        });
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("What a caller who runs out of patience sends as they leave \
+                                (Session Stats counts them as Callers Lost)"),
+                        );
                 });
-            });
-
-        ui.add_space(8.0);
-
-        // Contest-specific settings
-        egui::CollapsingHeader::new(RichText::new("Active Contest").strong())
-            .default_open(true)
-            .show(ui, |ui| {
-                let contest_id = settings.contest.active_contest_id.clone();
-                let contest_settings = settings.contest.settings_for_mut(active_contest);
-                render_contest_settings(
-                    ui,
-                    active_contest,
-                    contest_settings,
-                    settings_changed,
-                    file_dialog,
-                    file_dialog_target,
-                    &contest_id,
-                );
-            });
 
-        ui.add_space(8.0);
-
-        // Simulation Settings
-        egui::CollapsingHeader::new(RichText::new("Simulation Settings").strong())
-            .default_open(true)
-            .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Max Simultaneous Stations:");
+                    ui.label(tr("Caller Needs Repeat Probability:"));
                     if ui
-                        .add(egui::Slider::new(
-                            &mut settings.simulation.max_simultaneous_stations,
-                            1..=5,
-                        ))
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.agn_request_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(
+                            tr("Probability that a caller will request you repeat your exchange"),
+                        )
                         .changed()
                     {
                         *settings_changed = true;
@@ -168,15 +1105,19 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Station Probability:");
+                    ui.label(tr("Fill Request Probability:"));
                     if ui
                         .add(
                             egui::Slider::new(
-                                &mut settings.simulation.station_probability,
-                                0.1..=1.0,
+                                &mut settings.simulation.fill_request_probability,
+                                0.0..=1.0,
                             )
                             .fixed_decimals(2),
                         )
+                        .on_hover_text(
+                            tr("Probability that a caller asks for one part of your exchange, \
+                                such as NR? or SEC?; answer with Shift+F2"),
+                        )
                         .changed()
                     {
                         *settings_changed = true;
@@ -184,33 +1125,39 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("WPM Range:");
-                    let mut changed = false;
-                    changed |= ui
-                        .add(egui::DragValue::new(&mut settings.simulation.wpm_min).range(10..=50))
-                        .changed();
-                    ui.label("-");
-                    changed |= ui
-                        .add(egui::DragValue::new(&mut settings.simulation.wpm_max).range(10..=50))
-                        .changed();
-                    if changed {
-                        // Ensure min <= max
-                        if settings.simulation.wpm_min > settings.simulation.wpm_max {
-                            settings.simulation.wpm_max = settings.simulation.wpm_min;
-                        }
+                    ui.label(tr("Doubles Probability:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.double_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(
+                            tr("Probability that two callers double: same pitch, same moment, \
+                                so neither call copies cleanly (needs Max Callers of 2 or more)"),
+                        )
+                        .changed()
+                    {
                         *settings_changed = true;
                     }
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Filter Width (Hz):");
+                    ui.label(tr("NIL Probability:"));
                     if ui
                         .add(
                             egui::Slider::new(
-                                &mut settings.simulation.frequency_spread_hz,
-                                100.0..=500.0,
+                                &mut settings.simulation.nil_probability,
+                                0.0..=1.0,
                             )
-                            .fixed_decimals(0),
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(
+                            tr("Probability that a caller you sent your exchange to, then dropped \
+                                by calling CQ without logging them, logged you anyway: a NIL \
+                                that costs two QSOs' points in the penalized score"),
                         )
                         .changed()
                     {
@@ -218,43 +1165,93 @@ pub fn render_settings_panel(
                     }
                 });
 
+                if ui
+                    .checkbox(
+                        &mut settings.simulation.require_sent_call,
+                        tr("Require Sent Call"),
+                    )
+                    .on_hover_text(
+                        tr("A QSO scores no points unless you sent the caller's call right at \
+                            least once, as in a real QSO where they must know you have them"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
                 ui.horizontal(|ui| {
-                    ui.label("Signal Strength Range:");
-                    let mut changed = false;
-                    changed |= ui
+                    ui.label(tr("Cut Number Probability:"));
+                    if ui
                         .add(
-                            egui::Slider::new(&mut settings.simulation.amplitude_min, 0.1..=1.0)
-                                .fixed_decimals(2)
-                                .text("min"),
+                            egui::Slider::new(
+                                &mut settings.simulation.cut_number_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
                         )
-                        .changed();
-                    changed |= ui
+                        .on_hover_text(
+                            tr("Probability that a caller sends numbers as cut numbers (5NN, T03, A2)"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Courtesy Word Probability:"));
+                    if ui
                         .add(
-                            egui::Slider::new(&mut settings.simulation.amplitude_max, 0.1..=1.0)
-                                .fixed_decimals(2)
-                                .text("max"),
+                            egui::Slider::new(
+                                &mut settings.simulation.courtesy_probability,
+                                0.0..=1.0,
+                            )
+                            .fixed_decimals(2),
                         )
-                        .changed();
-                    if changed {
-                        if settings.simulation.amplitude_min > settings.simulation.amplitude_max {
-                            settings.simulation.amplitude_max = settings.simulation.amplitude_min;
-                        }
+                        .on_hover_text(
+                            tr("Probability that a caller adds TU, EE, 73 or GL to their exchange \
+                                (not part of the exchange, so don't log it)"),
+                        )
+                        .changed()
+                    {
                         *settings_changed = true;
                     }
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Caller Needs Repeat Probability:");
+                    ui.label(tr("Weak Character Focus:"));
                     if ui
                         .add(
                             egui::Slider::new(
-                                &mut settings.simulation.agn_request_probability,
+                                &mut settings.simulation.weak_character_focus,
                                 0.0..=1.0,
                             )
                             .fixed_decimals(2),
                         )
                         .on_hover_text(
-                            "Probability that a caller will request you repeat your exchange",
+                            tr("How strongly callers are picked for the characters you miscopy \
+                                most this session (see Session Stats); 0 = off"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Call Match Threshold:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(
+                                &mut settings.simulation.call_match_threshold,
+                                0.1..=1.0,
+                            )
+                            .fixed_decimals(2),
+                        )
+                        .on_hover_text(
+                            tr("How close a partial or busted call must be to a caller's call \
+                                to select them. Characters that sound alike in CW (S/H, U/V, N/A) \
+                                count as closer than others; 1.0 = exact calls only"),
                         )
                         .changed()
                     {
@@ -265,9 +1262,9 @@ pub fn render_settings_panel(
                 if ui
                     .checkbox(
                         &mut settings.simulation.same_country_filter_enabled,
-                        "Filter Callers by Country",
+                        tr("Filter Callers by Country"),
                     )
-                    .on_hover_text("When enabled, controls how often callers are from your country")
+                    .on_hover_text(tr("When enabled, controls how often callers are from your country"))
                     .changed()
                 {
                     *settings_changed = true;
@@ -276,7 +1273,7 @@ pub fn render_settings_panel(
                 if settings.simulation.same_country_filter_enabled {
                     ui.horizontal(|ui| {
                         ui.add_space(20.0); // indent
-                        ui.label("Same Country Probability:");
+                        ui.label(tr("Same Country Probability:"));
                         if ui
                             .add(
                                 egui::Slider::new(
@@ -286,24 +1283,290 @@ pub fn render_settings_panel(
                                 .fixed_decimals(2),
                             )
                             .on_hover_text(
-                                "Probability that a caller will be from the same country as you",
+                                tr("Probability that a caller will be from the same country as you"),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                }
+
+                egui::CollapsingHeader::new(tr("Caller Geography"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new(
+                                tr("Relative share of callers from each continent (from cty.dat)"),
+                            )
+                            .small()
+                            .italics(),
+                        );
+                        let weights = &mut settings.simulation.continent_weights;
+                        for continent in Continent::ALL {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "{} ({}):",
+                                    continent_name(continent),
+                                    continent.code()
+                                ));
+                                if ui
+                                    .add(
+                                        egui::Slider::new(
+                                            weights.weight_mut(continent),
+                                            0.0..=1.0,
+                                        )
+                                        .fixed_decimals(2),
+                                    )
+                                    .on_hover_text(
+                                        tr("1.0 keeps every caller from this continent; lower values thin them out compared with the highest weight"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                        }
+                        if ui.button(tr("Reset")).clicked() {
+                            *weights = ContinentWeights::default();
+                            *settings_changed = true;
+                        }
+                    });
+
+                egui::CollapsingHeader::new(tr("Synthetic Callsigns"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let synthetic = &mut settings.simulation.synthetic_calls;
+                        if ui
+                            .checkbox(&mut synthetic.enabled, tr("Generate Callsigns"))
+                            .on_hover_text(
+                                tr("Make up calls from cty.dat prefixes instead of reading the \
+                                    callsign file, so there's no pool to memorize \
+                                    (CQ WPX and CQ WW only)"),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                        ui.add_enabled_ui(synthetic.enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Suffix Letters:"));
+                                for (index, weight) in
+                                    synthetic.suffix_length_weights.iter_mut().enumerate()
+                                {
+                                    ui.label(format!("{}:", index + 1));
+                                    if ui
+                                        .add(
+                                            egui::DragValue::new(weight)
+                                                .range(0.0..=10.0)
+                                                .speed(0.1),
+                                        )
+                                        .on_hover_text(
+                                            tr("Relative share of calls with this many letters \
+                                                after the digit"),
+                                        )
+                                        .changed()
+                                    {
+                                        *settings_changed = true;
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Portable Probability:"));
+                                if ui
+                                    .add(
+                                        egui::Slider::new(
+                                            &mut synthetic.portable_probability,
+                                            0.0..=1.0,
+                                        )
+                                        .fixed_decimals(2),
+                                    )
+                                    .on_hover_text(
+                                        tr("Probability a call ends in /P, /QRP, /M or a call area \
+                                            such as /7"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                        });
+                    });
+
+                egui::CollapsingHeader::new(tr("Lids and Bad Behavior"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let lids = &mut settings.simulation.lids;
+                        let rows: [(&'static str, &mut f32, &'static str); 4] = [
+                            (
+                                "Out of Turn Caller:",
+                                &mut lids.out_of_turn_probability,
+                                "Probability that a new station calls while the caller you're working sends their exchange",
+                            ),
+                            (
+                                "Calls Over QSO:",
+                                &mut lids.call_over_probability,
+                                "Probability that a caller you didn't pick calls again while you work someone else",
+                            ),
+                            (
+                                "Continuous Caller:",
+                                &mut lids.continuous_caller_probability,
+                                "Probability that a new caller never listens and calls after every transmission",
+                            ),
+                            (
+                                "Sends Your Call Wrong:",
+                                &mut lids.wrong_call_probability,
+                                "Probability that a caller sends your call with a mistake before their exchange",
+                            ),
+                        ];
+                        for (label, value, hover) in rows {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(label));
+                                if ui
+                                    .add(egui::Slider::new(value, 0.0..=1.0).fixed_decimals(2))
+                                    .on_hover_text(tr(hover))
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                        }
+                    });
+
+                egui::CollapsingHeader::new(tr("Background Signals"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let ambience = &mut settings.simulation.ambience;
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Events per Minute:"));
+                            if ui
+                                .add(
+                                    egui::Slider::new(&mut ambience.events_per_minute, 0.0..=6.0)
+                                        .fixed_decimals(1),
+                                )
+                                .on_hover_text(
+                                    tr("How often something unrelated to your run is heard on \
+                                        frequency (0 = never)"),
+                                )
+                                .changed()
+                            {
+                                *settings_changed = true;
+                            }
+                        });
+                        let kinds: [(&mut bool, &'static str, &'static str); 3] = [
+                            (
+                                &mut ambience.qrl_query,
+                                "QRL?",
+                                "Someone asks whether the frequency is in use",
+                            ),
+                            (
+                                &mut ambience.tune_up,
+                                "Tune-Ups",
+                                "Someone sends a brief carrier while tuning up",
+                            ),
+                            (
+                                &mut ambience.cq_on_frequency,
+                                "CQ on Frequency",
+                                "Someone calls CQ on your frequency while you aren't running",
+                            ),
+                        ];
+                        for (value, label, hover) in kinds {
+                            if ui
+                                .checkbox(value, tr(label))
+                                .on_hover_text(tr(hover))
+                                .changed()
+                            {
+                                *settings_changed = true;
+                            }
+                        }
+                    });
+
+                egui::CollapsingHeader::new(tr("Frequency Fights"))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let fight = &mut settings.simulation.frequency_fight;
+                        if ui
+                            .checkbox(&mut fight.enabled, tr("Frequency Fights"))
+                            .on_hover_text(
+                                tr("After a while running, a loud station may start calling CQ \
+                                    on your frequency. Hold on until they leave or QSY with Alt+Q."),
                             )
                             .changed()
                         {
                             *settings_changed = true;
                         }
+                        ui.add_enabled_ui(fight.enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Run Before a Fight (min):"));
+                                if ui
+                                    .add(
+                                        egui::Slider::new(&mut fight.min_run_minutes, 0.0..=60.0)
+                                            .fixed_decimals(0),
+                                    )
+                                    .on_hover_text(
+                                        tr("How long you hold a frequency before anyone tries to \
+                                            take it"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Chance per Minute:"));
+                                if ui
+                                    .add(
+                                        egui::Slider::new(&mut fight.chance_per_minute, 0.0..=1.0)
+                                            .fixed_decimals(2),
+                                    )
+                                    .on_hover_text(
+                                        tr("Chance each minute after that of someone turning up"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Callers Heard:"));
+                                if ui
+                                    .add(
+                                        egui::Slider::new(&mut fight.callers_heard, 0.0..=1.0)
+                                            .fixed_decimals(2),
+                                    )
+                                    .on_hover_text(
+                                        tr("Share of callers who still get through while someone \
+                                            is CQing over you"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr("QSY Time (s):"));
+                                if ui
+                                    .add(egui::Slider::new(&mut fight.qsy_secs, 0..=120))
+                                    .on_hover_text(
+                                        tr("How long finding a clear frequency takes after Alt+Q"),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            });
+                        });
                     });
-                }
             });
 
         ui.add_space(8.0);
 
         // Audio Settings
-        egui::CollapsingHeader::new(RichText::new("Audio Settings").strong())
+        egui::CollapsingHeader::new(RichText::new(tr("Audio Settings")).strong())
             .default_open(false)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Tone Frequency (Hz):");
+                    ui.label(tr("Tone Frequency (Hz):"));
                     if ui
                         .add(
                             egui::Slider::new(
@@ -319,7 +1582,7 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Noise Level:");
+                    ui.label(tr("Noise Level:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise_level, 0.0..=0.5)
@@ -332,13 +1595,13 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Noise Bandwidth (Hz):");
+                    ui.label(tr("Noise Bandwidth (Hz):"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise_bandwidth, 100.0..=1000.0)
                                 .fixed_decimals(0),
                         )
-                        .on_hover_text("Simulates receiver CW filter bandwidth")
+                        .on_hover_text(tr("Simulates receiver CW filter bandwidth"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -346,7 +1609,7 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Master Volume:");
+                    ui.label(tr("Master Volume:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.master_volume, 0.0..=1.0)
@@ -358,38 +1621,217 @@ pub fn render_settings_panel(
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label(tr("UI Sounds Volume:"));
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut settings.audio.ui_sound_volume, 0.0..=1.0)
+                                .fixed_decimals(2),
+                        )
+                        .on_hover_text(tr("Level of the milestone chimes, apart from the CW"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
                 if ui
                     .checkbox(
                         &mut settings.audio.mute_rx_during_tx,
-                        "Mute RX during TX (callers + noise)",
+                        tr("Mute RX during TX (callers + noise)"),
                     )
                     .changed()
                 {
                     *settings_changed = true;
                 }
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(&mut settings.audio.qsk.enabled, tr("QSK (full break-in)"))
+                        .on_hover_text(
+                            tr("Mute the receiver only while the key is down, so you hear \
+                                callers between your dits and dahs"),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                    ui.add_enabled_ui(settings.audio.qsk.enabled, |ui| {
+                        ui.label(tr("Delay (ms):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut settings.audio.qsk.delay_ms, 0.0..=500.0)
+                                    .fixed_decimals(0),
+                            )
+                            .on_hover_text(
+                                tr("How long the receiver stays muted after each element; \
+                                    longer delays behave like semi break-in"),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                });
                 if ui
                     .checkbox(
                         &mut settings.audio.mute_sidetone_during_tx,
-                        "Mute sidetone during TX",
+                        tr("Mute sidetone during TX"),
+                    )
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+                if ui
+                    .checkbox(&mut settings.audio.spatial_audio, tr("Spatial audio"))
+                    .on_hover_text(
+                        tr("Pan each caller left or right by how far it is from your pitch (needs stereo headphones)"),
                     )
                     .changed()
                 {
                     *settings_changed = true;
                 }
 
+                ui.horizontal(|ui| {
+                    ui.label(tr("Output:"));
+                    for (backend, label) in [
+                        (AudioBackendKind::SoundCard, "Sound card"),
+                        (AudioBackendKind::Jack, "JACK"),
+                        (AudioBackendKind::Silent, "None (silent)"),
+                    ] {
+                        // JACK only where this build can open it, unless a
+                        // shared settings file already picked it
+                        if backend == AudioBackendKind::Jack
+                            && !JACK_AVAILABLE
+                            && settings.audio.backend != backend
+                        {
+                            continue;
+                        }
+                        if ui
+                            .selectable_value(&mut settings.audio.backend, backend, tr(label))
+                            .on_hover_text(
+                                tr("Where the audio goes. JACK plays through a running JACK \
+                                    server for routing to other programs. With no output the \
+                                    session still runs in real time, without sound"),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("Buffer Size:"));
+                    egui::ComboBox::from_id_salt("audio_buffer_frames")
+                        .selected_text(buffer_frames_label(settings.audio.buffer_frames))
+                        .show_ui(ui, |ui| {
+                            for frames in BUFFER_FRAME_CHOICES {
+                                if ui
+                                    .selectable_value(
+                                        &mut settings.audio.buffer_frames,
+                                        frames,
+                                        buffer_frames_label(frames),
+                                    )
+                                    .changed()
+                                {
+                                    *settings_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            tr("Frames per audio callback. Raise this if CW sounds choppy; lower it for less delay"),
+                        );
+                });
+                if let Some(underruns) = audio_underruns {
+                    let text = tr_args("Underruns: {}", &[&underruns.to_string()]);
+                    if underruns > 0 {
+                        ui.label(RichText::new(text).color(egui::Color32::from_rgb(230, 150, 50)));
+                    } else {
+                        ui.label(RichText::new(text).weak());
+                    }
+                }
+
+                ui.add_space(10.0);
+                ui.label(RichText::new(tr("Sidetone Settings")).strong());
+                ui.separator();
+
+                let sidetone = &mut settings.audio.sidetone;
+                if ui
+                    .checkbox(&mut sidetone.match_rx_pitch, tr("Sidetone matches tone frequency"))
+                    .changed()
+                {
+                    *settings_changed = true;
+                }
+
+                if !sidetone.match_rx_pitch {
+                    ui.horizontal(|ui| {
+                        ui.add_space(20.0); // indent
+                        ui.label(tr("Sidetone Pitch (Hz):"));
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut sidetone.pitch_hz, 300.0..=1200.0)
+                                    .fixed_decimals(0),
+                            )
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Sidetone Level:"));
+                    if ui
+                        .add(egui::Slider::new(&mut sidetone.level, 0.0..=1.0).fixed_decimals(2))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Rise/Fall Time (ms):"));
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut sidetone.rise_time_ms, 1.0..=10.0)
+                                .fixed_decimals(1),
+                        )
+                        .on_hover_text(tr("Keying envelope; shorter sounds harder, longer sounds softer"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Timbre:"));
+                    for (timbre, label) in [
+                        (SidetoneTimbre::Sine, "Sine"),
+                        (SidetoneTimbre::Rich, "Rich"),
+                    ] {
+                        if ui
+                            .selectable_value(&mut sidetone.timbre, timbre, tr(label))
+                            .on_hover_text(tr("Rich adds a little harmonic content so your signal sounds different from the callers"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    }
+                });
+
                 ui.add_space(10.0);
-                ui.label(RichText::new("Static/QRN Settings").strong());
+                ui.label(RichText::new(tr("Static/QRN Settings")).strong());
                 ui.separator();
 
                 ui.horizontal(|ui| {
-                    ui.label("Crash Rate:");
+                    ui.label(tr("Crash Rate:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise.crash_rate, 0.0..=2.0)
                                 .fixed_decimals(1)
-                                .suffix("/sec"),
+                                .suffix(tr("/sec")),
                         )
-                        .on_hover_text("Static crashes per second")
+                        .on_hover_text(tr("Static crashes per second"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -397,13 +1839,13 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Crash Intensity:");
+                    ui.label(tr("Crash Intensity:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise.crash_intensity, 0.0..=1.0)
                                 .fixed_decimals(2),
                         )
-                        .on_hover_text("Volume of static crashes")
+                        .on_hover_text(tr("Volume of static crashes"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -411,14 +1853,14 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Pop Rate:");
+                    ui.label(tr("Pop Rate:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise.pop_rate, 0.0..=10.0)
                                 .fixed_decimals(1)
-                                .suffix("/sec"),
+                                .suffix(tr("/sec")),
                         )
-                        .on_hover_text("Clicks/pops per second")
+                        .on_hover_text(tr("Clicks/pops per second"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -426,13 +1868,13 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Pop Intensity:");
+                    ui.label(tr("Pop Intensity:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise.pop_intensity, 0.0..=1.0)
                                 .fixed_decimals(2),
                         )
-                        .on_hover_text("Volume of pops/clicks")
+                        .on_hover_text(tr("Volume of pops/clicks"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -440,13 +1882,13 @@ pub fn render_settings_panel(
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("QRN Intensity:");
+                    ui.label(tr("QRN Intensity:"));
                     if ui
                         .add(
                             egui::Slider::new(&mut settings.audio.noise.qrn_intensity, 0.0..=1.0)
                                 .fixed_decimals(2),
                         )
-                        .on_hover_text("Atmospheric noise rumble")
+                        .on_hover_text(tr("Atmospheric noise rumble"))
                         .changed()
                     {
                         *settings_changed = true;
@@ -454,12 +1896,12 @@ pub fn render_settings_panel(
                 });
 
                 ui.add_space(10.0);
-                ui.label(RichText::new("QSB (Fading) Settings").strong());
+                ui.label(RichText::new(tr("QSB (Fading) Settings")).strong());
                 ui.separator();
 
                 if ui
-                    .checkbox(&mut settings.audio.qsb.enabled, "Enable QSB")
-                    .on_hover_text("Simulate signal fading on caller signals")
+                    .checkbox(&mut settings.audio.qsb.enabled, tr("Enable QSB"))
+                    .on_hover_text(tr("Simulate signal fading on caller signals"))
                     .changed()
                 {
                     *settings_changed = true;
@@ -468,14 +1910,14 @@ pub fn render_settings_panel(
                 if settings.audio.qsb.enabled {
                     ui.horizontal(|ui| {
                         ui.add_space(20.0); // indent
-                        ui.label("Fade Depth:");
+                        ui.label(tr("Fade Depth:"));
                         if ui
                             .add(
                                 egui::Slider::new(&mut settings.audio.qsb.depth, 0.0..=1.0)
                                     .fixed_decimals(2),
                             )
                             .on_hover_text(
-                                "How much the signal fades (0 = none, 1 = full fade to silence)",
+                                tr("How much the signal fades (0 = none, 1 = full fade to silence)"),
                             )
                             .changed()
                         {
@@ -485,24 +1927,267 @@ pub fn render_settings_panel(
 
                     ui.horizontal(|ui| {
                         ui.add_space(20.0); // indent
-                        ui.label("Fade Rate:");
+                        ui.label(tr("Fade Rate:"));
                         if ui
                             .add(
                                 egui::Slider::new(&mut settings.audio.qsb.rate, 1.0..=20.0)
                                     .fixed_decimals(1)
-                                    .suffix(" cpm"),
+                                    .suffix(tr(" cpm")),
+                            )
+                            .on_hover_text(tr("Fading cycles per minute (higher = faster fading)"))
+                            .changed()
+                        {
+                            *settings_changed = true;
+                        }
+                    });
+                }
+
+                ui.add_space(10.0);
+                ui.label(RichText::new(tr("AGC Settings")).strong());
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("AGC:"));
+                    for (mode, label) in [
+                        (AgcMode::Off, "Off"),
+                        (AgcMode::Fast, "Fast"),
+                        (AgcMode::Slow, "Slow"),
+                    ] {
+                        if ui
+                            .selectable_value(&mut settings.audio.agc, mode, tr(label))
+                            .on_hover_text(
+                                tr("Receiver gain control: loud callers and static crashes \
+                                    pull the gain down, then it recovers (quickly with Fast, \
+                                    slowly with Slow)"),
                             )
-                            .on_hover_text("Fading cycles per minute (higher = faster fading)")
                             .changed()
                         {
                             *settings_changed = true;
                         }
+                    }
+                });
+            });
+
+        ui.add_space(8.0);
+
+        // Downloaded data and where it comes from
+        egui::CollapsingHeader::new(RichText::new(tr("Data Updates")).strong())
+            .default_open(false)
+            .show(ui, |ui| {
+                for (file, version) in data_update::versions() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}:", file.name()));
+                        ui.label(version);
                     });
                 }
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.label(tr("cty.dat URL:"));
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut settings.data_updates.cty_url)
+                                .desired_width(300.0),
+                        )
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("CWT roster URL:"));
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut settings.data_updates.cwt_roster_url)
+                                .hint_text(tr("CWT callsign file format"))
+                                .desired_width(300.0),
+                        )
+                        .on_hover_text(tr(
+                            "Used in place of the bundled cwt_callsigns.txt; leave blank to skip",
+                        ))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+                ui.label(
+                    RichText::new(tr("Download with File > Check for Data Updates"))
+                        .small()
+                        .weak(),
+                );
+            });
+
+        ui.add_space(8.0);
+
+        // Settings bundle import/export
+        egui::CollapsingHeader::new(RichText::new(tr("Share Settings")).strong())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new(
+                        tr("Export or import the full configuration as a single file. \
+                            Your callsign and export directory are never overwritten on import."),
+                    )
+                    .small(),
+                );
+                ui.add_space(4.0);
+
+                let include_id = ui.make_persistent_id("bundle_include_file_references");
+                let mut include_file_references =
+                    ui.data_mut(|d| *d.get_temp_mut_or_default::<bool>(include_id));
+                if ui
+                    .checkbox(&mut include_file_references, tr("Include callsign file paths"))
+                    .on_hover_text(tr("File paths are usually only valid on the exporting machine"))
+                    .changed()
+                {
+                    ui.data_mut(|d| d.insert_temp(include_id, include_file_references));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Export Settings...")).clicked() {
+                        *file_dialog_target = Some(FileDialogTarget::ExportSettingsBundle {
+                            include_file_references,
+                        });
+                        file_dialog.save_file();
+                    }
+                    if ui.button(tr("Import Settings...")).clicked() {
+                        *file_dialog_target = Some(FileDialogTarget::ImportSettingsBundle);
+                        file_dialog.pick_file();
+                    }
+                });
             });
     });
 }
 
+fn render_cut_number_override(
+    ui: &mut egui::Ui,
+    contest_settings: &mut toml::Value,
+    global_probability: f32,
+    settings_changed: &mut bool,
+) {
+    let toml::Value::Table(table) = contest_settings else {
+        return;
+    };
+
+    let current = table
+        .get(CUT_NUMBER_PROBABILITY_KEY)
+        .and_then(|value| value.as_float());
+    let mut override_enabled = current.is_some();
+    if ui
+        .checkbox(&mut override_enabled, tr("Override Cut Number Probability"))
+        .on_hover_text(tr(
+            "Use a different cut number probability for this contest",
+        ))
+        .changed()
+    {
+        if override_enabled {
+            table.insert(
+                CUT_NUMBER_PROBABILITY_KEY.to_string(),
+                toml::Value::Float(global_probability as f64),
+            );
+        } else {
+            table.remove(CUT_NUMBER_PROBABILITY_KEY);
+        }
+        *settings_changed = true;
+    }
+
+    if let Some(current) = current.filter(|_| override_enabled) {
+        let mut probability = current as f32;
+        ui.horizontal(|ui| {
+            ui.label(tr("Contest Cut Number Probability:"));
+            if ui
+                .add(egui::Slider::new(&mut probability, 0.0..=1.0).fixed_decimals(2))
+                .changed()
+            {
+                table.insert(
+                    CUT_NUMBER_PROBABILITY_KEY.to_string(),
+                    toml::Value::Float(probability as f64),
+                );
+                *settings_changed = true;
+            }
+        });
+    }
+}
+
+/// How often callers in this contest put extra calls ahead of their exchange
+fn render_caller_format(
+    ui: &mut egui::Ui,
+    contest_settings: &mut toml::Value,
+    settings_changed: &mut bool,
+) {
+    let toml::Value::Table(table) = contest_settings else {
+        return;
+    };
+
+    for (key, label, hover) in [
+        (
+            OUR_CALL_PROBABILITY_KEY,
+            "Callers Send Your Call:",
+            "Probability a caller starts their exchange with your call, as in W1AW 5NN 05",
+        ),
+        (
+            DE_PROBABILITY_KEY,
+            "Callers Sign with DE:",
+            "Probability a caller signs with DE and their call, as in DE K3LR 5NN 05",
+        ),
+        (
+            SIGN_TWICE_PROBABILITY_KEY,
+            "Callers Sign Twice:",
+            "Probability a caller sends their own call twice before their exchange",
+        ),
+    ] {
+        let mut probability = table
+            .get(key)
+            .and_then(|value| value.as_float())
+            .unwrap_or(0.0) as f32;
+        ui.horizontal(|ui| {
+            ui.label(tr(label));
+            if ui
+                .add(egui::Slider::new(&mut probability, 0.0..=1.0).fixed_decimals(2))
+                .on_hover_text(tr(hover))
+                .changed()
+            {
+                if probability > 0.0 {
+                    table.insert(key.to_string(), toml::Value::Float(probability as f64));
+                } else {
+                    table.remove(key);
+                }
+                *settings_changed = true;
+            }
+        });
+    }
+}
+
+/// Where custom contest files are read from, and any that failed to load
+fn render_custom_contests(ui: &mut egui::Ui, active_id: &str) {
+    let custom = custom::loaded();
+    if let Some(def) = custom.contests.iter().find(|def| def.id == active_id) {
+        ui.label(
+            RichText::new(tr_args(
+                "Custom contest from {}",
+                &[&def.path.display().to_string()],
+            ))
+            .small()
+            .weak(),
+        );
+    }
+    ui.label(
+        RichText::new(tr_args(
+            "Custom contests: .toml files in {}",
+            &[&custom::contests_dir().display().to_string()],
+        ))
+        .small()
+        .weak(),
+    )
+    .on_hover_text(tr("See docs/ADDING_CONTESTS.md for the file format"));
+    for error in &custom.errors {
+        ui.label(
+            RichText::new(error)
+                .small()
+                .color(egui::Color32::from_rgb(230, 150, 50)),
+        );
+    }
+}
+
 fn render_contest_settings(
     ui: &mut egui::Ui,
     contest: &dyn Contest,
@@ -510,7 +2195,6 @@ fn render_contest_settings(
     settings_changed: &mut bool,
     file_dialog: &mut FileDialog,
     file_dialog_target: &mut Option<FileDialogTarget>,
-    contest_id: &str,
 ) {
     let mut contest_fields = Vec::new();
     let mut user_fields = Vec::new();
@@ -523,45 +2207,49 @@ fn render_contest_settings(
     }
 
     if !contest_fields.is_empty() {
-        ui.label(RichText::new("Contest").strong());
+        ui.label(RichText::new(tr("Contest")).strong());
         render_setting_group(
             ui,
+            contest,
             &contest_fields,
             contest_settings,
             settings_changed,
             file_dialog,
             file_dialog_target,
-            contest_id,
         );
         ui.add_space(6.0);
     }
 
     if !user_fields.is_empty() {
-        ui.label(RichText::new("Your Exchange").strong());
+        ui.label(RichText::new(tr("Your Exchange")).strong());
         render_setting_group(
             ui,
+            contest,
             &user_fields,
             contest_settings,
             settings_changed,
             file_dialog,
             file_dialog_target,
-            contest_id,
         );
     }
 }
 
 fn render_setting_group(
     ui: &mut egui::Ui,
+    contest: &dyn Contest,
     fields: &[crate::contest::SettingField],
     contest_settings: &mut toml::Value,
     settings_changed: &mut bool,
     file_dialog: &mut FileDialog,
     file_dialog_target: &mut Option<FileDialogTarget>,
-    contest_id: &str,
 ) {
+    let contest_id = contest.id();
+    let settings_snapshot = contest_settings.clone();
+    let defaults = contest.default_settings();
     let table = contest_settings_table(contest_settings);
 
     for field in fields {
+        let check_id = egui::Id::new(("file_check", contest_id, field.key));
         ui.horizontal(|ui| {
             ui.label(field.label);
             match field.kind {
@@ -577,13 +2265,30 @@ fn render_setting_group(
                             .interactive(false)
                             .desired_width(250.0),
                     );
-                    if ui.button("Browse...").clicked() {
+                    if ui.button(tr("Browse...")).clicked() {
                         *file_dialog_target = Some(FileDialogTarget::ContestSetting {
                             contest_id: contest_id.to_string(),
                             key: field.key.to_string(),
                         });
                         file_dialog.pick_file();
                     }
+                    if ui
+                        .button(tr("Test file"))
+                        .on_hover_text(tr(
+                            "Read the file as this contest would and show what it found",
+                        ))
+                        .clicked()
+                    {
+                        let check =
+                            contest.check_callsign_file(field.key, &value, &settings_snapshot);
+                        ui.data_mut(|d| d.insert_temp(check_id, (value.clone(), check)));
+                    }
+                    // Files that are off by default can be turned off again
+                    let optional = defaults.get(field.key).and_then(|v| v.as_str()) == Some("");
+                    if optional && !value.is_empty() && ui.button(tr("Clear")).clicked() {
+                        table.insert(field.key.to_string(), toml::Value::String(String::new()));
+                        *settings_changed = true;
+                    }
                 }
                 SettingFieldKind::Text => {
                     let width_px = setting_field_width(ui, field.width_chars);
@@ -624,8 +2329,87 @@ fn render_setting_group(
                         *settings_changed = true;
                     }
                 }
+                SettingFieldKind::Toggle => {
+                    let mut value = table
+                        .get(field.key)
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    if ui.checkbox(&mut value, "").changed() {
+                        table.insert(field.key.to_string(), toml::Value::Boolean(value));
+                        *settings_changed = true;
+                    }
+                }
             }
         });
+        if field.kind == SettingFieldKind::FilePath {
+            let path = table.get(field.key).and_then(|v| v.as_str()).unwrap_or("");
+            let check = ui.data(|d| d.get_temp::<(String, Result<FileCheck, String>)>(check_id));
+            // A result for a file no longer chosen is stale
+            if let Some((_, check)) = check.filter(|(checked, _)| checked == path) {
+                render_file_check(ui, &check);
+            }
+        }
+    }
+}
+
+/// How many skipped lines "Test file" lists
+const REJECTS_SHOWN: usize = 5;
+
+fn render_file_check(ui: &mut egui::Ui, check: &Result<FileCheck, String>) {
+    let warning = egui::Color32::from_rgb(230, 150, 50);
+    let check = match check {
+        Ok(check) => check,
+        Err(e) => {
+            ui.label(
+                RichText::new(tr_args("Couldn't read the file: {}", &[e]))
+                    .small()
+                    .color(warning),
+            );
+            return;
+        }
+    };
+    ui.label(
+        RichText::new(tr_args(
+            "{} usable entries, {} lines skipped",
+            &[&check.valid.to_string(), &check.rejects.len().to_string()],
+        ))
+        .small(),
+    );
+    if check.valid == 0 {
+        ui.label(
+            RichText::new(tr("Nothing usable; the built-in calls will be used"))
+                .small()
+                .color(warning),
+        );
+    }
+    for reject in check.rejects.iter().take(REJECTS_SHOWN) {
+        ui.label(
+            RichText::new(tr_args(
+                "Line {}: {} ({})",
+                &[&reject.line.to_string(), &reject.text, &reject.reason],
+            ))
+            .small()
+            .weak(),
+        );
+    }
+    if check.rejects.len() > REJECTS_SHOWN {
+        ui.label(
+            RichText::new(tr_args(
+                "...and {} more",
+                &[&(check.rejects.len() - REJECTS_SHOWN).to_string()],
+            ))
+            .small()
+            .weak(),
+        );
+    }
+    if let Some((call, exchange)) = &check.sample {
+        ui.label(
+            RichText::new(tr_args(
+                "Sample caller: {} {}",
+                &[call, &exchange.fields.join(" ")],
+            ))
+            .small(),
+        );
     }
 }
 
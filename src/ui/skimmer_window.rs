@@ -0,0 +1,90 @@
+use crate::config::SkimmerSettings;
+use crate::i18n::{tr, tr_args};
+use crate::skimmer::Skimmer;
+use egui::RichText;
+use std::time::Instant;
+
+pub fn render_skimmer_window(
+    ctx: &egui::Context,
+    skimmer: &Skimmer,
+    settings: &mut SkimmerSettings,
+    settings_changed: &mut bool,
+    show_skimmer: &mut bool,
+) {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("skimmer_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Skimmer"))
+            .with_inner_size([360.0, 400.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Accuracy:"));
+                    if ui
+                        .add(egui::Slider::new(&mut settings.accuracy, 0.0..=1.0).fixed_decimals(2))
+                        .on_hover_text(tr("Share of spots with the call decoded right"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("Delay (ms):"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut settings.delay_ms)
+                                .range(0..=10000)
+                                .speed(50),
+                        )
+                        .on_hover_text(tr("How long after the call ends the spot appears"))
+                        .changed()
+                    {
+                        *settings_changed = true;
+                    }
+                });
+                ui.label(
+                    RichText::new(tr("Spots can be wrong: check every call by ear"))
+                        .small()
+                        .italics(),
+                );
+
+                ui.add_space(4.0);
+                ui.separator();
+                ui.add_space(4.0);
+
+                if skimmer.spots().is_empty() {
+                    ui.label(tr("No spots yet"));
+                    return;
+                }
+
+                let now = Instant::now();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("skimmer_grid")
+                        .num_columns(4)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(tr("Call")).strong());
+                            ui.label(RichText::new(tr("Offset")).strong());
+                            ui.label(RichText::new(tr("WPM")).strong());
+                            ui.label(RichText::new(tr("Age")).strong());
+                            ui.end_row();
+
+                            for spot in skimmer.spots() {
+                                ui.label(RichText::new(&spot.callsign).monospace());
+                                ui.label(format!("{:+.0} Hz", spot.offset_hz));
+                                ui.label(format!("{}", spot.wpm));
+                                let age = now.saturating_duration_since(spot.spotted_at);
+                                ui.label(tr_args("{} s", &[&age.as_secs().to_string()]));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_skimmer = false;
+            }
+        },
+    );
+}
@@ -1,37 +1,62 @@
 use crate::config::AppSettings;
-use crate::export::export_session_stats;
-use crate::stats::SessionStats;
-use crate::ui::render_export_dialog;
+use crate::export::{export_session_stats, ExportFormat};
+use crate::i18n::{tr, tr_args};
+use crate::stats::{format_counts, format_seconds, DensitySample, QsoRecord, SessionStats};
+use crate::ui::{render_export_dialog, Palette};
 use egui::RichText;
 
+/// Returns a QSO the user marked as counting (true) or not (false)
 pub fn render_stats_window(
     ctx: &egui::Context,
     settings: &AppSettings,
     stats: &SessionStats,
     show_stats: &mut bool,
     export_result: &mut Option<String>,
-) {
+) -> Option<(usize, bool)> {
+    let mut counted_change = None;
     ctx.show_viewport_immediate(
         egui::ViewportId::from_hash_of("stats_viewport"),
         egui::ViewportBuilder::default()
-            .with_title("Session Statistics")
+            .with_title(tr("Session Statistics"))
             .with_inner_size([450.0, 550.0]),
         |ctx, _class| {
             egui::CentralPanel::default().show(ctx, |ui| {
-                // Centered Export Stats button at the top
-                ui.vertical_centered(|ui| {
-                    if ui.button("Export Stats").clicked() {
-                        match export_session_stats(settings, stats) {
+                // Export format and button at the top
+                let format_id = ui.id().with("export_format");
+                let mut format =
+                    ui.data_mut(|d| *d.get_temp_mut_or_default::<ExportFormat>(format_id));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("export_format")
+                        .selected_text(format.label())
+                        .show_ui(ui, |ui| {
+                            for option in ExportFormat::ALL {
+                                ui.selectable_value(&mut format, option, option.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr(
+                            "Markdown for reading, CSV (one row per QSO) for spreadsheets, JSON (full analysis) for scripts",
+                        ));
+                    if ui.button(tr("Export Stats")).clicked() {
+                        match export_session_stats(settings, stats, format) {
                             Ok(filename) => *export_result = Some(filename),
-                            Err(e) => *export_result = Some(format!("Error: {}", e)),
+                            Err(e) => {
+                                *export_result = Some(tr_args("Error: {}", &[&e.to_string()]))
+                            }
                         }
                     }
                 });
+                ui.data_mut(|d| d.insert_temp(format_id, format));
                 ui.add_space(8.0);
                 ui.separator();
                 ui.add_space(8.0);
 
-                render_stats_content(ui, stats);
+                render_stats_content(
+                    ui,
+                    stats,
+                    &Palette::for_user(&settings.user),
+                    &mut counted_change,
+                );
             });
 
             // Render export dialog within this viewport
@@ -42,34 +67,55 @@ pub fn render_stats_window(
             }
         },
     );
+    counted_change
 }
 
-fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
+fn render_stats_content(
+    ui: &mut egui::Ui,
+    stats: &SessionStats,
+    palette: &Palette,
+    counted_change: &mut Option<(usize, bool)>,
+) {
     let analysis = stats.analyze();
 
     egui::ScrollArea::vertical().show(ui, |ui| {
         // Summary section
-        ui.heading("Session Summary");
+        ui.heading(tr("Session Summary"));
         ui.add_space(8.0);
 
         egui::Grid::new("summary_grid")
             .num_columns(2)
             .spacing([40.0, 4.0])
             .show(ui, |ui| {
-                ui.label("Total QSOs:");
+                ui.label(tr("Total QSOs:"));
                 ui.label(format!("{}", analysis.total_qsos));
                 ui.end_row();
 
-                ui.label("Correct QSOs:");
+                ui.label(tr("Correct QSOs:"));
                 ui.label(format!(
                     "{} ({:.1}%)",
                     analysis.correct_qsos, analysis.correct_rate
                 ));
                 ui.end_row();
 
-                ui.label("Total Points:");
+                ui.label(tr("Total Points:"));
                 ui.label(format!("{}", analysis.total_points));
                 ui.end_row();
+
+                ui.label(tr("Penalties:"));
+                ui.label(format!("-{}", analysis.penalty_points))
+                    .on_hover_text(tr(
+                        "Log checking takes out a busted call or exchange and charges one more QSO's points; a NIL, a QSO you dropped after sending your exchange that the other station logged, costs two",
+                    ));
+                ui.end_row();
+
+                ui.label(tr("NILs:"));
+                ui.label(format!("{}", analysis.nil_count));
+                ui.end_row();
+
+                ui.label(tr("Penalized Score:"));
+                ui.label(RichText::new(format!("{}", analysis.penalized_points)).strong());
+                ui.end_row();
             });
 
         ui.add_space(4.0);
@@ -79,26 +125,44 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.add_space(8.0);
 
         // Accuracy section
-        ui.heading("Accuracy");
+        ui.heading(tr("Accuracy"));
         ui.add_space(8.0);
 
         egui::Grid::new("accuracy_grid")
             .num_columns(2)
             .spacing([40.0, 4.0])
             .show(ui, |ui| {
-                ui.label("Callsign Accuracy:");
+                ui.label(tr("Callsign Accuracy:"));
                 ui.label(format!(
                     "{}/{} ({:.1}%)",
                     analysis.correct_callsigns, analysis.total_qsos, analysis.callsign_accuracy
                 ));
                 ui.end_row();
 
-                ui.label("Exchange Accuracy:");
+                ui.label(tr("Exchange Accuracy:"));
                 ui.label(format!(
                     "{}/{} ({:.1}%)",
                     analysis.correct_exchanges, analysis.total_qsos, analysis.exchange_accuracy
                 ));
                 ui.end_row();
+
+                // How each exchange element was copied
+                for field in &analysis.field_accuracy {
+                    ui.label(format!("  {}:", field.label));
+                    ui.label(format!(
+                        "{}/{} ({:.1}%)",
+                        field.correct, field.total, field.accuracy_pct
+                    ));
+                    ui.end_row();
+                }
+                if analysis.impossible_entry_count > 0 {
+                    ui.label(format!("  {}", tr("Impossible Entries:")));
+                    ui.label(format!("{}", analysis.impossible_entry_count))
+                        .on_hover_text(tr(
+                            "Wrong fields logged with something that can't be right, like a section that doesn't exist, rather than a miscopy",
+                        ));
+                    ui.end_row();
+                }
             });
 
         ui.add_space(16.0);
@@ -106,33 +170,33 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.add_space(8.0);
 
         // Streaks section
-        ui.heading("Streaks");
+        ui.heading(tr("Streaks"));
         ui.add_space(8.0);
 
         egui::Grid::new("streaks_grid")
             .num_columns(2)
             .spacing([40.0, 4.0])
             .show(ui, |ui| {
-                ui.label("Current Clean:");
+                ui.label(tr("Current Clean:"));
                 ui.label(format!("{}", analysis.streaks.current_clean));
                 ui.end_row();
 
-                ui.label("Max Clean:");
+                ui.label(tr("Max Clean:"));
                 ui.label(format!("{}", analysis.streaks.max_clean));
                 ui.end_row();
 
-                ui.label("Current Error:");
+                ui.label(tr("Current Error:"));
                 ui.label(format!("{}", analysis.streaks.current_error));
                 ui.end_row();
 
-                ui.label("Max Error:");
+                ui.label(tr("Max Error:"));
                 ui.label(format!("{}", analysis.streaks.max_error));
                 ui.end_row();
             });
 
         ui.add_space(4.0);
         ui.label(
-            RichText::new("Clean = callsign and exchange both correct")
+            RichText::new(tr("Clean = callsign and exchange both correct"))
                 .small()
                 .italics(),
         );
@@ -142,26 +206,33 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.add_space(8.0);
 
         // F5/F8 Usage section
-        ui.heading("F5/F8 Usage");
+        ui.heading(tr("F5/F8 Usage"));
         ui.add_space(8.0);
 
         egui::Grid::new("agn_grid")
             .num_columns(2)
             .spacing([40.0, 4.0])
             .show(ui, |ui| {
-                ui.label("F5 (His Call):");
+                ui.label(tr("F5 (His Call):"));
                 ui.label(format!("{}", analysis.f5_callsign_count));
                 ui.end_row();
 
-                ui.label("F8 Callsign:");
+                ui.label(tr("Wrong Partial Match:"));
+                ui.label(format!("{}", analysis.wrong_station_count))
+                    .on_hover_text(tr(
+                    "QSOs logged with the call of another station that answered your partial query",
+                ));
+                ui.end_row();
+
+                ui.label(tr("F8 Callsign:"));
                 ui.label(format!("{}", analysis.agn_callsign_count));
                 ui.end_row();
 
-                ui.label("F8 Exchange:");
+                ui.label(tr("F8 Exchange:"));
                 ui.label(format!("{}", analysis.agn_exchange_count));
                 ui.end_row();
 
-                ui.label("Total with F8:");
+                ui.label(tr("Total with F8:"));
                 if analysis.total_qsos > 0 {
                     let agn_pct =
                         (analysis.agn_any_count as f32 / analysis.total_qsos as f32) * 100.0;
@@ -170,14 +241,245 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                     ui.label("0");
                 }
                 ui.end_row();
+
+                if !analysis.repeats_by_element.is_empty() {
+                    ui.label(tr("Repeats Asked For:"));
+                    ui.label(format_counts(&analysis.repeats_by_element))
+                        .on_hover_text(tr(
+                            "What you asked callers to repeat, most often first (Shift+F8 asks \
+                             for just the element in the focused box)",
+                        ));
+                    ui.end_row();
+                }
+
+                if analysis.fill_request_count > 0 {
+                    ui.label(tr("Fill Requests:"));
+                    ui.label(format!("{}", analysis.fill_request_count))
+                        .on_hover_text(tr(
+                            "QSOs where the caller asked for one part of your exchange",
+                        ));
+                    ui.end_row();
+
+                    let fill_pct = analysis.fill_element_count as f32
+                        / analysis.fill_request_count as f32
+                        * 100.0;
+                    ui.label(tr("Answered with Fill:"));
+                    ui.label(format!("{} ({:.1}%)", analysis.fill_element_count, fill_pct))
+                        .on_hover_text(tr("Sent just the part asked for (Shift+F2)"));
+                    ui.end_row();
+
+                    ui.label(tr("Resent Whole Exchange:"));
+                    ui.label(format!("{}", analysis.fill_full_count));
+                    ui.end_row();
+
+                    ui.label(tr("Fill Not Sent:"));
+                    ui.label(format!(
+                        "{}",
+                        analysis.fill_request_count
+                            - analysis.fill_element_count
+                            - analysis.fill_full_count
+                    ))
+                    .on_hover_text(tr("QSOs logged without answering the fill request"));
+                    ui.end_row();
+                }
+
+                if analysis.prefill_count > 0 {
+                    ui.label(tr("Pre-filled:"));
+                    ui.label(format!("{}", analysis.prefill_count));
+                    ui.end_row();
+
+                    ui.label(tr("Pre-fill Unchecked:"));
+                    ui.label(format!("{}", analysis.prefill_unverified_count))
+                        .on_hover_text(tr(
+                            "QSOs logged without moving into the pre-filled fields to check them",
+                        ));
+                    ui.end_row();
+                }
+
+                if analysis.sent_busted_call_count > 0 {
+                    ui.label(tr("Sent Busted Call:"));
+                    ui.label(format!("{}", analysis.sent_busted_call_count))
+                        .on_hover_text(tr(
+                            "QSOs where every call you sent the station was wrong",
+                        ));
+                    ui.end_row();
+                }
+
+                if analysis.early_count > 0 {
+                    ui.label(tr("Logged Early:"));
+                    ui.label(tr_args(
+                        "{} ({} right)",
+                        &[
+                            &analysis.early_count.to_string(),
+                            &analysis.early_correct_count.to_string(),
+                        ],
+                    ))
+                    .on_hover_text(tr(
+                        "QSOs logged before the caller finished sending the exchange",
+                    ));
+                    ui.end_row();
+                }
             });
 
         ui.add_space(16.0);
         ui.separator();
         ui.add_space(8.0);
 
+        // Doubles section
+        ui.heading(tr("Doubles"));
+        ui.add_space(8.0);
+
+        let doubles = &analysis.doubles;
+        if doubles.total == 0 {
+            ui.label(tr("No doubles heard yet"));
+        } else {
+            egui::Grid::new("doubles_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(tr("Doubles Heard:"));
+                    ui.label(format!("{}", doubles.total));
+                    ui.end_row();
+
+                    ui.label(tr("Asked Again:"));
+                    ui.label(format!("{}", doubles.asked_again))
+                        .on_hover_text(tr("Sent AGN or a partial query instead of guessing"));
+                    ui.end_row();
+
+                    ui.label(tr("Pulled Out a Call:"));
+                    ui.label(format!("{}", doubles.pulled_out))
+                        .on_hover_text(tr("Came back to one of the two calls, copied right"));
+                    ui.end_row();
+
+                    ui.label(tr("Garbled Call:"));
+                    ui.label(RichText::new(format!("{}", doubles.garbled)).color(
+                        if doubles.garbled > 0 {
+                            palette.bad
+                        } else {
+                            palette.good
+                        },
+                    ))
+                    .on_hover_text(tr("Came back to a call that was neither station"));
+                    ui.end_row();
+
+                    ui.label(tr("Resolved:"));
+                    ui.label(format!("{:.1}%", doubles.success_pct));
+                    ui.end_row();
+                });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        // Efficiency section: CQ cycle timing
+        ui.heading(tr("Efficiency"));
+        ui.add_space(8.0);
+
+        let efficiency = &analysis.efficiency;
+        if efficiency.session_time.is_zero() {
+            ui.label(tr("No CQs sent yet"));
+        } else {
+            egui::Grid::new("efficiency_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(tr("CQs Sent:"));
+                    ui.label(format!("{}", efficiency.cq_count));
+                    ui.end_row();
+
+                    ui.label(tr("Unanswered CQs:"));
+                    ui.label(format!("{}", efficiency.unanswered_cqs));
+                    ui.end_row();
+
+                    ui.label(tr("TU to Next CQ:"));
+                    match (efficiency.avg_tu_to_cq, efficiency.median_tu_to_cq) {
+                        (Some(avg), Some(median)) => {
+                            ui.label(tr_args(
+                                "{} avg, {} median",
+                                &[&format_seconds(avg), &format_seconds(median)],
+                            ))
+                            .on_hover_text(tr(
+                                "From the end of your TU to the start of your next CQ \
+                                 (gaps over 30 s count as breaks)",
+                            ));
+                        }
+                        _ => {
+                            ui.label("-");
+                        }
+                    }
+                    ui.end_row();
+
+                    ui.label(tr("Dead Time:"));
+                    ui.label(format_seconds(efficiency.dead_time))
+                        .on_hover_text(tr("Listening after a CQ with nobody calling"));
+                    ui.end_row();
+
+                    ui.label(tr("Transmitting:"));
+                    ui.label(format!("{:.1}%", efficiency.transmit_pct));
+                    ui.end_row();
+
+                    ui.label(tr("Listening:"));
+                    ui.label(format!("{:.1}%", efficiency.listen_pct));
+                    ui.end_row();
+
+                    ui.label(tr("Idle:"));
+                    ui.label(format!("{:.1}%", efficiency.idle_pct));
+                    ui.end_row();
+
+                    ui.label(tr("Callers Lost:"));
+                    ui.label(RichText::new(format!("{}", efficiency.callers_lost)).color(
+                        if efficiency.callers_lost > 0 {
+                            palette.bad
+                        } else {
+                            palette.good
+                        },
+                    ))
+                    .on_hover_text(tr(
+                        "Callers who ran out of patience and left before you worked them",
+                    ));
+                    ui.end_row();
+
+                    let wide = &stats.wide_pileup;
+                    if wide.heard() > 0 {
+                        ui.label(tr("Wide Pileup Recovered:"));
+                        ui.label(tr_args(
+                            "{} of {}",
+                            &[&wide.recovered().to_string(), &wide.heard().to_string()],
+                        ))
+                        .on_hover_text(tr("Callers worked out of those heard calling"));
+                        ui.end_row();
+                    }
+
+                    let fights = &stats.fights;
+                    if fights.fights > 0 || fights.qsys > 0 {
+                        ui.label(tr("Frequency Fights:"));
+                        ui.label(tr_args(
+                            "{} ({} QSYs)",
+                            &[&fights.fights.to_string(), &fights.qsys.to_string()],
+                        ))
+                        .on_hover_text(tr(
+                            "Stations that started calling CQ on your frequency, \
+                             and the times you moved off",
+                        ));
+                        ui.end_row();
+
+                        ui.label(tr("Time Lost to Fights:"));
+                        ui.label(format!("{} s", fights.time_lost().as_secs()))
+                            .on_hover_text(tr(
+                                "Time sharing your frequency plus time finding a new one",
+                            ));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.add_space(8.0);
+
         // WPM section
-        ui.heading("Calling Station Speed");
+        ui.heading(tr("Calling Station Speed"));
         ui.add_space(8.0);
 
         if analysis.total_qsos > 0 {
@@ -185,11 +487,11 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                 .num_columns(2)
                 .spacing([40.0, 4.0])
                 .show(ui, |ui| {
-                    ui.label("Average WPM:");
+                    ui.label(tr("Average WPM:"));
                     ui.label(format!("{:.1}", analysis.avg_station_wpm));
                     ui.end_row();
 
-                    ui.label("WPM Range:");
+                    ui.label(tr("WPM Range:"));
                     ui.label(format!(
                         "{} - {}",
                         analysis.min_station_wpm, analysis.max_station_wpm
@@ -197,7 +499,7 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                     ui.end_row();
                 });
         } else {
-            ui.label("No QSOs logged yet");
+            ui.label(tr("No QSOs logged yet"));
         }
 
         ui.add_space(16.0);
@@ -205,20 +507,20 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.add_space(8.0);
 
         // WPM bucket accuracy
-        ui.heading("WPM Accuracy (2-WPM buckets)");
+        ui.heading(tr("WPM Accuracy (2-WPM buckets)"));
         ui.add_space(8.0);
 
         if analysis.wpm_buckets.is_empty() {
-            ui.label("No QSOs logged yet");
+            ui.label(tr("No QSOs logged yet"));
         } else {
             egui::Grid::new("wpm_bucket_grid")
                 .num_columns(4)
                 .spacing([20.0, 4.0])
                 .show(ui, |ui| {
-                    ui.label(RichText::new("Bucket").strong());
-                    ui.label(RichText::new("Total").strong());
-                    ui.label(RichText::new("Correct").strong());
-                    ui.label(RichText::new("Accuracy").strong());
+                    ui.label(RichText::new(tr("Bucket")).strong());
+                    ui.label(RichText::new(tr("Total")).strong());
+                    ui.label(RichText::new(tr("Correct")).strong());
+                    ui.label(RichText::new(tr("Accuracy")).strong());
                     ui.end_row();
 
                     for bucket in &analysis.wpm_buckets {
@@ -235,23 +537,139 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.separator();
         ui.add_space(8.0);
 
+        // SNR bucket accuracy
+        ui.heading(tr("SNR Accuracy (5 dB buckets)"));
+        ui.add_space(8.0);
+
+        if analysis.snr_buckets.is_empty() {
+            ui.label(tr("No QSOs with a signal-to-noise reading yet"));
+        } else {
+            egui::Grid::new("snr_bucket_grid")
+                .num_columns(4)
+                .spacing([20.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr("SNR")).strong()).on_hover_text(tr(
+                        "Caller's signal over the band noise in the receive filter, approximate",
+                    ));
+                    ui.label(RichText::new(tr("Total")).strong());
+                    ui.label(RichText::new(tr("Correct")).strong());
+                    ui.label(RichText::new(tr("Accuracy")).strong());
+                    ui.end_row();
+
+                    for bucket in &analysis.snr_buckets {
+                        ui.label(bucket.label.clone());
+                        ui.label(format!("{}", bucket.total));
+                        ui.label(format!("{}", bucket.correct));
+                        ui.label(format!("{:.1}%", bucket.accuracy_pct));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        // Accuracy and copy time by pileup size
+        ui.heading(tr("Accuracy by Pileup Size"));
+        ui.add_space(8.0);
+
+        if analysis.pileup_sizes.is_empty() {
+            ui.label(tr("No QSOs with a pileup size yet"));
+        } else {
+            egui::Grid::new("pileup_size_grid")
+                .num_columns(5)
+                .spacing([20.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr("Callers")).strong())
+                        .on_hover_text(tr("Stations calling when the QSO started"));
+                    ui.label(RichText::new(tr("Total")).strong());
+                    ui.label(RichText::new(tr("Correct")).strong());
+                    ui.label(RichText::new(tr("Accuracy")).strong());
+                    ui.label(RichText::new(tr("Avg Copy Time")).strong())
+                        .on_hover_text(tr("From the callers starting to your reply with a call"));
+                    ui.end_row();
+
+                    for size in &analysis.pileup_sizes {
+                        ui.label(size.label.clone());
+                        ui.label(format!("{}", size.total));
+                        ui.label(format!("{}", size.correct));
+                        ui.label(format!("{:.1}%", size.accuracy_pct));
+                        ui.label(
+                            size.avg_copy_secs
+                                .map(|secs| format!("{:.1} s", secs))
+                                .unwrap_or_else(|| "-".to_string()),
+                        );
+                        ui.end_row();
+                    }
+                });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        // Caller density over the session, with Dynamic Density on
+        if !stats.density.is_empty() {
+            ui.heading(tr("Caller Density"));
+            ui.add_space(8.0);
+            render_density_chart(ui, &stats.density, palette);
+
+            ui.add_space(16.0);
+            ui.separator();
+            ui.add_space(8.0);
+        }
+
+        // Hour-by-hour breakdown, like a post-contest rate sheet
+        ui.heading(tr("QSOs by Hour (UTC)"));
+        ui.add_space(8.0);
+
+        if analysis.hours.is_empty() {
+            ui.label(tr("No QSOs with a logged time yet"));
+        } else {
+            egui::Grid::new("hour_grid")
+                .num_columns(5)
+                .spacing([20.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr("Hour")).strong());
+                    ui.label(RichText::new(tr("QSOs")).strong());
+                    ui.label(RichText::new(tr("Correct")).strong());
+                    ui.label(RichText::new(tr("Accuracy")).strong());
+                    ui.label(RichText::new(tr("Points")).strong());
+                    ui.end_row();
+
+                    for hour in &analysis.hours {
+                        ui.label(RichText::new(&hour.label).monospace());
+                        ui.label(format!("{}", hour.qsos));
+                        ui.label(format!("{}", hour.correct));
+                        ui.label(format!("{:.1}%", hour.accuracy_pct));
+                        ui.label(format!("{}", hour.points));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.add_space(8.0);
+
         // Character error analysis
-        ui.heading("Character Error Analysis");
+        ui.heading(tr("Character Error Analysis"));
         ui.add_space(8.0);
 
         if analysis.char_error_rates.is_empty() {
-            ui.label("Not enough data for character analysis");
+            ui.label(tr("Not enough data for character analysis"));
         } else {
-            ui.label(RichText::new("Characters with highest error rates:").small());
+            ui.label(RichText::new(tr("Characters with highest error rates:")).small());
             ui.add_space(4.0);
 
             egui::Grid::new("char_error_grid")
                 .num_columns(3)
                 .spacing([20.0, 4.0])
                 .show(ui, |ui| {
-                    ui.label(RichText::new("Char").strong());
-                    ui.label(RichText::new("Error Rate").strong());
-                    ui.label(RichText::new("Samples").strong());
+                    ui.label(RichText::new(tr("Char")).strong());
+                    ui.label(RichText::new(tr("Error Rate")).strong());
+                    ui.label(RichText::new(tr("Samples")).strong());
                     ui.end_row();
 
                     for (ch, error_rate, count) in analysis
@@ -261,7 +679,7 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                         .take(10)
                     {
                         let char_display = if *ch == ' ' {
-                            "[space]".to_string()
+                            tr("[space]").to_string()
                         } else {
                             ch.to_string()
                         };
@@ -278,31 +696,50 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
         ui.add_space(8.0);
 
         // Recent QSOs
-        ui.heading("Recent QSOs");
+        let flagged_id = ui.id().with("flagged_only");
+        let mut flagged_only = ui.data_mut(|d| *d.get_temp_mut_or_default::<bool>(flagged_id));
+        ui.horizontal(|ui| {
+            ui.heading(tr("Recent QSOs"));
+            ui.add_space(12.0);
+            ui.checkbox(&mut flagged_only, tr("Flagged only"));
+        });
+        ui.data_mut(|d| d.insert_temp(flagged_id, flagged_only));
         ui.add_space(8.0);
 
+        // All flagged QSOs when filtering, otherwise the last 15
+        let shown: Vec<(usize, &QsoRecord)> = if flagged_only {
+            stats
+                .qsos
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, q)| q.flagged)
+                .collect()
+        } else {
+            stats.qsos.iter().enumerate().rev().take(15).collect()
+        };
+
         if stats.qsos.is_empty() {
-            ui.label("No QSOs logged yet");
+            ui.label(tr("No QSOs logged yet"));
+        } else if shown.is_empty() {
+            ui.label(tr("No flagged QSOs"));
         } else {
             egui::Grid::new("qso_grid")
-                .num_columns(5)
+                .num_columns(7)
                 .spacing([12.0, 4.0])
                 .show(ui, |ui| {
-                    ui.label(RichText::new("Callsign").strong());
-                    ui.label(RichText::new("Exchange").strong());
-                    ui.label(RichText::new("WPM").strong());
-                    ui.label(RichText::new("AGN").strong());
-                    ui.label(RichText::new("Result").strong());
+                    ui.label(RichText::new(tr("Callsign")).strong());
+                    ui.label(RichText::new(tr("Exchange")).strong());
+                    ui.label(RichText::new(tr("WPM")).strong());
+                    ui.label(RichText::new(tr("AGN")).strong());
+                    ui.label(RichText::new(tr("Result")).strong());
+                    ui.label(RichText::new(tr("Counts")).strong());
+                    ui.label(RichText::new(tr("Note")).strong());
                     ui.end_row();
 
-                    // Show last 15 QSOs in reverse order
-                    for qso in stats.qsos.iter().rev().take(15) {
+                    for (index, qso) in shown {
                         // Callsign column
-                        let call_color = if qso.callsign_correct {
-                            egui::Color32::GREEN
-                        } else {
-                            egui::Color32::RED
-                        };
+                        let call_color = palette.correct(qso.callsign_correct);
                         ui.label(
                             RichText::new(&qso.entered_callsign)
                                 .monospace()
@@ -310,16 +747,27 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                         );
 
                         // Exchange column
-                        let exch_color = if qso.exchange_correct {
-                            egui::Color32::GREEN
-                        } else {
-                            egui::Color32::RED
-                        };
-                        ui.label(
-                            RichText::new(&qso.entered_exchange)
-                                .monospace()
-                                .color(exch_color),
-                        );
+                        let exch_color = palette.correct(qso.exchange_correct);
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(&qso.entered_exchange)
+                                    .monospace()
+                                    .color(exch_color),
+                            );
+                            // Worked before: the station already gave us this exchange
+                            if !qso.exchange_correct {
+                                if let Some(previous) = &qso.worked_before_exchange {
+                                    ui.label(
+                                        RichText::new(tr_args(
+                                            "told you {} last time",
+                                            &[previous],
+                                        ))
+                                        .small()
+                                        .weak(),
+                                    );
+                                }
+                            }
+                        });
 
                         // WPM column
                         ui.label(format!("{}", qso.station_wpm));
@@ -334,9 +782,7 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                             if qso.used_agn_exchange {
                                 agn_parts.push("X");
                             }
-                            ui.label(
-                                RichText::new(agn_parts.join(",")).color(egui::Color32::YELLOW),
-                            );
+                            ui.label(RichText::new(agn_parts.join(",")).color(palette.warning));
                         } else {
                             ui.label("-");
                         }
@@ -345,23 +791,98 @@ fn render_stats_content(ui: &mut egui::Ui, stats: &SessionStats) {
                         let is_correct = qso.callsign_correct && qso.exchange_correct;
                         let is_perfect = is_correct && !agn_used && !qso.used_f5_callsign;
                         let (result_text, result_color) = if is_perfect {
-                            ("OK", egui::Color32::GREEN)
+                            ("OK", palette.good)
                         } else if is_correct {
-                            ("ok", egui::Color32::LIGHT_GREEN)
+                            ("ok", palette.partial)
                         } else {
-                            ("ERR", egui::Color32::RED)
+                            ("ERR", palette.bad)
                         };
                         ui.label(RichText::new(result_text).color(result_color));
+
+                        // Counts column: untick to leave a QSO out of the score
+                        let mut counts = !qso.excluded;
+                        if ui
+                            .checkbox(&mut counts, "")
+                            .on_hover_text(tr("Untick if this QSO should not count"))
+                            .changed()
+                        {
+                            *counted_change = Some((index, counts));
+                        }
+
+                        // Note column: flagged QSOs and the user's note
+                        if qso.flagged {
+                            let note = if qso.note.is_empty() {
+                                tr("flagged").to_string()
+                            } else {
+                                qso.note.clone()
+                            };
+                            ui.label(RichText::new(note).color(palette.warning));
+                        } else {
+                            ui.label("");
+                        }
                         ui.end_row();
                     }
                 });
 
             ui.add_space(4.0);
             ui.label(
-                RichText::new("AGN: C=callsign, X=exchange | ok=correct with AGN")
+                RichText::new(tr("AGN: C=callsign, X=exchange | ok=correct with AGN"))
                     .small()
                     .italics(),
             );
         }
     });
 }
+
+/// Station probability after each QSO, as a line over the session
+fn render_density_chart(ui: &mut egui::Ui, samples: &[DensitySample], palette: &Palette) {
+    let size = egui::vec2(ui.available_width().min(480.0), 100.0);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(
+        rect,
+        0.0,
+        egui::Stroke::new(1.0, palette.muted),
+        egui::StrokeKind::Inside,
+    );
+
+    let span = samples
+        .last()
+        .map(|sample| sample.minutes)
+        .unwrap_or(0.0)
+        .max(1.0);
+    let points: Vec<egui::Pos2> = samples
+        .iter()
+        .map(|sample| {
+            egui::pos2(
+                rect.left() + rect.width() * sample.minutes / span,
+                rect.bottom() - rect.height() * sample.probability.clamp(0.0, 1.0),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, palette.accent),
+    ));
+
+    let low = samples.iter().map(|s| s.probability).fold(1.0, f32::min);
+    let high = samples.iter().map(|s| s.probability).fold(0.0, f32::max);
+    let now = samples.last().map(|s| s.probability).unwrap_or(0.0);
+    response.on_hover_text(tr_args(
+        "Station probability now {}, between {} and {} this session",
+        &[
+            &format!("{:.2}", now),
+            &format!("{:.2}", low),
+            &format!("{:.2}", high),
+        ],
+    ));
+
+    ui.horizontal(|ui| {
+        ui.set_width(size.x);
+        let small = |text: String| RichText::new(text).small().weak();
+        ui.label(small(tr_args("{} min", &["0"])));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(small(tr_args("{} min", &[&format!("{:.0}", span)])));
+        });
+    });
+}
@@ -0,0 +1,99 @@
+use crate::i18n::{tr, tr_args};
+use crate::summary::SessionSummary;
+use crate::ui::Palette;
+use egui::RichText;
+
+/// Grade and tips for the session that just ended; closes on OK
+pub fn render_session_summary(
+    ctx: &egui::Context,
+    summary: &mut Option<SessionSummary>,
+    palette: &Palette,
+) {
+    let Some(shown) = summary.as_ref() else {
+        return;
+    };
+    let mut close = false;
+
+    egui::Window::new(tr("Session Summary"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                let grade_color = match shown.grade {
+                    'A' | 'B' => palette.good,
+                    'C' => palette.partial,
+                    _ => palette.bad,
+                };
+                ui.label(
+                    RichText::new(shown.grade.to_string())
+                        .size(36.0)
+                        .strong()
+                        .color(grade_color),
+                );
+                ui.add_space(12.0);
+                ui.vertical(|ui| {
+                    ui.label(tr_args("Score: {}/100", &[&shown.score.to_string()]));
+                    ui.label(tr_args(
+                        "{} QSOs, {} correct, {}/hr",
+                        &[
+                            &shown.qsos.to_string(),
+                            &format!("{:.0}%", shown.accuracy_pct),
+                            &format!("{:.0}", shown.rate_per_hour),
+                        ],
+                    ));
+                    ui.label(tr_args(
+                        "Repeats asked on {} of QSOs",
+                        &[&format!("{:.0}%", shown.agn_pct)],
+                    ));
+                });
+            });
+
+            ui.add_space(12.0);
+            ui.label(RichText::new(tr("Tips")).strong());
+            ui.add_space(4.0);
+            for tip in &shown.tips {
+                ui.horizontal_wrapped(|ui| {
+                    ui.set_max_width(420.0);
+                    ui.label("\u{2022}");
+                    ui.label(tip.text());
+                });
+            }
+
+            if !shown.bests.is_empty() {
+                ui.add_space(12.0);
+                ui.label(RichText::new(tr("Personal Bests")).strong());
+                ui.add_space(4.0);
+                egui::Grid::new("personal_bests_grid")
+                    .num_columns(3)
+                    .spacing([16.0, 4.0])
+                    .show(ui, |ui| {
+                        for best in &shown.bests {
+                            ui.label(best.kind.label());
+                            ui.label(&best.session);
+                            if best.new_best {
+                                ui.label(RichText::new(tr("New PB!")).strong().color(palette.good));
+                            } else if let Some(previous) = &best.previous {
+                                ui.label(RichText::new(tr_args("best {}", &[previous])).weak());
+                            } else {
+                                ui.label("");
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            ui.add_space(12.0);
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                if ui.button(tr("OK")).clicked() {
+                    close = true;
+                }
+            });
+            ui.add_space(4.0);
+        });
+
+    if close {
+        *summary = None;
+    }
+}
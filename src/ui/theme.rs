@@ -0,0 +1,187 @@
+// Color themes: egui base visuals plus the colors used for QSO feedback
+
+use crate::config::{AccentColor, ColorTheme, UserSettings};
+use crate::i18n::tr;
+use crate::state::StatusColor;
+use egui::Color32;
+
+/// Colors the UI uses to mean something (right, wrong, attention, ...)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    /// Correct copy
+    pub good: Color32,
+    /// Correct, but needed help (AGN)
+    pub partial: Color32,
+    /// Wrong copy
+    pub bad: Color32,
+    /// Notices and AGN usage
+    pub warning: Color32,
+    pub info: Color32,
+    pub caution: Color32,
+    pub muted: Color32,
+    /// Normal level on meters and the working status
+    pub active: Color32,
+    /// Selections and highlights
+    pub accent: Color32,
+}
+
+impl Palette {
+    pub fn new(theme: ColorTheme, accent: AccentColor) -> Self {
+        let accent = accent_color(theme, accent);
+        match theme {
+            ColorTheme::Dark => Self {
+                good: Color32::GREEN,
+                partial: Color32::LIGHT_GREEN,
+                bad: Color32::RED,
+                warning: Color32::YELLOW,
+                info: Color32::LIGHT_BLUE,
+                caution: Color32::from_rgb(255, 165, 0),
+                muted: Color32::GRAY,
+                active: Color32::from_rgb(100, 200, 100),
+                accent,
+            },
+            ColorTheme::Light => Self {
+                good: Color32::from_rgb(0, 128, 0),
+                partial: Color32::from_rgb(70, 150, 70),
+                bad: Color32::from_rgb(190, 0, 0),
+                warning: Color32::from_rgb(160, 110, 0),
+                info: Color32::from_rgb(0, 90, 180),
+                caution: Color32::from_rgb(200, 100, 0),
+                muted: Color32::from_gray(110),
+                active: Color32::from_rgb(30, 140, 30),
+                accent,
+            },
+            ColorTheme::HighContrast => Self {
+                good: Color32::from_rgb(0, 255, 0),
+                partial: Color32::from_rgb(170, 255, 170),
+                bad: Color32::from_rgb(255, 70, 70),
+                warning: Color32::from_rgb(255, 255, 0),
+                info: Color32::from_rgb(0, 220, 255),
+                caution: Color32::from_rgb(255, 150, 0),
+                muted: Color32::from_gray(210),
+                active: Color32::from_rgb(0, 255, 0),
+                accent,
+            },
+            // Okabe-Ito colors, which stay apart for all common color vision types
+            ColorTheme::Colorblind => Self {
+                good: Color32::from_rgb(86, 180, 233),
+                partial: Color32::from_rgb(170, 215, 240),
+                bad: Color32::from_rgb(230, 159, 0),
+                warning: Color32::from_rgb(240, 228, 66),
+                info: Color32::from_rgb(0, 158, 115),
+                caution: Color32::from_rgb(204, 121, 167),
+                muted: Color32::GRAY,
+                active: Color32::from_rgb(86, 180, 233),
+                accent,
+            },
+        }
+    }
+
+    pub fn for_user(user: &UserSettings) -> Self {
+        Self::new(user.theme, user.accent)
+    }
+
+    /// Color for right/wrong feedback
+    pub fn correct(&self, correct: bool) -> Color32 {
+        if correct {
+            self.good
+        } else {
+            self.bad
+        }
+    }
+
+    pub fn status_color(&self, status: StatusColor) -> Color32 {
+        match status {
+            StatusColor::Gray => self.muted,
+            StatusColor::Yellow => self.warning,
+            StatusColor::LightBlue => self.info,
+            StatusColor::Green => self.active,
+            StatusColor::Orange => self.caution,
+        }
+    }
+}
+
+fn accent_color(theme: ColorTheme, accent: AccentColor) -> Color32 {
+    let (r, g, b) = match accent {
+        AccentColor::Blue => (0, 92, 128),
+        AccentColor::Green => (40, 110, 50),
+        AccentColor::Orange => (170, 90, 0),
+        AccentColor::Purple => (100, 60, 140),
+        AccentColor::Teal => (0, 110, 110),
+    };
+    let color = Color32::from_rgb(r, g, b);
+    if theme == ColorTheme::Light {
+        // Selections need to stay light enough to read dark text on
+        lerp_color(color, Color32::WHITE, 0.55)
+    } else {
+        color
+    }
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+}
+
+pub fn theme_name(theme: ColorTheme) -> &'static str {
+    match theme {
+        ColorTheme::Dark => tr("Dark"),
+        ColorTheme::Light => tr("Light"),
+        ColorTheme::HighContrast => tr("High Contrast"),
+        ColorTheme::Colorblind => tr("Colorblind"),
+    }
+}
+
+pub fn accent_name(accent: AccentColor) -> &'static str {
+    match accent {
+        AccentColor::Blue => tr("Blue"),
+        AccentColor::Green => tr("Green"),
+        AccentColor::Orange => tr("Orange"),
+        AccentColor::Purple => tr("Purple"),
+        AccentColor::Teal => tr("Teal"),
+    }
+}
+
+/// Set egui's visuals for the user's theme and accent
+pub fn apply_theme(ctx: &egui::Context, user: &UserSettings) {
+    let palette = Palette::for_user(user);
+    let mut visuals = match user.theme {
+        ColorTheme::Light => egui::Visuals::light(),
+        _ => egui::Visuals::dark(),
+    };
+    if user.theme == ColorTheme::HighContrast {
+        visuals.override_text_color = Some(Color32::WHITE);
+        visuals.panel_fill = Color32::BLACK;
+        visuals.window_fill = Color32::BLACK;
+        visuals.extreme_bg_color = Color32::BLACK;
+        visuals.widgets.noninteractive.bg_stroke.color = Color32::from_gray(200);
+        visuals.widgets.inactive.bg_stroke = egui::Stroke::new(1.0, Color32::from_gray(200));
+    }
+    visuals.selection.bg_fill = palette.accent;
+    visuals.hyperlink_color = lerp_color(palette.accent, visuals.strong_text_color(), 0.3);
+    ctx.set_visuals(visuals);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_keep_right_and_wrong_apart() {
+        let palette = Palette::new(ColorTheme::Colorblind, AccentColor::default());
+        // Right is blue-ish, wrong is orange-ish
+        assert!(palette.good.b() > palette.good.r());
+        assert!(palette.bad.r() > palette.bad.b());
+
+        for theme in [
+            ColorTheme::Dark,
+            ColorTheme::Light,
+            ColorTheme::HighContrast,
+            ColorTheme::Colorblind,
+        ] {
+            let palette = Palette::new(theme, AccentColor::Teal);
+            assert_ne!(palette.good, palette.bad);
+            assert_ne!(palette.correct(true), palette.correct(false));
+        }
+    }
+}
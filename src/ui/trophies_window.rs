@@ -0,0 +1,65 @@
+use crate::achievements::{Achievement, Achievements};
+use crate::i18n::{tr, tr_args};
+use crate::ui::Palette;
+use egui::RichText;
+
+/// Every achievement, earned or not: when each was earned, and how far
+/// along the ones counted up over time are
+pub fn render_trophies_window(
+    ctx: &egui::Context,
+    achievements: &Achievements,
+    palette: &Palette,
+    show_trophies: &mut bool,
+) {
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("trophies_viewport"),
+        egui::ViewportBuilder::default()
+            .with_title(tr("Trophies"))
+            .with_inner_size([420.0, 300.0]),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label(tr_args(
+                    "{} of {} earned",
+                    &[
+                        &achievements.unlocked.len().to_string(),
+                        &Achievement::ALL.len().to_string(),
+                    ],
+                ));
+                ui.add_space(8.0);
+                egui::Grid::new("trophies_grid")
+                    .num_columns(2)
+                    .spacing([24.0, 8.0])
+                    .show(ui, |ui| {
+                        for achievement in Achievement::ALL {
+                            let earned = achievements.unlocked.get(&achievement);
+                            ui.vertical(|ui| {
+                                let name = RichText::new(achievement.name()).strong();
+                                ui.label(if earned.is_some() {
+                                    name.color(palette.good)
+                                } else {
+                                    name.weak()
+                                });
+                                ui.label(RichText::new(achievement.description()).small());
+                            });
+                            match earned {
+                                Some(date) => {
+                                    ui.label(tr_args("Earned {}", &[date]));
+                                }
+                                None => {
+                                    let progress = achievements
+                                        .progress(achievement)
+                                        .unwrap_or_else(|| tr("Not yet").to_string());
+                                    ui.label(RichText::new(progress).weak());
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                *show_trophies = false;
+            }
+        },
+    );
+}
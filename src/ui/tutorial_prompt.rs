@@ -0,0 +1,66 @@
+use egui::RichText;
+
+use crate::i18n::{tr, tr_args};
+use crate::tutorial::TutorialStep;
+use crate::ui::Palette;
+
+/// User's answer to the tutorial prompt
+pub enum TutorialPromptResponse {
+    /// Work another QSO with the prompts
+    Again,
+    /// Leave the tutorial for normal operation
+    Finish,
+}
+
+/// The current step's instructions, kept in the top-right corner of the
+/// main window where they don't cover the entry boxes
+pub fn render_tutorial_prompt(
+    ctx: &egui::Context,
+    step: TutorialStep,
+    palette: &Palette,
+) -> Option<TutorialPromptResponse> {
+    let mut response = None;
+
+    egui::Window::new(tr("Tutorial"))
+        .collapsible(false)
+        .resizable(false)
+        .default_width(260.0)
+        .anchor(egui::Align2::RIGHT_TOP, [-12.0, 36.0])
+        .show(ctx, |ui| {
+            if let Some(index) = TutorialStep::QSO.iter().position(|s| *s == step) {
+                ui.label(
+                    RichText::new(tr_args(
+                        "Step {} of {}",
+                        &[
+                            &(index + 1).to_string(),
+                            &TutorialStep::QSO.len().to_string(),
+                        ],
+                    ))
+                    .weak(),
+                );
+            }
+            ui.label(
+                RichText::new(tr(step.title()))
+                    .strong()
+                    .color(palette.accent),
+            );
+            ui.add_space(4.0);
+            ui.label(tr(step.instructions()));
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                if step == TutorialStep::Done {
+                    if ui.button(tr("Another QSO")).clicked() {
+                        response = Some(TutorialPromptResponse::Again);
+                    }
+                    if ui.button(tr("Start Contesting")).clicked() {
+                        response = Some(TutorialPromptResponse::Finish);
+                    }
+                } else if ui.button(tr("Skip Tutorial")).clicked() {
+                    response = Some(TutorialPromptResponse::Finish);
+                }
+            });
+        });
+
+    response
+}
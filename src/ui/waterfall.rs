@@ -0,0 +1,115 @@
+use crate::i18n::tr_args;
+use egui::{Color32, ColorImage, RichText, TextureHandle, TextureOptions};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Frequency span shown either side of the receive pitch (Hz)
+pub const WATERFALL_SPAN_HZ: f32 = 500.0;
+/// Frequency columns per row
+pub const WATERFALL_BINS: usize = 200;
+/// Rows of history kept on screen
+const WATERFALL_ROWS: usize = 60;
+/// Time between rows (sets how fast the display scrolls)
+const ROW_INTERVAL: Duration = Duration::from_millis(50);
+/// dB range mapped onto the color scale
+const FLOOR_DB: f32 = -80.0;
+const CEILING_DB: f32 = -20.0;
+
+/// Scrolling spectrum history of the receiver audio
+pub struct Waterfall {
+    rows: VecDeque<Vec<f32>>,
+    last_row: Option<Instant>,
+    texture: Option<TextureHandle>,
+}
+
+impl Waterfall {
+    pub fn new() -> Self {
+        Self {
+            rows: VecDeque::with_capacity(WATERFALL_ROWS),
+            last_row: None,
+            texture: None,
+        }
+    }
+
+    /// Whether it's time to add another row
+    pub fn row_due(&self) -> bool {
+        self.last_row
+            .map(|last| last.elapsed() >= ROW_INTERVAL)
+            .unwrap_or(true)
+    }
+
+    /// Add the newest spectrum row (in dB) at the top
+    pub fn push_row(&mut self, row: Vec<f32>) {
+        self.rows.push_front(row);
+        self.rows.truncate(WATERFALL_ROWS);
+        self.last_row = Some(Instant::now());
+    }
+
+    fn image(&self) -> ColorImage {
+        let mut image = ColorImage::filled([WATERFALL_BINS, WATERFALL_ROWS], Color32::BLACK);
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, db) in row.iter().take(WATERFALL_BINS).enumerate() {
+                image.pixels[y * WATERFALL_BINS + x] = heat_color(*db);
+            }
+        }
+        image
+    }
+}
+
+/// Black -> blue -> yellow -> white as the level rises
+fn heat_color(db: f32) -> Color32 {
+    let t = ((db - FLOOR_DB) / (CEILING_DB - FLOOR_DB)).clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8, f: f32| (a as f32 + (b as f32 - a as f32) * f) as u8;
+    if t < 0.4 {
+        let f = t / 0.4;
+        Color32::from_rgb(0, 0, lerp(0, 160, f))
+    } else if t < 0.8 {
+        let f = (t - 0.4) / 0.4;
+        Color32::from_rgb(lerp(0, 255, f), lerp(0, 220, f), lerp(160, 0, f))
+    } else {
+        let f = (t - 0.8) / 0.2;
+        Color32::from_rgb(255, lerp(220, 255, f), lerp(0, 255, f))
+    }
+}
+
+pub fn render_waterfall(ui: &mut egui::Ui, waterfall: &mut Waterfall, center_hz: f32) {
+    let image = waterfall.image();
+    let texture = match &mut waterfall.texture {
+        Some(texture) => {
+            texture.set(image, TextureOptions::LINEAR);
+            texture.clone()
+        }
+        None => {
+            let texture = ui
+                .ctx()
+                .load_texture("waterfall", image, TextureOptions::LINEAR);
+            waterfall.texture = Some(texture.clone());
+            texture
+        }
+    };
+
+    let size = egui::vec2(ui.available_width(), 60.0);
+    let response = ui.add(egui::Image::new((texture.id(), size)));
+
+    // Zero-beat marker at the receive pitch
+    let rect = response.rect;
+    ui.painter().line_segment(
+        [rect.center_top(), rect.center_bottom()],
+        egui::Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 80)),
+    );
+    response.on_hover_text(tr_args(
+        "Receiver audio {}-{} Hz; the center line is your pitch",
+        &[
+            &format!("{:.0}", center_hz - WATERFALL_SPAN_HZ),
+            &format!("{:.0}", center_hz + WATERFALL_SPAN_HZ),
+        ],
+    ));
+
+    ui.horizontal(|ui| {
+        let small = |text: String| RichText::new(text).small().weak();
+        ui.label(small(format!("-{:.0} Hz", WATERFALL_SPAN_HZ)));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(small(format!("+{:.0} Hz", WATERFALL_SPAN_HZ)));
+        });
+    });
+}